@@ -0,0 +1,168 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Typed client for the Nodle Chain built on top of subxt, so that services reading or
+//! submitting allocations do not each have to describe the runtime.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), substrate_subxt::Error> {
+//! use chain_client::ChainClient;
+//!
+//! let client = ChainClient::new("ws://127.0.0.1:9944").await?;
+//! println!("{} coins can still be allocated", client.coins_left().await?);
+//! # Ok(())
+//! # }
+//! ```
+
+mod runtime;
+
+pub use runtime::*;
+
+use futures::{future, stream, Stream, StreamExt};
+use jsonrpsee::{common::Params, Client as RpcClient};
+use nodle_chain_primitives::{AccountId, Balance, BlockNumber, Hash};
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use substrate_subxt::{Client, ClientBuilder, Error, Signer};
+
+/// An allocation included in a block, as streamed by the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RewardNotification {
+    pub block_number: BlockNumber,
+    pub block_hash: Hash,
+    pub event_index: u32,
+    pub to: AccountId,
+    /// Amount received by the grantee, protocol fee excluded
+    pub amount: Balance,
+    /// Amount taken by the protocol
+    pub fee: Balance,
+    pub proof: Vec<u8>,
+    /// Allocations are notified once when their block becomes the best block and
+    /// once more when it is finalized
+    pub finalized: bool,
+}
+
+/// A batch of allocations submitted to the chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RewardBatch {
+    /// Merkle root of the batch, passed as the proof of every allocation
+    pub root: Hash,
+    /// Hash of the extrinsic carrying the batch
+    pub extrinsic: Hash,
+}
+
+/// A connection to a node, see [`ChainClient::subxt`] for everything not covered by
+/// the helpers.
+#[derive(Clone)]
+pub struct ChainClient {
+    rpc: RpcClient,
+    client: Client<NodleRuntime>,
+}
+
+impl ChainClient {
+    /// Connect to the websocket RPC endpoint at `url`.
+    pub async fn new(url: &str) -> Result<Self, Error> {
+        let rpc = jsonrpsee::ws_client(url).await?;
+        let client = ClientBuilder::<NodleRuntime>::new()
+            .set_client(rpc.clone())
+            .skip_type_sizes_check()
+            .build()
+            .await?;
+
+        Ok(Self { rpc, client })
+    }
+
+    /// The underlying subxt client.
+    pub fn subxt(&self) -> &Client<NodleRuntime> {
+        &self.client
+    }
+
+    /// Allocate `rewards` in a single extrinsic signed by `oracle`, every allocation
+    /// carries the merkle root of the batch as its proof. The call returns once the
+    /// extrinsic is in the pool.
+    pub async fn submit_reward_batch(
+        &self,
+        oracle: &(dyn Signer<NodleRuntime> + Send + Sync),
+        rewards: &[(AccountId, Balance)],
+    ) -> Result<RewardBatch, Error> {
+        let leaves = rewards
+            .iter()
+            .map(|(who, amount)| chain_merkle::leaf(who, *amount))
+            .collect::<Vec<_>>();
+        let root = chain_merkle::root(&leaves)
+            .ok_or_else(|| Error::Other("Cannot submit an empty batch".into()))?;
+
+        let mut calls = rewards
+            .iter()
+            .map(|(who, amount)| AllocateCall::<NodleRuntime> {
+                to: who.clone(),
+                amount: *amount,
+                proof: root.as_bytes().to_vec(),
+            });
+        let extrinsic = if rewards.len() == 1 {
+            let call = calls.next().expect("checked the batch has one reward; qed");
+            self.client.submit(call, oracle).await?
+        } else {
+            let calls = calls
+                .map(|call| self.client.encode(call))
+                .collect::<Result<Vec<_>, _>>()?;
+            self.client
+                .submit(
+                    BatchCall::<NodleRuntime> {
+                        calls,
+                        _runtime: PhantomData,
+                    },
+                    oracle,
+                )
+                .await?
+        };
+
+        Ok(RewardBatch { root, extrinsic })
+    }
+
+    /// Stream the allocations made to `account`, relies on the node's
+    /// `allocations_subscribeRewards` subscription.
+    pub async fn watch_allocations(
+        &self,
+        account: AccountId,
+    ) -> Result<impl Stream<Item = RewardNotification>, Error> {
+        let subscription = self
+            .rpc
+            .subscribe::<RewardNotification>(
+                "allocations_subscribeRewards",
+                Params::None,
+                "allocations_unsubscribeRewards",
+            )
+            .await?;
+
+        Ok(stream::unfold(subscription, |mut subscription| async move {
+            let notification = subscription.next().await;
+            Some((notification, subscription))
+        })
+        .filter(move |notification| future::ready(notification.to == account)))
+    }
+
+    /// Amount that oracles can still allocate before the budget is exhausted.
+    pub async fn coins_left(&self) -> Result<Balance, Error> {
+        let budget = self.client.allocations_budget(None).await?;
+        let consumed = self.client.coins_consumed(None).await?;
+
+        Ok(budget.saturating_sub(consumed))
+    }
+}
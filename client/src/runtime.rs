@@ -0,0 +1,302 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! subxt description of the runtime, limited to the calls and storage items used by
+//! the client and the end to end tests.
+//!
+//! Events are not decoded, subxt would need the size of every type used by the
+//! events of a block. Allocations are streamed by the node's rewards subscription
+//! instead.
+
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    generic::Era, traits::SignedExtension, transaction_validity::TransactionValidityError,
+    MultiSignature, OpaqueExtrinsic, Perbill,
+};
+use std::{fmt::Debug, marker::PhantomData};
+use substrate_subxt::{
+    balances::{AccountData, Balances},
+    extrinsic::{
+        ChargeTransactionPayment, CheckEra, CheckGenesis, CheckNonce, CheckSpecVersion,
+        CheckTxVersion, CheckWeight, SignedExtra,
+    },
+    module,
+    system::System,
+    Call, Encoded, Runtime, Store,
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NodleRuntime;
+
+impl Runtime for NodleRuntime {
+    type Signature = MultiSignature;
+    type Extra = NodleExtra<Self>;
+}
+
+impl System for NodleRuntime {
+    type Index = nodle_chain_primitives::Index;
+    type BlockNumber = nodle_chain_primitives::BlockNumber;
+    type Hash = nodle_chain_primitives::Hash;
+    type Hashing = sp_runtime::traits::BlakeTwo256;
+    type AccountId = nodle_chain_primitives::AccountId;
+    type Address = nodle_chain_runtime::Address;
+    type Header = nodle_chain_primitives::Header;
+    type Extrinsic = OpaqueExtrinsic;
+    type AccountData = AccountData<<Self as Balances>::Balance>;
+}
+
+impl Balances for NodleRuntime {
+    type Balance = nodle_chain_primitives::Balance;
+}
+
+impl Allocations for NodleRuntime {}
+impl Emission for NodleRuntime {}
+impl technical_committee::TechnicalCommittee for NodleRuntime {}
+impl financial_committee::FinancialCommittee for NodleRuntime {}
+impl Parameters for NodleRuntime {}
+impl Utility for NodleRuntime {}
+
+/// Rate limiting has no payload, it only has to be accounted for in the encoding.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Debug)]
+pub struct CheckRateLimit<T: System>(PhantomData<T>);
+
+impl<T: System + Clone + Debug + Eq + Send + Sync> SignedExtension for CheckRateLimit<T> {
+    const IDENTIFIER: &'static str = "CheckRateLimit";
+    type AccountId = u64;
+    type Call = ();
+    type AdditionalSigned = ();
+    type Pre = ();
+    fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+        Ok(())
+    }
+}
+
+/// Session keys are checked against the chain state, nothing is signed along.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Debug)]
+pub struct CheckSessionKey<T: System>(PhantomData<T>);
+
+impl<T: System + Clone + Debug + Eq + Send + Sync> SignedExtension for CheckSessionKey<T> {
+    const IDENTIFIER: &'static str = "CheckSessionKey";
+    type AccountId = u64;
+    type Call = ();
+    type AdditionalSigned = ();
+    type Pre = ();
+    fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+        Ok(())
+    }
+}
+
+/// Oracle senders are checked against the chain state, nothing is signed along.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Debug)]
+pub struct CheckOracle<T: System>(PhantomData<T>);
+
+impl<T: System + Clone + Debug + Eq + Send + Sync> SignedExtension for CheckOracle<T> {
+    const IDENTIFIER: &'static str = "CheckOracle";
+    type AccountId = u64;
+    type Call = ();
+    type AdditionalSigned = ();
+    type Pre = ();
+    fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+        Ok(())
+    }
+}
+
+/// Our signed extensions, `ChargeFeelessOrPayment` encodes like the payment extension
+/// it wraps.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Debug)]
+pub struct NodleExtra<T: System> {
+    spec_version: u32,
+    tx_version: u32,
+    nonce: T::Index,
+    genesis_hash: T::Hash,
+}
+
+impl<T: System + Balances + Clone + Debug + Eq + Send + Sync> SignedExtra<T> for NodleExtra<T> {
+    type Extra = (
+        CheckSpecVersion<T>,
+        CheckTxVersion<T>,
+        CheckGenesis<T>,
+        CheckEra<T>,
+        CheckNonce<T>,
+        CheckWeight<T>,
+        CheckRateLimit<T>,
+        CheckSessionKey<T>,
+        CheckOracle<T>,
+        ChargeTransactionPayment<T>,
+    );
+
+    fn new(spec_version: u32, tx_version: u32, nonce: T::Index, genesis_hash: T::Hash) -> Self {
+        Self {
+            spec_version,
+            tx_version,
+            nonce,
+            genesis_hash,
+        }
+    }
+
+    fn extra(&self) -> Self::Extra {
+        (
+            CheckSpecVersion(PhantomData, self.spec_version),
+            CheckTxVersion(PhantomData, self.tx_version),
+            CheckGenesis(PhantomData, self.genesis_hash),
+            CheckEra((Era::Immortal, PhantomData), self.genesis_hash),
+            CheckNonce(self.nonce),
+            CheckWeight(PhantomData),
+            CheckRateLimit(PhantomData),
+            CheckSessionKey(PhantomData),
+            CheckOracle(PhantomData),
+            ChargeTransactionPayment(<T as Balances>::Balance::default()),
+        )
+    }
+}
+
+impl<T: System + Balances + Clone + Debug + Eq + Send + Sync> SignedExtension for NodleExtra<T> {
+    const IDENTIFIER: &'static str = "NodleExtra";
+    type AccountId = T::AccountId;
+    type Call = ();
+    type AdditionalSigned = <<Self as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned;
+    type Pre = ();
+    fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+        self.extra().additional_signed()
+    }
+}
+
+#[module]
+pub trait Allocations: System + Balances {}
+
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct AllocateCall<T: Allocations> {
+    pub to: <T as System>::AccountId,
+    pub amount: <T as Balances>::Balance,
+    pub proof: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct AddOracleCall<T: Allocations> {
+    pub who: <T as System>::AccountId,
+}
+
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct RemoveOracleCall<T: Allocations> {
+    pub who: <T as System>::AccountId,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct OraclesStore<T: Allocations> {
+    #[store(returns = Vec<<T as System>::AccountId>)]
+    pub _runtime: PhantomData<T>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct CoinsConsumedStore<T: Allocations> {
+    #[store(returns = <T as Balances>::Balance)]
+    pub _runtime: PhantomData<T>,
+}
+
+#[module]
+pub trait Emission: System + Balances {}
+
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct AllocationsBudgetStore<T: Emission> {
+    #[store(returns = <T as Balances>::Balance)]
+    pub _runtime: PhantomData<T>,
+}
+
+/// Collectives dispatching calls with their origin through `execute`, development
+/// chains have a single member in every collective.
+pub mod technical_committee {
+    use super::*;
+
+    #[module]
+    pub trait TechnicalCommittee: System {}
+
+    #[derive(Clone, Debug, PartialEq, Call, Encode)]
+    pub struct ExecuteCall<'a, T: TechnicalCommittee> {
+        pub proposal: &'a Encoded,
+        #[codec(compact)]
+        pub length_bound: u32,
+        pub _runtime: PhantomData<T>,
+    }
+
+    /// Dispatch `proposal` with the origin of the technical committee.
+    pub fn execute(proposal: &Encoded) -> ExecuteCall<NodleRuntime> {
+        ExecuteCall {
+            proposal,
+            length_bound: proposal.0.len() as u32,
+            _runtime: PhantomData,
+        }
+    }
+}
+
+pub mod financial_committee {
+    use super::*;
+
+    #[module]
+    pub trait FinancialCommittee: System {}
+
+    #[derive(Clone, Debug, PartialEq, Call, Encode)]
+    pub struct ExecuteCall<'a, T: FinancialCommittee> {
+        pub proposal: &'a Encoded,
+        #[codec(compact)]
+        pub length_bound: u32,
+        pub _runtime: PhantomData<T>,
+    }
+
+    /// Dispatch `proposal` with the origin of the financial committee.
+    pub fn execute(proposal: &Encoded) -> ExecuteCall<NodleRuntime> {
+        ExecuteCall {
+            proposal,
+            length_bound: proposal.0.len() as u32,
+            _runtime: PhantomData,
+        }
+    }
+}
+
+#[module]
+pub trait Parameters: System + Balances {}
+
+/// Mirrors `pallet_parameters::ParameterValue`, variants must stay in the same order.
+#[derive(Clone, Debug, PartialEq, Encode)]
+pub enum ParameterValue<Balance> {
+    AllocationFee(Perbill),
+    MinimumAllocation(Balance),
+    MaxTransactionsPerBlock(u32),
+    FeelessQuota(u32),
+    ReserveFeeShare(Perbill),
+    TransfersEnabled(bool),
+}
+
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct SetCall<T: Parameters> {
+    pub value: ParameterValue<<T as Balances>::Balance>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct MinimumAllocationStore<T: Parameters> {
+    #[store(returns = <T as Balances>::Balance)]
+    pub _runtime: PhantomData<T>,
+}
+
+#[module]
+pub trait Utility: System {}
+
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct BatchCall<T: Utility> {
+    pub calls: Vec<Encoded>,
+    pub _runtime: PhantomData<T>,
+}
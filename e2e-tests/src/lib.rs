@@ -0,0 +1,236 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Helpers for the end to end tests: a development node, or a network of validators,
+//! spawned for every test. The runtime description is shared with `chain-client`.
+//!
+//! Tests assert on storage, events are not decoded.
+
+pub use chain_client::*;
+
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::atomic::{AtomicU16, Ordering},
+    time::Duration,
+};
+use substrate_subxt::{Client, ClientBuilder};
+
+static NEXT_PORT: AtomicU16 = AtomicU16::new(0);
+
+/// A node of a test network, or a development node sealing a block for every
+/// transaction. Killed when dropped.
+pub struct TestNode {
+    process: Child,
+    pub rpc_url: String,
+    pub ws_url: String,
+}
+
+impl TestNode {
+    /// Spawn the binary pointed to by `NODLE_CHAIN_BINARY`, the release build of the
+    /// workspace by default.
+    pub fn spawn() -> Self {
+        let args = ["--dev", "--instant-seal", "--tmp"];
+        Self::spawn_on(Self::next_ports(), args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn binary() -> String {
+        std::env::var("NODLE_CHAIN_BINARY").unwrap_or_else(|_| {
+            concat!(env!("CARGO_MANIFEST_DIR"), "/../target/release/nodle-chain").into()
+        })
+    }
+
+    /// Ports a new node listens on: p2p, rpc and websocket.
+    fn next_ports() -> (u16, u16, u16) {
+        // Tests run in parallel, give every node its own set of ports
+        let base = 20_000
+            + (std::process::id() % 1_000) as u16 * 30
+            + NEXT_PORT.fetch_add(3, Ordering::SeqCst);
+        (base, base + 1, base + 2)
+    }
+
+    fn spawn_on((p2p, rpc, ws): (u16, u16, u16), args: impl IntoIterator<Item = String>) -> Self {
+        let binary = Self::binary();
+        let process = Command::new(&binary)
+            .args(args)
+            .args(&["--no-prometheus", "--no-telemetry"])
+            .args(&["--rpc-methods", "Unsafe"])
+            .args(&["--port", &p2p.to_string()])
+            .args(&["--rpc-port", &rpc.to_string()])
+            .args(&["--ws-port", &ws.to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap_or_else(|e| panic!("Unable to spawn {}: {}", binary, e));
+
+        Self {
+            process,
+            rpc_url: format!("http://127.0.0.1:{}", rpc),
+            ws_url: format!("ws://127.0.0.1:{}", ws),
+        }
+    }
+
+    /// Connect to the node, waiting for it to be up.
+    pub async fn client(&self) -> Client<NodleRuntime> {
+        for _ in 0..60 {
+            let client = ClientBuilder::<NodleRuntime>::new()
+                .set_url(self.ws_url.clone())
+                .skip_type_sizes_check()
+                .build()
+                .await;
+            if let Ok(client) = client {
+                return client;
+            }
+            async_std::task::sleep(Duration::from_millis(500)).await;
+        }
+        panic!("The node did not start in time");
+    }
+
+    /// Call one of our custom RPCs, subxt only knows about Substrate's. Returns the
+    /// error object if the call failed.
+    pub fn rpc(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, serde_json::Value> {
+        let response: serde_json::Value = reqwest::blocking::Client::new()
+            .post(&self.rpc_url)
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .and_then(|response| response.json())
+            .expect("The node answers RPC calls");
+        match response.get("error") {
+            Some(error) => Err(error.clone()),
+            None => Ok(response["result"].clone()),
+        }
+    }
+}
+
+impl Drop for TestNode {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Node key of the first validator of a network and the peer id it yields, the other
+/// validators use it as their boot node.
+const BOOT_NODE_KEY: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const BOOT_NODE_PEER_ID: &str = "12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp";
+
+/// A local network authoring blocks with BABE and finalizing them with GRANDPA, one
+/// node per validator. Alice is root and the only oracle.
+pub struct TestNetwork {
+    pub nodes: Vec<TestNode>,
+    spec: PathBuf,
+}
+
+impl TestNetwork {
+    /// Spawn a node for every validator, named after the development accounts their
+    /// session keys are derived from, e.g. `&["Alice", "Bob", "Charlie"]`.
+    pub fn spawn(validators: &[&str]) -> Self {
+        let ports = validators
+            .iter()
+            .map(|_| TestNode::next_ports())
+            .collect::<Vec<_>>();
+        let spec = std::env::temp_dir().join(format!("e2e-network-{}.json", ports[0].0));
+        Self::write_spec(&spec, validators);
+
+        let boot_node = format!(
+            "/ip4/127.0.0.1/tcp/{}/p2p/{}",
+            ports[0].0, BOOT_NODE_PEER_ID
+        );
+        let nodes = validators
+            .iter()
+            .zip(ports)
+            .enumerate()
+            .map(|(index, (validator, ports))| {
+                let mut args = vec![
+                    "--chain".into(),
+                    spec.display().to_string(),
+                    format!("--{}", validator.to_lowercase()),
+                    "--tmp".into(),
+                ];
+                if index == 0 {
+                    args.extend(vec!["--node-key".into(), BOOT_NODE_KEY.into()]);
+                } else {
+                    args.extend(vec!["--bootnodes".into(), boot_node.clone()]);
+                }
+                TestNode::spawn_on(ports, args)
+            })
+            .collect();
+
+        Self { nodes, spec }
+    }
+
+    fn write_spec(path: &Path, validators: &[&str]) {
+        let alice = AccountKeyring::Alice.to_account_id().to_ss58check();
+        let mut command = Command::new(TestNode::binary());
+        command
+            .args(&["chain-spec", "--network", "local", "--raw"])
+            .args(&["--name", "E2E Testnet", "--id", "e2e"])
+            .args(&["--root", &alice, "--oracle", &alice]);
+        for validator in validators {
+            command.args(&["--validator-seed", validator]);
+        }
+
+        let output = command.output().expect("The node generates chain specs");
+        assert!(output.status.success(), "Unable to generate the chain spec");
+        std::fs::write(path, output.stdout).expect("The chain spec can be written");
+    }
+}
+
+impl Drop for TestNetwork {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.spec);
+    }
+}
+
+/// Poll `condition` until it holds, blocks are sealed as soon as a transaction is
+/// submitted but it still takes a moment for them to be imported.
+pub async fn eventually<F, Fut>(condition: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    eventually_within(10, condition).await
+}
+
+/// Poll `condition` for up to `seconds`, networks take a few slots to author and
+/// finalize blocks.
+pub async fn eventually_within<F, Fut>(seconds: u64, condition: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    for _ in 0..seconds * 5 {
+        if condition().await {
+            return;
+        }
+        async_std::task::sleep(Duration::from_millis(200)).await;
+    }
+    panic!("The condition was never met");
+}
@@ -0,0 +1,56 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use e2e_tests::*;
+use futures::StreamExt;
+use nodle_chain_runtime::constants::NODL;
+use sp_keyring::AccountKeyring;
+use substrate_subxt::PairSigner;
+
+#[async_std::test]
+#[ignore = "needs a release build of the node, run with `cargo test -p e2e-tests -- --ignored`"]
+async fn client_submits_and_watches_reward_batches() {
+    let node = TestNode::spawn();
+    // Wait for the node to be up
+    node.client().await;
+    let client = ChainClient::new(&node.ws_url).await.unwrap();
+    let alice = PairSigner::<NodleRuntime, _>::new(AccountKeyring::Alice.pair());
+    let bob_id = AccountKeyring::Bob.to_account_id();
+
+    let budget = client.coins_left().await.unwrap();
+    let mut allocations = client.watch_allocations(bob_id.clone()).await.unwrap();
+
+    let batch = client
+        .submit_reward_batch(
+            &alice,
+            &[
+                (bob_id.clone(), 10 * NODL),
+                (AccountKeyring::Charlie.to_account_id(), 20 * NODL),
+            ],
+        )
+        .await
+        .unwrap();
+
+    let allocation = allocations.next().await.unwrap();
+    assert_eq!(allocation.to, bob_id);
+    assert_eq!(allocation.amount + allocation.fee, 10 * NODL);
+    assert_eq!(allocation.proof, batch.root.as_bytes().to_vec());
+
+    let client = &client;
+    eventually(|| async move { client.coins_left().await.unwrap() == budget - 30 * NODL }).await;
+}
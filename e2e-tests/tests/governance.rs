@@ -0,0 +1,59 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use e2e_tests::*;
+use nodle_chain_runtime::constants::NODL;
+use sp_keyring::AccountKeyring;
+use substrate_subxt::{system::AccountStoreExt, PairSigner};
+
+#[async_std::test]
+#[ignore = "needs a release build of the node, run with `cargo test -p e2e-tests -- --ignored`"]
+async fn financial_committee_can_raise_the_minimum_allocation() {
+    let node = TestNode::spawn();
+    let client = &node.client().await;
+    let alice = PairSigner::<NodleRuntime, _>::new(AccountKeyring::Alice.pair());
+    let alice_id = &AccountKeyring::Alice.to_account_id();
+
+    let set = client
+        .encode(SetCall::<NodleRuntime> {
+            value: ParameterValue::MinimumAllocation(100 * NODL),
+        })
+        .unwrap();
+    client
+        .submit(financial_committee::execute(&set), &alice)
+        .await
+        .unwrap();
+    eventually(|| async move { client.minimum_allocation(None).await.unwrap() == 100 * NODL })
+        .await;
+
+    // Alice is an oracle of the development chain, her allocation is included but
+    // rejected when dispatched
+    client
+        .submit(
+            AllocateCall::<NodleRuntime> {
+                to: AccountKeyring::Bob.to_account_id(),
+                amount: 10 * NODL,
+                proof: Vec::new(),
+            },
+            &alice,
+        )
+        .await
+        .unwrap();
+    eventually(|| async move { client.account(alice_id, None).await.unwrap().nonce == 2 }).await;
+    assert_eq!(client.coins_consumed(None).await.unwrap(), 0);
+}
@@ -0,0 +1,112 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use e2e_tests::*;
+use nodle_chain_runtime::constants::NODL;
+use sp_keyring::AccountKeyring;
+use substrate_subxt::{Client, PairSigner};
+
+const VALIDATORS: [&str; 3] = ["Alice", "Bob", "Charlie"];
+
+async fn finalized_number(client: &Client<NodleRuntime>) -> u32 {
+    let hash = client.finalized_head().await.unwrap();
+    client.header(Some(hash)).await.unwrap().unwrap().number
+}
+
+#[async_std::test]
+#[ignore = "needs a release build of the node, run with `cargo test -p e2e-tests -- --ignored`"]
+async fn validators_finalize_the_same_chain() {
+    let network = TestNetwork::spawn(&VALIDATORS);
+    let mut clients = Vec::new();
+    for node in &network.nodes {
+        clients.push(node.client().await);
+    }
+
+    for client in &clients {
+        eventually_within(120, || async move { finalized_number(client).await >= 3 }).await;
+    }
+    let finalized = clients[0].block_hash(Some(3u32.into())).await.unwrap();
+    for client in &clients[1..] {
+        assert_eq!(
+            client.block_hash(Some(3u32.into())).await.unwrap(),
+            finalized
+        );
+    }
+}
+
+#[async_std::test]
+#[ignore = "needs a release build of the node, run with `cargo test -p e2e-tests -- --ignored`"]
+async fn oracles_rotated_by_governance_allocate_on_every_node() {
+    let network = TestNetwork::spawn(&VALIDATORS);
+    let mut clients = Vec::new();
+    for node in &network.nodes {
+        clients.push(node.client().await);
+    }
+    let client = &clients[0];
+    let alice = PairSigner::<NodleRuntime, _>::new(AccountKeyring::Alice.pair());
+    let dave = PairSigner::<NodleRuntime, _>::new(AccountKeyring::Dave.pair());
+    let alice_id = &AccountKeyring::Alice.to_account_id();
+    let dave_id = &AccountKeyring::Dave.to_account_id();
+
+    // Dave replaces Alice as the oracle
+    let add = client
+        .encode(AddOracleCall::<NodleRuntime> {
+            who: dave_id.clone(),
+        })
+        .unwrap();
+    client
+        .submit(technical_committee::execute(&add), &alice)
+        .await
+        .unwrap();
+    eventually_within(120, || async move {
+        client.oracles(None).await.unwrap().contains(dave_id)
+    })
+    .await;
+    let remove = client
+        .encode(RemoveOracleCall::<NodleRuntime> {
+            who: alice_id.clone(),
+        })
+        .unwrap();
+    client
+        .submit(technical_committee::execute(&remove), &alice)
+        .await
+        .unwrap();
+
+    // Submitted to another node than the one governance went through
+    clients[1]
+        .submit(
+            AllocateCall::<NodleRuntime> {
+                to: AccountKeyring::Eve.to_account_id(),
+                amount: 1_000 * NODL,
+                proof: Vec::new(),
+            },
+            &dave,
+        )
+        .await
+        .unwrap();
+
+    for client in &clients {
+        eventually_within(120, || async move {
+            let finalized = client.finalized_head().await.unwrap();
+            let oracles = client.oracles(Some(finalized)).await.unwrap();
+            oracles == vec![dave_id.clone()]
+                && client.coins_consumed(Some(finalized)).await.unwrap() == 1_000 * NODL
+        })
+        .await;
+    }
+}
@@ -0,0 +1,148 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use e2e_tests::*;
+use nodle_chain_runtime::constants::NODL;
+use sp_core::crypto::Ss58Codec;
+use sp_keyring::AccountKeyring;
+use std::marker::PhantomData;
+use substrate_subxt::{system::AccountStoreExt, PairSigner};
+
+#[async_std::test]
+#[ignore = "needs a release build of the node, run with `cargo test -p e2e-tests -- --ignored`"]
+async fn oracles_can_be_onboarded_and_revoked() {
+    let node = TestNode::spawn();
+    let client = &node.client().await;
+    let alice = PairSigner::<NodleRuntime, _>::new(AccountKeyring::Alice.pair());
+    let bob = PairSigner::<NodleRuntime, _>::new(AccountKeyring::Bob.pair());
+    let bob_id = &AccountKeyring::Bob.to_account_id();
+    let charlie_id = AccountKeyring::Charlie.to_account_id();
+
+    let add = client
+        .encode(AddOracleCall::<NodleRuntime> {
+            who: bob_id.clone(),
+        })
+        .unwrap();
+    client
+        .submit(technical_committee::execute(&add), &alice)
+        .await
+        .unwrap();
+    eventually(|| async move { client.oracles(None).await.unwrap().contains(bob_id) }).await;
+
+    client
+        .submit(
+            AllocateCall::<NodleRuntime> {
+                to: charlie_id.clone(),
+                amount: 1_000 * NODL,
+                proof: Vec::new(),
+            },
+            &bob,
+        )
+        .await
+        .unwrap();
+    eventually(|| async move { client.coins_consumed(None).await.unwrap() == 1_000 * NODL }).await;
+
+    let remove = client
+        .encode(RemoveOracleCall::<NodleRuntime> {
+            who: bob_id.clone(),
+        })
+        .unwrap();
+    client
+        .submit(technical_committee::execute(&remove), &alice)
+        .await
+        .unwrap();
+    eventually(|| async move { !client.oracles(None).await.unwrap().contains(bob_id) }).await;
+
+    // The transaction is included but fails to dispatch, wait for its nonce to be used
+    client
+        .submit(
+            AllocateCall::<NodleRuntime> {
+                to: charlie_id,
+                amount: 1_000 * NODL,
+                proof: Vec::new(),
+            },
+            &bob,
+        )
+        .await
+        .unwrap();
+    eventually(|| async move { client.account(bob_id, None).await.unwrap().nonce == 2 }).await;
+    assert_eq!(client.coins_consumed(None).await.unwrap(), 1_000 * NODL);
+}
+
+#[async_std::test]
+#[ignore = "needs a release build of the node, run with `cargo test -p e2e-tests -- --ignored`"]
+async fn batched_allocations_can_be_proven() {
+    let node = TestNode::spawn();
+    let client = &node.client().await;
+    let alice = PairSigner::<NodleRuntime, _>::new(AccountKeyring::Alice.pair());
+
+    let rewards = [
+        (AccountKeyring::Bob.to_account_id(), 10 * NODL),
+        (AccountKeyring::Charlie.to_account_id(), 20 * NODL),
+        (AccountKeyring::Dave.to_account_id(), 30 * NODL),
+    ];
+    let leaves = rewards
+        .iter()
+        .map(|(who, amount)| chain_merkle::leaf(who, *amount))
+        .collect::<Vec<_>>();
+    let root = chain_merkle::root(&leaves).unwrap();
+
+    let calls = rewards
+        .iter()
+        .map(|(who, amount)| {
+            client.encode(AllocateCall::<NodleRuntime> {
+                to: who.clone(),
+                amount: *amount,
+                proof: root.as_bytes().to_vec(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    client
+        .submit(
+            BatchCall::<NodleRuntime> {
+                calls,
+                _runtime: PhantomData,
+            },
+            &alice,
+        )
+        .await
+        .unwrap();
+    eventually(|| async move { client.coins_consumed(None).await.unwrap() == 60 * NODL }).await;
+
+    // The node indexes blocks in the background
+    let params = serde_json::json!([
+        format!("{:?}", root),
+        AccountKeyring::Dave.to_account_id().to_ss58check()
+    ]);
+    let node = &node;
+    let params = &params;
+    eventually(|| async move {
+        node.rpc("allocations_generateProof", params.clone())
+            .is_ok()
+    })
+    .await;
+
+    let proof = node
+        .rpc("allocations_generateProof", params.clone())
+        .unwrap();
+    let proof: chain_merkle::MerkleProof = serde_json::from_value(proof).unwrap();
+    assert_eq!(proof.root, root);
+    assert_eq!(proof.leaf, leaves[2]);
+    assert!(chain_merkle::verify(&proof));
+}
@@ -0,0 +1,284 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Mock runtime shared by the fuzzing targets. Targets decode their input as a
+//! sequence of `(caller, blocks, call)` and dispatch every call after moving
+//! `blocks % 4` blocks ahead, the storage invariants and issuance are checked after
+//! every call.
+
+use frame_support::{
+    dispatch::Dispatchable,
+    impl_outer_dispatch, impl_outer_origin, ord_parameter_types, parameter_types,
+    storage::IterableStorageMap,
+    traits::{ChangeMembers, Contains, Currency, Get, InitializeMembers, OnRuntimeUpgrade},
+    weights::Weight,
+};
+use frame_system::EnsureSignedBy;
+use nodle_support::{HousekeepingTask, MemberSet, WithAccountId};
+use parity_scale_codec::Decode;
+use sp_runtime::{
+    testing::{TestSignature, UintAuthorityId},
+    ModuleId, Perbill,
+};
+use std::cell::RefCell;
+use test_utils::{impl_mock_balances, impl_mock_system};
+
+impl_outer_origin! {
+    pub enum Origin for Runtime {}
+}
+
+impl_outer_dispatch! {
+    pub enum Call for Runtime where origin: Origin {
+        pallet_allocations::Allocations,
+        pallet_grants::Grants,
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Runtime;
+impl_mock_system!(
+    Runtime,
+    Origin,
+    Call,
+    pallet_balances::AccountData<u64>,
+    Allocations,
+    Allocations
+);
+parameter_types! {
+    // Every coin minted reaches an account, none is lost as dust
+    pub const ExistentialDeposit: u64 = 1;
+}
+impl_mock_balances!(Runtime, ExistentialDeposit);
+
+ord_parameter_types! {
+    pub const Admin: u64 = 7;
+}
+parameter_types! {
+    pub const MinimumPeriod: u64 = 1;
+}
+impl pallet_timestamp::Trait for Runtime {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = MinimumPeriod;
+    type WeightInfo = ();
+}
+impl pallet_emergency_shutdown::Trait for Runtime {
+    type Event = ();
+    type ShutdownOrigin = EnsureSignedBy<Admin, u64>;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const Receiver: u64 = 100;
+    pub const MinimumAllocation: u64 = 1;
+    pub const Fee: Perbill = Perbill::from_percent(10);
+    pub const PayoutEraDuration: u64 = 5;
+    pub const MaxSettlementsPerBlock: u32 = 2;
+    pub const MaxPrunedPerBlock: u32 = 3;
+    pub const MaxUnsignedClaimsPerBlock: u32 = 1;
+    pub const UnsignedPriority: u64 = 1;
+    pub const MaxClaimsPerBatch: u32 = 3;
+    pub const RewardsUnlockBlock: u64 = 0;
+    pub const PriceValidity: u64 = 10;
+    pub const StallThreshold: u64 = 0;
+    pub const OracleActivationDelay: u64 = 0;
+}
+thread_local! {
+    static COINS_LIMIT: RefCell<u64> = RefCell::new(0);
+}
+pub struct CoinsLimit;
+impl Get<u64> for CoinsLimit {
+    fn get() -> u64 {
+        COINS_LIMIT.with(|c| *c.borrow())
+    }
+}
+/// Nobody is denied, fuzzing focuses on the allocation and payout logic.
+pub struct NoneDenied;
+impl Contains<u64> for NoneDenied {
+    fn sorted_members() -> Vec<u64> {
+        vec![]
+    }
+}
+/// Stands for the membership pallet, which calls back into the allocations pallet.
+pub struct OracleMembership;
+impl OracleMembership {
+    fn members() -> Vec<u64> {
+        let mut members = Allocations::oracles();
+        members.extend(
+            Allocations::pending_oracles()
+                .into_iter()
+                .map(|(oracle, _)| oracle),
+        );
+        members.sort();
+        members
+    }
+}
+impl MemberSet<u64> for OracleMembership {
+    fn add_member(who: &u64) -> Result<(), &'static str> {
+        let mut members = Self::members();
+        let position = members.binary_search(who).err().ok_or("Already a member")?;
+        members.insert(position, *who);
+        Allocations::change_members_sorted(&[*who], &[], &members);
+        Ok(())
+    }
+
+    fn remove_member(who: &u64) -> Result<(), &'static str> {
+        let mut members = Self::members();
+        let position = members.binary_search(who).map_err(|_| "Not a member")?;
+        members.remove(position);
+        Allocations::change_members_sorted(&[], &[*who], &members);
+        Ok(())
+    }
+}
+impl WithAccountId<u64> for Receiver {
+    fn account_id() -> u64 {
+        Receiver::get()
+    }
+}
+impl pallet_allocations::Trait for Runtime {
+    type Event = ();
+    type Currency = Balances;
+    type ProtocolFee = Fee;
+    type FeeRounding = pallet_allocations::rounding::RemainderToBeneficiary;
+    type ProtocolFeeReceiver = Receiver;
+    type MaximumCoinsEverAllocated = CoinsLimit;
+    type MinimumAllocation = MinimumAllocation;
+    type ExistentialDeposit = ExistentialDeposit;
+    type OnDust = pallet_allocations::dust::Accumulate<Runtime>;
+    type OracleOrigin = pallet_allocations::EnsureOracle<Runtime>;
+    type OracleActivationDelay = OracleActivationDelay;
+    type OracleMembership = OracleMembership;
+    type CommitteeOrigin = frame_system::EnsureRoot<u64>;
+    type FallbackOrigin = EnsureSignedBy<Admin, u64>;
+    type StallThreshold = StallThreshold;
+    type DeviceId = u64;
+    type Devices = ();
+    type OrganizationAccounts = ();
+    type ComplianceCheck = NoneDenied;
+    type PriceValidity = PriceValidity;
+    type PointsRateOrigin = EnsureSignedBy<Admin, u64>;
+    type DelegationOrigin = EnsureSignedBy<Admin, u64>;
+    type Coverage = ();
+    type OnAllocation = ();
+    type ReportingPeriods = ();
+    type Time = Timestamp;
+    type PayoutEraDuration = PayoutEraDuration;
+    type MaxSettlementsPerBlock = MaxSettlementsPerBlock;
+    type MaxPrunedPerBlock = MaxPrunedPerBlock;
+    type MaxUnsignedClaimsPerBlock = MaxUnsignedClaimsPerBlock;
+    type UnsignedPriority = UnsignedPriority;
+    type MaxClaimsPerBatch = MaxClaimsPerBatch;
+    type Public = UintAuthorityId;
+    type Signature = TestSignature;
+    type RewardsUnlockBlock = RewardsUnlockBlock;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const GrantsModuleId: ModuleId = ModuleId(*b"py/grnts");
+}
+impl pallet_grants::Trait for Runtime {
+    type Event = ();
+    type Currency = Balances;
+    type CancelOrigin = EnsureSignedBy<Admin, u64>;
+    type ModuleId = GrantsModuleId;
+    type WeightInfo = ();
+}
+
+pub type Allocations = pallet_allocations::Module<Runtime>;
+pub type Balances = pallet_balances::Module<Runtime>;
+pub type Grants = pallet_grants::Module<Runtime>;
+pub type System = frame_system::Module<Runtime>;
+pub type Timestamp = pallet_timestamp::Module<Runtime>;
+
+/// Callers are picked among these accounts, all of them endowed.
+pub const ACCOUNTS: u64 = 8;
+pub const ORACLES: [u64; 2] = [0, 1];
+pub const COINS: u64 = 1_000_000_000;
+
+/// Endowed accounts, the oracles set and the coins limit the calls are run against.
+fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::default()
+        .build_storage::<Runtime>()
+        .expect("system genesis is valid");
+    pallet_balances::GenesisConfig::<Runtime> {
+        balances: (0..ACCOUNTS).map(|who| (who, 1_000_000)).collect(),
+    }
+    .assimilate_storage(&mut storage)
+    .expect("balances genesis is valid");
+
+    let mut ext = sp_io::TestExternalities::from(storage);
+    ext.execute_with(|| {
+        COINS_LIMIT.with(|c| *c.borrow_mut() = COINS);
+        Allocations::initialize_members(&ORACLES);
+    });
+    ext
+}
+
+/// Decode `input` as calls of type `C` and dispatch them one after the other.
+pub fn run<C: Decode + Into<Call>>(mut input: &[u8]) {
+    new_test_ext().execute_with(|| {
+        // The pallet has no genesis config, its storage is versioned on upgrades
+        Allocations::on_runtime_upgrade();
+        let initial_issuance = Balances::total_issuance();
+        while let Ok((caller, blocks, call)) = <(u8, u8, C)>::decode(&mut input) {
+            for _ in 0..blocks % 4 {
+                next_block();
+            }
+            let call: Call = call.into();
+            let _ = call.dispatch(Origin::signed(caller as u64 % ACCOUNTS));
+            check_invariants(initial_issuance);
+        }
+
+        // Every allocation is eventually paid out
+        for _ in 0..10_000 {
+            if Allocations::unsettled_eras().is_empty() {
+                break;
+            }
+            next_block();
+        }
+        assert!(Allocations::unsettled_eras().is_empty());
+        assert_eq!(
+            Balances::total_issuance(),
+            initial_issuance + Allocations::coins_consumed()
+        );
+        check_invariants(initial_issuance);
+    });
+}
+
+fn next_block() {
+    let n = System::block_number() + 1;
+    System::set_block_number(n);
+    // Six seconds blocks, campaigns scheduled in the calls open and close over time
+    Timestamp::set_timestamp(n * 6_000);
+    Allocations::housekeep(n, Weight::max_value());
+}
+
+fn check_invariants(initial_issuance: u64) {
+    Allocations::try_state().expect("allocations storage is consistent");
+    Grants::try_state().expect("grants storage is consistent");
+
+    let issuance = Balances::total_issuance();
+    let balances = <frame_system::Account<Runtime>>::iter()
+        .map(|(_, account)| account.data.free + account.data.reserved)
+        .sum::<u64>();
+    assert_eq!(issuance, balances);
+    // Payouts may still be pending but nothing is minted outside of allocations
+    assert!(Allocations::coins_consumed() <= COINS);
+    assert!(issuance <= initial_issuance + Allocations::coins_consumed());
+}
@@ -0,0 +1,131 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Token migration helper: turns a snapshot of the ERC-20 holders into the genesis
+//! balances of the holders who registered a Nodle account, to be passed to
+//! `nodle-chain chain-spec --balances`, and an airdrop merkle tree for the others,
+//! to be passed to `nodle-chain chain-spec --airdrop` and claimed through `pallet-airdrop`.
+
+mod snapshot;
+
+use chain_merkle::MerkleProof;
+use nodle_chain_primitives::{AccountId, Balance};
+use serde::Serialize;
+use sp_core::{H160, H256};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "genesis-builder")]
+struct Opt {
+    /// CSV snapshot of the token holders, as `address,balance[,account]`.
+    #[structopt(long, parse(from_os_str))]
+    snapshot: PathBuf,
+
+    /// Number of decimals of the ERC-20 token.
+    #[structopt(long, default_value = "18")]
+    token_decimals: u32,
+
+    /// Where to write the genesis balances.
+    #[structopt(long, parse(from_os_str), default_value = "balances.json")]
+    balances: PathBuf,
+
+    /// Where to write the airdrop root and the proofs of every holder.
+    #[structopt(long, parse(from_os_str), default_value = "airdrop.json")]
+    airdrop: PathBuf,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Claim {
+    address: H160,
+    amount: Balance,
+    proof: MerkleProof,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Airdrop {
+    /// `None` if every holder registered a Nodle account
+    root: Option<H256>,
+    total: Balance,
+    claims: Vec<Claim>,
+}
+
+fn write_json(path: &PathBuf, value: &impl Serialize) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+fn main() -> Result<(), String> {
+    let opt = Opt::from_args();
+    let csv = fs::read_to_string(&opt.snapshot)
+        .map_err(|e| format!("failed to read {}: {}", opt.snapshot.display(), e))?;
+    let snapshot = snapshot::parse(&csv, opt.token_decimals)?;
+
+    // Several addresses may be migrated to the same account
+    let mut balances = BTreeMap::<AccountId, Balance>::new();
+    let mut unclaimed = Vec::new();
+    for holder in snapshot.holders {
+        match holder.account {
+            Some(account) => {
+                let balance = balances.entry(account).or_default();
+                *balance = balance.saturating_add(holder.amount);
+            }
+            None => unclaimed.push((holder.address, holder.amount)),
+        }
+    }
+    let balances = balances.into_iter().collect::<Vec<_>>();
+
+    unclaimed.sort();
+    let leaves = unclaimed
+        .iter()
+        .map(|(address, amount)| chain_merkle::leaf(address, *amount))
+        .collect::<Vec<_>>();
+    let airdrop = Airdrop {
+        root: chain_merkle::root(&leaves),
+        total: unclaimed.iter().map(|(_, amount)| amount).sum(),
+        claims: unclaimed
+            .iter()
+            .zip(chain_merkle::prove_all(&leaves))
+            .map(|((address, amount), proof)| Claim {
+                address: *address,
+                amount: *amount,
+                proof,
+            })
+            .collect(),
+    };
+
+    write_json(&opt.balances, &balances)?;
+    write_json(&opt.airdrop, &airdrop)?;
+
+    eprintln!(
+        "{} accounts endowed with {} at genesis, {} addresses sharing {} in the airdrop",
+        balances.len(),
+        balances.iter().map(|(_, amount)| amount).sum::<Balance>(),
+        airdrop.claims.len(),
+        airdrop.total,
+    );
+    if snapshot.dust > 0 {
+        eprintln!(
+            "{} token units were lost to rounding, NODL has fewer decimals than the token",
+            snapshot.dust
+        );
+    }
+    Ok(())
+}
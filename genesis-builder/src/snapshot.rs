@@ -0,0 +1,176 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Parsing of ERC-20 holder snapshots: CSV files with an `address,balance` header
+//! followed by one row per holder. Holders who already registered a Nodle account may
+//! name it in a third `account` column, their tokens are then minted at genesis.
+//! Balances are expressed in the token's smallest unit.
+
+use nodle_chain_primitives::{AccountId, Balance};
+use sp_core::{crypto::Ss58Codec, H160};
+use std::{collections::BTreeSet, str::FromStr};
+
+/// Number of decimals of NODL, `constants::NODL` in the runtime.
+const NODL_DECIMALS: u32 = 12;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Holder {
+    pub address: H160,
+    /// Amount converted to NODL
+    pub amount: Balance,
+    /// Nodle account the holder registered, if any
+    pub account: Option<AccountId>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    pub holders: Vec<Holder>,
+    /// Amount lost to rounding when converting to NODL, in the token's smallest unit
+    pub dust: u128,
+}
+
+/// Convert `raw` token units to NODL, returns the converted amount and what was lost
+/// to rounding.
+fn to_nodl(raw: u128, token_decimals: u32) -> Result<(Balance, u128), String> {
+    if token_decimals >= NODL_DECIMALS {
+        let scale = 10u128
+            .checked_pow(token_decimals - NODL_DECIMALS)
+            .ok_or("too many decimals")?;
+        Ok((raw / scale, raw % scale))
+    } else {
+        let amount = raw
+            .checked_mul(10u128.pow(NODL_DECIMALS - token_decimals))
+            .ok_or("balance overflow")?;
+        Ok((amount, 0))
+    }
+}
+
+fn parse_address(s: &str) -> Result<H160, String> {
+    let hex = s.strip_prefix("0x").unwrap_or(s);
+    if hex.len() != 40 {
+        return Err(format!("{} is not an Ethereum address", s));
+    }
+    H160::from_str(hex).map_err(|e| format!("{} is not an Ethereum address: {:?}", s, e))
+}
+
+/// Parse a snapshot, rejecting malformed rows and duplicated addresses since they
+/// usually mean the export went wrong. Holders left with nothing after the
+/// conversion are dropped.
+pub fn parse(csv: &str, token_decimals: u32) -> Result<Snapshot, String> {
+    let mut snapshot = Snapshot::default();
+    let mut seen = BTreeSet::new();
+
+    for (number, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (number == 0 && line.to_lowercase().starts_with("address")) {
+            continue;
+        }
+
+        let error = |e: String| format!("line {}: {}", number + 1, e);
+        let columns = line.split(',').map(str::trim).collect::<Vec<_>>();
+        let (address, balance, account) = match columns[..] {
+            [address, balance] => (address, balance, None),
+            [address, balance, ""] => (address, balance, None),
+            [address, balance, account] => (address, balance, Some(account)),
+            _ => return Err(error("expected address,balance[,account]".into())),
+        };
+
+        let address = parse_address(address).map_err(error)?;
+        if !seen.insert(address) {
+            return Err(error(format!("{:?} appears more than once", address)));
+        }
+        let raw = balance
+            .parse::<u128>()
+            .map_err(|e| error(format!("invalid balance {}: {}", balance, e)))?;
+        let (amount, dust) = to_nodl(raw, token_decimals).map_err(error)?;
+        let account = account
+            .map(|account| {
+                AccountId::from_ss58check(account)
+                    .map_err(|e| error(format!("invalid account {}: {:?}", account, e)))
+            })
+            .transpose()?;
+
+        snapshot.dust = snapshot.dust.saturating_add(dust);
+        if amount > 0 {
+            snapshot.holders.push(Holder {
+                address,
+                amount,
+                account,
+            });
+        }
+    }
+
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    #[test]
+    fn converts_token_units() {
+        assert_eq!(to_nodl(1_234_567, 18), Ok((1, 234_567)));
+        assert_eq!(to_nodl(5, 12), Ok((5, 0)));
+        assert_eq!(to_nodl(5, 6), Ok((5_000_000, 0)));
+        assert!(to_nodl(u128::max_value(), 0).is_err());
+    }
+
+    #[test]
+    fn parses_snapshots() {
+        let csv = format!(
+            "address,balance,account\n\
+             0x{},3000000,\n\
+             0x{},2500000,{}\n\
+             0x{},999999,\n",
+            "11".repeat(20),
+            "22".repeat(20),
+            ALICE,
+            "33".repeat(20),
+        );
+        let snapshot = parse(&csv, 18).expect("the snapshot is valid");
+
+        assert_eq!(
+            snapshot.holders,
+            vec![
+                Holder {
+                    address: H160::repeat_byte(0x11),
+                    amount: 3,
+                    account: None,
+                },
+                Holder {
+                    address: H160::repeat_byte(0x22),
+                    amount: 2,
+                    account: Some(AccountId::from_ss58check(ALICE).unwrap()),
+                },
+            ]
+        );
+        assert_eq!(snapshot.dust, 500_000 + 999_999);
+    }
+
+    #[test]
+    fn rejects_invalid_snapshots() {
+        let address = format!("0x{}", "11".repeat(20));
+
+        assert!(parse("0x1234,10", 18).is_err());
+        assert!(parse(&format!("{},ten", address), 18).is_err());
+        assert!(parse(&format!("{},10,nope", address), 18).is_err());
+        assert!(parse(&format!("{},10\n{},20", address, address), 18).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Helpers for the integration tests: the full runtime built in test externalities
+//! with Alice, Bob and Charlie on the committees and Dave as the only oracle. Tests
+//! exercise flows spanning several pallets that their own mocks can not cover.
+
+pub use nodle_chain_runtime::*;
+
+use frame_support::{traits::OnInitialize, weights::GetDispatchInfo};
+use nodle_chain_primitives::{AccountId, Balance, BlockNumber};
+use pallet_collective::Module as Collective;
+use parity_scale_codec::Encode;
+use sp_keyring::AccountKeyring;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+pub fn alice() -> AccountId {
+    AccountKeyring::Alice.to_account_id()
+}
+
+pub fn bob() -> AccountId {
+    AccountKeyring::Bob.to_account_id()
+}
+
+pub fn charlie() -> AccountId {
+    AccountKeyring::Charlie.to_account_id()
+}
+
+pub fn oracle() -> AccountId {
+    AccountKeyring::Dave.to_account_id()
+}
+
+pub const ENDOWMENT: Balance = 100 * constants::NODL;
+
+/// Test externalities for the full runtime, oracles being allowed to allocate up to
+/// `allocations_budget`.
+pub fn new_test_ext(allocations_budget: Balance) -> sp_io::TestExternalities {
+    let committee = vec![alice(), bob(), charlie()];
+    let storage = GenesisConfig {
+        frame_system: Some(Default::default()),
+        pallet_balances: Some(BalancesConfig {
+            balances: AccountKeyring::iter()
+                .map(|who| (who.to_account_id(), ENDOWMENT))
+                .collect(),
+        }),
+        pallet_indices: Some(IndicesConfig { indices: vec![] }),
+        pallet_grants: Some(GrantsConfig { vesting: vec![] }),
+        pallet_airdrop: Some(Default::default()),
+
+        // Consensus is not exercised
+        pallet_session: None,
+        pallet_babe: None,
+        pallet_im_online: None,
+        pallet_authority_discovery: None,
+        pallet_grandpa: None,
+        pallet_membership_Instance2: None,
+
+        // Governance
+        pallet_collective_Instance2: Some(Default::default()),
+        pallet_membership_Instance1: Some(TechnicalMembershipConfig {
+            members: committee.clone(),
+            phantom: Default::default(),
+        }),
+        pallet_collective_Instance3: Some(Default::default()),
+        pallet_membership_Instance3: Some(FinancialMembershipConfig {
+            members: committee.clone(),
+            phantom: Default::default(),
+        }),
+        pallet_reserve_Instance1: Some(Default::default()),
+        pallet_reserve_Instance2: Some(Default::default()),
+        pallet_reserve_Instance3: Some(Default::default()),
+        pallet_collective_Instance4: Some(Default::default()),
+        pallet_membership_Instance4: Some(RootMembershipConfig {
+            members: committee,
+            phantom: Default::default(),
+        }),
+
+        // Allocations
+        pallet_emission: Some(EmissionConfig { allocations_budget }),
+        pallet_membership_Instance5: Some(AllocationsOraclesConfig {
+            members: vec![oracle()],
+            phantom: Default::default(),
+        }),
+    }
+    .build_storage()
+    .expect("genesis is valid");
+
+    let mut ext = sp_io::TestExternalities::from(storage);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+/// Move to block `n`, running the hooks of the pallets under test on the way.
+pub fn run_to_block(n: BlockNumber) {
+    while System::block_number() < n {
+        let next = System::block_number() + 1;
+        System::set_block_number(next);
+        Emission::on_initialize(next);
+        Allocations::on_initialize(next);
+    }
+}
+
+/// Have a majority of the committee of collective `I` approve `call`: Alice proposes
+/// it, Bob seconds it and Charlie closes the vote.
+pub fn pass_motion<I>(call: Call)
+where
+    I: pallet_collective::Instance,
+    Runtime: pallet_collective::Trait<I, Proposal = Call>,
+{
+    let length = call.encoded_size() as u32;
+    let weight = call.get_dispatch_info().weight;
+    let hash = BlakeTwo256::hash_of(&call);
+    let index = Collective::<Runtime, I>::proposal_count();
+
+    Collective::<Runtime, I>::propose(Origin::signed(alice()), 2, Box::new(call), length)
+        .expect("Alice is a member");
+    Collective::<Runtime, I>::vote(Origin::signed(bob()), hash, index, true)
+        .expect("Bob is a member");
+    Collective::<Runtime, I>::close(Origin::signed(charlie()), hash, index, weight, length)
+        .expect("the motion has enough ayes");
+}
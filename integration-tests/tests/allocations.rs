@@ -0,0 +1,182 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use frame_support::{assert_noop, assert_ok, traits::Currency, weights::GetDispatchInfo};
+use integration_tests::*;
+use nodle_chain_primitives::{AccountId, Balance};
+use nodle_support::WithAccountId;
+use pallet_collective::{Instance2 as Technical, Instance3 as Financial};
+use pallet_emission::EmissionCurve;
+use pallet_parameters::ParameterValue;
+use sp_keyring::AccountKeyring;
+use sp_runtime::{
+    traits::SignedExtension, transaction_validity::InvalidTransaction, DispatchResult, Perbill,
+};
+
+const NODL: Balance = constants::NODL;
+
+fn allocate(oracle: AccountId, to: AccountId, amount: Balance) -> DispatchResult {
+    Allocations::allocate(Origin::signed(oracle), to, amount, Vec::new())
+}
+
+#[test]
+fn governance_funds_allocations_whose_fees_the_reserve_spends() {
+    new_test_ext(0).execute_with(|| {
+        let grantee = AccountKeyring::Eve.to_account_id();
+        let recipient = AccountKeyring::Ferdie.to_account_id();
+        let reserve = <CompanyReserve as WithAccountId<AccountId>>::account_id();
+
+        // Nothing can be allocated until the budget grows
+        assert_noop!(
+            allocate(oracle(), grantee.clone(), 100 * NODL),
+            pallet_allocations::Error::<Runtime>::TooManyCoinsToAllocate
+        );
+
+        pass_motion::<Financial>(Call::Emission(pallet_emission::Call::set_curve(
+            EmissionCurve::Capped {
+                per_era: 1_000 * NODL,
+                cap: 10_000 * NODL,
+            },
+        )));
+        pass_motion::<Financial>(Call::Parameters(pallet_parameters::Call::set(
+            ParameterValue::AllocationFee(Perbill::from_percent(10)),
+        )));
+        run_to_block(constants::DAYS);
+        // Validators get 20% of the emission, the rest can be allocated
+        assert_eq!(Emission::allocations_budget(), 800 * NODL);
+
+        let reserve_before = Balances::free_balance(&reserve);
+        assert_ok!(allocate(oracle(), grantee.clone(), 100 * NODL));
+        assert_eq!(Balances::free_balance(&reserve), reserve_before + 10 * NODL);
+        assert_noop!(
+            allocate(oracle(), grantee.clone(), 701 * NODL),
+            pallet_allocations::Error::<Runtime>::TooManyCoinsToAllocate
+        );
+
+        // The grantee is paid out once the payout era is over
+        assert_eq!(Balances::free_balance(&grantee), ENDOWMENT);
+        run_to_block(2 * constants::DAYS);
+        assert_eq!(Balances::free_balance(&grantee), ENDOWMENT + 90 * NODL);
+
+        let reserve_before = Balances::free_balance(&reserve);
+        pass_motion::<Financial>(Call::CompanyReserve(pallet_reserve::Call::spend(
+            recipient.clone(),
+            10 * NODL,
+        )));
+        assert_eq!(Balances::free_balance(&recipient), ENDOWMENT + 10 * NODL);
+        assert_eq!(Balances::free_balance(&reserve), reserve_before - 10 * NODL);
+    })
+}
+
+#[test]
+fn technical_committee_manages_the_oracles() {
+    new_test_ext(1_000 * NODL).execute_with(|| {
+        let eve = AccountKeyring::Eve.to_account_id();
+        let grantee = AccountKeyring::Ferdie.to_account_id();
+
+        assert_noop!(
+            allocate(eve.clone(), grantee.clone(), 10 * NODL),
+            pallet_allocations::Error::<Runtime>::OracleAccessDenied
+        );
+
+        pass_motion::<Technical>(Call::Allocations(pallet_allocations::Call::add_oracle(
+            eve.clone(),
+        )));
+        assert_ok!(allocate(eve, grantee.clone(), 10 * NODL));
+
+        pass_motion::<Technical>(Call::Allocations(pallet_allocations::Call::remove_oracle(
+            oracle(),
+        )));
+        assert_noop!(
+            allocate(oracle(), grantee, 10 * NODL),
+            pallet_allocations::Error::<Runtime>::OracleAccessDenied
+        );
+    })
+}
+
+#[test]
+fn technical_committee_keeps_the_oracles_within_bounds() {
+    new_test_ext(1_000 * NODL).execute_with(|| {
+        let eve = AccountKeyring::Eve.to_account_id();
+        assert_ok!(Allocations::set_committee_bounds(Origin::root(), 1, 1));
+
+        // The membership pallet can only be changed directly by root
+        assert_noop!(
+            AllocationsOracles::add_member(Origin::signed(alice()), eve.clone()),
+            sp_runtime::traits::BadOrigin
+        );
+
+        pass_motion::<Technical>(Call::Allocations(pallet_allocations::Call::add_oracle(
+            eve.clone(),
+        )));
+        assert!(!AllocationsOracles::members().contains(&eve));
+        let too_large: sp_runtime::DispatchError =
+            pallet_allocations::Error::<Runtime>::CommitteeTooLarge.into();
+        assert!(System::events().iter().any(|record| matches!(
+            &record.event,
+            Event::pallet_collective_Instance2(pallet_collective::RawEvent::Executed(_, Err(e)))
+                if *e == too_large
+        )));
+
+        pass_motion::<Technical>(Call::Allocations(pallet_allocations::Call::remove_oracle(
+            oracle(),
+        )));
+        assert!(AllocationsOracles::members().contains(&oracle()));
+    })
+}
+
+#[test]
+fn operational_calls_of_non_oracles_are_kept_out_of_the_pool() {
+    new_test_ext(1_000 * NODL).execute_with(|| {
+        let eve = AccountKeyring::Eve.to_account_id();
+        let ferdie = AccountKeyring::Ferdie.to_account_id();
+        let check = |who: &AccountId, call: Call| {
+            let info = call.get_dispatch_info();
+            pallet_allocations::CheckOracle::<Runtime>::new()
+                .validate(who, &call, &info, 0)
+                .map(|_| ())
+        };
+        let calls = vec![
+            Call::Allocations(pallet_allocations::Call::allocate(
+                ferdie.clone(),
+                NODL,
+                Vec::new(),
+            )),
+            Call::Allocations(pallet_allocations::Call::allocate_batch(
+                vec![(ferdie.clone(), NODL)],
+                Vec::new(),
+            )),
+            Call::Allocations(pallet_allocations::Call::credit_points(ferdie.clone(), 10)),
+            Call::Allocations(pallet_allocations::Call::submit_price(1)),
+        ];
+
+        for call in calls {
+            assert_eq!(
+                check(&eve, call.clone()),
+                Err(InvalidTransaction::Call.into())
+            );
+            assert_eq!(check(&oracle(), call), Ok(()));
+        }
+
+        // Other calls are left to their own checks
+        assert_eq!(
+            check(&eve, Call::System(frame_system::Call::remark(Vec::new()))),
+            Ok(())
+        );
+    })
+}
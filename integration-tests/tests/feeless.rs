@@ -0,0 +1,134 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use frame_support::{assert_ok, traits::Get, weights::GetDispatchInfo};
+use integration_tests::*;
+use nodle_chain_primitives::{AccountId, Balance, Signature};
+use pallet_attestations::Attestation;
+use pallet_feeless::ChargeFeelessOrPayment;
+use pallet_transaction_payment::ChargeTransactionPayment;
+use parity_scale_codec::Encode;
+use sp_core::{sr25519, H256};
+use sp_keyring::AccountKeyring;
+use sp_runtime::traits::SignedExtension;
+
+fn signature() -> Signature {
+    sr25519::Signature::from_raw([0; 64]).into()
+}
+
+fn heartbeat() -> Call {
+    Call::Connectivity(pallet_connectivity::Call::respond(0, signature()))
+}
+
+fn attestation_anchor() -> Call {
+    Call::Attestations(pallet_attestations::Call::submit_attestation(
+        Attestation {
+            issuer: alice(),
+            device: bob(),
+            data: H256::repeat_byte(1),
+        },
+        signature(),
+    ))
+}
+
+fn session_remark() -> Call {
+    Call::DeviceSessions(pallet_device_sessions::Call::act(Box::new(Call::System(
+        frame_system::Call::remark(b"alive".to_vec()),
+    ))))
+}
+
+fn transfer() -> Call {
+    Call::Balances(pallet_balances::Call::transfer(
+        bob().into(),
+        constants::NODL,
+    ))
+}
+
+/// What `who` is charged by the signed extension when submitting `call`.
+fn charged(who: &AccountId, call: &Call) -> Balance {
+    let before = Balances::free_balance(who);
+    let len = call.encoded_size();
+    let extension = ChargeFeelessOrPayment::<Runtime>::from(ChargeTransactionPayment::from(0));
+    assert_ok!(extension.pre_dispatch(who, call, &call.get_dispatch_info(), len));
+    before - Balances::free_balance(who)
+}
+
+#[test]
+fn device_check_ins_are_feeless() {
+    new_test_ext(0).execute_with(|| {
+        for call in &[heartbeat(), attestation_anchor(), session_remark()] {
+            let info = call.get_dispatch_info();
+            assert!(Feeless::is_feeless(&alice(), call, &info));
+            assert_eq!(
+                Feeless::estimate_fee(&alice(), call, &info, call.encoded_size() as u32),
+                0
+            );
+            assert_eq!(charged(&alice(), call), 0);
+        }
+    })
+}
+
+#[test]
+fn other_calls_pay_fees() {
+    new_test_ext(0).execute_with(|| {
+        let call = transfer();
+        let info = call.get_dispatch_info();
+        assert!(!Feeless::is_feeless(&alice(), &call, &info));
+
+        let estimate = Feeless::estimate_fee(&alice(), &call, &info, call.encoded_size() as u32);
+        assert!(estimate > 0);
+        assert_eq!(charged(&alice(), &call), estimate);
+    })
+}
+
+#[test]
+fn check_ins_pay_fees_once_the_quota_is_used() {
+    new_test_ext(0).execute_with(|| {
+        let call = heartbeat();
+        let quota: u32 = FeelessQuota::get();
+        for _ in 0..quota {
+            assert_eq!(charged(&alice(), &call), 0);
+        }
+
+        assert_eq!(Feeless::quota_left(&alice()), 0);
+        assert!(charged(&alice(), &call) > 0);
+    })
+}
+
+#[test]
+fn claims_are_feeless_only_when_a_payout_is_pending() {
+    new_test_ext(1_000 * constants::NODL).execute_with(|| {
+        let grantee = AccountKeyring::Eve.to_account_id();
+        let claim = Call::Allocations(pallet_allocations::Call::claim(0));
+
+        // Nothing to claim, a sybil would only spam the chain for free
+        assert!(charged(&grantee, &claim) > 0);
+
+        assert_ok!(Allocations::allocate(
+            Origin::signed(oracle()),
+            grantee.clone(),
+            100 * constants::NODL,
+            Vec::new()
+        ));
+        assert_eq!(charged(&grantee, &claim), 0);
+        assert_ok!(Allocations::claim(Origin::signed(grantee.clone()), 0));
+
+        // Claiming twice the same payout is charged
+        assert!(charged(&grantee, &claim) > 0);
+    })
+}
@@ -0,0 +1,145 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use frame_support::{assert_ok, weights::GetDispatchInfo};
+use integration_tests::*;
+use nodle_chain_primitives::{AccountId, Balance, Index};
+use pallet_feeless::ChargeFeelessOrPayment;
+use pallet_transaction_payment::ChargeTransactionPayment;
+use parity_scale_codec::Encode;
+use sp_keyring::AccountKeyring;
+use sp_runtime::{
+    generic::Era,
+    traits::Applyable,
+    transaction_validity::{
+        InvalidTransaction, TransactionLongevity, TransactionSource, TransactionValidityError,
+    },
+};
+
+const NODL: Balance = constants::NODL;
+
+/// The extensions the oracle daemon signs its transactions with.
+fn extra(nonce: Index) -> SignedExtra {
+    (
+        frame_system::CheckSpecVersion::<Runtime>::new(),
+        frame_system::CheckTxVersion::<Runtime>::new(),
+        frame_system::CheckGenesis::<Runtime>::new(),
+        frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+        frame_system::CheckNonce::<Runtime>::from(nonce),
+        frame_system::CheckWeight::<Runtime>::new(),
+        pallet_rate_limit::CheckRateLimit::<Runtime>::new(),
+        pallet_device_sessions::CheckSessionKey::<Runtime>::new(),
+        pallet_allocations::CheckOracle::<Runtime>::new(),
+        ChargeFeelessOrPayment::<Runtime>::from(ChargeTransactionPayment::from(0)),
+    )
+}
+
+fn allocation(nonce: Index) -> CheckedExtrinsic {
+    CheckedExtrinsic {
+        signed: Some((oracle(), extra(nonce))),
+        function: Call::Allocations(pallet_allocations::Call::allocate_batch(
+            vec![(AccountKeyring::Ferdie.to_account_id(), NODL)],
+            Vec::new(),
+        )),
+    }
+}
+
+fn nonce_tag(who: AccountId, nonce: Index) -> Vec<u8> {
+    (who, nonce).encode()
+}
+
+fn validate(
+    xt: CheckedExtrinsic,
+) -> Result<(Vec<Vec<u8>>, TransactionLongevity), TransactionValidityError> {
+    let info = xt.function.get_dispatch_info();
+    let len = xt.function.encoded_size();
+    xt.validate::<Runtime>(TransactionSource::External, &info, len)
+        .map(|valid| (valid.requires, valid.longevity))
+}
+
+fn apply(xt: CheckedExtrinsic) -> Result<(), TransactionValidityError> {
+    let info = xt.function.get_dispatch_info();
+    let len = xt.function.encoded_size();
+    xt.apply::<Runtime>(&info, len).map(|outcome| {
+        assert_ok!(outcome);
+    })
+}
+
+#[test]
+fn bursts_of_future_nonces_are_kept_in_the_pool() {
+    new_test_ext(1_000 * NODL).execute_with(|| {
+        let burst = 50;
+        assert_eq!(
+            validate(allocation(0)),
+            Ok((vec![], TransactionLongevity::max_value()))
+        );
+
+        // Every transaction of the burst waits for its predecessor and never expires
+        // from the pool, however far ahead of the account nonce it is.
+        for nonce in 1..burst {
+            assert_eq!(
+                validate(allocation(nonce)),
+                Ok((
+                    vec![nonce_tag(oracle(), nonce - 1)],
+                    TransactionLongevity::max_value()
+                ))
+            );
+        }
+    })
+}
+
+#[test]
+fn future_nonces_are_requeued_until_their_predecessors_are_included() {
+    new_test_ext(1_000 * NODL).execute_with(|| {
+        let burst = 10;
+
+        // Later transactions can not be included before the earlier ones, they are
+        // reported as future so that the pool keeps them around
+        for nonce in 1..burst {
+            assert_eq!(
+                apply(allocation(nonce)),
+                Err(InvalidTransaction::Future.into())
+            );
+        }
+
+        for nonce in 0..burst {
+            assert_ok!(apply(allocation(nonce)));
+        }
+        assert_eq!(System::account_nonce(oracle()), burst);
+        assert_eq!(Allocations::coins_consumed(), burst as Balance * NODL);
+    })
+}
+
+#[test]
+fn included_nonces_are_rejected_as_stale() {
+    new_test_ext(1_000 * NODL).execute_with(|| {
+        assert_ok!(apply(allocation(0)));
+        assert_ok!(apply(allocation(1)));
+
+        for nonce in 0..2 {
+            assert_eq!(
+                validate(allocation(nonce)),
+                Err(InvalidTransaction::Stale.into())
+            );
+            assert_eq!(
+                apply(allocation(nonce)),
+                Err(InvalidTransaction::Stale.into())
+            );
+        }
+    })
+}
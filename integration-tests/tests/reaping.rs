@@ -0,0 +1,101 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::{Currency, ExistenceRequirement, Get, WithdrawReasons},
+};
+use integration_tests::*;
+use nodle_chain_primitives::{AccountId, Balance};
+use pallet_grants::VestingSchedule;
+use sp_keyring::AccountKeyring;
+
+/// An account holding nothing but what it is granted.
+fn newcomer() -> AccountId {
+    AccountId::from([42u8; 32])
+}
+
+fn grant(to: AccountId, per_period: Balance) -> sp_runtime::DispatchResult {
+    let schedule = VestingSchedule {
+        start: 1,
+        period: constants::DAYS,
+        period_count: 10,
+        per_period,
+    };
+    Grants::add_vesting_schedule(
+        Origin::signed(AccountKeyring::Eve.to_account_id()),
+        to.into(),
+        schedule,
+    )
+}
+
+#[test]
+fn accounts_holding_unvested_grants_are_not_reaped() {
+    new_test_ext(0).execute_with(|| {
+        let ed = ExistentialDeposit::get();
+        let grantee = newcomer();
+        assert_ok!(grant(grantee.clone(), ed / 2));
+        assert_eq!(Balances::free_balance(&grantee), 5 * ed);
+
+        assert_noop!(
+            Balances::transfer(
+                Origin::signed(grantee.clone()),
+                AccountKeyring::Eve.to_account_id().into(),
+                5 * ed
+            ),
+            pallet_balances::Error::<Runtime, pallet_balances::DefaultInstance>::LiquidityRestrictions
+        );
+
+        // Half of the existential deposit is left to vest, it is locked all the same
+        System::set_block_number(1 + 9 * constants::DAYS);
+        assert_ok!(Grants::claim(Origin::signed(grantee.clone())));
+        assert_noop!(
+            Balances::withdraw(
+                &grantee,
+                5 * ed,
+                WithdrawReasons::all(),
+                ExistenceRequirement::AllowDeath
+            ),
+            pallet_balances::Error::<Runtime, pallet_balances::DefaultInstance>::LiquidityRestrictions
+        );
+        assert_ok!(Balances::withdraw(
+            &grantee,
+            4 * ed,
+            WithdrawReasons::all(),
+            ExistenceRequirement::AllowDeath
+        ));
+        assert_eq!(Balances::free_balance(&grantee), ed);
+        assert!(System::account_exists(&grantee));
+    })
+}
+
+#[test]
+fn payouts_below_the_existential_deposit_are_refused() {
+    new_test_ext(1_000 * constants::NODL).execute_with(|| {
+        let ed = ExistentialDeposit::get();
+        assert_noop!(
+            grant(newcomer(), 0),
+            pallet_grants::Error::<Runtime>::AmountBelowExistentialDeposit
+        );
+        assert_noop!(
+            Allocations::allocate(Origin::signed(oracle()), newcomer(), ed - 1, Vec::new()),
+            pallet_allocations::Error::<Runtime>::DoesNotSatisfyExistentialDeposit
+        );
+        assert!(!System::account_exists(&newcomer()));
+    })
+}
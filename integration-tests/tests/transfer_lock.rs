@@ -0,0 +1,158 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use frame_support::assert_ok;
+use integration_tests::*;
+use nodle_chain_primitives::{AccountId, Balance, BlockNumber};
+use pallet_collective::Instance3 as Financial;
+use pallet_grants::VestingSchedule;
+use pallet_parameters::ParameterValue;
+use sp_keyring::AccountKeyring;
+use sp_runtime::{traits::Dispatchable, DispatchError};
+
+const NODL: Balance = constants::NODL;
+
+fn set_transfers_enabled(enabled: bool) {
+    pass_motion::<Financial>(Call::Parameters(pallet_parameters::Call::set(
+        ParameterValue::TransfersEnabled(enabled),
+    )));
+    assert_eq!(Parameters::transfers_enabled(), enabled);
+}
+
+fn eve() -> AccountId {
+    AccountKeyring::Eve.to_account_id()
+}
+
+fn schedule() -> VestingSchedule<BlockNumber, Balance> {
+    VestingSchedule {
+        start: 1,
+        period: constants::DAYS,
+        period_count: 10,
+        per_period: NODL,
+    }
+}
+
+/// Test externalities in which Alice owns the vesting position 0.
+fn new_test_ext_with_position() -> sp_io::TestExternalities {
+    let mut ext = new_test_ext(0);
+    ext.execute_with(|| {
+        assert_ok!(Grants::add_vesting_schedule(
+            Origin::signed(eve()),
+            alice().into(),
+            schedule()
+        ));
+        assert_ok!(Grants::tokenize(Origin::signed(alice()), 0, NODL / 2));
+    });
+    ext
+}
+
+/// Calls moving value from Alice to Bob, which the transfer lock blocks. The position
+/// is split before being transferred.
+fn value_transfers() -> Vec<Call> {
+    vec![
+        Call::Balances(pallet_balances::Call::transfer(bob().into(), NODL)),
+        Call::Balances(pallet_balances::Call::transfer_keep_alive(
+            bob().into(),
+            NODL,
+        )),
+        Call::Grants(pallet_grants::Call::add_vesting_schedule(
+            bob().into(),
+            schedule(),
+        )),
+        Call::Grants(pallet_grants::Call::split_position(
+            0,
+            NODL / 4,
+            bob().into(),
+        )),
+        Call::Grants(pallet_grants::Call::transfer_position(0, bob().into())),
+    ]
+}
+
+#[test]
+fn locked_transfers_are_filtered() {
+    new_test_ext_with_position().execute_with(|| {
+        set_transfers_enabled(false);
+
+        for call in value_transfers() {
+            assert_eq!(
+                call.clone()
+                    .dispatch(Origin::signed(alice()))
+                    .map_err(|e| e.error),
+                Err(DispatchError::BadOrigin),
+                "{:?} went through the transfer lock",
+                call
+            );
+        }
+        assert_eq!(Grants::position(0).map(|p| p.owner), Some(alice()));
+        assert_eq!(Grants::position_count(), 1);
+    })
+}
+
+#[test]
+fn no_positions_are_created_under_the_lock() {
+    new_test_ext_with_position().execute_with(|| {
+        set_transfers_enabled(false);
+
+        assert_eq!(
+            Call::Grants(pallet_grants::Call::tokenize(0, NODL / 4))
+                .dispatch(Origin::signed(alice()))
+                .map_err(|e| e.error),
+            Err(DispatchError::BadOrigin)
+        );
+        assert_eq!(Grants::position_count(), 1);
+    })
+}
+
+#[test]
+fn locked_transfers_can_not_be_nested() {
+    new_test_ext_with_position().execute_with(|| {
+        set_transfers_enabled(false);
+
+        // Batched calls go through the same filter
+        for call in value_transfers() {
+            assert_ok!(Utility::batch(Origin::signed(alice()), vec![call]));
+        }
+        assert_eq!(Grants::position(0).map(|p| p.owner), Some(alice()));
+        assert_eq!(Grants::position_count(), 1);
+        assert_eq!(Balances::free_balance(&bob()), ENDOWMENT);
+    })
+}
+
+#[test]
+fn transfers_go_through_once_enabled() {
+    new_test_ext_with_position().execute_with(|| {
+        set_transfers_enabled(false);
+        set_transfers_enabled(true);
+
+        for call in value_transfers() {
+            assert_ok!(call.dispatch(Origin::signed(alice())));
+        }
+        assert_eq!(Grants::position(0).map(|p| p.owner), Some(bob()));
+    })
+}
+
+#[test]
+fn claims_work_while_transfers_are_locked() {
+    new_test_ext_with_position().execute_with(|| {
+        set_transfers_enabled(false);
+        System::set_block_number(1 + constants::DAYS);
+
+        assert_ok!(Grants::claim(Origin::signed(alice())));
+        assert_ok!(Grants::claim_position(Origin::signed(alice()), 0));
+    })
+}
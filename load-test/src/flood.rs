@@ -0,0 +1,186 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Allocation traffic: batches of allocations signed with an oracle key and submitted
+//! at a steady rate, nonces being tracked locally so that the pool is never starved.
+
+use crate::rpc;
+use chain_merkle as merkle;
+use nodle_chain_primitives::{AccountId, Balance, Hash, Index};
+use nodle_chain_runtime::{Call, Runtime, SignedExtra, SignedPayload, UncheckedExtrinsic};
+use parity_scale_codec::Encode;
+use sp_core::{blake2_256, sr25519, Bytes, Pair};
+use sp_runtime::{generic::Era, MultiSignature};
+use sp_version::RuntimeVersion;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Shape of the traffic.
+pub struct Traffic {
+    /// Allocations per transaction
+    pub batch_size: usize,
+    /// Transactions submitted per second
+    pub rate: f64,
+    pub duration: Duration,
+    /// Number of distinct grantees allocations cycle through
+    pub grantees: u32,
+    pub amount: Balance,
+}
+
+/// What was sent to the node.
+#[derive(Debug, Default)]
+pub struct Submitted {
+    pub accepted: u64,
+    pub rejected: u64,
+}
+
+fn grantee(index: u32) -> AccountId {
+    blake2_256(&index.encode()).into()
+}
+
+pub struct Flooder {
+    client: rpc::Client,
+    oracle: sr25519::Pair,
+    account: AccountId,
+    version: RuntimeVersion,
+    genesis: Hash,
+}
+
+impl Flooder {
+    pub fn new(client: rpc::Client, oracle: sr25519::Pair) -> Result<Self, rpc::Error> {
+        let version = client.call("state_getRuntimeVersion", Vec::<()>::new())?;
+        let genesis = client.call("chain_getBlockHash", (0,))?;
+        Ok(Self {
+            account: oracle.public().into(),
+            client,
+            oracle,
+            version,
+            genesis,
+        })
+    }
+
+    pub fn account(&self) -> &AccountId {
+        &self.account
+    }
+
+    /// Submit `traffic`, returning once it is over.
+    pub fn run(&self, traffic: &Traffic) -> Result<Submitted, rpc::Error> {
+        let mut nonce: Index = self
+            .client
+            .call("system_accountNextIndex", (&self.account,))?;
+        let mut submitted = Submitted::default();
+        let mut next_grantee = 0;
+        let interval = Duration::from_secs_f64(1.0 / traffic.rate.max(0.001));
+        let start = Instant::now();
+
+        while start.elapsed() < traffic.duration {
+            let batch = (0..traffic.batch_size.max(1))
+                .map(|_| {
+                    next_grantee = (next_grantee + 1) % traffic.grantees.max(1);
+                    (grantee(next_grantee), traffic.amount)
+                })
+                .collect::<Vec<_>>();
+
+            let xt = self.sign(&batch, nonce);
+            match self
+                .client
+                .call::<_, Hash>("author_submitExtrinsic", (Bytes(xt.encode()),))
+            {
+                Ok(_) => {
+                    submitted.accepted += 1;
+                    nonce += 1;
+                }
+                // A full pool is what we are looking for, keep going
+                Err(e) => {
+                    log::debug!("Submission rejected: {}", e);
+                    submitted.rejected += 1;
+                }
+            }
+
+            let next = interval * (submitted.accepted + submitted.rejected) as u32;
+            if let Some(wait) = next.checked_sub(start.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+
+        Ok(submitted)
+    }
+
+    fn sign(&self, batch: &[(AccountId, Balance)], nonce: Index) -> UncheckedExtrinsic {
+        let leaves = batch
+            .iter()
+            .map(|(to, amount)| merkle::leaf(to, *amount))
+            .collect::<Vec<_>>();
+        let root = merkle::root(&leaves).unwrap_or_default();
+        let mut calls = batch
+            .iter()
+            .map(|(to, amount)| {
+                Call::Allocations(pallet_allocations::Call::allocate(
+                    to.clone(),
+                    *amount,
+                    root.as_bytes().to_vec(),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let call = match calls.len() {
+            1 => calls.remove(0),
+            _ => Call::Utility(pallet_utility::Call::batch(calls)),
+        };
+
+        let extra: SignedExtra = (
+            frame_system::CheckSpecVersion::<Runtime>::new(),
+            frame_system::CheckTxVersion::<Runtime>::new(),
+            frame_system::CheckGenesis::<Runtime>::new(),
+            frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+            frame_system::CheckNonce::<Runtime>::from(nonce),
+            frame_system::CheckWeight::<Runtime>::new(),
+            pallet_rate_limit::CheckRateLimit::<Runtime>::new(),
+            pallet_device_sessions::CheckSessionKey::<Runtime>::new(),
+            pallet_allocations::CheckOracle::<Runtime>::new(),
+            pallet_feeless::ChargeFeelessOrPayment::<Runtime>::from(
+                pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+            ),
+        );
+        let payload = SignedPayload::from_raw(
+            call,
+            extra,
+            (
+                self.version.spec_version,
+                self.version.transaction_version,
+                self.genesis,
+                self.genesis,
+                (),
+                (),
+                (),
+                (),
+                (),
+            ),
+        );
+        let signature = payload.using_encoded(|payload| self.oracle.sign(payload));
+
+        let (call, extra, _) = payload.deconstruct();
+        UncheckedExtrinsic::new_signed(
+            call,
+            self.account.clone().into(),
+            MultiSignature::from(signature),
+            extra,
+        )
+    }
+}
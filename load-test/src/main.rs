@@ -0,0 +1,124 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Load test: floods a development network with batches of allocations and reports
+//! the throughput and block fullness it reached, which tells how large batches and
+//! blocks can be. The signing key must be an oracle's, Alice's on `--dev` chains.
+
+mod flood;
+mod report;
+mod rpc;
+
+use flood::{Flooder, Traffic};
+use nodle_chain_primitives::Balance;
+use sp_core::{sr25519, Pair};
+use std::{thread, time::Duration};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "load-test")]
+struct Opt {
+    /// JSON-RPC endpoint of the node.
+    #[structopt(long, default_value = "http://localhost:9933")]
+    node_url: String,
+
+    /// Secret URI of the oracle key signing the allocations.
+    #[structopt(long, default_value = "//Alice")]
+    oracle_seed: String,
+
+    /// Allocations per transaction, batched with the utility pallet.
+    #[structopt(long, default_value = "100")]
+    batch_size: usize,
+
+    /// Transactions submitted per second.
+    #[structopt(long, default_value = "10")]
+    rate: f64,
+
+    /// Seconds to submit transactions for.
+    #[structopt(long, default_value = "60")]
+    duration: u64,
+
+    /// Number of distinct grantees, allocations to new accounts weigh more.
+    #[structopt(long, default_value = "1000")]
+    grantees: u32,
+
+    /// Amount of every allocation, 1 NODL by default.
+    #[structopt(long, default_value = "1000000000000")]
+    amount: Balance,
+
+    /// Seconds to wait for the transaction pool to drain once the traffic stopped.
+    #[structopt(long, default_value = "60")]
+    drain_timeout: u64,
+}
+
+fn main() -> Result<(), String> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let opt = Opt::from_args();
+
+    let oracle = sr25519::Pair::from_string(&opt.oracle_seed, None)
+        .map_err(|e| format!("Invalid oracle seed: {:?}", e))?;
+    let client = rpc::Client::new(&opt.node_url);
+    let flooder = Flooder::new(rpc::Client::new(&opt.node_url), oracle)
+        .map_err(|e| format!("Failed to reach the node: {}", e))?;
+    let first = report::best_number(&client).map_err(|e| e.to_string())? + 1;
+
+    log::info!(
+        "Submitting {} transactions per second of {} allocations for {}s",
+        opt.rate,
+        opt.batch_size,
+        opt.duration
+    );
+    let submitted = flooder
+        .run(&Traffic {
+            batch_size: opt.batch_size,
+            rate: opt.rate,
+            duration: Duration::from_secs(opt.duration),
+            grantees: opt.grantees,
+            amount: opt.amount,
+        })
+        .map_err(|e| format!("Failed to submit the traffic: {}", e))?;
+    log::info!(
+        "{} transactions accepted by the pool, {} rejected",
+        submitted.accepted,
+        submitted.rejected
+    );
+
+    for _ in 0..opt.drain_timeout {
+        let pending: Vec<sp_core::Bytes> = client
+            .call("author_pendingExtrinsics", Vec::<()>::new())
+            .map_err(|e| e.to_string())?;
+        if pending.is_empty() {
+            break;
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    let last = report::best_number(&client).map_err(|e| e.to_string())?;
+    let blocks = (first..=last)
+        .map(|number| report::block_stats(&client, flooder.account(), number))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read the blocks back: {}", e))?;
+    // Blocks produced after the pool drained would lower the averages
+    let produced = blocks
+        .iter()
+        .rposition(|block| block.transactions > 0)
+        .map_or(0, |last| last + 1);
+
+    print!("{}", report::Report::new(&blocks[..produced]));
+    Ok(())
+}
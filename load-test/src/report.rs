@@ -0,0 +1,251 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Throughput of the blocks produced during a load test, read back from the node.
+
+use crate::rpc;
+use nodle_chain_primitives::{AccountId, BlockNumber, Hash, Moment};
+use nodle_chain_runtime::{
+    Address, Call, MaximumBlockLength, MaximumBlockWeight, UncheckedExtrinsic,
+};
+use parity_scale_codec::Decode;
+use serde::Deserialize;
+use sp_core::{storage::StorageKey, twox_128, Bytes};
+use sp_runtime::traits::Get;
+use std::fmt;
+
+#[derive(Deserialize)]
+struct Header {
+    #[serde(deserialize_with = "number")]
+    number: BlockNumber,
+}
+
+fn number<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<BlockNumber, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    BlockNumber::from_str_radix(hex.trim_start_matches("0x"), 16).map_err(serde::de::Error::custom)
+}
+
+#[derive(Deserialize)]
+struct Block {
+    extrinsics: Vec<Bytes>,
+}
+
+#[derive(Deserialize)]
+struct SignedBlock {
+    block: Block,
+}
+
+/// What a single block contained.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BlockStats {
+    pub timestamp: Moment,
+    pub transactions: u64,
+    pub allocations: u64,
+    /// Weight used by normal and operational extrinsics, allocations are operational
+    pub weight: (u64, u64),
+    pub length: u64,
+}
+
+pub fn best_number(client: &rpc::Client) -> Result<BlockNumber, rpc::Error> {
+    let header: Header = client.call("chain_getHeader", Vec::<()>::new())?;
+    Ok(header.number)
+}
+
+/// Allocation traffic sent by `oracle` in block `number`.
+pub fn block_stats(
+    client: &rpc::Client,
+    oracle: &AccountId,
+    number: BlockNumber,
+) -> Result<BlockStats, rpc::Error> {
+    let hash: Hash = client.call("chain_getBlockHash", (number,))?;
+    let block: SignedBlock = client.call("chain_getBlock", (hash,))?;
+    // `System::BlockWeight`, only reset when the next block is initialized
+    let key = StorageKey([twox_128(b"System"), twox_128(b"BlockWeight")].concat());
+    let weight: Option<Bytes> = client.call("state_getStorage", (key, hash))?;
+
+    let mut stats = BlockStats {
+        weight: weight
+            .and_then(|raw| Decode::decode(&mut &raw[..]).ok())
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+    for raw in block.block.extrinsics {
+        stats.length += raw.len() as u64;
+        let xt = UncheckedExtrinsic::decode(&mut &raw[..])
+            .map_err(|e| rpc::Error::Decode(e.to_string()))?;
+        match (&xt.signature, &xt.function) {
+            (None, Call::Timestamp(pallet_timestamp::Call::set(now))) => stats.timestamp = *now,
+            (Some((Address::Id(sender), ..)), call) if sender == oracle => {
+                stats.transactions += 1;
+                stats.allocations += match call {
+                    Call::Utility(pallet_utility::Call::batch(calls)) => calls.len() as u64,
+                    _ => 1,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Summary of a load test.
+#[derive(Debug, PartialEq)]
+pub struct Report {
+    pub blocks: usize,
+    pub transactions: u64,
+    pub allocations: u64,
+    /// Seconds between the first and the last block
+    pub seconds: f64,
+    /// Average and peak share of the maximum block weight used
+    pub fullness: (f64, f64),
+    /// Average and peak share of the maximum block length used
+    pub length: (f64, f64),
+    /// Operational weight consumed per allocation
+    pub weight_per_allocation: Option<u64>,
+}
+
+impl Report {
+    pub fn new(blocks: &[BlockStats]) -> Self {
+        let share = |value: u64, max: u64| value as f64 / max as f64;
+        let average_and_peak = |shares: Vec<f64>| {
+            let average = shares.iter().sum::<f64>() / shares.len().max(1) as f64;
+            let peak = shares.into_iter().fold(0.0, f64::max);
+            (average, peak)
+        };
+
+        let transactions = blocks.iter().map(|block| block.transactions).sum();
+        let allocations = blocks.iter().map(|block| block.allocations).sum::<u64>();
+        let operational_weight = blocks.iter().map(|block| block.weight.1).sum::<u64>();
+        let seconds = match (blocks.first(), blocks.last()) {
+            (Some(first), Some(last)) => {
+                last.timestamp.saturating_sub(first.timestamp) as f64 / 1_000.0
+            }
+            _ => 0.0,
+        };
+
+        Report {
+            blocks: blocks.len(),
+            transactions,
+            allocations,
+            seconds,
+            fullness: average_and_peak(
+                blocks
+                    .iter()
+                    .map(|block| share(block.weight.0 + block.weight.1, MaximumBlockWeight::get()))
+                    .collect(),
+            ),
+            length: average_and_peak(
+                blocks
+                    .iter()
+                    .map(|block| share(block.length, MaximumBlockLength::get().into()))
+                    .collect(),
+            ),
+            weight_per_allocation: operational_weight.checked_div(allocations),
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let per_second = |count: u64| count as f64 / self.seconds.max(f64::EPSILON);
+
+        writeln!(
+            f,
+            "Blocks                 {} over {:.1}s",
+            self.blocks, self.seconds
+        )?;
+        writeln!(
+            f,
+            "Transactions           {} ({:.1} tx/s)",
+            self.transactions,
+            per_second(self.transactions)
+        )?;
+        writeln!(
+            f,
+            "Allocations            {} ({:.1} allocations/s)",
+            self.allocations,
+            per_second(self.allocations)
+        )?;
+        writeln!(
+            f,
+            "Block weight           {:.1}% average, {:.1}% peak",
+            self.fullness.0 * 100.0,
+            self.fullness.1 * 100.0
+        )?;
+        writeln!(
+            f,
+            "Block length           {:.1}% average, {:.1}% peak",
+            self.length.0 * 100.0,
+            self.length.1 * 100.0
+        )?;
+        if let Some(weight) = self.weight_per_allocation {
+            writeln!(f, "Weight per allocation  {}", weight)?;
+            writeln!(
+                f,
+                "Allocations per block  {} at most",
+                MaximumBlockWeight::get() / weight.max(1)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_blocks() {
+        let max = MaximumBlockWeight::get();
+        let blocks = vec![
+            BlockStats {
+                timestamp: 6_000,
+                transactions: 2,
+                allocations: 200,
+                weight: (0, max / 2),
+                length: 0,
+            },
+            BlockStats {
+                timestamp: 12_000,
+                transactions: 1,
+                allocations: 100,
+                weight: (0, max / 4),
+                length: 0,
+            },
+        ];
+
+        let report = Report::new(&blocks);
+
+        assert_eq!(report.blocks, 2);
+        assert_eq!(report.transactions, 3);
+        assert_eq!(report.allocations, 300);
+        assert_eq!(report.seconds, 6.0);
+        assert_eq!(report.fullness, (0.375, 0.5));
+        assert_eq!(report.weight_per_allocation, Some(max * 3 / 4 / 300));
+    }
+
+    #[test]
+    fn empty_tests_report_nothing() {
+        let report = Report::new(&[]);
+
+        assert_eq!(report.blocks, 0);
+        assert_eq!(report.fullness, (0.0, 0.0));
+        assert_eq!(report.weight_per_allocation, None);
+    }
+}
@@ -6,4 +6,7 @@ fn main() {
     generate_cargo_keys(ConstantsFlags::SHA_SHORT).expect(ERROR_MSG);
 
     substrate_build_script_utils::rerun_if_git_head_changed();
+
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/nodle.proto").expect(ERROR_MSG);
 }
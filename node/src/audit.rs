@@ -0,0 +1,416 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! `export-allocations-state` and `verify-allocations-state` subcommands, dumping
+//! everything auditors need to reconcile allocations at a given block and checking
+//! such a dump against the chain later on.
+//!
+//! Dumps are canonical: oracles are sorted by account and allocations by position in
+//! the chain, so that exporting twice at the same block yields the same bytes and a
+//! dump can be verified by regenerating it.
+
+use crate::indexer::{block_records, IndexedEvent, Store};
+use nodle_chain_primitives::{AccountId, Balance, Block, BlockNumber, Hash};
+use pallet_allocations::OracleStatus;
+use pallet_allocations_rpc::OraclesRuntimeApi;
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{Backend, StorageProvider};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, Bytes};
+use sp_runtime::generic::BlockId;
+use std::{fs, path::PathBuf, str::FromStr};
+use structopt::StructOpt;
+
+const CSV_HEADER: &str = "kind,block_number,block_hash,event_index,account,amount,fee,proof\n";
+
+/// Formats dumps can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            other => Err(format!("unknown format {}, expected json or csv", other)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OracleEntry {
+    pub account: AccountId,
+    /// Total allocated by the oracle, protocol fees included
+    pub allocated: Balance,
+    pub last_seen: BlockNumber,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationEntry {
+    pub block_number: BlockNumber,
+    pub block_hash: Hash,
+    pub event_index: u32,
+    pub to: AccountId,
+    /// Amount received by the grantee, protocol fee excluded
+    pub amount: Balance,
+    /// Amount taken by the protocol
+    pub fee: Balance,
+    pub proof: Bytes,
+}
+
+/// State of the allocations at a given block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationsState {
+    pub block_number: BlockNumber,
+    pub block_hash: Hash,
+    /// Total oracles are allowed to allocate since genesis
+    pub budget: Balance,
+    /// Total allocated since genesis, protocol fees included
+    pub coins_consumed: Balance,
+    pub oracles: Vec<OracleEntry>,
+    /// Every allocation from genesis up to the block
+    pub allocations: Vec<AllocationEntry>,
+}
+
+impl AllocationsState {
+    /// Read the state at block `number` of the best chain. Allocations are read from
+    /// the index, falling back to the block state for blocks that were not indexed.
+    pub fn at<C, B>(client: &C, store: &Store, number: BlockNumber) -> Result<Self, String>
+    where
+        C: HeaderBackend<Block> + StorageProvider<Block, B> + ProvideRuntimeApi<Block>,
+        C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
+        B: Backend<Block>,
+    {
+        let hash_of = |number| {
+            client
+                .hash(number)
+                .map_err(|e| format!("{:?}", e))?
+                .ok_or_else(|| format!("Block {} is not part of the best chain", number))
+        };
+
+        let block_hash = hash_of(number)?;
+        let at = BlockId::hash(block_hash);
+        let api = client.runtime_api();
+        let (coins_consumed, budget) = api
+            .budget(&at)
+            .map_err(|e| format!("Unable to query the budget: {:?}", e))?;
+        let mut oracles = api
+            .oracles(&at)
+            .map_err(|e| format!("Unable to query the oracles: {:?}", e))?
+            .into_iter()
+            .map(|(account, status)| OracleEntry {
+                account,
+                allocated: status.allocated,
+                last_seen: status.last_seen,
+            })
+            .collect::<Vec<_>>();
+        oracles.sort_by(|a, b| a.account.cmp(&b.account));
+
+        let mut allocations = Vec::new();
+        for number in 1..=number {
+            let hash = hash_of(number)?;
+            let records = match store.at(hash).map_err(|e| e.to_string())? {
+                Some(records) => records,
+                None => block_records(client, hash).map_err(|e| {
+                    format!(
+                        "Block {} was not indexed and its state is unavailable: {:?}",
+                        number, e
+                    )
+                })?,
+            };
+
+            allocations.extend(records.into_iter().filter_map(|record| match record.event {
+                IndexedEvent::Allocation {
+                    to,
+                    amount,
+                    fee,
+                    proof,
+                } => Some(AllocationEntry {
+                    block_number: record.block_number,
+                    block_hash: record.block_hash,
+                    event_index: record.event_index,
+                    to,
+                    amount,
+                    fee,
+                    proof: Bytes(proof),
+                }),
+                _ => None,
+            }));
+        }
+        allocations.sort_by_key(|entry| (entry.block_number, entry.event_index));
+
+        Ok(Self {
+            block_number: number,
+            block_hash,
+            budget,
+            coins_consumed,
+            oracles,
+            allocations,
+        })
+    }
+
+    /// Render the state in `format`.
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Json => {
+                let mut json =
+                    serde_json::to_string_pretty(self).expect("the state can be serialized; qed");
+                json.push('\n');
+                json
+            }
+            Format::Csv => self.to_csv(),
+        }
+    }
+
+    /// One row per item, the `kind` column telling which columns are used.
+    fn to_csv(&self) -> String {
+        let mut csv = String::from(CSV_HEADER);
+        csv += &format!("block,{},{:?},,,,,\n", self.block_number, self.block_hash);
+        csv += &format!("budget,,,,,{},,\n", self.budget);
+        csv += &format!("coins_consumed,,,,,{},,\n", self.coins_consumed);
+        for oracle in &self.oracles {
+            csv += &format!(
+                "oracle,{},,,{},{},,\n",
+                oracle.last_seen,
+                oracle.account.to_ss58check(),
+                oracle.allocated
+            );
+        }
+        for allocation in &self.allocations {
+            csv += &format!(
+                "allocation,{},{:?},{},{},{},{},{}\n",
+                allocation.block_number,
+                allocation.block_hash,
+                allocation.event_index,
+                allocation.to.to_ss58check(),
+                allocation.amount,
+                allocation.fee,
+                sp_core::bytes::to_hex(&allocation.proof, false),
+            );
+        }
+        csv
+    }
+}
+
+/// Block number a dump was taken at, along with the dump's format.
+fn dump_block(dump: &str) -> Result<(BlockNumber, Format), String> {
+    if dump.trim_start().starts_with('{') {
+        let state: AllocationsState =
+            serde_json::from_str(dump).map_err(|e| format!("Invalid JSON dump: {}", e))?;
+        return Ok((state.block_number, Format::Json));
+    }
+
+    dump.lines()
+        .find_map(|line| line.strip_prefix("block,"))
+        .and_then(|row| row.split(',').next())
+        .and_then(|number| number.parse().ok())
+        .map(|number| (number, Format::Csv))
+        .ok_or_else(|| "The dump is neither JSON nor CSV with a block row".into())
+}
+
+/// Export the allocations state at a given block.
+#[derive(Debug, StructOpt)]
+pub struct ExportAllocationsStateCmd {
+    /// Block number to export the state at, the best block by default.
+    #[structopt(long, value_name = "BLOCK")]
+    pub at: Option<BlockNumber>,
+
+    /// Format of the dump: json or csv.
+    #[structopt(long, default_value = "json")]
+    pub format: Format,
+
+    /// File to write the dump to, standard output by default.
+    #[structopt(long, short, parse(from_os_str))]
+    pub output: Option<PathBuf>,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub shared_params: SharedParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub pruning_params: PruningParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub database_params: DatabaseParams,
+}
+
+impl ExportAllocationsStateCmd {
+    pub fn run<C, B>(&self, client: &C, store: &Store) -> sc_cli::Result<()>
+    where
+        C: HeaderBackend<Block> + StorageProvider<Block, B> + ProvideRuntimeApi<Block>,
+        C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
+        B: Backend<Block>,
+    {
+        let number = self.at.unwrap_or_else(|| client.info().best_number);
+        let dump = AllocationsState::at(client, store, number)?.render(self.format);
+
+        match &self.output {
+            Some(path) => fs::write(path, dump)?,
+            None => print!("{}", dump),
+        }
+        Ok(())
+    }
+}
+
+impl CliConfiguration for ExportAllocationsStateCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+
+    fn pruning_params(&self) -> Option<&PruningParams> {
+        Some(&self.pruning_params)
+    }
+
+    fn database_params(&self) -> Option<&DatabaseParams> {
+        Some(&self.database_params)
+    }
+}
+
+/// Check a dump made by `export-allocations-state` against the chain.
+#[derive(Debug, StructOpt)]
+pub struct VerifyAllocationsStateCmd {
+    /// Dump to verify, its format is detected from its content.
+    #[structopt(parse(from_os_str))]
+    pub input: PathBuf,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub shared_params: SharedParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub pruning_params: PruningParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub database_params: DatabaseParams,
+}
+
+impl VerifyAllocationsStateCmd {
+    pub fn run<C, B>(&self, client: &C, store: &Store) -> sc_cli::Result<()>
+    where
+        C: HeaderBackend<Block> + StorageProvider<Block, B> + ProvideRuntimeApi<Block>,
+        C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
+        B: Backend<Block>,
+    {
+        let dump = fs::read_to_string(&self.input)?;
+        let (number, format) = dump_block(&dump)?;
+        let expected = AllocationsState::at(client, store, number)?.render(format);
+
+        let mismatch = dump
+            .lines()
+            .zip(expected.lines())
+            .enumerate()
+            .find(|(_, (found, expected))| found != expected);
+        if let Some((line, (found, expected))) = mismatch {
+            return Err(format!(
+                "The dump does not match the chain at line {}:\n  dump:  {}\n  chain: {}",
+                line + 1,
+                found,
+                expected
+            )
+            .into());
+        }
+        if dump.lines().count() != expected.lines().count() {
+            return Err("The dump does not have as many lines as expected".into());
+        }
+
+        println!("The dump matches the chain state at block {}", number);
+        Ok(())
+    }
+}
+
+impl CliConfiguration for VerifyAllocationsStateCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+
+    fn pruning_params(&self) -> Option<&PruningParams> {
+        Some(&self.pruning_params)
+    }
+
+    fn database_params(&self) -> Option<&DatabaseParams> {
+        Some(&self.database_params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> AllocationsState {
+        AllocationsState {
+            block_number: 42,
+            block_hash: Hash::repeat_byte(1),
+            budget: 1_000,
+            coins_consumed: 100,
+            oracles: vec![OracleEntry {
+                account: AccountId::from([1; 32]),
+                allocated: 100,
+                last_seen: 40,
+            }],
+            allocations: vec![AllocationEntry {
+                block_number: 40,
+                block_hash: Hash::repeat_byte(2),
+                event_index: 3,
+                to: AccountId::from([2; 32]),
+                amount: 95,
+                fee: 5,
+                proof: Bytes(vec![0xaa]),
+            }],
+        }
+    }
+
+    #[test]
+    fn finds_the_block_of_dumps() {
+        let state = state();
+
+        assert_eq!(
+            dump_block(&state.render(Format::Json)),
+            Ok((42, Format::Json))
+        );
+        assert_eq!(
+            dump_block(&state.render(Format::Csv)),
+            Ok((42, Format::Csv))
+        );
+        assert!(dump_block("kind,block_number\n").is_err());
+    }
+
+    #[test]
+    fn json_dumps_round_trip() {
+        let state = state();
+        let dump = state.render(Format::Json);
+
+        assert_eq!(
+            serde_json::from_str::<AllocationsState>(&dump).unwrap(),
+            state
+        );
+    }
+}
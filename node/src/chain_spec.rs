@@ -17,12 +17,7 @@
  */
 
 use nodle_chain_primitives::{AccountId, Balance, BlockNumber, Signature};
-use nodle_chain_runtime::{
-    constants::*, AuthorityDiscoveryConfig, BabeConfig, BalancesConfig, FinancialMembershipConfig,
-    GenesisConfig, GrandpaConfig, GrantsConfig, ImOnlineConfig, IndicesConfig,
-    RootMembershipConfig, SessionConfig, SessionKeys, SystemConfig, TechnicalMembershipConfig,
-    ValidatorsSetConfig, WASM_BINARY,
-};
+use nodle_chain_runtime::{constants::*, EmissionConfig, GenesisConfig};
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
 use sc_service::ChainType;
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
@@ -33,20 +28,7 @@ use sp_runtime::traits::{IdentifyAccount, Verify};
 
 type AccountPublic = <Signature as Verify>::Signer;
 pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
-
-fn session_keys(
-    grandpa: GrandpaId,
-    babe: BabeId,
-    im_online: ImOnlineId,
-    authority_discovery: AuthorityDiscoveryId,
-) -> SessionKeys {
-    SessionKeys {
-        grandpa,
-        babe,
-        im_online,
-        authority_discovery,
-    }
-}
+pub type StagingChainSpec = sc_service::GenericChainSpec<nodle_staging_runtime::GenesisConfig>;
 
 /// Helper function to generate a crypto pair from seed
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
@@ -84,136 +66,170 @@ pub fn get_authority_keys_from_seed(
     )
 }
 
-/// Helper function to create GenesisConfig for testing
-pub fn testnet_genesis(
-    initial_authorities: Vec<(
-        AccountId,
-        AccountId,
-        GrandpaId,
-        BabeId,
-        ImOnlineId,
-        AuthorityDiscoveryId,
-    )>,
-    roots: Vec<AccountId>,
-    oracles: Vec<AccountId>,
-    endowed_accounts: Option<Vec<AccountId>>,
-    grants: Option<Vec<(AccountId, Vec<(BlockNumber, BlockNumber, u32, Balance)>)>>,
-) -> GenesisConfig {
-    let endowed_accounts: Vec<AccountId> = endowed_accounts.unwrap_or_else(|| {
-        vec![
-            get_account_id_from_seed::<sr25519::Public>("Alice"),
-            get_account_id_from_seed::<sr25519::Public>("Bob"),
-            get_account_id_from_seed::<sr25519::Public>("Charlie"),
-            get_account_id_from_seed::<sr25519::Public>("Dave"),
-            get_account_id_from_seed::<sr25519::Public>("Eve"),
-            get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
-            get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
-            get_account_id_from_seed::<sr25519::Public>("Charlie//stash"),
-            get_account_id_from_seed::<sr25519::Public>("Dave//stash"),
-            get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
-        ]
-    });
+/// All our runtimes share the same pallets and thus the same genesis layout, generate
+/// a helper function creating a testing `GenesisConfig` for the given runtime crate.
+macro_rules! impl_testnet_genesis {
+    ($name:ident, $runtime:ident) => {
+        /// Helper function to create GenesisConfig for testing
+        pub fn $name(
+            initial_authorities: Vec<(
+                AccountId,
+                AccountId,
+                GrandpaId,
+                BabeId,
+                ImOnlineId,
+                AuthorityDiscoveryId,
+            )>,
+            roots: Vec<AccountId>,
+            oracles: Vec<AccountId>,
+            endowed_accounts: Option<Vec<AccountId>>,
+            grants: Option<Vec<(AccountId, Vec<(BlockNumber, BlockNumber, u32, Balance)>)>>,
+        ) -> $runtime::GenesisConfig {
+            use $runtime::{
+                AllocationsOraclesConfig, AuthorityDiscoveryConfig, BabeConfig, BalancesConfig,
+                FinancialMembershipConfig, GenesisConfig, GrandpaConfig, GrantsConfig,
+                ImOnlineConfig, IndicesConfig, RootMembershipConfig, SessionConfig, SessionKeys,
+                SystemConfig, TechnicalMembershipConfig, ValidatorsSetConfig, WASM_BINARY,
+            };
 
-    let vested_grants = grants.unwrap_or_else(|| {
-        vec![(
-            // Ferdie has a network launch grant:
-            // 1. after 1000 blocks a cliff of 1000 NODL unlocks
-            // 2. for the next 100000 blocks a grant of 100 NODL unlocks every 1000 blocks
-            get_account_id_from_seed::<sr25519::Public>("Ferdie"),
-            vec![
-                (1_000, 1, 1, 1000 * NODL),      // Cliff
-                (2_000, 1_000, 100, 100 * NODL), // Vesting
-            ],
-        )]
-    });
+            let endowed_accounts: Vec<AccountId> = endowed_accounts.unwrap_or_else(|| {
+                vec![
+                    get_account_id_from_seed::<sr25519::Public>("Alice"),
+                    get_account_id_from_seed::<sr25519::Public>("Bob"),
+                    get_account_id_from_seed::<sr25519::Public>("Charlie"),
+                    get_account_id_from_seed::<sr25519::Public>("Dave"),
+                    get_account_id_from_seed::<sr25519::Public>("Eve"),
+                    get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
+                    get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
+                    get_account_id_from_seed::<sr25519::Public>("Charlie//stash"),
+                    get_account_id_from_seed::<sr25519::Public>("Dave//stash"),
+                    get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
+                ]
+            });
 
-    const ENDOWMENT: Balance = 100 * NODL;
-
-    GenesisConfig {
-        // Core
-        frame_system: Some(SystemConfig {
-            code: WASM_BINARY.to_vec(),
-            changes_trie_config: Default::default(),
-        }),
-        pallet_balances: Some(BalancesConfig {
-            balances: endowed_accounts
-                .iter()
-                .cloned()
-                .map(|k| (k, ENDOWMENT))
-                .chain(oracles.iter().map(|x| (x.clone(), ENDOWMENT)))
-                .chain(roots.iter().map(|x| (x.clone(), ENDOWMENT)))
-                .collect(),
-        }),
-        pallet_indices: Some(IndicesConfig { indices: vec![] }),
-        pallet_grants: Some(GrantsConfig {
-            vesting: vested_grants,
-        }),
-
-        // Consensus
-        pallet_session: Some(SessionConfig {
-            keys: initial_authorities
-                .iter()
-                .map(|x| {
-                    (
-                        x.0.clone(),
-                        x.0.clone(),
-                        session_keys(x.2.clone(), x.3.clone(), x.4.clone(), x.5.clone()),
-                    )
-                })
-                .collect::<Vec<_>>(),
-        }),
-        pallet_babe: Some(BabeConfig {
-            authorities: vec![],
-        }),
-        pallet_im_online: Some(ImOnlineConfig { keys: vec![] }),
-        pallet_authority_discovery: Some(AuthorityDiscoveryConfig { keys: vec![] }),
-        pallet_grandpa: Some(GrandpaConfig {
-            authorities: vec![],
-        }),
-        pallet_membership_Instance2: Some(ValidatorsSetConfig {
-            members: initial_authorities
-                .iter()
-                .map(|x| x.0.clone())
-                .collect::<Vec<_>>(),
-            phantom: Default::default(),
-        }),
-
-        // Governance
-        // Technical Committee
-        pallet_collective_Instance2: Some(Default::default()),
-        pallet_membership_Instance1: Some(TechnicalMembershipConfig {
-            members: roots.clone(),
-            phantom: Default::default(),
-        }),
-        // Financial Committee
-        pallet_collective_Instance3: Some(Default::default()),
-        pallet_membership_Instance3: Some(FinancialMembershipConfig {
-            members: roots.clone(),
-            phantom: Default::default(),
-        }),
-        pallet_reserve_Instance1: Some(Default::default()),
-        pallet_reserve_Instance2: Some(Default::default()),
-        pallet_reserve_Instance3: Some(Default::default()),
-        // Root Committee
-        pallet_collective_Instance4: Some(Default::default()),
-        pallet_membership_Instance4: Some(RootMembershipConfig {
-            members: roots.clone(),
-            phantom: Default::default(),
-        }),
-
-        // Allocations
-        pallet_membership_Instance5: Some(Default::default()),
-    }
+            let vested_grants = grants.unwrap_or_else(|| {
+                vec![(
+                    // Ferdie has a network launch grant:
+                    // 1. after 1000 blocks a cliff of 1000 NODL unlocks
+                    // 2. for the next 100000 blocks a grant of 100 NODL unlocks every 1000 blocks
+                    get_account_id_from_seed::<sr25519::Public>("Ferdie"),
+                    vec![
+                        (1_000, 1, 1, 1000 * NODL),      // Cliff
+                        (2_000, 1_000, 100, 100 * NODL), // Vesting
+                    ],
+                )]
+            });
+
+            const ENDOWMENT: Balance = 100 * NODL;
+
+            GenesisConfig {
+                // Core
+                frame_system: Some(SystemConfig {
+                    code: WASM_BINARY.to_vec(),
+                    changes_trie_config: Default::default(),
+                }),
+                pallet_balances: Some(BalancesConfig {
+                    balances: endowed_accounts
+                        .iter()
+                        .cloned()
+                        .map(|k| (k, ENDOWMENT))
+                        .chain(oracles.iter().map(|x| (x.clone(), ENDOWMENT)))
+                        .chain(roots.iter().map(|x| (x.clone(), ENDOWMENT)))
+                        .collect(),
+                }),
+                pallet_indices: Some(IndicesConfig { indices: vec![] }),
+                pallet_grants: Some(GrantsConfig {
+                    vesting: vested_grants,
+                }),
+                pallet_airdrop: Some(Default::default()),
+
+                // Consensus
+                pallet_session: Some(SessionConfig {
+                    keys: initial_authorities
+                        .iter()
+                        .map(|x| {
+                            (
+                                x.0.clone(),
+                                x.0.clone(),
+                                SessionKeys {
+                                    grandpa: x.2.clone(),
+                                    babe: x.3.clone(),
+                                    im_online: x.4.clone(),
+                                    authority_discovery: x.5.clone(),
+                                },
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                }),
+                pallet_babe: Some(BabeConfig {
+                    authorities: vec![],
+                }),
+                pallet_im_online: Some(ImOnlineConfig { keys: vec![] }),
+                pallet_authority_discovery: Some(AuthorityDiscoveryConfig { keys: vec![] }),
+                pallet_grandpa: Some(GrandpaConfig {
+                    authorities: vec![],
+                }),
+                pallet_membership_Instance2: Some(ValidatorsSetConfig {
+                    members: initial_authorities
+                        .iter()
+                        .map(|x| x.0.clone())
+                        .collect::<Vec<_>>(),
+                    phantom: Default::default(),
+                }),
+
+                // Governance
+                // Technical Committee
+                pallet_collective_Instance2: Some(Default::default()),
+                pallet_membership_Instance1: Some(TechnicalMembershipConfig {
+                    members: roots.clone(),
+                    phantom: Default::default(),
+                }),
+                // Financial Committee
+                pallet_collective_Instance3: Some(Default::default()),
+                pallet_membership_Instance3: Some(FinancialMembershipConfig {
+                    members: roots.clone(),
+                    phantom: Default::default(),
+                }),
+                pallet_reserve_Instance1: Some(Default::default()),
+                pallet_reserve_Instance2: Some(Default::default()),
+                pallet_reserve_Instance3: Some(Default::default()),
+                // Root Committee
+                pallet_collective_Instance4: Some(Default::default()),
+                pallet_membership_Instance4: Some(RootMembershipConfig {
+                    members: roots.clone(),
+                    phantom: Default::default(),
+                }),
+                pallet_democracy: Some(Default::default()),
+
+                // Allocations
+                pallet_emission: Some(Default::default()),
+                pallet_membership_Instance5: Some(AllocationsOraclesConfig {
+                    members: oracles,
+                    phantom: Default::default(),
+                }),
+            }
+        }
+    };
 }
 
+impl_testnet_genesis!(testnet_genesis, nodle_chain_runtime);
+impl_testnet_genesis!(staging_testnet_genesis, nodle_staging_runtime);
+
 fn development_config_genesis() -> GenesisConfig {
-    testnet_genesis(
+    let mut genesis = testnet_genesis(
         vec![get_authority_keys_from_seed("Alice")],
         vec![get_account_id_from_seed::<sr25519::Public>("Alice")],
-        vec![get_account_id_from_seed::<sr25519::Public>("Ferdie")],
+        vec![get_account_id_from_seed::<sr25519::Public>("Alice")],
         None,
         None,
-    )
+    );
+
+    // Let developers test reward flows without having to grow the budget first
+    genesis.pallet_emission = Some(EmissionConfig {
+        allocations_budget: 1_000_000_000 * NODL,
+    });
+
+    genesis
 }
 
 /// Development config (single validator Alice)
@@ -288,6 +304,43 @@ pub fn dummy_testnet_config() -> ChainSpec {
     )
 }
 
+fn staging_testnet_config_genesis() -> nodle_staging_runtime::GenesisConfig {
+    let faucet = get_account_id_from_seed::<sr25519::Public>("Faucet");
+    let mut genesis = staging_testnet_genesis(
+        vec![
+            get_authority_keys_from_seed("Alice"),
+            get_authority_keys_from_seed("Bob"),
+        ],
+        vec![get_account_id_from_seed::<sr25519::Public>("Alice")],
+        vec![get_account_id_from_seed::<sr25519::Public>("Ferdie")],
+        None,
+        None,
+    );
+
+    // Testnet tokens are worthless, give the faucet enough of them to never run dry
+    if let Some(balances) = genesis.pallet_balances.as_mut() {
+        balances.balances.push((faucet, 1_000_000_000 * NODL));
+    }
+
+    genesis
+}
+
+/// Staging testnet config, runs the staging runtime with shorter sessions and a
+/// well funded faucet account
+pub fn staging_testnet_config() -> StagingChainSpec {
+    StagingChainSpec::from_genesis(
+        "Staging Testnet",
+        "staging_testnet",
+        ChainType::Live,
+        staging_testnet_config_genesis,
+        vec![],
+        None,
+        None,
+        None,
+        Default::default(),
+    )
+}
+
 /// Arcadia config, from json chainspec
 pub fn arcadia_config() -> ChainSpec {
     ChainSpec::from_json_bytes(&include_bytes!("../res/arcadia.json")[..]).unwrap()
@@ -313,6 +366,11 @@ pub(crate) mod tests {
         local_testnet_config().build_storage().unwrap();
     }
 
+    #[test]
+    fn test_create_staging_testnet_chain_spec() {
+        staging_testnet_config().build_storage().unwrap();
+    }
+
     #[test]
     fn test_create_arcadia_chain_spec() {
         arcadia_config().build_storage().unwrap();
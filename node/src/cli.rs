@@ -16,7 +16,8 @@
  * along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use sc_cli::{KeySubcommand, RunCmd, SignCmd, VanityCmd, VerifyCmd};
+use crate::{key::KeyCmd, signer::RemoteKey};
+use sc_cli::{RunCmd, SignCmd, VanityCmd, VerifyCmd};
 use structopt::StructOpt;
 
 /// An overarching CLI command definition.
@@ -28,17 +29,59 @@ pub struct Cli {
     #[allow(missing_docs)]
     #[structopt(flatten)]
     pub run: RunCmd,
+    /// Seal a block as soon as a transaction is submitted instead of running BABE and
+    /// GRANDPA, only available with the development chain.
+    #[structopt(long)]
+    pub instant_seal: bool,
+    /// Keep every block but only the state of the last BLOCKS blocks, historical
+    /// events are then served from the node's event index. Meant for explorers.
+    #[structopt(long, value_name = "BLOCKS", conflicts_with = "pruning")]
+    pub archive_lite: Option<u32>,
+    /// Delegate signatures to the remote signer service at this URL for the keys
+    /// listed with `--remote-signer-key`.
+    #[structopt(long, value_name = "URL", requires = "remote-signer-key")]
+    pub remote_signer: Option<String>,
+    /// A key held by the remote signer, formatted as `TYPE:CRYPTO:PUBLIC`, for instance
+    /// `orcl:sr25519:0x...`. Only sr25519 and ed25519 keys are supported and BABE keys
+    /// have to stay in the local keystore.
+    #[structopt(long, value_name = "KEY", requires = "remote-signer")]
+    pub remote_signer_key: Vec<RemoteKey>,
+    /// Append the allocations of finalized blocks to CSV files in this directory,
+    /// meant as an accounting feed.
+    #[structopt(long, value_name = "DIR", parse(from_os_str))]
+    pub allocations_export: Option<std::path::PathBuf>,
+    /// Number of blocks covered by every file of the allocations export, a day worth
+    /// of blocks by default.
+    #[structopt(long, value_name = "BLOCKS", default_value = "14400")]
+    pub allocations_export_rotation: u32,
+    /// POST the allocations of finalized blocks to this URL, may be repeated.
+    #[structopt(long, value_name = "URL")]
+    pub allocation_webhook: Vec<String>,
+    /// Sign the webhook payloads with HMAC-SHA256 keyed with this secret.
+    #[structopt(long, value_name = "SECRET")]
+    pub allocation_webhook_secret: Option<String>,
+    /// Number of times a failed webhook delivery is retried before being dropped.
+    #[structopt(long, value_name = "COUNT", default_value = "5")]
+    pub allocation_webhook_retries: u32,
+    /// Serve the gRPC gateway on this port of the local interface.
+    #[cfg(feature = "grpc")]
+    #[structopt(long, value_name = "PORT")]
+    pub grpc_port: Option<u16>,
+    /// Listen to gRPC requests on all interfaces instead of the local one only.
+    #[cfg(feature = "grpc")]
+    #[structopt(long)]
+    pub grpc_external: bool,
 }
 
 /// Possible subcommands of the main binary.
 #[derive(Debug, StructOpt)]
 pub enum Subcommand {
     /// Key management cli utilities
-    Key(KeySubcommand),
+    Key(KeyCmd),
 
-    /// The custom benchmark subcommmand benchmarking runtime pallets.
-    #[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
-    Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+    /// Benchmarks used to produce the weights of the runtime.
+    #[structopt(name = "benchmark")]
+    Benchmark(BenchmarkCmd),
 
     /// Verify a signature for a message, provided on STDIN, with a given (public or secret) key.
     Verify(VerifyCmd),
@@ -52,6 +95,10 @@ pub enum Subcommand {
     /// Build a chain specification.
     BuildSpec(sc_cli::BuildSpecCmd),
 
+    /// Generate the chain specification of one of our networks.
+    #[structopt(name = "chain-spec")]
+    ChainSpec(crate::spec_builder::ChainSpecCmd),
+
     /// Build a chain specification with a light client sync state.
     BuildSyncSpec(sc_cli::BuildSyncSpecCmd),
 
@@ -72,4 +119,30 @@ pub enum Subcommand {
 
     /// Revert the chain to a previous state.
     Revert(sc_cli::RevertCmd),
+
+    /// Export allocations, budgets and oracles at a given block for audits.
+    ExportAllocationsState(crate::audit::ExportAllocationsStateCmd),
+
+    /// Verify a dump made by `export-allocations-state` against the chain.
+    VerifyAllocationsState(crate::audit::VerifyAllocationsStateCmd),
+
+    /// Apply a candidate runtime upgrade on top of the chain state and check its effects.
+    DryRunUpgrade(crate::upgrade::DryRunUpgradeCmd),
+
+    /// Write the specification of a local replica of a live chain.
+    ForkOff(crate::fork::ForkOffCmd),
+
+    /// Write the raw specification light clients join a network with.
+    LightSpec(crate::light_spec::LightSpecCmd),
+
+    /// Check a locally built runtime against the runtimes enacted on chain.
+    VerifyRuntime(crate::verify_runtime::VerifyRuntimeCmd),
+}
+
+/// Benchmarking subcommands.
+#[derive(Debug, StructOpt)]
+pub enum BenchmarkCmd {
+    /// Benchmark the extrinsics of a runtime pallet.
+    #[structopt(name = "pallet")]
+    Pallet(frame_benchmarking_cli::BenchmarkCmd),
 }
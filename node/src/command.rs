@@ -18,13 +18,15 @@
 
 use crate::{
     chain_spec,
-    cli::{Cli, Subcommand},
-    service::{self, new_full_base, new_partial, NewFullBase},
+    cli::{BenchmarkCmd, Cli, Subcommand},
+    export,
+    service::{self, new_full_base, new_partial, FullBackend, NewFullBase},
+    signer, webhooks,
 };
 use nodle_chain_executor::Executor;
 use nodle_chain_primitives::Block;
 use sc_cli::{ChainSpec, Result, Role, RuntimeVersion, SubstrateCli};
-use sc_service::PartialComponents;
+use sc_service::{config::PruningMode, PartialComponents};
 
 impl SubstrateCli for Cli {
     fn impl_name() -> String {
@@ -58,6 +60,7 @@ impl SubstrateCli for Cli {
             // Dummy chain is a chain wiht no accounts and only alice as an authority. Useful for forks
             "dummy" => Box::new(chain_spec::dummy_testnet_config()),
             "main" => Box::new(chain_spec::main_config()),
+            "staging" => Box::new(chain_spec::staging_testnet_config()),
             "" | "arcadia" => Box::new(chain_spec::arcadia_config()),
             path => Box::new(chain_spec::ChainSpec::from_json_file(
                 std::path::PathBuf::from(path),
@@ -77,12 +80,90 @@ pub fn run() -> Result<()> {
     match &cli.subcommand {
         None => {
             let runner = cli.create_runner(&cli.run)?;
-            runner.run_node_until_exit(|config| match config.role {
-                Role::Light => service::new_light(config),
-                _ => service::new_full(config),
+            let remote_signer = cli.remote_signer.clone().map(|url| signer::Config {
+                url,
+                keys: cli.remote_signer_key.clone(),
+            });
+            if cli.instant_seal {
+                return runner.run_node_until_exit(|config| {
+                    if config.chain_spec.id() != "dev" {
+                        return Err("Instant seal is only available with `--dev`.".into());
+                    }
+                    service::new_dev(config, remote_signer)
+                });
+            }
+
+            let archive_lite = cli.archive_lite;
+            let export = cli.allocations_export.clone().map(|dir| export::Config {
+                dir,
+                rotation: cli.allocations_export_rotation,
+            });
+            let webhooks = if cli.allocation_webhook.is_empty() {
+                None
+            } else {
+                Some(webhooks::Config {
+                    urls: cli.allocation_webhook.clone(),
+                    secret: cli.allocation_webhook_secret.clone(),
+                    retries: cli.allocation_webhook_retries,
+                })
+            };
+            #[cfg(feature = "grpc")]
+            let grpc_addr = cli.grpc_port.map(|port| {
+                let interface = if cli.grpc_external {
+                    std::net::Ipv4Addr::UNSPECIFIED
+                } else {
+                    std::net::Ipv4Addr::LOCALHOST
+                };
+                std::net::SocketAddr::from((interface, port))
+            });
+            runner.run_node_until_exit(|mut config| {
+                if let Some(blocks) = archive_lite {
+                    // Block bodies are never pruned, only their state is
+                    config.pruning = PruningMode::keep_blocks(blocks);
+                }
+
+                match config.role {
+                    Role::Light => {
+                        if remote_signer.is_some() {
+                            return Err("Light clients have no keys to sign with.".into());
+                        }
+                        if export.is_some() || webhooks.is_some() {
+                            return Err("Light clients have no events to export.".into());
+                        }
+                        service::new_light(config)
+                    }
+                    _ => {
+                        let NewFullBase {
+                            task_manager,
+                            client,
+                            ..
+                        } = new_full_base(config, remote_signer, |_, _| ())?;
+                        if let Some(export) = export {
+                            task_manager.spawn_handle().spawn(
+                                "allocations-export",
+                                export::run::<_, FullBackend>(client.clone(), export),
+                            );
+                        }
+                        if let Some(webhooks) = webhooks {
+                            task_manager.spawn_handle().spawn(
+                                "allocation-webhooks",
+                                webhooks::run::<_, FullBackend>(client.clone(), webhooks),
+                            );
+                        }
+                        #[cfg(feature = "grpc")]
+                        {
+                            if let Some(grpc_addr) = grpc_addr {
+                                task_manager
+                                    .spawn_handle()
+                                    .spawn("grpc", crate::grpc::run(client, grpc_addr));
+                            }
+                        }
+                        Ok(task_manager)
+                    }
+                }
             })
         }
-        Some(Subcommand::Benchmark(cmd)) => {
+        Some(Subcommand::Benchmark(BenchmarkCmd::Pallet(cmd))) => {
             if cfg!(feature = "runtime-benchmarks") {
                 let runner = cli.create_runner(cmd)?;
 
@@ -101,6 +182,9 @@ pub fn run() -> Result<()> {
             let runner = cli.create_runner(cmd)?;
             runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
         }
+        Some(Subcommand::ChainSpec(cmd)) => cmd.run(),
+        Some(Subcommand::ForkOff(cmd)) => cmd.run(),
+        Some(Subcommand::LightSpec(cmd)) => cmd.run(cli.load_spec(&cmd.chain)?),
         Some(Subcommand::BuildSyncSpec(cmd)) => {
             let runner = cli.create_runner(cmd)?;
             runner.async_run(|config| {
@@ -111,7 +195,7 @@ pub fn run() -> Result<()> {
                     client,
                     network_status_sinks,
                     ..
-                } = new_full_base(config, |_, _| ())?;
+                } = new_full_base(config, None, |_, _| ())?;
 
                 Ok((
                     cmd.run(chain_spec, network_config, client, network_status_sinks),
@@ -127,7 +211,7 @@ pub fn run() -> Result<()> {
                     task_manager,
                     import_queue,
                     ..
-                } = new_partial(&config)?;
+                } = new_partial(&config, None)?;
                 Ok((cmd.run(client, import_queue), task_manager))
             })
         }
@@ -138,7 +222,7 @@ pub fn run() -> Result<()> {
                     client,
                     task_manager,
                     ..
-                } = new_partial(&config)?;
+                } = new_partial(&config, None)?;
                 Ok((cmd.run(client, config.database), task_manager))
             })
         }
@@ -149,7 +233,7 @@ pub fn run() -> Result<()> {
                     client,
                     task_manager,
                     ..
-                } = new_partial(&config)?;
+                } = new_partial(&config, None)?;
                 Ok((cmd.run(client, config.chain_spec), task_manager))
             })
         }
@@ -161,7 +245,7 @@ pub fn run() -> Result<()> {
                     task_manager,
                     import_queue,
                     ..
-                } = new_partial(&config)?;
+                } = new_partial(&config, None)?;
                 Ok((cmd.run(client, import_queue), task_manager))
             })
         }
@@ -177,9 +261,63 @@ pub fn run() -> Result<()> {
                     task_manager,
                     backend,
                     ..
-                } = new_partial(&config)?;
+                } = new_partial(&config, None)?;
                 Ok((cmd.run(client, backend), task_manager))
             })
         }
+        Some(Subcommand::ExportAllocationsState(cmd)) => {
+            let runner = cli.create_runner(cmd)?;
+            runner.async_run(|config| {
+                let PartialComponents {
+                    client,
+                    task_manager,
+                    other: (_, _, (_, _, indexer, _)),
+                    ..
+                } = new_partial(&config, None)?;
+                let export = async move { cmd.run::<_, FullBackend>(&*client, &indexer) };
+                Ok((export, task_manager))
+            })
+        }
+        Some(Subcommand::VerifyAllocationsState(cmd)) => {
+            let runner = cli.create_runner(cmd)?;
+            runner.async_run(|config| {
+                let PartialComponents {
+                    client,
+                    task_manager,
+                    other: (_, _, (_, _, indexer, _)),
+                    ..
+                } = new_partial(&config, None)?;
+                let verify = async move { cmd.run::<_, FullBackend>(&*client, &indexer) };
+                Ok((verify, task_manager))
+            })
+        }
+        Some(Subcommand::DryRunUpgrade(cmd)) => {
+            let runner = cli.create_runner(cmd)?;
+            runner.async_run(|config| {
+                let PartialComponents {
+                    client,
+                    backend,
+                    task_manager,
+                    ..
+                } = new_partial(&config, None)?;
+                let spawn_handle = task_manager.spawn_handle();
+                let wasm_method = config.wasm_method;
+                let dry_run =
+                    async move { cmd.run(&*client, &*backend, wasm_method, spawn_handle) };
+                Ok((dry_run, task_manager))
+            })
+        }
+        Some(Subcommand::VerifyRuntime(cmd)) => {
+            let runner = cli.create_runner(cmd)?;
+            runner.async_run(|config| {
+                let PartialComponents {
+                    client,
+                    task_manager,
+                    ..
+                } = new_partial(&config, None)?;
+                let verify = async move { cmd.run::<_, FullBackend>(&*client) };
+                Ok((verify, task_manager))
+            })
+        }
     }
 }
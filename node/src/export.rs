@@ -0,0 +1,232 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Accounting feed: the allocations of every finalized block appended to CSV files,
+//! a new file being started every `rotation` blocks so that finance can pick up
+//! closed files without running an indexer of its own.
+//!
+//! Files are named after the first block they may contain, e.g.
+//! `allocations-0000014400.csv`, and the last exported block is kept in a `cursor`
+//! file so that the feed resumes where it stopped. Rows are written at least once, a
+//! crash between writing a block and moving the cursor repeats the block's rows;
+//! `block_number` and `event_index` identify a row.
+
+use crate::indexer::{block_records, IndexedEvent, IndexedRecord};
+use futures::prelude::*;
+use nodle_chain_primitives::{Block, BlockNumber};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::crypto::Ss58Codec;
+use sp_runtime::traits::Header as HeaderT;
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+
+const CSV_HEADER: &str = "block_number,block_hash,event_index,account,amount,fee,proof\n";
+const CURSOR: &str = "cursor";
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Directory the files are written to
+    pub dir: PathBuf,
+    /// Number of blocks covered by a file
+    pub rotation: BlockNumber,
+}
+
+struct Feed {
+    config: Config,
+}
+
+impl Feed {
+    fn open(config: Config) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        Ok(Self { config })
+    }
+
+    /// Last exported block, `None` if nothing was exported yet.
+    fn cursor(&self) -> io::Result<Option<BlockNumber>> {
+        match fs::read_to_string(self.config.dir.join(CURSOR)) {
+            Ok(cursor) => cursor
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn file(&self, number: BlockNumber) -> PathBuf {
+        let first = number - number % self.config.rotation.max(1);
+        self.config
+            .dir
+            .join(format!("allocations-{:010}.csv", first))
+    }
+
+    /// Append the allocations of block `number` and move the cursor past it.
+    fn append(&self, number: BlockNumber, records: &[IndexedRecord]) -> io::Result<()> {
+        let rows = records.iter().filter_map(row).collect::<String>();
+        if !rows.is_empty() {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.file(number))?;
+            if file.metadata()?.len() == 0 {
+                file.write_all(CSV_HEADER.as_bytes())?;
+            }
+            file.write_all(rows.as_bytes())?;
+            file.sync_data()?;
+        }
+
+        // Renames are atomic, the cursor is never left half written
+        let cursor = self.config.dir.join(CURSOR);
+        let pending = cursor.with_extension("tmp");
+        fs::write(&pending, number.to_string())?;
+        fs::rename(pending, cursor)
+    }
+}
+
+fn row(record: &IndexedRecord) -> Option<String> {
+    match &record.event {
+        IndexedEvent::Allocation {
+            to,
+            amount,
+            fee,
+            proof,
+        } => Some(format!(
+            "{},{:?},{},{},{},{},{}\n",
+            record.block_number,
+            record.block_hash,
+            record.event_index,
+            to.to_ss58check(),
+            amount,
+            fee,
+            sp_core::bytes::to_hex(proof, false),
+        )),
+        _ => None,
+    }
+}
+
+fn export_block<C, B>(client: &C, feed: &Feed, number: BlockNumber) -> Result<(), String>
+where
+    C: HeaderBackend<Block> + StorageProvider<Block, B>,
+    B: Backend<Block>,
+{
+    let hash = client
+        .hash(number)
+        .map_err(|e| format!("{:?}", e))?
+        .ok_or_else(|| format!("block {} is unknown", number))?;
+    let records = block_records(client, hash).map_err(|e| format!("{:?}", e))?;
+    feed.append(number, &records).map_err(|e| e.to_string())
+}
+
+/// Export the allocations of every finalized block, to be spawned as a background
+/// task of full nodes. A new feed starts at the first block finalized after launch.
+pub async fn run<C, B>(client: Arc<C>, config: Config)
+where
+    C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B>,
+    B: Backend<Block>,
+{
+    let feed = match Feed::open(config) {
+        Ok(feed) => feed,
+        Err(e) => {
+            log::error!(target: "export", "Failed to open the allocations export: {}", e);
+            return;
+        }
+    };
+    let mut exported = match feed.cursor() {
+        Ok(cursor) => cursor,
+        Err(e) => {
+            log::error!(target: "export", "Failed to read the export cursor: {}", e);
+            return;
+        }
+    };
+
+    let mut finality = client.finality_notification_stream();
+    while let Some(notification) = finality.next().await {
+        let finalized = *notification.header.number();
+        // Finality notifications may skip blocks, export everything in between
+        let next = exported.map_or(finalized, |exported| exported + 1);
+        for number in next..=finalized {
+            if let Err(e) = export_block(&*client, &feed, number) {
+                log::warn!(target: "export", "Failed to export block {}: {}", number, e);
+                break;
+            }
+            exported = Some(number);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nodle_chain_primitives::{AccountId, Hash};
+
+    fn feed(name: &str) -> Feed {
+        let dir = std::env::temp_dir().join(format!("export-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Feed::open(Config { dir, rotation: 10 }).unwrap()
+    }
+
+    fn allocation(block_number: BlockNumber, event_index: u32) -> IndexedRecord {
+        IndexedRecord {
+            block_number,
+            block_hash: Hash::repeat_byte(block_number as u8),
+            event_index,
+            event: IndexedEvent::Allocation {
+                to: AccountId::from([1; 32]),
+                amount: 95,
+                fee: 5,
+                proof: vec![0xaa],
+            },
+        }
+    }
+
+    #[test]
+    fn files_rotate_every_few_blocks() {
+        let feed = feed("rotation");
+        feed.append(3, &[allocation(3, 1), allocation(3, 2)])
+            .unwrap();
+        feed.append(9, &[allocation(9, 1)]).unwrap();
+        feed.append(10, &[allocation(10, 4)]).unwrap();
+
+        let first = fs::read_to_string(feed.file(0)).unwrap();
+        assert!(first.starts_with(CSV_HEADER));
+        assert_eq!(first.lines().count(), 4);
+        assert!(first.lines().nth(3).unwrap().starts_with("9,"));
+
+        let second = fs::read_to_string(feed.file(10)).unwrap();
+        assert_eq!(second.lines().count(), 2);
+        assert!(feed.file(10).ends_with("allocations-0000000010.csv"));
+        assert_eq!(feed.cursor().unwrap(), Some(10));
+    }
+
+    #[test]
+    fn blocks_without_allocations_only_move_the_cursor() {
+        let feed = feed("empty");
+        assert_eq!(feed.cursor().unwrap(), None);
+
+        feed.append(4, &[]).unwrap();
+
+        assert_eq!(feed.cursor().unwrap(), Some(4));
+        assert!(!feed.file(4).exists());
+    }
+}
@@ -0,0 +1,442 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! `fork-off` subcommand, writing the raw specification of a local replica of a live
+//! chain. The state of its pallets is scraped over RPC and put on top of the genesis
+//! of a new chain whose validators and committees are under our control, so that
+//! migrations and oracle scenarios can be rehearsed against realistic data.
+
+use crate::{
+    chain_spec::{
+        get_account_id_from_seed, get_authority_keys_from_seed, testnet_genesis, ChainSpec,
+    },
+    spec_builder::parse_account,
+    upgrade::{decoded, storage_key},
+};
+use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+use nodle_chain_primitives::{AccountId, Balance, Hash, Index};
+use nodle_chain_runtime::Runtime;
+use parity_scale_codec::{Decode, Encode};
+use sc_service::ChainType;
+use serde::{de::DeserializeOwned, Deserialize};
+use sp_core::{
+    bytes::{from_hex, to_hex},
+    sr25519,
+    storage::well_known_keys,
+    twox_128, Bytes,
+};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// Top level storage of a chain, as found in raw chain specifications.
+type Storage = BTreeMap<Vec<u8>, Vec<u8>>;
+
+type AccountInfo = frame_system::AccountInfo<Index, <Runtime as frame_system::Trait>::AccountData>;
+
+/// Pallets whose state always comes from the genesis of the fork: the consensus ones
+/// so that it is secured by its own validators and the governance ones so that its
+/// roots control it.
+const REKEYED: &[&str] = &[
+    "Timestamp",
+    "RandomnessCollectiveFlip",
+    "Babe",
+    "Grandpa",
+    "Authorship",
+    "ImOnline",
+    "Offences",
+    "PoaSessions",
+    "ValidatorsSet",
+    "Session",
+    "TechnicalCommittee",
+    "TechnicalMembership",
+    "FinancialCommittee",
+    "FinancialMembership",
+    "RootCommittee",
+    "RootMembership",
+];
+
+/// Pages of keys and batches of values fetched at once.
+const PAGE_SIZE: usize = 1_000;
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<serde_json::Value>,
+}
+
+struct Rpc {
+    url: String,
+    at: Option<Hash>,
+    client: reqwest::blocking::Client,
+}
+
+impl Rpc {
+    fn batch<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<Vec<T>, String> {
+        let requests = params
+            .into_iter()
+            .enumerate()
+            .map(|(id, params)| {
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": method,
+                    "params": params,
+                })
+            })
+            .collect::<Vec<_>>();
+        let count = requests.len();
+        let mut responses: Vec<(usize, RpcResponse<T>)> = self
+            .client
+            .post(&self.url)
+            .json(&requests)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json::<Vec<serde_json::Value>>())
+            .map_err(|e| format!("{} failed: {}", method, e))?
+            .into_iter()
+            .map(|response| {
+                let id = response["id"].as_u64().unwrap_or_default() as usize;
+                serde_json::from_value(response)
+                    .map(|response| (id, response))
+                    .map_err(|e| format!("{} returned unexpected data: {}", method, e))
+            })
+            .collect::<Result<_, _>>()?;
+        if responses.len() != count {
+            return Err(format!(
+                "{} answered {} of {} calls",
+                method,
+                responses.len(),
+                count
+            ));
+        }
+        // Batched responses may come in any order
+        responses.sort_by_key(|(id, _)| *id);
+        responses
+            .into_iter()
+            .map(|(_, response)| match (response.result, response.error) {
+                (Some(result), _) => Ok(result),
+                (None, error) => Err(format!("{} failed: {:?}", method, error)),
+            })
+            .collect()
+    }
+
+    fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, String> {
+        self.batch(method, vec![params])?
+            .pop()
+            .ok_or_else(|| format!("{} returned nothing", method))
+    }
+
+    fn metadata(&self) -> Result<RuntimeMetadataPrefixed, String> {
+        let raw: Bytes = self.call("state_getMetadata", serde_json::json!([self.at]))?;
+        RuntimeMetadataPrefixed::decode(&mut &raw[..])
+            .map_err(|e| format!("invalid metadata: {}", e))
+    }
+
+    /// Every key starting with `prefix` and its value.
+    fn storage(&self, prefix: &[u8], storage: &mut Storage) -> Result<(), String> {
+        let mut start: Option<Bytes> = None;
+        loop {
+            let keys: Vec<Bytes> = self.call(
+                "state_getKeysPaged",
+                serde_json::json!([Bytes(prefix.to_vec()), PAGE_SIZE, start, self.at]),
+            )?;
+            if keys.is_empty() {
+                return Ok(());
+            }
+
+            let values: Vec<Option<Bytes>> = self.batch(
+                "state_getStorage",
+                keys.iter()
+                    .map(|key| serde_json::json!([key, self.at]))
+                    .collect(),
+            )?;
+            for (key, value) in keys.iter().zip(values) {
+                if let Some(value) = value {
+                    storage.insert(key.to_vec(), value.0);
+                }
+            }
+
+            if keys.len() < PAGE_SIZE {
+                return Ok(());
+            }
+            start = keys.last().cloned();
+        }
+    }
+}
+
+/// Storage prefixes of the pallets of `metadata` to copy, keyed by pallet name.
+fn pallet_prefixes(
+    metadata: RuntimeMetadataPrefixed,
+    selected: &[String],
+    rekeyed: &[&str],
+) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let modules = match metadata.1 {
+        RuntimeMetadata::V11(metadata) => metadata.modules,
+        _ => return Err("unsupported metadata version".into()),
+    };
+
+    let mut prefixes = BTreeMap::new();
+    for module in decoded(&modules).into_iter().flatten() {
+        let name = decoded(&module.name).cloned().unwrap_or_default();
+        let prefix = match module.storage.as_ref().and_then(decoded) {
+            Some(storage) => decoded(&storage.prefix).cloned().unwrap_or_default(),
+            None => continue,
+        };
+        if rekeyed.contains(&name.as_str()) || !(selected.is_empty() || selected.contains(&name)) {
+            continue;
+        }
+
+        // Only accounts are worth copying from `System`, the rest describes blocks
+        let prefix = if name == "System" {
+            storage_key(&prefix, "Account")
+        } else {
+            twox_128(prefix.as_bytes()).to_vec()
+        };
+        prefixes.insert(name, prefix);
+    }
+
+    if let Some(unknown) = selected.iter().find(|name| !prefixes.contains_key(*name)) {
+        return Err(format!(
+            "{} is not a pallet with storage that can be copied",
+            unknown
+        ));
+    }
+    Ok(prefixes)
+}
+
+/// Put `scraped` on top of `genesis`. Accounts endowed at genesis, such as the roots,
+/// keep their balance and are accounted for in the total issuance.
+fn fork(mut genesis: Storage, scraped: Storage) -> Result<Storage, String> {
+    let accounts = storage_key("System", "Account");
+    let issuance = storage_key("Balances", "TotalIssuance");
+    if !scraped.contains_key(&issuance) {
+        return Ok(genesis.into_iter().chain(scraped).collect());
+    }
+
+    let mut total = Balance::decode(&mut &scraped[&issuance][..])
+        .map_err(|e| format!("invalid total issuance: {}", e))?;
+    for (key, value) in &genesis {
+        if key.starts_with(&accounts) && !scraped.contains_key(key) {
+            let account = AccountInfo::decode(&mut &value[..])
+                .map_err(|e| format!("invalid account: {}", e))?;
+            total = total
+                .saturating_add(account.data.free)
+                .saturating_add(account.data.reserved);
+        }
+    }
+
+    genesis.extend(scraped);
+    genesis.insert(issuance, total.encode());
+    Ok(genesis)
+}
+
+/// Write the raw specification of a local replica of a live chain.
+#[derive(Debug, StructOpt)]
+pub struct ForkOffCmd {
+    /// HTTP RPC endpoint of a node of the chain to fork.
+    #[structopt(long, default_value = "http://localhost:9933")]
+    pub rpc_url: String,
+
+    /// Hash of the block to fork from, the best block by default.
+    #[structopt(long)]
+    pub at: Option<Hash>,
+
+    /// Pallets to copy the state of, by their name in the runtime, all of them by
+    /// default. The state of consensus and governance pallets is never copied.
+    #[structopt(long = "pallet")]
+    pub pallets: Vec<String>,
+
+    /// Seeds the session keys of the validators of the fork are derived from, Alice by
+    /// default.
+    #[structopt(long = "validator-seed")]
+    pub validator_seeds: Vec<String>,
+
+    /// Members of the root, technical and financial committees of the fork, Alice by
+    /// default.
+    #[structopt(long = "root", parse(try_from_str = parse_account))]
+    pub roots: Vec<AccountId>,
+
+    /// Members of the allocations oracles set of the fork, the one of the forked chain
+    /// is kept if none is given.
+    #[structopt(long = "oracle", parse(try_from_str = parse_account))]
+    pub oracles: Vec<AccountId>,
+
+    /// Identifier of the fork, also used as the name of its data directory.
+    #[structopt(long, default_value = "fork")]
+    pub id: String,
+
+    /// Where to write the specification, standard output by default.
+    #[structopt(short, long, parse(from_os_str))]
+    pub output: Option<PathBuf>,
+}
+
+impl ForkOffCmd {
+    pub fn run(&self) -> sc_cli::Result<()> {
+        let spec = self.build()?;
+        match &self.output {
+            Some(path) => fs::write(path, spec)?,
+            None => println!("{}", spec),
+        }
+        Ok(())
+    }
+
+    fn build(&self) -> Result<String, String> {
+        let rpc = Rpc {
+            url: self.rpc_url.clone(),
+            at: self.at,
+            client: reqwest::blocking::Client::new(),
+        };
+        let mut rekeyed = REKEYED.to_vec();
+        if !self.oracles.is_empty() {
+            rekeyed.push("AllocationsOracles");
+        }
+        let prefixes = pallet_prefixes(rpc.metadata()?, &self.pallets, &rekeyed)?;
+
+        let mut scraped = Storage::new();
+        for (name, prefix) in &prefixes {
+            let before = scraped.len();
+            rpc.storage(prefix, &mut scraped)?;
+            log::info!("Copied {} keys of {}", scraped.len() - before, name);
+        }
+        // The fork runs the runtime of the forked chain
+        let code: Bytes = rpc.call(
+            "state_getStorage",
+            serde_json::json!([Bytes(well_known_keys::CODE.to_vec()), self.at]),
+        )?;
+        scraped.insert(well_known_keys::CODE.to_vec(), code.0);
+
+        let raw = self.genesis().as_json(true)?;
+        let mut spec: serde_json::Value =
+            serde_json::from_str(&raw).map_err(|e| format!("invalid genesis: {}", e))?;
+        let genesis = spec["genesis"]["raw"]["top"]
+            .as_object()
+            .ok_or("the genesis is not raw")?
+            .iter()
+            .map(|(key, value)| {
+                Ok((
+                    from_hex(key).map_err(|e| format!("{:?}", e))?,
+                    from_hex(value.as_str().unwrap_or_default()).map_err(|e| format!("{:?}", e))?,
+                ))
+            })
+            .collect::<Result<Storage, String>>()?;
+
+        spec["genesis"]["raw"]["top"] = fork(genesis, scraped)?
+            .into_iter()
+            .map(|(key, value)| (to_hex(&key, false), to_hex(&value, false).into()))
+            .collect::<serde_json::Map<_, _>>()
+            .into();
+        serde_json::to_string_pretty(&spec).map_err(|e| e.to_string())
+    }
+
+    fn genesis(&self) -> ChainSpec {
+        let seeds = if self.validator_seeds.is_empty() {
+            vec!["Alice".to_string()]
+        } else {
+            self.validator_seeds.clone()
+        };
+        let authorities = seeds
+            .iter()
+            .map(|seed| get_authority_keys_from_seed(seed))
+            .collect::<Vec<_>>();
+        let roots = if self.roots.is_empty() {
+            vec![get_account_id_from_seed::<sr25519::Public>("Alice")]
+        } else {
+            self.roots.clone()
+        };
+        let oracles = self.oracles.clone();
+
+        ChainSpec::from_genesis(
+            &format!("Fork of {}", self.rpc_url),
+            &self.id,
+            ChainType::Local,
+            move || {
+                testnet_genesis(
+                    authorities.clone(),
+                    roots.clone(),
+                    oracles.clone(),
+                    Some(vec![]),
+                    Some(vec![]),
+                )
+            },
+            vec![],
+            None,
+            None,
+            None,
+            Default::default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(free: Balance) -> Vec<u8> {
+        let mut account = AccountInfo::default();
+        account.data.free = free;
+        account.encode()
+    }
+
+    fn account_key(who: u8) -> Vec<u8> {
+        [storage_key("System", "Account"), vec![who; 48]].concat()
+    }
+
+    #[test]
+    fn scraped_state_overrides_the_genesis() {
+        let issuance = storage_key("Balances", "TotalIssuance");
+        let genesis = vec![
+            (account_key(1), account(10)),
+            (account_key(2), account(20)),
+            (issuance.clone(), 30u128.encode()),
+            (well_known_keys::CODE.to_vec(), vec![1]),
+        ]
+        .into_iter()
+        .collect();
+        let scraped = vec![
+            (account_key(2), account(200)),
+            (account_key(3), account(300)),
+            (issuance.clone(), 500u128.encode()),
+            (well_known_keys::CODE.to_vec(), vec![2]),
+        ]
+        .into_iter()
+        .collect();
+
+        let forked = fork(genesis, scraped).expect("valid state");
+
+        assert_eq!(forked[&account_key(1)], account(10));
+        assert_eq!(forked[&account_key(2)], account(200));
+        assert_eq!(forked[&account_key(3)], account(300));
+        assert_eq!(forked[&issuance], 510u128.encode());
+        assert_eq!(forked[well_known_keys::CODE], vec![2]);
+    }
+
+    #[test]
+    fn genesis_only_forks_keep_their_issuance() {
+        let genesis: Storage = vec![(account_key(1), account(10))].into_iter().collect();
+
+        assert_eq!(fork(genesis.clone(), Storage::new()), Ok(genesis));
+    }
+}
@@ -0,0 +1,171 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Optional gRPC gateway mirroring the allocations and root of trust runtime APIs,
+//! see `proto/nodle.proto`. Built with the `grpc` feature and started with
+//! `--grpc-port`.
+
+use nodle_chain_primitives::{AccountId, Balance, Block, BlockNumber, CertificateId, Hash};
+use pallet_allocations::OracleStatus;
+use pallet_allocations_rpc::OraclesRuntimeApi;
+use pallet_root_of_trust_rpc::RootOfTrustRuntimeApi;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::generic::BlockId;
+use std::{convert::TryFrom, net::SocketAddr, sync::Arc};
+use tonic::{transport::Server, Request, Response, Status};
+
+pub mod proto {
+    tonic::include_proto!("nodle");
+}
+
+use proto::{
+    allocations_server::{Allocations, AllocationsServer},
+    root_of_trust_server::{RootOfTrust, RootOfTrustServer},
+    AtBlock, BudgetReply, ChildCertificateRequest, Oracle, OracleList, RootCertificateRequest,
+    Validity,
+};
+
+fn account(bytes: &[u8]) -> Result<AccountId, Status> {
+    <[u8; 32]>::try_from(bytes)
+        .map(AccountId::from)
+        .map_err(|_| Status::invalid_argument("Accounts are 32 bytes long"))
+}
+
+fn runtime_error(e: impl std::fmt::Debug) -> Status {
+    Status::internal(format!("Runtime API error: {:?}", e))
+}
+
+pub struct Gateway<C> {
+    client: Arc<C>,
+}
+
+impl<C> Gateway<C>
+where
+    C: HeaderBackend<Block>,
+{
+    fn at(&self, at: Option<AtBlock>) -> Result<BlockId<Block>, Status> {
+        match at.map(|at| at.hash).unwrap_or_default() {
+            hash if hash.is_empty() => Ok(BlockId::hash(self.client.info().best_hash)),
+            hash if hash.len() == 32 => Ok(BlockId::hash(Hash::from_slice(&hash))),
+            _ => Err(Status::invalid_argument("Block hashes are 32 bytes long")),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl<C> Allocations for Gateway<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
+{
+    async fn list_oracles(
+        &self,
+        request: Request<AtBlock>,
+    ) -> Result<Response<OracleList>, Status> {
+        let at = self.at(Some(request.into_inner()))?;
+        let oracles = self
+            .client
+            .runtime_api()
+            .oracles(&at)
+            .map_err(runtime_error)?
+            .into_iter()
+            .map(|(account, status)| Oracle {
+                account: AsRef::<[u8]>::as_ref(&account).to_vec(),
+                allocated: status.allocated.to_string(),
+                last_seen: status.last_seen,
+            })
+            .collect();
+
+        Ok(Response::new(OracleList { oracles }))
+    }
+
+    async fn budget(&self, request: Request<AtBlock>) -> Result<Response<BudgetReply>, Status> {
+        let at = self.at(Some(request.into_inner()))?;
+        let (consumed, maximum) = self
+            .client
+            .runtime_api()
+            .budget(&at)
+            .map_err(runtime_error)?;
+
+        Ok(Response::new(BudgetReply {
+            consumed: consumed.to_string(),
+            maximum: maximum.to_string(),
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl<C> RootOfTrust for Gateway<C>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: RootOfTrustRuntimeApi<Block, CertificateId>,
+{
+    async fn is_root_certificate_valid(
+        &self,
+        request: Request<RootCertificateRequest>,
+    ) -> Result<Response<Validity>, Status> {
+        let request = request.into_inner();
+        let at = self.at(request.at)?;
+        let certificate = account(&request.certificate)?;
+        let valid = self
+            .client
+            .runtime_api()
+            .is_root_certificate_valid(&at, &certificate)
+            .map_err(runtime_error)?;
+
+        Ok(Response::new(Validity { valid }))
+    }
+
+    async fn is_child_certificate_valid(
+        &self,
+        request: Request<ChildCertificateRequest>,
+    ) -> Result<Response<Validity>, Status> {
+        let request = request.into_inner();
+        let at = self.at(request.at)?;
+        let root = account(&request.root)?;
+        let child = account(&request.child)?;
+        let valid = self
+            .client
+            .runtime_api()
+            .is_child_certificate_valid(&at, &root, &child)
+            .map_err(runtime_error)?;
+
+        Ok(Response::new(Validity { valid }))
+    }
+}
+
+/// Serve the gateway on `addr` until the node stops.
+pub async fn run<C>(client: Arc<C>, addr: SocketAddr)
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
+    C::Api: RootOfTrustRuntimeApi<Block, CertificateId>,
+{
+    let served = Server::builder()
+        .add_service(AllocationsServer::new(Gateway {
+            client: client.clone(),
+        }))
+        .add_service(RootOfTrustServer::new(Gateway { client }))
+        .serve(addr)
+        .await;
+
+    if let Err(e) = served {
+        log::error!(target: "grpc", "gRPC gateway stopped: {}", e);
+    }
+}
@@ -0,0 +1,132 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Readiness checks specific to our chain, complementing `system_health`. They let
+//! orchestration systems hold off the oracle daemon until the node can actually have
+//! its allocations accepted.
+
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use nodle_chain_primitives::{AccountId, Balance, Block, BlockNumber};
+use pallet_allocations::{OracleStatus, ORACLE_KEY_TYPE};
+use pallet_allocations_rpc::OraclesRuntimeApi;
+use parity_scale_codec::Decode;
+use sc_client_api::{Backend, StorageProvider};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, traits::BareCryptoStorePtr, twox_128};
+use sp_runtime::generic::BlockId;
+use std::{marker::PhantomData, sync::Arc};
+
+/// Outcome of the readiness checks at the best block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Readiness {
+    /// Oracle accounts whose key is in the node's keystore or held by its remote
+    /// signer
+    pub oracle_keys: Vec<AccountId>,
+    /// Whether one of the keys above belongs to a current oracle
+    pub is_oracle: bool,
+    /// Coins that can still be allocated
+    pub budget_left: Balance,
+    /// Whether the emergency shutdown is active, allocations are refused if so
+    pub shutdown: bool,
+    /// Whether all of the above allow the node to submit allocations
+    pub ready: bool,
+}
+
+#[rpc]
+pub trait HealthApi {
+    /// Check whether this node can submit allocations: an oracle key is in the
+    /// keystore, the allocations budget is not exhausted and the chain is not shut
+    /// down.
+    #[rpc(name = "oracle_readiness")]
+    fn readiness(&self) -> RpcResult<Readiness>;
+}
+
+pub struct Health<C, B> {
+    client: Arc<C>,
+    keystore: BareCryptoStorePtr,
+    _marker: PhantomData<B>,
+}
+
+impl<C, B> Health<C, B> {
+    pub fn new(client: Arc<C>, keystore: BareCryptoStorePtr) -> Self {
+        Self {
+            client,
+            keystore,
+            _marker: Default::default(),
+        }
+    }
+}
+
+fn internal_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::InternalError,
+        message: message.into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+impl<C, B> HealthApi for Health<C, B>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + StorageProvider<Block, B>,
+    C: Send + Sync + 'static,
+    C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
+    B: Backend<Block> + Send + Sync + 'static,
+{
+    fn readiness(&self) -> RpcResult<Readiness> {
+        let at = BlockId::hash(self.client.info().best_hash);
+        let api = self.client.runtime_api();
+
+        let oracle_keys = self
+            .keystore
+            .read()
+            .sr25519_public_keys(ORACLE_KEY_TYPE)
+            .into_iter()
+            .map(AccountId::from)
+            .collect::<Vec<_>>();
+        let oracles = api
+            .oracles(&at)
+            .map_err(|e| internal_error("Unable to query the oracles", e))?;
+        let is_oracle = oracles.iter().any(|(who, _)| oracle_keys.contains(who));
+
+        let (consumed, budget) = api
+            .budget(&at)
+            .map_err(|e| internal_error("Unable to query the allocations budget", e))?;
+        let budget_left = budget.saturating_sub(consumed);
+
+        let shutdown_key =
+            StorageKey([twox_128(b"EmergencyShutdown"), twox_128(b"Shutdown")].concat());
+        let shutdown = self
+            .client
+            .storage(&at, &shutdown_key)
+            .map_err(|e| internal_error("Unable to read the shutdown state", e))?
+            .and_then(|raw| bool::decode(&mut &raw.0[..]).ok())
+            .unwrap_or(false);
+
+        Ok(Readiness {
+            ready: is_oracle && budget_left > 0 && !shutdown,
+            oracle_keys,
+            is_oracle,
+            budget_left,
+            shutdown,
+        })
+    }
+}
@@ -0,0 +1,540 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Lightweight explorer backend. Allocation, vesting and certificate events are
+//! extracted from every block joining the best chain and stored in a dedicated
+//! database, indexed by the accounts they concern and, for allocations, by the root
+//! committed to in their proof. This lets wallets and explorers query an account's
+//! history without running a separate indexer.
+//!
+//! Every block is also recorded as a whole so that the events of blocks whose state
+//! was pruned, as on `--archive-lite` nodes, can still be served.
+
+use chain_merkle::{self as merkle, MerkleProof};
+use futures::prelude::*;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use kvdb::{DBTransaction, KeyValueDB};
+use nodle_chain_primitives::{AccountId, Balance, Block, BlockNumber, Hash};
+use nodle_chain_runtime::Event;
+use parity_scale_codec::{Decode, Encode};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_service::config::{Configuration, DatabaseConfig};
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::{blake2_256, storage::StorageKey, twox_128, Bytes};
+use sp_runtime::generic::BlockId;
+use std::{io, marker::PhantomData, sync::Arc};
+
+/// Records keyed by `account ++ block number ++ event index`.
+const COLUMN_ACCOUNTS: u32 = 0;
+/// Records keyed by `blake2_256(root) ++ block number ++ event index`.
+const COLUMN_ROOTS: u32 = 1;
+/// Records of a whole block keyed by block hash.
+const COLUMN_BLOCKS: u32 = 2;
+const NUM_COLUMNS: u32 = 3;
+
+/// Maximum number of records returned by a single RPC call, callers can fetch the
+/// next page by querying again from the block following the last record.
+const MAX_RECORDS_PER_PAGE: usize = 500;
+
+/// An event worth indexing, flattened so that it can be served as is over RPC.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum IndexedEvent {
+    #[serde(rename_all = "camelCase")]
+    Allocation {
+        to: AccountId,
+        amount: Balance,
+        fee: Balance,
+        proof: Vec<u8>,
+    },
+    #[serde(rename_all = "camelCase")]
+    VestingScheduleAdded {
+        from: AccountId,
+        to: AccountId,
+        start: BlockNumber,
+        period: BlockNumber,
+        period_count: u32,
+        per_period: Balance,
+    },
+    #[serde(rename_all = "camelCase")]
+    VestingClaimed { who: AccountId, locked: Balance },
+    #[serde(rename_all = "camelCase")]
+    VestingSchedulesCanceled { who: AccountId },
+    #[serde(rename_all = "camelCase")]
+    CertificateBooked {
+        owner: AccountId,
+        certificate: AccountId,
+    },
+    #[serde(rename_all = "camelCase")]
+    CertificateRenewed { certificate: AccountId },
+    #[serde(rename_all = "camelCase")]
+    CertificateRevoked { certificate: AccountId },
+    #[serde(rename_all = "camelCase")]
+    ChildCertificateRevoked { root: AccountId, child: AccountId },
+}
+
+impl IndexedEvent {
+    fn from_runtime(event: Event) -> Option<Self> {
+        use pallet_allocations::RawEvent as Allocations;
+        use pallet_grants::RawEvent as Grants;
+        use pallet_root_of_trust::RawEvent as RootOfTrust;
+
+        Some(match event {
+            Event::pallet_allocations(Allocations::NewAllocation(to, amount, fee, proof)) => {
+                IndexedEvent::Allocation {
+                    to,
+                    amount,
+                    fee,
+                    proof,
+                }
+            }
+            Event::pallet_grants(Grants::VestingScheduleAdded(from, to, schedule)) => {
+                IndexedEvent::VestingScheduleAdded {
+                    from,
+                    to,
+                    start: schedule.start,
+                    period: schedule.period,
+                    period_count: schedule.period_count,
+                    per_period: schedule.per_period,
+                }
+            }
+            Event::pallet_grants(Grants::Claimed(who, locked)) => {
+                IndexedEvent::VestingClaimed { who, locked }
+            }
+            Event::pallet_grants(Grants::VestingSchedulesCanceled(who)) => {
+                IndexedEvent::VestingSchedulesCanceled { who }
+            }
+            Event::pallet_root_of_trust(RootOfTrust::SlotTaken(owner, certificate)) => {
+                IndexedEvent::CertificateBooked { owner, certificate }
+            }
+            Event::pallet_root_of_trust(RootOfTrust::SlotRenewed(certificate)) => {
+                IndexedEvent::CertificateRenewed { certificate }
+            }
+            Event::pallet_root_of_trust(RootOfTrust::SlotRevoked(certificate)) => {
+                IndexedEvent::CertificateRevoked { certificate }
+            }
+            Event::pallet_root_of_trust(RootOfTrust::ChildSlotRevoked(root, child)) => {
+                IndexedEvent::ChildCertificateRevoked { root, child }
+            }
+            _ => return None,
+        })
+    }
+
+    /// Accounts under which the event can be looked up.
+    fn accounts(&self) -> Vec<&AccountId> {
+        match self {
+            IndexedEvent::Allocation { to, .. } => vec![to],
+            IndexedEvent::VestingScheduleAdded { from, to, .. } => vec![from, to],
+            IndexedEvent::VestingClaimed { who, .. }
+            | IndexedEvent::VestingSchedulesCanceled { who } => vec![who],
+            IndexedEvent::CertificateBooked { owner, certificate } => vec![owner, certificate],
+            IndexedEvent::CertificateRenewed { certificate }
+            | IndexedEvent::CertificateRevoked { certificate } => vec![certificate],
+            IndexedEvent::ChildCertificateRevoked { root, child } => vec![root, child],
+        }
+    }
+
+    /// Root the event can be looked up by, oracles commit to the merkle root of the
+    /// data backing an allocation in its proof.
+    fn root(&self) -> Option<&[u8]> {
+        match self {
+            IndexedEvent::Allocation { proof, .. } if !proof.is_empty() => Some(proof),
+            _ => None,
+        }
+    }
+}
+
+/// An indexed event along with its position in the chain.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedRecord {
+    pub block_number: BlockNumber,
+    pub block_hash: Hash,
+    pub event_index: u32,
+    pub event: IndexedEvent,
+}
+
+impl IndexedRecord {
+    fn key_suffix(&self) -> [u8; 8] {
+        let mut suffix = [0; 8];
+        suffix[..4].copy_from_slice(&self.block_number.to_be_bytes());
+        suffix[4..].copy_from_slice(&self.event_index.to_be_bytes());
+        suffix
+    }
+}
+
+/// An allocation received by an account.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationRecord {
+    pub block_number: BlockNumber,
+    pub block_hash: Hash,
+    /// Amount received by the account, protocol fee excluded
+    pub amount: Balance,
+    /// Amount taken by the protocol
+    pub fee: Balance,
+    pub proof: Vec<u8>,
+}
+
+/// Database holding the index, kept next to the chain database.
+pub struct Store {
+    db: Arc<dyn KeyValueDB>,
+}
+
+impl Store {
+    /// Open the index of the chain described by `config`, falling back to an in
+    /// memory database if the chain database is not stored on disk.
+    pub fn open(config: &Configuration) -> io::Result<Self> {
+        let path = match &config.database {
+            DatabaseConfig::RocksDb { path, .. } | DatabaseConfig::ParityDb { path } => {
+                path.parent().map(|parent| parent.join("indexer"))
+            }
+            DatabaseConfig::Custom(_) => None,
+        };
+
+        let db: Arc<dyn KeyValueDB> = match path {
+            Some(path) => {
+                std::fs::create_dir_all(&path)?;
+                let db_config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
+                Arc::new(kvdb_rocksdb::Database::open(
+                    &db_config,
+                    &path.to_string_lossy(),
+                )?)
+            }
+            None => Arc::new(kvdb_memorydb::create(NUM_COLUMNS)),
+        };
+
+        Ok(Self { db })
+    }
+
+    /// Store the `records` of block `hash`, writing the same records again is a no-op.
+    fn insert(&self, hash: Hash, records: &[IndexedRecord]) -> io::Result<()> {
+        let mut transaction = DBTransaction::new();
+        transaction.put(COLUMN_BLOCKS, hash.as_ref(), &records.encode());
+        for record in records {
+            let value = record.encode();
+            for who in record.event.accounts() {
+                let key = [&who.encode()[..], &record.key_suffix()[..]].concat();
+                transaction.put(COLUMN_ACCOUNTS, &key, &value);
+            }
+            if let Some(root) = record.event.root() {
+                let key = [&blake2_256(root)[..], &record.key_suffix()[..]].concat();
+                transaction.put(COLUMN_ROOTS, &key, &value);
+            }
+        }
+
+        self.db.write(transaction)
+    }
+
+    fn records_with_prefix(&self, column: u32, prefix: &[u8]) -> Vec<IndexedRecord> {
+        self.db
+            .iter_with_prefix(column, prefix)
+            .filter_map(|(_, value)| IndexedRecord::decode(&mut &value[..]).ok())
+            .collect()
+    }
+
+    /// Events concerning `who` between `from_block` and `to_block` (inclusive),
+    /// oldest first.
+    pub fn by_account(
+        &self,
+        who: &AccountId,
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+    ) -> Vec<IndexedRecord> {
+        self.records_with_prefix(COLUMN_ACCOUNTS, &who.encode())
+            .into_iter()
+            .filter(|r| r.block_number >= from_block && r.block_number <= to_block)
+            .collect()
+    }
+
+    /// Events committing to `root`, oldest first.
+    pub fn by_root(&self, root: &[u8]) -> Vec<IndexedRecord> {
+        self.records_with_prefix(COLUMN_ROOTS, &blake2_256(root))
+    }
+
+    /// Events of block `hash`, `None` if the block was never indexed.
+    pub fn at(&self, hash: Hash) -> io::Result<Option<Vec<IndexedRecord>>> {
+        Ok(self
+            .db
+            .get(COLUMN_BLOCKS, hash.as_ref())?
+            .and_then(|value| Vec::<IndexedRecord>::decode(&mut &value[..]).ok()))
+    }
+}
+
+/// Extract the events we are interested in from block `hash`.
+pub(crate) fn block_records<C, B>(
+    client: &C,
+    hash: Hash,
+) -> sp_blockchain::Result<Vec<IndexedRecord>>
+where
+    C: HeaderBackend<Block> + StorageProvider<Block, B>,
+    B: Backend<Block>,
+{
+    let block_number = match client.number(hash)? {
+        Some(number) => number,
+        None => return Ok(Vec::new()),
+    };
+
+    let events_key = StorageKey([twox_128(b"System"), twox_128(b"Events")].concat());
+    let events = match client.storage(&BlockId::hash(hash), &events_key)? {
+        Some(raw) => Vec::<frame_system::EventRecord<Event, Hash>>::decode(&mut &raw.0[..])
+            .map_err(|e| sp_blockchain::Error::Msg(format!("undecodable events: {}", e)))?,
+        None => return Ok(Vec::new()),
+    };
+
+    Ok(events
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, record)| {
+            IndexedEvent::from_runtime(record.event).map(|event| IndexedRecord {
+                block_number,
+                block_hash: hash,
+                event_index: index as u32,
+                event,
+            })
+        })
+        .collect())
+}
+
+/// Index every block that becomes part of the best chain, to be spawned as a
+/// background task of full nodes.
+pub async fn run<C, B>(client: Arc<C>, store: Arc<Store>)
+where
+    C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B>,
+    B: Backend<Block>,
+{
+    let mut imports = client.import_notification_stream();
+
+    while let Some(notification) = imports.next().await {
+        if !notification.is_new_best {
+            continue;
+        }
+
+        // On reorgs the tree route lists every block we switch to, the new best
+        // block included.
+        let enacted = match notification.tree_route {
+            Some(ref route) => route.enacted().iter().map(|block| block.hash).collect(),
+            None => vec![notification.hash],
+        };
+
+        for hash in enacted {
+            let indexed = block_records(&*client, hash)
+                .map_err(|e| format!("{:?}", e))
+                .and_then(|records| store.insert(hash, &records).map_err(|e| e.to_string()));
+            if let Err(e) = indexed {
+                log::warn!(target: "indexer", "Failed to index block {}: {}", hash, e);
+            }
+        }
+    }
+}
+
+#[rpc]
+pub trait IndexerApi {
+    /// Allocations received by `account` between `from_block` and `to_block`
+    /// (inclusive) on the best chain, oldest first. At most 500 records are returned
+    /// at once.
+    #[rpc(name = "allocations_contributorHistory")]
+    fn contributor_history(
+        &self,
+        account: AccountId,
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+    ) -> RpcResult<Vec<AllocationRecord>>;
+
+    /// Indexed events concerning `account` between `from_block` and `to_block`
+    /// (inclusive) on the best chain, oldest first. At most 500 records are returned
+    /// at once.
+    #[rpc(name = "indexer_eventsByAccount")]
+    fn events_by_account(
+        &self,
+        account: AccountId,
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+    ) -> RpcResult<Vec<IndexedRecord>>;
+
+    /// Allocations on the best chain whose proof commits to `root`.
+    #[rpc(name = "indexer_eventsByRoot")]
+    fn events_by_root(&self, root: Bytes) -> RpcResult<Vec<IndexedRecord>>;
+
+    /// Indexed events of block `at`, or of the best block if omitted. They are
+    /// read from the block's state when available and from the index otherwise,
+    /// which lets nodes pruning their state keep serving historical events.
+    #[rpc(name = "indexer_eventsAt")]
+    fn events_at(&self, at: Option<Hash>) -> RpcResult<Vec<IndexedRecord>>;
+
+    /// Merkle proof that `account` received an allocation from the batch committed
+    /// to by `root`, built from the allocations of the best chain carrying `root` as
+    /// their proof. If the account was allocated several times in the batch the
+    /// proof covers the first allocation.
+    #[rpc(name = "allocations_generateProof")]
+    fn generate_proof(&self, root: Bytes, account: AccountId) -> RpcResult<MerkleProof>;
+}
+
+pub struct Indexer<C, B> {
+    client: Arc<C>,
+    store: Arc<Store>,
+    _marker: PhantomData<B>,
+}
+
+impl<C, B> Indexer<C, B> {
+    pub fn new(client: Arc<C>, store: Arc<Store>) -> Self {
+        Self {
+            client,
+            store,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, B> Indexer<C, B>
+where
+    C: HeaderBackend<Block>,
+{
+    /// Records of blocks that were retracted in a reorg are kept around, only
+    /// serve the ones that are still part of the best chain.
+    fn canonical(&self, records: Vec<IndexedRecord>) -> impl Iterator<Item = IndexedRecord> + '_ {
+        records
+            .into_iter()
+            .filter(move |r| self.client.hash(r.block_number).ok().flatten() == Some(r.block_hash))
+    }
+}
+
+impl<C, B> IndexerApi for Indexer<C, B>
+where
+    C: HeaderBackend<Block> + StorageProvider<Block, B> + Send + Sync + 'static,
+    B: Backend<Block> + Send + Sync + 'static,
+{
+    fn contributor_history(
+        &self,
+        account: AccountId,
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+    ) -> RpcResult<Vec<AllocationRecord>> {
+        let records = self.store.by_account(&account, from_block, to_block);
+
+        Ok(self
+            .canonical(records)
+            .filter_map(|r| match r.event {
+                IndexedEvent::Allocation {
+                    to,
+                    amount,
+                    fee,
+                    proof,
+                } if to == account => Some(AllocationRecord {
+                    block_number: r.block_number,
+                    block_hash: r.block_hash,
+                    amount,
+                    fee,
+                    proof,
+                }),
+                _ => None,
+            })
+            .take(MAX_RECORDS_PER_PAGE)
+            .collect())
+    }
+
+    fn events_by_account(
+        &self,
+        account: AccountId,
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+    ) -> RpcResult<Vec<IndexedRecord>> {
+        let records = self.store.by_account(&account, from_block, to_block);
+
+        Ok(self.canonical(records).take(MAX_RECORDS_PER_PAGE).collect())
+    }
+
+    fn events_by_root(&self, root: Bytes) -> RpcResult<Vec<IndexedRecord>> {
+        if root.is_empty() {
+            return Err(RpcError {
+                code: ErrorCode::InvalidParams,
+                message: "The root can not be empty".into(),
+                data: None,
+            });
+        }
+
+        let records = self.store.by_root(&root);
+
+        Ok(self.canonical(records).take(MAX_RECORDS_PER_PAGE).collect())
+    }
+    fn events_at(&self, at: Option<Hash>) -> RpcResult<Vec<IndexedRecord>> {
+        let hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        match block_records(&*self.client, hash) {
+            Ok(records) => Ok(records),
+            Err(state_error) => match self.store.at(hash) {
+                Ok(Some(records)) => Ok(records),
+                Ok(None) => Err(RpcError {
+                    code: ErrorCode::ServerError(1),
+                    message: "The block state is unavailable and the block was not indexed".into(),
+                    data: Some(format!("{:?}", state_error).into()),
+                }),
+                Err(e) => Err(RpcError {
+                    code: ErrorCode::InternalError,
+                    message: "Unable to read the index".into(),
+                    data: Some(e.to_string().into()),
+                }),
+            },
+        }
+    }
+    fn generate_proof(&self, root: Bytes, account: AccountId) -> RpcResult<MerkleProof> {
+        if root.is_empty() {
+            return Err(RpcError {
+                code: ErrorCode::InvalidParams,
+                message: "The root can not be empty".into(),
+                data: None,
+            });
+        }
+
+        // The whole batch is needed to rebuild the tree, do not paginate
+        let records = self
+            .canonical(self.store.by_root(&root))
+            .collect::<Vec<_>>();
+        let leaves = records
+            .iter()
+            .filter_map(|r| match &r.event {
+                IndexedEvent::Allocation {
+                    to, amount, fee, ..
+                } => Some((to, merkle::leaf(to, amount.saturating_add(*fee)))),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let index = leaves
+            .iter()
+            .position(|(to, _)| **to == account)
+            .ok_or_else(|| RpcError {
+                code: ErrorCode::ServerError(2),
+                message: "The account was not allocated anything in this batch".into(),
+                data: None,
+            })?;
+        let hashes = leaves.into_iter().map(|(_, leaf)| leaf).collect::<Vec<_>>();
+        let proof = merkle::prove(&hashes, index).expect("index was found in leaves; qed");
+
+        if proof.root.as_ref() != &root[..] {
+            return Err(RpcError {
+                code: ErrorCode::ServerError(3),
+                message: "The indexed allocations do not match the root".into(),
+                data: Some(format!("{:?}", proof.root).into()),
+            });
+        }
+
+        Ok(proof)
+    }
+}
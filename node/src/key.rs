@@ -0,0 +1,134 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Key management subcommands, extending Substrate's with helpers for oracle operators.
+
+use pallet_allocations::ORACLE_KEY_TYPE;
+use sc_cli::{Error, InsertCmd, KeySubcommand};
+use sp_core::{
+    crypto::{Pair as PairT, Ss58Codec},
+    hexdisplay::HexDisplay,
+    sr25519,
+};
+use structopt::StructOpt;
+
+/// Key management subcommands.
+#[derive(Debug, StructOpt)]
+pub enum KeyCmd {
+    #[structopt(flatten)]
+    Base(KeySubcommand),
+
+    /// Insert an oracle key into the keystore of a running node and print the
+    /// account to add to the oracles set.
+    InsertOracle(InsertOracleCmd),
+
+    /// Generate a new oracle key, insert it into the keystore of a running node and
+    /// print the motion replacing the previous oracle account.
+    RotateOracle(RotateOracleCmd),
+}
+
+/// Parameters shared by the oracle key subcommands.
+#[derive(Debug, StructOpt)]
+pub struct OracleNodeParams {
+    /// JSON-RPC endpoint of the oracle's node, it must accept unsafe RPC calls.
+    #[structopt(long, default_value = "http://localhost:9933")]
+    pub node_url: String,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct InsertOracleCmd {
+    /// Secret URI of the oracle key.
+    #[structopt(long)]
+    pub suri: String,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub node: OracleNodeParams,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct RotateOracleCmd {
+    /// Account of the oracle key being replaced.
+    #[structopt(long)]
+    pub previous: Option<String>,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub node: OracleNodeParams,
+}
+
+impl KeyCmd {
+    pub fn run(&self) -> Result<(), Error> {
+        match self {
+            KeyCmd::Base(cmd) => cmd.run(),
+            KeyCmd::InsertOracle(cmd) => {
+                let account = insert_oracle_key(&cmd.suri, &cmd.node)?;
+
+                println!("Oracle account: {}", account.to_ss58check());
+                println!("Public key: 0x{}", HexDisplay::from(&account.0));
+                println!(
+                    "Onboard it with a motion calling allocations.addOracle({})",
+                    account.to_ss58check()
+                );
+                Ok(())
+            }
+            KeyCmd::RotateOracle(cmd) => {
+                let (_, phrase, _) = sr25519::Pair::generate_with_phrase(None);
+                let account = insert_oracle_key(&phrase, &cmd.node)?;
+
+                println!("Secret phrase (back it up): {}", phrase);
+                println!("Oracle account: {}", account.to_ss58check());
+                println!("Public key: 0x{}", HexDisplay::from(&account.0));
+                match &cmd.previous {
+                    Some(previous) => println!(
+                        "Activate it with a motion calling allocationsOracles.swapMember({}, {})",
+                        previous,
+                        account.to_ss58check()
+                    ),
+                    None => println!(
+                        "Activate it with a motion calling allocations.addOracle({})",
+                        account.to_ss58check()
+                    ),
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Insert the oracle key derived from `suri` into the node's keystore and return its
+/// public key, which is also the oracle's account.
+fn insert_oracle_key(suri: &str, node: &OracleNodeParams) -> Result<sr25519::Public, Error> {
+    let pair = sr25519::Pair::from_string(suri, None)
+        .map_err(|_| Error::Input("Invalid secret URI".into()))?;
+    let key_type = String::from_utf8_lossy(&ORACLE_KEY_TYPE.0).into_owned();
+
+    InsertCmd::from_iter(&[
+        "insert",
+        "--suri",
+        suri,
+        "--key-type",
+        &key_type,
+        "--scheme",
+        "sr25519",
+        "--node-url",
+        &node.node_url,
+    ])
+    .run()?;
+
+    Ok(pair.public())
+}
@@ -0,0 +1,134 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! `light-spec` subcommand, writing the chain specification light clients such as
+//! smoldot, embedded in mobile wallets, start from. They verify the allocations they
+//! display themselves instead of trusting an RPC provider, but can only join the
+//! network through the boot nodes listed in their specification.
+
+use sc_network::config::MultiaddrWithPeerId;
+use sc_service::ChainSpec;
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// Whether light clients running in browsers can reach `node`, they can only dial
+/// websockets.
+fn is_websocket(node: &str) -> bool {
+    node.split('/')
+        .any(|protocol| protocol == "ws" || protocol == "wss")
+}
+
+/// Write the raw chain specification of a network with the boot nodes light clients
+/// should dial. Use `build-sync-spec` on the result to add a checkpoint sparing light
+/// clients the sync from genesis.
+#[derive(Debug, StructOpt)]
+pub struct LightSpecCmd {
+    /// Chain to write the specification of: dev, local, main, staging, arcadia or the
+    /// path to a specification file.
+    #[structopt(long, default_value = "main")]
+    pub chain: String,
+
+    /// Boot node to embed, as a multiaddress ending with its peer id, may be repeated.
+    /// Light clients in browsers only reach nodes listening on websockets.
+    #[structopt(long = "bootnode", required = true)]
+    pub boot_nodes: Vec<MultiaddrWithPeerId>,
+
+    /// Keep the boot nodes of the specification after the given ones.
+    #[structopt(long)]
+    pub keep_boot_nodes: bool,
+
+    /// Where to write the specification, standard output by default.
+    #[structopt(short, long, parse(from_os_str))]
+    pub output: Option<PathBuf>,
+}
+
+impl LightSpecCmd {
+    pub fn run(&self, spec: Box<dyn ChainSpec>) -> sc_cli::Result<()> {
+        let spec = self.build(spec)?;
+        match &self.output {
+            Some(path) => fs::write(path, spec)?,
+            None => println!("{}", spec),
+        }
+        Ok(())
+    }
+
+    fn build(&self, spec: Box<dyn ChainSpec>) -> Result<String, String> {
+        let mut boot_nodes = self
+            .boot_nodes
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        if self.keep_boot_nodes {
+            for node in spec.boot_nodes().iter().map(ToString::to_string) {
+                if !boot_nodes.contains(&node) {
+                    boot_nodes.push(node);
+                }
+            }
+        }
+        if !boot_nodes.iter().any(|node| is_websocket(node)) {
+            log::warn!("No boot node listens on websockets, browsers will not reach any");
+        }
+
+        // Light clients do not build the genesis storage themselves
+        let raw = spec.as_json(true)?;
+        let mut spec: serde_json::Value =
+            serde_json::from_str(&raw).map_err(|e| format!("invalid specification: {}", e))?;
+        spec["bootNodes"] = boot_nodes.into();
+        serde_json::to_string_pretty(&spec).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_spec::{development_config, main_config};
+
+    const PEER: &str = "12D3KooWRGndWiTnB9XkpJ4x1efJbmGNLybeCVNRNG1jxGTt6Fex";
+
+    fn build(spec: impl ChainSpec + 'static, args: &[&str]) -> serde_json::Value {
+        let cmd = LightSpecCmd::from_iter([&["light-spec"][..], args].concat());
+        let spec = cmd.build(Box::new(spec)).expect("specifications are valid");
+        serde_json::from_str(&spec).expect("specifications are JSON")
+    }
+
+    #[test]
+    fn embeds_the_boot_nodes_in_a_raw_specification() {
+        let node = format!("/dns4/boot.example.com/tcp/443/wss/p2p/{}", PEER);
+        let spec = build(development_config(), &["--bootnode", &node]);
+
+        assert_eq!(spec["bootNodes"], serde_json::json!([node]));
+        assert!(spec["genesis"]["raw"]["top"].is_object());
+    }
+
+    #[test]
+    fn keeps_the_boot_nodes_of_the_specification_if_asked() {
+        let node = format!("/dns4/boot.example.com/tcp/443/wss/p2p/{}", PEER);
+        let kept = main_config().boot_nodes().len();
+        let spec = build(main_config(), &["--bootnode", &node, "--keep-boot-nodes"]);
+
+        let boot_nodes = spec["bootNodes"].as_array().expect("boot nodes are listed");
+        assert_eq!(boot_nodes[0], node);
+        assert_eq!(boot_nodes.len(), kept + 1);
+    }
+
+    #[test]
+    fn detects_websockets() {
+        assert!(is_websocket("/ip4/127.0.0.1/tcp/30334/ws"));
+        assert!(is_websocket("/dns4/example.com/tcp/443/wss"));
+        assert!(!is_websocket("/ip4/127.0.0.1/tcp/30333"));
+    }
+}
@@ -16,12 +16,31 @@
  * along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+mod audit;
 mod chain_spec;
 #[macro_use]
 mod service;
 mod cli;
 mod command;
+mod export;
+mod fork;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod health;
+mod indexer;
+mod key;
+mod light_spec;
+mod metrics;
+mod oracle;
+mod pending;
+mod rewards;
 mod rpc;
+mod signer;
+mod spec_builder;
+mod telemetry;
+mod upgrade;
+mod verify_runtime;
+mod webhooks;
 
 fn main() -> sc_cli::Result<()> {
     command::run()
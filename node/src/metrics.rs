@@ -0,0 +1,129 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Prometheus metrics tracking the allocations pipeline, meant to let operators
+//! alert when oracles stop submitting rewards.
+
+use crate::indexer::{block_records, IndexedEvent};
+use futures::prelude::*;
+use nodle_chain_primitives::{AccountId, Balance, Block, BlockNumber};
+use pallet_allocations::OracleStatus;
+use pallet_allocations_rpc::OraclesRuntimeApi;
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use std::sync::Arc;
+use substrate_prometheus_endpoint::{
+    register, Counter, Gauge, GaugeVec, Opts, PrometheusError, Registry, F64, U64,
+};
+
+struct Metrics {
+    allocations_per_block: Gauge<U64>,
+    allocations_total: Counter<U64>,
+    minted_total: Counter<F64>,
+    oracle_heartbeat_age: GaugeVec<U64>,
+}
+
+impl Metrics {
+    fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+        Ok(Self {
+            allocations_per_block: register(
+                Gauge::new(
+                    "nodle_allocations_per_block",
+                    "Number of allocations in the last best block",
+                )?,
+                registry,
+            )?,
+            allocations_total: register(
+                Counter::new(
+                    "nodle_allocations_total",
+                    "Number of allocations seen in best blocks",
+                )?,
+                registry,
+            )?,
+            minted_total: register(
+                Counter::new(
+                    "nodle_allocations_minted_total",
+                    "Amount minted by allocations seen in best blocks, protocol fees included",
+                )?,
+                registry,
+            )?,
+            oracle_heartbeat_age: register(
+                GaugeVec::new(
+                    Opts::new(
+                        "nodle_oracle_heartbeat_age",
+                        "Number of blocks since each oracle last submitted an allocation",
+                    ),
+                    &["oracle"],
+                )?,
+                registry,
+            )?,
+        })
+    }
+}
+
+/// Update the allocation metrics every time we import a new best block, to be
+/// spawned as a background task of full nodes exposing Prometheus metrics.
+pub async fn run<C, B>(client: Arc<C>, registry: Registry)
+where
+    C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B>,
+    C: ProvideRuntimeApi<Block>,
+    C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
+    B: Backend<Block>,
+{
+    let metrics = match Metrics::register(&registry) {
+        Ok(metrics) => metrics,
+        Err(e) => {
+            log::warn!(target: "metrics", "Failed to register allocation metrics: {:?}", e);
+            return;
+        }
+    };
+
+    let mut imports = client.import_notification_stream();
+    while let Some(notification) = imports.next().await {
+        if !notification.is_new_best {
+            continue;
+        }
+
+        let records = block_records(&*client, notification.hash).unwrap_or_default();
+        let mut allocations = 0;
+        for record in records {
+            if let IndexedEvent::Allocation { amount, fee, .. } = record.event {
+                allocations += 1;
+                metrics
+                    .minted_total
+                    .inc_by(amount.saturating_add(fee) as f64);
+            }
+        }
+        metrics.allocations_per_block.set(allocations);
+        metrics.allocations_total.inc_by(allocations);
+
+        let best = *notification.header.number();
+        let statuses = client
+            .runtime_api()
+            .oracles(&BlockId::hash(notification.hash))
+            .unwrap_or_default();
+        metrics.oracle_heartbeat_age.reset();
+        for (oracle, status) in statuses {
+            metrics
+                .oracle_heartbeat_age
+                .with_label_values(&[&oracle.to_string()])
+                .set(best.saturating_sub(status.last_seen).into());
+        }
+    }
+}
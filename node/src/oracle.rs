@@ -0,0 +1,294 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Node side signing of allocations. The oracle key is kept in the node's keystore,
+//! see the `key insert-oracle` subcommand, or behind its remote signer so that oracle
+//! daemons can submit batches without ever handling private keys. The RPC is unsafe and thus only served to
+//! local connections unless the operator opts out.
+
+use futures::{FutureExt, TryFutureExt};
+use jsonrpc_core::{BoxFuture, Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use nodle_chain_primitives::{AccountId, Balance, Block, BlockNumber, Hash, Index};
+use nodle_chain_runtime::{Call, Runtime, SignedExtra, SignedPayload, UncheckedExtrinsic};
+use pallet_allocations::{OracleStatus, ORACLE_KEY_TYPE};
+use pallet_allocations_rpc::OraclesRuntimeApi;
+use parity_scale_codec::{Decode, Encode};
+use sc_rpc_api::DenyUnsafe;
+use serde::{Deserialize, Serialize};
+use sp_api::{Core, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+    crypto::{CryptoTypePublicPair, Public},
+    sr25519,
+    traits::BareCryptoStorePtr,
+    Bytes,
+};
+use sp_runtime::{
+    generic::{BlockId, Era},
+    MultiSignature,
+};
+use sp_transaction_pool::{InPoolTransaction, TransactionPool, TransactionSource};
+use std::sync::Arc;
+use substrate_frame_rpc_system::AccountNonceApi;
+
+/// An allocation to be signed by the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationRequest {
+    pub to: AccountId,
+    /// Amount to allocate, protocol fee included
+    pub amount: Balance,
+    pub proof: Bytes,
+}
+
+#[rpc]
+pub trait OracleApi {
+    /// Sign the allocations of `batch` with the oracle key of the keystore and submit
+    /// them in a single transaction, returns the transaction hash.
+    #[rpc(name = "oracle_submitAllocationBatch")]
+    fn submit_allocation_batch(&self, batch: Vec<AllocationRequest>) -> BoxFuture<Hash>;
+}
+
+pub struct Oracle<C, P> {
+    client: Arc<C>,
+    pool: Arc<P>,
+    keystore: BareCryptoStorePtr,
+    deny_unsafe: DenyUnsafe,
+}
+
+impl<C, P> Oracle<C, P> {
+    pub fn new(
+        client: Arc<C>,
+        pool: Arc<P>,
+        keystore: BareCryptoStorePtr,
+        deny_unsafe: DenyUnsafe,
+    ) -> Self {
+        Self {
+            client,
+            pool,
+            keystore,
+            deny_unsafe,
+        }
+    }
+}
+
+fn error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(1),
+        message: message.into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+impl<C, P> Oracle<C, P>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: AccountNonceApi<Block, AccountId, Index>,
+    C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
+    P: TransactionPool<Block = Block, Hash = Hash> + 'static,
+{
+    /// Oracle key of the keystore whose account is in the oracle set.
+    fn oracle_key(&self, at: &BlockId<Block>) -> RpcResult<sr25519::Public> {
+        let oracles = self
+            .client
+            .runtime_api()
+            .oracles(at)
+            .map_err(|e| error("Unable to query the oracles", e))?;
+        self.keystore
+            .read()
+            .sr25519_public_keys(ORACLE_KEY_TYPE)
+            .into_iter()
+            .find(|public| {
+                oracles
+                    .iter()
+                    .any(|(who, _)| *who == AccountId::from(*public))
+            })
+            .ok_or_else(|| RpcError {
+                code: ErrorCode::ServerError(2),
+                message: "No key of the keystore belongs to an oracle".into(),
+                data: None,
+            })
+    }
+
+    /// Nonce of `account` once the transactions it has in the pool are included.
+    fn next_nonce(&self, at: &BlockId<Block>, account: &AccountId) -> RpcResult<Index> {
+        let mut nonce = self
+            .client
+            .runtime_api()
+            .account_nonce(at, account.clone())
+            .map_err(|e| error("Unable to query the oracle nonce", e))?;
+
+        let mut tag = (account, nonce).encode();
+        for tx in self.pool.ready() {
+            if tx.provides().get(0) == Some(&tag) {
+                nonce += 1;
+                tag = (account, nonce).encode();
+            }
+        }
+
+        Ok(nonce)
+    }
+
+    fn sign(
+        &self,
+        batch: Vec<AllocationRequest>,
+    ) -> RpcResult<(BlockId<Block>, UncheckedExtrinsic)> {
+        let shared_proof = batch
+            .first()
+            .map(|first| batch.iter().all(|request| request.proof == first.proof))
+            .unwrap_or(false);
+        let call = match batch.len() {
+            0 => {
+                return Err(RpcError {
+                    code: ErrorCode::InvalidParams,
+                    message: "The batch can not be empty".into(),
+                    data: None,
+                })
+            }
+            1 => {
+                let request = batch.into_iter().next().expect("the batch has one request");
+                Call::Allocations(pallet_allocations::Call::allocate(
+                    request.to,
+                    request.amount,
+                    request.proof.to_vec(),
+                ))
+            }
+            // Allocations that fail are then skipped instead of interrupting the batch
+            _ if shared_proof => {
+                let proof = batch[0].proof.to_vec();
+                Call::Allocations(pallet_allocations::Call::allocate_batch(
+                    batch
+                        .into_iter()
+                        .map(|request| (request.to, request.amount))
+                        .collect(),
+                    proof,
+                ))
+            }
+            _ => Call::Utility(pallet_utility::Call::batch(
+                batch
+                    .into_iter()
+                    .map(|request| {
+                        Call::Allocations(pallet_allocations::Call::allocate(
+                            request.to,
+                            request.amount,
+                            request.proof.to_vec(),
+                        ))
+                    })
+                    .collect(),
+            )),
+        };
+
+        let info = self.client.info();
+        let at = BlockId::hash(info.best_hash);
+        let public = self.oracle_key(&at)?;
+        let account = AccountId::from(public);
+        let nonce = self.next_nonce(&at, &account)?;
+        let version = self
+            .client
+            .runtime_api()
+            .version(&at)
+            .map_err(|e| error("Unable to query the runtime version", e))?;
+
+        let extra: SignedExtra = (
+            frame_system::CheckSpecVersion::<Runtime>::new(),
+            frame_system::CheckTxVersion::<Runtime>::new(),
+            frame_system::CheckGenesis::<Runtime>::new(),
+            frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+            frame_system::CheckNonce::<Runtime>::from(nonce),
+            frame_system::CheckWeight::<Runtime>::new(),
+            pallet_rate_limit::CheckRateLimit::<Runtime>::new(),
+            pallet_device_sessions::CheckSessionKey::<Runtime>::new(),
+            pallet_allocations::CheckOracle::<Runtime>::new(),
+            pallet_feeless::ChargeFeelessOrPayment::<Runtime>::from(
+                pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+            ),
+        );
+        // Immortal transactions commit to the genesis hash in place of their era
+        let payload = SignedPayload::from_raw(
+            call,
+            extra,
+            (
+                version.spec_version,
+                version.transaction_version,
+                info.genesis_hash,
+                info.genesis_hash,
+                (),
+                (),
+                (),
+                (),
+                (),
+            ),
+        );
+        // The key may be held by a remote signer, hence going through the keystore
+        let signature = payload
+            .using_encoded(|payload| {
+                self.keystore.read().sign_with(
+                    ORACLE_KEY_TYPE,
+                    &CryptoTypePublicPair(sr25519::CRYPTO_ID, public.to_raw_vec()),
+                    payload,
+                )
+            })
+            .map_err(|e| error("Unable to sign the transaction", e))?;
+        let signature = sr25519::Signature::from_slice(&signature);
+        let (call, extra, _) = payload.deconstruct();
+
+        Ok((
+            at,
+            UncheckedExtrinsic::new_signed(
+                call,
+                account.into(),
+                MultiSignature::from(signature),
+                extra,
+            ),
+        ))
+    }
+}
+
+impl<C, P> OracleApi for Oracle<C, P>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: AccountNonceApi<Block, AccountId, Index>,
+    C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
+    P: TransactionPool<Block = Block, Hash = Hash> + 'static,
+{
+    fn submit_allocation_batch(&self, batch: Vec<AllocationRequest>) -> BoxFuture<Hash> {
+        let signed = self
+            .deny_unsafe
+            .check_if_safe()
+            .map_err(Into::into)
+            .and_then(|_| self.sign(batch))
+            .and_then(|(at, xt)| {
+                Decode::decode(&mut &xt.encode()[..])
+                    .map(|xt| (at, xt))
+                    .map_err(|e| error("Unable to encode the transaction", e))
+            });
+        let (at, xt) = match signed {
+            Ok(signed) => signed,
+            Err(e) => return Box::new(jsonrpc_core::futures::future::err(e)),
+        };
+
+        Box::new(
+            self.pool
+                .submit_one(&at, TransactionSource::Local, xt)
+                .map_err(|e| error("The transaction was rejected by the pool", e))
+                .boxed()
+                .compat(),
+        )
+    }
+}
@@ -0,0 +1,140 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Inspection of the allocations waiting in the transaction pool. Oracle daemons use
+//! it to detect duplicate and stuck submissions before signing them again.
+
+use jsonrpc_core::Result as RpcResult;
+use jsonrpc_derive::rpc;
+use nodle_chain_primitives::{AccountId, Balance, Hash, Index};
+use nodle_chain_runtime::{Call, UncheckedExtrinsic};
+use pallet_indices::address::Address;
+use parity_scale_codec::{Compact, Decode, Encode};
+use serde::{Deserialize, Serialize};
+use sp_transaction_pool::{InPoolTransaction, TransactionPool};
+use std::sync::Arc;
+
+/// Whether a transaction can be included in the next block or waits for another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PendingStatus {
+    Ready,
+    /// Waiting for a transaction with a lower nonce
+    Future,
+}
+
+/// An allocation in the transaction pool.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingAllocation {
+    pub hash: Hash,
+    pub status: PendingStatus,
+    /// Signer of the transaction, `None` if it was signed with an account index
+    pub oracle: Option<AccountId>,
+    pub nonce: Option<Index>,
+    pub to: AccountId,
+    /// Amount to allocate, protocol fee included
+    pub amount: Balance,
+    pub proof: Vec<u8>,
+}
+
+impl PendingAllocation {
+    /// Allocations of the transaction, one per item of a batch of allocations.
+    fn from_extrinsic(hash: Hash, status: PendingStatus, encoded: &[u8]) -> Vec<Self> {
+        let xt = match UncheckedExtrinsic::decode(&mut &encoded[..]) {
+            Ok(xt) => xt,
+            Err(_) => return Vec::new(),
+        };
+        let allocations = match xt.function {
+            Call::Allocations(pallet_allocations::Call::allocate(to, amount, proof)) => {
+                vec![(to, amount, proof)]
+            }
+            Call::Allocations(pallet_allocations::Call::allocate_batch(allocations, proof)) => {
+                allocations
+                    .into_iter()
+                    .map(|(to, amount)| (to, amount, proof.clone()))
+                    .collect()
+            }
+            _ => return Vec::new(),
+        };
+        let (oracle, nonce) = match xt.signature {
+            Some((address, _, extra)) => {
+                let oracle = match address {
+                    Address::Id(who) => Some(who),
+                    Address::Index(_) => None,
+                };
+                // `CheckNonce` does not expose the nonce, it is its only field though
+                let nonce = Compact::<Index>::decode(&mut &extra.4.encode()[..])
+                    .ok()
+                    .map(|nonce| nonce.0);
+                (oracle, nonce)
+            }
+            None => (None, None),
+        };
+
+        allocations
+            .into_iter()
+            .map(|(to, amount, proof)| Self {
+                hash,
+                status,
+                oracle: oracle.clone(),
+                nonce,
+                to,
+                amount,
+                proof,
+            })
+            .collect()
+    }
+}
+
+#[rpc]
+pub trait PendingApi {
+    /// Allocations currently in the transaction pool, ready ones first.
+    #[rpc(name = "allocations_pending")]
+    fn pending(&self) -> RpcResult<Vec<PendingAllocation>>;
+}
+
+pub struct Pending<P> {
+    pool: Arc<P>,
+}
+
+impl<P> Pending<P> {
+    pub fn new(pool: Arc<P>) -> Self {
+        Self { pool }
+    }
+}
+
+impl<P> PendingApi for Pending<P>
+where
+    P: TransactionPool<Hash = Hash> + 'static,
+{
+    fn pending(&self) -> RpcResult<Vec<PendingAllocation>> {
+        let ready = self.pool.ready().flat_map(|tx| {
+            PendingAllocation::from_extrinsic(*tx.hash(), PendingStatus::Ready, &tx.data().encode())
+        });
+        let future = self.pool.futures().into_iter().flat_map(|tx| {
+            PendingAllocation::from_extrinsic(
+                *tx.hash(),
+                PendingStatus::Future,
+                &tx.data().encode(),
+            )
+        });
+
+        Ok(ready.chain(future).collect())
+    }
+}
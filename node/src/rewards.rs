@@ -0,0 +1,216 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Subscription streaming allocations as they are included in the best chain and
+//! once more when they are finalized, so that payout dashboards do not need to poll
+//! and decode `system_events` themselves.
+
+use crate::indexer::{block_records, IndexedEvent};
+use futures::{future, prelude::*, stream};
+use jsonrpc_core::futures::{future::Future as Future01, sink::Sink as Sink01};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use nodle_chain_primitives::{AccountId, Balance, Block, BlockNumber, Hash};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Header as HeaderT;
+use std::{marker::PhantomData, sync::Arc};
+
+/// An allocation included in a block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RewardNotification {
+    pub block_number: BlockNumber,
+    pub block_hash: Hash,
+    pub event_index: u32,
+    pub to: AccountId,
+    /// Amount received by the grantee, protocol fee excluded
+    pub amount: Balance,
+    /// Amount taken by the protocol
+    pub fee: Balance,
+    pub proof: Vec<u8>,
+    /// Whether the block is finalized, allocations are first notified when their
+    /// block becomes the best block and once more when it is finalized
+    pub finalized: bool,
+}
+
+/// Allocations of block `hash`, errors are logged and yield no notification.
+fn block_rewards<C, B>(client: &C, hash: Hash, finalized: bool) -> Vec<RewardNotification>
+where
+    C: HeaderBackend<Block> + StorageProvider<Block, B>,
+    B: Backend<Block>,
+{
+    let records = match block_records(client, hash) {
+        Ok(records) => records,
+        Err(e) => {
+            log::warn!(target: "rpc", "Failed to read the allocations of {}: {:?}", hash, e);
+            return Vec::new();
+        }
+    };
+
+    records
+        .into_iter()
+        .filter_map(|record| match record.event {
+            IndexedEvent::Allocation {
+                to,
+                amount,
+                fee,
+                proof,
+            } => Some(RewardNotification {
+                block_number: record.block_number,
+                block_hash: record.block_hash,
+                event_index: record.event_index,
+                to,
+                amount,
+                fee,
+                proof,
+                finalized,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Notifications for every allocation from now on.
+fn reward_notifications<C, B>(client: Arc<C>) -> impl Stream<Item = RewardNotification>
+where
+    C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B>,
+    B: Backend<Block>,
+{
+    let best = {
+        let client = client.clone();
+        client
+            .import_notification_stream()
+            .filter(|notification| future::ready(notification.is_new_best))
+            .map(move |notification| {
+                // On reorgs the tree route lists every block we switch to, the new
+                // best block included.
+                let enacted = match notification.tree_route {
+                    Some(ref route) => route.enacted().iter().map(|block| block.hash).collect(),
+                    None => vec![notification.hash],
+                };
+                enacted
+                    .into_iter()
+                    .flat_map(|hash| block_rewards(&*client, hash, false))
+                    .collect::<Vec<_>>()
+            })
+    };
+
+    // Only the new finalized head is notified, blocks finalized along with it are
+    // looked up by number.
+    let last_finalized = client.info().finalized_number;
+    let finalized = client.finality_notification_stream().scan(
+        last_finalized,
+        move |last_finalized, notification| {
+            let number = *notification.header.number();
+            let rewards = (*last_finalized + 1..=number)
+                .filter_map(|n| client.hash(n).ok().flatten())
+                .flat_map(|hash| block_rewards(&*client, hash, true))
+                .collect::<Vec<_>>();
+            *last_finalized = number.max(*last_finalized);
+            future::ready(Some(rewards))
+        },
+    );
+
+    stream::select(best, finalized).flat_map(stream::iter)
+}
+
+#[rpc]
+pub trait RewardsApi {
+    type Metadata;
+
+    /// Stream the allocations included in the best chain, each allocation is notified
+    /// a second time with `finalized` set once its block is finalized.
+    #[pubsub(
+        subscription = "allocations_rewards",
+        subscribe,
+        name = "allocations_subscribeRewards"
+    )]
+    fn subscribe_rewards(
+        &self,
+        metadata: Self::Metadata,
+        subscriber: Subscriber<RewardNotification>,
+    );
+
+    /// Stop streaming allocations.
+    #[pubsub(
+        subscription = "allocations_rewards",
+        unsubscribe,
+        name = "allocations_unsubscribeRewards"
+    )]
+    fn unsubscribe_rewards(
+        &self,
+        metadata: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+}
+
+pub struct Rewards<C, B> {
+    client: Arc<C>,
+    manager: SubscriptionManager,
+    _marker: PhantomData<B>,
+}
+
+impl<C, B> Rewards<C, B> {
+    pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+        Self {
+            client,
+            manager: SubscriptionManager::new(Arc::new(executor)),
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, B> RewardsApi for Rewards<C, B>
+where
+    C: BlockchainEvents<Block>
+        + HeaderBackend<Block>
+        + StorageProvider<Block, B>
+        + Send
+        + Sync
+        + 'static,
+    B: Backend<Block> + Send + Sync + 'static,
+{
+    type Metadata = sc_rpc::Metadata;
+
+    fn subscribe_rewards(
+        &self,
+        _metadata: Self::Metadata,
+        subscriber: Subscriber<RewardNotification>,
+    ) {
+        let stream = reward_notifications(self.client.clone())
+            .map(|notification| Ok::<_, ()>(Ok(notification)))
+            .compat();
+
+        self.manager.add(subscriber, |sink| {
+            sink.sink_map_err(|e| log::warn!(target: "rpc", "Error sending rewards: {:?}", e))
+                .send_all(stream)
+                .map(|_| ())
+        });
+    }
+
+    fn unsubscribe_rewards(
+        &self,
+        _metadata: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        Ok(self.manager.cancel(id))
+    }
+}
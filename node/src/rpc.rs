@@ -18,8 +18,19 @@
 
 //! RPC APIs instantiation code for the Nodle Chain.
 
+use crate::{
+    health::{Health, HealthApi},
+    indexer::{Indexer, IndexerApi, Store as IndexerStore},
+    oracle::{Oracle, OracleApi},
+    pending::{Pending, PendingApi},
+    rewards::{Rewards, RewardsApi},
+};
 use nodle_chain_primitives::{AccountId, Balance, Block, BlockNumber, CertificateId, Hash, Index};
+use pallet_allocations::OracleStatus;
+use pallet_allocations_rpc::{Oracles, OraclesApi, OraclesRuntimeApi};
+use pallet_feeless_rpc::{FeelessRuntimeApi, Fees, FeesApi};
 use pallet_root_of_trust_rpc::{RootOfTrust, RootOfTrustApi, RootOfTrustRuntimeApi};
+use sc_client_api::{BlockchainEvents, StorageProvider};
 use sc_consensus_babe::{Config, Epoch};
 use sc_consensus_babe_rpc::BabeRpcHandler;
 use sc_consensus_epochs::SharedEpochChanges;
@@ -35,6 +46,7 @@ use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 use sp_consensus::SelectChain;
 use sp_consensus_babe::BabeApi;
+use sp_core::traits::BareCryptoStorePtr;
 use sp_transaction_pool::TransactionPool;
 use std::sync::Arc;
 
@@ -88,33 +100,116 @@ pub struct FullDeps<C, P, SC, B> {
     pub babe: BabeDeps,
     /// GRANDPA specific dependencies.
     pub grandpa: GrandpaDeps<B>,
+    /// Events indexed by the node.
+    pub indexer: Arc<IndexerStore>,
+    /// Keystore signing oracle transactions, may delegate to a remote signer.
+    pub signer: BareCryptoStorePtr,
+}
+
+/// Dependencies of the RPC extensions that do not depend on consensus, these are all
+/// the extensions exposed by instant seal development nodes.
+pub struct DevDeps<C, P> {
+    /// The client instance to use.
+    pub client: Arc<C>,
+    /// Transaction pool instance.
+    pub pool: Arc<P>,
+    /// Whether to deny unsafe calls
+    pub deny_unsafe: DenyUnsafe,
+    /// Events indexed by the node.
+    pub indexer: Arc<IndexerStore>,
+    /// Executor to drive the subscription manager of the rewards RPC.
+    pub subscription_executor: SubscriptionTaskExecutor,
+    /// Keystore signing oracle transactions, may delegate to a remote signer.
+    pub signer: BareCryptoStorePtr,
 }
 
 /// A IO handler that uses all Full RPC extensions.
 pub type IoHandler = jsonrpc_core::IoHandler<sc_rpc::Metadata>;
 
+/// Instantiate the RPC extensions that do not depend on consensus.
+pub fn create_dev<C, P, B>(deps: DevDeps<C, P>) -> jsonrpc_core::IoHandler<sc_rpc_api::Metadata>
+where
+    C: ProvideRuntimeApi<Block> + StorageProvider<Block, B> + BlockchainEvents<Block>,
+    C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+    C: Send + Sync + 'static,
+    C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
+    C::Api: RootOfTrustRuntimeApi<Block, CertificateId>,
+    C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
+    C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+    C::Api: FeelessRuntimeApi<Block, Balance>,
+    C::Api: BlockBuilder<Block>,
+    P: TransactionPool<Block = Block, Hash = Hash> + 'static,
+    B: sc_client_api::Backend<Block> + Send + Sync + 'static,
+{
+    use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+    use substrate_frame_rpc_system::{FullSystem, SystemApi};
+
+    let mut io = jsonrpc_core::IoHandler::default();
+    let DevDeps {
+        client,
+        pool,
+        deny_unsafe,
+        indexer,
+        subscription_executor,
+        signer,
+    } = deps;
+
+    io.extend_with(SystemApi::to_delegate(FullSystem::new(
+        client.clone(),
+        pool.clone(),
+        deny_unsafe,
+    )));
+    io.extend_with(PendingApi::to_delegate(Pending::new(pool.clone())));
+    io.extend_with(OracleApi::to_delegate(Oracle::new(
+        client.clone(),
+        pool,
+        signer.clone(),
+        deny_unsafe,
+    )));
+    io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(
+        client.clone(),
+    )));
+    io.extend_with(FeesApi::to_delegate(Fees::new(client.clone())));
+    io.extend_with(RootOfTrustApi::to_delegate(RootOfTrust::new(
+        client.clone(),
+    )));
+    io.extend_with(OraclesApi::to_delegate(Oracles::new(client.clone())));
+    io.extend_with(HealthApi::to_delegate(Health::<_, B>::new(
+        client.clone(),
+        signer,
+    )));
+    io.extend_with(IndexerApi::to_delegate(Indexer::<_, B>::new(
+        client.clone(),
+        indexer,
+    )));
+    io.extend_with(RewardsApi::to_delegate(Rewards::<_, B>::new(
+        client,
+        subscription_executor,
+    )));
+
+    io
+}
+
 /// Instantiate all Full RPC extensions.
 pub fn create_full<C, P, SC, B>(
     deps: FullDeps<C, P, SC, B>,
 ) -> jsonrpc_core::IoHandler<sc_rpc_api::Metadata>
 where
-    C: ProvideRuntimeApi<Block>,
+    C: ProvideRuntimeApi<Block> + StorageProvider<Block, B> + BlockchainEvents<Block>,
     C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
     C: Send + Sync + 'static,
     C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
     C::Api: RootOfTrustRuntimeApi<Block, CertificateId>,
+    C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+    C::Api: FeelessRuntimeApi<Block, Balance>,
     C::Api: BabeApi<Block>,
     C::Api: BlockBuilder<Block>,
-    P: TransactionPool + 'static,
+    P: TransactionPool<Block = Block, Hash = Hash> + 'static,
     SC: SelectChain<Block> + 'static,
     B: sc_client_api::Backend<Block> + Send + Sync + 'static,
     B::State: sc_client_api::backend::StateBackend<sp_runtime::traits::HashFor<Block>>,
 {
-    use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
-    use substrate_frame_rpc_system::{FullSystem, SystemApi};
-
-    let mut io = jsonrpc_core::IoHandler::default();
     let FullDeps {
         client,
         pool,
@@ -122,6 +217,8 @@ where
         deny_unsafe,
         babe,
         grandpa,
+        indexer,
+        signer,
     } = deps;
 
     let BabeDeps {
@@ -137,14 +234,14 @@ where
         finality_provider,
     } = grandpa;
 
-    io.extend_with(SystemApi::to_delegate(FullSystem::new(
-        client.clone(),
+    let mut io = create_dev::<_, _, B>(DevDeps {
+        client: client.clone(),
         pool,
         deny_unsafe,
-    )));
-    io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(
-        client.clone(),
-    )));
+        indexer,
+        subscription_executor: subscription_executor.clone(),
+        signer,
+    });
     io.extend_with(sc_consensus_babe_rpc::BabeApi::to_delegate(
         BabeRpcHandler::new(
             client.clone(),
@@ -164,7 +261,6 @@ where
             finality_provider,
         ),
     ));
-    io.extend_with(RootOfTrustApi::to_delegate(RootOfTrust::new(client)));
 
     io
 }
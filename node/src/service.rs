@@ -18,7 +18,11 @@
 
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use crate::rpc::{self, DenyUnsafe, IoHandler};
+use crate::{
+    indexer, metrics,
+    rpc::{self, DenyUnsafe, IoHandler},
+    signer, telemetry,
+};
 use futures::prelude::*;
 use nodle_chain_executor::Executor;
 use nodle_chain_primitives::Block;
@@ -38,14 +42,25 @@ use sp_runtime::traits::Block as BlockT;
 use std::sync::Arc;
 
 type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
-type FullBackend = sc_service::TFullBackend<Block>;
+pub type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 type FullGrandpaBlockImport =
     sc_finality_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>;
 type LightClient = sc_service::TLightClient<Block, RuntimeApi, Executor>;
 
+/// Oracles submit bursts of allocations signed with consecutive nonces and some of
+/// them may reach us before their predecessors. Make sure the future queue is at
+/// least as large as the ready one so that these are not dropped from the pool.
+fn pool_options(config: &Configuration) -> sc_transaction_pool::txpool::Options {
+    let mut options = config.transaction_pool.clone();
+    options.future.count = options.future.count.max(options.ready.count);
+    options.future.total_bytes = options.future.total_bytes.max(options.ready.total_bytes);
+    options
+}
+
 pub fn new_partial(
     config: &Configuration,
+    remote_signer: Option<signer::Config>,
 ) -> Result<
     sc_service::PartialComponents<
         FullClient,
@@ -63,6 +78,8 @@ pub fn new_partial(
             (
                 sc_finality_grandpa::SharedVoterState,
                 Arc<GrandpaFinalityProofProvider<FullBackend, Block>>,
+                Arc<indexer::Store>,
+                BareCryptoStorePtr,
             ),
         ),
     >,
@@ -71,11 +88,12 @@ pub fn new_partial(
     let (client, backend, keystore, task_manager) =
         sc_service::new_full_parts::<Block, RuntimeApi, Executor>(&config)?;
     let client = Arc::new(client);
+    let signer = signer::keystore(keystore.clone(), remote_signer);
 
     let select_chain = sc_consensus::LongestChain::new(backend.clone());
 
     let transaction_pool = sc_transaction_pool::BasicPool::new_full(
-        config.transaction_pool.clone(),
+        pool_options(config),
         config.prometheus_registry(),
         task_manager.spawn_handle(),
         client.clone(),
@@ -120,7 +138,15 @@ pub fn new_partial(
         let finality_proof_provider =
             GrandpaFinalityProofProvider::new_for_service(backend.clone(), client.clone());
 
-        let rpc_setup = (shared_voter_state.clone(), finality_proof_provider.clone());
+        let indexer =
+            Arc::new(indexer::Store::open(config).map_err(|e| ServiceError::Other(e.to_string()))?);
+
+        let rpc_setup = (
+            shared_voter_state.clone(),
+            finality_proof_provider.clone(),
+            indexer.clone(),
+            signer.clone(),
+        );
 
         let babe_config = babe_link.config().clone();
         let shared_epoch_changes = babe_link.epoch_changes().clone();
@@ -148,6 +174,8 @@ pub fn new_partial(
                     subscription_executor,
                     finality_provider: finality_proof_provider.clone(),
                 },
+                indexer: indexer.clone(),
+                signer: signer.clone(),
             };
 
             rpc::create_full(deps)
@@ -178,9 +206,11 @@ pub struct NewFullBase {
     pub transaction_pool: Arc<sc_transaction_pool::FullPool<Block, FullClient>>,
 }
 
-/// Creates a full service from the configuration.
+/// Creates a full service from the configuration, oracle and GRANDPA keys may be held
+/// by `remote_signer`.
 pub fn new_full_base(
     config: Configuration,
+    remote_signer: Option<signer::Config>,
     with_startup_data: impl FnOnce(
         &sc_consensus_babe::BabeBlockImport<Block, FullClient, FullGrandpaBlockImport>,
         &sc_consensus_babe::BabeLink<Block>,
@@ -196,9 +226,9 @@ pub fn new_full_base(
         transaction_pool,
         inherent_data_providers,
         other: (rpc_extensions_builder, import_setup, rpc_setup),
-    } = new_partial(&config)?;
+    } = new_partial(&config, remote_signer)?;
 
-    let (shared_voter_state, finality_proof_provider) = rpc_setup;
+    let (shared_voter_state, finality_proof_provider, indexer, signer) = rpc_setup;
 
     let (network, network_status_sinks, system_rpc_tx, network_starter) =
         sc_service::build_network(sc_service::BuildNetworkParams {
@@ -246,6 +276,23 @@ pub fn new_full_base(
         system_rpc_tx,
     })?;
 
+    task_manager.spawn_handle().spawn(
+        "indexer",
+        indexer::run::<_, FullBackend>(client.clone(), indexer),
+    );
+
+    if let Some(registry) = prometheus_registry.clone() {
+        task_manager.spawn_handle().spawn(
+            "allocation-metrics",
+            metrics::run::<_, FullBackend>(client.clone(), registry),
+        );
+    }
+
+    task_manager.spawn_handle().spawn(
+        "allocation-telemetry",
+        telemetry::run::<_, FullBackend>(client.clone()),
+    );
+
     let (block_import, grandpa_link, babe_link) = import_setup;
 
     (with_startup_data)(&block_import, &babe_link);
@@ -284,7 +331,7 @@ pub fn new_full_base(
         let (sentries, authority_discovery_role) = match role {
             sc_service::config::Role::Authority { ref sentry_nodes } => (
                 sentry_nodes.clone(),
-                sc_authority_discovery::Role::Authority(keystore.clone()),
+                sc_authority_discovery::Role::Authority(signer.clone()),
             ),
             sc_service::config::Role::Sentry { .. } => {
                 (vec![], sc_authority_discovery::Role::Sentry)
@@ -318,7 +365,7 @@ pub fn new_full_base(
     // if the node isn't actively participating in consensus then it doesn't
     // need a keystore, regardless of which protocol we use below.
     let keystore = if role.is_authority() {
-        Some(keystore as BareCryptoStorePtr)
+        Some(signer)
     } else {
         None
     };
@@ -376,11 +423,6 @@ pub fn new_full_base(
     })
 }
 
-/// Builds a new service for a full client.
-pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
-    new_full_base(config, |_, _| ()).map(|NewFullBase { task_manager, .. }| task_manager)
-}
-
 pub fn new_light_base(
     config: Configuration,
 ) -> Result<
@@ -501,6 +543,149 @@ pub fn new_light_base(
     ))
 }
 
+/// Builds a development service sealing a block as soon as a transaction reaches the
+/// pool. BABE still drives the runtime, its slot digests being produced by the manual
+/// seal engine from the node's keystore, but there is no GRANDPA and no peering.
+pub fn new_dev(
+    config: Configuration,
+    remote_signer: Option<signer::Config>,
+) -> Result<TaskManager, ServiceError> {
+    let (client, backend, keystore, mut task_manager) =
+        sc_service::new_full_parts::<Block, RuntimeApi, Executor>(&config)?;
+    let client = Arc::new(client);
+    let signer = signer::keystore(keystore.clone(), remote_signer);
+
+    let select_chain = sc_consensus::LongestChain::new(backend.clone());
+
+    let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+        pool_options(&config),
+        config.prometheus_registry(),
+        task_manager.spawn_handle(),
+        client.clone(),
+    );
+
+    let babe_config = sc_consensus_babe::Config::get_or_compute(&*client)?;
+    let (block_import, babe_link) =
+        sc_consensus_babe::block_import(babe_config.clone(), client.clone(), client.clone())?;
+
+    let inherent_data_providers = sp_inherents::InherentDataProviders::new();
+    inherent_data_providers
+        .register_provider(sp_timestamp::InherentDataProvider)
+        .map_err(Into::into)
+        .map_err(sp_consensus::error::Error::InherentData)?;
+
+    let consensus_data_provider =
+        sc_consensus_manual_seal::consensus::babe::BabeConsensusDataProvider::new(
+            client.clone(),
+            keystore.clone(),
+            &inherent_data_providers,
+            babe_link.epoch_changes().clone(),
+            babe_config.genesis_authorities.clone(),
+        )
+        .map_err(|e| ServiceError::Other(e.to_string()))?;
+
+    let import_queue = sc_consensus_manual_seal::import_queue(
+        Box::new(block_import.clone()),
+        &task_manager.spawn_handle(),
+        config.prometheus_registry(),
+    );
+
+    let (network, network_status_sinks, system_rpc_tx, network_starter) =
+        sc_service::build_network(sc_service::BuildNetworkParams {
+            config: &config,
+            client: client.clone(),
+            transaction_pool: transaction_pool.clone(),
+            spawn_handle: task_manager.spawn_handle(),
+            import_queue,
+            on_demand: None,
+            block_announce_validator_builder: None,
+            finality_proof_request_builder: None,
+            finality_proof_provider: None,
+        })?;
+
+    if config.offchain_worker.enabled {
+        sc_service::build_offchain_workers(
+            &config,
+            backend.clone(),
+            task_manager.spawn_handle(),
+            client.clone(),
+            network.clone(),
+        );
+    }
+
+    let indexer =
+        Arc::new(indexer::Store::open(&config).map_err(|e| ServiceError::Other(e.to_string()))?);
+    let prometheus_registry = config.prometheus_registry().cloned();
+
+    let rpc_extensions_builder = {
+        let client = client.clone();
+        let pool = transaction_pool.clone();
+        let indexer = indexer.clone();
+
+        move |deny_unsafe, subscription_executor| {
+            rpc::create_dev::<_, _, FullBackend>(rpc::DevDeps {
+                client: client.clone(),
+                pool: pool.clone(),
+                deny_unsafe,
+                indexer: indexer.clone(),
+                subscription_executor,
+                signer: signer.clone(),
+            })
+        }
+    };
+
+    sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+        config,
+        backend: backend.clone(),
+        client: client.clone(),
+        keystore,
+        network,
+        rpc_extensions_builder: Box::new(rpc_extensions_builder),
+        transaction_pool: transaction_pool.clone(),
+        task_manager: &mut task_manager,
+        on_demand: None,
+        remote_blockchain: None,
+        telemetry_connection_sinks: sc_service::TelemetryConnectionSinks::default(),
+        network_status_sinks,
+        system_rpc_tx,
+    })?;
+
+    task_manager.spawn_handle().spawn(
+        "indexer",
+        indexer::run::<_, FullBackend>(client.clone(), indexer),
+    );
+
+    if let Some(registry) = prometheus_registry.clone() {
+        task_manager.spawn_handle().spawn(
+            "allocation-metrics",
+            metrics::run::<_, FullBackend>(client.clone(), registry),
+        );
+    }
+
+    let proposer = sc_basic_authorship::ProposerFactory::new(
+        client.clone(),
+        transaction_pool.clone(),
+        prometheus_registry.as_ref(),
+    );
+
+    let instant_seal =
+        sc_consensus_manual_seal::run_instant_seal(sc_consensus_manual_seal::InstantSealParams {
+            block_import,
+            env: proposer,
+            client,
+            pool: transaction_pool.pool().clone(),
+            select_chain,
+            consensus_data_provider: Some(Box::new(consensus_data_provider)),
+            inherent_data_providers,
+        });
+    task_manager
+        .spawn_essential_handle()
+        .spawn_blocking("instant-seal", instant_seal);
+
+    network_starter.start_network();
+    Ok(task_manager)
+}
+
 /// Builds a new service for a light client.
 pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
     new_light_base(config).map(|(task_manager, _, _, _, _)| task_manager)
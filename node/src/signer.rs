@@ -0,0 +1,335 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Keystore delegating signatures to a remote signer service, typically fronting an
+//! HSM, for the keys it was configured with. Every other key is looked up in the
+//! local keystore.
+//!
+//! The signer answers `{ keyType, crypto, public, payload }` POST requests with
+//! `{ signature }`, bytes being hex encoded. VRF outputs can not be produced
+//! remotely, BABE keys have to stay in the local keystore.
+
+use parking_lot::RwLock;
+use sc_keystore::KeyStorePtr;
+use serde::{Deserialize, Serialize};
+use sp_core::{
+    crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId},
+    ecdsa, ed25519, sr25519,
+    traits::{BareCryptoStore, BareCryptoStoreError, BareCryptoStorePtr},
+    vrf::{VRFSignature, VRFTranscriptData},
+    Bytes,
+};
+use std::{convert::TryFrom, str::FromStr, sync::Arc, time::Duration};
+
+/// Signing requests taking longer than this are considered failed.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A key held by the remote signer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteKey {
+    pub key_type: KeyTypeId,
+    pub public: CryptoTypePublicPair,
+}
+
+impl FromStr for RemoteKey {
+    type Err = String;
+
+    /// Parse `TYPE:CRYPTO:PUBLIC`, for instance `orcl:sr25519:0x...`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        let parts = s.splitn(3, ':').collect::<Vec<_>>();
+        if parts.len() != 3 {
+            return Err("Remote keys are formatted as TYPE:CRYPTO:PUBLIC".into());
+        }
+
+        let key_type = KeyTypeId::try_from(parts[0])
+            .map_err(|_| format!("{} is not a valid key type", parts[0]))?;
+        let crypto = match parts[1] {
+            "sr25519" => sr25519::CRYPTO_ID,
+            "ed25519" => ed25519::CRYPTO_ID,
+            other => return Err(format!("{} keys can not be held remotely", other)),
+        };
+        let public = sp_core::bytes::from_hex(parts[2])
+            .map_err(|e| format!("{} is not a valid public key: {}", parts[2], e))?;
+        if public.len() != 32 {
+            return Err(format!("{} is not 32 bytes long", parts[2]));
+        }
+
+        Ok(Self {
+            key_type,
+            public: CryptoTypePublicPair(crypto, public),
+        })
+    }
+}
+
+/// Where to find the remote signer and which keys it holds.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub url: String,
+    pub keys: Vec<RemoteKey>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignRequest {
+    key_type: String,
+    crypto: &'static str,
+    public: Bytes,
+    payload: Bytes,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: Bytes,
+}
+
+fn crypto_name(id: CryptoTypeId) -> &'static str {
+    if id == sr25519::CRYPTO_ID {
+        "sr25519"
+    } else {
+        "ed25519"
+    }
+}
+
+/// The keystore used to sign with the node's keys, `local` alone unless a remote
+/// signer is configured.
+pub fn keystore(local: KeyStorePtr, remote: Option<Config>) -> BareCryptoStorePtr {
+    match remote {
+        Some(config) => Arc::new(RwLock::new(RemoteSigner::new(local, config))),
+        None => local,
+    }
+}
+
+pub struct RemoteSigner {
+    local: KeyStorePtr,
+    config: Config,
+}
+
+impl RemoteSigner {
+    pub fn new(local: KeyStorePtr, config: Config) -> Self {
+        Self { local, config }
+    }
+
+    fn remote_keys(&self, id: KeyTypeId, crypto: CryptoTypeId) -> impl Iterator<Item = &[u8]> {
+        self.config
+            .keys
+            .iter()
+            .filter(move |key| key.key_type == id && key.public.0 == crypto)
+            .map(|key| &key.public.1[..])
+    }
+
+    fn is_remote(&self, id: KeyTypeId, public: &CryptoTypePublicPair) -> bool {
+        self.config
+            .keys
+            .iter()
+            .any(|key| key.key_type == id && key.public == *public)
+    }
+
+    fn sign_remotely(
+        &self,
+        id: KeyTypeId,
+        public: &CryptoTypePublicPair,
+        msg: &[u8],
+    ) -> Result<Vec<u8>, BareCryptoStoreError> {
+        let url = self.config.url.clone();
+        let request = SignRequest {
+            key_type: String::from_utf8_lossy(&id.0).into_owned(),
+            crypto: crypto_name(public.0),
+            public: Bytes(public.1.clone()),
+            payload: Bytes(msg.to_vec()),
+        };
+
+        // The blocking client can not be used from the node's async tasks, give it a
+        // thread of its own
+        let response = std::thread::spawn(move || {
+            reqwest::blocking::Client::builder()
+                .timeout(TIMEOUT)
+                .build()?
+                .post(&url)
+                .json(&request)
+                .send()?
+                .error_for_status()?
+                .json::<SignResponse>()
+        })
+        .join()
+        .map_err(|_| BareCryptoStoreError::Unavailable)?
+        .map_err(|e| BareCryptoStoreError::Other(format!("Remote signer failed: {}", e)))?;
+
+        if response.signature.len() != 64 {
+            return Err(BareCryptoStoreError::ValidationError(
+                "Signatures are 64 bytes long".into(),
+            ));
+        }
+        Ok(response.signature.to_vec())
+    }
+}
+
+impl BareCryptoStore for RemoteSigner {
+    fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+        let mut keys = self.local.read().sr25519_public_keys(id);
+        keys.extend(
+            self.remote_keys(id, sr25519::CRYPTO_ID)
+                .map(sr25519::Public::from_slice),
+        );
+        keys
+    }
+
+    fn sr25519_generate_new(
+        &mut self,
+        id: KeyTypeId,
+        seed: Option<&str>,
+    ) -> Result<sr25519::Public, BareCryptoStoreError> {
+        self.local.write().sr25519_generate_new(id, seed)
+    }
+
+    fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+        let mut keys = self.local.read().ed25519_public_keys(id);
+        keys.extend(
+            self.remote_keys(id, ed25519::CRYPTO_ID)
+                .map(ed25519::Public::from_slice),
+        );
+        keys
+    }
+
+    fn ed25519_generate_new(
+        &mut self,
+        id: KeyTypeId,
+        seed: Option<&str>,
+    ) -> Result<ed25519::Public, BareCryptoStoreError> {
+        self.local.write().ed25519_generate_new(id, seed)
+    }
+
+    fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+        self.local.read().ecdsa_public_keys(id)
+    }
+
+    fn ecdsa_generate_new(
+        &mut self,
+        id: KeyTypeId,
+        seed: Option<&str>,
+    ) -> Result<ecdsa::Public, BareCryptoStoreError> {
+        self.local.write().ecdsa_generate_new(id, seed)
+    }
+
+    fn insert_unknown(&mut self, key_type: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+        self.local.write().insert_unknown(key_type, suri, public)
+    }
+
+    /// The local keystore is behind a lock, its password can not be borrowed.
+    fn password(&self) -> Option<&str> {
+        None
+    }
+
+    fn supported_keys(
+        &self,
+        id: KeyTypeId,
+        keys: Vec<CryptoTypePublicPair>,
+    ) -> Result<Vec<CryptoTypePublicPair>, BareCryptoStoreError> {
+        let known = self.keys(id)?;
+        Ok(keys.into_iter().filter(|key| known.contains(key)).collect())
+    }
+
+    fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, BareCryptoStoreError> {
+        let mut keys = self.local.read().keys(id)?;
+        keys.extend(
+            self.config
+                .keys
+                .iter()
+                .filter(|key| key.key_type == id)
+                .map(|key| key.public.clone()),
+        );
+        Ok(keys)
+    }
+
+    fn sign_with(
+        &self,
+        id: KeyTypeId,
+        key: &CryptoTypePublicPair,
+        msg: &[u8],
+    ) -> Result<Vec<u8>, BareCryptoStoreError> {
+        if self.is_remote(id, key) {
+            self.sign_remotely(id, key, msg)
+        } else {
+            self.local.read().sign_with(id, key, msg)
+        }
+    }
+
+    fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+        public_keys.iter().all(|(public, id)| {
+            self.config
+                .keys
+                .iter()
+                .any(|key| key.key_type == *id && key.public.1 == *public)
+                || self.local.read().has_keys(&[(public.clone(), *id)])
+        })
+    }
+
+    fn vrf_sign(
+        &self,
+        key_type: KeyTypeId,
+        public: &sr25519::Public,
+        transcript_data: VRFTranscriptData,
+    ) -> Result<VRFSignature, BareCryptoStoreError> {
+        self.local
+            .read()
+            .vrf_sign(key_type, public, transcript_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pallet_allocations::ORACLE_KEY_TYPE;
+
+    /// Key type of the oracle keys, as written on the command line.
+    fn oracle_key_type() -> String {
+        String::from_utf8_lossy(&ORACLE_KEY_TYPE.0).into_owned()
+    }
+
+    #[test]
+    fn parses_remote_keys() {
+        let key = format!("{}:sr25519:0x{}", oracle_key_type(), "2a".repeat(32))
+            .parse::<RemoteKey>()
+            .expect("the key is valid");
+
+        assert_eq!(key.key_type, ORACLE_KEY_TYPE);
+        assert_eq!(
+            key.public,
+            CryptoTypePublicPair(sr25519::CRYPTO_ID, vec![42; 32])
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_remote_keys() {
+        let public = "2a".repeat(32);
+
+        let key_type = oracle_key_type();
+
+        assert!(format!("{}:sr25519", key_type)
+            .parse::<RemoteKey>()
+            .is_err());
+        assert!(format!("oracle:sr25519:0x{}", public)
+            .parse::<RemoteKey>()
+            .is_err());
+        assert!(format!("{}:ecdsa:0x{}", key_type, public)
+            .parse::<RemoteKey>()
+            .is_err());
+        assert!(format!("{}:sr25519:0x2a2a", key_type)
+            .parse::<RemoteKey>()
+            .is_err());
+    }
+}
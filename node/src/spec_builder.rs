@@ -0,0 +1,412 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! `chain-spec` subcommand, generating the chain specification of one of our
+//! networks from a handful of parameters instead of hand editing JSON files.
+
+use crate::chain_spec::{
+    get_authority_keys_from_seed, staging_testnet_genesis, testnet_genesis, ChainSpec,
+    StagingChainSpec,
+};
+use nodle_chain_primitives::{AccountId, Balance, BlockNumber};
+use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
+use sc_service::ChainType;
+use serde::Deserialize;
+use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
+use sp_consensus_babe::AuthorityId as BabeId;
+use sp_core::{crypto::Ss58Codec, H256};
+use sp_finality_grandpa::AuthorityId as GrandpaId;
+use std::{fs, path::PathBuf, str::FromStr};
+use structopt::StructOpt;
+
+type Grants = Vec<(AccountId, Vec<(BlockNumber, BlockNumber, u32, Balance)>)>;
+type AuthorityKeys = (
+    AccountId,
+    AccountId,
+    GrandpaId,
+    BabeId,
+    ImOnlineId,
+    AuthorityDiscoveryId,
+);
+
+/// What the genesis needs of the airdrop written by `genesis-builder`, the proofs
+/// are left to the holders.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Airdrop {
+    root: Option<H256>,
+    total: Balance,
+}
+
+/// Networks we know how to generate a specification for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Dev,
+    Local,
+    Staging,
+    Main,
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dev" => Ok(Network::Dev),
+            "local" => Ok(Network::Local),
+            "staging" => Ok(Network::Staging),
+            "main" => Ok(Network::Main),
+            other => Err(format!(
+                "unknown network {}, expected one of dev, local, staging or main",
+                other
+            )),
+        }
+    }
+}
+
+impl Network {
+    fn chain_type(self) -> ChainType {
+        match self {
+            Network::Dev => ChainType::Development,
+            Network::Local => ChainType::Local,
+            Network::Staging | Network::Main => ChainType::Live,
+        }
+    }
+}
+
+pub(crate) fn parse_account(s: &str) -> Result<AccountId, String> {
+    AccountId::from_ss58check(s).map_err(|e| format!("invalid address {}: {:?}", s, e))
+}
+
+/// Parse an account or a public key given in SS58 or as 0x prefixed hex.
+fn parse_key<K: Ss58Codec + AsMut<[u8]> + Default>(s: &str) -> Result<K, String> {
+    if !s.starts_with("0x") {
+        return K::from_ss58check(s).map_err(|e| format!("invalid key {}: {:?}", s, e));
+    }
+
+    let raw = sp_core::bytes::from_hex(s).map_err(|e| format!("invalid key {}: {}", s, e))?;
+    let mut key = K::default();
+    if key.as_mut().len() != raw.len() {
+        return Err(format!("{} is not {} bytes long", s, key.as_mut().len()));
+    }
+    key.as_mut().copy_from_slice(&raw);
+    Ok(key)
+}
+
+/// Parse the keys of a validator, given as
+/// `stash,controller,babe,grandpa,im_online,authority_discovery`.
+fn parse_validator(s: &str) -> Result<AuthorityKeys, String> {
+    match s.split(',').collect::<Vec<_>>()[..] {
+        [stash, controller, babe, grandpa, im_online, authority_discovery] => Ok((
+            parse_key(stash)?,
+            parse_key(controller)?,
+            parse_key(grandpa)?,
+            parse_key(babe)?,
+            parse_key(im_online)?,
+            parse_key(authority_discovery)?,
+        )),
+        _ => Err(format!(
+            "{} is not formatted as stash,controller,babe,grandpa,im_online,authority_discovery",
+            s
+        )),
+    }
+}
+
+/// Add `extra` to `balances`, `extra` must not list an account twice.
+fn merge_balances(balances: &mut Vec<(AccountId, Balance)>, extra: &[(AccountId, Balance)]) {
+    let endowed = balances.len();
+    for (who, amount) in extra {
+        match balances[..endowed]
+            .iter_mut()
+            .find(|(account, _)| account == who)
+        {
+            Some((_, balance)) => *balance = balance.saturating_add(*amount),
+            None => balances.push((who.clone(), *amount)),
+        }
+    }
+}
+
+/// Generate a chain specification for one of our networks.
+#[derive(Debug, StructOpt)]
+pub struct ChainSpecCmd {
+    /// Network to generate the specification for: dev, local, staging or main. The
+    /// staging network runs the staging runtime.
+    #[structopt(long, default_value = "local")]
+    pub network: Network,
+
+    /// Human readable name of the chain.
+    #[structopt(long)]
+    pub name: String,
+
+    /// Identifier of the chain, also used as the name of its data directory.
+    #[structopt(long)]
+    pub id: String,
+
+    /// Keys of a validator, as `stash,controller,babe,grandpa,im_online,authority_discovery`
+    /// in SS58 or as 0x prefixed hex. Live networks only take these.
+    #[structopt(long = "validator", parse(try_from_str = parse_validator))]
+    pub validators: Vec<AuthorityKeys>,
+
+    /// Seeds the keys of a validator are derived from, on the dev and local networks
+    /// only as anyone knowing the seed controls the validator.
+    #[structopt(long = "validator-seed")]
+    pub validator_seeds: Vec<String>,
+
+    /// Members of the root, technical and financial committees.
+    #[structopt(long = "root", parse(try_from_str = parse_account))]
+    pub roots: Vec<AccountId>,
+
+    /// Members of the allocations oracles set.
+    #[structopt(long = "oracle", parse(try_from_str = parse_account))]
+    pub oracles: Vec<AccountId>,
+
+    /// Accounts to endow, the testing accounts are endowed if none is given.
+    #[structopt(long = "endowed", parse(try_from_str = parse_account))]
+    pub endowed: Vec<AccountId>,
+
+    /// JSON file listing the vesting grants to create, as
+    /// `[[account, [[start, period, period_count, per_period], ...]], ...]`.
+    #[structopt(long, parse(from_os_str))]
+    pub grants: Option<PathBuf>,
+
+    /// JSON file listing additional balances as `[[account, amount], ...]`, such as the
+    /// one written by `genesis-builder` from an ERC-20 holders snapshot. Amounts are
+    /// added to those of the accounts endowed otherwise.
+    #[structopt(long, parse(from_os_str))]
+    pub balances: Option<PathBuf>,
+
+    /// JSON file written by `genesis-builder` committing to the holders who can claim
+    /// an airdrop, its total is minted at genesis.
+    #[structopt(long, parse(from_os_str))]
+    pub airdrop: Option<PathBuf>,
+
+    /// Amount minted into the company reserve at genesis.
+    #[structopt(long, default_value = "0")]
+    pub reserve_funding: Balance,
+
+    /// Output the genesis storage rather than its human readable representation.
+    #[structopt(long)]
+    pub raw: bool,
+}
+
+impl ChainSpecCmd {
+    pub fn run(&self) -> sc_cli::Result<()> {
+        println!("{}", self.build()?);
+        Ok(())
+    }
+
+    fn build(&self) -> Result<String, String> {
+        if matches!(self.network.chain_type(), ChainType::Live) && !self.validator_seeds.is_empty()
+        {
+            return Err("validators of live networks must be given by their public keys".into());
+        }
+        let authorities = self
+            .validators
+            .iter()
+            .cloned()
+            .chain(
+                self.validator_seeds
+                    .iter()
+                    .map(|seed| get_authority_keys_from_seed(seed)),
+            )
+            .collect::<Vec<_>>();
+        if authorities.is_empty() {
+            return Err("at least one validator is needed".into());
+        }
+        let roots = self.roots.clone();
+        let oracles = self.oracles.clone();
+        let endowed = if self.endowed.is_empty() {
+            None
+        } else {
+            Some(self.endowed.clone())
+        };
+        let grants = match &self.grants {
+            Some(path) => {
+                let raw = fs::read(path).map_err(|e| format!("failed to read grants: {}", e))?;
+                Some(
+                    serde_json::from_slice::<Grants>(&raw)
+                        .map_err(|e| format!("invalid grants: {}", e))?,
+                )
+            }
+            None => None,
+        };
+        let balances = match &self.balances {
+            Some(path) => {
+                let raw = fs::read(path).map_err(|e| format!("failed to read balances: {}", e))?;
+                serde_json::from_slice::<Vec<(AccountId, Balance)>>(&raw)
+                    .map_err(|e| format!("invalid balances: {}", e))?
+            }
+            None => Vec::new(),
+        };
+        let airdrop = match &self.airdrop {
+            Some(path) => {
+                let raw = fs::read(path).map_err(|e| format!("failed to read airdrop: {}", e))?;
+                serde_json::from_slice::<Airdrop>(&raw)
+                    .map_err(|e| format!("invalid airdrop: {}", e))?
+            }
+            None => Airdrop::default(),
+        };
+        let reserve_funding = self.reserve_funding;
+
+        // Both runtimes share the same genesis layout but not the same types.
+        macro_rules! build_spec {
+            ($spec:ident, $runtime:ident, $genesis:ident) => {
+                $spec::from_genesis(
+                    &self.name,
+                    &self.id,
+                    self.network.chain_type(),
+                    move || {
+                        let mut genesis = $genesis(
+                            authorities.clone(),
+                            roots.clone(),
+                            oracles.clone(),
+                            endowed.clone(),
+                            grants.clone(),
+                        );
+                        genesis.pallet_reserve_Instance1 = Some($runtime::CompanyReserveConfig {
+                            initial_funding: reserve_funding,
+                        });
+                        genesis.pallet_airdrop = Some($runtime::AirdropConfig {
+                            root: airdrop.root,
+                            total: airdrop.total,
+                        });
+                        if let Some(config) = genesis.pallet_balances.as_mut() {
+                            merge_balances(&mut config.balances, &balances);
+                        }
+                        genesis
+                    },
+                    vec![],
+                    None,
+                    None,
+                    None,
+                    Default::default(),
+                )
+                .as_json(self.raw)
+            };
+        }
+
+        match self.network {
+            Network::Staging => build_spec!(
+                StagingChainSpec,
+                nodle_staging_runtime,
+                staging_testnet_genesis
+            ),
+            _ => build_spec!(ChainSpec, nodle_chain_runtime, testnet_genesis),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The `--validator` argument of the validator derived from `seed`, with keys
+    /// given both in SS58 and in hex.
+    fn validator(seed: &str) -> String {
+        let (stash, controller, grandpa, babe, im_online, authority_discovery) =
+            get_authority_keys_from_seed(seed);
+        format!(
+            "{},{},{},{},{},{}",
+            stash.to_ss58check(),
+            controller.to_ss58check(),
+            babe.to_ss58check(),
+            grandpa.to_ss58check(),
+            im_online.to_ss58check(),
+            sp_core::bytes::to_hex(authority_discovery.as_ref(), false),
+        )
+    }
+
+    fn chain_spec_cmd(network: &str, validator: &[&str]) -> ChainSpecCmd {
+        let mut args = vec![
+            "chain-spec",
+            "--network",
+            network,
+            "--name",
+            "Test",
+            "--id",
+            "test",
+            "--reserve-funding",
+            "1000",
+        ];
+        args.extend(validator);
+        ChainSpecCmd::from_iter(&args)
+    }
+
+    #[test]
+    fn builds_every_network() {
+        for network in &["dev", "local"] {
+            let cmd = chain_spec_cmd(network, &["--validator-seed", "Alice"]);
+            assert!(cmd.build().is_ok());
+        }
+
+        let alice = validator("Alice");
+        for network in &["dev", "local", "staging", "main"] {
+            let cmd = chain_spec_cmd(network, &["--validator", alice.as_str()]);
+            assert!(cmd.build().is_ok());
+        }
+    }
+
+    #[test]
+    fn live_networks_only_take_public_keys() {
+        for network in &["staging", "main"] {
+            let cmd = chain_spec_cmd(network, &["--validator-seed", "Alice"]);
+            assert!(cmd.build().is_err());
+        }
+        assert!(chain_spec_cmd("main", &[]).build().is_err());
+    }
+
+    #[test]
+    fn parses_validator_keys() {
+        assert_eq!(
+            parse_validator(&validator("Alice")),
+            Ok(get_authority_keys_from_seed("Alice"))
+        );
+
+        let mut keys = validator("Alice")
+            .split(',')
+            .map(String::from)
+            .collect::<Vec<_>>();
+        keys.pop();
+        assert!(parse_validator(&keys.join(",")).is_err());
+        keys.push("0x1234".into());
+        assert!(parse_validator(&keys.join(",")).is_err());
+    }
+
+    #[test]
+    fn reads_the_airdrop_of_genesis_builder() {
+        let airdrop = serde_json::from_str::<Airdrop>(
+            r#"{
+                "root": "0x1111111111111111111111111111111111111111111111111111111111111111",
+                "total": 300,
+                "claims": []
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(airdrop.root, Some(H256::repeat_byte(0x11)));
+        assert_eq!(airdrop.total, 300);
+    }
+
+    #[test]
+    fn merges_balances() {
+        let (alice, bob) = (AccountId::from([1; 32]), AccountId::from([2; 32]));
+        let mut balances = vec![(alice.clone(), 10)];
+        merge_balances(&mut balances, &[(alice.clone(), 5), (bob.clone(), 7)]);
+
+        assert_eq!(balances, vec![(alice, 15), (bob, 7)]);
+    }
+}
@@ -0,0 +1,87 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Telemetry messages reporting the health of the reward system, so that the network
+//! dashboard can show it next to the standard node telemetry.
+
+use crate::indexer::{block_records, IndexedEvent};
+use futures::prelude::*;
+use nodle_chain_primitives::{AccountId, Balance, Block, BlockNumber};
+use nodle_chain_runtime::constants::HOURS;
+use pallet_allocations::OracleStatus;
+use pallet_allocations_rpc::OraclesRuntimeApi;
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_telemetry::{telemetry, SUBSTRATE_INFO};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use std::sync::Arc;
+
+/// Oracles which did not allocate anything for this long are reported as down.
+const ORACLE_LIVENESS: BlockNumber = HOURS;
+
+/// Send an `allocations.stats` message for every new best block: allocations it
+/// contains, budget left and how many oracles are live.
+pub async fn run<C, B>(client: Arc<C>)
+where
+    C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B>,
+    C: ProvideRuntimeApi<Block>,
+    C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus<Balance, BlockNumber>>,
+    B: Backend<Block>,
+{
+    let mut imports = client.import_notification_stream();
+    while let Some(notification) = imports.next().await {
+        if !notification.is_new_best {
+            continue;
+        }
+
+        let (allocations, minted) = block_records(&*client, notification.hash)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|record| match record.event {
+                IndexedEvent::Allocation { amount, fee, .. } => Some(amount.saturating_add(fee)),
+                _ => None,
+            })
+            .fold((0u32, 0 as Balance), |(count, total), minted| {
+                (count + 1, total.saturating_add(minted))
+            });
+
+        let at = BlockId::hash(notification.hash);
+        let api = client.runtime_api();
+        let budget_left = api
+            .budget(&at)
+            .map(|(consumed, budget)| budget.saturating_sub(consumed))
+            .unwrap_or_default();
+        let statuses = api.oracles(&at).unwrap_or_default();
+        let best = *notification.header.number();
+        let live = statuses
+            .iter()
+            .filter(|(_, status)| best.saturating_sub(status.last_seen) <= ORACLE_LIVENESS)
+            .count();
+
+        telemetry!(SUBSTRATE_INFO; "allocations.stats";
+            "height" => best,
+            "best" => ?notification.hash,
+            "allocations" => allocations,
+            "minted" => %minted,
+            "budget_left" => %budget_left,
+            "oracles" => statuses.len(),
+            "oracles_live" => live,
+        );
+    }
+}
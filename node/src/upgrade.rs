@@ -0,0 +1,425 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! `dry-run-upgrade` subcommand, a pre-flight check for runtime upgrades. The
+//! candidate WASM runtime is swapped in on top of the state of a block of the local
+//! database and a block is initialized with it, which runs its migrations. The
+//! resulting storage changes are then reported along with the outcome of invariants
+//! the allocations rely on.
+//!
+//! The node has to be synced beforehand, the state is not fetched from a remote
+//! node. With `--try-state` the storage invariants of the pallets are checked as
+//! well once the migrations ran, which needs a candidate runtime built with the
+//! `try-runtime` feature.
+
+use frame_metadata::{DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed};
+use nodle_chain_executor::Executor;
+use nodle_chain_primitives::{AccountId, Balance, Block, BlockNumber, Header};
+use pallet_allocations::OracleStatus;
+use parity_scale_codec::{Decode, Encode};
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::Backend;
+use sc_executor::{NativeExecutor, WasmExecutionMethod};
+use sc_service::SpawnTaskHandle;
+use sp_blockchain::HeaderBackend;
+use sp_consensus_babe::{
+    digests::{PreDigest, SecondaryPlainPreDigest},
+    BABE_ENGINE_ID,
+};
+use sp_core::{
+    blake2_256,
+    storage::well_known_keys,
+    traits::{RuntimeCode, WrappedRuntimeCode},
+    twox_128,
+};
+use sp_runtime::{
+    generic::{BlockId, Digest, DigestItem},
+    traits::{BlakeTwo256, Header as HeaderT},
+};
+use sp_state_machine::{
+    backend::{Backend as StateBackend, BackendRuntimeCode},
+    ExecutionStrategy, Extensions, OverlayedChanges, StateMachine,
+};
+use sp_version::RuntimeVersion;
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use structopt::StructOpt;
+
+type Oracles = Vec<(AccountId, OracleStatus<Balance, BlockNumber>)>;
+
+pub(crate) fn storage_key(prefix: &str, item: &str) -> Vec<u8> {
+    [twox_128(prefix.as_bytes()), twox_128(item.as_bytes())].concat()
+}
+
+/// Runs runtime calls against the state of a block.
+struct Runner<'a, S> {
+    state: &'a S,
+    executor: NativeExecutor<Executor>,
+    spawn_handle: SpawnTaskHandle,
+}
+
+impl<'a, S: StateBackend<BlakeTwo256>> Runner<'a, S> {
+    fn call(
+        &self,
+        overlay: &mut OverlayedChanges,
+        code: &RuntimeCode,
+        method: &str,
+        data: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        StateMachine::<_, BlakeTwo256, BlockNumber, _>::new(
+            self.state,
+            None,
+            overlay,
+            &self.executor,
+            method,
+            data,
+            Extensions::default(),
+            code,
+            Box::new(self.spawn_handle.clone()),
+        )
+        .execute(ExecutionStrategy::AlwaysWasm)
+        .map_err(|e| format!("{} failed: {}", method, e))
+    }
+
+    fn call_decoded<T: Decode>(
+        &self,
+        overlay: &mut OverlayedChanges,
+        code: &RuntimeCode,
+        method: &str,
+    ) -> Result<T, String> {
+        let raw = self.call(overlay, code, method, &[])?;
+        T::decode(&mut &raw[..]).map_err(|e| format!("{} returned undecodable data: {}", method, e))
+    }
+
+    /// Value of `key` once `overlay` is applied.
+    fn storage(&self, overlay: &OverlayedChanges, key: &[u8]) -> Option<Vec<u8>> {
+        match overlay.storage(key) {
+            Some(value) => value.map(|value| value.to_vec()),
+            None => self.state.storage(key).ok().flatten(),
+        }
+    }
+}
+
+/// Metadata decoded from its SCALE representation is always `Decoded`.
+pub(crate) fn decoded<B, O>(value: &DecodeDifferent<B, O>) -> Option<&O> {
+    match value {
+        DecodeDifferent::Decoded(value) => Some(value),
+        DecodeDifferent::Encode(_) => None,
+    }
+}
+
+/// Name of the storage items of a runtime, keyed by the prefix of their keys.
+fn storage_names(metadata: &[u8]) -> BTreeMap<Vec<u8>, String> {
+    let mut names = BTreeMap::new();
+    let modules = match Vec::<u8>::decode(&mut &metadata[..])
+        .ok()
+        .and_then(|raw| RuntimeMetadataPrefixed::decode(&mut &raw[..]).ok())
+    {
+        Some(RuntimeMetadataPrefixed(_, RuntimeMetadata::V11(metadata))) => metadata.modules,
+        _ => return names,
+    };
+
+    for module in decoded(&modules).into_iter().flatten() {
+        let storage = match module.storage.as_ref().and_then(decoded) {
+            Some(storage) => storage,
+            None => continue,
+        };
+        let prefix = decoded(&storage.prefix).cloned().unwrap_or_default();
+        for entry in decoded(&storage.entries).into_iter().flatten() {
+            let name = decoded(&entry.name).cloned().unwrap_or_default();
+            names.insert(storage_key(&prefix, &name), format!("{}::{}", prefix, name));
+        }
+    }
+    names
+}
+
+/// Outcome of a check, printed in the report.
+struct Check {
+    description: &'static str,
+    outcome: Result<(), String>,
+}
+
+fn check(description: &'static str, holds: bool, details: impl FnOnce() -> String) -> Check {
+    Check {
+        description,
+        outcome: if holds { Ok(()) } else { Err(details()) },
+    }
+}
+
+/// Apply a runtime upgrade on top of the state of a block and check its effects.
+#[derive(Debug, StructOpt)]
+pub struct DryRunUpgradeCmd {
+    /// Candidate WASM runtime.
+    #[structopt(parse(from_os_str))]
+    pub wasm: PathBuf,
+
+    /// Block number whose state the upgrade is applied to, the best block by default.
+    #[structopt(long, value_name = "BLOCK")]
+    pub at: Option<BlockNumber>,
+
+    /// Check the storage invariants of the pallets after the migrations, the
+    /// candidate runtime must be built with the `try-runtime` feature.
+    #[structopt(long)]
+    pub try_state: bool,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub shared_params: SharedParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub pruning_params: PruningParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub database_params: DatabaseParams,
+}
+
+impl DryRunUpgradeCmd {
+    pub fn run<C, B>(
+        &self,
+        client: &C,
+        backend: &B,
+        wasm_method: WasmExecutionMethod,
+        spawn_handle: SpawnTaskHandle,
+    ) -> sc_cli::Result<()>
+    where
+        C: HeaderBackend<Block>,
+        B: Backend<Block>,
+    {
+        let wasm = fs::read(&self.wasm)?;
+        let number = self.at.unwrap_or_else(|| client.info().best_number);
+        let hash = client
+            .hash(number)
+            .map_err(|e| format!("{:?}", e))?
+            .ok_or_else(|| format!("Block {} is not part of the best chain", number))?;
+        let state = backend
+            .state_at(BlockId::hash(hash))
+            .map_err(|e| format!("The state of block {} is unavailable: {:?}", number, e))?;
+
+        let runner = Runner {
+            state: &state,
+            executor: NativeExecutor::<Executor>::new(wasm_method, None, 1),
+            spawn_handle,
+        };
+        let current_code = BackendRuntimeCode::new(&state);
+        let current_code = current_code.runtime_code()?;
+        let candidate_code = WrappedRuntimeCode(wasm.as_slice().into());
+        let candidate_code = RuntimeCode {
+            code_fetcher: &candidate_code,
+            heap_pages: current_code.heap_pages,
+            hash: blake2_256(&wasm).to_vec(),
+        };
+
+        let mut before = OverlayedChanges::default();
+        let current_version: RuntimeVersion =
+            runner.call_decoded(&mut before, &current_code, "Core_version")?;
+        let current_budget: (Balance, Balance) =
+            runner.call_decoded(&mut before, &current_code, "OraclesApi_budget")?;
+        let current_oracles: Oracles =
+            runner.call_decoded(&mut before, &current_code, "OraclesApi_oracles")?;
+
+        // Initializing a block with the candidate runtime runs its migrations
+        let mut after = OverlayedChanges::default();
+        after.set_storage(well_known_keys::CODE.to_vec(), Some(wasm.clone()));
+        let slot_key = storage_key("Babe", "CurrentSlot");
+        let slot = runner
+            .storage(&after, &slot_key)
+            .and_then(|raw| u64::decode(&mut &raw[..]).ok())
+            .unwrap_or_default();
+        let pre_digest = PreDigest::SecondaryPlain(SecondaryPlainPreDigest {
+            authority_index: 0,
+            slot_number: slot + 1,
+        });
+        let header = Header::new(
+            number + 1,
+            Default::default(),
+            Default::default(),
+            hash,
+            Digest {
+                logs: vec![DigestItem::PreRuntime(BABE_ENGINE_ID, pre_digest.encode())],
+            },
+        );
+        let candidate_version: RuntimeVersion =
+            runner.call_decoded(&mut after, &candidate_code, "Core_version")?;
+        let initialized = runner
+            .call(
+                &mut after,
+                &candidate_code,
+                "Core_initialize_block",
+                &header.encode(),
+            )
+            .map(|_| ());
+        let candidate_budget: Result<(Balance, Balance), String> =
+            runner.call_decoded(&mut after, &candidate_code, "OraclesApi_budget");
+        let candidate_oracles: Result<Oracles, String> =
+            runner.call_decoded(&mut after, &candidate_code, "OraclesApi_oracles");
+        let try_state = if self.try_state {
+            Some(
+                runner
+                    .call_decoded::<Result<(), Vec<u8>>>(
+                        &mut after,
+                        &candidate_code,
+                        "TryRuntime_try_state",
+                    )
+                    .and_then(|outcome| {
+                        outcome.map_err(|e| String::from_utf8_lossy(&e).into_owned())
+                    }),
+            )
+        } else {
+            None
+        };
+        let issuance_key = storage_key("Balances", "TotalIssuance");
+        let current_issuance = runner.storage(&OverlayedChanges::default(), &issuance_key);
+        let candidate_issuance = runner.storage(&after, &issuance_key);
+        let names = runner
+            .call(&mut after, &candidate_code, "Metadata_metadata", &[])
+            .map(|metadata| storage_names(&metadata))
+            .unwrap_or_default();
+
+        let mut checks = vec![
+            check(
+                "The spec name is unchanged",
+                candidate_version.spec_name == current_version.spec_name,
+                || {
+                    format!(
+                        "{} became {}",
+                        current_version.spec_name, candidate_version.spec_name
+                    )
+                },
+            ),
+            check(
+                "The spec version is bumped",
+                candidate_version.spec_version > current_version.spec_version,
+                || {
+                    format!(
+                        "{} is not above {}",
+                        candidate_version.spec_version, current_version.spec_version
+                    )
+                },
+            ),
+            Check {
+                description: "Migrations and block initialization succeed",
+                outcome: initialized,
+            },
+            check(
+                "The total issuance is unchanged",
+                candidate_issuance == current_issuance,
+                || "Tokens were minted or burned".into(),
+            ),
+            Check {
+                description: "Coins consumed by the oracles are unchanged and within the budget",
+                outcome: candidate_budget.and_then(|(consumed, budget)| {
+                    if consumed != current_budget.0 {
+                        Err(format!("{} became {}", current_budget.0, consumed))
+                    } else if consumed > budget {
+                        Err(format!(
+                            "{} consumed out of a budget of {}",
+                            consumed, budget
+                        ))
+                    } else {
+                        Ok(())
+                    }
+                }),
+            },
+            Check {
+                description: "The oracles and their statuses are unchanged",
+                outcome: candidate_oracles.and_then(|oracles| {
+                    if oracles == current_oracles {
+                        Ok(())
+                    } else {
+                        Err(format!("{:?} became {:?}", current_oracles, oracles))
+                    }
+                }),
+            },
+        ];
+        if let Some(outcome) = try_state {
+            checks.push(Check {
+                description: "The storage invariants of the pallets hold",
+                outcome,
+            });
+        }
+
+        let changes = after
+            .drain_storage_changes::<_, BlakeTwo256, BlockNumber>(
+                &state,
+                None,
+                hash,
+                &mut Default::default(),
+            )?
+            .main_storage_changes;
+        let mut changed = BTreeMap::<String, usize>::new();
+        for (key, _) in changes {
+            let name = if key == well_known_keys::CODE {
+                ":code".into()
+            } else {
+                key.get(..32)
+                    .and_then(|prefix| names.get(prefix))
+                    .cloned()
+                    .unwrap_or_else(|| format!("0x{}", hex(&key[..key.len().min(32)])))
+            };
+            *changed.entry(name).or_default() += 1;
+        }
+
+        println!(
+            "Upgrade from {} to {} at block #{} ({:?})",
+            current_version, candidate_version, number, hash
+        );
+        println!("\nStorage changes:");
+        for (name, count) in &changed {
+            println!("  {:<50} {} key(s)", name, count);
+        }
+        println!("\nChecks:");
+        let mut failed = 0;
+        for Check {
+            description,
+            outcome,
+        } in checks
+        {
+            match outcome {
+                Ok(()) => println!("  [ok]   {}", description),
+                Err(e) => {
+                    failed += 1;
+                    println!("  [fail] {}: {}", description, e);
+                }
+            }
+        }
+
+        if failed > 0 {
+            return Err(format!("{} check(s) failed", failed).into());
+        }
+        Ok(())
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl CliConfiguration for DryRunUpgradeCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+
+    fn pruning_params(&self) -> Option<&PruningParams> {
+        Some(&self.pruning_params)
+    }
+
+    fn database_params(&self) -> Option<&DatabaseParams> {
+        Some(&self.database_params)
+    }
+}
@@ -0,0 +1,182 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! `verify-runtime` subcommand, checking a locally built runtime against the code
+//! registry. Validators rebuild the runtime deterministically, see
+//! `scripts/build-runtime.sh`, and make sure the WASM enacted on chain is the one
+//! built from the audited sources.
+
+use crate::upgrade::storage_key;
+use nodle_chain_primitives::{AccountId, Block, BlockNumber, Hash};
+use pallet_code_registry::CodeRecord;
+use parity_scale_codec::Decode;
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{Backend, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+    blake2_256,
+    crypto::Ss58Codec,
+    storage::{well_known_keys, StorageKey},
+};
+use sp_runtime::generic::BlockId;
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+type Record = CodeRecord<AccountId, BlockNumber, Hash>;
+
+/// Check a locally built runtime against the runtimes enacted on chain.
+#[derive(Debug, StructOpt)]
+pub struct VerifyRuntimeCmd {
+    /// WASM runtime to verify, the compact one produced by `scripts/build-runtime.sh`.
+    #[structopt(parse(from_os_str))]
+    pub wasm: PathBuf,
+
+    /// Block to read the registry at, the best block by default.
+    #[structopt(long, value_name = "BLOCK")]
+    pub at: Option<BlockNumber>,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub shared_params: SharedParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub pruning_params: PruningParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub database_params: DatabaseParams,
+}
+
+/// Tell where `code_hash` stands with regard to the registry and the code running at
+/// the block the registry was read at.
+fn verify(records: &[Record], running: Hash, code_hash: Hash) -> Result<String, String> {
+    let record = match records.iter().find(|record| record.code_hash == code_hash) {
+        Some(record) => record,
+        // Runtimes enacted before the registry was introduced are not recorded
+        None if code_hash == running => {
+            return Ok(format!(
+                "The runtime {:?} is running but was enacted before the registry",
+                code_hash
+            ))
+        }
+        None => {
+            return Err(format!(
+                "The runtime {:?} was never enacted, the chain runs {:?}",
+                code_hash, running
+            ))
+        }
+    };
+
+    let proposer = record
+        .proposer
+        .as_ref()
+        .map(|who| who.to_ss58check())
+        .unwrap_or_else(|| "an unannounced proposer".into());
+    let status = if code_hash == running {
+        "is running"
+    } else {
+        "was replaced since"
+    };
+    Ok(format!(
+        "The runtime {:?}, spec version {}, was enacted at block {} and {}. It was proposed by {}.",
+        code_hash, record.spec_version, record.enacted_at, status, proposer
+    ))
+}
+
+impl VerifyRuntimeCmd {
+    pub fn run<C, B>(&self, client: &C) -> sc_cli::Result<()>
+    where
+        C: HeaderBackend<Block> + StorageProvider<Block, B>,
+        B: Backend<Block>,
+    {
+        let code_hash = Hash::from(blake2_256(&fs::read(&self.wasm)?));
+
+        let number = self.at.unwrap_or_else(|| client.info().best_number);
+        let at = BlockId::Number(number);
+        let read = |key: Vec<u8>| {
+            client
+                .storage(&at, &StorageKey(key))
+                .map_err(|e| format!("Unable to read the state of block {}: {:?}", number, e))
+        };
+
+        let running = read(well_known_keys::CODE.to_vec())?
+            .map(|code| Hash::from(blake2_256(&code.0)))
+            .ok_or_else(|| format!("No runtime is stored at block {}", number))?;
+        let records = match read(storage_key("CodeRegistry", "Enacted"))? {
+            Some(raw) => Vec::<Record>::decode(&mut &raw.0[..])
+                .map_err(|e| format!("Unable to decode the registry: {:?}", e))?,
+            None => Vec::new(),
+        };
+
+        println!("{}", verify(&records, running, code_hash)?);
+        Ok(())
+    }
+}
+
+impl CliConfiguration for VerifyRuntimeCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+
+    fn pruning_params(&self) -> Option<&PruningParams> {
+        Some(&self.pruning_params)
+    }
+
+    fn database_params(&self) -> Option<&DatabaseParams> {
+        Some(&self.database_params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(code: u8, enacted_at: BlockNumber) -> Record {
+        CodeRecord {
+            code_hash: Hash::repeat_byte(code),
+            spec_version: code.into(),
+            proposer: Some(AccountId::from([1; 32])),
+            enacted_at,
+        }
+    }
+
+    #[test]
+    fn enacted_runtimes_are_verified() {
+        let records = vec![record(1, 10), record(2, 20)];
+        let running = Hash::repeat_byte(2);
+
+        let current = verify(&records, running, Hash::repeat_byte(2)).unwrap();
+        assert!(current.contains("enacted at block 20 and is running"));
+        let replaced = verify(&records, running, Hash::repeat_byte(1)).unwrap();
+        assert!(replaced.contains("enacted at block 10 and was replaced since"));
+    }
+
+    #[test]
+    fn unknown_runtimes_are_rejected() {
+        let records = vec![record(1, 10)];
+
+        assert!(verify(&records, Hash::repeat_byte(1), Hash::repeat_byte(3)).is_err());
+    }
+
+    #[test]
+    fn runtimes_predating_the_registry_are_recognized() {
+        assert!(verify(&[], Hash::repeat_byte(1), Hash::repeat_byte(1)).is_ok());
+        assert!(verify(&[], Hash::repeat_byte(1), Hash::repeat_byte(2)).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Webhooks notified when allocations are finalized, so that off-chain systems can
+//! react to payouts without running a node client of their own.
+//!
+//! Every finalized block with allocations is POSTed as JSON to each configured URL.
+//! When a secret is configured the body is signed with HMAC-SHA256 and the hex
+//! encoded signature sent in the `X-Nodle-Signature` header as `sha256=<signature>`.
+//! Failed deliveries are retried with an exponential backoff, then dropped.
+
+use crate::indexer::{block_records, IndexedEvent, IndexedRecord};
+use futures::prelude::*;
+use hmac::{Hmac, Mac, NewMac};
+use nodle_chain_primitives::{AccountId, Balance, Block, BlockNumber, Hash};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use serde::Serialize;
+use sha2::Sha256;
+use sp_blockchain::HeaderBackend;
+use sp_core::{hexdisplay::HexDisplay, Bytes};
+use sp_runtime::traits::Header as HeaderT;
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+pub const SIGNATURE_HEADER: &str = "X-Nodle-Signature";
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub urls: Vec<String>,
+    /// Key the payloads are signed with, unsigned if `None`
+    pub secret: Option<String>,
+    /// Number of times a failed delivery is retried
+    pub retries: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalizedAllocation {
+    pub event_index: u32,
+    pub to: AccountId,
+    /// Amount received by the grantee, protocol fee excluded
+    pub amount: Balance,
+    /// Amount taken by the protocol
+    pub fee: Balance,
+    pub proof: Bytes,
+}
+
+/// Body of the webhook requests.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+    pub block_number: BlockNumber,
+    pub block_hash: Hash,
+    pub allocations: Vec<FinalizedAllocation>,
+}
+
+impl Payload {
+    /// Payload for the allocations among `records`, `None` if there are none.
+    fn from_records(
+        block_number: BlockNumber,
+        block_hash: Hash,
+        records: Vec<IndexedRecord>,
+    ) -> Option<Self> {
+        let allocations = records
+            .into_iter()
+            .filter_map(|record| match record.event {
+                IndexedEvent::Allocation {
+                    to,
+                    amount,
+                    fee,
+                    proof,
+                } => Some(FinalizedAllocation {
+                    event_index: record.event_index,
+                    to,
+                    amount,
+                    fee,
+                    proof: Bytes(proof),
+                }),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if allocations.is_empty() {
+            None
+        } else {
+            Some(Self {
+                block_number,
+                block_hash,
+                allocations,
+            })
+        }
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of `body` keyed with `secret`.
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_varkey(secret.as_bytes())
+        .expect("HMAC accepts keys of any length; qed");
+    mac.update(body);
+    HexDisplay::from(&mac.finalize().into_bytes().as_slice()).to_string()
+}
+
+/// Deliver `body` to `url`, retrying with an exponential backoff.
+fn deliver(client: &reqwest::blocking::Client, config: &Config, url: &str, body: &[u8]) {
+    let mut attempt = 0;
+    loop {
+        let mut request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+        if let Some(secret) = &config.secret {
+            request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, body)));
+        }
+
+        match request
+            .send()
+            .and_then(|response| response.error_for_status())
+        {
+            Ok(_) => return,
+            Err(e) if attempt < config.retries => {
+                log::debug!(target: "webhooks", "Delivery to {} failed: {}", url, e);
+                thread::sleep(Duration::from_secs(1 << attempt.min(6)));
+                attempt += 1;
+            }
+            Err(e) => {
+                log::warn!(target: "webhooks", "Dropping a notification to {}: {}", url, e);
+                return;
+            }
+        }
+    }
+}
+
+/// Notify the webhooks of the allocations of every finalized block, to be spawned as
+/// a background task of full nodes.
+pub async fn run<C, B>(client: Arc<C>, config: Config)
+where
+    C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B>,
+    B: Backend<Block>,
+{
+    // The blocking client can not be used from the node's async tasks, deliveries get
+    // a thread of their own and are made in the order blocks were finalized
+    let (sender, receiver) = mpsc::channel::<Vec<u8>>();
+    let delivery_config = config.clone();
+    thread::spawn(move || {
+        let client = match reqwest::blocking::Client::builder()
+            .timeout(TIMEOUT)
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                log::error!(target: "webhooks", "Failed to build the HTTP client: {}", e);
+                return;
+            }
+        };
+        for body in receiver {
+            for url in &delivery_config.urls {
+                deliver(&client, &delivery_config, url, &body);
+            }
+        }
+    });
+
+    let mut notified: Option<BlockNumber> = None;
+    let mut finality = client.finality_notification_stream();
+    while let Some(notification) = finality.next().await {
+        let finalized = *notification.header.number();
+        // Finality notifications may skip blocks, notify everything in between
+        let next = notified.map_or(finalized, |notified| notified + 1);
+        for number in next..=finalized {
+            let payload = client
+                .hash(number)
+                .map_err(|e| format!("{:?}", e))
+                .and_then(|hash| hash.ok_or_else(|| "unknown block".to_string()))
+                .and_then(|hash| {
+                    block_records(&*client, hash)
+                        .map(|records| Payload::from_records(number, hash, records))
+                        .map_err(|e| format!("{:?}", e))
+                });
+            match payload {
+                Ok(Some(payload)) => {
+                    let body = serde_json::to_vec(&payload).expect("payloads serialize; qed");
+                    if sender.send(body).is_err() {
+                        return;
+                    }
+                }
+                Ok(None) => (),
+                Err(e) => log::warn!(target: "webhooks", "Failed to read block {}: {}", number, e),
+            }
+        }
+        notified = Some(finalized);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_are_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn payloads_only_carry_allocations() {
+        let hash = Hash::repeat_byte(1);
+        let record = |event_index, event| IndexedRecord {
+            block_number: 7,
+            block_hash: hash,
+            event_index,
+            event,
+        };
+        let allocation = IndexedEvent::Allocation {
+            to: AccountId::from([2; 32]),
+            amount: 95,
+            fee: 5,
+            proof: vec![0xaa],
+        };
+        let claim = IndexedEvent::VestingClaimed {
+            who: AccountId::from([3; 32]),
+            locked: 10,
+        };
+
+        assert_eq!(
+            Payload::from_records(7, hash, vec![record(0, claim.clone())]),
+            None
+        );
+
+        let payload = Payload::from_records(7, hash, vec![record(0, claim), record(1, allocation)])
+            .expect("there is an allocation");
+        assert_eq!(payload.allocations.len(), 1);
+        assert_eq!(payload.allocations[0].event_index, 1);
+
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["blockNumber"], 7);
+        assert_eq!(json["allocations"][0]["proof"], "0xaa");
+    }
+}
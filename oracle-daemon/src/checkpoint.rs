@@ -0,0 +1,145 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Durable checkpoints of the submissions. A batch is checkpointed before it is
+//! signed and the checkpoint is only dropped once its rewards are recorded as
+//! submitted, so that a crash at any point leaves enough behind to tell whether the
+//! batch reached the chain and to submit it again, unchanged, if it did not.
+
+use crate::source::Reward;
+use nodle_chain_primitives::{Hash, Index};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fs, path::Path};
+
+const SUBMITTED_PREFIX: &[u8] = b"submitted/";
+const CHECKPOINT_KEY: &[u8] = b"checkpoint";
+const LAST_ROOT_KEY: &[u8] = b"last_root";
+
+/// A batch on its way to the chain.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Checkpoint {
+    /// Merkle root of the batch, every allocation carries it as its proof
+    pub root: Hash,
+    pub rewards: Vec<Reward>,
+    /// Nonce the batch is signed with, unknown when the node signs it
+    pub nonce: Option<Index>,
+}
+
+/// Rewards already submitted, with the root of their batch unless imported from a
+/// JSON ledger, and the batch being submitted if any, stored in a sled database.
+pub struct Store {
+    db: sled::Db,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let db = sled::open(path)
+            .map_err(|e| format!("Failed to open the database {}: {}", path.display(), e))?;
+        Ok(Self { db })
+    }
+
+    /// Record the rewards of the JSON ledger kept by previous versions of the daemon
+    /// as submitted. Missing ledgers are ignored, importing one twice is harmless.
+    pub fn import_ledger(&self, path: &Path) -> Result<usize, String> {
+        let submitted: BTreeSet<String> = match fs::read(path) {
+            Ok(raw) => serde_json::from_slice(&raw)
+                .map_err(|e| format!("Corrupted state file {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+
+        let mut batch = sled::Batch::default();
+        for id in &submitted {
+            batch.insert(submitted_key(id), Vec::<u8>::new());
+        }
+        self.apply(batch)?;
+        Ok(submitted.len())
+    }
+
+    pub fn contains(&self, id: &str) -> Result<bool, String> {
+        self.db
+            .contains_key(submitted_key(id))
+            .map_err(|e| format!("Failed to read the database: {}", e))
+    }
+
+    /// Batch interrupted by a crash or a failed submission.
+    pub fn checkpoint(&self) -> Result<Option<Checkpoint>, String> {
+        let raw = match self
+            .db
+            .get(CHECKPOINT_KEY)
+            .map_err(|e| format!("Failed to read the database: {}", e))?
+        {
+            Some(raw) => raw,
+            None => return Ok(None),
+        };
+        serde_json::from_slice(&raw)
+            .map(Some)
+            .map_err(|e| format!("Corrupted checkpoint: {}", e))
+    }
+
+    /// Root of the last batch known to have reached the node.
+    pub fn last_root(&self) -> Result<Option<Hash>, String> {
+        self.db
+            .get(LAST_ROOT_KEY)
+            .map(|raw| {
+                raw.filter(|raw| raw.len() == 32)
+                    .map(|raw| Hash::from_slice(&raw))
+            })
+            .map_err(|e| format!("Failed to read the database: {}", e))
+    }
+
+    /// Persist `checkpoint` before anything is signed. Only one batch is submitted at
+    /// a time, the previous checkpoint must have been completed or discarded.
+    pub fn begin(&self, checkpoint: &Checkpoint) -> Result<(), String> {
+        let raw = serde_json::to_vec(checkpoint).map_err(|e| e.to_string())?;
+        let mut batch = sled::Batch::default();
+        batch.insert(CHECKPOINT_KEY, raw);
+        self.apply(batch)
+    }
+
+    /// Record the rewards of the checkpointed batch as submitted and drop the
+    /// checkpoint, atomically.
+    pub fn complete(&self, checkpoint: &Checkpoint) -> Result<(), String> {
+        let mut batch = sled::Batch::default();
+        for reward in &checkpoint.rewards {
+            batch.insert(submitted_key(&reward.id), checkpoint.root.as_bytes());
+        }
+        batch.insert(LAST_ROOT_KEY, checkpoint.root.as_bytes());
+        batch.remove(CHECKPOINT_KEY);
+        self.apply(batch)
+    }
+
+    /// Drop the checkpoint without recording its rewards, they will be part of a
+    /// new batch.
+    pub fn discard(&self) -> Result<(), String> {
+        let mut batch = sled::Batch::default();
+        batch.remove(CHECKPOINT_KEY);
+        self.apply(batch)
+    }
+
+    fn apply(&self, batch: sled::Batch) -> Result<(), String> {
+        self.db
+            .apply_batch(batch)
+            .and_then(|_| self.db.flush().map(|_| ()))
+            .map_err(|e| format!("Failed to write the database: {}", e))
+    }
+}
+
+fn submitted_key(id: &str) -> Vec<u8> {
+    [SUBMITTED_PREFIX, id.as_bytes()].concat()
+}
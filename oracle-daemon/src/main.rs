@@ -0,0 +1,163 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Oracle daemon: periodically reads the rewards owed to contributors from a data
+//! source, groups them in batches committed to by a merkle root and submits them as
+//! allocations, signed by the node's keystore or by a remote signer. Every batch is
+//! checkpointed before it is submitted and resumed after a crash.
+
+mod checkpoint;
+mod rpc;
+mod source;
+mod submitter;
+
+use checkpoint::{Checkpoint, Store};
+use nodle_chain_primitives::AccountId;
+use sp_core::crypto::Ss58Codec;
+use std::{path::PathBuf, thread, time::Duration};
+use structopt::StructOpt;
+use submitter::{Signer, Submission, Submitter};
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "oracle-daemon")]
+struct Opt {
+    /// JSON-RPC endpoint of the node. Signing with the node's keystore requires it to
+    /// accept unsafe RPC calls.
+    #[structopt(long, default_value = "http://localhost:9933")]
+    node_url: String,
+
+    /// Where to read the rewards from, either an HTTP(S) URL or a path to a JSON file.
+    #[structopt(long)]
+    source: String,
+
+    /// Database recording the rewards that were already submitted and the batch
+    /// being submitted.
+    #[structopt(long, default_value = "oracle-daemon-db")]
+    db: PathBuf,
+
+    /// JSON ledger of the rewards submitted by previous versions of the daemon,
+    /// imported into the database on start up.
+    #[structopt(long, default_value = "oracle-daemon-state.json")]
+    state: PathBuf,
+
+    /// Drop the checkpointed batch instead of resuming it, its rewards are submitted
+    /// again in a new batch. Only use this once sure the batch never reached the
+    /// chain.
+    #[structopt(long)]
+    discard_checkpoint: bool,
+
+    /// Seconds to wait between two reads of the source.
+    #[structopt(long, default_value = "60")]
+    poll_interval: u64,
+
+    /// Maximum number of allocations submitted in a single transaction.
+    #[structopt(long, default_value = "100")]
+    batch_size: usize,
+
+    /// Number of times a failed submission is retried.
+    #[structopt(long, default_value = "5")]
+    retries: u32,
+
+    /// Sign with the remote signer at this URL instead of the node's keystore.
+    #[structopt(long, requires = "oracle")]
+    remote_signer: Option<String>,
+
+    /// Account of the oracle, required when using a remote signer.
+    #[structopt(long, parse(try_from_str = AccountId::from_ss58check))]
+    oracle: Option<AccountId>,
+}
+
+/// Submit the batch of `checkpoint` and record its rewards as submitted.
+fn submit(submitter: &Submitter, store: &Store, checkpoint: &Checkpoint) -> Result<(), String> {
+    match submitter
+        .submit(checkpoint)
+        .map_err(|e| format!("Failed to submit batch {:?}: {}", checkpoint.root, e))?
+    {
+        Submission::Sent(hash) => log::info!(
+            "Submitted {} allocations in {:?}",
+            checkpoint.rewards.len(),
+            hash
+        ),
+        Submission::Included => log::info!(
+            "Batch {:?} of {} allocations was already included",
+            checkpoint.root,
+            checkpoint.rewards.len()
+        ),
+    }
+    store.complete(checkpoint)
+}
+
+fn run_once(opt: &Opt, submitter: &Submitter, store: &Store) -> Result<usize, String> {
+    // Nothing else is submitted before the interrupted batch made it
+    if let Some(checkpoint) = store.checkpoint()? {
+        log::info!("Resuming the submission of batch {:?}", checkpoint.root);
+        submit(submitter, store, &checkpoint)?;
+    }
+
+    let mut rewards = Vec::new();
+    for reward in source::fetch(&opt.source)? {
+        if !store.contains(&reward.id)? {
+            rewards.push(reward);
+        }
+    }
+
+    for batch in rewards.chunks(opt.batch_size.max(1)) {
+        let checkpoint = submitter
+            .checkpoint(batch)
+            .map_err(|e| format!("Failed to prepare a batch: {}", e))?;
+        store.begin(&checkpoint)?;
+        submit(submitter, store, &checkpoint)?;
+    }
+
+    Ok(rewards.len())
+}
+
+fn main() -> Result<(), String> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let opt = Opt::from_args();
+
+    let signer = match (&opt.remote_signer, &opt.oracle) {
+        (Some(url), Some(oracle)) => Signer::Remote {
+            url: url.clone(),
+            oracle: oracle.clone(),
+        },
+        _ => Signer::Keystore,
+    };
+    let submitter = Submitter::new(rpc::Client::new(&opt.node_url), signer, opt.retries);
+    let store = Store::open(&opt.db)?;
+    let imported = store.import_ledger(&opt.state)?;
+    if imported > 0 {
+        log::info!("Imported {} rewards from {}", imported, opt.state.display());
+    }
+    if opt.discard_checkpoint {
+        if let Some(checkpoint) = store.checkpoint()? {
+            log::warn!("Discarding the checkpoint of batch {:?}", checkpoint.root);
+            store.discard()?;
+        }
+    }
+    if let Some(root) = store.last_root()? {
+        log::info!("Last batch submitted: {:?}", root);
+    }
+
+    loop {
+        if let Err(e) = run_once(&opt, &submitter, &store) {
+            log::error!("{}", e);
+        }
+        thread::sleep(Duration::from_secs(opt.poll_interval));
+    }
+}
@@ -0,0 +1,131 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Minimal blocking JSON-RPC client.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+#[derive(Debug)]
+pub enum Error {
+    Transport(reqwest::Error),
+    /// The node answered with an error
+    Rpc {
+        code: i64,
+        message: String,
+    },
+    /// The node answered with something we did not expect
+    Decode(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Transport(e) => write!(f, "transport error: {}", e),
+            Error::Rpc { code, message } => write!(f, "node error {}: {}", code, message),
+            Error::Decode(e) => write!(f, "unexpected answer: {}", e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Transport(e)
+    }
+}
+
+#[derive(Serialize)]
+struct RpcRequest<'a, P> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: P,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "T: DeserializeOwned")]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+pub struct Client {
+    url: String,
+    http: reqwest::blocking::Client,
+    next_id: AtomicU64,
+}
+
+impl Client {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.into(),
+            http: reqwest::blocking::Client::new(),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Call `method`. Requests and responses are (de)serialized directly rather than
+    /// through `serde_json::Value` which can not represent balances.
+    pub fn call<P: Serialize, T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<T, Error> {
+        self.call_optional(method, params)?
+            .ok_or_else(|| Error::Decode("neither a result nor an error".into()))
+    }
+
+    /// Call `method` whose result may be `null`, such as `state_getStorage` for a key
+    /// without a value.
+    pub fn call_optional<P: Serialize, T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<Option<T>, Error> {
+        let request = serde_json::to_vec(&RpcRequest {
+            jsonrpc: "2.0",
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            method,
+            params,
+        })
+        .map_err(|e| Error::Decode(e.to_string()))?;
+        let response = self
+            .http
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(request)
+            .send()?
+            .bytes()?;
+        let response: RpcResponse<T> =
+            serde_json::from_slice(&response).map_err(|e| Error::Decode(e.to_string()))?;
+
+        match (response.result, response.error) {
+            (_, Some(RpcError { code, message })) => Err(Error::Rpc { code, message }),
+            (result, None) => Ok(result),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rewards data source.
+
+use nodle_chain_primitives::{AccountId, Balance};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fs;
+
+/// A reward owed to a contributor, as served by the data source.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reward {
+    /// Unique identifier of the reward, used to never submit it twice
+    pub id: String,
+    pub to: AccountId,
+    /// Amount to allocate, protocol fee included, as a decimal string since it does
+    /// not fit in a JSON number
+    #[serde(
+        deserialize_with = "balance_from_str",
+        serialize_with = "balance_to_str"
+    )]
+    pub amount: Balance,
+}
+
+fn balance_from_str<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Balance, D::Error> {
+    let amount = String::deserialize(deserializer)?;
+    amount.parse().map_err(serde::de::Error::custom)
+}
+
+fn balance_to_str<S: Serializer>(amount: &Balance, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&amount.to_string())
+}
+
+/// Read the rewards currently owed, `source` is either an HTTP(S) URL or a path to
+/// a JSON file holding an array of rewards.
+pub fn fetch(source: &str) -> Result<Vec<Reward>, String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        reqwest::blocking::get(source)
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json())
+            .map_err(|e| format!("Failed to fetch rewards from {}: {}", source, e))
+    } else {
+        let raw = fs::read(source).map_err(|e| format!("Failed to read {}: {}", source, e))?;
+        serde_json::from_slice(&raw).map_err(|e| format!("Invalid rewards in {}: {}", source, e))
+    }
+}
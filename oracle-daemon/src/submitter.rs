@@ -0,0 +1,389 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Signing and submission of allocation batches, retried until they reach the node's
+//! transaction pool. Before every attempt the pool and the chain are searched for the
+//! batch so that it is never allocated twice, even across restarts.
+
+use crate::{checkpoint::Checkpoint, rpc, source::Reward};
+use chain_merkle as merkle;
+use frame_support::storage::StorageDoubleMap;
+use nodle_chain_primitives::{AccountId, Balance, Hash, Header, Index};
+use nodle_chain_runtime::{
+    Call, PayoutEraDuration, Runtime, SignedExtra, SignedPayload, UncheckedExtrinsic,
+};
+use pallet_allocations::BatchRoots;
+use parity_scale_codec::Encode;
+use serde::{Deserialize, Serialize};
+use sp_core::{sr25519, Bytes};
+use sp_runtime::{generic::Era, MultiSignature};
+use sp_version::RuntimeVersion;
+use std::{fmt, thread, time::Duration};
+
+#[derive(Debug)]
+pub enum Error {
+    EmptyBatch,
+    Rpc(rpc::Error),
+    /// The remote signer failed or returned an invalid signature
+    Signer(String),
+    /// The nonce of the batch was used but the batch with this root is neither
+    /// pending nor on chain, it must be looked into before being submitted again
+    Unresolved(Hash),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::EmptyBatch => write!(f, "the batch is empty"),
+            Error::Rpc(e) => write!(f, "{}", e),
+            Error::Signer(e) => write!(f, "remote signer error: {}", e),
+            Error::Unresolved(root) => write!(
+                f,
+                "the nonce of batch {:?} was used by another transaction",
+                root
+            ),
+        }
+    }
+}
+
+impl From<rpc::Error> for Error {
+    fn from(e: rpc::Error) -> Self {
+        Error::Rpc(e)
+    }
+}
+
+/// Who signs the allocations.
+pub enum Signer {
+    /// The node, with the oracle key of its keystore
+    Keystore,
+    /// A remote service answering `{ account, payload }` POST requests with
+    /// `{ signature }`, all bytes being hex encoded and signatures sr25519 ones
+    Remote { url: String, oracle: AccountId },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AllocationRequest<'a> {
+    to: &'a AccountId,
+    amount: Balance,
+    proof: Bytes,
+}
+
+#[derive(Serialize)]
+struct SignRequest<'a> {
+    account: &'a AccountId,
+    payload: Bytes,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: Bytes,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingAllocation {
+    hash: Hash,
+    oracle: Option<AccountId>,
+    proof: Vec<u8>,
+}
+
+/// Where a checkpointed batch stands.
+pub enum Submission {
+    /// The batch was sent, or is still waiting in the pool, in this transaction
+    Sent(Hash),
+    /// The batch is already part of the chain
+    Included,
+}
+
+/// Leaves of the merkle tree committing to `batch`, in the order of its rewards.
+fn leaves(batch: &[Reward]) -> Vec<Hash> {
+    batch
+        .iter()
+        .map(|reward| merkle::leaf(&reward.to, reward.amount))
+        .collect()
+}
+
+pub struct Submitter {
+    client: rpc::Client,
+    signer: Signer,
+    retries: u32,
+}
+
+impl Submitter {
+    pub fn new(client: rpc::Client, signer: Signer, retries: u32) -> Self {
+        Self {
+            client,
+            signer,
+            retries,
+        }
+    }
+
+    /// Checkpoint of `batch`, to be persisted before it is submitted. Batches signed
+    /// remotely commit to their nonce so that they can only be included once.
+    pub fn checkpoint(&self, batch: &[Reward]) -> Result<Checkpoint, Error> {
+        let root = merkle::root(&leaves(batch)).ok_or(Error::EmptyBatch)?;
+        // The node accounts for the transactions we have in its pool
+        let nonce = match &self.signer {
+            Signer::Remote { oracle, .. } => {
+                Some(self.client.call("system_accountNextIndex", (oracle,))?)
+            }
+            Signer::Keystore => None,
+        };
+
+        Ok(Checkpoint {
+            root,
+            rewards: batch.to_vec(),
+            nonce,
+        })
+    }
+
+    /// Submit the batch of `checkpoint`, every allocation carrying the batch's
+    /// merkle root as its proof, unless it already reached the node.
+    pub fn submit(&self, checkpoint: &Checkpoint) -> Result<Submission, Error> {
+        let mut attempt = 0;
+        loop {
+            // A failed attempt, or a run that crashed, may still have reached the node
+            let result = match self.find(checkpoint) {
+                Ok(Some(submission)) => return Ok(submission),
+                Ok(None) => self.try_submit(checkpoint).map(Submission::Sent),
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(submission) => return Ok(submission),
+                Err(e @ Error::Unresolved(_)) => return Err(e),
+                Err(e) if attempt < self.retries => {
+                    log::warn!("Submission attempt {} failed: {}", attempt + 1, e);
+                    thread::sleep(Duration::from_secs(1 << attempt.min(6)));
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Look for the batch in the pool, then on chain. When its nonce is known and
+    /// was used, the batch must have been included or replaced.
+    fn find(&self, checkpoint: &Checkpoint) -> Result<Option<Submission>, Error> {
+        if let Some(hash) = self.pending_with_root(&checkpoint.root)? {
+            return Ok(Some(Submission::Sent(hash)));
+        }
+        if self.included(&checkpoint.root)? {
+            return Ok(Some(Submission::Included));
+        }
+        if let (Signer::Remote { oracle, .. }, Some(nonce)) = (&self.signer, checkpoint.nonce) {
+            let next: Index = self.client.call("system_accountNextIndex", (oracle,))?;
+            if next > nonce {
+                return Err(Error::Unresolved(checkpoint.root));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn pending_with_root(&self, root: &Hash) -> Result<Option<Hash>, Error> {
+        let oracle = match &self.signer {
+            Signer::Remote { oracle, .. } => Some(oracle),
+            Signer::Keystore => None,
+        };
+
+        Ok(self
+            .client
+            .call::<_, Vec<PendingAllocation>>("allocations_pending", Vec::<()>::new())?
+            .into_iter()
+            .find(|pending| {
+                pending.proof == root.as_bytes()
+                    && oracle.map_or(true, |oracle| pending.oracle.as_ref() == Some(oracle))
+            })
+            .map(|pending| pending.hash))
+    }
+
+    /// Whether an allocation referencing `root` was included during the current or
+    /// the previous payout era. Roots of older eras are pruned once they are settled.
+    fn included(&self, root: &Hash) -> Result<bool, Error> {
+        let header: Header = self.client.call("chain_getHeader", Vec::<()>::new())?;
+        let era = header.number / PayoutEraDuration::get().max(1);
+
+        for era in [era, era.saturating_sub(1)].iter() {
+            let key = BatchRoots::hashed_key_for(era, root);
+            let value: Option<Bytes> = self
+                .client
+                .call_optional("state_getStorage", (Bytes(key),))?;
+            if value.is_some() {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn try_submit(&self, checkpoint: &Checkpoint) -> Result<Hash, Error> {
+        let root = &checkpoint.root;
+        match &self.signer {
+            Signer::Keystore => {
+                let requests = checkpoint
+                    .rewards
+                    .iter()
+                    .map(|reward| AllocationRequest {
+                        to: &reward.to,
+                        amount: reward.amount,
+                        proof: Bytes(root.as_bytes().to_vec()),
+                    })
+                    .collect::<Vec<_>>();
+                Ok(self
+                    .client
+                    .call("oracle_submitAllocationBatch", (requests,))?)
+            }
+            Signer::Remote { url, oracle } => {
+                let xt = self.sign_remotely(url, oracle, checkpoint)?;
+                Ok(self
+                    .client
+                    .call("author_submitExtrinsic", (Bytes(xt.encode()),))?)
+            }
+        }
+    }
+
+    fn sign_remotely(
+        &self,
+        url: &str,
+        oracle: &AccountId,
+        checkpoint: &Checkpoint,
+    ) -> Result<UncheckedExtrinsic, Error> {
+        let proof = checkpoint.root.as_bytes().to_vec();
+        // Allocations of a batch that fail are skipped, events tell which ones
+        let call = match checkpoint.rewards.as_slice() {
+            [] => return Err(Error::EmptyBatch),
+            [reward] => Call::Allocations(pallet_allocations::Call::allocate(
+                reward.to.clone(),
+                reward.amount,
+                proof,
+            )),
+            rewards => Call::Allocations(pallet_allocations::Call::allocate_batch(
+                rewards
+                    .iter()
+                    .map(|reward| (reward.to.clone(), reward.amount))
+                    .collect(),
+                proof,
+            )),
+        };
+
+        // Resubmissions reuse the nonce of the checkpoint, only one of them can make it
+        let nonce: Index = match checkpoint.nonce {
+            Some(nonce) => nonce,
+            None => self.client.call("system_accountNextIndex", (oracle,))?,
+        };
+        let version: RuntimeVersion = self
+            .client
+            .call("state_getRuntimeVersion", Vec::<()>::new())?;
+        let genesis: Hash = self.client.call("chain_getBlockHash", (0,))?;
+
+        let extra: SignedExtra = (
+            frame_system::CheckSpecVersion::<Runtime>::new(),
+            frame_system::CheckTxVersion::<Runtime>::new(),
+            frame_system::CheckGenesis::<Runtime>::new(),
+            frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+            frame_system::CheckNonce::<Runtime>::from(nonce),
+            frame_system::CheckWeight::<Runtime>::new(),
+            pallet_rate_limit::CheckRateLimit::<Runtime>::new(),
+            pallet_device_sessions::CheckSessionKey::<Runtime>::new(),
+            pallet_allocations::CheckOracle::<Runtime>::new(),
+            pallet_feeless::ChargeFeelessOrPayment::<Runtime>::from(
+                pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+            ),
+        );
+        // Immortal transactions commit to the genesis hash in place of their era
+        let payload = SignedPayload::from_raw(
+            call,
+            extra,
+            (
+                version.spec_version,
+                version.transaction_version,
+                genesis,
+                genesis,
+                (),
+                (),
+                (),
+                (),
+                (),
+            ),
+        );
+
+        let response: SignResponse = payload
+            .using_encoded(|payload| {
+                reqwest::blocking::Client::new()
+                    .post(url)
+                    .json(&SignRequest {
+                        account: oracle,
+                        payload: Bytes(payload.to_vec()),
+                    })
+                    .send()
+                    .and_then(|response| response.error_for_status())
+                    .and_then(|response| response.json())
+            })
+            .map_err(|e| Error::Signer(e.to_string()))?;
+        if response.signature.len() != 64 {
+            return Err(Error::Signer("signatures are 64 bytes long".into()));
+        }
+        let signature = sr25519::Signature::from_slice(&response.signature);
+
+        let (call, extra, _) = payload.deconstruct();
+        Ok(UncheckedExtrinsic::new_signed(
+            call,
+            oracle.clone().into(),
+            MultiSignature::from(signature),
+            extra,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Allocation {
+        to: Hash,
+        amount: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Vector {
+        allocations: Vec<Allocation>,
+        root: Option<Hash>,
+    }
+
+    #[test]
+    fn batches_are_committed_to_like_the_test_vectors() {
+        let vectors: Vec<Vector> =
+            serde_json::from_str(merkle::TEST_VECTORS).expect("valid vectors");
+        for vector in vectors {
+            let batch = vector
+                .allocations
+                .into_iter()
+                .enumerate()
+                .map(|(index, allocation)| Reward {
+                    id: index.to_string(),
+                    to: AccountId::from(allocation.to.to_fixed_bytes()),
+                    amount: allocation.amount.parse().expect("valid amount"),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(merkle::root(&leaves(&batch)), vector.root);
+        }
+    }
+}
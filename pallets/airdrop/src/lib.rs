@@ -0,0 +1,257 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Airdrop of the ERC-20 holders who had not registered a Nodle account when the
+//! genesis was built. `genesis-builder` commits to their `(address, amount)` pairs
+//! with a merkle root stored at genesis, and the total is minted into the account of
+//! this pallet. Holders claim into the account of their choice with an unsigned
+//! transaction carrying the proof of their leaf and an Ethereum signature of the
+//! destination account, made with the key of the holding address.
+
+#[cfg(test)]
+mod tests;
+
+use chain_merkle::{self as merkle, MerkleProof};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{Currency, ExistenceRequirement, Get},
+    unsigned::ValidateUnsigned,
+    weights::Weight,
+    StorageMap, StorageValue,
+};
+use frame_system::ensure_none;
+use nodle_support::WithAccountId;
+use parity_scale_codec::{Decode, Encode};
+use sp_core::{H160, H256};
+use sp_io::{crypto::secp256k1_ecdsa_recover, hashing::keccak_256};
+use sp_runtime::{
+    traits::{AccountIdConversion, Saturating},
+    transaction_validity::{
+        InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
+        ValidTransaction,
+    },
+    DispatchResult, ModuleId,
+};
+use sp_std::prelude::Vec;
+
+/// Signed by the holders before the hex encoded destination account, so that their
+/// signatures can not pass for another message.
+pub const CLAIM_PREFIX: &[u8] = b"Pay NODL to the Nodle account:";
+
+type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// The address of an ERC-20 holder.
+pub type EthereumAddress = H160;
+
+/// A recoverable secp256k1 signature, as produced by Ethereum wallets.
+#[derive(Encode, Decode, Clone)]
+pub struct EcdsaSignature(pub [u8; 65]);
+
+impl PartialEq for EcdsaSignature {
+    fn eq(&self, other: &Self) -> bool {
+        self.0[..] == other.0[..]
+    }
+}
+
+impl Eq for EcdsaSignature {}
+
+impl sp_std::fmt::Debug for EcdsaSignature {
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "EcdsaSignature({:?})", &self.0[..])
+    }
+}
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    type Currency: Currency<Self::AccountId>;
+    /// The account holding the airdrop is derived from it
+    type ModuleId: Get<ModuleId>;
+    /// Priority of the unsigned claims in the pool
+    type UnsignedPriority: Get<TransactionPriority>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Airdrop {
+        /// Root of the merkle tree over the `(address, amount)` leaves of the holders,
+        /// `None` if every holder registered a Nodle account
+        pub Root get(fn root): Option<H256>;
+        /// Addresses whose airdrop was already claimed
+        pub Claimed get(fn claimed): map hasher(blake2_128_concat) EthereumAddress => bool;
+    }
+    add_extra_genesis {
+        config(root): Option<H256>;
+        /// Sum of the amounts of the tree, minted into the airdrop account on top of the
+        /// existential deposit.
+        config(total): BalanceOf<T>;
+        build(|config| {
+            if let Some(root) = config.root {
+                Root::put(root);
+                let _ = T::Currency::make_free_balance_be(
+                    &<Module<T>>::account_id(),
+                    config.total.saturating_add(T::Currency::minimum_balance()),
+                );
+            }
+        });
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        Balance = BalanceOf<T>,
+    {
+        /// The airdrop of an address was paid out to an account
+        Claimed(EthereumAddress, AccountId, Balance),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// The signature does not match a public key
+        InvalidSignature,
+        /// The proof does not show that the signer was owed the amount
+        InvalidProof,
+        /// The airdrop of the signer was already claimed
+        AlreadyClaimed,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Pay `amount` out to `dest` from an unsigned transaction, as holders have no
+        /// funds to pay fees with. `signature` is the Ethereum signature of `dest` by a
+        /// holder and `proof` must show that `amount` is owed to its address
+        #[weight = 150_000_000
+            + 1_000_000 * proof.siblings.len() as Weight
+            + T::DbWeight::get().reads_writes(4, 3)]
+        pub fn claim(origin, dest: T::AccountId, amount: BalanceOf<T>, proof: MerkleProof, signature: EcdsaSignature) -> DispatchResult {
+            ensure_none(origin)?;
+            let address = Self::check_claim(&dest, amount, &proof, &signature)?;
+
+            T::Currency::transfer(&Self::account_id(), &dest, amount, ExistenceRequirement::KeepAlive)?;
+            Claimed::insert(&address, true);
+
+            Self::deposit_event(RawEvent::Claimed(address, dest, amount));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// The message wallets sign with `personal_sign` to claim into `dest`.
+    pub fn signable_message(dest: &T::AccountId) -> Vec<u8> {
+        let mut payload = CLAIM_PREFIX.to_vec();
+        for byte in dest.encode() {
+            payload.push(b"0123456789abcdef"[(byte >> 4) as usize]);
+            payload.push(b"0123456789abcdef"[(byte & 0xf) as usize]);
+        }
+
+        let mut length = Vec::new();
+        let mut remaining = payload.len();
+        while remaining > 0 {
+            length.push(b'0' + (remaining % 10) as u8);
+            remaining /= 10;
+        }
+        length.reverse();
+
+        let mut message = b"\x19Ethereum Signed Message:\n".to_vec();
+        message.extend(length);
+        message.extend(payload);
+        message
+    }
+
+    /// The address that signed the claim of `dest`, if any.
+    fn recover_signer(dest: &T::AccountId, signature: &EcdsaSignature) -> Option<EthereumAddress> {
+        let hash = keccak_256(&Self::signable_message(dest));
+        let public = secp256k1_ecdsa_recover(&signature.0, &hash).ok()?;
+        Some(H160::from_slice(&keccak_256(&public)[12..]))
+    }
+
+    /// Check that the signer of `signature` can claim `amount` into `dest` and return
+    /// its address.
+    fn check_claim(
+        dest: &T::AccountId,
+        amount: BalanceOf<T>,
+        proof: &MerkleProof,
+        signature: &EcdsaSignature,
+    ) -> Result<EthereumAddress, Error<T>> {
+        let address = Self::recover_signer(dest, signature).ok_or(Error::<T>::InvalidSignature)?;
+        ensure!(
+            Self::root() == Some(proof.root)
+                && proof.leaf == merkle::leaf(&address, amount)
+                && merkle::verify(proof),
+            Error::<T>::InvalidProof
+        );
+        ensure!(!Self::claimed(&address), Error::<T>::AlreadyClaimed);
+        Ok(address)
+    }
+}
+
+impl<T: Trait> WithAccountId<T::AccountId> for Module<T> {
+    fn account_id() -> T::AccountId {
+        T::ModuleId::get().into_account()
+    }
+}
+
+impl<T: Trait> ValidateUnsigned for Module<T> {
+    type Call = Call<T>;
+
+    fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+        let (dest, amount, proof, signature) = match call {
+            Call::claim(dest, amount, proof, signature) => (dest, amount, proof, signature),
+            _ => return InvalidTransaction::Call.into(),
+        };
+        let address = Self::check_claim(dest, *amount, proof, signature).map_err(|e| match e {
+            Error::<T>::AlreadyClaimed => InvalidTransaction::Stale,
+            _ => InvalidTransaction::BadProof,
+        })?;
+
+        ValidTransaction::with_tag_prefix("Airdrop")
+            .priority(T::UnsignedPriority::get())
+            // An address can only claim once, the pool drops the duplicates
+            .and_provides(address)
+            .longevity(64)
+            .propagate(true)
+            .build()
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        use frame_support::IterableStorageMap;
+
+        ensure!(
+            Self::root().is_some() || Claimed::iter().next().is_none(),
+            "airdrop claimed without a root"
+        );
+        Ok(())
+    }
+}
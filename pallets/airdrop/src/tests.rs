@@ -0,0 +1,243 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{assert_noop, assert_ok, impl_outer_origin, parameter_types};
+use sp_runtime::{BuildStorage, DispatchError::BadOrigin};
+use test_utils::{impl_mock_balances, impl_mock_system};
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), pallet_balances::AccountData<u64>);
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+impl_mock_balances!(Test, ExistentialDeposit);
+
+parameter_types! {
+    pub const AirdropModuleId: ModuleId = ModuleId(*b"py/airdr");
+    pub const AirdropUnsignedPriority: TransactionPriority = TransactionPriority::max_value();
+}
+impl Trait for Test {
+    type Event = ();
+    type Currency = pallet_balances::Module<Self>;
+    type ModuleId = AirdropModuleId;
+    type UnsignedPriority = AirdropUnsignedPriority;
+}
+type Airdrop = Module<Test>;
+type Balances = pallet_balances::Module<Test>;
+type Errors = Error<Test>;
+
+const DEST: u64 = 10;
+
+fn secret(seed: &[u8]) -> secp256k1::SecretKey {
+    secp256k1::SecretKey::parse(&keccak_256(seed)).unwrap()
+}
+
+fn address(secret: &secp256k1::SecretKey) -> EthereumAddress {
+    let public = secp256k1::PublicKey::from_secret_key(secret);
+    H160::from_slice(&keccak_256(&public.serialize()[1..65])[12..])
+}
+
+fn sign(secret: &secp256k1::SecretKey, dest: u64) -> EcdsaSignature {
+    let hash = keccak_256(&Airdrop::signable_message(&dest));
+    let (signature, recovery_id) = secp256k1::sign(&secp256k1::Message::parse(&hash), secret);
+    let mut raw = [0; 65];
+    raw[..64].copy_from_slice(&signature.serialize());
+    raw[64] = recovery_id.serialize();
+    EcdsaSignature(raw)
+}
+
+/// Alice is owed 100, Bob 200.
+fn holders() -> Vec<(secp256k1::SecretKey, u64)> {
+    vec![(secret(b"alice"), 100), (secret(b"bob"), 200)]
+}
+
+fn proofs() -> Vec<MerkleProof> {
+    let leaves = holders()
+        .iter()
+        .map(|(secret, amount)| merkle::leaf(&address(secret), *amount))
+        .collect::<Vec<_>>();
+    merkle::prove_all(&leaves)
+}
+
+fn new_test_ext(root: Option<H256>) -> sp_io::TestExternalities {
+    GenesisConfig::<Test> { root, total: 300 }
+        .build_storage()
+        .unwrap()
+        .into()
+}
+
+fn claim(dest: u64, amount: u64, proof: MerkleProof, signature: EcdsaSignature) -> DispatchResult {
+    Airdrop::claim(Origin::none(), dest, amount, proof, signature)
+}
+
+fn validate(
+    dest: u64,
+    amount: u64,
+    proof: MerkleProof,
+    signature: EcdsaSignature,
+) -> TransactionValidity {
+    Airdrop::validate_unsigned(
+        TransactionSource::External,
+        &Call::claim(dest, amount, proof, signature),
+    )
+}
+
+#[test]
+fn genesis_mints_the_airdrop() {
+    new_test_ext(Some(proofs()[0].root)).execute_with(|| {
+        assert_eq!(Airdrop::root(), Some(proofs()[0].root));
+        // The existential deposit keeps the account alive until the last claim
+        assert_eq!(Balances::free_balance(Airdrop::account_id()), 301);
+    })
+}
+
+#[test]
+fn signable_message_is_an_ethereum_personal_message() {
+    assert_eq!(
+        Airdrop::signable_message(&1),
+        b"\x19Ethereum Signed Message:\n46Pay NODL to the Nodle account:0100000000000000".to_vec()
+    );
+}
+
+#[test]
+fn holders_claim_into_the_account_they_signed() {
+    new_test_ext(Some(proofs()[0].root)).execute_with(|| {
+        for ((secret, amount), proof) in holders().into_iter().zip(proofs()) {
+            assert_ok!(validate(DEST, amount, proof.clone(), sign(&secret, DEST)));
+            assert_ok!(claim(DEST, amount, proof, sign(&secret, DEST)));
+            assert!(Airdrop::claimed(address(&secret)));
+        }
+
+        assert_eq!(Balances::free_balance(DEST), 300);
+        assert_eq!(Balances::free_balance(Airdrop::account_id()), 1);
+    })
+}
+
+#[test]
+fn claims_are_paid_once() {
+    new_test_ext(Some(proofs()[0].root)).execute_with(|| {
+        let (secret, amount) = holders().remove(0);
+        assert_ok!(claim(
+            DEST,
+            amount,
+            proofs()[0].clone(),
+            sign(&secret, DEST)
+        ));
+
+        assert_eq!(
+            validate(DEST, amount, proofs()[0].clone(), sign(&secret, DEST)),
+            InvalidTransaction::Stale.into()
+        );
+        assert_noop!(
+            claim(DEST, amount, proofs()[0].clone(), sign(&secret, DEST)),
+            Errors::AlreadyClaimed
+        );
+    })
+}
+
+#[test]
+fn claims_need_the_signature_of_the_holder() {
+    new_test_ext(Some(proofs()[0].root)).execute_with(|| {
+        let (secret, amount) = holders().remove(0);
+
+        // Signed for another account
+        assert_eq!(
+            validate(DEST, amount, proofs()[0].clone(), sign(&secret, DEST + 1)),
+            InvalidTransaction::BadProof.into()
+        );
+        assert_noop!(
+            claim(DEST, amount, proofs()[0].clone(), sign(&secret, DEST + 1)),
+            Errors::InvalidProof
+        );
+        // Signed by another holder
+        assert_noop!(
+            claim(
+                DEST,
+                amount,
+                proofs()[0].clone(),
+                sign(&holders()[1].0, DEST)
+            ),
+            Errors::InvalidProof
+        );
+        // Not a signature at all
+        assert_noop!(
+            claim(DEST, amount, proofs()[0].clone(), EcdsaSignature([0; 65])),
+            Errors::InvalidSignature
+        );
+    })
+}
+
+#[test]
+fn claims_need_a_proof_of_the_amount() {
+    new_test_ext(Some(proofs()[0].root)).execute_with(|| {
+        let (secret, amount) = holders().remove(0);
+
+        assert_noop!(
+            claim(DEST, amount + 1, proofs()[0].clone(), sign(&secret, DEST)),
+            Errors::InvalidProof
+        );
+        // The proof of another leaf
+        assert_noop!(
+            claim(DEST, amount, proofs()[1].clone(), sign(&secret, DEST)),
+            Errors::InvalidProof
+        );
+    })
+}
+
+#[test]
+fn nothing_is_claimed_without_a_root() {
+    new_test_ext(None).execute_with(|| {
+        let (secret, amount) = holders().remove(0);
+
+        assert_eq!(Balances::free_balance(Airdrop::account_id()), 0);
+        assert_noop!(
+            claim(DEST, amount, proofs()[0].clone(), sign(&secret, DEST)),
+            Errors::InvalidProof
+        );
+    })
+}
+
+#[test]
+fn claims_are_unsigned() {
+    new_test_ext(Some(proofs()[0].root)).execute_with(|| {
+        let (secret, amount) = holders().remove(0);
+
+        assert_noop!(
+            Airdrop::claim(
+                Origin::signed(DEST),
+                DEST,
+                amount,
+                proofs()[0].clone(),
+                sign(&secret, DEST)
+            ),
+            BadOrigin
+        );
+    })
+}
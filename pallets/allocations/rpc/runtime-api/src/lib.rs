@@ -0,0 +1,45 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use parity_scale_codec::{Decode, Encode};
+use sp_std::vec::Vec;
+
+/// Aggregates of an allocations campaign. The chain runs a single campaign, funded by
+/// the allocations budget, so these cover every allocation ever made.
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct CampaignStats<Balance> {
+    /// Coins allocated so far, protocol fees included
+    pub spent: Balance,
+    /// Coins left to allocate before the budget is exhausted
+    pub remaining: Balance,
+    /// Number of distinct accounts that received an allocation
+    pub unique_recipients: u32,
+    /// Largest share of a single allocation received by a grantee
+    pub largest_payout: Balance,
+}
+
+sp_api::decl_runtime_apis! {
+    pub trait OraclesApi<AccountId, Balance, OracleStatus> where
+        AccountId: parity_scale_codec::Codec,
+        Balance: parity_scale_codec::Codec,
+        OracleStatus: parity_scale_codec::Codec,
+    {
+        fn oracles() -> Vec<(AccountId, OracleStatus)>;
+        fn budget() -> (Balance, Balance);
+    }
+
+    pub trait OracleMembershipApi<AccountId, BlockNumber> where
+        AccountId: parity_scale_codec::Codec,
+        BlockNumber: parity_scale_codec::Codec,
+    {
+        /// Accounts added to the oracles set which can not allocate yet, and the
+        /// block they will be able to from
+        fn pending_oracles() -> Vec<(AccountId, BlockNumber)>;
+    }
+
+    pub trait CampaignsApi<Balance> where
+        Balance: parity_scale_codec::Codec,
+    {
+        fn campaign() -> CampaignStats<Balance>;
+    }
+}
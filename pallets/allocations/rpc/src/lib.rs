@@ -0,0 +1,71 @@
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+pub use pallet_allocations_runtime_api::OraclesApi as OraclesRuntimeApi;
+use parity_scale_codec::Codec;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+mod rpc_errors;
+use rpc_errors::misc_rpc_error;
+
+#[rpc]
+pub trait OraclesApi<BlockHash, AccountId, Balance, OracleStatus>
+where
+    AccountId: Codec,
+    Balance: Codec,
+    OracleStatus: Codec,
+{
+    /// Current oracles along with how much they allocated and when they were last seen.
+    #[rpc(name = "oracles_list")]
+    fn list(&self, at: Option<BlockHash>) -> Result<Vec<(AccountId, OracleStatus)>>;
+    /// Coins allocated so far and the maximum that can ever be allocated.
+    #[rpc(name = "oracles_budget")]
+    fn budget(&self, at: Option<BlockHash>) -> Result<(Balance, Balance)>;
+}
+
+pub struct Oracles<C, M> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<M>,
+}
+
+impl<C, M> Oracles<C, M> {
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, Block, AccountId, Balance, OracleStatus>
+    OraclesApi<<Block as BlockT>::Hash, AccountId, Balance, OracleStatus> for Oracles<C, Block>
+where
+    AccountId: Codec,
+    Balance: Codec,
+    OracleStatus: Codec,
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block>,
+    C: HeaderBackend<Block>,
+    C::Api: OraclesRuntimeApi<Block, AccountId, Balance, OracleStatus>,
+{
+    fn list(&self, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<(AccountId, OracleStatus)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+        api.oracles(&at).map_err(misc_rpc_error)
+    }
+
+    fn budget(&self, at: Option<<Block as BlockT>::Hash>) -> Result<(Balance, Balance)> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+        api.budget(&at).map_err(misc_rpc_error)
+    }
+}
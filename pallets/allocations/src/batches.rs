@@ -0,0 +1,73 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Leaves of the batches oracles allocate, stored in a child trie per batch rather than
+//! in the main trie. Batches are pruned, a bounded number of leaves per block, once
+//! their payout era is settled, and clients can prove their leaves with a child
+//! storage proof of `trie(era, root)`, the keys being the leaves and the values
+//! whether they were claimed.
+
+use frame_support::storage::child::{self, ChildInfo};
+use parity_scale_codec::Encode;
+use sp_core::H256;
+use sp_io::default_child_storage;
+
+/// Prefix of the storage keys of the batch tries.
+pub const BATCH_TRIE_PREFIX: &[u8] = b"allocations:batch";
+
+/// Child trie holding the leaves of the batch committed to by `root` during `era`.
+pub fn trie(era: u32, root: &H256) -> ChildInfo {
+    ChildInfo::new_default(&(BATCH_TRIE_PREFIX, era, root).encode())
+}
+
+/// Record that `leaf` was allocated as part of the batch.
+pub fn insert_leaf(era: u32, root: &H256, leaf: &H256) {
+    child::put(&trie(era, root), leaf.as_bytes(), &false);
+}
+
+/// Whether `leaf` of the batch was already used to claim a payout.
+pub fn is_claimed(era: u32, root: &H256, leaf: &H256) -> bool {
+    child::get(&trie(era, root), leaf.as_bytes()).unwrap_or(false)
+}
+
+/// Mark `leaf` of the batch as claimed.
+pub fn mark_claimed(era: u32, root: &H256, leaf: &H256) {
+    child::put(&trie(era, root), leaf.as_bytes(), &true);
+}
+
+/// Remove every leaf of the batch, only meant for batches `prune_leaves` emptied.
+pub fn prune(era: u32, root: &H256) {
+    child::kill_storage(&trie(era, root));
+}
+
+/// Remove up to `max` leaves of the batch. Returns the number of leaves removed and
+/// whether the batch has no leaves left.
+pub fn prune_leaves(era: u32, root: &H256, max: u32) -> (u32, bool) {
+    let trie = trie(era, root);
+    let mut removed = 0;
+    while removed < max {
+        match default_child_storage::next_key(trie.storage_key(), &[]) {
+            Some(leaf) => child::kill(&trie, &leaf),
+            None => return (removed, true),
+        }
+        removed += 1;
+    }
+
+    let emptied = default_child_storage::next_key(trie.storage_key(), &[]).is_none();
+    (removed, emptied)
+}
@@ -23,10 +23,36 @@
 use super::*;
 
 use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::UnfilteredDispatchable;
 use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
 use sp_std::prelude::*;
 
 const MAX_BYTES: u32 = 1_024;
+const MAX_SETTLEMENTS: u32 = 1_000;
+const MAX_PRUNED: u32 = 1_000;
+const MAX_ORACLES: u32 = 1_000;
+const MAX_PROOF_DEPTH: u32 = 16;
+const MAX_BATCH_CLAIMS: u32 = 100;
+
+/// Oracles set of `count` members, returns the one sorted last.
+fn oracles<T: Trait>(count: u32) -> T::AccountId {
+    let mut oracles = (0..count)
+        .map(|i| account("oracle", i, SEED))
+        .collect::<Vec<T::AccountId>>();
+    oracles.sort();
+    Module::<T>::initialize_members(&oracles);
+    oracles.pop().unwrap_or_default()
+}
+
+/// Correlation check looking up the age of every beneficiary of a batch.
+fn correlation_check<T: Trait>() -> CorrelationCheckOf<T> {
+    CorrelationCheck {
+        window: T::BlockNumber::max_value(),
+        max_share: Perbill::zero(),
+        reject: false,
+    }
+}
 const SEED: u32 = 0;
 
 benchmarks! {
@@ -35,12 +61,226 @@ benchmarks! {
     allocate {
         let u in 1 .. 1000;
         let b in 1 .. MAX_BYTES;
+        let o in 1 .. MAX_ORACLES;
 
         let grantee: T::AccountId = account("grantee", u, SEED);
-        let oracle: T::AccountId = account("oracle", u, SEED);
-
-        Module::<T>::initialize_members(&[oracle.clone()]);
+        let oracle = oracles::<T>(o);
     }: _(RawOrigin::Signed(oracle), grantee, 100.into(), vec![1; b as usize])
+
+    allocate_fiat {
+        let o in 1 .. MAX_ORACLES;
+
+        let grantee: T::AccountId = account("grantee", 0, SEED);
+        let oracle = oracles::<T>(o);
+        for oracle in Module::<T>::oracles() {
+            <Prices<T>>::insert(&oracle, (BalanceOf::<T>::from(10u32), T::BlockNumber::from(0u32)));
+        }
+    }: _(RawOrigin::Signed(oracle), grantee, 10, 0.into(), 100.into(), vec![1; 32])
+
+    submit_price {
+        let oracle = oracles::<T>(1);
+    }: _(RawOrigin::Signed(oracle), 10.into())
+
+    // Cost of the oracle check alone, the set is read and binary searched
+    ensure_oracle {
+        let o in 1 .. MAX_ORACLES;
+
+        let oracle = oracles::<T>(o);
+    }: {
+        assert!(Module::<T>::is_oracle(oracle));
+    }
+
+    claim {
+        let grantee: T::AccountId = account("grantee", 0, SEED);
+        <PendingPayouts<T>>::insert(0, &grantee, BalanceOf::<T>::from(100u32));
+    }: _(RawOrigin::Signed(grantee), 0)
+
+    claim_unsigned {
+        let s in 0 .. MAX_PROOF_DEPTH;
+
+        let grantee: T::AccountId = account("grantee", 0, SEED);
+        let amount = BalanceOf::<T>::from(100u32);
+        let mut leaves = vec![merkle::leaf(&grantee, amount)];
+        leaves.resize(1 << s, H256::repeat_byte(1));
+        let proof = merkle::prove(&leaves, 0).expect("the leaf exists");
+        BatchRoots::insert(0, proof.root, true);
+        <PendingPayouts<T>>::insert(0, &grantee, amount);
+    }: _(RawOrigin::None, 0, grantee, amount, proof)
+
+    batch_claim {
+        let n in 1 .. MAX_BATCH_CLAIMS;
+        let s in 0 .. MAX_PROOF_DEPTH;
+
+        let amount = BalanceOf::<T>::from(100u32);
+        let grantees = (0..n)
+            .map(|i| account("grantee", i, SEED))
+            .collect::<Vec<T::AccountId>>();
+        let mut leaves = grantees
+            .iter()
+            .map(|grantee| merkle::leaf(grantee, amount))
+            .collect::<Vec<_>>();
+        leaves.resize(leaves.len().max(1 << s), H256::repeat_byte(1));
+        let proofs = merkle::prove_all(&leaves);
+        let root = proofs[0].root;
+        BatchRoots::insert(0, root, true);
+        let claims = grantees
+            .into_iter()
+            .zip(proofs)
+            .map(|(grantee, proof)| {
+                <PendingPayouts<T>>::insert(0, &grantee, amount);
+                (grantee, amount, proof)
+            })
+            .collect::<Vec<_>>();
+        let relayer: T::AccountId = account("relayer", 0, SEED);
+    }: _(RawOrigin::Signed(relayer), 0, root, claims)
+
+    unlock_rewards {
+        let grantee: T::AccountId = account("grantee", 0, SEED);
+        let amount = BalanceOf::<T>::from(100u32);
+        T::Currency::make_free_balance_be(&grantee, amount);
+        T::Currency::set_lock(REWARDS_LOCK_ID, &grantee, amount, WithdrawReason::Transfer.into());
+        <LockedRewards<T>>::insert(&grantee, amount);
+        frame_system::Module::<T>::set_block_number(T::RewardsUnlockBlock::get());
+        let caller: T::AccountId = account("caller", 0, SEED);
+    }: _(RawOrigin::Signed(caller), grantee)
+
+    settle_payouts {
+        let n in 1 .. MAX_SETTLEMENTS;
+
+        for i in 0 .. n {
+            let grantee: T::AccountId = account("grantee", i, SEED);
+            <PendingPayouts<T>>::insert(0, &grantee, BalanceOf::<T>::from(100u32));
+        }
+        UnsettledEras::put(vec![0]);
+    }: {
+        Module::<T>::settle_payouts(1, n);
+    }
+
+    prune_batches {
+        let n in 1 .. MAX_PRUNED;
+
+        let root = H256::repeat_byte(1);
+        BatchRoots::insert(0, root, true);
+        for i in 0 .. n {
+            batches::insert_leaf(0, &root, &H256::from_low_u64_be(i.into()));
+        }
+        PrunableEras::put(vec![0]);
+    }: {
+        Module::<T>::prune_batches(n);
+    }
+
+    // Authorizations carry a signature of the grantee which benchmarks can not produce
+    // for any signature scheme, `claim_authorized` is estimated
+
+    revoke_claim_authorizations {
+        let grantee: T::AccountId = account("grantee", 0, SEED);
+    }: _(RawOrigin::Signed(grantee))
+
+    resign {
+        let oracle = oracles::<T>(MAX_ORACLES);
+        let _ = T::OracleMembership::add_member(&oracle);
+    }: _(RawOrigin::Signed(oracle))
+
+    credit_points {
+        let oracle = oracles::<T>(MAX_ORACLES);
+        let grantee: T::AccountId = account("grantee", 0, SEED);
+    }: _(RawOrigin::Signed(oracle), grantee, 10)
+
+    convert_points {
+        let grantee: T::AccountId = account("grantee", 0, SEED);
+        <Points<T>>::insert(&grantee, 10);
+        <PointsRate<T>>::put(BalanceOf::<T>::from(10u32));
+    }: _(RawOrigin::Signed(grantee.clone()))
+    verify {
+        assert_eq!(Module::<T>::points(&grantee), 0);
+    }
+
+    set_points_rate {
+    }: _(RawOrigin::Root, 10.into())
+
+    reference_coverage {
+        let oracle = oracles::<T>(MAX_ORACLES);
+        T::Coverage::add_coverage(0, &H256::zero());
+    }: _(RawOrigin::Signed(oracle), H256::repeat_byte(1), 0)
+
+    delegate_budget {
+        let organization: T::AccountId = account("organization", 0, SEED);
+    }: _(RawOrigin::Root, organization, 0.into())
+
+    appoint_sub_oracle {
+        let organization: T::AccountId = account("organization", 0, SEED);
+        let sub_oracle: T::AccountId = account("sub_oracle", 0, SEED);
+        <Delegations<T>>::insert(&organization, BalanceOf::<T>::zero());
+        <SubOracles<T>>::remove(&sub_oracle);
+    }: _(RawOrigin::Signed(organization), sub_oracle)
+
+    dismiss_sub_oracle {
+        let organization: T::AccountId = account("organization", 0, SEED);
+        let sub_oracle: T::AccountId = account("sub_oracle", 0, SEED);
+        <SubOracles<T>>::insert(&sub_oracle, &organization);
+    }: _(RawOrigin::Signed(organization), sub_oracle)
+
+    allocate_delegated {
+        let organization: T::AccountId = account("organization", 0, SEED);
+        let sub_oracle: T::AccountId = account("sub_oracle", 0, SEED);
+        let grantee: T::AccountId = account("grantee", 0, SEED);
+        let amount = BalanceOf::<T>::from(100u32);
+        <Delegations<T>>::insert(&organization, amount);
+        <DelegatedCoins<T>>::mutate(|delegated| *delegated = delegated.saturating_add(amount));
+        <SubOracles<T>>::insert(&sub_oracle, &organization);
+    }: _(RawOrigin::Signed(sub_oracle), grantee, amount, vec![1; 32])
+
+    schedule_campaign {
+    }: _(RawOrigin::Root, Some((Zero::zero(), MomentOf::<T>::max_value())))
+
+    allocate_batch {
+        let n in 1 .. MAX_BATCH_CLAIMS;
+
+        let oracle = oracles::<T>(MAX_ORACLES);
+        let allocations = (0..n)
+            .map(|i| (account("grantee", i, SEED), 100.into()))
+            .collect::<Vec<(T::AccountId, BalanceOf<T>)>>();
+        <CorrelationCheckConfig<T>>::put(correlation_check::<T>());
+    }: _(RawOrigin::Signed(oracle), allocations, vec![1; 32])
+
+    set_committee_bounds {
+    }: _(RawOrigin::Root, 1, MAX_ORACLES)
+
+    add_oracle {
+        oracles::<T>(MAX_ORACLES - 1);
+        let oracle: T::AccountId = account("new_oracle", 0, SEED);
+        let call = Call::<T>::add_oracle(oracle);
+        let origin = T::CommitteeOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+
+    remove_oracle {
+        let oracle = oracles::<T>(MAX_ORACLES);
+        let _ = T::OracleMembership::add_member(&oracle);
+        let call = Call::<T>::remove_oracle(oracle);
+        let origin = T::CommitteeOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+
+    set_correlation_check {
+    }: _(RawOrigin::Root, Some(correlation_check::<T>()))
+
+    set_installment_plan {
+        let plan = InstallmentPlan {
+            threshold: BalanceOf::<T>::from(100u32),
+            installments: 12,
+        };
+    }: _(RawOrigin::Root, Some(plan))
+
+    activate_oracles {
+        let n in 1 .. MAX_ORACLES;
+
+        oracles::<T>(MAX_ORACLES);
+        let pending = (0..n)
+            .map(|i| (account("pending", i, SEED), T::BlockNumber::zero()))
+            .collect::<Vec<(T::AccountId, T::BlockNumber)>>();
+        <PendingOracles<T>>::put(pending);
+    }: {
+        Module::<T>::activate_oracles(T::BlockNumber::zero());
+    }
 }
 
 #[cfg(test)]
@@ -53,6 +293,33 @@ mod tests {
     fn test_benchmarks() {
         new_test_ext().execute_with(|| {
             assert_ok!(test_benchmark_allocate::<Test>());
+            assert_ok!(test_benchmark_allocate_fiat::<Test>());
+            assert_ok!(test_benchmark_submit_price::<Test>());
+            assert_ok!(test_benchmark_ensure_oracle::<Test>());
+            assert_ok!(test_benchmark_claim::<Test>());
+            assert_ok!(test_benchmark_claim_unsigned::<Test>());
+            assert_ok!(test_benchmark_batch_claim::<Test>());
+            assert_ok!(test_benchmark_revoke_claim_authorizations::<Test>());
+            assert_ok!(test_benchmark_unlock_rewards::<Test>());
+            assert_ok!(test_benchmark_settle_payouts::<Test>());
+            assert_ok!(test_benchmark_prune_batches::<Test>());
+            assert_ok!(test_benchmark_activate_oracles::<Test>());
+            assert_ok!(test_benchmark_resign::<Test>());
+            assert_ok!(test_benchmark_credit_points::<Test>());
+            assert_ok!(test_benchmark_convert_points::<Test>());
+            assert_ok!(test_benchmark_set_points_rate::<Test>());
+            assert_ok!(test_benchmark_reference_coverage::<Test>());
+            assert_ok!(test_benchmark_delegate_budget::<Test>());
+            assert_ok!(test_benchmark_appoint_sub_oracle::<Test>());
+            assert_ok!(test_benchmark_dismiss_sub_oracle::<Test>());
+            assert_ok!(test_benchmark_allocate_delegated::<Test>());
+            assert_ok!(test_benchmark_schedule_campaign::<Test>());
+            assert_ok!(test_benchmark_allocate_batch::<Test>());
+            assert_ok!(test_benchmark_set_committee_bounds::<Test>());
+            assert_ok!(test_benchmark_add_oracle::<Test>());
+            assert_ok!(test_benchmark_remove_oracle::<Test>());
+            assert_ok!(test_benchmark_set_correlation_check::<Test>());
+            assert_ok!(test_benchmark_set_installment_plan::<Test>());
         });
     }
 }
@@ -0,0 +1,58 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Handling of payouts too small to be deposited. A payout below the existential
+//! deposit can not create the account of its grantee, rather than dropping it the
+//! pallet hands it over to an `OnDust` handler.
+
+use crate::{BalanceOf, Dust, Trait};
+use frame_support::traits::Currency;
+use nodle_support::WithAccountId;
+use sp_runtime::traits::Saturating;
+use sp_std::marker::PhantomData;
+
+pub trait OnDust<AccountId, Balance> {
+    /// `amount` could not be paid out to `who`, it is below the existential deposit.
+    fn on_dust(who: &AccountId, amount: Balance);
+}
+
+/// Burn the dust, it is never minted.
+pub struct Burn;
+impl<AccountId, Balance> OnDust<AccountId, Balance> for Burn {
+    fn on_dust(_who: &AccountId, _amount: Balance) {}
+}
+
+/// Mint the dust into the account of `Receiver`, typically a reserve.
+pub struct Reserve<C, Receiver>(PhantomData<(C, Receiver)>);
+impl<AccountId, C, Receiver> OnDust<AccountId, C::Balance> for Reserve<C, Receiver>
+where
+    C: Currency<AccountId>,
+    Receiver: WithAccountId<AccountId>,
+{
+    fn on_dust(_who: &AccountId, amount: C::Balance) {
+        C::resolve_creating(&Receiver::account_id(), C::issue(amount));
+    }
+}
+
+/// Keep the dust for the grantee, it is added to its next payout.
+pub struct Accumulate<T>(PhantomData<T>);
+impl<T: Trait> OnDust<T::AccountId, BalanceOf<T>> for Accumulate<T> {
+    fn on_dust(who: &T::AccountId, amount: BalanceOf<T>) {
+        <Dust<T>>::mutate(who, |dust| *dust = dust.saturating_add(amount));
+    }
+}
@@ -0,0 +1,230 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Pins the SCALE encoding of every event so that indexers decoding events by
+//! variant index and field order keep working when events are added or enriched.
+
+#![cfg(test)]
+
+use super::*;
+
+type TestEvent = RawEvent<u64, u64, u64, u64, u64>;
+
+fn le(value: u64) -> Vec<u8> {
+    value.to_le_bytes().to_vec()
+}
+
+/// Expected encoding of a variant, its index followed by its fields.
+fn variant(index: u8, fields: &[Vec<u8>]) -> Vec<u8> {
+    let mut encoded = vec![index];
+    fields
+        .iter()
+        .for_each(|field| encoded.extend_from_slice(field));
+    encoded
+}
+
+fn v1_events() -> Vec<(TestEvent, Vec<u8>)> {
+    vec![
+        (
+            RawEvent::NewAllocation(1, 50, 5, vec![7, 8]),
+            variant(0, &[le(1), le(50), le(5), vec![8, 7, 8]]),
+        ),
+        (RawEvent::PayoutsSettled(3), variant(1, &[vec![3, 0, 0, 0]])),
+        (RawEvent::PayoutClaimed(1, 50), variant(2, &[le(1), le(50)])),
+        (
+            RawEvent::ClaimFailed(1, DispatchError::BadOrigin),
+            variant(3, &[le(1), vec![2]]),
+        ),
+        (RawEvent::RewardsUnlocked(1), variant(4, &[le(1)])),
+        (RawEvent::AllocationsStalled(20), variant(5, &[le(20)])),
+        (RawEvent::AllocationsResumed, variant(6, &[])),
+        (
+            RawEvent::DeviceAllocation(9, 1),
+            variant(7, &[le(9), le(1)]),
+        ),
+        (
+            RawEvent::OrganizationAccountCreated(1001, 1),
+            variant(8, &[le(1001), le(1)]),
+        ),
+        (
+            RawEvent::PriceSubmitted(1, 42),
+            variant(9, &[le(1), le(42)]),
+        ),
+        (
+            RawEvent::OracleAdditionScheduled(1, 6),
+            variant(10, &[le(1), le(6)]),
+        ),
+        (RawEvent::OracleActivated(1), variant(11, &[le(1)])),
+        (RawEvent::OracleResigned(1), variant(12, &[le(1)])),
+        (
+            RawEvent::PayoutWithheld(1, 50),
+            variant(13, &[le(1), le(50)]),
+        ),
+    ]
+}
+
+fn v2_events() -> Vec<(TestEvent, Vec<u8>)> {
+    vec![
+        (RawEvent::PointsCredited(1, 5), variant(14, &[le(1), le(5)])),
+        (
+            RawEvent::PointsConverted(1, 5, 50),
+            variant(15, &[le(1), le(5), le(50)]),
+        ),
+        (RawEvent::PointsRateSet(10), variant(16, &[le(10)])),
+    ]
+}
+
+fn v3_events() -> Vec<(TestEvent, Vec<u8>)> {
+    vec![(
+        RawEvent::CoverageReferenced(4, H256::repeat_byte(1), 2),
+        variant(17, &[vec![4, 0, 0, 0], vec![1; 32], vec![2, 0, 0, 0]]),
+    )]
+}
+
+fn v4_events() -> Vec<(TestEvent, Vec<u8>)> {
+    vec![
+        (
+            RawEvent::BudgetDelegated(30, 400),
+            variant(18, &[le(30), le(400)]),
+        ),
+        (
+            RawEvent::SubOracleAppointed(30, 32),
+            variant(19, &[le(30), le(32)]),
+        ),
+        (
+            RawEvent::SubOracleDismissed(30, 32),
+            variant(20, &[le(30), le(32)]),
+        ),
+        (
+            RawEvent::DelegatedAllocation(30, 32),
+            variant(21, &[le(30), le(32)]),
+        ),
+    ]
+}
+
+fn v5_events() -> Vec<(TestEvent, Vec<u8>)> {
+    vec![(
+        RawEvent::CampaignScheduled(Some((1_000, 2_000))),
+        variant(22, &[vec![1], le(1_000), le(2_000)]),
+    )]
+}
+
+fn v6_events() -> Vec<(TestEvent, Vec<u8>)> {
+    vec![
+        (
+            RawEvent::ClaimFailedV6(2, 1, BatchItemError::AlreadyClaimed),
+            variant(23, &[vec![2, 0, 0, 0], le(1), vec![6]]),
+        ),
+        (
+            RawEvent::AllocationFailed(1, 4, BatchItemError::AmountTooLow),
+            variant(24, &[vec![1, 0, 0, 0], le(4), vec![1]]),
+        ),
+    ]
+}
+
+fn v7_events() -> Vec<(TestEvent, Vec<u8>)> {
+    vec![(RawEvent::PayoutDusted(2, 1), variant(25, &[le(2), le(1)]))]
+}
+
+fn v8_events() -> Vec<(TestEvent, Vec<u8>)> {
+    vec![(
+        RawEvent::CommitteeBoundsSet(1, 3),
+        variant(26, &[vec![1, 0, 0, 0], vec![3, 0, 0, 0]]),
+    )]
+}
+
+fn v9_events() -> Vec<(TestEvent, Vec<u8>)> {
+    vec![
+        (
+            RawEvent::CorrelationCheckSet(Some(CorrelationCheck {
+                window: 100,
+                max_share: Perbill::from_parts(500_000_000),
+                reject: true,
+            })),
+            variant(
+                27,
+                &[vec![1], le(100), vec![0x00, 0x65, 0xcd, 0x1d], vec![1]],
+            ),
+        ),
+        (
+            RawEvent::CorrelatedBatch(Perbill::from_parts(1)),
+            variant(28, &[vec![1, 0, 0, 0]]),
+        ),
+    ]
+}
+
+fn v10_events() -> Vec<(TestEvent, Vec<u8>)> {
+    vec![
+        (
+            RawEvent::InstallmentPlanSet(Some(InstallmentPlan {
+                threshold: 1_000,
+                installments: 4,
+            })),
+            variant(29, &[vec![1], le(1_000), vec![4, 0, 0, 0]]),
+        ),
+        (
+            RawEvent::PayoutInInstallments(1, 250, 4),
+            variant(30, &[le(1), le(250), vec![4, 0, 0, 0]]),
+        ),
+    ]
+}
+
+fn v11_events() -> Vec<(TestEvent, Vec<u8>)> {
+    vec![(
+        RawEvent::ClaimAuthorizationsRevoked(2, 1),
+        variant(31, &[le(2), vec![1, 0, 0, 0]]),
+    )]
+}
+
+fn events() -> Vec<(TestEvent, Vec<u8>)> {
+    v1_events()
+        .into_iter()
+        .chain(v2_events())
+        .chain(v3_events())
+        .chain(v4_events())
+        .chain(v5_events())
+        .chain(v6_events())
+        .chain(v7_events())
+        .chain(v8_events())
+        .chain(v9_events())
+        .chain(v10_events())
+        .chain(v11_events())
+        .collect()
+}
+
+#[test]
+fn events_keep_their_encoding() {
+    for (event, encoded) in events() {
+        assert_eq!(event.encode(), encoded, "{:?} changed encoding", event);
+    }
+}
+
+#[test]
+fn older_payloads_decode_to_the_same_events() {
+    for (event, encoded) in events() {
+        assert_eq!(TestEvent::decode(&mut &encoded[..]).ok(), Some(event));
+    }
+}
+
+#[test]
+fn appending_an_event_bumps_the_version() {
+    // Appending a variant must come with its fixture above and a new version
+    let next_index = events().len() as u8;
+    assert!(TestEvent::decode(&mut &[next_index][..]).is_err());
+    assert_eq!(EVENTS_VERSION, 11);
+}
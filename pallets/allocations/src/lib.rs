@@ -18,34 +18,271 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod batches;
 mod benchmarking;
+pub mod dust;
+mod event_compat;
+mod migrations;
+pub mod reporting;
+pub mod rounding;
 mod tests;
+pub mod weights;
 
+pub use dust::OnDust;
+pub use migrations::Releases;
+pub use reporting::ReportingPeriods;
+pub use rounding::RoundingPolicy;
+pub use weights::WeightInfo;
+
+use chain_merkle::{self as merkle, MerkleProof};
+use chain_primitives::{ComplianceCheck, DeviceResolver, OnAllocation};
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage, ensure,
-    traits::{ChangeMembers, Currency, Get, InitializeMembers},
+    storage::IterableStorageDoubleMap,
+    traits::{
+        ChangeMembers, Contains, Currency, EnsureOrigin, Get, InitializeMembers, IsSubType,
+        LockIdentifier, LockableCurrency, OnKilledAccount, OnNewAccount, Time, WithdrawReason,
+    },
+    unsigned::ValidateUnsigned,
+    weights::{DispatchClass, Weight},
+    Parameter,
+};
+use frame_system::{ensure_none, ensure_root, ensure_signed, RawOrigin};
+use nodle_support::{
+    deposit_indexed_event, AllocationBatches, CoverageEpochs, HousekeepingTask, KeylessAccounts,
+    MemberSet, WithAccountId,
 };
-use frame_system::ensure_signed;
-use nodle_support::WithAccountId;
+use parity_scale_codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::{crypto::KeyTypeId, H256};
 use sp_runtime::{
-    traits::{CheckedAdd, Saturating},
-    DispatchResult, Perbill,
+    traits::{
+        CheckedAdd, DispatchInfoOf, IdentifyAccount, One, SaturatedConversion, Saturating,
+        SignedExtension, Verify, Zero,
+    },
+    transaction_validity::{
+        InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
+        TransactionValidityError, ValidTransaction,
+    },
+    DispatchError, DispatchResult, Perbill, RuntimeDebug,
 };
-use sp_std::prelude::Vec;
+use sp_std::{fmt::Debug, marker::PhantomData, prelude::Vec};
+
+/// Lock keeping the payouts made before `RewardsUnlockBlock` from being transferred.
+pub const REWARDS_LOCK_ID: LockIdentifier = *b"nrewards";
+
+/// Signed along with claim authorizations so that they can not pass for another payload.
+pub const CLAIM_AUTHORIZATION_CONTEXT: &[u8] = b"nodle/claim-authorization";
+
+/// Key type of the keys oracles sign their submissions with.
+pub const ORACLE_KEY_TYPE: KeyTypeId = KeyTypeId(*b"orcl");
+
+/// Version of the layout of `Event`, bumped whenever a variant is appended so that
+/// indexers can tell which variants they may come across.
+pub const EVENTS_VERSION: u16 = 11;
+
+/// Oracle keys, the account of an oracle is derived from its key.
+pub mod crypto {
+    use super::ORACLE_KEY_TYPE;
+    use sp_runtime::{
+        app_crypto::{app_crypto, sr25519},
+        MultiSignature, MultiSigner,
+    };
+
+    app_crypto!(sr25519, ORACLE_KEY_TYPE);
+
+    /// Lets off-chain workers sign transactions with the oracle key.
+    pub struct OracleAuthId;
+    impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for OracleAuthId {
+        type RuntimeAppPublic = Public;
+        type GenericSignature = sp_core::sr25519::Signature;
+        type GenericPublic = sp_core::sr25519::Public;
+    }
+}
 
 type BalanceOf<T> =
     <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+pub type MomentOf<T> = <<T as Trait>::Time as Time>::Moment;
+
+pub type OracleStatusOf<T> = OracleStatus<BalanceOf<T>, <T as frame_system::Trait>::BlockNumber>;
+
+/// Activity of an oracle, meant to be surfaced to operators via RPC.
+#[derive(Clone, Default, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct OracleStatus<Balance, BlockNumber> {
+    /// Total amount of coins allocated by the oracle, protocol fees included
+    pub allocated: Balance,
+    /// Last block at which the oracle submitted an allocation
+    pub last_seen: BlockNumber,
+}
+
+/// Why an item of a batch was skipped, so that operators know which ones to retry.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub enum BatchItemError {
+    /// The beneficiary is not allowed to receive coins
+    BeneficiaryBlocked,
+    /// The amount is below the minimum allocation or the existential deposit
+    AmountTooLow,
+    /// What is left of the budget, or of the delegated slice, does not cover the
+    /// allocation
+    BudgetExhausted,
+    /// The batch does not reference the coverage of an epoch
+    MissingCoverage,
+    /// No coins are waiting to be paid out to the grantee
+    NothingToClaim,
+    /// The merkle proof does not show the claimed leaf in the batch
+    InvalidProof,
+    /// The leaf was already used to claim a payout
+    AlreadyClaimed,
+    /// Any other error
+    Other(DispatchError),
+}
+
+/// Coins allocated during a reporting period.
+#[derive(Clone, Default, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct PeriodTotals<Balance> {
+    /// Grantees' share of the allocations
+    pub allocated: Balance,
+    /// Protocol fees taken on the allocations
+    pub protocol_fees: Balance,
+    /// Payouts of blocked accounts dropped during the period, these coins were
+    /// returned to the allocations budget
+    pub withheld: Balance,
+    /// Number of allocations made
+    pub allocations: u32,
+}
+
+pub type PeriodTotalsOf<T> = PeriodTotals<BalanceOf<T>>;
+
+/// Tripwire against batches of allocations favouring freshly created accounts, as
+/// sybil farms would.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CorrelationCheck<BlockNumber> {
+    /// Accounts created during the last this many blocks are considered new
+    pub window: BlockNumber,
+    /// Largest share of the value of a batch that may go to new accounts
+    pub max_share: Perbill,
+    /// Reject the batches going over `max_share`, they are only flagged otherwise
+    pub reject: bool,
+}
+
+pub type CorrelationCheckOf<T> = CorrelationCheck<<T as frame_system::Trait>::BlockNumber>;
+
+/// Large payouts paid out in equal installments over several payout eras rather than
+/// all at once, so that big contributors do not all sell at the same time.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct InstallmentPlan<Balance> {
+    /// Payouts of at least this amount are paid in installments
+    pub threshold: Balance,
+    /// Number of installments, the first one is paid when the payout is settled or
+    /// claimed and each of the others at the end of the following payout eras
+    pub installments: u32,
+}
+
+pub type InstallmentPlanOf<T> = InstallmentPlan<BalanceOf<T>>;
+
+/// Signed off-chain by a grantee to let a relayer, such as a custodial service, claim
+/// its payouts. The coins are always paid to the grantee.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ClaimAuthorization<AccountId, BlockNumber> {
+    pub grantee: AccountId,
+    pub relayer: AccountId,
+    /// Last block the authorization can be used in
+    pub expires_at: BlockNumber,
+    /// Authorizations revoked by the grantee so far, see `revoke_claim_authorizations`
+    pub nonce: u32,
+}
+
+pub type ClaimAuthorizationOf<T> = ClaimAuthorization<
+    <T as frame_system::Trait>::AccountId,
+    <T as frame_system::Trait>::BlockNumber,
+>;
 
 /// The module's configuration trait.
 pub trait Trait: frame_system::Trait + pallet_emergency_shutdown::Trait {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
-    type Currency: Currency<Self::AccountId>;
+    type Currency: LockableCurrency<Self::AccountId>;
     type ProtocolFee: Get<Perbill>;
+    /// Who receives the remainder when the fee does not divide an allocation exactly
+    type FeeRounding: RoundingPolicy;
     type ProtocolFeeReceiver: WithAccountId<Self::AccountId>;
     type MaximumCoinsEverAllocated: Get<BalanceOf<Self>>;
+    /// Smallest amount that can be allocated at once
+    type MinimumAllocation: Get<BalanceOf<Self>>;
 
     /// Runtime existential deposit
     type ExistentialDeposit: Get<BalanceOf<Self>>;
+    /// What becomes of payouts too small to create the account of their grantee
+    type OnDust: OnDust<Self::AccountId, BalanceOf<Self>>;
+
+    /// Origin allowed to submit allocations, typically `EnsureOracle`
+    type OracleOrigin: EnsureOrigin<Self::Origin, Success = Self::AccountId>;
+    /// Oracles added to the set can only allocate after this many blocks, giving
+    /// time to react to suspicious additions. Removals are immediate
+    type OracleActivationDelay: Get<Self::BlockNumber>;
+    /// Set the oracles are managed in, oracles resigning are removed from it
+    type OracleMembership: MemberSet<Self::AccountId>;
+    /// Origin allowed to add oracles to the set and to remove them from it
+    type CommitteeOrigin: EnsureOrigin<Self::Origin>;
+    /// Origin allowed to submit allocations while the oracles are stalled
+    type FallbackOrigin: EnsureOrigin<Self::Origin>;
+    /// Oracles are considered stalled when none of them allocated anything for this
+    /// many blocks, zero disables the detection
+    type StallThreshold: Get<Self::BlockNumber>;
+    /// Stable identity of a device, rewards can be addressed to
+    type DeviceId: Parameter;
+    /// Resolves devices to the account their rewards are paid to
+    type Devices: DeviceResolver<Self::DeviceId, Self::AccountId>;
+    /// Creates the keyless accounts organizations are paid into
+    type OrganizationAccounts: KeylessAccounts<Self::AccountId>;
+    /// Accounts no coins may be minted to, for instance because of sanctions
+    type ComplianceCheck: ComplianceCheck<Self::AccountId>;
+    /// Prices submitted by oracles longer ago than this many blocks are ignored
+    type PriceValidity: Get<Self::BlockNumber>;
+    /// Origin allowed to set how many coins a contribution point converts to
+    type PointsRateOrigin: EnsureOrigin<Self::Origin>;
+    /// Origin allowed to delegate slices of the budget to organizations and to
+    /// schedule the campaign
+    type DelegationOrigin: EnsureOrigin<Self::Origin>;
+    /// Coverage scores the batches of allocations reference
+    type Coverage: CoverageEpochs<H256>;
+    /// Told about the coins allocated to grantees
+    type OnAllocation: OnAllocation<Self::AccountId, BalanceOf<Self>>;
+    /// Periods allocations are totalled in, for instance calendar months
+    type ReportingPeriods: ReportingPeriods;
+    /// Wall-clock time campaigns are scheduled in, typically `pallet_timestamp`
+    type Time: Time;
+
+    /// Length of a payout era, in blocks. The coins allocated to a grantee during an
+    /// era are accumulated and paid out at once when the era is over
+    type PayoutEraDuration: Get<Self::BlockNumber>;
+    /// Maximum number of pending payouts settled at the beginning of a block
+    type MaxSettlementsPerBlock: Get<u32>;
+    /// Maximum number of batch leaves and roots of settled eras pruned at the
+    /// beginning of a block
+    type MaxPrunedPerBlock: Get<u32>;
+
+    /// Maximum number of unsigned claims included in a block, they pay no fees
+    type MaxUnsignedClaimsPerBlock: Get<u32>;
+    /// Priority of unsigned claims in the transaction pool
+    type UnsignedPriority: Get<TransactionPriority>;
+    /// Maximum number of claims relayed in a single batch
+    type MaxClaimsPerBatch: Get<u32>;
+    /// Key grantees sign the authorizations of their relayers with
+    type Public: IdentifyAccount<AccountId = Self::AccountId>;
+    /// Signature of a grantee over a claim authorization
+    type Signature: Parameter + Verify<Signer = Self::Public>;
+    /// Payouts made before this block can be used to pay fees, reserved or staked but
+    /// not transferred until this block, zero disables the lock
+    type RewardsUnlockBlock: Get<Self::BlockNumber>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
 }
 
 decl_error! {
@@ -58,76 +295,1311 @@ decl_error! {
         UnderShutdown,
         /// Amount is too low and will conflict with the ExistentialDeposit parameter
         DoesNotSatisfyExistentialDeposit,
+        /// Amount is lower than the configured minimum allocation
+        AllocationBelowMinimum,
+        /// No coins are waiting to be paid out for this era
+        NothingToClaim,
+        /// The merkle proof does not show the allocation of the claimed amount
+        InvalidClaimProof,
+        /// No oracle committed to this batch during the era
+        UnknownBatch,
+        /// This leaf of the batch was already used to claim a payout
+        LeafAlreadyClaimed,
+        /// A batch holds more claims than allowed
+        TooManyClaims,
+        /// The device is unknown or no longer trusted
+        UnknownDevice,
+        /// No oracle submitted a recent enough price
+        PriceUnavailable,
+        /// The price moved and the allocation fell out of the given bounds
+        SlippageExceeded,
+        /// Rewards can not be unlocked before `RewardsUnlockBlock`
+        RewardsStillLocked,
+        /// The account has no locked rewards
+        NothingLocked,
+        /// The beneficiary is not allowed to receive coins
+        BeneficiaryBlocked,
+        /// The caller is neither an oracle nor about to become one
+        NotAnOracle,
+        /// No keyless account could be created for the organization
+        OrganizationAccountUnavailable,
+        /// The account holds no contribution points
+        NoPoints,
+        /// No conversion rate was set for contribution points
+        PointsConversionDisabled,
+        /// The coverage of this epoch was not committed to yet
+        UnknownCoverage,
+        /// The batch does not reference the coverage of an epoch, see
+        /// `reference_coverage`
+        MissingCoverage,
+        /// No slice of the budget is delegated to the organization
+        NoDelegation,
+        /// The account is not a sub-oracle of the organization
+        NotASubOracle,
+        /// The account already allocates on behalf of an organization
+        AlreadySubOracle,
+        /// The allocation exceeds what is left of the slice of the organization
+        DelegationExhausted,
+        /// The campaign does not end after it starts
+        InvalidCampaignSchedule,
+        /// The campaign did not start yet, nothing can be allocated
+        CampaignNotStarted,
+        /// The campaign is over, nothing can be allocated anymore
+        CampaignOver,
+        /// The minimum size of the oracles committee is above its maximum
+        InvalidCommitteeBounds,
+        /// The change would shrink the oracles committee below its minimum size
+        CommitteeTooSmall,
+        /// The change would grow the oracles committee beyond its maximum size
+        CommitteeTooLarge,
+        /// Too much of the value of the batch goes to accounts created recently
+        CorrelatedBatch,
+        /// Payouts can not be paid in zero installments
+        InvalidInstallmentPlan,
+        /// The claim authorization was given to another relayer
+        RelayerNotAuthorized,
+        /// The claim authorization can not be used anymore
+        AuthorizationExpired,
+        /// The grantee revoked the claim authorization
+        AuthorizationRevoked,
+        /// The claim authorization was not signed by the grantee
+        InvalidAuthorizationSignature,
     }
 }
 
 decl_event!(
+    /// Indexers decode these events by variant index and field order, variants are
+    /// thus only ever appended. Enriching the payload of an event means appending a
+    /// new variant suffixed with the next `EVENTS_VERSION`, e.g. `PayoutClaimedV2`,
+    /// and keeping the previous one around for as long as indexers rely on it.
     pub enum Event<T>
     where
         AccountId = <T as frame_system::Trait>::AccountId,
         Balance = BalanceOf<T>,
+        BlockNumber = <T as frame_system::Trait>::BlockNumber,
+        DeviceId = <T as Trait>::DeviceId,
+        Moment = MomentOf<T>,
     {
-        /// An allocation was triggered
+        /// An allocation was triggered, the grantee's share is paid out at the end of
+        /// the payout era
         NewAllocation(AccountId, Balance, Balance, Vec<u8>),
+        /// Every payout of the given era was settled
+        PayoutsSettled(u32),
+        /// A grantee claimed its payout ahead of the settlement of its era
+        PayoutClaimed(AccountId, Balance),
+        /// The claim of a grantee's payout in a batch of claims failed, superseded by
+        /// `ClaimFailedV6`
+        ClaimFailed(AccountId, DispatchError),
+        /// The lock on the rewards of an account was lifted
+        RewardsUnlocked(AccountId),
+        /// No oracle allocated anything since the given block, the fallback origin can
+        /// now submit allocations
+        AllocationsStalled(BlockNumber),
+        /// The oracles allocated again after being stalled
+        AllocationsResumed,
+        /// The allocation that precedes was addressed to a device and paid to this account
+        DeviceAllocation(DeviceId, AccountId),
+        /// A keyless account controlled by the given account, typically a multisig,
+        /// was created to receive the allocations of an organization
+        OrganizationAccountCreated(AccountId, AccountId),
+        /// An oracle submitted the price of a US cent
+        PriceSubmitted(AccountId, Balance),
+        /// The account will join the oracles at the given block
+        OracleAdditionScheduled(AccountId, BlockNumber),
+        /// The account joined the oracles and can now allocate
+        OracleActivated(AccountId),
+        /// The oracle left the set on its own
+        OracleResigned(AccountId),
+        /// The payout of a blocked account was dropped during settlement, the coins
+        /// are returned to the allocations budget
+        PayoutWithheld(AccountId, Balance),
+        /// An oracle credited the account with contribution points (added in version 2)
+        PointsCredited(AccountId, u64),
+        /// The account converted its contribution points into the given amount of
+        /// coins, protocol fee included (added in version 2)
+        PointsConverted(AccountId, u64, Balance),
+        /// Contribution points now convert to the given amount of coins each (added in
+        /// version 2)
+        PointsRateSet(Balance),
+        /// The batch of the given payout era and root references the coverage of the
+        /// given epoch (added in version 3)
+        CoverageReferenced(u32, H256, u32),
+        /// The organization controlled by the account can now allocate up to the given
+        /// amount through its sub-oracles (added in version 4)
+        BudgetDelegated(AccountId, Balance),
+        /// The organization appointed the given sub-oracle (added in version 4)
+        SubOracleAppointed(AccountId, AccountId),
+        /// The organization dismissed the given sub-oracle (added in version 4)
+        SubOracleDismissed(AccountId, AccountId),
+        /// The allocation that precedes was made by the given sub-oracle on behalf of
+        /// the organization (added in version 4)
+        DelegatedAllocation(AccountId, AccountId),
+        /// Allocations can now only be made between the given times, or at any time
+        /// if there are none (added in version 5)
+        CampaignScheduled(Option<(Moment, Moment)>),
+        /// The claim at the given index of a batch of claims, on behalf of the
+        /// account, failed and was skipped (added in version 6)
+        ClaimFailedV6(u32, AccountId, BatchItemError),
+        /// The allocation at the given index of a batch of allocations, to the
+        /// account, failed and was skipped (added in version 6)
+        AllocationFailed(u32, AccountId, BatchItemError),
+        /// The payout of the account was below the existential deposit and handed to
+        /// the `OnDust` handler (added in version 7)
+        PayoutDusted(AccountId, Balance),
+        /// The oracles committee must now count between the given minimum and maximum
+        /// number of members (added in version 8)
+        CommitteeBoundsSet(u32, u32),
+        /// Batches are now checked against the given correlation check, or not
+        /// anymore if `None` (added in version 9)
+        CorrelationCheckSet(Option<CorrelationCheck<BlockNumber>>),
+        /// The batch of allocations that follows gives the given share of its value
+        /// to accounts created recently (added in version 9)
+        CorrelatedBatch(Perbill),
+        /// Large payouts are now paid in installments as given, or at once again if
+        /// `None` (added in version 10)
+        InstallmentPlanSet(Option<InstallmentPlan<Balance>>),
+        /// The payout of the account is paid in the given number of installments of
+        /// the given amount, the first one was just paid out (added in version 10)
+        PayoutInInstallments(AccountId, Balance, u32),
+        /// The account revoked the claim authorizations it signed, new ones carry the
+        /// given nonce (added in version 11)
+        ClaimAuthorizationsRevoked(AccountId, u32),
     }
 );
 
 decl_storage! {
     trait Store for Module<T: Trait> as Allocations {
+        /// Members of the oracles set, sorted so that lookups can be binary searches
         Oracles get(fn oracles): Vec<T::AccountId>;
+        /// Accounts added to the oracles set and the block they can start allocating
+        /// at, in the order they will be activated
+        PendingOracles get(fn pending_oracles): Vec<(T::AccountId, T::BlockNumber)>;
         CoinsConsumed get(fn coins_consumed): BalanceOf<T>;
+        OracleStatuses get(fn oracle_status): map hasher(blake2_128_concat) T::AccountId => OracleStatusOf<T>;
+        /// Grantees' share of the coins allocated during a payout era, not paid out yet
+        PendingPayouts get(fn pending_payout): double_map hasher(twox_64_concat) u32, hasher(blake2_128_concat) T::AccountId => BalanceOf<T>;
+        /// Payout eras with pending payouts, oldest first
+        UnsettledEras get(fn unsettled_eras): Vec<u32>;
+        /// Settled payout eras whose batches are still to be pruned, oldest first
+        PrunableEras get(fn prunable_eras): Vec<u32>;
+        /// Merkle roots oracles passed as the proof of their allocations, by payout era
+        pub BatchRoots get(fn is_batch_root): double_map hasher(twox_64_concat) u32, hasher(blake2_128_concat) H256 => bool;
+        /// Block of the last unsigned claim and the number of claims it included
+        UnsignedClaimsInBlock get(fn unsigned_claims_in_block): (T::BlockNumber, u32);
+        /// Accounts that received at least one allocation, counted since the storage
+        /// item was introduced
+        Recipients get(fn is_recipient): map hasher(blake2_128_concat) T::AccountId => bool;
+        RecipientsCount get(fn recipients_count): u32;
+        /// Largest share of a single allocation received by a grantee
+        LargestAllocation get(fn largest_allocation): BalanceOf<T>;
+        /// Payouts made to an account while rewards were locked
+        LockedRewards get(fn locked_rewards): map hasher(blake2_128_concat) T::AccountId => BalanceOf<T>;
+        /// Last block in which an oracle allocated coins
+        LastAllocation get(fn last_allocation): T::BlockNumber;
+        /// Whether the oracles are stalled, the fallback origin can allocate then
+        Stalled get(fn stalled): bool;
+        /// Account controlling each keyless account allocations were paid into
+        OrganizationControllers get(fn organization_controller): map hasher(blake2_128_concat) T::AccountId => Option<T::AccountId>;
+        /// Price of a US cent submitted by every oracle and the block it was submitted in
+        Prices get(fn price_of): map hasher(blake2_128_concat) T::AccountId => Option<(BalanceOf<T>, T::BlockNumber)>;
+        /// Contribution points credited to every account, they can not be transferred
+        /// and are only worth something once converted
+        Points get(fn points): map hasher(blake2_128_concat) T::AccountId => u64;
+        /// Coins, protocol fee included, a contribution point converts to. Zero
+        /// disables conversions
+        PointsRate get(fn points_rate): BalanceOf<T>;
+        /// Coverage epoch referenced by the batches of a payout era
+        BatchCoverage get(fn batch_coverage): double_map hasher(twox_64_concat) u32, hasher(blake2_128_concat) H256 => Option<u32>;
+        /// Part of the budget delegated to each organization, keyed by the account
+        /// controlling it, that its sub-oracles did not allocate yet
+        Delegations get(fn delegation): map hasher(blake2_128_concat) T::AccountId => Option<BalanceOf<T>>;
+        /// Sum of the delegations, the oracles can not allocate these coins
+        DelegatedCoins get(fn delegated_coins): BalanceOf<T>;
+        /// Organization every sub-oracle allocates on behalf of
+        SubOracles get(fn sub_oracle_of): map hasher(blake2_128_concat) T::AccountId => Option<T::AccountId>;
+        /// Coins allocated during every reporting period, see `ReportingPeriods`
+        Periods get(fn period_totals): map hasher(twox_64_concat) u32 => PeriodTotalsOf<T>;
+        /// Times the campaign starts and ends at, allocations can be made at any time
+        /// if there are none
+        CampaignSchedule get(fn campaign_schedule): Option<(MomentOf<T>, MomentOf<T>)>;
+        /// Payouts too small to be deposited kept for their grantee, added to its next
+        /// payout, see `dust::Accumulate`
+        Dust get(fn dust): map hasher(blake2_128_concat) T::AccountId => BalanceOf<T>;
+        /// Minimum and maximum number of members of the oracles committee, pending
+        /// oracles included, the committee can have any size if there are none
+        CommitteeBounds get(fn committee_bounds): Option<(u32, u32)>;
+        /// Block every account still alive was created in, batches giving much to
+        /// recent accounts are caught by the correlation check
+        AccountCreatedAt get(fn account_created_at): map hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+        /// What batches are checked against, they are not if there is none
+        CorrelationCheckConfig get(fn correlation_check): Option<CorrelationCheckOf<T>>;
+        /// How large payouts are split, they are paid at once if there is none
+        InstallmentPlanConfig get(fn installment_plan): Option<InstallmentPlanOf<T>>;
+        /// Installments due to every grantee at the end of a payout era, each with the
+        /// number of installments left for its payout, this one included
+        Installments get(fn installments): double_map hasher(twox_64_concat) u32, hasher(blake2_128_concat) T::AccountId => Vec<(BalanceOf<T>, u32)>;
+        /// Claim authorizations revoked by every grantee, only those carrying the
+        /// current count are valid
+        ClaimAuthorizationNonces get(fn claim_authorization_nonce): map hasher(blake2_128_concat) T::AccountId => u32;
+        /// Layout of the storage, tells which migrations to run
+        StorageVersion get(fn storage_version): Releases;
     }
 }
 
 decl_module! {
     /// The module declaration.
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const PayoutEraDuration: T::BlockNumber = T::PayoutEraDuration::get();
+        const MaxSettlementsPerBlock: u32 = T::MaxSettlementsPerBlock::get();
+        const MaxPrunedPerBlock: u32 = T::MaxPrunedPerBlock::get();
+        const MaxClaimsPerBatch: u32 = T::MaxClaimsPerBatch::get();
+        const RewardsUnlockBlock: T::BlockNumber = T::RewardsUnlockBlock::get();
+        const StallThreshold: T::BlockNumber = T::StallThreshold::get();
+        const PriceValidity: T::BlockNumber = T::PriceValidity::get();
+        const OracleActivationDelay: T::BlockNumber = T::OracleActivationDelay::get();
+        /// Version of the layout of the events of the module.
+        const EventsVersion: u16 = EVENTS_VERSION;
+
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            migrations::migrate::<T>()
+        }
+
         /// Can only be called by an oracle, trigger a coin creation and an event
-        #[weight = 50_000_000]
+        #[weight = (<T as Trait>::WeightInfo::allocate(), DispatchClass::Operational)]
         pub fn allocate(origin, to: T::AccountId, amount: BalanceOf<T>, proof: Vec<u8>) -> DispatchResult {
-            Self::ensure_oracle(origin)?;
+            let oracle = Self::ensure_allocator(origin)?;
+            Self::ensure_campaign_running()?;
+            Self::do_allocate(oracle, None, to, amount, proof)
+        }
+
+        /// Allocate `amount` to the account the rewards of `device` are paid to, so that
+        /// reward data can reference devices rather than accounts
+        #[weight = (<T as Trait>::WeightInfo::allocate_to_device(), DispatchClass::Operational)]
+        pub fn allocate_to_device(origin, device: T::DeviceId, amount: BalanceOf<T>, proof: Vec<u8>) -> DispatchResult {
+            let oracle = Self::ensure_allocator(origin)?;
+            Self::ensure_campaign_running()?;
+            let to = T::Devices::payout_account(&device).ok_or(Error::<T>::UnknownDevice)?;
+
+            Self::do_allocate(oracle, None, to.clone(), amount, proof)?;
+            Self::deposit_event(RawEvent::DeviceAllocation(device, to));
+            Ok(())
+        }
+
+        /// Allocate `amount` to the keyless account controlled by `controller`, typically
+        /// the multisig of an organization. The account is created on first use
+        #[weight = (<T as Trait>::WeightInfo::allocate_to_organization(), DispatchClass::Operational)]
+        pub fn allocate_to_organization(origin, controller: T::AccountId, amount: BalanceOf<T>, proof: Vec<u8>) -> DispatchResult {
+            let oracle = Self::ensure_allocator(origin)?;
+            Self::ensure_campaign_running()?;
+            ensure!(!T::ComplianceCheck::contains(&controller), Error::<T>::BeneficiaryBlocked);
+            let to = T::OrganizationAccounts::keyless_account(&controller)
+                .ok_or(Error::<T>::OrganizationAccountUnavailable)?;
+            let created = Self::organization_controller(&to).is_none();
+
+            Self::do_allocate(oracle, None, to.clone(), amount, proof)?;
+            if created {
+                T::OrganizationAccounts::create_keyless_account(&controller);
+                <OrganizationControllers<T>>::insert(&to, &controller);
+                Self::deposit_event(RawEvent::OrganizationAccountCreated(to, controller));
+            }
+            Ok(())
+        }
+
+        /// Allocate the equivalent of `cents` US cents at the median of the prices
+        /// recently submitted by the oracles. Fails if the amount, protocol fee
+        /// included, falls out of `min_amount..=max_amount`
+        #[weight = (<T as Trait>::WeightInfo::allocate_fiat(), DispatchClass::Operational)]
+        pub fn allocate_fiat(origin, to: T::AccountId, cents: u32, min_amount: BalanceOf<T>, max_amount: BalanceOf<T>, proof: Vec<u8>) -> DispatchResult {
+            let oracle = Self::ensure_allocator(origin)?;
+            Self::ensure_campaign_running()?;
+            let per_cent = Self::median_price().ok_or(Error::<T>::PriceUnavailable)?;
+            let amount = per_cent.checked_mul(&cents.into()).ok_or("Overflow converting cents")?;
+            ensure!(amount >= min_amount && amount <= max_amount, Error::<T>::SlippageExceeded);
+
+            Self::do_allocate(oracle, None, to, amount, proof)
+        }
+
+        /// Submit the price of a US cent in the smallest unit of the currency, used to
+        /// convert fiat denominated allocations
+        #[weight = (<T as Trait>::WeightInfo::submit_price(), DispatchClass::Operational)]
+        pub fn submit_price(origin, per_cent: BalanceOf<T>) -> DispatchResult {
+            let oracle = T::OracleOrigin::ensure_origin(origin).map_err(|_| Error::<T>::OracleAccessDenied)?;
+
+            <Prices<T>>::insert(&oracle, (per_cent, <frame_system::Module<T>>::block_number()));
+            Self::deposit_event(RawEvent::PriceSubmitted(oracle, per_cent));
+            Ok(())
+        }
+
+        /// Receive the coins allocated to the caller during `era` without waiting for
+        /// the era to be settled
+        #[weight = <T as Trait>::WeightInfo::claim()]
+        pub fn claim(origin, era: u32) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!pallet_emergency_shutdown::Module::<T>::shutdown(), Error::<T>::UnderShutdown);
+            ensure!(!T::ComplianceCheck::contains(&who), Error::<T>::BeneficiaryBlocked);
+
+            let amount = <PendingPayouts<T>>::take(era, &who);
+            ensure!(!amount.is_zero(), Error::<T>::NothingToClaim);
+            Self::pay_out_or_schedule(&who, amount);
+
+            Self::deposit_event(RawEvent::PayoutClaimed(who, amount));
+            Ok(())
+        }
+
+        /// Pay out what `who` is owed for `era` from an unsigned transaction, so that
+        /// grantees without funds can claim their first payout. `proof` must show that
+        /// `amount`, protocol fee included, was allocated to `who` in a batch of the era
+        #[weight = <T as Trait>::WeightInfo::claim_unsigned(proof.siblings.len() as u32)]
+        pub fn claim_unsigned(origin, era: u32, who: T::AccountId, amount: BalanceOf<T>, proof: MerkleProof) -> DispatchResult {
+            ensure_none(origin)?;
+            ensure!(!pallet_emergency_shutdown::Module::<T>::shutdown(), Error::<T>::UnderShutdown);
+            Self::claim_proven(era, &who, amount, &proof)?;
+
+            let now = <frame_system::Module<T>>::block_number();
+            <UnsignedClaimsInBlock<T>>::mutate(|(block, count)| {
+                if *block != now {
+                    *block = now;
+                    *count = 0;
+                }
+                *count = count.saturating_add(1);
+            });
+            Ok(())
+        }
+
+        /// Claim the payouts of many grantees at once, proven against the `root` of one
+        /// of the batches of `era`, so that relayers pay a single fee for all of them.
+        /// Claims that fail are skipped, an event reports why
+        #[weight = <T as Trait>::WeightInfo::batch_claim(
+            claims.len() as u32,
+            claims.iter().map(|(_, _, proof)| proof.siblings.len() as u32).max().unwrap_or(0),
+        )]
+        pub fn batch_claim(origin, era: u32, root: H256, claims: Vec<(T::AccountId, BalanceOf<T>, MerkleProof)>) -> DispatchResult {
+            ensure_signed(origin)?;
             ensure!(!pallet_emergency_shutdown::Module::<T>::shutdown(), Error::<T>::UnderShutdown);
+            ensure!(claims.len() as u32 <= T::MaxClaimsPerBatch::get(), Error::<T>::TooManyClaims);
+            ensure!(Self::is_batch_root(era, root), Error::<T>::UnknownBatch);
 
-            let coins_already_allocated = Self::coins_consumed();
-            let coins_that_will_be_consumed = coins_already_allocated.checked_add(&amount).ok_or("Overflow computing coins consumed")?;
+            for (index, (who, amount, proof)) in claims.into_iter().enumerate() {
+                let claimed = if proof.root == root {
+                    Self::claim_proven(era, &who, amount, &proof)
+                } else {
+                    Err(Error::<T>::InvalidClaimProof.into())
+                };
+                if let Err(e) = claimed {
+                    deposit_indexed_event::<T, <T as Trait>::Event>(
+                        &[&who],
+                        RawEvent::ClaimFailedV6(index as u32, who.clone(), Self::batch_item_error(e)).into(),
+                    );
+                }
+            }
+            Ok(())
+        }
 
-            ensure!(coins_that_will_be_consumed <= T::MaximumCoinsEverAllocated::get(), Error::<T>::TooManyCoinsToAllocate);
+        /// Claim what the grantee of `authorization` is owed for `era`, as the relayer it
+        /// authorized with `signature`. The coins are paid to the grantee
+        #[weight = <T as Trait>::WeightInfo::claim_authorized()]
+        pub fn claim_authorized(origin, era: u32, authorization: ClaimAuthorizationOf<T>, signature: T::Signature) -> DispatchResult {
+            let relayer = ensure_signed(origin)?;
+            ensure!(!pallet_emergency_shutdown::Module::<T>::shutdown(), Error::<T>::UnderShutdown);
+            Self::check_authorization(&relayer, &authorization, &signature)?;
+            let who = authorization.grantee;
+            ensure!(!T::ComplianceCheck::contains(&who), Error::<T>::BeneficiaryBlocked);
 
-            // When using a Perbill type as T::ProtocolFee::get() returns the default way to go is to used the standard mathematic
-            // operands. The risk of {over, under}flow is void as this operation will effectively take a part of `amount` and thus
-            // always produce a lower number. (We use Perbill to represent percentages)
-            let amount_for_protocol = T::ProtocolFee::get() * amount;
-            let amount_for_grantee = amount.saturating_sub(amount_for_protocol);
+            let amount = <PendingPayouts<T>>::take(era, &who);
+            ensure!(!amount.is_zero(), Error::<T>::NothingToClaim);
+            Self::pay_out_or_schedule(&who, amount);
 
-            Self::ensure_satisfy_existential_deposit(&T::ProtocolFeeReceiver::account_id(), amount_for_protocol)?;
-            Self::ensure_satisfy_existential_deposit(&to, amount_for_grantee)?;
+            Self::deposit_event(RawEvent::PayoutClaimed(who, amount));
+            Ok(())
+        }
 
-            <CoinsConsumed<T>>::put(coins_that_will_be_consumed);
+        /// Revoke every claim authorization the caller signed so far
+        #[weight = <T as Trait>::WeightInfo::revoke_claim_authorizations()]
+        pub fn revoke_claim_authorizations(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
 
-            T::Currency::resolve_creating(&T::ProtocolFeeReceiver::account_id(), T::Currency::issue(amount_for_protocol));
-            T::Currency::resolve_creating(&to, T::Currency::issue(amount_for_grantee));
+            let nonce = <ClaimAuthorizationNonces<T>>::mutate(&who, |nonce| {
+                *nonce = nonce.saturating_add(1);
+                *nonce
+            });
+            Self::deposit_event(RawEvent::ClaimAuthorizationsRevoked(who, nonce));
+            Ok(())
+        }
+
+        /// Lift the lock on the rewards of `who` once `RewardsUnlockBlock` is reached,
+        /// anyone can do so on behalf of the grantee
+        #[weight = <T as Trait>::WeightInfo::unlock_rewards()]
+        pub fn unlock_rewards(origin, who: T::AccountId) -> DispatchResult {
+            ensure_signed(origin)?;
+            ensure!(<frame_system::Module<T>>::block_number() >= T::RewardsUnlockBlock::get(), Error::<T>::RewardsStillLocked);
+            ensure!(<LockedRewards<T>>::contains_key(&who), Error::<T>::NothingLocked);
 
-            Self::deposit_event(RawEvent::NewAllocation(to, amount_for_grantee, amount_for_protocol, proof));
+            <LockedRewards<T>>::remove(&who);
+            T::Currency::remove_lock(REWARDS_LOCK_ID, &who);
 
+            Self::deposit_event(RawEvent::RewardsUnlocked(who));
+            Ok(())
+        }
+
+        /// Credit `to` with contribution points. Unlike allocations nothing is minted,
+        /// which keeps frequent small rewards cheap, the points are turned into coins
+        /// later on with `convert_points`
+        #[weight = (<T as Trait>::WeightInfo::credit_points(), DispatchClass::Operational)]
+        pub fn credit_points(origin, to: T::AccountId, points: u64) -> DispatchResult {
+            T::OracleOrigin::ensure_origin(origin).map_err(|_| Error::<T>::OracleAccessDenied)?;
+            ensure!(!pallet_emergency_shutdown::Module::<T>::shutdown(), Error::<T>::UnderShutdown);
+
+            <Points<T>>::mutate(&to, |balance| *balance = balance.saturating_add(points));
+            Self::deposit_event(RawEvent::PointsCredited(to, points));
+            Ok(())
+        }
+
+        /// Convert all the contribution points of the caller into coins at the current
+        /// rate. The coins are taken from the allocations budget and paid out like an
+        /// allocation, at the end of the payout era
+        #[weight = <T as Trait>::WeightInfo::convert_points()]
+        pub fn convert_points(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let points = Self::points(&who);
+            ensure!(points > 0, Error::<T>::NoPoints);
+            let rate = Self::points_rate();
+            ensure!(!rate.is_zero(), Error::<T>::PointsConversionDisabled);
+            let amount = rate
+                .checked_mul(&points.saturated_into())
+                .ok_or("Overflow converting points")?;
+
+            Self::do_allocate(None, None, who.clone(), amount, Vec::new())?;
+            <Points<T>>::remove(&who);
+
+            Self::deposit_event(RawEvent::PointsConverted(who, points, amount));
+            Ok(())
+        }
+
+        /// Set how many coins, protocol fee included, a contribution point converts
+        /// to. Zero disables conversions
+        #[weight = (<T as Trait>::WeightInfo::set_points_rate(), DispatchClass::Operational)]
+        pub fn set_points_rate(origin, rate: BalanceOf<T>) -> DispatchResult {
+            T::PointsRateOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+
+            <PointsRate<T>>::put(rate);
+            Self::deposit_event(RawEvent::PointsRateSet(rate));
+            Ok(())
+        }
+
+        /// Reference the coverage scores of `epoch` as the justification of the batch
+        /// committed to by `root` in the current payout era. Once references are
+        /// required nothing can be allocated in a batch before it references an epoch
+        #[weight = (<T as Trait>::WeightInfo::reference_coverage(), DispatchClass::Operational)]
+        pub fn reference_coverage(origin, root: H256, epoch: u32) -> DispatchResult {
+            Self::ensure_allocator(origin)?;
+            ensure!(T::Coverage::coverage_root(epoch).is_some(), Error::<T>::UnknownCoverage);
+
+            let era = Self::payout_era(<frame_system::Module<T>>::block_number());
+            BatchCoverage::insert(era, root, epoch);
+            Self::deposit_event(RawEvent::CoverageReferenced(era, root, epoch));
+            Ok(())
+        }
+
+        /// Let the organization controlled by `organization`, typically a multisig,
+        /// allocate up to `amount` through the sub-oracles it appoints. The slice
+        /// replaces the one previously delegated, zero revokes it
+        #[weight = (<T as Trait>::WeightInfo::delegate_budget(), DispatchClass::Operational)]
+        pub fn delegate_budget(origin, organization: T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+            T::DelegationOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+            let previous = Self::delegation(&organization).unwrap_or_else(Zero::zero);
+            let delegated = Self::delegated_coins()
+                .saturating_sub(previous)
+                .checked_add(&amount)
+                .ok_or("Overflow computing coins delegated")?;
+            ensure!(
+                Self::coins_consumed().saturating_add(delegated) <= T::MaximumCoinsEverAllocated::get(),
+                Error::<T>::TooManyCoinsToAllocate
+            );
+
+            if amount.is_zero() {
+                <Delegations<T>>::remove(&organization);
+            } else {
+                <Delegations<T>>::insert(&organization, amount);
+            }
+            <DelegatedCoins<T>>::put(delegated);
+
+            Self::deposit_event(RawEvent::BudgetDelegated(organization, amount));
+            Ok(())
+        }
+
+        /// Let `sub_oracle` allocate on behalf of the calling organization, within the
+        /// slice of the budget delegated to it
+        #[weight = <T as Trait>::WeightInfo::appoint_sub_oracle()]
+        pub fn appoint_sub_oracle(origin, sub_oracle: T::AccountId) -> DispatchResult {
+            let organization = ensure_signed(origin)?;
+            ensure!(<Delegations<T>>::contains_key(&organization), Error::<T>::NoDelegation);
+            ensure!(!<SubOracles<T>>::contains_key(&sub_oracle), Error::<T>::AlreadySubOracle);
+
+            <SubOracles<T>>::insert(&sub_oracle, &organization);
+            Self::deposit_event(RawEvent::SubOracleAppointed(organization, sub_oracle));
+            Ok(())
+        }
+
+        /// Revoke the right of `sub_oracle` to allocate on behalf of the calling
+        /// organization
+        #[weight = <T as Trait>::WeightInfo::dismiss_sub_oracle()]
+        pub fn dismiss_sub_oracle(origin, sub_oracle: T::AccountId) -> DispatchResult {
+            let organization = ensure_signed(origin)?;
+            ensure!(
+                Self::sub_oracle_of(&sub_oracle) == Some(organization.clone()),
+                Error::<T>::NotASubOracle
+            );
+
+            <SubOracles<T>>::remove(&sub_oracle);
+            Self::deposit_event(RawEvent::SubOracleDismissed(organization, sub_oracle));
+            Ok(())
+        }
+
+        /// Allocate `amount` out of the slice of the budget delegated to the
+        /// organization the caller is a sub-oracle of
+        #[weight = (<T as Trait>::WeightInfo::allocate_delegated(), DispatchClass::Operational)]
+        pub fn allocate_delegated(origin, to: T::AccountId, amount: BalanceOf<T>, proof: Vec<u8>) -> DispatchResult {
+            let sub_oracle = ensure_signed(origin)?;
+            let organization = Self::sub_oracle_of(&sub_oracle).ok_or(Error::<T>::NotASubOracle)?;
+            Self::ensure_campaign_running()?;
+
+            Self::do_allocate(None, Some(&organization), to, amount, proof)?;
+            Self::deposit_event(RawEvent::DelegatedAllocation(organization, sub_oracle));
+            Ok(())
+        }
+
+        /// Leave the oracles set, or cancel a pending addition to it. Minting rights
+        /// are revoked immediately
+        #[weight = (<T as Trait>::WeightInfo::resign(), DispatchClass::Operational)]
+        pub fn resign(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                Self::is_oracle(who.clone())
+                    || Self::pending_oracles().iter().any(|(oracle, _)| *oracle == who),
+                Error::<T>::NotAnOracle
+            );
+
+            Self::ensure_committee_size(Self::committee_size().saturating_sub(1))?;
+
+            // The set calls back into `change_members_sorted` which clears our state
+            T::OracleMembership::remove_member(&who)?;
+
+            Self::deposit_event(RawEvent::OracleResigned(who));
+            Ok(())
+        }
+
+        /// Only let allocations be made from `start` included to `end` excluded, or at
+        /// any time if `None`. Times are read from the `Time` source, not derived
+        /// from block numbers, so that schedules hold whatever the block time
+        #[weight = (<T as Trait>::WeightInfo::schedule_campaign(), DispatchClass::Operational)]
+        pub fn schedule_campaign(origin, schedule: Option<(MomentOf<T>, MomentOf<T>)>) -> DispatchResult {
+            T::DelegationOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+            if let Some((start, end)) = schedule {
+                ensure!(start < end, Error::<T>::InvalidCampaignSchedule);
+            }
+
+            <CampaignSchedule<T>>::set(schedule);
+            Self::deposit_event(RawEvent::CampaignScheduled(schedule));
+            Ok(())
+        }
+
+        /// Keep the oracles committee between `min` and `max` members. Changes to the
+        /// set moving its size out of these bounds are rejected, a committee already
+        /// out of them can only move towards them
+        #[weight = (<T as Trait>::WeightInfo::set_committee_bounds(), DispatchClass::Operational)]
+        pub fn set_committee_bounds(origin, min: u32, max: u32) -> DispatchResult {
+            ensure_root(origin)?;
+            ensure!(min <= max, Error::<T>::InvalidCommitteeBounds);
+
+            <CommitteeBounds>::put((min, max));
+            Self::deposit_event(RawEvent::CommitteeBoundsSet(min, max));
+            Ok(())
+        }
+
+        /// Add `who` to the oracles set, as long as the committee stays within its
+        /// bounds
+        #[weight = (<T as Trait>::WeightInfo::add_oracle(), DispatchClass::Operational)]
+        pub fn add_oracle(origin, who: T::AccountId) -> DispatchResult {
+            T::CommitteeOrigin::ensure_origin(origin)?;
+            Self::ensure_committee_size(Self::committee_size().saturating_add(1))?;
+
+            // The set calls back into `change_members_sorted` which schedules its activation
+            T::OracleMembership::add_member(&who)?;
+            Ok(())
+        }
+
+        /// Remove `who` from the oracles set, as long as the committee stays within
+        /// its bounds
+        #[weight = (<T as Trait>::WeightInfo::remove_oracle(), DispatchClass::Operational)]
+        pub fn remove_oracle(origin, who: T::AccountId) -> DispatchResult {
+            T::CommitteeOrigin::ensure_origin(origin)?;
+            Self::ensure_committee_size(Self::committee_size().saturating_sub(1))?;
+
+            // The set calls back into `change_members_sorted` which clears our state
+            T::OracleMembership::remove_member(&who)?;
+            Ok(())
+        }
+
+        /// Allocate to every account of `allocations` the amount next to it, all of
+        /// them sharing `proof`. Allocations that fail are skipped, an event reports
+        /// their index and why
+        #[weight = (<T as Trait>::WeightInfo::allocate_batch(allocations.len() as u32), DispatchClass::Operational)]
+        pub fn allocate_batch(origin, allocations: Vec<(T::AccountId, BalanceOf<T>)>, proof: Vec<u8>) -> DispatchResult {
+            let oracle = Self::ensure_allocator(origin)?;
+            Self::ensure_campaign_running()?;
+            ensure!(!pallet_emergency_shutdown::Module::<T>::shutdown(), Error::<T>::UnderShutdown);
+            Self::check_correlation(&allocations)?;
+
+            for (index, (to, amount)) in allocations.into_iter().enumerate() {
+                if let Err(e) = Self::do_allocate(oracle.clone(), None, to.clone(), amount, proof.clone()) {
+                    deposit_indexed_event::<T, <T as Trait>::Event>(
+                        &[&to],
+                        RawEvent::AllocationFailed(index as u32, to.clone(), Self::batch_item_error(e)).into(),
+                    );
+                }
+            }
+            Ok(())
+        }
+
+        /// Flag, or reject, the batches of allocations giving more than a share of
+        /// their value to accounts created recently. `None` disables the check
+        #[weight = (<T as Trait>::WeightInfo::set_correlation_check(), DispatchClass::Operational)]
+        pub fn set_correlation_check(origin, check: Option<CorrelationCheckOf<T>>) -> DispatchResult {
+            ensure_root(origin)?;
+
+            <CorrelationCheckConfig<T>>::set(check.clone());
+            Self::deposit_event(RawEvent::CorrelationCheckSet(check));
+            Ok(())
+        }
+
+        /// Pay the payouts of at least `plan.threshold` in `plan.installments` equal
+        /// installments, one per payout era. `None` pays every payout at once again,
+        /// installments already scheduled are paid as planned either way
+        #[weight = (<T as Trait>::WeightInfo::set_installment_plan(), DispatchClass::Operational)]
+        pub fn set_installment_plan(origin, plan: Option<InstallmentPlanOf<T>>) -> DispatchResult {
+            ensure_root(origin)?;
+            if let Some(plan) = &plan {
+                ensure!(plan.installments > 0, Error::<T>::InvalidInstallmentPlan);
+            }
+
+            <InstallmentPlanConfig<T>>::set(plan.clone());
+            Self::deposit_event(RawEvent::InstallmentPlanSet(plan));
             Ok(())
         }
     }
 }
 
 impl<T: Trait> Module<T> {
+    /// Ensure `origin` can allocate, returns the oracle behind it or `None` for the
+    /// fallback origin, which can only allocate while the oracles are stalled.
+    fn ensure_allocator(origin: T::Origin) -> Result<Option<T::AccountId>, Error<T>> {
+        match T::OracleOrigin::try_origin(origin) {
+            Ok(oracle) => Ok(Some(oracle)),
+            Err(origin) if Self::stalled() => T::FallbackOrigin::ensure_origin(origin)
+                .map(|_| None)
+                .map_err(|_| Error::<T>::OracleAccessDenied),
+            Err(_) => Err(Error::<T>::OracleAccessDenied),
+        }
+    }
+
+    /// Ensure the campaign is running at the time of the current block. Campaigns
+    /// are scheduled in wall-clock time, which block numbers only approximate.
+    fn ensure_campaign_running() -> DispatchResult {
+        if let Some((start, end)) = Self::campaign_schedule() {
+            let now = T::Time::now();
+            ensure!(now >= start, Error::<T>::CampaignNotStarted);
+            ensure!(now < end, Error::<T>::CampaignOver);
+        }
+        Ok(())
+    }
+
+    /// Typed reason of the failure of an item of a batch.
+    fn batch_item_error(e: DispatchError) -> BatchItemError {
+        let is = |error: Error<T>| e == DispatchError::from(error);
+        if is(Error::<T>::BeneficiaryBlocked) {
+            BatchItemError::BeneficiaryBlocked
+        } else if is(Error::<T>::AllocationBelowMinimum)
+            || is(Error::<T>::DoesNotSatisfyExistentialDeposit)
+        {
+            BatchItemError::AmountTooLow
+        } else if is(Error::<T>::TooManyCoinsToAllocate) || is(Error::<T>::DelegationExhausted) {
+            BatchItemError::BudgetExhausted
+        } else if is(Error::<T>::MissingCoverage) {
+            BatchItemError::MissingCoverage
+        } else if is(Error::<T>::NothingToClaim) {
+            BatchItemError::NothingToClaim
+        } else if is(Error::<T>::InvalidClaimProof) || is(Error::<T>::UnknownBatch) {
+            BatchItemError::InvalidProof
+        } else if is(Error::<T>::LeafAlreadyClaimed) {
+            BatchItemError::AlreadyClaimed
+        } else {
+            BatchItemError::Other(e)
+        }
+    }
+
+    /// Median of the prices of a US cent submitted by the oracles during the last
+    /// `PriceValidity` blocks, the lower one if there is an even number of them.
+    pub fn median_price() -> Option<BalanceOf<T>> {
+        let now = <frame_system::Module<T>>::block_number();
+        let mut prices = Self::oracles()
+            .iter()
+            .filter_map(|oracle| Self::price_of(oracle))
+            .filter(|(_, at)| now.saturating_sub(*at) <= T::PriceValidity::get())
+            .map(|(price, _)| price)
+            .collect::<Vec<_>>();
+        if prices.is_empty() {
+            return None;
+        }
+
+        prices.sort();
+        Some(prices[(prices.len() - 1) / 2])
+    }
+
+    /// Allocate `amount` to `to`, on behalf of `oracle` or, if `None`, of the fallback
+    /// origin, of a conversion of contribution points or of a dispute ruling. When
+    /// `delegation` is set the coins come from the slice delegated to that organization.
+    fn do_allocate(
+        oracle: Option<T::AccountId>,
+        delegation: Option<&T::AccountId>,
+        to: T::AccountId,
+        amount: BalanceOf<T>,
+        proof: Vec<u8>,
+    ) -> DispatchResult {
+        ensure!(
+            !pallet_emergency_shutdown::Module::<T>::shutdown(),
+            Error::<T>::UnderShutdown
+        );
+        ensure!(
+            amount >= T::MinimumAllocation::get(),
+            Error::<T>::AllocationBelowMinimum
+        );
+        ensure!(
+            !T::ComplianceCheck::contains(&to),
+            Error::<T>::BeneficiaryBlocked
+        );
+        // Once required, whatever the oracles and sub oracles allocate must be part of a
+        // batch referencing the coverage of an epoch
+        if (oracle.is_some() || delegation.is_some()) && T::Coverage::is_required() {
+            let era = Self::payout_era(<frame_system::Module<T>>::block_number());
+            ensure!(
+                proof.len() == H256::len_bytes()
+                    && BatchCoverage::contains_key(era, H256::from_slice(&proof)),
+                Error::<T>::MissingCoverage
+            );
+        }
+
+        let slice = delegation
+            .map(|organization| Self::delegation(organization).unwrap_or_else(Zero::zero));
+        if let Some(slice) = slice {
+            ensure!(amount <= slice, Error::<T>::DelegationExhausted);
+        }
+        // Coins delegated to organizations are out of reach of everyone else
+        let delegated = Self::delegated_coins();
+        let reserved = match slice {
+            Some(_) => delegated.saturating_sub(amount),
+            None => delegated,
+        };
+
+        let coins_already_allocated = Self::coins_consumed();
+        let coins_that_will_be_consumed = coins_already_allocated
+            .checked_add(&amount)
+            .ok_or("Overflow computing coins consumed")?;
+
+        ensure!(
+            coins_that_will_be_consumed.saturating_add(reserved)
+                <= T::MaximumCoinsEverAllocated::get(),
+            Error::<T>::TooManyCoinsToAllocate
+        );
+
+        let (amount_for_protocol, amount_for_grantee) =
+            T::FeeRounding::split(T::ProtocolFee::get(), amount);
+
+        // Grantees are paid once per era, what matters is what they will receive then
+        let era = Self::payout_era(<frame_system::Module<T>>::block_number());
+        let pending = Self::pending_payout(era, &to).saturating_add(amount_for_grantee);
+
+        Self::ensure_satisfy_existential_deposit(
+            &T::ProtocolFeeReceiver::account_id(),
+            amount_for_protocol,
+        )?;
+        Self::ensure_satisfy_existential_deposit(&to, pending)?;
+
+        <CoinsConsumed<T>>::put(coins_that_will_be_consumed);
+        if let (Some(organization), Some(slice)) = (delegation, slice) {
+            <Delegations<T>>::insert(organization, slice.saturating_sub(amount));
+            <DelegatedCoins<T>>::put(reserved);
+        }
+        // Allocations made by the fallback origin do not tell that the oracles are back
+        if let Some(oracle) = oracle {
+            let now = <frame_system::Module<T>>::block_number();
+            <OracleStatuses<T>>::mutate(&oracle, |status| {
+                status.allocated = status.allocated.saturating_add(amount);
+                status.last_seen = now;
+            });
+            <LastAllocation<T>>::put(now);
+            if Self::stalled() {
+                Stalled::put(false);
+                Self::deposit_event(RawEvent::AllocationsResumed);
+            }
+        }
+
+        T::Currency::resolve_creating(
+            &T::ProtocolFeeReceiver::account_id(),
+            T::Currency::issue(amount_for_protocol),
+        );
+        <PendingPayouts<T>>::insert(era, &to, pending);
+        T::OnAllocation::on_allocation(&to, amount_for_grantee);
+        if !Self::is_recipient(&to) {
+            <Recipients<T>>::insert(&to, true);
+            RecipientsCount::mutate(|count| *count = count.saturating_add(1));
+        }
+        if amount_for_grantee > Self::largest_allocation() {
+            <LargestAllocation<T>>::put(amount_for_grantee);
+        }
+        <Periods<T>>::mutate(T::ReportingPeriods::current(), |totals| {
+            totals.allocated = totals.allocated.saturating_add(amount_for_grantee);
+            totals.protocol_fees = totals.protocol_fees.saturating_add(amount_for_protocol);
+            totals.allocations = totals.allocations.saturating_add(1);
+        });
+        // Batches are committed to by their merkle root, remember it for unsigned claims
+        if proof.len() == H256::len_bytes() {
+            let root = H256::from_slice(&proof);
+            BatchRoots::insert(era, root, true);
+            batches::insert_leaf(era, &root, &merkle::leaf(&to, amount));
+        }
+        Self::mark_unsettled(era);
+
+        deposit_indexed_event::<T, <T as Trait>::Event>(
+            &[&to],
+            RawEvent::NewAllocation(to.clone(), amount_for_grantee, amount_for_protocol, proof)
+                .into(),
+        );
+
+        Ok(())
+    }
+
+    /// Flag the oracles as stalled when they did not allocate anything for
+    /// `StallThreshold` blocks. Returns the weight consumed.
+    fn detect_stall(n: T::BlockNumber) -> Weight {
+        let threshold = T::StallThreshold::get();
+        if threshold.is_zero() || Self::stalled() {
+            return T::DbWeight::get().reads(1);
+        }
+
+        let last = Self::last_allocation();
+        if last.is_zero() {
+            // Start counting from here on chains upgraded from a version not tracking
+            // the last allocation, and on new chains
+            <LastAllocation<T>>::put(n);
+            return T::DbWeight::get().reads_writes(2, 1);
+        }
+        if n.saturating_sub(last) >= threshold {
+            Stalled::put(true);
+            Self::deposit_event(RawEvent::AllocationsStalled(last));
+            return T::DbWeight::get().reads_writes(2, 2);
+        }
+        T::DbWeight::get().reads(2)
+    }
+
+    /// Add the pending oracles due at block `n` to the set. Returns the number of
+    /// oracles activated.
+    fn activate_oracles(n: T::BlockNumber) -> u32 {
+        let mut pending = Self::pending_oracles();
+        let due = pending.iter().take_while(|(_, at)| *at <= n).count();
+        if due == 0 {
+            return 0;
+        }
+
+        let mut oracles = Self::oracles();
+        for (oracle, _) in pending.drain(..due) {
+            if let Err(position) = oracles.binary_search(&oracle) {
+                oracles.insert(position, oracle.clone());
+            }
+            Self::deposit_event(RawEvent::OracleActivated(oracle));
+        }
+        <Oracles<T>>::put(oracles);
+        <PendingOracles<T>>::put(pending);
+
+        due as u32
+    }
+
+    /// Credit `who` with a payout and the dust kept for it, locked against transfers
+    /// if it happens before `RewardsUnlockBlock`. Payouts too small to create the
+    /// account of `who` are handed to `OnDust` instead.
+    ///
+    /// The lock never goes below the existential deposit: withdrawals do not check the
+    /// references of an account, a smaller lock would let one reap it with its rewards.
+    fn pay_out(who: &T::AccountId, amount: BalanceOf<T>) {
+        let amount = amount.saturating_add(<Dust<T>>::take(who));
+        // Depositing less than the existential deposit to a dead account is a no-op
+        if amount < T::ExistentialDeposit::get() && T::Currency::total_balance(who).is_zero() {
+            T::OnDust::on_dust(who, amount);
+            Self::deposit_event(RawEvent::PayoutDusted(who.clone(), amount));
+            return;
+        }
+
+        T::Currency::deposit_creating(who, amount);
+        if <frame_system::Module<T>>::block_number() < T::RewardsUnlockBlock::get() {
+            let locked = Self::locked_rewards(who).saturating_add(amount);
+            T::Currency::set_lock(
+                REWARDS_LOCK_ID,
+                who,
+                locked.max(T::ExistentialDeposit::get()),
+                WithdrawReason::Transfer.into(),
+            );
+            <LockedRewards<T>>::insert(who, locked);
+        }
+    }
+
+    /// Pay out `amount` to `who`, or only its first installment if the installment plan
+    /// covers it, the others being due at the end of the current payout era and the
+    /// following ones.
+    fn pay_out_or_schedule(who: &T::AccountId, amount: BalanceOf<T>) {
+        let era = Self::payout_era(<frame_system::Module<T>>::block_number());
+        if Self::schedule_installments(who, amount, era) {
+            Self::mark_unsettled(era);
+        }
+    }
+
+    /// Pay out `amount` to `who`, or only its first installment with the others due at
+    /// the end of payout era `due`, one per era. Returns whether any was scheduled.
+    fn schedule_installments(who: &T::AccountId, amount: BalanceOf<T>, due: u32) -> bool {
+        let plan = match Self::installment_plan() {
+            Some(plan) if plan.installments > 1 && amount >= plan.threshold => plan,
+            _ => {
+                Self::pay_out(who, amount);
+                return false;
+            }
+        };
+        let installment = amount / plan.installments.into();
+        if installment.is_zero() {
+            Self::pay_out(who, amount);
+            return false;
+        }
+
+        // Rounding leftovers go to the first installment
+        let left = plan.installments - 1;
+        Self::pay_out(
+            who,
+            amount.saturating_sub(installment.saturating_mul(left.into())),
+        );
+        <Installments<T>>::mutate(due, who, |installments| {
+            installments.push((installment, left))
+        });
+        deposit_indexed_event::<T, <T as Trait>::Event>(
+            &[who],
+            RawEvent::PayoutInInstallments(who.clone(), installment, plan.installments).into(),
+        );
+        true
+    }
+
+    /// Remember that payout era `era` has payouts or installments to settle.
+    fn mark_unsettled(era: u32) {
+        let mut eras = Self::unsettled_eras();
+        if eras.last() != Some(&era) {
+            eras.push(era);
+            UnsettledEras::put(eras);
+        }
+    }
+
+    /// Return `amount` to the allocations budget rather than paying it out if `who`
+    /// was blocked after the coins were allocated. Returns whether it was withheld.
+    fn withhold_if_blocked(who: &T::AccountId, amount: BalanceOf<T>) -> bool {
+        if !T::ComplianceCheck::contains(who) {
+            return false;
+        }
+
+        <CoinsConsumed<T>>::mutate(|consumed| *consumed = consumed.saturating_sub(amount));
+        <Periods<T>>::mutate(T::ReportingPeriods::current(), |totals| {
+            totals.withheld = totals.withheld.saturating_add(amount)
+        });
+        Self::deposit_event(RawEvent::PayoutWithheld(who.clone(), amount));
+        true
+    }
+
+    /// Payload grantees sign to authorize a relayer to claim their payouts.
+    pub fn authorization_payload(authorization: &ClaimAuthorizationOf<T>) -> Vec<u8> {
+        (CLAIM_AUTHORIZATION_CONTEXT, authorization).encode()
+    }
+
+    /// Check that `authorization` lets `relayer` claim on behalf of its grantee now.
+    fn check_authorization(
+        relayer: &T::AccountId,
+        authorization: &ClaimAuthorizationOf<T>,
+        signature: &T::Signature,
+    ) -> DispatchResult {
+        ensure!(
+            &authorization.relayer == relayer,
+            Error::<T>::RelayerNotAuthorized
+        );
+        ensure!(
+            <frame_system::Module<T>>::block_number() <= authorization.expires_at,
+            Error::<T>::AuthorizationExpired
+        );
+        ensure!(
+            authorization.nonce == Self::claim_authorization_nonce(&authorization.grantee),
+            Error::<T>::AuthorizationRevoked
+        );
+        ensure!(
+            signature.verify(
+                &Self::authorization_payload(authorization)[..],
+                &authorization.grantee
+            ),
+            Error::<T>::InvalidAuthorizationSignature
+        );
+        Ok(())
+    }
+
+    /// Pay out what `who` is owed for `era` if `proof` shows that it was allocated
+    /// `amount`, and mark the leaf as claimed. Storage is left untouched on errors.
+    fn claim_proven(
+        era: u32,
+        who: &T::AccountId,
+        amount: BalanceOf<T>,
+        proof: &MerkleProof,
+    ) -> DispatchResult {
+        Self::check_claim_proof(era, who, amount, proof)?;
+        ensure!(
+            !T::ComplianceCheck::contains(who),
+            Error::<T>::BeneficiaryBlocked
+        );
+        let payout = Self::pending_payout(era, who);
+        ensure!(!payout.is_zero(), Error::<T>::NothingToClaim);
+
+        <PendingPayouts<T>>::remove(era, who);
+        batches::mark_claimed(era, &proof.root, &proof.leaf);
+        Self::pay_out_or_schedule(who, payout);
+
+        Self::deposit_event(RawEvent::PayoutClaimed(who.clone(), payout));
+        Ok(())
+    }
+
+    /// Check that `proof` is a valid proof, never used before, of the allocation of
+    /// `amount` to `who` in one of the batches of `era`.
+    fn check_claim_proof(
+        era: u32,
+        who: &T::AccountId,
+        amount: BalanceOf<T>,
+        proof: &MerkleProof,
+    ) -> Result<(), Error<T>> {
+        ensure!(
+            proof.leaf == merkle::leaf(who, amount) && merkle::verify(proof),
+            Error::<T>::InvalidClaimProof
+        );
+        ensure!(
+            Self::is_batch_root(era, proof.root),
+            Error::<T>::UnknownBatch
+        );
+        ensure!(
+            !batches::is_claimed(era, &proof.root, &proof.leaf),
+            Error::<T>::LeafAlreadyClaimed
+        );
+        Ok(())
+    }
+
     pub fn is_oracle(who: T::AccountId) -> bool {
-        Self::oracles().contains(&who)
+        Self::oracles().binary_search(&who).is_ok()
+    }
+
+    /// Check `allocations` against the correlation check, if any. Batches going over
+    /// it are rejected or flagged with an event depending on its configuration.
+    fn check_correlation(allocations: &[(T::AccountId, BalanceOf<T>)]) -> DispatchResult {
+        let check = match Self::correlation_check() {
+            Some(check) => check,
+            None => return Ok(()),
+        };
+
+        let now = <frame_system::Module<T>>::block_number();
+        let is_new = |who: &T::AccountId| {
+            Self::account_created_at(who)
+                .map_or(false, |created| now.saturating_sub(created) < check.window)
+        };
+        let (total, new) = allocations.iter().fold(
+            (BalanceOf::<T>::zero(), BalanceOf::<T>::zero()),
+            |(total, new), (who, amount)| {
+                let new = if is_new(who) {
+                    new.saturating_add(*amount)
+                } else {
+                    new
+                };
+                (total.saturating_add(*amount), new)
+            },
+        );
+        if total.is_zero() {
+            return Ok(());
+        }
+
+        let share = Perbill::from_rational_approximation(new, total);
+        if share > check.max_share {
+            ensure!(!check.reject, Error::<T>::CorrelatedBatch);
+            Self::deposit_event(RawEvent::CorrelatedBatch(share));
+        }
+        Ok(())
     }
 
-    fn ensure_oracle(origin: T::Origin) -> DispatchResult {
-        let sender = ensure_signed(origin)?;
-        ensure!(Self::is_oracle(sender), Error::<T>::OracleAccessDenied);
+    /// Number of members of the oracles committee, pending oracles included.
+    pub fn committee_size() -> u32 {
+        Self::oracles()
+            .len()
+            .saturating_add(Self::pending_oracles().len()) as u32
+    }
 
+    /// Ensure the oracles committee may be resized to `size` members, changes made
+    /// through other pallets, such as registry syncs, are checked against it too.
+    pub fn ensure_committee_size(size: u32) -> DispatchResult {
+        if let Some((min, max)) = Self::committee_bounds() {
+            let current = Self::committee_size();
+            ensure!(
+                size >= min || size >= current,
+                Error::<T>::CommitteeTooSmall
+            );
+            ensure!(
+                size <= max || size <= current,
+                Error::<T>::CommitteeTooLarge
+            );
+        }
         Ok(())
     }
 
+    /// Status of every oracle currently in the set.
+    pub fn oracle_statuses() -> Vec<(T::AccountId, OracleStatusOf<T>)> {
+        Self::oracles()
+            .into_iter()
+            .map(|oracle| {
+                let status = Self::oracle_status(&oracle);
+                (oracle, status)
+            })
+            .collect()
+    }
+
+    /// Return true if a `claim` of `who` for `era` would pay out something, meant to
+    /// only waive the fees of claims that will succeed.
+    pub fn can_claim(era: u32, who: &T::AccountId) -> bool {
+        !pallet_emergency_shutdown::Module::<T>::shutdown()
+            && !T::ComplianceCheck::contains(who)
+            && !Self::pending_payout(era, who).is_zero()
+    }
+
+    /// Payout era `n` belongs to.
+    pub fn payout_era(n: T::BlockNumber) -> u32 {
+        (n / T::PayoutEraDuration::get().max(One::one())).saturated_into()
+    }
+
+    /// Pay out up to `max` pending payouts, or installments, of the eras before
+    /// `current`, oldest era first. Returns the number of payouts settled.
+    fn settle_payouts(current: u32, max: u32) -> u32 {
+        let mut eras = Self::unsettled_eras();
+        let mut changed = false;
+        // Installments of large payouts are due at the end of the current era
+        let mut scheduled = false;
+        let mut settled = 0;
+        while let Some(&era) = eras.first() {
+            if era >= current || settled >= max {
+                break;
+            }
+
+            let left = max - settled;
+            let payouts = <PendingPayouts<T>>::iter_prefix(era)
+                .take(left as usize)
+                .collect::<Vec<_>>();
+            for (who, amount) in &payouts {
+                <PendingPayouts<T>>::remove(era, who);
+                if !Self::withhold_if_blocked(who, *amount) {
+                    scheduled |= Self::schedule_installments(who, *amount, current);
+                }
+            }
+            // Installments are settled once the payouts of the era are
+            let installments = <Installments<T>>::iter_prefix(era)
+                .take(left as usize - payouts.len())
+                .collect::<Vec<_>>();
+            for (who, due) in &installments {
+                <Installments<T>>::remove(era, who);
+                for (amount, count) in due {
+                    // The installments left are withheld at once
+                    let total = amount.saturating_mul((*count).into());
+                    if Self::withhold_if_blocked(who, total) {
+                        continue;
+                    }
+
+                    Self::pay_out(who, *amount);
+                    if *count > 1 {
+                        <Installments<T>>::mutate(current, who, |installments| {
+                            installments.push((*amount, count - 1))
+                        });
+                        scheduled = true;
+                    }
+                }
+            }
+            let processed = (payouts.len() + installments.len()) as u32;
+            settled += processed;
+
+            // Otherwise there may be more to settle in the next block
+            if processed < left {
+                eras.remove(0);
+                changed = true;
+                // Nothing is left to claim, roots and leaves of the era are useless
+                PrunableEras::append(era);
+                Self::deposit_event(RawEvent::PayoutsSettled(era));
+            }
+        }
+
+        if scheduled && eras.last() != Some(&current) {
+            eras.push(current);
+            changed = true;
+        }
+        if changed {
+            UnsettledEras::put(eras);
+        }
+        settled
+    }
+
+    /// Prune up to `max` leaves and roots of the batches of settled eras, oldest era
+    /// first. Returns the number of leaves and roots pruned.
+    fn prune_batches(max: u32) -> u32 {
+        let mut eras = Self::prunable_eras();
+        let mut changed = false;
+        let mut pruned = 0;
+        while let Some(&era) = eras.first() {
+            if pruned >= max {
+                break;
+            }
+
+            let root = match BatchRoots::iter_prefix(era).next() {
+                Some((root, _)) => root,
+                None => {
+                    eras.remove(0);
+                    changed = true;
+                    pruned += 1;
+                    continue;
+                }
+            };
+            let (removed, emptied) = batches::prune_leaves(era, &root, max - pruned);
+            pruned += removed;
+            // Otherwise there are more leaves to prune in the next block
+            if !emptied || pruned >= max {
+                break;
+            }
+
+            batches::prune(era, &root);
+            BatchRoots::remove(era, root);
+            BatchCoverage::remove(era, root);
+            pruned += 1;
+        }
+
+        if changed {
+            PrunableEras::put(eras);
+        }
+        pruned
+    }
+
     fn ensure_satisfy_existential_deposit(
         who: &T::AccountId,
         amount: BalanceOf<T>,
@@ -143,18 +1615,306 @@ impl<T: Trait> Module<T> {
     }
 }
 
+impl<T: Trait> HousekeepingTask<T::BlockNumber> for Module<T> {
+    fn housekeep(n: T::BlockNumber, budget: Weight) -> Weight {
+        let mut consumed = T::DbWeight::get().reads(1);
+        if budget < consumed {
+            return 0;
+        }
+        if pallet_emergency_shutdown::Module::<T>::shutdown() {
+            return consumed;
+        }
+
+        // Both are cheap and bounded by the size of the oracles set, they always run
+        consumed = consumed.saturating_add(Self::detect_stall(n));
+        let activated = Self::activate_oracles(n);
+        consumed = consumed.saturating_add(<T as Trait>::WeightInfo::activate_oracles(activated));
+
+        // Payouts which do not fit in the budget are settled in the following blocks
+        let remaining = budget.saturating_sub(consumed);
+        let base = <T as Trait>::WeightInfo::settle_payouts(0);
+        let per_payout = <T as Trait>::WeightInfo::settle_payouts(1)
+            .saturating_sub(base)
+            .max(1);
+        let max = (remaining.saturating_sub(base) / per_payout)
+            .min(T::MaxSettlementsPerBlock::get().into()) as u32;
+        if remaining < base || max.is_zero() {
+            return consumed;
+        }
+
+        let settled = Self::settle_payouts(Self::payout_era(n), max);
+        consumed = consumed.saturating_add(<T as Trait>::WeightInfo::settle_payouts(settled));
+
+        // Batches of the settled eras are pruned with what is left of the budget
+        let remaining = budget.saturating_sub(consumed);
+        let base = <T as Trait>::WeightInfo::prune_batches(0);
+        let per_item = <T as Trait>::WeightInfo::prune_batches(1)
+            .saturating_sub(base)
+            .max(1);
+        let max = (remaining.saturating_sub(base) / per_item)
+            .min(T::MaxPrunedPerBlock::get().into()) as u32;
+        if remaining < base || max.is_zero() {
+            return consumed;
+        }
+
+        let pruned = Self::prune_batches(max);
+        consumed.saturating_add(<T as Trait>::WeightInfo::prune_batches(pruned))
+    }
+}
+
+impl<T: Trait> AllocationBatches<T::AccountId, BalanceOf<T>, H256> for Module<T> {
+    fn is_batch(era: u32, root: &H256) -> bool {
+        Self::is_batch_root(era, root)
+    }
+
+    fn correct(who: &T::AccountId, amount: BalanceOf<T>) -> Result<(), &'static str> {
+        Self::do_allocate(None, None, who.clone(), amount, Vec::new()).map_err(Into::into)
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_batch(era: u32, root: &H256) {
+        BatchRoots::insert(era, root, true);
+    }
+}
+
+impl<T: Trait> ValidateUnsigned for Module<T> {
+    type Call = Call<T>;
+
+    fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+        let (era, who, amount, proof) = match call {
+            Call::claim_unsigned(era, who, amount, proof) => (era, who, amount, proof),
+            _ => return InvalidTransaction::Call.into(),
+        };
+        if pallet_emergency_shutdown::Module::<T>::shutdown() {
+            return InvalidTransaction::Call.into();
+        }
+        Self::check_claim_proof(*era, who, *amount, proof).map_err(|e| match e {
+            Error::<T>::LeafAlreadyClaimed => InvalidTransaction::Stale,
+            _ => InvalidTransaction::BadProof,
+        })?;
+        if Self::pending_payout(*era, who).is_zero() {
+            return InvalidTransaction::Stale.into();
+        }
+        if T::ComplianceCheck::contains(who) {
+            return InvalidTransaction::Call.into();
+        }
+
+        ValidTransaction::with_tag_prefix("AllocationsClaim")
+            .priority(T::UnsignedPriority::get())
+            // A leaf can only be claimed once, the pool drops the duplicates
+            .and_provides((era, proof.leaf))
+            .longevity(64)
+            .propagate(true)
+            .build()
+    }
+
+    fn pre_dispatch(call: &Self::Call) -> Result<(), TransactionValidityError> {
+        let (block, count) = Self::unsigned_claims_in_block();
+        if block == <frame_system::Module<T>>::block_number()
+            && count >= T::MaxUnsignedClaimsPerBlock::get()
+        {
+            // `ExhaustsResources` keeps the claim in the pool for a later block
+            return Err(InvalidTransaction::ExhaustsResources.into());
+        }
+        Self::validate_unsigned(TransactionSource::InBlock, call).map(|_| ())
+    }
+}
+
+/// Ensure the origin is signed by one of the oracles, returns the oracle's account.
+pub struct EnsureOracle<T>(PhantomData<T>);
+impl<T: Trait> EnsureOrigin<T::Origin> for EnsureOracle<T> {
+    type Success = T::AccountId;
+
+    fn try_origin(o: T::Origin) -> Result<Self::Success, T::Origin> {
+        o.into().and_then(|o| match o {
+            RawOrigin::Signed(who) if Module::<T>::is_oracle(who.clone()) => Ok(who),
+            r => Err(T::Origin::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn successful_origin() -> T::Origin {
+        let oracle = Module::<T>::oracles().first().cloned().unwrap_or_default();
+        T::Origin::from(RawOrigin::Signed(oracle))
+    }
+}
+
+/// Reject the operational calls of the oracles when they enter the pool if their
+/// sender could not submit them, so that other accounts can not use the operational
+/// share of the blocks.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckOracle<T: Trait + Send + Sync>(PhantomData<T>);
+
+impl<T: Trait + Send + Sync> CheckOracle<T> {
+    /// Create a new instance of the extension.
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Trait + Send + Sync> Default for CheckOracle<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Trait + Send + Sync> Debug for CheckOracle<T> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "CheckOracle")
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for CheckOracle<T>
+where
+    <T as frame_system::Trait>::Call: IsSubType<Call<T>>,
+{
+    const IDENTIFIER: &'static str = "CheckOracle";
+    type AccountId = T::AccountId;
+    type Call = <T as frame_system::Trait>::Call;
+    type AdditionalSigned = ();
+    type Pre = ();
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        _info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> TransactionValidity {
+        let origin = || T::Origin::from(RawOrigin::Signed(who.clone()));
+        let allowed = match call.is_sub_type() {
+            Some(Call::allocate(..))
+            | Some(Call::allocate_to_device(..))
+            | Some(Call::allocate_to_organization(..))
+            | Some(Call::allocate_fiat(..))
+            | Some(Call::allocate_batch(..))
+            | Some(Call::reference_coverage(..)) => Module::<T>::ensure_allocator(origin()).is_ok(),
+            Some(Call::submit_price(..)) | Some(Call::credit_points(..)) => {
+                T::OracleOrigin::try_origin(origin()).is_ok()
+            }
+            Some(Call::allocate_delegated(..)) => Module::<T>::sub_oracle_of(who).is_some(),
+            _ => true,
+        };
+
+        if !allowed {
+            return Err(InvalidTransaction::Call.into());
+        }
+        Ok(ValidTransaction::default())
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        use frame_support::storage::IterableStorageMap;
+
+        ensure!(
+            Self::oracles().windows(2).all(|pair| pair[0] < pair[1]),
+            "oracles are not sorted or contain duplicates"
+        );
+        ensure!(
+            Self::storage_version() == migrations::CURRENT,
+            "storage was not migrated"
+        );
+        ensure!(
+            Self::unsettled_eras()
+                .windows(2)
+                .all(|pair| pair[0] < pair[1]),
+            "unsettled eras are not sorted or contain duplicates"
+        );
+        let pending = Self::pending_oracles();
+        ensure!(
+            pending.windows(2).all(|pair| pair[0].1 <= pair[1].1),
+            "pending oracles are not sorted by activation block"
+        );
+        ensure!(
+            pending
+                .iter()
+                .all(|(oracle, _)| !Self::is_oracle(oracle.clone())),
+            "pending oracles are already active"
+        );
+        let delegated = <Delegations<T>>::iter()
+            .fold(Zero::zero(), |acc: BalanceOf<T>, (_, slice)| {
+                acc.saturating_add(slice)
+            });
+        ensure!(
+            delegated == Self::delegated_coins(),
+            "delegated coins do not match the delegations"
+        );
+        let eras = Self::unsettled_eras();
+        ensure!(
+            <Installments<T>>::iter().all(|(era, _, _)| eras.binary_search(&era).is_ok()),
+            "installments are due in settled eras"
+        );
+
+        Ok(())
+    }
+}
+
 impl<T: Trait> ChangeMembers<T::AccountId> for Module<T> {
     fn change_members_sorted(
-        _incoming: &[T::AccountId],
-        _outgoing: &[T::AccountId],
+        incoming: &[T::AccountId],
+        outgoing: &[T::AccountId],
         new: &[T::AccountId],
     ) {
-        <Oracles<T>>::put(new);
+        for oracle in outgoing {
+            <OracleStatuses<T>>::remove(oracle);
+            <Prices<T>>::remove(oracle);
+        }
+
+        // Removals are immediate, including the ones of oracles not activated yet
+        let mut pending = Self::pending_oracles();
+        pending.retain(|(oracle, _)| outgoing.binary_search(oracle).is_err());
+
+        let delay = T::OracleActivationDelay::get();
+        if !delay.is_zero() {
+            let at = <frame_system::Module<T>>::block_number().saturating_add(delay);
+            for oracle in incoming {
+                pending.push((oracle.clone(), at));
+                Self::deposit_event(RawEvent::OracleAdditionScheduled(oracle.clone(), at));
+            }
+        }
+
+        let oracles = new
+            .iter()
+            .filter(|member| !pending.iter().any(|(oracle, _)| oracle == *member))
+            .cloned()
+            .collect::<Vec<_>>();
+        <Oracles<T>>::put(oracles);
+        <PendingOracles<T>>::put(pending);
     }
 }
 
 impl<T: Trait> InitializeMembers<T::AccountId> for Module<T> {
     fn initialize_members(init: &[T::AccountId]) {
-        <Oracles<T>>::put(init);
+        let mut oracles = init.to_vec();
+        oracles.sort();
+        <Oracles<T>>::put(oracles);
+    }
+}
+
+/// Accounts are created and reaped by the system pallet, which tells when so that the
+/// correlation check knows their age.
+impl<T: Trait> OnNewAccount<T::AccountId> for Module<T> {
+    fn on_new_account(who: &T::AccountId) {
+        <AccountCreatedAt<T>>::insert(who, <frame_system::Module<T>>::block_number());
+    }
+}
+
+impl<T: Trait> OnKilledAccount<T::AccountId> for Module<T> {
+    fn on_killed_account(who: &T::AccountId) {
+        <AccountCreatedAt<T>>::remove(who);
     }
 }
@@ -0,0 +1,62 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Storage migrations. The layout in use is recorded in `StorageVersion` and every
+//! migration brings it one version forward, `migrate` runs the ones still needed
+//! from `on_runtime_upgrade`.
+
+mod v1;
+
+use crate::{StorageVersion, Trait};
+use frame_support::{traits::Get, weights::Weight, StorageValue};
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::RuntimeDebug;
+
+/// Storage layouts of the pallet.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum Releases {
+    /// Chains started before the layout was versioned
+    V0,
+    /// The oracles set is sorted
+    V1,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V0
+    }
+}
+
+/// Layout the code expects.
+pub const CURRENT: Releases = Releases::V1;
+
+/// Run the migrations the stored layout still needs, returns their weight.
+pub fn migrate<T: Trait>() -> Weight {
+    let mut weight = T::DbWeight::get().reads(1);
+    let version = StorageVersion::get();
+
+    if version < Releases::V1 {
+        weight = weight.saturating_add(v1::migrate::<T>());
+    }
+
+    if version < CURRENT {
+        StorageVersion::put(CURRENT);
+        weight = weight.saturating_add(T::DbWeight::get().writes(1));
+    }
+    weight
+}
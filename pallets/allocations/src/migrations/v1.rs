@@ -0,0 +1,67 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Oracles are looked up with a binary search, sort the set of chains which stored it
+//! as given at genesis.
+
+use crate::{Oracles, Trait};
+use frame_support::{traits::Get, weights::Weight, StorageValue};
+
+pub fn migrate<T: Trait>() -> Weight {
+    <Oracles<T>>::mutate(|oracles| {
+        oracles.sort();
+        oracles.dedup();
+    });
+
+    T::DbWeight::get().reads_writes(1, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        migrations::{migrate, Releases, CURRENT},
+        tests::{new_test_ext, Test},
+        Module, Oracles, StorageVersion,
+    };
+    use frame_support::StorageValue;
+
+    #[test]
+    fn sorts_the_oracles() {
+        new_test_ext().execute_with(|| {
+            <Oracles<Test>>::put(vec![9, 3, 7, 3]);
+            assert_eq!(Module::<Test>::storage_version(), Releases::V0);
+
+            migrate::<Test>();
+
+            assert_eq!(Module::<Test>::oracles(), vec![3, 7, 9]);
+            assert_eq!(Module::<Test>::storage_version(), CURRENT);
+        })
+    }
+
+    #[test]
+    fn only_runs_once() {
+        new_test_ext().execute_with(|| {
+            StorageVersion::put(Releases::V1);
+            <Oracles<Test>>::put(vec![9, 3]);
+
+            migrate::<Test>();
+
+            assert_eq!(Module::<Test>::oracles(), vec![9, 3]);
+        })
+    }
+}
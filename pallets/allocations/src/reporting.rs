@@ -0,0 +1,34 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Reporting periods the allocations are accounted in. Totals are kept per period so
+//! that emission reports can be read from the chain state, without replaying events.
+
+/// Tells which reporting period the current block falls in. Periods must never go
+/// back, the totals of a period are final once the next one started.
+pub trait ReportingPeriods {
+    /// Index of the period the current block falls in.
+    fn current() -> u32;
+}
+
+/// A single period covering the whole life of the chain.
+impl ReportingPeriods for () {
+    fn current() -> u32 {
+        0
+    }
+}
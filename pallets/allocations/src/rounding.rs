@@ -0,0 +1,68 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rounding of the protocol fee taken on allocations. A fee rarely divides an amount
+//! exactly, the policy decides who receives the remainder. Whatever the policy, the
+//! two shares always add up to the allocated amount.
+
+use sp_runtime::{traits::AtLeast32BitUnsigned, Perbill};
+
+pub trait RoundingPolicy {
+    /// Split `amount` into the protocol's share, at a rate of `fee`, and the
+    /// beneficiary's.
+    fn split<Balance: AtLeast32BitUnsigned + Copy>(
+        fee: Perbill,
+        amount: Balance,
+    ) -> (Balance, Balance);
+}
+
+/// Round the protocol's share down, the remainder goes to the beneficiary.
+pub struct RemainderToBeneficiary;
+impl RoundingPolicy for RemainderToBeneficiary {
+    fn split<Balance: AtLeast32BitUnsigned + Copy>(
+        fee: Perbill,
+        amount: Balance,
+    ) -> (Balance, Balance) {
+        let protocol = fee.mul_floor(amount);
+        (protocol, amount.saturating_sub(protocol))
+    }
+}
+
+/// Round the protocol's share up, the remainder goes to the protocol.
+pub struct RemainderToProtocol;
+impl RoundingPolicy for RemainderToProtocol {
+    fn split<Balance: AtLeast32BitUnsigned + Copy>(
+        fee: Perbill,
+        amount: Balance,
+    ) -> (Balance, Balance) {
+        let protocol = fee.mul_ceil(amount);
+        (protocol, amount.saturating_sub(protocol))
+    }
+}
+
+/// Round the protocol's share to the nearest unit, how fees used to be computed.
+pub struct Nearest;
+impl RoundingPolicy for Nearest {
+    fn split<Balance: AtLeast32BitUnsigned + Copy>(
+        fee: Perbill,
+        amount: Balance,
+    ) -> (Balance, Balance) {
+        let protocol = fee * amount;
+        (protocol, amount.saturating_sub(protocol))
+    }
+}
@@ -22,15 +22,18 @@ use super::*;
 
 use frame_support::{
     assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, parameter_types,
-    weights::Weight,
+    storage::child,
+    traits::{ExistenceRequirement, ReservableCurrency, WithdrawReasons},
+    weights::GetDispatchInfo,
 };
 use frame_system::EnsureSignedBy;
-use sp_core::H256;
 use sp_runtime::{
-    testing::Header,
-    traits::{BlakeTwo256, IdentityLookup},
+    testing::{TestSignature, UintAuthorityId},
+    traits::BlakeTwo256,
     Perbill,
 };
+use std::cell::RefCell;
+use test_utils::{impl_mock_balances, impl_mock_system};
 
 impl_outer_origin! {
     pub enum Origin for Test {}
@@ -41,59 +44,38 @@ impl_outer_origin! {
 // configuration traits of modules we want to use.
 #[derive(Clone, Eq, PartialEq)]
 pub struct Test;
-parameter_types! {
-    pub const BlockHashCount: u64 = 250;
-    pub const MaximumBlockWeight: Weight = 1024;
-    pub const MaximumBlockLength: u32 = 2 * 1024;
-    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
-}
-impl frame_system::Trait for Test {
-    type Origin = Origin;
-    type Call = ();
-    type Index = u64;
-    type BlockNumber = u64;
-    type Hash = H256;
-    type Hashing = BlakeTwo256;
-    type AccountId = u64;
-    type Lookup = IdentityLookup<Self::AccountId>;
-    type Header = Header;
-    type Event = ();
-    type BlockHashCount = BlockHashCount;
-    type MaximumBlockWeight = MaximumBlockWeight;
-    type MaximumBlockLength = MaximumBlockLength;
-    type AvailableBlockRatio = AvailableBlockRatio;
-    type Version = ();
-    type PalletInfo = ();
-    type AccountData = pallet_balances::AccountData<u64>;
-    type OnNewAccount = ();
-    type OnKilledAccount = ();
-    type DbWeight = ();
-    type BlockExecutionWeight = ();
-    type ExtrinsicBaseWeight = ();
-    type MaximumExtrinsicWeight = MaximumBlockWeight;
-    type BaseCallFilter = ();
-    type SystemWeightInfo = ();
-}
+impl_mock_system!(
+    Test,
+    Origin,
+    (),
+    pallet_balances::AccountData<u64>,
+    Allocations,
+    Allocations
+);
 parameter_types! {
     pub const ExistentialDeposit: u64 = 2;
-    pub const MaxLocks: u32 = 50;
-}
-impl pallet_balances::Trait for Test {
-    type Balance = u64;
-    type Event = ();
-    type DustRemoval = ();
-    type ExistentialDeposit = ExistentialDeposit;
-    type MaxLocks = MaxLocks;
-    type AccountStore = frame_system::Module<Test>;
-    type WeightInfo = ();
 }
+impl_mock_balances!(Test, ExistentialDeposit);
 
 ord_parameter_types! {
     pub const ShutdownAdmin: u64 = 21;
+    pub const Fallback: u64 = 22;
+    pub const PointsAdmin: u64 = 23;
+    pub const BudgetAdmin: u64 = 24;
+}
+parameter_types! {
+    pub const MinimumPeriod: u64 = 1;
+}
+impl pallet_timestamp::Trait for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = MinimumPeriod;
+    type WeightInfo = ();
 }
 impl pallet_emergency_shutdown::Trait for Test {
     type Event = ();
     type ShutdownOrigin = EnsureSignedBy<ShutdownAdmin, u64>;
+    type WeightInfo = ();
 }
 
 parameter_types! {
@@ -101,8 +83,146 @@ parameter_types! {
     pub const Hacker: u64 = 1;
     pub const Grantee: u64 = 2;
     pub const Receiver: u64 = 3;
-    pub const CoinsLimit: u64 = 1_000_000;
+    pub const MinimumAllocation: u64 = 1;
     pub const Fee: Perbill = Perbill::from_percent(10);
+    pub const PayoutEraDuration: u64 = 10;
+    pub const MaxSettlementsPerBlock: u32 = 2;
+    pub const MaxPrunedPerBlock: u32 = 3;
+    pub const MaxUnsignedClaimsPerBlock: u32 = 1;
+    pub const UnsignedPriority: u64 = 1;
+    pub const MaxClaimsPerBatch: u32 = 3;
+    pub const RewardsUnlockBlock: u64 = 5;
+    pub const PriceValidity: u64 = 10;
+    pub const StallThreshold: u64 = 20;
+    pub const OracleActivationDelay: u64 = 5;
+}
+thread_local! {
+    static COINS_LIMIT: RefCell<u64> = RefCell::new(0);
+    static DENIED: RefCell<Vec<u64>> = RefCell::new(vec![]);
+    static CREATED_ORGANIZATIONS: RefCell<Vec<u64>> = RefCell::new(vec![]);
+    static COVERAGE_REQUIRED: RefCell<bool> = RefCell::new(false);
+    static CLOSED_EPOCHS: RefCell<Vec<u32>> = RefCell::new(vec![]);
+    static DUST_POLICY: RefCell<DustPolicy> = RefCell::new(DustPolicy::Burn);
+    static ALLOCATED: RefCell<Vec<(u64, u64)>> = RefCell::new(vec![]);
+}
+pub struct CoinsLimit;
+impl Get<u64> for CoinsLimit {
+    fn get() -> u64 {
+        COINS_LIMIT.with(|c| *c.borrow())
+    }
+}
+/// Device 100 is owned by the grantee, other devices are unknown.
+pub struct Devices;
+impl DeviceResolver<u64, u64> for Devices {
+    fn payout_account(device: &u64) -> Option<u64> {
+        if *device == 100 {
+            Some(Grantee::get())
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_device(_device: &u64, _owner: &u64) {}
+}
+pub struct Denylist;
+impl Contains<u64> for Denylist {
+    fn sorted_members() -> Vec<u64> {
+        DENIED.with(|d| d.borrow().clone())
+    }
+}
+/// The keyless account of `controller` is `controller + 1000`.
+pub struct Organizations;
+impl KeylessAccounts<u64> for Organizations {
+    fn keyless_account(controller: &u64) -> Option<u64> {
+        Some(controller + 1000)
+    }
+
+    fn create_keyless_account(controller: &u64) {
+        CREATED_ORGANIZATIONS.with(|c| c.borrow_mut().push(*controller));
+    }
+}
+/// Epochs are committed to by a root made of their number.
+pub struct Coverage;
+impl CoverageEpochs<H256> for Coverage {
+    fn is_required() -> bool {
+        COVERAGE_REQUIRED.with(|r| *r.borrow())
+    }
+
+    fn coverage_root(epoch: u32) -> Option<H256> {
+        if CLOSED_EPOCHS.with(|e| e.borrow().contains(&epoch)) {
+            Some(H256::from_low_u64_be(epoch.into()))
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_coverage(epoch: u32, _root: &H256) {
+        CLOSED_EPOCHS.with(|e| e.borrow_mut().push(epoch));
+    }
+}
+#[derive(Clone, Copy)]
+enum DustPolicy {
+    Burn,
+    Reserve,
+    Accumulate,
+}
+/// Hands the dust to the handler picked with `DUST_POLICY`, burns it by default.
+pub struct DustHandler;
+impl OnDust<u64, u64> for DustHandler {
+    fn on_dust(who: &u64, amount: u64) {
+        match DUST_POLICY.with(|p| *p.borrow()) {
+            DustPolicy::Burn => dust::Burn::on_dust(who, amount),
+            DustPolicy::Reserve => dust::Reserve::<Balances, Receiver>::on_dust(who, amount),
+            DustPolicy::Accumulate => dust::Accumulate::<Test>::on_dust(who, amount),
+        }
+    }
+}
+/// Reporting periods last 25 blocks.
+pub struct Periods;
+impl ReportingPeriods for Periods {
+    fn current() -> u32 {
+        (System::block_number() / 25) as u32
+    }
+}
+/// Remembers the coins allocated to every grantee.
+pub struct Allocated;
+impl OnAllocation<u64, u64> for Allocated {
+    fn on_allocation(who: &u64, amount: u64) {
+        ALLOCATED.with(|a| a.borrow_mut().push((*who, amount)));
+    }
+}
+/// Stands for the membership pallet, which calls back into the allocations pallet.
+pub struct OracleMembership;
+impl OracleMembership {
+    fn members() -> Vec<u64> {
+        let mut members = Allocations::oracles();
+        members.extend(
+            Allocations::pending_oracles()
+                .into_iter()
+                .map(|(oracle, _)| oracle),
+        );
+        members.sort();
+        members
+    }
+}
+impl MemberSet<u64> for OracleMembership {
+    fn add_member(who: &u64) -> Result<(), &'static str> {
+        let mut members = Self::members();
+        let position = members.binary_search(who).err().ok_or("Already a member")?;
+        members.insert(position, *who);
+        Allocations::change_members_sorted(&[*who], &[], &members);
+        Ok(())
+    }
+
+    fn remove_member(who: &u64) -> Result<(), &'static str> {
+        let mut members = Self::members();
+        let position = members.binary_search(who).map_err(|_| "Not a member")?;
+        members.remove(position);
+        Allocations::change_members_sorted(&[], &[*who], &members);
+        Ok(())
+    }
 }
 impl WithAccountId<u64> for Receiver {
     fn account_id() -> u64 {
@@ -113,22 +233,108 @@ impl Trait for Test {
     type Event = ();
     type Currency = pallet_balances::Module<Self>;
     type ProtocolFee = Fee;
+    type FeeRounding = rounding::RemainderToBeneficiary;
     type ProtocolFeeReceiver = Receiver;
     type MaximumCoinsEverAllocated = CoinsLimit;
+    type MinimumAllocation = MinimumAllocation;
     type ExistentialDeposit = <Test as pallet_balances::Trait>::ExistentialDeposit;
+    type OnDust = DustHandler;
+    type OracleOrigin = EnsureOracle<Test>;
+    type OracleActivationDelay = OracleActivationDelay;
+    type OracleMembership = OracleMembership;
+    type CommitteeOrigin = frame_system::EnsureRoot<u64>;
+    type FallbackOrigin = EnsureSignedBy<Fallback, u64>;
+    type StallThreshold = StallThreshold;
+    type DeviceId = u64;
+    type Devices = Devices;
+    type OrganizationAccounts = Organizations;
+    type ComplianceCheck = Denylist;
+    type PriceValidity = PriceValidity;
+    type PointsRateOrigin = EnsureSignedBy<PointsAdmin, u64>;
+    type DelegationOrigin = EnsureSignedBy<BudgetAdmin, u64>;
+    type Coverage = Coverage;
+    type OnAllocation = Allocated;
+    type ReportingPeriods = Periods;
+    type Time = Timestamp;
+    type PayoutEraDuration = PayoutEraDuration;
+    type MaxSettlementsPerBlock = MaxSettlementsPerBlock;
+    type MaxPrunedPerBlock = MaxPrunedPerBlock;
+    type MaxUnsignedClaimsPerBlock = MaxUnsignedClaimsPerBlock;
+    type UnsignedPriority = UnsignedPriority;
+    type MaxClaimsPerBatch = MaxClaimsPerBatch;
+    type Public = UintAuthorityId;
+    type Signature = TestSignature;
+    type RewardsUnlockBlock = RewardsUnlockBlock;
+    type WeightInfo = ();
 }
 type Allocations = Module<Test>;
 type EmergencyShutdown = pallet_emergency_shutdown::Module<Test>;
 type Balances = pallet_balances::Module<Test>;
+type System = frame_system::Module<Test>;
+type Timestamp = pallet_timestamp::Module<Test>;
 type Errors = Error<Test>;
 
+/// Builds test externalities, for instance
+/// `ExtBuilder::default().coins(1000).oracles(vec![1, 2]).build()`.
+pub struct ExtBuilder {
+    balances: Vec<(u64, u64)>,
+    coins: u64,
+    oracles: Vec<u64>,
+}
+
+impl Default for ExtBuilder {
+    fn default() -> Self {
+        Self {
+            balances: Vec::new(),
+            coins: 1_000_000,
+            oracles: Vec::new(),
+        }
+    }
+}
+
+impl ExtBuilder {
+    /// Accounts endowed at genesis.
+    pub fn balances(mut self, balances: Vec<(u64, u64)>) -> Self {
+        self.balances = balances;
+        self
+    }
+
+    /// Total amount of coins oracles can allocate.
+    pub fn coins(mut self, coins: u64) -> Self {
+        self.coins = coins;
+        self
+    }
+
+    /// Members of the oracles set.
+    pub fn oracles(mut self, oracles: Vec<u64>) -> Self {
+        self.oracles = oracles;
+        self
+    }
+
+    pub fn build(self) -> sp_io::TestExternalities {
+        let mut storage = frame_system::GenesisConfig::default()
+            .build_storage::<Test>()
+            .unwrap();
+        pallet_balances::GenesisConfig::<Test> {
+            balances: self.balances,
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
+        let mut ext = sp_io::TestExternalities::from(storage);
+        // The coins limit is kept outside of the storage, always reset it
+        ext.execute_with(|| {
+            COINS_LIMIT.with(|c| *c.borrow_mut() = self.coins);
+            Allocations::initialize_members(&self.oracles);
+        });
+        ext
+    }
+}
+
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-    frame_system::GenesisConfig::default()
-        .build_storage::<Test>()
-        .unwrap()
-        .into()
+    ExtBuilder::default().build()
 }
 
 #[test]
@@ -174,9 +380,13 @@ fn allocate_the_right_amount_of_coins_to_everyone() {
             Vec::new(),
         ));
 
-        assert_eq!(Balances::free_balance(Grantee::get()), 45);
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 45);
         assert_eq!(Balances::free_balance(Receiver::get()), 5);
         assert_eq!(Allocations::coins_consumed(), 50);
+        assert_eq!(
+            ALLOCATED.with(|a| a.borrow().clone()),
+            vec![(Grantee::get(), 45)]
+        );
     })
 }
 
@@ -211,10 +421,7 @@ fn do_not_error_if_too_small_for_existential_deposit_but_balance_ok() {
             Vec::new()
         ),);
 
-        assert_eq!(
-            Balances::free_balance(Grantee::get()),
-            ExistentialDeposit::get().saturating_add(9)
-        );
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 9);
         assert_eq!(
             Balances::free_balance(Receiver::get()),
             ExistentialDeposit::get().saturating_add(1)
@@ -223,6 +430,23 @@ fn do_not_error_if_too_small_for_existential_deposit_but_balance_ok() {
     })
 }
 
+#[test]
+fn can_not_allocate_less_than_minimum() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+
+        assert_noop!(
+            Allocations::allocate(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                MinimumAllocation::get() - 1,
+                Vec::new(),
+            ),
+            Errors::AllocationBelowMinimum
+        );
+    })
+}
+
 #[test]
 fn can_not_allocate_more_coins_than_max() {
     new_test_ext().execute_with(|| {
@@ -259,3 +483,1960 @@ fn emergency_shutdown() {
         );
     })
 }
+
+#[test]
+fn allocations_are_operational() {
+    let call = Call::<Test>::allocate(Grantee::get(), 50, Vec::new());
+    assert_eq!(call.get_dispatch_info().class, DispatchClass::Operational);
+}
+
+#[test]
+fn allocation_events_are_indexed_by_grantee() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Allocations::initialize_members(&[Oracle::get()]);
+
+        assert_ok!(Allocations::allocate(
+            Origin::signed(Oracle::get()),
+            Grantee::get(),
+            50,
+            Vec::new(),
+        ));
+
+        assert_eq!(
+            System::event_topics(&BlakeTwo256::hash_of(&Grantee::get())),
+            vec![(1, System::event_count() - 1)]
+        );
+    })
+}
+
+#[test]
+fn oracle_status_tracks_allocations() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(3);
+        Allocations::initialize_members(&[Oracle::get()]);
+
+        assert_ok!(Allocations::allocate(
+            Origin::signed(Oracle::get()),
+            Grantee::get(),
+            50,
+            Vec::new(),
+        ));
+        assert_eq!(
+            Allocations::oracle_statuses(),
+            vec![(
+                Oracle::get(),
+                OracleStatus {
+                    allocated: 50,
+                    last_seen: 3
+                }
+            )]
+        );
+
+        Allocations::change_members_sorted(&[], &[Oracle::get()], &[]);
+        assert_eq!(
+            Allocations::oracle_status(Oracle::get()),
+            Default::default()
+        );
+    })
+}
+
+#[test]
+fn recipients_and_largest_allocation_are_tracked() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+
+        allocate(Grantee::get(), 50);
+        allocate(Grantee::get(), 30);
+        allocate(4, 20);
+
+        assert_eq!(Allocations::recipients_count(), 2);
+        assert!(Allocations::is_recipient(4));
+        assert_eq!(Allocations::largest_allocation(), 45);
+    })
+}
+
+#[test]
+fn fee_remainders_go_where_the_policy_says() {
+    use rounding::{Nearest, RemainderToBeneficiary, RemainderToProtocol};
+
+    let fee = Perbill::from_percent(10);
+    assert_eq!(RemainderToBeneficiary::split(fee, 19u64), (1, 18));
+    assert_eq!(RemainderToProtocol::split(fee, 11u64), (2, 9));
+    assert_eq!(Nearest::split(fee, 16u64), (2, 14));
+    assert_eq!(Nearest::split(fee, 14u64), (1, 13));
+
+    // Amounts too small to pay any fee
+    assert_eq!(RemainderToBeneficiary::split(fee, 9u64), (0, 9));
+    assert_eq!(RemainderToProtocol::split(fee, 1u64), (1, 0));
+}
+
+#[test]
+fn fee_splits_always_add_up() {
+    use rounding::{Nearest, RemainderToBeneficiary, RemainderToProtocol};
+
+    let fees = [
+        Perbill::zero(),
+        Perbill::from_parts(1),
+        Perbill::from_percent(10),
+        Perbill::from_rational_approximation(1u32, 3u32),
+        Perbill::one(),
+    ];
+    let amounts = [0u128, 1, 2, 3, 7, 10, 999_999_999, u128::max_value()];
+    for fee in fees.iter() {
+        for amount in amounts.iter() {
+            for (protocol, beneficiary) in [
+                RemainderToBeneficiary::split(*fee, *amount),
+                RemainderToProtocol::split(*fee, *amount),
+                Nearest::split(*fee, *amount),
+            ]
+            .iter()
+            {
+                assert_eq!(protocol + beneficiary, *amount, "{:?} of {}", fee, amount);
+            }
+        }
+    }
+
+    assert_eq!(RemainderToProtocol::split(Perbill::zero(), 10u64), (0, 10));
+    assert_eq!(
+        RemainderToBeneficiary::split(Perbill::one(), 10u64),
+        (10, 0)
+    );
+}
+
+#[test]
+fn allocations_leave_fee_remainders_to_grantees() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+
+        allocate(Grantee::get(), 39);
+
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 36);
+        assert_eq!(Balances::free_balance(Receiver::get()), 3);
+    })
+}
+
+/// Run our housekeeping at block `n` as if it was given the whole block.
+fn housekeep(n: u64) -> Weight {
+    Allocations::housekeep(n, Weight::max_value())
+}
+
+fn allocate(to: u64, amount: u64) {
+    assert_ok!(Allocations::allocate(
+        Origin::signed(Oracle::get()),
+        to,
+        amount,
+        Vec::new(),
+    ));
+}
+
+#[test]
+fn payouts_accumulate_during_an_era() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+
+        allocate(Grantee::get(), 50);
+        System::set_block_number(PayoutEraDuration::get() - 1);
+        allocate(Grantee::get(), 50);
+
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 90);
+        assert_eq!(Allocations::unsettled_eras(), vec![0]);
+        assert_eq!(Balances::free_balance(Grantee::get()), 0);
+        assert_eq!(Balances::total_issuance(), 10);
+    })
+}
+
+#[test]
+fn accumulated_payouts_can_satisfy_existential_deposit() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        Balances::make_free_balance_be(&Receiver::get(), ExistentialDeposit::get());
+
+        assert_noop!(
+            Allocations::allocate(Origin::signed(Oracle::get()), Grantee::get(), 1, Vec::new()),
+            Errors::DoesNotSatisfyExistentialDeposit,
+        );
+        allocate(Grantee::get(), 2);
+        allocate(Grantee::get(), 1);
+
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 3);
+    })
+}
+
+#[test]
+fn payouts_are_settled_once_their_era_is_over() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        for grantee in 10..13 {
+            allocate(grantee, 50);
+        }
+
+        housekeep(PayoutEraDuration::get() - 1);
+        assert_eq!(Allocations::unsettled_eras(), vec![0]);
+
+        // Only two payouts are settled per block
+        housekeep(PayoutEraDuration::get());
+        assert_eq!(
+            (10..13)
+                .filter(|grantee| Balances::free_balance(grantee) == 45)
+                .count(),
+            2
+        );
+        assert_eq!(Allocations::unsettled_eras(), vec![0]);
+
+        housekeep(PayoutEraDuration::get() + 1);
+        for grantee in 10..13 {
+            assert_eq!(Balances::free_balance(&grantee), 45);
+            assert_eq!(Allocations::pending_payout(0, grantee), 0);
+        }
+        assert!(Allocations::unsettled_eras().is_empty());
+        assert_eq!(Balances::total_issuance(), 150);
+    })
+}
+
+#[test]
+fn payouts_settlement_is_bounded_by_the_housekeeping_budget() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        for grantee in 10..13 {
+            allocate(grantee, 50);
+        }
+
+        // Enough for the housekeeping of a block without payouts, and a single payout
+        let budget = housekeep(PayoutEraDuration::get() - 1)
+            + <Test as Trait>::WeightInfo::settle_payouts(1);
+
+        let consumed = Allocations::housekeep(PayoutEraDuration::get(), budget);
+        assert!(consumed <= budget);
+        assert_eq!(
+            (10..13)
+                .filter(|grantee| Balances::free_balance(grantee) == 45)
+                .count(),
+            1
+        );
+    })
+}
+
+#[test]
+fn payouts_are_not_settled_under_shutdown() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        allocate(Grantee::get(), 50);
+        assert_ok!(EmergencyShutdown::toggle(Origin::signed(
+            ShutdownAdmin::get()
+        )));
+
+        housekeep(PayoutEraDuration::get());
+
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 45);
+        assert_noop!(
+            Allocations::claim(Origin::signed(Grantee::get()), 0),
+            Errors::UnderShutdown
+        );
+    })
+}
+
+#[test]
+fn grantees_can_claim_ahead_of_settlement() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        allocate(Grantee::get(), 50);
+
+        assert_ok!(Allocations::claim(Origin::signed(Grantee::get()), 0));
+        assert_eq!(Balances::free_balance(Grantee::get()), 45);
+        assert_noop!(
+            Allocations::claim(Origin::signed(Grantee::get()), 0),
+            Errors::NothingToClaim
+        );
+
+        housekeep(PayoutEraDuration::get());
+        assert_eq!(Balances::free_balance(Grantee::get()), 45);
+        assert!(Allocations::unsettled_eras().is_empty());
+    })
+}
+
+/// Lets account 40 claim on behalf of the grantee until block 10, with the grantee's
+/// signature over it.
+fn authorization() -> (ClaimAuthorizationOf<Test>, TestSignature) {
+    let authorization = ClaimAuthorization {
+        grantee: Grantee::get(),
+        relayer: 40,
+        expires_at: 10,
+        nonce: 0,
+    };
+    let signature = TestSignature(
+        Grantee::get(),
+        Allocations::authorization_payload(&authorization),
+    );
+    (authorization, signature)
+}
+
+#[test]
+fn relayers_claim_into_the_account_of_grantees_who_authorized_them() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        allocate(Grantee::get(), 50);
+        let (authorization, signature) = authorization();
+
+        assert_ok!(Allocations::claim_authorized(
+            Origin::signed(40),
+            0,
+            authorization.clone(),
+            signature.clone()
+        ));
+        assert_eq!(Balances::free_balance(Grantee::get()), 45);
+        assert_eq!(Balances::free_balance(40), 0);
+
+        // Authorizations can be used again until they expire
+        System::set_block_number(5);
+        allocate(Grantee::get(), 50);
+        assert_ok!(Allocations::claim_authorized(
+            Origin::signed(40),
+            0,
+            authorization,
+            signature
+        ));
+        assert_eq!(Balances::free_balance(Grantee::get()), 90);
+    })
+}
+
+#[test]
+fn claim_authorizations_are_checked() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        allocate(Grantee::get(), 50);
+        let (authorization, signature) = authorization();
+
+        assert_noop!(
+            Allocations::claim_authorized(
+                Origin::signed(Hacker::get()),
+                0,
+                authorization.clone(),
+                signature.clone()
+            ),
+            Errors::RelayerNotAuthorized
+        );
+        let mut forged = authorization.clone();
+        forged.expires_at = 20;
+        assert_noop!(
+            Allocations::claim_authorized(Origin::signed(40), 0, forged, signature.clone()),
+            Errors::InvalidAuthorizationSignature
+        );
+
+        System::set_block_number(11);
+        assert_noop!(
+            Allocations::claim_authorized(
+                Origin::signed(40),
+                0,
+                authorization.clone(),
+                signature.clone()
+            ),
+            Errors::AuthorizationExpired
+        );
+
+        System::set_block_number(10);
+        deny(Grantee::get());
+        assert_noop!(
+            Allocations::claim_authorized(Origin::signed(40), 0, authorization, signature),
+            Errors::BeneficiaryBlocked
+        );
+    })
+}
+
+#[test]
+fn grantees_can_revoke_their_claim_authorizations() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        allocate(Grantee::get(), 50);
+        let (authorization, signature) = authorization();
+
+        assert_ok!(Allocations::revoke_claim_authorizations(Origin::signed(
+            Grantee::get()
+        )));
+        assert_eq!(Allocations::claim_authorization_nonce(Grantee::get()), 1);
+        assert_noop!(
+            Allocations::claim_authorized(Origin::signed(40), 0, authorization.clone(), signature),
+            Errors::AuthorizationRevoked
+        );
+
+        // Authorizations signed afterwards carry the new nonce
+        let renewed = ClaimAuthorization {
+            nonce: 1,
+            ..authorization
+        };
+        let signature = TestSignature(Grantee::get(), Allocations::authorization_payload(&renewed));
+        assert_ok!(Allocations::claim_authorized(
+            Origin::signed(40),
+            0,
+            renewed,
+            signature
+        ));
+        assert_eq!(Balances::free_balance(Grantee::get()), 45);
+    })
+}
+
+/// Allocate a batch committed to by its merkle root, returns the proofs of its leaves.
+fn allocate_batch(batch: &[(u64, u64)]) -> Vec<MerkleProof> {
+    let leaves = batch
+        .iter()
+        .map(|(to, amount)| merkle::leaf(to, *amount))
+        .collect::<Vec<_>>();
+    let root = merkle::root(&leaves).expect("the batch is not empty");
+    for (to, amount) in batch {
+        assert_ok!(Allocations::allocate(
+            Origin::signed(Oracle::get()),
+            *to,
+            *amount,
+            root.as_bytes().to_vec(),
+        ));
+    }
+    merkle::prove_all(&leaves)
+}
+
+#[test]
+fn grantees_without_funds_claim_with_a_proof() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        let proofs = allocate_batch(&[(Grantee::get(), 50), (4, 60)]);
+        let call = Call::<Test>::claim_unsigned(0, Grantee::get(), 50, proofs[0].clone());
+
+        assert!(Allocations::validate_unsigned(TransactionSource::External, &call).is_ok());
+        assert_ok!(Allocations::claim_unsigned(
+            Origin::none(),
+            0,
+            Grantee::get(),
+            50,
+            proofs[0].clone()
+        ));
+        assert_eq!(Balances::free_balance(Grantee::get()), 45);
+        assert_eq!(Allocations::pending_payout(0, 4), 54);
+
+        // Leaves can not be replayed
+        assert_eq!(
+            Allocations::validate_unsigned(TransactionSource::External, &call),
+            InvalidTransaction::Stale.into()
+        );
+        assert_noop!(
+            Allocations::claim_unsigned(Origin::none(), 0, Grantee::get(), 50, proofs[0].clone()),
+            Errors::LeafAlreadyClaimed
+        );
+    })
+}
+
+#[test]
+fn forged_unsigned_claims_are_rejected() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        let proofs = allocate_batch(&[(Grantee::get(), 50), (4, 60)]);
+
+        assert_noop!(
+            Allocations::claim_unsigned(Origin::none(), 0, Grantee::get(), 60, proofs[0].clone()),
+            Errors::InvalidClaimProof
+        );
+        assert_noop!(
+            Allocations::claim_unsigned(Origin::none(), 0, Grantee::get(), 60, proofs[1].clone()),
+            Errors::InvalidClaimProof
+        );
+        assert_noop!(
+            Allocations::claim_unsigned(Origin::none(), 1, Grantee::get(), 50, proofs[0].clone()),
+            Errors::UnknownBatch
+        );
+        assert_noop!(
+            Allocations::claim_unsigned(
+                Origin::signed(Grantee::get()),
+                0,
+                Grantee::get(),
+                50,
+                proofs[0].clone()
+            ),
+            sp_runtime::traits::BadOrigin
+        );
+
+        let forged = Call::<Test>::claim_unsigned(0, Grantee::get(), 60, proofs[0].clone());
+        assert_eq!(
+            Allocations::validate_unsigned(TransactionSource::External, &forged),
+            InvalidTransaction::BadProof.into()
+        );
+    })
+}
+
+#[test]
+fn unsigned_claims_are_limited_per_block() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        let proofs = allocate_batch(&[(Grantee::get(), 50), (4, 60)]);
+        let first = Call::<Test>::claim_unsigned(0, Grantee::get(), 50, proofs[0].clone());
+        let second = Call::<Test>::claim_unsigned(0, 4, 60, proofs[1].clone());
+
+        System::set_block_number(1);
+        assert_ok!(Allocations::pre_dispatch(&first));
+        assert_ok!(Allocations::claim_unsigned(
+            Origin::none(),
+            0,
+            Grantee::get(),
+            50,
+            proofs[0].clone()
+        ));
+        assert_eq!(
+            Allocations::pre_dispatch(&second),
+            Err(InvalidTransaction::ExhaustsResources.into())
+        );
+
+        System::set_block_number(2);
+        assert_ok!(Allocations::pre_dispatch(&second));
+    })
+}
+
+#[test]
+fn settled_eras_forget_their_batches() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        let proofs = allocate_batch(&[(Grantee::get(), 50)]);
+        let trie = batches::trie(0, &proofs[0].root);
+        assert!(Allocations::is_batch_root(0, proofs[0].root));
+        assert_eq!(
+            child::get::<bool>(&trie, proofs[0].leaf.as_bytes()),
+            Some(false)
+        );
+
+        housekeep(PayoutEraDuration::get());
+
+        assert!(!Allocations::is_batch_root(0, proofs[0].root));
+        assert_eq!(child::get::<bool>(&trie, proofs[0].leaf.as_bytes()), None);
+        assert_noop!(
+            Allocations::claim_unsigned(Origin::none(), 0, Grantee::get(), 50, proofs[0].clone()),
+            Errors::UnknownBatch
+        );
+    })
+}
+
+#[test]
+fn batches_are_pruned_over_several_blocks() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        let proofs = allocate_batch(&[
+            (Grantee::get(), 50),
+            (Grantee::get(), 60),
+            (Grantee::get(), 70),
+        ]);
+        let trie = batches::trie(0, &proofs[0].root);
+
+        // A single payout is settled but three leaves and the root are more than can
+        // be pruned in a block
+        let consumed = housekeep(PayoutEraDuration::get());
+        assert!(consumed >= <Test as Trait>::WeightInfo::prune_batches(MaxPrunedPerBlock::get()));
+        assert!(Allocations::is_batch_root(0, proofs[0].root));
+        assert_eq!(
+            proofs
+                .iter()
+                .filter(|proof| child::exists(&trie, proof.leaf.as_bytes()))
+                .count(),
+            0
+        );
+        assert_eq!(Allocations::prunable_eras(), vec![0]);
+
+        housekeep(PayoutEraDuration::get() + 1);
+        assert!(!Allocations::is_batch_root(0, proofs[0].root));
+        assert_eq!(Allocations::prunable_eras(), Vec::<u32>::new());
+    })
+}
+
+#[test]
+fn relayers_claim_many_payouts_at_once() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        let proofs = allocate_batch(&[(Grantee::get(), 50), (4, 60), (5, 70)]);
+        let root = proofs[0].root;
+        // A leaf of another batch, with the wrong amount and already claimed
+        let other = allocate_batch(&[(6, 80)]);
+        assert_ok!(Allocations::claim_unsigned(
+            Origin::none(),
+            0,
+            5,
+            70,
+            proofs[2].clone()
+        ));
+
+        System::reset_events();
+        assert_ok!(Allocations::batch_claim(
+            Origin::signed(Hacker::get()),
+            0,
+            root,
+            vec![
+                (Grantee::get(), 50, proofs[0].clone()),
+                (6, 80, other[0].clone()),
+                (4, 70, proofs[1].clone()),
+                (5, 70, proofs[2].clone()),
+            ],
+        ));
+
+        assert_eq!(Balances::free_balance(Grantee::get()), 45);
+        assert_eq!(Allocations::pending_payout(0, 4), 54);
+        assert_eq!(Allocations::pending_payout(0, 6), 72);
+        // One event per claim, whether it succeeded or not
+        assert_eq!(System::event_count(), 4);
+    })
+}
+
+#[test]
+fn oracles_allocate_in_batches_skipping_failed_items() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        deny(7);
+        let allocations = vec![(Grantee::get(), 50), (4, 0), (7, 60), (5, 70)];
+        assert_noop!(
+            Allocations::allocate_batch(
+                Origin::signed(Hacker::get()),
+                allocations.clone(),
+                Vec::new()
+            ),
+            Errors::OracleAccessDenied
+        );
+
+        System::reset_events();
+        assert_ok!(Allocations::allocate_batch(
+            Origin::signed(Oracle::get()),
+            allocations,
+            Vec::new()
+        ));
+
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 45);
+        assert_eq!(Allocations::pending_payout(0, 4), 0);
+        assert_eq!(Allocations::pending_payout(0, 7), 0);
+        assert_eq!(Allocations::pending_payout(0, 5), 63);
+        assert_eq!(Allocations::coins_consumed(), 120);
+        // One event per allocation, whether it succeeded or not
+        assert_eq!(System::event_count(), 4);
+    })
+}
+
+#[test]
+fn failed_batch_items_have_typed_reasons() {
+    new_test_ext().execute_with(|| {
+        let reason = |error: Errors| Allocations::batch_item_error(error.into());
+        assert_eq!(
+            reason(Errors::BeneficiaryBlocked),
+            BatchItemError::BeneficiaryBlocked
+        );
+        assert_eq!(
+            reason(Errors::DoesNotSatisfyExistentialDeposit),
+            BatchItemError::AmountTooLow
+        );
+        assert_eq!(
+            reason(Errors::DelegationExhausted),
+            BatchItemError::BudgetExhausted
+        );
+        assert_eq!(reason(Errors::UnknownBatch), BatchItemError::InvalidProof);
+        assert_eq!(
+            reason(Errors::LeafAlreadyClaimed),
+            BatchItemError::AlreadyClaimed
+        );
+        assert_eq!(
+            Allocations::batch_item_error(sp_runtime::DispatchError::BadOrigin),
+            BatchItemError::Other(sp_runtime::DispatchError::BadOrigin)
+        );
+    })
+}
+
+#[test]
+fn accounts_remember_the_block_they_were_created_in() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(3);
+        let _ = Balances::make_free_balance_be(&7, 10);
+        assert_eq!(Allocations::account_created_at(7), Some(3));
+
+        let _ = Balances::make_free_balance_be(&7, 0);
+        assert_eq!(Allocations::account_created_at(7), None);
+    })
+}
+
+/// Batch giving three quarters of its value to an account created at block 100.
+fn correlated_batch() -> Vec<(u64, u64)> {
+    System::set_block_number(100);
+    let _ = Balances::make_free_balance_be(&7, 10);
+    vec![(Grantee::get(), 10), (7, 30)]
+}
+
+fn check_correlation(reject: bool) {
+    assert_ok!(Allocations::set_correlation_check(
+        Origin::root(),
+        Some(CorrelationCheck {
+            window: 50,
+            max_share: Perbill::from_percent(50),
+            reject,
+        })
+    ));
+}
+
+#[test]
+fn correlated_batches_can_be_flagged() {
+    ExtBuilder::default()
+        .balances(vec![(Grantee::get(), 10)])
+        .oracles(vec![Oracle::get()])
+        .build()
+        .execute_with(|| {
+            let batch = correlated_batch();
+            check_correlation(false);
+
+            System::reset_events();
+            assert_ok!(Allocations::allocate_batch(
+                Origin::signed(Oracle::get()),
+                batch,
+                Vec::new()
+            ));
+
+            // The batch is flagged and its allocations are made
+            assert_eq!(System::event_count(), 3);
+            assert_eq!(Allocations::pending_payout(10, Grantee::get()), 9);
+            assert_eq!(Allocations::pending_payout(10, 7), 27);
+        })
+}
+
+#[test]
+fn correlated_batches_can_be_rejected() {
+    ExtBuilder::default()
+        .balances(vec![(Grantee::get(), 10)])
+        .oracles(vec![Oracle::get()])
+        .build()
+        .execute_with(|| {
+            let batch = correlated_batch();
+            check_correlation(true);
+
+            assert_noop!(
+                Allocations::allocate_batch(Origin::signed(Oracle::get()), batch, Vec::new()),
+                Errors::CorrelatedBatch
+            );
+
+            // Within the limit
+            assert_ok!(Allocations::allocate_batch(
+                Origin::signed(Oracle::get()),
+                vec![(Grantee::get(), 30), (7, 10)],
+                Vec::new()
+            ));
+
+            assert_ok!(Allocations::set_correlation_check(Origin::root(), None));
+            assert_ok!(Allocations::allocate_batch(
+                Origin::signed(Oracle::get()),
+                vec![(Grantee::get(), 10), (7, 30)],
+                Vec::new()
+            ));
+
+            // Accounts are not new anymore once the window is over
+            check_correlation(true);
+            System::set_block_number(150);
+            assert_ok!(Allocations::allocate_batch(
+                Origin::signed(Oracle::get()),
+                vec![(Grantee::get(), 10), (7, 30)],
+                Vec::new()
+            ));
+        })
+}
+
+#[test]
+fn batch_claims_are_bounded() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        let proofs = allocate_batch(&[(Grantee::get(), 50)]);
+        let claim = (Grantee::get(), 50, proofs[0].clone());
+
+        assert_noop!(
+            Allocations::batch_claim(
+                Origin::signed(Hacker::get()),
+                0,
+                proofs[0].root,
+                vec![claim.clone(); MaxClaimsPerBatch::get() as usize + 1],
+            ),
+            Errors::TooManyClaims
+        );
+        assert_noop!(
+            Allocations::batch_claim(
+                Origin::signed(Hacker::get()),
+                1,
+                proofs[0].root,
+                vec![claim],
+            ),
+            Errors::UnknownBatch
+        );
+    })
+}
+
+#[test]
+fn early_rewards_can_be_used_but_not_transferred() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        allocate(Grantee::get(), 50);
+        assert_ok!(Allocations::claim(Origin::signed(Grantee::get()), 0));
+
+        assert_eq!(Allocations::locked_rewards(Grantee::get()), 45);
+        assert_noop!(
+            Balances::transfer(Origin::signed(Grantee::get()), 4, 10),
+            pallet_balances::Error::<Test, pallet_balances::DefaultInstance>::LiquidityRestrictions
+        );
+        assert_ok!(Balances::reserve(&Grantee::get(), 10));
+        assert_noop!(
+            Allocations::unlock_rewards(Origin::signed(Hacker::get()), Grantee::get()),
+            Errors::RewardsStillLocked
+        );
+
+        System::set_block_number(RewardsUnlockBlock::get());
+        assert_ok!(Allocations::unlock_rewards(
+            Origin::signed(Hacker::get()),
+            Grantee::get()
+        ));
+        assert_ok!(Balances::transfer(Origin::signed(Grantee::get()), 4, 10));
+        assert_noop!(
+            Allocations::unlock_rewards(Origin::signed(Hacker::get()), Grantee::get()),
+            Errors::NothingLocked
+        );
+    })
+}
+
+#[test]
+fn accounts_holding_locked_rewards_are_not_reaped() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        Balances::make_free_balance_be(&Grantee::get(), 10);
+        Balances::make_free_balance_be(&Receiver::get(), ExistentialDeposit::get());
+        allocate(Grantee::get(), 1);
+        assert_ok!(Allocations::claim(Origin::signed(Grantee::get()), 0));
+
+        // Rewards below the existential deposit still keep the account alive
+        assert_eq!(Allocations::locked_rewards(Grantee::get()), 1);
+        assert_noop!(
+            Balances::withdraw(
+                &Grantee::get(),
+                10,
+                WithdrawReasons::all(),
+                ExistenceRequirement::AllowDeath
+            ),
+            pallet_balances::Error::<Test, pallet_balances::DefaultInstance>::LiquidityRestrictions
+        );
+        assert_ok!(Balances::withdraw(
+            &Grantee::get(),
+            11 - ExistentialDeposit::get(),
+            WithdrawReasons::all(),
+            ExistenceRequirement::AllowDeath
+        ));
+        assert_eq!(
+            Balances::free_balance(Grantee::get()),
+            ExistentialDeposit::get()
+        );
+    })
+}
+
+#[test]
+fn rewards_are_not_locked_after_the_unlock_block() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        allocate(Grantee::get(), 50);
+
+        System::set_block_number(PayoutEraDuration::get());
+        housekeep(PayoutEraDuration::get());
+
+        assert_eq!(Balances::free_balance(Grantee::get()), 45);
+        assert_eq!(Allocations::locked_rewards(Grantee::get()), 0);
+        assert_ok!(Balances::transfer(Origin::signed(Grantee::get()), 4, 10));
+    })
+}
+
+#[test]
+fn the_fallback_origin_allocates_while_oracles_are_stalled() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        housekeep(1);
+        assert_eq!(Allocations::last_allocation(), 1);
+
+        assert_noop!(
+            Allocations::allocate(
+                Origin::signed(Fallback::get()),
+                Grantee::get(),
+                50,
+                Vec::new()
+            ),
+            Errors::OracleAccessDenied
+        );
+        housekeep(StallThreshold::get());
+        assert!(!Allocations::stalled());
+
+        housekeep(StallThreshold::get() + 1);
+        assert!(Allocations::stalled());
+        assert_noop!(
+            Allocations::allocate(
+                Origin::signed(Hacker::get()),
+                Grantee::get(),
+                50,
+                Vec::new()
+            ),
+            Errors::OracleAccessDenied
+        );
+        assert_ok!(Allocations::allocate(
+            Origin::signed(Fallback::get()),
+            Grantee::get(),
+            50,
+            Vec::new()
+        ));
+        assert!(Allocations::stalled());
+
+        // Oracles allocating again take over
+        System::set_block_number(StallThreshold::get() + 2);
+        allocate(Grantee::get(), 50);
+        assert!(!Allocations::stalled());
+        assert_eq!(Allocations::last_allocation(), StallThreshold::get() + 2);
+    })
+}
+
+#[test]
+fn fiat_allocations_use_the_median_price() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get(), 4, 5]);
+        assert_noop!(
+            Allocations::allocate_fiat(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                10,
+                0,
+                100,
+                Vec::new(),
+            ),
+            Errors::PriceUnavailable
+        );
+        assert_noop!(
+            Allocations::submit_price(Origin::signed(Hacker::get()), 1),
+            Errors::OracleAccessDenied
+        );
+
+        assert_ok!(Allocations::submit_price(Origin::signed(Oracle::get()), 3));
+        assert_ok!(Allocations::submit_price(Origin::signed(4), 5));
+        assert_ok!(Allocations::submit_price(Origin::signed(5), 100));
+        assert_eq!(Allocations::median_price(), Some(5));
+
+        assert_ok!(Allocations::allocate_fiat(
+            Origin::signed(Oracle::get()),
+            Grantee::get(),
+            10,
+            40,
+            60,
+            Vec::new()
+        ));
+        assert_eq!(Allocations::coins_consumed(), 50);
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 45);
+    })
+}
+
+#[test]
+fn fiat_allocations_are_bounded_against_slippage() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get(), 4]);
+        assert_ok!(Allocations::submit_price(Origin::signed(Oracle::get()), 8));
+        assert_ok!(Allocations::submit_price(Origin::signed(4), 6));
+        assert_eq!(Allocations::median_price(), Some(6));
+
+        assert_noop!(
+            Allocations::allocate_fiat(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                10,
+                40,
+                50,
+                Vec::new(),
+            ),
+            Errors::SlippageExceeded
+        );
+        assert_noop!(
+            Allocations::allocate_fiat(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                10,
+                70,
+                80,
+                Vec::new(),
+            ),
+            Errors::SlippageExceeded
+        );
+    })
+}
+
+#[test]
+fn stale_and_former_oracles_prices_are_ignored() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get(), 4]);
+        assert_ok!(Allocations::submit_price(Origin::signed(Oracle::get()), 3));
+        System::set_block_number(PriceValidity::get());
+        assert_ok!(Allocations::submit_price(Origin::signed(4), 7));
+        assert_eq!(Allocations::median_price(), Some(3));
+
+        System::set_block_number(PriceValidity::get() + 1);
+        assert_eq!(Allocations::median_price(), Some(7));
+
+        Allocations::change_members_sorted(&[], &[4], &[Oracle::get()]);
+        assert_eq!(Allocations::price_of(4), None);
+        assert_eq!(Allocations::median_price(), None);
+    })
+}
+
+#[test]
+fn allocations_to_devices_are_paid_to_their_owner() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+
+        assert_ok!(Allocations::allocate_to_device(
+            Origin::signed(Oracle::get()),
+            100,
+            50,
+            Vec::new()
+        ));
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 45);
+
+        assert_noop!(
+            Allocations::allocate_to_device(Origin::signed(Oracle::get()), 101, 50, Vec::new()),
+            Errors::UnknownDevice
+        );
+        assert_noop!(
+            Allocations::allocate_to_device(Origin::signed(Hacker::get()), 100, 50, Vec::new()),
+            Errors::OracleAccessDenied
+        );
+    })
+}
+
+fn deny(who: u64) {
+    DENIED.with(|d| d.borrow_mut().push(who));
+}
+
+#[test]
+fn blocked_beneficiaries_can_not_be_allocated_to() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        deny(Grantee::get());
+
+        assert_noop!(
+            Allocations::allocate(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                50,
+                Vec::new()
+            ),
+            Errors::BeneficiaryBlocked
+        );
+        assert_noop!(
+            Allocations::allocate_to_device(Origin::signed(Oracle::get()), 100, 50, Vec::new()),
+            Errors::BeneficiaryBlocked
+        );
+    })
+}
+
+#[test]
+fn payouts_of_accounts_blocked_later_are_withheld() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        allocate(Grantee::get(), 50);
+        allocate(10, 50);
+        deny(Grantee::get());
+
+        assert_noop!(
+            Allocations::claim(Origin::signed(Grantee::get()), 0),
+            Errors::BeneficiaryBlocked
+        );
+
+        housekeep(PayoutEraDuration::get());
+        assert_eq!(Balances::free_balance(Grantee::get()), 0);
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 0);
+        assert_eq!(Balances::free_balance(10), 45);
+        // The protocol fee was minted already, the withheld share can be allocated again
+        assert_eq!(Allocations::coins_consumed(), 55);
+        assert!(Allocations::unsettled_eras().is_empty());
+    })
+}
+
+/// Pay the payouts of at least 100 coins in four installments.
+fn pay_in_installments() {
+    assert_ok!(Allocations::set_installment_plan(
+        Origin::root(),
+        Some(InstallmentPlan {
+            threshold: 100,
+            installments: 4,
+        })
+    ));
+}
+
+#[test]
+fn installment_plans_are_set_by_root() {
+    new_test_ext().execute_with(|| {
+        let plan = InstallmentPlan {
+            threshold: 100,
+            installments: 0,
+        };
+        assert_noop!(
+            Allocations::set_installment_plan(Origin::signed(Hacker::get()), Some(plan.clone())),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Allocations::set_installment_plan(Origin::root(), Some(plan)),
+            Errors::InvalidInstallmentPlan
+        );
+
+        pay_in_installments();
+        assert_eq!(
+            Allocations::installment_plan().map(|plan| plan.installments),
+            Some(4)
+        );
+        assert_ok!(Allocations::set_installment_plan(Origin::root(), None));
+        assert_eq!(Allocations::installment_plan(), None);
+    })
+}
+
+#[test]
+fn large_payouts_are_settled_in_installments() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        pay_in_installments();
+        allocate(Grantee::get(), 50);
+        allocate(10, 500);
+
+        housekeep(PayoutEraDuration::get());
+        assert_eq!(Balances::free_balance(Grantee::get()), 45);
+        // Rounding leftovers are paid with the first installment
+        assert_eq!(Balances::free_balance(10), 114);
+        assert_eq!(Allocations::installments(1, 10), vec![(112, 3)]);
+
+        // Installments are only paid once their era is over
+        housekeep(2 * PayoutEraDuration::get() - 1);
+        assert_eq!(Balances::free_balance(10), 114);
+        assert_eq!(Allocations::unsettled_eras(), vec![1]);
+
+        for (era, balance) in [(2, 226), (3, 338), (4, 450)].iter() {
+            housekeep(era * PayoutEraDuration::get());
+            assert_eq!(Balances::free_balance(10), *balance);
+        }
+        assert!(Allocations::installments(3, 10).is_empty());
+        assert!(Allocations::unsettled_eras().is_empty());
+        assert_eq!(Balances::total_issuance(), 550);
+    })
+}
+
+#[test]
+fn claimed_payouts_are_paid_in_installments() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        pay_in_installments();
+        allocate(Grantee::get(), 500);
+
+        assert_ok!(Allocations::claim(Origin::signed(Grantee::get()), 0));
+        assert_eq!(Balances::free_balance(Grantee::get()), 114);
+        assert_eq!(Allocations::installments(0, Grantee::get()), vec![(112, 3)]);
+
+        housekeep(PayoutEraDuration::get());
+        assert_eq!(Balances::free_balance(Grantee::get()), 226);
+        assert_eq!(Allocations::installments(1, Grantee::get()), vec![(112, 2)]);
+    })
+}
+
+#[test]
+fn installments_of_accounts_blocked_later_are_withheld() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        pay_in_installments();
+        allocate(Grantee::get(), 500);
+        housekeep(PayoutEraDuration::get());
+        deny(Grantee::get());
+
+        housekeep(2 * PayoutEraDuration::get());
+        assert_eq!(Balances::free_balance(Grantee::get()), 114);
+        assert!(Allocations::installments(2, Grantee::get()).is_empty());
+        // Every installment left returns to the budget at once
+        assert_eq!(Allocations::coins_consumed(), 500 - 336);
+        assert!(Allocations::unsettled_eras().is_empty());
+    })
+}
+
+#[test]
+fn allocations_are_totalled_per_reporting_period() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        allocate(Grantee::get(), 50);
+        allocate(10, 30);
+
+        System::set_block_number(25);
+        allocate(Grantee::get(), 20);
+        deny(10);
+        housekeep(30);
+
+        assert_eq!(
+            Allocations::period_totals(0),
+            PeriodTotals {
+                allocated: 72,
+                protocol_fees: 8,
+                withheld: 0,
+                allocations: 2,
+            }
+        );
+        // The payout is withheld when settled, not when allocated
+        assert_eq!(
+            Allocations::period_totals(1),
+            PeriodTotals {
+                allocated: 18,
+                protocol_fees: 2,
+                withheld: 27,
+                allocations: 1,
+            }
+        );
+        assert_eq!(Allocations::period_totals(2), Default::default());
+    })
+}
+
+#[test]
+fn new_oracles_are_activated_after_a_delay() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        System::set_block_number(3);
+
+        Allocations::change_members_sorted(&[4], &[], &[Oracle::get(), 4]);
+        assert_eq!(Allocations::oracles(), vec![Oracle::get()]);
+        assert_eq!(Allocations::pending_oracles(), vec![(4, 8)]);
+        assert_noop!(
+            Allocations::allocate(Origin::signed(4), Grantee::get(), 50, Vec::new()),
+            Errors::OracleAccessDenied
+        );
+
+        housekeep(7);
+        assert!(!Allocations::is_oracle(4));
+        housekeep(8);
+        assert_eq!(Allocations::oracles(), vec![Oracle::get(), 4]);
+        assert!(Allocations::pending_oracles().is_empty());
+    })
+}
+
+#[test]
+fn oracles_are_removed_immediately() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+
+        Allocations::change_members_sorted(&[4], &[], &[Oracle::get(), 4]);
+        Allocations::change_members_sorted(&[], &[Oracle::get(), 4], &[]);
+        assert!(Allocations::oracles().is_empty());
+        assert!(Allocations::pending_oracles().is_empty());
+
+        housekeep(OracleActivationDelay::get());
+        assert!(!Allocations::is_oracle(4));
+    })
+}
+
+#[test]
+fn oracles_can_resign() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        assert_ok!(Allocations::submit_price(Origin::signed(Oracle::get()), 10));
+        Allocations::change_members_sorted(&[4], &[], &[Oracle::get(), 4]);
+
+        assert_ok!(Allocations::resign(Origin::signed(Oracle::get())));
+        assert!(!Allocations::is_oracle(Oracle::get()));
+        assert_eq!(Allocations::price_of(Oracle::get()), None);
+        assert_noop!(
+            Allocations::allocate(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                50,
+                Vec::new()
+            ),
+            Errors::OracleAccessDenied
+        );
+
+        // Pending oracles can back out too
+        assert_ok!(Allocations::resign(Origin::signed(4)));
+        assert!(Allocations::pending_oracles().is_empty());
+        assert_noop!(Allocations::resign(Origin::signed(4)), Errors::NotAnOracle);
+    })
+}
+
+#[test]
+fn committee_bounds_are_set_by_governance() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Allocations::set_committee_bounds(Origin::signed(Oracle::get()), 1, 3),
+            sp_runtime::traits::BadOrigin
+        );
+        assert_noop!(
+            Allocations::set_committee_bounds(Origin::root(), 3, 1),
+            Errors::InvalidCommitteeBounds
+        );
+
+        assert_ok!(Allocations::set_committee_bounds(Origin::root(), 1, 3));
+        assert_eq!(Allocations::committee_bounds(), Some((1, 3)));
+    })
+}
+
+#[test]
+fn oracles_can_not_resign_below_the_minimum_committee_size() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        Allocations::change_members_sorted(&[4], &[], &[Oracle::get(), 4]);
+        assert_ok!(Allocations::set_committee_bounds(Origin::root(), 2, 3));
+
+        assert_noop!(
+            Allocations::resign(Origin::signed(4)),
+            Errors::CommitteeTooSmall
+        );
+        assert_noop!(
+            Allocations::resign(Origin::signed(Oracle::get())),
+            Errors::CommitteeTooSmall
+        );
+
+        assert_ok!(Allocations::set_committee_bounds(Origin::root(), 1, 3));
+        assert_ok!(Allocations::resign(Origin::signed(4)));
+        assert_eq!(Allocations::committee_size(), 1);
+    })
+}
+
+#[test]
+fn governance_changes_the_committee_within_its_bounds() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get(), 4]);
+        assert_ok!(Allocations::set_committee_bounds(Origin::root(), 2, 3));
+
+        assert_noop!(
+            Allocations::add_oracle(Origin::signed(Oracle::get()), 5),
+            sp_runtime::traits::BadOrigin
+        );
+        assert_ok!(Allocations::add_oracle(Origin::root(), 5));
+        assert_eq!(Allocations::committee_size(), 3);
+        assert_noop!(
+            Allocations::add_oracle(Origin::root(), 6),
+            Errors::CommitteeTooLarge
+        );
+
+        assert_ok!(Allocations::remove_oracle(Origin::root(), 5));
+        assert_noop!(
+            Allocations::remove_oracle(Origin::root(), 4),
+            Errors::CommitteeTooSmall
+        );
+        assert_eq!(Allocations::committee_size(), 2);
+    })
+}
+
+#[test]
+fn committee_changes_are_checked_against_the_bounds() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get(), 4, 5]);
+
+        // Any size goes until bounds are set
+        assert_ok!(Allocations::ensure_committee_size(0));
+        assert_ok!(Allocations::ensure_committee_size(100));
+
+        assert_ok!(Allocations::set_committee_bounds(Origin::root(), 2, 4));
+        assert_ok!(Allocations::ensure_committee_size(2));
+        assert_ok!(Allocations::ensure_committee_size(4));
+        assert_noop!(
+            Allocations::ensure_committee_size(1),
+            Errors::CommitteeTooSmall
+        );
+        assert_noop!(
+            Allocations::ensure_committee_size(5),
+            Errors::CommitteeTooLarge
+        );
+
+        // A committee already out of its bounds can only move towards them
+        assert_ok!(Allocations::set_committee_bounds(Origin::root(), 4, 5));
+        assert_ok!(Allocations::ensure_committee_size(4));
+        assert_noop!(
+            Allocations::ensure_committee_size(2),
+            Errors::CommitteeTooSmall
+        );
+        assert_ok!(Allocations::set_committee_bounds(Origin::root(), 1, 2));
+        assert_ok!(Allocations::ensure_committee_size(2));
+        assert_noop!(
+            Allocations::ensure_committee_size(4),
+            Errors::CommitteeTooLarge
+        );
+    })
+}
+
+#[test]
+fn organizations_are_paid_into_keyless_accounts() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+
+        assert_ok!(Allocations::allocate_to_organization(
+            Origin::signed(Oracle::get()),
+            10,
+            50,
+            Vec::new()
+        ));
+        assert_ok!(Allocations::allocate_to_organization(
+            Origin::signed(Oracle::get()),
+            10,
+            50,
+            Vec::new()
+        ));
+        assert_eq!(Allocations::organization_controller(1010), Some(10));
+        assert_eq!(CREATED_ORGANIZATIONS.with(|c| c.borrow().clone()), vec![10]);
+
+        housekeep(PayoutEraDuration::get());
+        assert_eq!(Balances::free_balance(1010), 90);
+        assert_eq!(Balances::free_balance(10), 0);
+    })
+}
+
+#[test]
+fn blocked_organizations_can_not_be_allocated_to() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        deny(10);
+
+        assert_noop!(
+            Allocations::allocate_to_organization(
+                Origin::signed(Oracle::get()),
+                10,
+                50,
+                Vec::new()
+            ),
+            Errors::BeneficiaryBlocked
+        );
+    })
+}
+
+#[test]
+fn oracles_credit_points_without_minting() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+
+        assert_noop!(
+            Allocations::credit_points(Origin::signed(Hacker::get()), Grantee::get(), 5),
+            Errors::OracleAccessDenied
+        );
+        assert_ok!(Allocations::credit_points(
+            Origin::signed(Oracle::get()),
+            Grantee::get(),
+            5
+        ));
+        assert_ok!(Allocations::credit_points(
+            Origin::signed(Oracle::get()),
+            Grantee::get(),
+            3
+        ));
+
+        assert_eq!(Allocations::points(Grantee::get()), 8);
+        assert_eq!(Allocations::coins_consumed(), 0);
+        assert_eq!(Balances::total_issuance(), 0);
+    })
+}
+
+#[test]
+fn points_convert_at_the_governance_rate() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        assert_ok!(Allocations::credit_points(
+            Origin::signed(Oracle::get()),
+            Grantee::get(),
+            5
+        ));
+        assert_noop!(
+            Allocations::convert_points(Origin::signed(Grantee::get())),
+            Errors::PointsConversionDisabled
+        );
+
+        assert_noop!(
+            Allocations::set_points_rate(Origin::signed(Hacker::get()), 10),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(Allocations::set_points_rate(
+            Origin::signed(PointsAdmin::get()),
+            10
+        ));
+        assert_ok!(Allocations::convert_points(Origin::signed(Grantee::get())));
+
+        assert_eq!(Allocations::points(Grantee::get()), 0);
+        assert_eq!(Allocations::coins_consumed(), 50);
+        housekeep(PayoutEraDuration::get());
+        assert_eq!(Balances::free_balance(Grantee::get()), 45);
+        assert_eq!(Balances::free_balance(Receiver::get()), 5);
+
+        assert_noop!(
+            Allocations::convert_points(Origin::signed(Grantee::get())),
+            Errors::NoPoints
+        );
+    })
+}
+
+#[test]
+fn points_are_kept_when_the_budget_is_exhausted() {
+    ExtBuilder::default()
+        .coins(40)
+        .oracles(vec![Oracle::get()])
+        .build()
+        .execute_with(|| {
+            assert_ok!(Allocations::set_points_rate(RawOrigin::Root.into(), 10));
+            assert_ok!(Allocations::credit_points(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                5
+            ));
+
+            assert_noop!(
+                Allocations::convert_points(Origin::signed(Grantee::get())),
+                Errors::TooManyCoinsToAllocate
+            );
+            assert_eq!(Allocations::points(Grantee::get()), 5);
+        })
+}
+
+#[test]
+fn corrections_are_taken_from_the_budget() {
+    ExtBuilder::default().coins(80).build().execute_with(|| {
+        assert_ok!(<Allocations as AllocationBatches<_, _, _>>::correct(
+            &Grantee::get(),
+            50
+        ));
+        assert_eq!(Allocations::coins_consumed(), 50);
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 45);
+
+        assert!(<Allocations as AllocationBatches<_, _, _>>::correct(&Grantee::get(), 50).is_err());
+    })
+}
+
+#[test]
+fn oracles_are_looked_up_in_a_sorted_set() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[9, 3, 7]);
+
+        assert_eq!(Allocations::oracles(), vec![3, 7, 9]);
+        assert!([3, 7, 9]
+            .iter()
+            .all(|oracle| Allocations::is_oracle(*oracle)));
+        assert!(!Allocations::is_oracle(5));
+    })
+}
+
+#[test]
+fn oracles_share_a_limited_budget() {
+    ExtBuilder::default()
+        .coins(1_000)
+        .oracles(vec![Oracle::get(), 4])
+        .build()
+        .execute_with(|| {
+            assert_ok!(Allocations::allocate(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                600,
+                Vec::new(),
+            ));
+            assert_noop!(
+                Allocations::allocate(Origin::signed(4), Grantee::get(), 401, Vec::new(),),
+                Errors::TooManyCoinsToAllocate
+            );
+            assert_ok!(Allocations::allocate(
+                Origin::signed(4),
+                Grantee::get(),
+                400,
+                Vec::new(),
+            ));
+            assert_eq!(Allocations::coins_consumed(), 1_000);
+        })
+}
+
+#[test]
+fn batches_reference_coverage_once_required() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        CLOSED_EPOCHS.with(|e| *e.borrow_mut() = vec![1]);
+        let root = H256::repeat_byte(1);
+
+        // Nothing changes until references are required
+        assert_ok!(Allocations::allocate(
+            Origin::signed(Oracle::get()),
+            Grantee::get(),
+            50,
+            root.as_bytes().to_vec(),
+        ));
+
+        COVERAGE_REQUIRED.with(|r| *r.borrow_mut() = true);
+        let other = H256::repeat_byte(2);
+        assert_noop!(
+            Allocations::allocate(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                50,
+                other.as_bytes().to_vec(),
+            ),
+            Errors::MissingCoverage
+        );
+        assert_noop!(
+            Allocations::reference_coverage(Origin::signed(Hacker::get()), other, 1),
+            Errors::OracleAccessDenied
+        );
+        assert_noop!(
+            Allocations::reference_coverage(Origin::signed(Oracle::get()), other, 2),
+            Errors::UnknownCoverage
+        );
+
+        assert_ok!(Allocations::reference_coverage(
+            Origin::signed(Oracle::get()),
+            other,
+            1
+        ));
+        assert_eq!(Allocations::batch_coverage(0, other), Some(1));
+        assert_ok!(Allocations::allocate(
+            Origin::signed(Oracle::get()),
+            Grantee::get(),
+            50,
+            other.as_bytes().to_vec(),
+        ));
+        // Allocations can not skip the check by not being part of a batch
+        assert_noop!(
+            Allocations::allocate(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                50,
+                Vec::new(),
+            ),
+            Errors::MissingCoverage
+        );
+
+        housekeep(PayoutEraDuration::get());
+        assert_eq!(Allocations::batch_coverage(0, other), None);
+    })
+}
+
+#[test]
+fn proofs_which_are_not_roots_do_not_skip_coverage() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        CLOSED_EPOCHS.with(|e| *e.borrow_mut() = vec![1]);
+        COVERAGE_REQUIRED.with(|r| *r.borrow_mut() = true);
+        let root = H256::repeat_byte(1);
+        assert_ok!(Allocations::reference_coverage(
+            Origin::signed(Oracle::get()),
+            root,
+            1
+        ));
+
+        // Proofs one byte too short or too long, even when starting with the root
+        for proof in &[root[..31].to_vec(), [root.as_bytes(), &[1]].concat()] {
+            assert_noop!(
+                Allocations::allocate(
+                    Origin::signed(Oracle::get()),
+                    Grantee::get(),
+                    50,
+                    proof.clone(),
+                ),
+                Errors::MissingCoverage
+            );
+        }
+        assert_ok!(Allocations::allocate(
+            Origin::signed(Oracle::get()),
+            Grantee::get(),
+            50,
+            root.as_bytes().to_vec(),
+        ));
+    })
+}
+
+#[test]
+fn organizations_allocate_within_their_delegation() {
+    ExtBuilder::default()
+        .coins(1_000)
+        .oracles(vec![Oracle::get()])
+        .build()
+        .execute_with(|| {
+            let organization = 30;
+            let sub_oracle = 32;
+            assert_noop!(
+                Allocations::delegate_budget(Origin::signed(Hacker::get()), organization, 400),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Allocations::delegate_budget(
+                Origin::signed(BudgetAdmin::get()),
+                organization,
+                400
+            ));
+            assert_eq!(Allocations::delegation(organization), Some(400));
+            assert_eq!(Allocations::delegated_coins(), 400);
+
+            // The oracles can not touch the delegated coins
+            assert_noop!(
+                Allocations::allocate(
+                    Origin::signed(Oracle::get()),
+                    Grantee::get(),
+                    601,
+                    Vec::new(),
+                ),
+                Errors::TooManyCoinsToAllocate
+            );
+            assert_ok!(Allocations::allocate(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                600,
+                Vec::new(),
+            ));
+
+            assert_noop!(
+                Allocations::appoint_sub_oracle(Origin::signed(31), sub_oracle),
+                Errors::NoDelegation
+            );
+            assert_ok!(Allocations::appoint_sub_oracle(
+                Origin::signed(organization),
+                sub_oracle
+            ));
+            assert_noop!(
+                Allocations::appoint_sub_oracle(Origin::signed(organization), sub_oracle),
+                Errors::AlreadySubOracle
+            );
+
+            assert_noop!(
+                Allocations::allocate_delegated(
+                    Origin::signed(33),
+                    Grantee::get(),
+                    100,
+                    Vec::new(),
+                ),
+                Errors::NotASubOracle
+            );
+            assert_noop!(
+                Allocations::allocate_delegated(
+                    Origin::signed(sub_oracle),
+                    Grantee::get(),
+                    401,
+                    Vec::new(),
+                ),
+                Errors::DelegationExhausted
+            );
+            assert_ok!(Allocations::allocate_delegated(
+                Origin::signed(sub_oracle),
+                Grantee::get(),
+                400,
+                Vec::new(),
+            ));
+            assert_eq!(Allocations::delegation(organization), Some(0));
+            assert_eq!(Allocations::delegated_coins(), 0);
+            assert_eq!(Allocations::coins_consumed(), 1_000);
+            assert_eq!(Allocations::pending_payout(0, Grantee::get()), 900);
+
+            assert_noop!(
+                Allocations::dismiss_sub_oracle(Origin::signed(31), sub_oracle),
+                Errors::NotASubOracle
+            );
+            assert_ok!(Allocations::dismiss_sub_oracle(
+                Origin::signed(organization),
+                sub_oracle
+            ));
+            assert_eq!(Allocations::sub_oracle_of(sub_oracle), None);
+        })
+}
+
+#[test]
+fn delegations_are_bounded_by_the_budget() {
+    ExtBuilder::default()
+        .coins(1_000)
+        .oracles(vec![Oracle::get()])
+        .build()
+        .execute_with(|| {
+            let organization = 30;
+            assert_ok!(Allocations::allocate(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                700,
+                Vec::new(),
+            ));
+
+            assert_noop!(
+                Allocations::delegate_budget(RawOrigin::Root.into(), organization, 301),
+                Errors::TooManyCoinsToAllocate
+            );
+            assert_ok!(Allocations::delegate_budget(
+                RawOrigin::Root.into(),
+                organization,
+                300
+            ));
+
+            // Slices are replaced rather than topped up
+            assert_ok!(Allocations::delegate_budget(
+                RawOrigin::Root.into(),
+                organization,
+                100
+            ));
+            assert_eq!(Allocations::delegated_coins(), 100);
+
+            assert_ok!(Allocations::delegate_budget(
+                RawOrigin::Root.into(),
+                organization,
+                0
+            ));
+            assert_eq!(Allocations::delegation(organization), None);
+            assert_eq!(Allocations::delegated_coins(), 0);
+        })
+}
+
+#[test]
+fn campaigns_are_scheduled_in_wall_clock_time() {
+    new_test_ext().execute_with(|| {
+        Allocations::initialize_members(&[Oracle::get()]);
+        assert_noop!(
+            Allocations::schedule_campaign(Origin::signed(Hacker::get()), Some((1_000, 2_000))),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Allocations::schedule_campaign(
+                Origin::signed(BudgetAdmin::get()),
+                Some((2_000, 2_000))
+            ),
+            Errors::InvalidCampaignSchedule
+        );
+        assert_ok!(Allocations::schedule_campaign(
+            Origin::signed(BudgetAdmin::get()),
+            Some((1_000, 2_000))
+        ));
+        assert_eq!(Allocations::campaign_schedule(), Some((1_000, 2_000)));
+
+        Timestamp::set_timestamp(999);
+        assert_noop!(
+            Allocations::allocate(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                50,
+                Vec::new()
+            ),
+            Errors::CampaignNotStarted
+        );
+
+        Timestamp::set_timestamp(1_000);
+        allocate(Grantee::get(), 50);
+
+        Timestamp::set_timestamp(2_000);
+        assert_noop!(
+            Allocations::allocate(
+                Origin::signed(Oracle::get()),
+                Grantee::get(),
+                50,
+                Vec::new()
+            ),
+            Errors::CampaignOver
+        );
+
+        // Lifting the schedule lets allocations be made at any time
+        assert_ok!(Allocations::schedule_campaign(RawOrigin::Root.into(), None));
+        allocate(Grantee::get(), 50);
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 90);
+    })
+}
+
+#[test]
+fn campaign_deadlines_do_not_drift_with_block_time() {
+    ExtBuilder::default()
+        .coins(1_000)
+        .oracles(vec![Oracle::get()])
+        .build()
+        .execute_with(|| {
+            let organization = 30;
+            let sub_oracle = 32;
+            assert_ok!(Allocations::delegate_budget(
+                Origin::signed(BudgetAdmin::get()),
+                organization,
+                400
+            ));
+            assert_ok!(Allocations::appoint_sub_oracle(
+                Origin::signed(organization),
+                sub_oracle
+            ));
+            // With six seconds blocks the campaign would end at block 10
+            assert_ok!(Allocations::schedule_campaign(
+                Origin::signed(BudgetAdmin::get()),
+                Some((0, 60_000))
+            ));
+
+            // Blocks are slower than expected, the campaign is still running
+            System::set_block_number(20);
+            Timestamp::set_timestamp(59_999);
+            allocate(Grantee::get(), 50);
+            assert_ok!(Allocations::allocate_delegated(
+                Origin::signed(sub_oracle),
+                Grantee::get(),
+                50,
+                Vec::new(),
+            ));
+
+            // The next campaign would end at block 30, but blocks are faster than
+            // expected and it is over nonetheless
+            assert_ok!(Allocations::schedule_campaign(
+                Origin::signed(BudgetAdmin::get()),
+                Some((60_000, 120_000))
+            ));
+            System::set_block_number(25);
+            Timestamp::set_timestamp(120_000);
+            assert_noop!(
+                Allocations::allocate(
+                    Origin::signed(Oracle::get()),
+                    Grantee::get(),
+                    50,
+                    Vec::new()
+                ),
+                Errors::CampaignOver
+            );
+            assert_noop!(
+                Allocations::allocate_delegated(
+                    Origin::signed(sub_oracle),
+                    Grantee::get(),
+                    50,
+                    Vec::new(),
+                ),
+                Errors::CampaignOver
+            );
+        })
+}
+
+/// Run `test` once the grantee's account was reaped while a payout below the
+/// existential deposit was pending for it, with `policy` handling the dust.
+fn with_dust(policy: DustPolicy, test: impl FnOnce()) {
+    DUST_POLICY.with(|p| *p.borrow_mut() = policy);
+    ExtBuilder::default()
+        .balances(vec![(Grantee::get(), 10), (Receiver::get(), 10)])
+        .build()
+        .execute_with(|| {
+            Allocations::initialize_members(&[Oracle::get()]);
+            allocate(Grantee::get(), 1);
+            let _ = Balances::make_free_balance_be(&Grantee::get(), 0);
+            test();
+        });
+    DUST_POLICY.with(|p| *p.borrow_mut() = DustPolicy::Burn);
+}
+
+#[test]
+fn burnt_dust_is_never_minted() {
+    with_dust(DustPolicy::Burn, || {
+        let issuance = Balances::total_issuance();
+        housekeep(PayoutEraDuration::get());
+
+        assert_eq!(Balances::total_balance(&Grantee::get()), 0);
+        assert_eq!(Allocations::pending_payout(0, Grantee::get()), 0);
+        assert_eq!(Balances::total_issuance(), issuance);
+    })
+}
+
+#[test]
+fn reserved_dust_is_minted_to_the_reserve() {
+    with_dust(DustPolicy::Reserve, || {
+        let issuance = Balances::total_issuance();
+        housekeep(PayoutEraDuration::get());
+
+        assert_eq!(Balances::total_balance(&Grantee::get()), 0);
+        assert_eq!(Balances::free_balance(Receiver::get()), 11);
+        assert_eq!(Balances::total_issuance(), issuance + 1);
+    })
+}
+
+#[test]
+fn accumulated_dust_is_paid_out_with_the_next_payout() {
+    with_dust(DustPolicy::Accumulate, || {
+        let issuance = Balances::total_issuance();
+        housekeep(PayoutEraDuration::get());
+
+        assert_eq!(Balances::total_balance(&Grantee::get()), 0);
+        assert_eq!(Allocations::dust(Grantee::get()), 1);
+        assert_eq!(Balances::total_issuance(), issuance);
+
+        System::set_block_number(PayoutEraDuration::get());
+        allocate(Grantee::get(), 2);
+        housekeep(2 * PayoutEraDuration::get());
+
+        assert_eq!(Balances::free_balance(Grantee::get()), 3);
+        assert_eq!(Allocations::dust(Grantee::get()), 0);
+        assert_eq!(Balances::total_issuance(), issuance + 3);
+    })
+}
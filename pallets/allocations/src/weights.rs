@@ -0,0 +1,373 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Weights for pallet_allocations.
+//!
+//! The values below are carried over from the weights we used to hard code in the
+//! pallet and still need to be measured on reference hardware. Regenerate them with:
+//! `./scripts/benchmark.sh pallet_allocations`
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_allocations.
+pub trait WeightInfo {
+    fn allocate() -> Weight;
+    fn allocate_fiat() -> Weight;
+    fn allocate_to_device() -> Weight;
+    fn allocate_to_organization() -> Weight;
+    fn submit_price() -> Weight;
+    fn claim() -> Weight;
+    fn claim_unsigned(s: u32) -> Weight;
+    fn batch_claim(n: u32, s: u32) -> Weight;
+    fn claim_authorized() -> Weight;
+    fn revoke_claim_authorizations() -> Weight;
+    fn unlock_rewards() -> Weight;
+    fn settle_payouts(n: u32) -> Weight;
+    fn prune_batches(n: u32) -> Weight;
+    fn activate_oracles(n: u32) -> Weight;
+    fn resign() -> Weight;
+    fn credit_points() -> Weight;
+    fn convert_points() -> Weight;
+    fn set_points_rate() -> Weight;
+    fn reference_coverage() -> Weight;
+    fn delegate_budget() -> Weight;
+    fn appoint_sub_oracle() -> Weight;
+    fn dismiss_sub_oracle() -> Weight;
+    fn allocate_delegated() -> Weight;
+    fn schedule_campaign() -> Weight;
+    fn allocate_batch(n: u32) -> Weight;
+    fn set_committee_bounds() -> Weight;
+    fn add_oracle() -> Weight;
+    fn remove_oracle() -> Weight;
+    fn set_correlation_check() -> Weight;
+    fn set_installment_plan() -> Weight;
+}
+
+/// Estimated weights for pallet_allocations, see the module documentation.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Trait> WeightInfo for SubstrateWeight<T> {
+    fn allocate() -> Weight {
+        (50_000_000 as Weight)
+    }
+    fn allocate_fiat() -> Weight {
+        (90_000_000 as Weight).saturating_add(T::DbWeight::get().reads(5 as Weight))
+    }
+    fn allocate_to_device() -> Weight {
+        (60_000_000 as Weight).saturating_add(T::DbWeight::get().reads(2 as Weight))
+    }
+    fn allocate_to_organization() -> Weight {
+        (70_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn submit_price() -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn claim() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn claim_unsigned(s: u32) -> Weight {
+        (45_000_000 as Weight)
+            .saturating_add((1_000_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add(T::DbWeight::get().reads(6 as Weight))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+    }
+    fn batch_claim(n: u32, s: u32) -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add((40_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(
+                (1_000_000 as Weight).saturating_mul((n as Weight).saturating_mul(s as Weight)),
+            )
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().reads((5 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(n as Weight)))
+    }
+    // Authorizations carry a signature of the grantee which benchmarks can not produce
+    // for any signature scheme, this is the weight of a claim and of its verification
+    fn claim_authorized() -> Weight {
+        (100_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(6 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn revoke_claim_authorizations() -> Weight {
+        (15_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn unlock_rewards() -> Weight {
+        (30_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn settle_payouts(n: u32) -> Weight {
+        (5_000_000 as Weight)
+            .saturating_add((35_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().reads((5 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes((5 as Weight).saturating_mul(n as Weight)))
+    }
+    fn prune_batches(n: u32) -> Weight {
+        (5_000_000 as Weight)
+            .saturating_add((2_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+    }
+    fn activate_oracles(n: u32) -> Weight {
+        (2_000_000 as Weight)
+            .saturating_add((10_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn resign() -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(6 as Weight))
+    }
+    fn credit_points() -> Weight {
+        (10_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn convert_points() -> Weight {
+        (55_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn set_points_rate() -> Weight {
+        (10_000_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn reference_coverage() -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn delegate_budget() -> Weight {
+        (25_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn appoint_sub_oracle() -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn dismiss_sub_oracle() -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn allocate_delegated() -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn schedule_campaign() -> Weight {
+        (15_000_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn allocate_batch(n: u32) -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add((50_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+    }
+    fn set_committee_bounds() -> Weight {
+        (15_000_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn add_oracle() -> Weight {
+        (45_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn remove_oracle() -> Weight {
+        (55_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(6 as Weight))
+    }
+    fn set_correlation_check() -> Weight {
+        (15_000_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn set_installment_plan() -> Weight {
+        (15_000_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn allocate() -> Weight {
+        (50_000_000 as Weight)
+    }
+    fn allocate_fiat() -> Weight {
+        (90_000_000 as Weight).saturating_add(RocksDbWeight::get().reads(5 as Weight))
+    }
+    fn allocate_to_device() -> Weight {
+        (60_000_000 as Weight).saturating_add(RocksDbWeight::get().reads(2 as Weight))
+    }
+    fn allocate_to_organization() -> Weight {
+        (70_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(3 as Weight))
+    }
+    fn submit_price() -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(1 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn claim() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(3 as Weight))
+    }
+    fn claim_unsigned(s: u32) -> Weight {
+        (45_000_000 as Weight)
+            .saturating_add((1_000_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add(RocksDbWeight::get().reads(6 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(4 as Weight))
+    }
+    fn batch_claim(n: u32, s: u32) -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add((40_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(
+                (1_000_000 as Weight).saturating_mul((n as Weight).saturating_mul(s as Weight)),
+            )
+            .saturating_add(RocksDbWeight::get().reads(2 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((5 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(n as Weight)))
+    }
+    fn claim_authorized() -> Weight {
+        (100_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(6 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(3 as Weight))
+    }
+    fn revoke_claim_authorizations() -> Weight {
+        (15_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(1 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn unlock_rewards() -> Weight {
+        (30_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(3 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn settle_payouts(n: u32) -> Weight {
+        (5_000_000 as Weight)
+            .saturating_add((35_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(RocksDbWeight::get().reads(2 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((5 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(3 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((5 as Weight).saturating_mul(n as Weight)))
+    }
+    fn prune_batches(n: u32) -> Weight {
+        (5_000_000 as Weight)
+            .saturating_add((2_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(RocksDbWeight::get().reads(2 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+    }
+    fn activate_oracles(n: u32) -> Weight {
+        (2_000_000 as Weight)
+            .saturating_add((10_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(RocksDbWeight::get().reads(2 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn resign() -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(6 as Weight))
+    }
+    fn credit_points() -> Weight {
+        (10_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(3 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn convert_points() -> Weight {
+        (55_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(2 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn set_points_rate() -> Weight {
+        (10_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn reference_coverage() -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn delegate_budget() -> Weight {
+        (25_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(3 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn appoint_sub_oracle() -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(2 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn dismiss_sub_oracle() -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(1 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn allocate_delegated() -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(3 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn schedule_campaign() -> Weight {
+        (15_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn allocate_batch(n: u32) -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add((50_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+    }
+    fn set_committee_bounds() -> Weight {
+        (15_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn add_oracle() -> Weight {
+        (45_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(3 as Weight))
+    }
+    fn remove_oracle() -> Weight {
+        (55_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(6 as Weight))
+    }
+    fn set_correlation_check() -> Weight {
+        (15_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn set_installment_plan() -> Weight {
+        (15_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+}
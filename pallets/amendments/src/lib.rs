@@ -21,13 +21,15 @@
 //! An amendment module instance manages amendments to the chain. There could be a security
 //! delay configured along with a veto capability.
 
+pub use weights::WeightInfo;
+
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     traits::{
         schedule::DispatchTime::At, schedule::Named as ScheduleNamed, EnsureOrigin, Get,
         LockIdentifier,
     },
-    weights::GetDispatchInfo,
+    weights::{DispatchClass, GetDispatchInfo},
     Parameter,
 };
 use frame_system::{self as system, ensure_root};
@@ -37,6 +39,7 @@ use sp_std::prelude::Box;
 
 mod benchmarking;
 mod tests;
+pub mod weights;
 
 const AMENDMENTS_ID: LockIdentifier = *b"amendmen";
 
@@ -58,6 +61,9 @@ pub trait Trait: system::Trait {
 
     /// How much blocks have to be produced before executing the amendment
     type Delay: Get<Self::BlockNumber>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
 }
 
 decl_error! {
@@ -94,7 +100,7 @@ decl_module! {
         fn deposit_event() = default;
 
         /// Schedule `amendment` to be executed after the configured time, unless vetoed by `VetoOrigin`
-        #[weight = 100_000_000]
+        #[weight = (T::WeightInfo::propose(), DispatchClass::Operational)]
         fn propose(origin, amendment: Box<T::Amendment>) -> DispatchResult {
             T::SubmissionOrigin::try_origin(origin)
                 .map(|_| ())
@@ -125,7 +131,7 @@ decl_module! {
         }
 
         /// Veto and cancel a scheduled amendment
-        #[weight = 20_000_000]
+        #[weight = (T::WeightInfo::veto(), DispatchClass::Operational)]
         fn veto(origin, amendment_id: u64) -> DispatchResult {
             T::VetoOrigin::try_origin(origin)
                 .map(|_| ())
@@ -141,3 +147,13 @@ decl_module! {
         }
     }
 }
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        // Only the number of scheduled amendments is stored, the scheduler holds them
+        Ok(())
+    }
+}
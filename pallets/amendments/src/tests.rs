@@ -26,13 +26,8 @@ use frame_support::{
 };
 use frame_system::{EnsureRoot, EnsureSignedBy};
 use parity_scale_codec::Encode;
-use sp_core::H256;
-use sp_runtime::{
-    testing::Header,
-    traits::{BlakeTwo256, IdentityLookup},
-    DispatchError::BadOrigin,
-    Perbill,
-};
+use sp_runtime::{DispatchError::BadOrigin, Perbill};
+use test_utils::{impl_mock_system, MaximumBlockWeight};
 
 impl_outer_origin! {
     pub enum Origin for Test  where system = frame_system {}
@@ -48,39 +43,7 @@ impl_outer_dispatch! {
 // configuration traits of modules we want to use.
 #[derive(Clone, Eq, PartialEq)]
 pub struct Test;
-parameter_types! {
-    pub const BlockHashCount: u64 = 250;
-    pub const MaximumBlockWeight: Weight = 1024;
-    pub const MaximumBlockLength: u32 = 2 * 1024;
-    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
-}
-impl frame_system::Trait for Test {
-    type Origin = Origin;
-    type Call = Call;
-    type Index = u64;
-    type BlockNumber = u64;
-    type Hash = H256;
-    type Hashing = BlakeTwo256;
-    type AccountId = u64;
-    type Lookup = IdentityLookup<Self::AccountId>;
-    type Header = Header;
-    type Event = ();
-    type BlockHashCount = BlockHashCount;
-    type MaximumBlockWeight = MaximumBlockWeight;
-    type MaximumBlockLength = MaximumBlockLength;
-    type AvailableBlockRatio = AvailableBlockRatio;
-    type Version = ();
-    type PalletInfo = ();
-    type AccountData = ();
-    type OnNewAccount = ();
-    type OnKilledAccount = ();
-    type DbWeight = ();
-    type BlockExecutionWeight = ();
-    type ExtrinsicBaseWeight = ();
-    type MaximumExtrinsicWeight = MaximumBlockWeight;
-    type BaseCallFilter = ();
-    type SystemWeightInfo = ();
-}
+impl_mock_system!(Test, Origin, Call, ());
 parameter_types! {
     pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * MaximumBlockWeight::get();
     pub const MaxScheduledPerBlock: u32 = 50;
@@ -110,6 +73,7 @@ impl Trait for Test {
     type Delay = BlockDelay;
     type Scheduler = Scheduler;
     type PalletsOrigin = OriginCaller;
+    type WeightInfo = ();
 }
 
 type Amendments = Module<Test>;
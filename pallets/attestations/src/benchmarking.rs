@@ -0,0 +1,68 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Attestations pallet benchmarks
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks};
+use frame_system::RawOrigin;
+use sp_runtime::traits::{Hash, TrailingZeroInput};
+
+const SEED: u32 = 0;
+
+benchmarks! {
+    _ { }
+
+    submit_attestation {
+        let u in 1 .. 1000;
+
+        let issuer: T::AccountId = account("issuer", u, SEED);
+        let device: T::AccountId = account("device", u, SEED);
+        let owner: T::AccountId = account("owner", u, SEED);
+        T::Devices::add_device(&(issuer.clone(), device.clone()), &owner);
+
+        let data = T::Hashing::hash_of(&u);
+        // Previous runs may have anchored the same data
+        <Anchors<T>>::remove(&data);
+        // The device attested before, so that its interval is checked
+        <LastAttestation<T>>::insert(&device, T::BlockNumber::from(0u32));
+        frame_system::Module::<T>::set_block_number(T::MinAttestationInterval::get());
+
+        let attestation = Attestation { issuer, device, data };
+        // The signature is checked by `validate_unsigned`, not by the call itself
+        let signature = T::Signature::decode(&mut TrailingZeroInput::new(&[][..]))
+            .map_err(|_| "signatures must decode from zeroes")?;
+    }: _(RawOrigin::None, attestation, signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_submit_attestation::<Test>());
+        });
+    }
+}
@@ -0,0 +1,251 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Anchor data attested by devices. A device signs an `Attestation` of the hash of its
+//! data with its own key and submits it as an unsigned transaction, so that devices
+//! running on batteries do not have to hold coins to pay fees. The signature and the
+//! trust in the device are checked by `validate_unsigned`, before the transaction
+//! enters the pool and again when it is included in a block. Devices sign
+//! `attestation_payload`, which binds the attestation to this chain.
+//!
+//! Unsigned transactions are free, a device can thus only attest once every
+//! `MinAttestationInterval` blocks and blocks include at most
+//! `MaxUnsignedAttestationsPerBlock` attestations.
+
+mod benchmarking;
+pub mod weights;
+
+#[cfg(test)]
+mod tests;
+
+pub use weights::WeightInfo;
+
+use chain_primitives::DeviceResolver;
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure, traits::Get,
+    unsigned::ValidateUnsigned, Parameter,
+};
+use frame_system::ensure_none;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{IdentifyAccount, Member, Saturating, Verify, Zero},
+    transaction_validity::{
+        InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
+        TransactionValidityError, ValidTransaction,
+    },
+    DispatchResult, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+/// Signed by the devices along with their attestations, so that they can not be
+/// mistaken for anything else.
+pub const ATTESTATION_CONTEXT: &[u8] = b"nodle/attestation";
+
+pub type AttestationOf<T> =
+    Attestation<<T as frame_system::Trait>::AccountId, <T as frame_system::Trait>::Hash>;
+
+/// Payload signed by the key of a device.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Attestation<AccountId, Hash> {
+    /// Root certificate the device was issued under
+    pub issuer: AccountId,
+    /// Key of the device, which signs the attestation
+    pub device: AccountId,
+    /// Hash of the attested data
+    pub data: Hash,
+}
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Signature of the attestations by the device keys
+    type Signature: Verify<Signer = Self::Public> + Member + Parameter;
+    /// Device keys, the account of a device is derived from its key
+    type Public: IdentifyAccount<AccountId = Self::AccountId>;
+    /// Devices allowed to attest, identified by their issuer and their key
+    type Devices: DeviceResolver<(Self::AccountId, Self::AccountId), Self::AccountId>;
+    /// Number of blocks a device has to wait between two attestations
+    type MinAttestationInterval: Get<Self::BlockNumber>;
+    /// Maximum number of attestations included in a block
+    type MaxUnsignedAttestationsPerBlock: Get<u32>;
+    /// Priority of the attestations in the transaction pool
+    type UnsignedPriority: Get<TransactionPriority>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Attestations {
+        /// Device which attested the data with the given hash and the block it did so
+        Anchors get(fn anchor): map hasher(blake2_128_concat) T::Hash => Option<(T::AccountId, T::BlockNumber)>;
+        /// Block of the last attestation of each device
+        LastAttestation get(fn last_attestation): map hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+        /// Number of attestations included in the given block
+        AttestationsInBlock get(fn attestations_in_block): (T::BlockNumber, u32);
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        Hash = <T as frame_system::Trait>::Hash,
+    {
+        /// The device attested the data with the given hash
+        Attested(AccountId, Hash),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// The device is unknown or no longer trusted
+        UnknownDevice,
+        /// The data was already attested
+        AlreadyAnchored,
+        /// The device attested less than `MinAttestationInterval` blocks ago
+        TooSoon,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const MinAttestationInterval: T::BlockNumber = T::MinAttestationInterval::get();
+        const MaxUnsignedAttestationsPerBlock: u32 = T::MaxUnsignedAttestationsPerBlock::get();
+
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Anchor data attested by a device, `_signature` is the signature of the
+        /// `attestation_payload` by the key of the device and was checked by
+        /// `validate_unsigned`
+        #[weight = T::WeightInfo::submit_attestation()]
+        pub fn submit_attestation(origin, attestation: AttestationOf<T>, _signature: T::Signature) -> DispatchResult {
+            ensure_none(origin)?;
+            Self::check_attestation(&attestation)?;
+
+            let now = <frame_system::Module<T>>::block_number();
+            <Anchors<T>>::insert(&attestation.data, (attestation.device.clone(), now));
+            <LastAttestation<T>>::insert(&attestation.device, now);
+            <AttestationsInBlock<T>>::mutate(|(block, count)| {
+                if *block != now {
+                    *block = now;
+                    *count = 0;
+                }
+                *count = count.saturating_add(1);
+            });
+
+            Self::deposit_event(RawEvent::Attested(attestation.device, attestation.data));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Payload devices sign for an attestation, the genesis hash keeps attestations
+    /// made for another chain from being replayed on this one.
+    pub fn attestation_payload(attestation: &AttestationOf<T>) -> Vec<u8> {
+        let genesis = <frame_system::Module<T>>::block_hash(T::BlockNumber::zero());
+        (ATTESTATION_CONTEXT, genesis, attestation).encode()
+    }
+
+    /// Checks of an attestation which do not depend on its signature.
+    fn check_attestation(attestation: &AttestationOf<T>) -> Result<(), Error<T>> {
+        let device = (attestation.issuer.clone(), attestation.device.clone());
+        ensure!(
+            T::Devices::payout_account(&device).is_some(),
+            Error::<T>::UnknownDevice
+        );
+        ensure!(
+            !<Anchors<T>>::contains_key(&attestation.data),
+            Error::<T>::AlreadyAnchored
+        );
+        if let Some(last) = Self::last_attestation(&attestation.device) {
+            ensure!(
+                <frame_system::Module<T>>::block_number()
+                    >= last.saturating_add(T::MinAttestationInterval::get()),
+                Error::<T>::TooSoon
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<T: Trait> ValidateUnsigned for Module<T> {
+    type Call = Call<T>;
+
+    fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+        let (attestation, signature) = match call {
+            Call::submit_attestation(attestation, signature) => (attestation, signature),
+            _ => return InvalidTransaction::Call.into(),
+        };
+        if !signature.verify(
+            &Self::attestation_payload(attestation)[..],
+            &attestation.device,
+        ) {
+            return InvalidTransaction::BadProof.into();
+        }
+        Self::check_attestation(attestation).map_err(|e| match e {
+            Error::<T>::UnknownDevice => InvalidTransaction::BadProof,
+            Error::<T>::TooSoon => InvalidTransaction::Future,
+            _ => InvalidTransaction::Stale,
+        })?;
+
+        ValidTransaction::with_tag_prefix("Attestation")
+            .priority(T::UnsignedPriority::get())
+            .and_provides(&attestation.data)
+            // A device gets a single attestation in the pool per interval
+            .and_provides((
+                &attestation.device,
+                Self::last_attestation(&attestation.device),
+            ))
+            .longevity(64)
+            .propagate(true)
+            .build()
+    }
+
+    fn pre_dispatch(call: &Self::Call) -> Result<(), TransactionValidityError> {
+        let (block, count) = Self::attestations_in_block();
+        if block == <frame_system::Module<T>>::block_number()
+            && count >= T::MaxUnsignedAttestationsPerBlock::get()
+        {
+            // `ExhaustsResources` keeps the attestation in the pool for a later block
+            return Err(InvalidTransaction::ExhaustsResources.into());
+        }
+        Self::validate_unsigned(TransactionSource::InBlock, call).map(|_| ())
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        let now = <frame_system::Module<T>>::block_number();
+        ensure!(
+            Self::attestations_in_block().0 <= now,
+            "attestations are counted for a future block"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{assert_noop, assert_ok, impl_outer_origin, parameter_types};
+use sp_core::H256;
+use sp_runtime::{
+    testing::{TestSignature, UintAuthorityId},
+    DispatchError::BadOrigin,
+};
+use std::cell::RefCell;
+use test_utils::impl_mock_system;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), ());
+
+thread_local! {
+    static DEVICES: RefCell<Vec<(u64, u64)>> = RefCell::new(vec![(ISSUER, DEVICE)]);
+}
+
+/// Devices are paid to their issuer.
+pub struct Devices;
+impl DeviceResolver<(u64, u64), u64> for Devices {
+    fn payout_account(device: &(u64, u64)) -> Option<u64> {
+        DEVICES.with(|d| {
+            if d.borrow().contains(device) {
+                Some(device.0)
+            } else {
+                None
+            }
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_device(device: &(u64, u64), _owner: &u64) {
+        DEVICES.with(|d| d.borrow_mut().push(*device));
+    }
+}
+
+parameter_types! {
+    pub const MinAttestationInterval: u64 = 10;
+    pub const MaxUnsignedAttestationsPerBlock: u32 = 2;
+    pub const UnsignedPriority: u64 = 1;
+}
+impl Trait for Test {
+    type Event = ();
+    type Signature = TestSignature;
+    type Public = UintAuthorityId;
+    type Devices = Devices;
+    type MinAttestationInterval = MinAttestationInterval;
+    type MaxUnsignedAttestationsPerBlock = MaxUnsignedAttestationsPerBlock;
+    type UnsignedPriority = UnsignedPriority;
+    type WeightInfo = ();
+}
+type Attestations = Module<Test>;
+type System = frame_system::Module<Test>;
+type Errors = Error<Test>;
+
+const ISSUER: u64 = 1;
+const DEVICE: u64 = 2;
+const OTHER_DEVICE: u64 = 3;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut ext: sp_io::TestExternalities = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+fn attestation(device: u64, byte: u8) -> AttestationOf<Test> {
+    Attestation {
+        issuer: ISSUER,
+        device,
+        data: H256::repeat_byte(byte),
+    }
+}
+
+fn call(attestation: AttestationOf<Test>) -> Call<Test> {
+    let signature = TestSignature(
+        attestation.device,
+        Attestations::attestation_payload(&attestation),
+    );
+    Call::submit_attestation(attestation, signature)
+}
+
+fn validate(call: &Call<Test>) -> TransactionValidity {
+    <Attestations as ValidateUnsigned>::validate_unsigned(TransactionSource::External, call)
+}
+
+#[test]
+fn devices_anchor_data() {
+    new_test_ext().execute_with(|| {
+        let payload = attestation(DEVICE, 1);
+        assert!(validate(&call(payload.clone())).is_ok());
+
+        assert_ok!(Attestations::submit_attestation(
+            Origin::none(),
+            payload.clone(),
+            TestSignature(DEVICE, Attestations::attestation_payload(&payload))
+        ));
+        assert_eq!(Attestations::anchor(payload.data), Some((DEVICE, 1)));
+        assert_eq!(Attestations::last_attestation(DEVICE), Some(1));
+    })
+}
+
+#[test]
+fn attestations_are_unsigned() {
+    new_test_ext().execute_with(|| {
+        let payload = attestation(DEVICE, 1);
+        assert_noop!(
+            Attestations::submit_attestation(
+                Origin::signed(DEVICE),
+                payload.clone(),
+                TestSignature(DEVICE, Attestations::attestation_payload(&payload))
+            ),
+            BadOrigin
+        );
+    })
+}
+
+#[test]
+fn attestations_must_be_signed_by_the_device() {
+    new_test_ext().execute_with(|| {
+        let payload = attestation(DEVICE, 1);
+        let forged = Call::submit_attestation(
+            payload.clone(),
+            TestSignature(OTHER_DEVICE, Attestations::attestation_payload(&payload)),
+        );
+        assert_eq!(validate(&forged), InvalidTransaction::BadProof.into());
+
+        let tampered = Call::submit_attestation(
+            payload,
+            TestSignature(
+                DEVICE,
+                Attestations::attestation_payload(&attestation(DEVICE, 2)),
+            ),
+        );
+        assert_eq!(validate(&tampered), InvalidTransaction::BadProof.into());
+    })
+}
+
+#[test]
+fn attestations_are_bound_to_the_chain() {
+    new_test_ext().execute_with(|| {
+        let payload = attestation(DEVICE, 1);
+        let bare =
+            Call::submit_attestation(payload.clone(), TestSignature(DEVICE, payload.encode()));
+        assert_eq!(validate(&bare), InvalidTransaction::BadProof.into());
+
+        let other_chain = (ATTESTATION_CONTEXT, H256::repeat_byte(9), &payload).encode();
+        let replayed = Call::submit_attestation(payload, TestSignature(DEVICE, other_chain));
+        assert_eq!(validate(&replayed), InvalidTransaction::BadProof.into());
+    })
+}
+
+#[test]
+fn unknown_devices_can_not_attest() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            validate(&call(attestation(OTHER_DEVICE, 1))),
+            InvalidTransaction::BadProof.into()
+        );
+        assert_noop!(
+            Attestations::submit_attestation(
+                Origin::none(),
+                attestation(OTHER_DEVICE, 1),
+                TestSignature(OTHER_DEVICE, vec![])
+            ),
+            Errors::UnknownDevice
+        );
+    })
+}
+
+#[test]
+fn data_is_anchored_once() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(<Attestations as ValidateUnsigned>::pre_dispatch(&call(
+            attestation(DEVICE, 1)
+        )));
+        assert_ok!(Attestations::submit_attestation(
+            Origin::none(),
+            attestation(DEVICE, 1),
+            TestSignature(DEVICE, vec![])
+        ));
+
+        System::set_block_number(20);
+        assert_eq!(
+            validate(&call(attestation(DEVICE, 1))),
+            InvalidTransaction::Stale.into()
+        );
+    })
+}
+
+#[test]
+fn devices_wait_between_attestations() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Attestations::submit_attestation(
+            Origin::none(),
+            attestation(DEVICE, 1),
+            TestSignature(DEVICE, vec![])
+        ));
+
+        assert_eq!(
+            validate(&call(attestation(DEVICE, 2))),
+            InvalidTransaction::Future.into()
+        );
+        assert_noop!(
+            Attestations::submit_attestation(
+                Origin::none(),
+                attestation(DEVICE, 2),
+                TestSignature(DEVICE, vec![])
+            ),
+            Errors::TooSoon
+        );
+
+        System::set_block_number(11);
+        assert!(validate(&call(attestation(DEVICE, 2))).is_ok());
+    })
+}
+
+#[test]
+fn blocks_include_a_bounded_number_of_attestations() {
+    new_test_ext().execute_with(|| {
+        DEVICES.with(|d| d.borrow_mut().extend(vec![(ISSUER, 4), (ISSUER, 5)]));
+        for (device, byte) in vec![(DEVICE, 1), (4, 2)] {
+            assert_ok!(Attestations::submit_attestation(
+                Origin::none(),
+                attestation(device, byte),
+                TestSignature(device, vec![])
+            ));
+        }
+
+        assert_eq!(
+            <Attestations as ValidateUnsigned>::pre_dispatch(&call(attestation(5, 3))),
+            Err(InvalidTransaction::ExhaustsResources.into())
+        );
+        System::set_block_number(2);
+        assert_ok!(<Attestations as ValidateUnsigned>::pre_dispatch(&call(
+            attestation(5, 3)
+        )));
+    })
+}
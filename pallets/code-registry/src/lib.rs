@@ -0,0 +1,187 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Keep a registry of every runtime enacted on chain: the hash of its WASM, its version
+//! and who proposed it. Validators rebuilding the runtime from sources can then check
+//! that they obtain the very same WASM, see the `verify-runtime` subcommand of the node.
+//!
+//! Proposers announce the hash of the code they are about to submit to governance, the
+//! code is recorded when it is enacted along with the account which announced it.
+
+mod benchmarking;
+pub mod weights;
+
+#[cfg(test)]
+mod tests;
+
+pub use weights::WeightInfo;
+
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{EnsureOrigin, Get},
+    weights::Weight,
+};
+use parity_scale_codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::storage::well_known_keys;
+use sp_runtime::{
+    traits::{Hash, One, Saturating},
+    RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+/// A runtime which was enacted on chain.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CodeRecord<AccountId, BlockNumber, Hash> {
+    /// Hash of the WASM blob
+    pub code_hash: Hash,
+    /// `spec_version` reported by the runtime
+    pub spec_version: u32,
+    /// Account which announced the code, if any did
+    pub proposer: Option<AccountId>,
+    /// First block executed with the runtime
+    pub enacted_at: BlockNumber,
+}
+
+pub type CodeRecordOf<T> = CodeRecord<
+    <T as frame_system::Trait>::AccountId,
+    <T as frame_system::Trait>::BlockNumber,
+    <T as frame_system::Trait>::Hash,
+>;
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Origin allowed to announce the code it proposes, typically members of the
+    /// technical committee
+    type ProposerOrigin: EnsureOrigin<Self::Origin, Success = Self::AccountId>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as CodeRegistry {
+        /// Hashes of the code announced by proposers and not enacted yet
+        Announcements get(fn announcement): map hasher(identity) T::Hash => Option<T::AccountId>;
+        /// Every runtime enacted since the registry was introduced, oldest first
+        Enacted get(fn enacted): Vec<CodeRecordOf<T>>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        Hash = <T as frame_system::Trait>::Hash,
+    {
+        /// Some code was announced by its proposer
+        CodeAnnounced(Hash, AccountId),
+    }
+);
+
+decl_error! {
+    /// Error for the code registry module.
+    pub enum Error for Module<T: Trait> {
+        /// The code was already announced, possibly by someone else
+        AlreadyAnnounced,
+        /// The code is the one currently running
+        AlreadyEnacted,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+        fn deposit_event() = default;
+
+        /// Announce the hash of a runtime about to be proposed, the caller is credited
+        /// as its proposer once it is enacted.
+        #[weight = T::WeightInfo::announce()]
+        fn announce(origin, code_hash: T::Hash) {
+            let who = T::ProposerOrigin::ensure_origin(origin)?;
+            ensure!(!<Announcements<T>>::contains_key(code_hash), Error::<T>::AlreadyAnnounced);
+            ensure!(
+                Self::current().map(|record| record.code_hash) != Some(code_hash),
+                Error::<T>::AlreadyEnacted
+            );
+
+            <Announcements<T>>::insert(code_hash, who.clone());
+            Self::deposit_event(RawEvent::CodeAnnounced(code_hash, who));
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            Self::note_code()
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// The runtime currently enacted, as far as the registry knows.
+    pub fn current() -> Option<CodeRecordOf<T>> {
+        Self::enacted().pop()
+    }
+
+    /// Record the code stored on chain, unless it is the last one recorded already.
+    /// Events deposited here would be cleared when the block is initialized, the
+    /// registry itself is the record.
+    fn note_code() -> Weight {
+        let code = match sp_io::storage::get(well_known_keys::CODE) {
+            Some(code) => code,
+            None => return T::DbWeight::get().reads(1),
+        };
+        let code_hash = T::Hashing::hash(&code);
+
+        let mut enacted = Self::enacted();
+        if enacted.last().map(|record| record.code_hash) == Some(code_hash) {
+            return T::DbWeight::get().reads(2);
+        }
+
+        enacted.push(CodeRecord {
+            code_hash,
+            spec_version: T::Version::get().spec_version,
+            proposer: <Announcements<T>>::take(code_hash),
+            // Upgrades run before the system module moves to the new block
+            enacted_at: <frame_system::Module<T>>::block_number().saturating_add(One::one()),
+        });
+        <Enacted<T>>::put(enacted);
+
+        T::DbWeight::get().reads_writes(4, 2)
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        ensure!(
+            Self::enacted().windows(2).all(|pair| {
+                pair[0].enacted_at < pair[1].enacted_at && pair[0].code_hash != pair[1].code_hash
+            }),
+            "enacted runtimes are out of order or repeated"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, traits::OnRuntimeUpgrade,
+};
+use frame_system::EnsureSignedBy;
+use sp_runtime::{traits::BlakeTwo256, DispatchError::BadOrigin};
+use test_utils::impl_mock_system;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), ());
+
+ord_parameter_types! {
+    pub const Proposer: u64 = 1;
+}
+impl Trait for Test {
+    type Event = ();
+    type ProposerOrigin = EnsureSignedBy<Proposer, u64>;
+    type WeightInfo = ();
+}
+type CodeRegistry = Module<Test>;
+type System = frame_system::Module<Test>;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
+
+/// Swap the code stored on chain as `set_code` would, and run the upgrade hook in
+/// the following block.
+fn enact(code: &[u8]) {
+    sp_io::storage::set(well_known_keys::CODE, code);
+    CodeRegistry::on_runtime_upgrade();
+    System::set_block_number(System::block_number() + 1);
+}
+
+#[test]
+fn enacted_code_is_recorded() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        enact(b"runtime v1");
+
+        assert_eq!(
+            CodeRegistry::enacted(),
+            vec![CodeRecord {
+                code_hash: BlakeTwo256::hash(b"runtime v1"),
+                spec_version: 0,
+                proposer: None,
+                enacted_at: 11,
+            }]
+        );
+    })
+}
+
+#[test]
+fn proposers_are_credited() {
+    new_test_ext().execute_with(|| {
+        let code_hash = BlakeTwo256::hash(b"runtime v2");
+        enact(b"runtime v1");
+
+        assert_ok!(CodeRegistry::announce(
+            Origin::signed(Proposer::get()),
+            code_hash
+        ));
+        assert_eq!(CodeRegistry::announcement(code_hash), Some(Proposer::get()));
+
+        enact(b"runtime v2");
+
+        assert_eq!(CodeRegistry::announcement(code_hash), None);
+        assert_eq!(
+            CodeRegistry::current().map(|record| (record.code_hash, record.proposer)),
+            Some((code_hash, Some(Proposer::get())))
+        );
+        assert_eq!(CodeRegistry::enacted().len(), 2);
+    })
+}
+
+#[test]
+fn same_code_is_not_recorded_twice() {
+    new_test_ext().execute_with(|| {
+        enact(b"runtime v1");
+        enact(b"runtime v1");
+
+        assert_eq!(CodeRegistry::enacted().len(), 1);
+    })
+}
+
+#[test]
+fn announcements_are_checked() {
+    new_test_ext().execute_with(|| {
+        enact(b"runtime v1");
+
+        assert_noop!(
+            CodeRegistry::announce(Origin::signed(0), BlakeTwo256::hash(b"runtime v2")),
+            BadOrigin
+        );
+        assert_noop!(
+            CodeRegistry::announce(
+                Origin::signed(Proposer::get()),
+                BlakeTwo256::hash(b"runtime v1")
+            ),
+            Error::<Test>::AlreadyEnacted
+        );
+
+        assert_ok!(CodeRegistry::announce(
+            Origin::signed(Proposer::get()),
+            BlakeTwo256::hash(b"runtime v2")
+        ));
+        assert_noop!(
+            CodeRegistry::announce(
+                Origin::signed(Proposer::get()),
+                BlakeTwo256::hash(b"runtime v2")
+            ),
+            Error::<Test>::AlreadyAnnounced
+        );
+    })
+}
@@ -0,0 +1,100 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Connectivity pallet benchmarks
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::UnfilteredDispatchable;
+use frame_system::RawOrigin;
+use sp_std::prelude::*;
+
+const SEED: u32 = 0;
+
+/// Register gateway `index` unless a previous run already did.
+fn gateway<T: Trait>(index: u32) -> T::AccountId {
+    let who: T::AccountId = account("gateway", index, SEED);
+    if !Module::<T>::is_gateway(&who) {
+        Module::<T>::register_gateway(RawOrigin::Signed(who.clone()).into())
+            .expect("there is room for the gateway");
+    }
+    who
+}
+
+benchmarks! {
+    _ { }
+
+    register_gateway {
+        let who: T::AccountId = account("gateway", 0, SEED);
+    }: _(RawOrigin::Signed(who))
+
+    unregister_gateway {
+        let who = gateway::<T>(0);
+    }: _(RawOrigin::Signed(who))
+
+    issue_challenge {
+        let verifier: T::AccountId = account("verifier", 0, SEED);
+        T::Verifiers::add(&verifier);
+        let gateway = gateway::<T>(0);
+    }: _(RawOrigin::Signed(verifier), gateway)
+
+    // Responses carry a signature of the gateway which benchmarks can not produce
+    // for any signature scheme, its verification is part of the estimate of `respond`
+
+    set_coverage_required {
+        let call = Call::<T>::set_coverage_required(true);
+        let origin = T::ManagerOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+
+    // Half of the gateways were challenged during the epoch closed, the others during
+    // the one before, whose coverage is dropped
+    close_epoch {
+        let g in 2 .. T::MaxGateways::get() * 2;
+
+        for i in 0..g {
+            let who: T::AccountId = account("gateway", i, SEED);
+            let epoch = i % 2;
+            <EpochCoverage<T>>::insert(epoch, who, Coverage { answered: 1, issued: 2 });
+            ChallengedGateways::mutate(epoch, |count| *count += 1);
+        }
+    }: { Module::<T>::close_epoch(1) }
+    verify {
+        assert!(Module::<T>::epoch_root(1).is_some());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_register_gateway::<Test>());
+            assert_ok!(test_benchmark_unregister_gateway::<Test>());
+            assert_ok!(test_benchmark_issue_challenge::<Test>());
+            assert_ok!(test_benchmark_set_coverage_required::<Test>());
+            assert_ok!(test_benchmark_close_epoch::<Test>());
+        });
+    }
+}
@@ -0,0 +1,422 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Measure the coverage of the network on chain. Verifiers challenge registered
+//! gateways with a random nonce, the gateway signs it and anyone can submit the signed
+//! response before the challenge expires. Every gateway gets a coverage score per
+//! epoch, the share of its challenges it answered.
+//!
+//! Once an epoch is over and its last challenges expired, the pallet commits to the
+//! scores of the epoch: the hash of the list of `(gateway, Coverage)` sorted by
+//! gateway. The oracles reference these commitments when they submit their batches
+//! of rewards, the scores of the last closed epoch are kept in storage so that they
+//! can be read when building the batches.
+
+mod benchmarking;
+pub mod weights;
+
+#[cfg(test)]
+mod tests;
+
+pub use weights::WeightInfo;
+
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{Contains, EnsureOrigin, Get, Randomness},
+    weights::Weight,
+    IterableStorageDoubleMap, Parameter,
+};
+use frame_system::{ensure_root, ensure_signed};
+use nodle_support::{CoverageEpochs, HousekeepingTask};
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{Hash, IdentifyAccount, One, SaturatedConversion, Saturating, Verify},
+    DispatchResult, Perbill, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+/// Challenges are numbered in the order they are issued.
+pub type ChallengeId = u64;
+pub type ChallengeOf<T> = Challenge<
+    <T as frame_system::Trait>::AccountId,
+    <T as frame_system::Trait>::Hash,
+    <T as frame_system::Trait>::BlockNumber,
+>;
+
+/// Signed by gateways along with the challenge, so that their responses can not be
+/// mistaken for anything else.
+pub const CHALLENGE_CONTEXT: &[u8] = b"nodle/connectivity";
+
+/// A challenge waiting for the response of a gateway.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Challenge<AccountId, Hash, BlockNumber> {
+    /// Verifier which issued the challenge
+    pub verifier: AccountId,
+    /// Gateway expected to answer
+    pub gateway: AccountId,
+    /// Random value the gateway signs
+    pub nonce: Hash,
+    /// Epoch the challenge counts towards
+    pub epoch: u32,
+    /// Last block at which a response is accepted
+    pub deadline: BlockNumber,
+}
+
+/// Challenges issued to a gateway during an epoch and how many it answered.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Coverage {
+    pub answered: u32,
+    pub issued: u32,
+}
+
+impl Coverage {
+    /// Share of its challenges the gateway answered.
+    pub fn score(&self) -> Perbill {
+        Perbill::from_rational_approximation(self.answered, self.issued)
+    }
+}
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Accounts allowed to challenge gateways
+    type Verifiers: Contains<Self::AccountId>;
+    /// Source of the nonces of the challenges
+    type Randomness: Randomness<Self::Hash>;
+    /// Key gateways sign their responses with, it identifies their account
+    type Public: IdentifyAccount<AccountId = Self::AccountId>;
+    /// Signature of a gateway over a challenge
+    type Signature: Parameter + Verify<Signer = Self::Public>;
+    /// Length of an epoch, in blocks
+    type EpochDuration: Get<Self::BlockNumber>;
+    /// Number of blocks gateways have to answer a challenge
+    type ResponseWindow: Get<Self::BlockNumber>;
+    /// Maximum number of gateways registered, or challenged during an epoch. Bounds
+    /// the cost of closing an epoch
+    type MaxGateways: Get<u32>;
+    /// Origin deciding whether batches of allocations must reference an epoch
+    type ManagerOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Connectivity {
+        /// Gateways verifiers can challenge
+        Gateways get(fn is_gateway): map hasher(blake2_128_concat) T::AccountId => bool;
+        GatewayCount get(fn gateway_count): u32;
+        /// Challenges waiting for a response
+        Challenges get(fn challenge): map hasher(twox_64_concat) ChallengeId => Option<ChallengeOf<T>>;
+        /// Number of challenges ever issued, also the identifier of the next one
+        ChallengeCount get(fn challenge_count): ChallengeId;
+        /// Challenges before this one were answered or removed once expired
+        OldestChallenge get(fn oldest_challenge): ChallengeId;
+        /// Coverage of the gateways challenged during an epoch, kept until the
+        /// following epoch is closed
+        EpochCoverage get(fn coverage): double_map hasher(twox_64_concat) u32, hasher(blake2_128_concat) T::AccountId => Coverage;
+        /// Number of gateways challenged during an epoch
+        ChallengedGateways get(fn challenged_gateways): map hasher(twox_64_concat) u32 => u32;
+        /// Commitment to the coverage of every closed epoch
+        EpochRoots get(fn epoch_root): map hasher(twox_64_concat) u32 => Option<T::Hash>;
+        /// Next epoch to close
+        NextEpoch get(fn next_epoch): u32;
+        /// Whether batches of allocations must reference the coverage of an epoch
+        CoverageRequired get(fn coverage_required): bool;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        Hash = <T as frame_system::Trait>::Hash,
+    {
+        /// The account registered as a gateway
+        GatewayRegistered(AccountId),
+        /// The gateway left, it can no longer be challenged
+        GatewayUnregistered(AccountId),
+        /// The gateway was challenged to sign the given nonce
+        ChallengeIssued(ChallengeId, AccountId, Hash),
+        /// The gateway answered the challenge
+        ChallengeAnswered(ChallengeId, AccountId),
+        /// The coverage of the epoch was committed to with the given root
+        EpochClosed(u32, Hash),
+        /// Batches of allocations must now, or no longer, reference an epoch
+        CoverageRequirementSet(bool),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// The account is already a gateway
+        AlreadyRegistered,
+        /// The account is not a gateway
+        NotAGateway,
+        /// No more gateways can be registered or challenged this epoch
+        TooManyGateways,
+        /// Only verifiers can issue challenges
+        NotAVerifier,
+        /// No challenge is waiting for a response under this identifier
+        UnknownChallenge,
+        /// The response window of the challenge is over
+        ChallengeExpired,
+        /// The signature does not come from the gateway or is not over the challenge
+        InvalidResponse,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const EpochDuration: T::BlockNumber = T::EpochDuration::get();
+        const ResponseWindow: T::BlockNumber = T::ResponseWindow::get();
+        const MaxGateways: u32 = T::MaxGateways::get();
+
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Register the caller as a gateway, its key answers the challenges
+        #[weight = T::WeightInfo::register_gateway()]
+        pub fn register_gateway(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!Self::is_gateway(&who), Error::<T>::AlreadyRegistered);
+            let count = Self::gateway_count();
+            ensure!(count < T::MaxGateways::get(), Error::<T>::TooManyGateways);
+
+            <Gateways<T>>::insert(&who, true);
+            GatewayCount::put(count.saturating_add(1));
+
+            Self::deposit_event(RawEvent::GatewayRegistered(who));
+            Ok(())
+        }
+
+        /// Stop being challenged, the coverage of the current epoch is kept
+        #[weight = T::WeightInfo::unregister_gateway()]
+        pub fn unregister_gateway(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::is_gateway(&who), Error::<T>::NotAGateway);
+
+            <Gateways<T>>::remove(&who);
+            GatewayCount::mutate(|count| *count = count.saturating_sub(1));
+
+            Self::deposit_event(RawEvent::GatewayUnregistered(who));
+            Ok(())
+        }
+
+        /// Challenge `gateway` to sign a random nonce within `ResponseWindow` blocks
+        #[weight = T::WeightInfo::issue_challenge()]
+        pub fn issue_challenge(origin, gateway: T::AccountId) -> DispatchResult {
+            let verifier = ensure_signed(origin)?;
+            ensure!(T::Verifiers::contains(&verifier), Error::<T>::NotAVerifier);
+            ensure!(Self::is_gateway(&gateway), Error::<T>::NotAGateway);
+
+            let now = <frame_system::Module<T>>::block_number();
+            let epoch = Self::epoch(now);
+            let first = !<EpochCoverage<T>>::contains_key(epoch, &gateway);
+            let challenged = Self::challenged_gateways(epoch);
+            ensure!(
+                !first || challenged < T::MaxGateways::get(),
+                Error::<T>::TooManyGateways
+            );
+
+            let id = Self::challenge_count();
+            let nonce = T::Randomness::random(&(CHALLENGE_CONTEXT, id).encode());
+            <Challenges<T>>::insert(id, Challenge {
+                verifier,
+                gateway: gateway.clone(),
+                nonce,
+                epoch,
+                deadline: now.saturating_add(T::ResponseWindow::get()),
+            });
+            ChallengeCount::put(id.saturating_add(1));
+            <EpochCoverage<T>>::mutate(epoch, &gateway, |coverage| {
+                coverage.issued = coverage.issued.saturating_add(1)
+            });
+            if first {
+                ChallengedGateways::insert(epoch, challenged.saturating_add(1));
+            }
+
+            Self::deposit_event(RawEvent::ChallengeIssued(id, gateway, nonce));
+            Ok(())
+        }
+
+        /// Submit the signature of the gateway over the challenge, see
+        /// `response_payload`. Anyone can relay it
+        #[weight = T::WeightInfo::respond()]
+        pub fn respond(origin, id: ChallengeId, signature: T::Signature) -> DispatchResult {
+            ensure_signed(origin)?;
+            let challenge = Self::challenge(id).ok_or(Error::<T>::UnknownChallenge)?;
+            ensure!(
+                <frame_system::Module<T>>::block_number() <= challenge.deadline,
+                Error::<T>::ChallengeExpired
+            );
+            ensure!(
+                signature.verify(&Self::response_payload(id, &challenge.nonce)[..], &challenge.gateway),
+                Error::<T>::InvalidResponse
+            );
+
+            <Challenges<T>>::remove(id);
+            <EpochCoverage<T>>::mutate(challenge.epoch, &challenge.gateway, |coverage| {
+                coverage.answered = coverage.answered.saturating_add(1)
+            });
+
+            Self::deposit_event(RawEvent::ChallengeAnswered(id, challenge.gateway));
+            Ok(())
+        }
+
+        /// Require, or stop requiring, the batches of allocations to reference the
+        /// coverage of an epoch
+        #[weight = T::WeightInfo::set_coverage_required()]
+        pub fn set_coverage_required(origin, required: bool) -> DispatchResult {
+            T::ManagerOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+
+            CoverageRequired::put(required);
+            Self::deposit_event(RawEvent::CoverageRequirementSet(required));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Epoch block `n` belongs to.
+    pub fn epoch(n: T::BlockNumber) -> u32 {
+        (n / T::EpochDuration::get().max(One::one())).saturated_into()
+    }
+
+    /// What gateways sign to answer challenge `id`.
+    pub fn response_payload(id: ChallengeId, nonce: &T::Hash) -> Vec<u8> {
+        (CHALLENGE_CONTEXT, id, nonce).encode()
+    }
+
+    /// Whether every challenge of `epoch` expired at block `n`.
+    fn is_closable(epoch: u32, n: T::BlockNumber) -> bool {
+        let end = T::BlockNumber::from(epoch.saturating_add(1))
+            .saturating_mul(T::EpochDuration::get().max(One::one()));
+        n >= end.saturating_add(T::ResponseWindow::get())
+    }
+
+    /// Commit to the coverage of `epoch` and drop the one of the epoch before.
+    fn close_epoch(epoch: u32) {
+        let mut coverage: Vec<(T::AccountId, Coverage)> =
+            <EpochCoverage<T>>::iter_prefix(epoch).collect();
+        coverage.sort_by(|a, b| a.0.cmp(&b.0));
+        let root = T::Hashing::hash_of(&coverage);
+
+        if let Some(previous) = epoch.checked_sub(1) {
+            <EpochCoverage<T>>::remove_prefix(previous);
+            ChallengedGateways::remove(previous);
+        }
+        <EpochRoots<T>>::insert(epoch, root);
+        NextEpoch::put(epoch.saturating_add(1));
+
+        Self::deposit_event(RawEvent::EpochClosed(epoch, root));
+    }
+}
+
+impl<T: Trait> CoverageEpochs<T::Hash> for Module<T> {
+    fn is_required() -> bool {
+        Self::coverage_required()
+    }
+
+    fn coverage_root(epoch: u32) -> Option<T::Hash> {
+        Self::epoch_root(epoch)
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_coverage(epoch: u32, root: &T::Hash) {
+        <EpochRoots<T>>::insert(epoch, root);
+    }
+}
+
+impl<T: Trait> HousekeepingTask<T::BlockNumber> for Module<T> {
+    fn housekeep(n: T::BlockNumber, budget: Weight) -> Weight {
+        let mut consumed = T::DbWeight::get().reads(3);
+        if budget < consumed {
+            return 0;
+        }
+
+        // Deadlines follow the order challenges are issued in, expired ones are
+        // removed oldest first and the rest in the following blocks
+        let per_challenge = T::DbWeight::get().reads_writes(1, 1);
+        let count = Self::challenge_count();
+        let mut oldest = Self::oldest_challenge();
+        while oldest < count && consumed.saturating_add(per_challenge) <= budget {
+            if let Some(challenge) = Self::challenge(oldest) {
+                if challenge.deadline >= n {
+                    break;
+                }
+                <Challenges<T>>::remove(oldest);
+            }
+            oldest = oldest.saturating_add(1);
+            consumed = consumed.saturating_add(per_challenge);
+        }
+        if oldest != Self::oldest_challenge() {
+            OldestChallenge::put(oldest);
+            consumed = consumed.saturating_add(T::DbWeight::get().writes(1));
+        }
+
+        let mut epoch = Self::next_epoch();
+        while Self::is_closable(epoch, n) {
+            let gateways = Self::challenged_gateways(epoch).saturating_add(
+                epoch
+                    .checked_sub(1)
+                    .map_or(0, |previous| Self::challenged_gateways(previous)),
+            );
+            let weight = T::WeightInfo::close_epoch(gateways);
+            if consumed.saturating_add(weight) > budget {
+                break;
+            }
+            Self::close_epoch(epoch);
+            consumed = consumed.saturating_add(weight);
+            epoch = epoch.saturating_add(1);
+        }
+
+        consumed
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        use frame_support::IterableStorageMap;
+
+        ensure!(
+            <Gateways<T>>::iter().count() == Self::gateway_count() as usize,
+            "gateway count does not match the registered gateways"
+        );
+        ensure!(
+            Self::oldest_challenge() <= Self::challenge_count(),
+            "oldest challenge is ahead of the challenge count"
+        );
+        ensure!(
+            Challenges::<T>::iter()
+                .all(|(id, _)| id >= Self::oldest_challenge() && id < Self::challenge_count()),
+            "challenge stored outside of the open range"
+        );
+
+        Ok(())
+    }
+}
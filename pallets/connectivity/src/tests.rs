@@ -0,0 +1,338 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, parameter_types,
+};
+use frame_system::{EnsureSignedBy, RawOrigin};
+use sp_core::H256;
+use sp_runtime::{
+    testing::{TestSignature, UintAuthorityId},
+    traits::BlakeTwo256,
+    DispatchError::BadOrigin,
+};
+use std::cell::RefCell;
+use test_utils::impl_mock_system;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), ());
+
+thread_local! {
+    static VERIFIERS: RefCell<Vec<u64>> = RefCell::new(vec![VERIFIER]);
+}
+
+pub struct Verifiers;
+impl Contains<u64> for Verifiers {
+    fn sorted_members() -> Vec<u64> {
+        VERIFIERS.with(|v| v.borrow().clone())
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add(who: &u64) {
+        VERIFIERS.with(|v| {
+            let mut verifiers = v.borrow_mut();
+            if let Err(position) = verifiers.binary_search(who) {
+                verifiers.insert(position, *who);
+            }
+        });
+    }
+}
+
+/// Hashes the subject, nonces only need to differ from one challenge to the next.
+pub struct TestRandomness;
+impl Randomness<H256> for TestRandomness {
+    fn random(subject: &[u8]) -> H256 {
+        BlakeTwo256::hash(subject)
+    }
+}
+
+ord_parameter_types! {
+    pub const Manager: u64 = 1;
+}
+parameter_types! {
+    pub const EpochDuration: u64 = 10;
+    pub const ResponseWindow: u64 = 3;
+    pub const MaxGateways: u32 = 2;
+}
+impl Trait for Test {
+    type Event = ();
+    type Verifiers = Verifiers;
+    type Randomness = TestRandomness;
+    type Public = UintAuthorityId;
+    type Signature = TestSignature;
+    type EpochDuration = EpochDuration;
+    type ResponseWindow = ResponseWindow;
+    type MaxGateways = MaxGateways;
+    type ManagerOrigin = EnsureSignedBy<Manager, u64>;
+    type WeightInfo = ();
+}
+type Connectivity = Module<Test>;
+type System = frame_system::Module<Test>;
+type Errors = Error<Test>;
+
+const MANAGER: u64 = 1;
+const VERIFIER: u64 = 2;
+const GATEWAYS: [u64; 3] = [10, 11, 12];
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let storage = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    let mut ext: sp_io::TestExternalities = storage.into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+fn register(gateway: u64) -> DispatchResult {
+    Connectivity::register_gateway(Origin::signed(gateway))
+}
+
+fn challenge(gateway: u64) -> DispatchResult {
+    Connectivity::issue_challenge(Origin::signed(VERIFIER), gateway)
+}
+
+/// Signature of `gateway` over challenge `id`.
+fn answer(gateway: u64, id: ChallengeId) -> TestSignature {
+    let nonce = Connectivity::challenge(id)
+        .expect("the challenge is pending")
+        .nonce;
+    TestSignature(gateway, Connectivity::response_payload(id, &nonce))
+}
+
+fn respond(id: ChallengeId, signature: TestSignature) -> DispatchResult {
+    Connectivity::respond(Origin::signed(VERIFIER), id, signature)
+}
+
+#[test]
+fn gateways_register() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register(GATEWAYS[0]));
+        assert!(Connectivity::is_gateway(GATEWAYS[0]));
+        assert_eq!(Connectivity::gateway_count(), 1);
+        assert_noop!(register(GATEWAYS[0]), Errors::AlreadyRegistered);
+
+        assert_ok!(register(GATEWAYS[1]));
+        assert_noop!(register(GATEWAYS[2]), Errors::TooManyGateways);
+
+        assert_noop!(
+            Connectivity::unregister_gateway(Origin::signed(GATEWAYS[2])),
+            Errors::NotAGateway
+        );
+        assert_ok!(Connectivity::unregister_gateway(Origin::signed(
+            GATEWAYS[0]
+        )));
+        assert!(!Connectivity::is_gateway(GATEWAYS[0]));
+        assert_ok!(register(GATEWAYS[2]));
+    })
+}
+
+#[test]
+fn verifiers_challenge_gateways() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register(GATEWAYS[0]));
+        assert_noop!(
+            Connectivity::issue_challenge(Origin::signed(GATEWAYS[1]), GATEWAYS[0]),
+            Errors::NotAVerifier
+        );
+        assert_noop!(challenge(GATEWAYS[1]), Errors::NotAGateway);
+
+        assert_ok!(challenge(GATEWAYS[0]));
+        assert_eq!(
+            Connectivity::challenge(0),
+            Some(Challenge {
+                verifier: VERIFIER,
+                gateway: GATEWAYS[0],
+                nonce: BlakeTwo256::hash(&(CHALLENGE_CONTEXT, 0u64).encode()),
+                epoch: 0,
+                deadline: 4,
+            })
+        );
+        assert_eq!(Connectivity::challenge_count(), 1);
+        assert_eq!(
+            Connectivity::coverage(0, GATEWAYS[0]),
+            Coverage {
+                answered: 0,
+                issued: 1
+            }
+        );
+        assert_eq!(Connectivity::challenged_gateways(0), 1);
+    })
+}
+
+#[test]
+fn gateways_answer_with_their_signature() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register(GATEWAYS[0]));
+        assert_ok!(challenge(GATEWAYS[0]));
+
+        assert_noop!(respond(0, answer(GATEWAYS[1], 0)), Errors::InvalidResponse);
+        assert_noop!(
+            respond(0, TestSignature(GATEWAYS[0], vec![1, 2, 3])),
+            Errors::InvalidResponse
+        );
+
+        let signature = answer(GATEWAYS[0], 0);
+        assert_ok!(respond(0, signature.clone()));
+        assert_eq!(Connectivity::challenge(0), None);
+        assert_eq!(
+            Connectivity::coverage(0, GATEWAYS[0]),
+            Coverage {
+                answered: 1,
+                issued: 1
+            }
+        );
+        assert_noop!(respond(0, signature), Errors::UnknownChallenge);
+    })
+}
+
+#[test]
+fn late_responses_are_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register(GATEWAYS[0]));
+        assert_ok!(challenge(GATEWAYS[0]));
+        let signature = answer(GATEWAYS[0], 0);
+
+        System::set_block_number(5);
+        assert_noop!(respond(0, signature), Errors::ChallengeExpired);
+    })
+}
+
+#[test]
+fn challenged_gateways_are_bounded_per_epoch() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register(GATEWAYS[0]));
+        assert_ok!(register(GATEWAYS[1]));
+        assert_ok!(challenge(GATEWAYS[0]));
+        assert_ok!(challenge(GATEWAYS[1]));
+
+        assert_ok!(Connectivity::unregister_gateway(Origin::signed(
+            GATEWAYS[0]
+        )));
+        assert_ok!(register(GATEWAYS[2]));
+        assert_noop!(challenge(GATEWAYS[2]), Errors::TooManyGateways);
+        assert_ok!(challenge(GATEWAYS[1]));
+
+        System::set_block_number(10);
+        assert_ok!(challenge(GATEWAYS[2]));
+    })
+}
+
+#[test]
+fn expired_challenges_are_removed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register(GATEWAYS[0]));
+        assert_ok!(challenge(GATEWAYS[0]));
+        assert_ok!(challenge(GATEWAYS[0]));
+        assert_ok!(respond(1, answer(GATEWAYS[0], 1)));
+        System::set_block_number(3);
+        assert_ok!(challenge(GATEWAYS[0]));
+
+        Connectivity::housekeep(5, Weight::max_value());
+        assert_eq!(Connectivity::challenge(0), None);
+        assert!(Connectivity::challenge(2).is_some());
+        assert_eq!(Connectivity::oldest_challenge(), 2);
+
+        Connectivity::housekeep(7, Weight::max_value());
+        assert_eq!(Connectivity::challenge(2), None);
+        assert_eq!(Connectivity::oldest_challenge(), 3);
+    })
+}
+
+#[test]
+fn epochs_are_committed_to_once_their_challenges_expired() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register(GATEWAYS[0]));
+        assert_ok!(register(GATEWAYS[1]));
+        assert_ok!(challenge(GATEWAYS[1]));
+        assert_ok!(challenge(GATEWAYS[0]));
+        assert_ok!(respond(1, answer(GATEWAYS[0], 1)));
+
+        Connectivity::housekeep(12, Weight::max_value());
+        assert_eq!(Connectivity::epoch_root(0), None);
+        // Not enough room to close the epoch
+        Connectivity::housekeep(13, 0);
+        assert_eq!(Connectivity::epoch_root(0), None);
+
+        Connectivity::housekeep(13, Weight::max_value());
+        let coverage = vec![
+            (
+                GATEWAYS[0],
+                Coverage {
+                    answered: 1,
+                    issued: 1,
+                },
+            ),
+            (
+                GATEWAYS[1],
+                Coverage {
+                    answered: 0,
+                    issued: 1,
+                },
+            ),
+        ];
+        let root = BlakeTwo256::hash_of(&coverage);
+        assert_eq!(Connectivity::epoch_root(0), Some(root));
+        assert_eq!(Connectivity::coverage_root(0), Some(root));
+        assert_eq!(Connectivity::next_epoch(), 1);
+        assert_eq!(coverage[0].1.score(), Perbill::one());
+        assert_eq!(coverage[1].1.score(), Perbill::zero());
+
+        // The scores of the last closed epoch are kept around
+        assert_eq!(Connectivity::coverage(0, GATEWAYS[0]), coverage[0].1);
+        Connectivity::housekeep(23, Weight::max_value());
+        assert!(Connectivity::epoch_root(1).is_some());
+        assert_eq!(Connectivity::coverage(0, GATEWAYS[0]), Coverage::default());
+        assert_eq!(Connectivity::challenged_gateways(0), 0);
+    })
+}
+
+#[test]
+fn manager_requires_coverage_references() {
+    new_test_ext().execute_with(|| {
+        assert!(!Connectivity::is_required());
+        assert_noop!(
+            Connectivity::set_coverage_required(Origin::signed(VERIFIER), true),
+            BadOrigin
+        );
+
+        assert_ok!(Connectivity::set_coverage_required(
+            Origin::signed(MANAGER),
+            true
+        ));
+        assert!(Connectivity::is_required());
+        assert_ok!(Connectivity::set_coverage_required(
+            RawOrigin::Root.into(),
+            false
+        ));
+        assert!(!Connectivity::is_required());
+    })
+}
@@ -0,0 +1,141 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Data market pallet benchmarks
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::UnfilteredDispatchable;
+use frame_system::RawOrigin;
+use sp_runtime::traits::Hash;
+use sp_std::prelude::*;
+
+const SEED: u32 = 0;
+
+fn price<T: Trait>() -> BalanceOf<T> {
+    T::Currency::minimum_balance().saturating_mul(10u32.into())
+}
+
+fn budget<T: Trait>() -> BalanceOf<T> {
+    price::<T>().saturating_mul(100u32.into())
+}
+
+fn buyer<T: Trait>() -> T::AccountId {
+    let buyer: T::AccountId = account("buyer", 0, SEED);
+    T::Currency::make_free_balance_be(&buyer, budget::<T>().saturating_mul(10u32.into()));
+    buyer
+}
+
+/// Oracle filling the orders, funded so that it can be paid.
+fn seller<T: Trait>() -> T::AccountId {
+    let seller: T::AccountId = account("seller", 0, SEED);
+    T::Currency::make_free_balance_be(&seller, T::Currency::minimum_balance());
+    T::Oracles::add(&seller);
+    seller
+}
+
+/// Post an order with the longest type of data, returns its identifier.
+fn order<T: Trait>(buyer: &T::AccountId) -> OrderId {
+    let id = Module::<T>::order_count();
+    Module::<T>::post_order(
+        RawOrigin::Signed(buyer.clone()).into(),
+        vec![0; T::MaxDataTypeLength::get() as usize],
+        price::<T>(),
+        budget::<T>(),
+    )
+    .expect("the buyer can afford the budget");
+    id
+}
+
+/// Fill an order with data not delivered before, returns the delivery identifier.
+fn delivery<T: Trait>() -> DeliveryId {
+    let buyer = buyer::<T>();
+    let order = order::<T>(&buyer);
+    let id = Module::<T>::delivery_count();
+    Module::<T>::fill(
+        RawOrigin::Signed(seller::<T>()).into(),
+        order,
+        None,
+        1,
+        T::Hashing::hash_of(&id),
+    )
+    .expect("the oracle can fill the order");
+    id
+}
+
+benchmarks! {
+    _ { }
+
+    post_order {
+        let buyer = buyer::<T>();
+        let data_type = vec![0; T::MaxDataTypeLength::get() as usize];
+    }: _(RawOrigin::Signed(buyer), data_type, price::<T>(), budget::<T>())
+
+    cancel_order {
+        let buyer = buyer::<T>();
+        let id = order::<T>(&buyer);
+    }: _(RawOrigin::Signed(buyer), id)
+
+    fill {
+        let buyer = buyer::<T>();
+        let id = order::<T>(&buyer);
+        let seller = seller::<T>();
+        let data = T::Hashing::hash_of(&Module::<T>::delivery_count());
+    }: _(RawOrigin::Signed(seller), id, None, 1, data)
+
+    dispute {
+        let id = delivery::<T>();
+    }: _(RawOrigin::Signed(buyer::<T>()), id)
+
+    claim {
+        let id = delivery::<T>();
+        let release = Module::<T>::delivery(id).map(|d| d.release).unwrap_or_default();
+        frame_system::Module::<T>::set_block_number(release);
+    }: _(RawOrigin::Signed(buyer::<T>()), id)
+
+    // Accepting is the most expensive ruling as the seller is paid
+    resolve {
+        let id = delivery::<T>();
+        Module::<T>::dispute(RawOrigin::Signed(buyer::<T>()).into(), id)
+            .expect("the delivery was just made");
+        let call = Call::<T>::resolve(id, true);
+        let origin = T::ArbiterOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_post_order::<Test>());
+            assert_ok!(test_benchmark_cancel_order::<Test>());
+            assert_ok!(test_benchmark_fill::<Test>());
+            assert_ok!(test_benchmark_dispute::<Test>());
+            assert_ok!(test_benchmark_claim::<Test>());
+            assert_ok!(test_benchmark_resolve::<Test>());
+        });
+    }
+}
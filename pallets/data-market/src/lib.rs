@@ -0,0 +1,421 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Order book for data purchases. Buyers post orders for a type of data at a price
+//! per unit, the whole budget of the order is reserved until it is spent or the order
+//! cancelled. Registered devices, through their payout account, and oracles fill the
+//! orders with deliveries anchoring the hash of the data they provided.
+//!
+//! Deliveries are paid out of the reserved budget once `AcceptancePeriod` elapsed,
+//! unless the buyer disputed them in the meantime. Disputed deliveries are settled
+//! by `ArbiterOrigin`, either paying the seller or refunding the buyer.
+
+mod benchmarking;
+pub mod weights;
+
+#[cfg(test)]
+mod tests;
+
+pub use weights::WeightInfo;
+
+use chain_primitives::DeviceResolver;
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{BalanceStatus, Contains, Currency, EnsureOrigin, Get, ReservableCurrency},
+    IterableStorageMap, Parameter,
+};
+use frame_system::ensure_signed;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{CheckedMul, Member, Saturating, Zero},
+    DispatchResult, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+pub type OrderOf<T> = Order<<T as frame_system::Trait>::AccountId, BalanceOf<T>>;
+pub type DeliveryOf<T> = Delivery<
+    <T as frame_system::Trait>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Trait>::Hash,
+    <T as frame_system::Trait>::BlockNumber,
+>;
+
+/// Orders are numbered in the order they are posted.
+pub type OrderId = u64;
+/// Deliveries are numbered in the order they are made.
+pub type DeliveryId = u64;
+
+/// Standing order for a type of data.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Order<AccountId, Balance> {
+    /// Account which posted the order and pays for the deliveries
+    pub buyer: AccountId,
+    /// Type of data wanted, as agreed upon off chain
+    pub data_type: Vec<u8>,
+    /// Price paid per unit of data
+    pub price: Balance,
+    /// Part of the budget not spent on deliveries yet, reserved from the buyer
+    pub budget: Balance,
+}
+
+/// Data delivered against an order, waiting to be paid.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Delivery<AccountId, Balance, Hash, BlockNumber> {
+    /// Order the delivery fills
+    pub order: OrderId,
+    /// Account paying for the delivery, the amount is reserved from it
+    pub buyer: AccountId,
+    /// Account the delivery is paid to
+    pub seller: AccountId,
+    /// Number of units delivered
+    pub units: u32,
+    /// Amount due for the delivery
+    pub amount: Balance,
+    /// Hash of the delivered data
+    pub data: Hash,
+    /// Block from which the delivery can be paid if not disputed
+    pub release: BlockNumber,
+    /// Whether the buyer disputed the delivery
+    pub disputed: bool,
+}
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// Stable identity of the devices
+    type DeviceId: Member + Parameter;
+    /// Resolves devices filling orders to the account they are paid to
+    type Devices: DeviceResolver<Self::DeviceId, Self::AccountId>;
+    /// Oracles allowed to fill orders on behalf of the network
+    type Oracles: Contains<Self::AccountId>;
+    /// Number of blocks buyers have to dispute a delivery
+    type AcceptancePeriod: Get<Self::BlockNumber>;
+    /// Maximum length of the type of data of an order
+    type MaxDataTypeLength: Get<u32>;
+    /// Origin settling disputed deliveries
+    type ArbiterOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as DataMarket {
+        /// Open orders
+        Orders get(fn order): map hasher(twox_64_concat) OrderId => Option<OrderOf<T>>;
+        /// Number of orders ever posted, also the identifier of the next one
+        OrderCount get(fn order_count): OrderId;
+        /// Deliveries waiting to be paid
+        Deliveries get(fn delivery): map hasher(twox_64_concat) DeliveryId => Option<DeliveryOf<T>>;
+        /// Number of deliveries ever made, also the identifier of the next one
+        DeliveryCount get(fn delivery_count): DeliveryId;
+        /// Block at which the data with the given hash was delivered, so that the same
+        /// data can not be sold twice
+        Anchors get(fn anchor): map hasher(blake2_128_concat) T::Hash => Option<T::BlockNumber>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        Balance = BalanceOf<T>,
+        Hash = <T as frame_system::Trait>::Hash,
+    {
+        /// The buyer posted an order at the given price per unit and budget
+        OrderPosted(OrderId, AccountId, Balance, Balance),
+        /// The order was cancelled or its budget spent, the given remainder was
+        /// returned to the buyer
+        OrderClosed(OrderId, Balance),
+        /// The seller delivered the given number of units of the data with the given
+        /// hash against the order
+        OrderFilled(OrderId, DeliveryId, AccountId, u32, Hash),
+        /// The buyer disputed the delivery
+        DeliveryDisputed(DeliveryId),
+        /// The given amount was paid to the seller for the delivery
+        DeliveryPaid(DeliveryId, AccountId, Balance),
+        /// The disputed delivery was refunded to the buyer
+        DeliveryRefunded(DeliveryId, AccountId, Balance),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// The price or the budget is zero
+        ZeroAmount,
+        /// The type of data is longer than `MaxDataTypeLength`
+        DataTypeTooLong,
+        /// The buyer can not afford the budget
+        InsufficientBudget,
+        /// No order is open under this identifier
+        UnknownOrder,
+        /// Only the buyer can do this
+        NotBuyer,
+        /// The caller is neither an oracle nor the payout account of the device
+        NotSeller,
+        /// The cost of the delivery exceeds the remaining budget
+        ExceedsBudget,
+        /// The data was already delivered
+        AlreadyAnchored,
+        /// No delivery is waiting to be paid under this identifier
+        UnknownDelivery,
+        /// The delivery can not be disputed anymore
+        AcceptanceOver,
+        /// The delivery can not be paid yet
+        AcceptancePending,
+        /// The delivery was already disputed
+        AlreadyDisputed,
+        /// The delivery was not disputed
+        NotDisputed,
+        /// No more orders or deliveries can be made
+        Overflow,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const AcceptancePeriod: T::BlockNumber = T::AcceptancePeriod::get();
+        const MaxDataTypeLength: u32 = T::MaxDataTypeLength::get();
+
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Post an order for `data_type` at `price` per unit, `budget` is reserved
+        /// until spent on deliveries or the order is cancelled
+        #[weight = T::WeightInfo::post_order()]
+        pub fn post_order(origin, data_type: Vec<u8>, price: BalanceOf<T>, budget: BalanceOf<T>) -> DispatchResult {
+            let buyer = ensure_signed(origin)?;
+            ensure!(!price.is_zero() && !budget.is_zero(), Error::<T>::ZeroAmount);
+            ensure!(
+                data_type.len() <= T::MaxDataTypeLength::get() as usize,
+                Error::<T>::DataTypeTooLong
+            );
+            let id = Self::order_count();
+            let next = id.checked_add(1).ok_or(Error::<T>::Overflow)?;
+
+            T::Currency::reserve(&buyer, budget).map_err(|_| Error::<T>::InsufficientBudget)?;
+            <Orders<T>>::insert(id, Order {
+                buyer: buyer.clone(),
+                data_type,
+                price,
+                budget,
+            });
+            OrderCount::put(next);
+
+            Self::deposit_event(RawEvent::OrderPosted(id, buyer, price, budget));
+            Ok(())
+        }
+
+        /// Cancel an order and get back the part of its budget not spent yet,
+        /// deliveries already made are still paid
+        #[weight = T::WeightInfo::cancel_order()]
+        pub fn cancel_order(origin, id: OrderId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let order = Self::order(id).ok_or(Error::<T>::UnknownOrder)?;
+            ensure!(order.buyer == who, Error::<T>::NotBuyer);
+
+            Self::close_order(id, &order);
+            Ok(())
+        }
+
+        /// Deliver `units` of the data hashed to `data` against the order. The caller
+        /// must be an oracle or, if `device` is set, the payout account of the device
+        #[weight = T::WeightInfo::fill()]
+        pub fn fill(origin, id: OrderId, device: Option<T::DeviceId>, units: u32, data: T::Hash) -> DispatchResult {
+            let seller = ensure_signed(origin)?;
+            let is_seller = match device {
+                Some(device) => T::Devices::payout_account(&device) == Some(seller.clone()),
+                None => T::Oracles::contains(&seller),
+            };
+            ensure!(is_seller, Error::<T>::NotSeller);
+            ensure!(units > 0, Error::<T>::ZeroAmount);
+            ensure!(!<Anchors<T>>::contains_key(&data), Error::<T>::AlreadyAnchored);
+
+            let mut order = Self::order(id).ok_or(Error::<T>::UnknownOrder)?;
+            let amount = order
+                .price
+                .checked_mul(&units.into())
+                .ok_or(Error::<T>::ExceedsBudget)?;
+            ensure!(amount <= order.budget, Error::<T>::ExceedsBudget);
+            let delivery_id = Self::delivery_count();
+            let next = delivery_id.checked_add(1).ok_or(Error::<T>::Overflow)?;
+
+            let now = <frame_system::Module<T>>::block_number();
+            <Deliveries<T>>::insert(delivery_id, Delivery {
+                order: id,
+                buyer: order.buyer.clone(),
+                seller: seller.clone(),
+                units,
+                amount,
+                data,
+                release: now.saturating_add(T::AcceptancePeriod::get()),
+                disputed: false,
+            });
+            DeliveryCount::put(next);
+            <Anchors<T>>::insert(&data, now);
+
+            order.budget = order.budget.saturating_sub(amount);
+            Self::deposit_event(RawEvent::OrderFilled(id, delivery_id, seller, units, data));
+
+            // Close orders which can not pay for another unit
+            if order.budget < order.price {
+                Self::close_order(id, &order);
+            } else {
+                <Orders<T>>::insert(id, order);
+            }
+            Ok(())
+        }
+
+        /// Dispute a delivery before it can be paid, it is then settled by the arbiters
+        #[weight = T::WeightInfo::dispute()]
+        pub fn dispute(origin, id: DeliveryId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let mut delivery = Self::delivery(id).ok_or(Error::<T>::UnknownDelivery)?;
+            ensure!(delivery.buyer == who, Error::<T>::NotBuyer);
+            ensure!(!delivery.disputed, Error::<T>::AlreadyDisputed);
+            ensure!(
+                <frame_system::Module<T>>::block_number() < delivery.release,
+                Error::<T>::AcceptanceOver
+            );
+
+            delivery.disputed = true;
+            <Deliveries<T>>::insert(id, delivery);
+
+            Self::deposit_event(RawEvent::DeliveryDisputed(id));
+            Ok(())
+        }
+
+        /// Pay an undisputed delivery once its acceptance period is over, anyone can
+        /// trigger the payment
+        #[weight = T::WeightInfo::claim()]
+        pub fn claim(origin, id: DeliveryId) -> DispatchResult {
+            ensure_signed(origin)?;
+            let delivery = Self::delivery(id).ok_or(Error::<T>::UnknownDelivery)?;
+            ensure!(!delivery.disputed, Error::<T>::AlreadyDisputed);
+            ensure!(
+                <frame_system::Module<T>>::block_number() >= delivery.release,
+                Error::<T>::AcceptancePending
+            );
+
+            Self::pay(id, &delivery)
+        }
+
+        /// Settle a disputed delivery, paying the seller if `accept` is set and
+        /// refunding the buyer otherwise
+        #[weight = T::WeightInfo::resolve()]
+        pub fn resolve(origin, id: DeliveryId, accept: bool) -> DispatchResult {
+            T::ArbiterOrigin::ensure_origin(origin)?;
+            let delivery = Self::delivery(id).ok_or(Error::<T>::UnknownDelivery)?;
+            ensure!(delivery.disputed, Error::<T>::NotDisputed);
+
+            if accept {
+                return Self::pay(id, &delivery);
+            }
+
+            <Deliveries<T>>::remove(id);
+            T::Currency::unreserve(&delivery.buyer, delivery.amount);
+
+            Self::deposit_event(RawEvent::DeliveryRefunded(id, delivery.buyer, delivery.amount));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Funds reserved from `who` for its open orders and the deliveries it has yet to
+    /// pay for.
+    pub fn deposits_of(who: &T::AccountId) -> BalanceOf<T> {
+        let budgets = <Orders<T>>::iter()
+            .filter(|(_, order)| &order.buyer == who)
+            .fold(Zero::zero(), |total: BalanceOf<T>, (_, order)| {
+                total.saturating_add(order.budget)
+            });
+        <Deliveries<T>>::iter()
+            .filter(|(_, delivery)| &delivery.buyer == who)
+            .fold(budgets, |total, (_, delivery)| {
+                total.saturating_add(delivery.amount)
+            })
+    }
+
+    /// Remove the order and return the remainder of its budget to the buyer.
+    fn close_order(id: OrderId, order: &OrderOf<T>) {
+        <Orders<T>>::remove(id);
+        T::Currency::unreserve(&order.buyer, order.budget);
+
+        Self::deposit_event(RawEvent::OrderClosed(id, order.budget));
+    }
+
+    /// Move the amount of the delivery from the reserve of the buyer to the seller.
+    fn pay(id: DeliveryId, delivery: &DeliveryOf<T>) -> DispatchResult {
+        T::Currency::repatriate_reserved(
+            &delivery.buyer,
+            &delivery.seller,
+            delivery.amount,
+            BalanceStatus::Free,
+        )?;
+        <Deliveries<T>>::remove(id);
+
+        Self::deposit_event(RawEvent::DeliveryPaid(
+            id,
+            delivery.seller.clone(),
+            delivery.amount,
+        ));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        let mut owed =
+            sp_std::collections::btree_map::BTreeMap::<T::AccountId, BalanceOf<T>>::new();
+        for (id, order) in <Orders<T>>::iter() {
+            ensure!(
+                id < Self::order_count(),
+                "order stored past the order count"
+            );
+            let total = owed.entry(order.buyer).or_insert_with(Zero::zero);
+            *total = total.saturating_add(order.budget);
+        }
+        for (id, delivery) in <Deliveries<T>>::iter() {
+            ensure!(
+                id < Self::delivery_count(),
+                "delivery stored past the delivery count"
+            );
+            let total = owed.entry(delivery.buyer).or_insert_with(Zero::zero);
+            *total = total.saturating_add(delivery.amount);
+        }
+        for (buyer, total) in owed {
+            ensure!(
+                T::Currency::reserved_balance(&buyer) >= total,
+                "buyer reserves less than its budgets and pending deliveries"
+            );
+        }
+
+        Ok(())
+    }
+}
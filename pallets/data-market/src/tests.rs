@@ -0,0 +1,381 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, parameter_types,
+};
+use frame_system::EnsureSignedBy;
+use sp_core::H256;
+use sp_runtime::DispatchError::BadOrigin;
+use std::cell::RefCell;
+use test_utils::{impl_mock_balances, impl_mock_system};
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), pallet_balances::AccountData<u64>);
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+impl_mock_balances!(Test, ExistentialDeposit);
+
+thread_local! {
+    static ORACLES: RefCell<Vec<u64>> = RefCell::new(vec![ORACLE]);
+}
+
+pub struct Oracles;
+impl Contains<u64> for Oracles {
+    fn sorted_members() -> Vec<u64> {
+        ORACLES.with(|v| v.borrow().clone())
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add(who: &u64) {
+        ORACLES.with(|v| {
+            let mut oracles = v.borrow_mut();
+            if let Err(position) = oracles.binary_search(who) {
+                oracles.insert(position, *who);
+            }
+        });
+    }
+}
+
+/// Only `DEVICE` is registered, it pays out to `DEVICE_OWNER`.
+pub struct Devices;
+impl DeviceResolver<u64, u64> for Devices {
+    fn payout_account(device: &u64) -> Option<u64> {
+        if *device == DEVICE {
+            Some(DEVICE_OWNER)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_device(_device: &u64, _owner: &u64) {}
+}
+
+ord_parameter_types! {
+    pub const Arbiter: u64 = 1;
+}
+parameter_types! {
+    pub const AcceptancePeriod: u64 = 10;
+    pub const MaxDataTypeLength: u32 = 8;
+}
+impl Trait for Test {
+    type Event = ();
+    type Currency = pallet_balances::Module<Self>;
+    type DeviceId = u64;
+    type Devices = Devices;
+    type Oracles = Oracles;
+    type AcceptancePeriod = AcceptancePeriod;
+    type MaxDataTypeLength = MaxDataTypeLength;
+    type ArbiterOrigin = EnsureSignedBy<Arbiter, u64>;
+    type WeightInfo = ();
+}
+type DataMarket = Module<Test>;
+type Balances = pallet_balances::Module<Test>;
+type System = frame_system::Module<Test>;
+type Errors = Error<Test>;
+
+const ARBITER: u64 = 1;
+const BUYER: u64 = 2;
+const ORACLE: u64 = 3;
+const DEVICE_OWNER: u64 = 4;
+const DEVICE: u64 = 42;
+const NOBODY: u64 = 5;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(BUYER, 1_000), (ORACLE, 10), (DEVICE_OWNER, 10)],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+    storage.into()
+}
+
+fn data(byte: u8) -> H256 {
+    H256::repeat_byte(byte)
+}
+
+/// Post an order of 10 per unit for a budget of 100.
+fn post_order() {
+    assert_ok!(DataMarket::post_order(
+        Origin::signed(BUYER),
+        b"noise".to_vec(),
+        10,
+        100
+    ));
+}
+
+#[test]
+fn buyers_post_orders() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            DataMarket::post_order(Origin::signed(BUYER), b"noise".to_vec(), 0, 100),
+            Errors::ZeroAmount
+        );
+        assert_noop!(
+            DataMarket::post_order(Origin::signed(BUYER), b"noise".to_vec(), 10, 0),
+            Errors::ZeroAmount
+        );
+        assert_noop!(
+            DataMarket::post_order(Origin::signed(BUYER), b"too long!".to_vec(), 10, 100),
+            Errors::DataTypeTooLong
+        );
+        assert_noop!(
+            DataMarket::post_order(Origin::signed(BUYER), b"noise".to_vec(), 10, 1_001),
+            Errors::InsufficientBudget
+        );
+
+        post_order();
+        assert_eq!(
+            DataMarket::order(0),
+            Some(Order {
+                buyer: BUYER,
+                data_type: b"noise".to_vec(),
+                price: 10,
+                budget: 100,
+            })
+        );
+        assert_eq!(DataMarket::order_count(), 1);
+        assert_eq!(Balances::reserved_balance(BUYER), 100);
+    });
+}
+
+#[test]
+fn buyers_cancel_their_orders() {
+    new_test_ext().execute_with(|| {
+        post_order();
+        assert_ok!(DataMarket::fill(
+            Origin::signed(ORACLE),
+            0,
+            None,
+            3,
+            data(1)
+        ));
+
+        assert_noop!(
+            DataMarket::cancel_order(Origin::signed(NOBODY), 0),
+            Errors::NotBuyer
+        );
+        assert_ok!(DataMarket::cancel_order(Origin::signed(BUYER), 0));
+        assert_eq!(DataMarket::order(0), None);
+
+        // The delivery made before the cancellation is still reserved for
+        assert_eq!(Balances::reserved_balance(BUYER), 30);
+        assert_noop!(
+            DataMarket::cancel_order(Origin::signed(BUYER), 0),
+            Errors::UnknownOrder
+        );
+    });
+}
+
+#[test]
+fn oracles_and_devices_fill_orders() {
+    new_test_ext().execute_with(|| {
+        post_order();
+        System::set_block_number(5);
+
+        assert_noop!(
+            DataMarket::fill(Origin::signed(NOBODY), 0, None, 1, data(1)),
+            Errors::NotSeller
+        );
+        assert_noop!(
+            DataMarket::fill(Origin::signed(NOBODY), 0, Some(DEVICE), 1, data(1)),
+            Errors::NotSeller
+        );
+        assert_noop!(
+            DataMarket::fill(Origin::signed(ORACLE), 0, None, 0, data(1)),
+            Errors::ZeroAmount
+        );
+        assert_noop!(
+            DataMarket::fill(Origin::signed(ORACLE), 1, None, 1, data(1)),
+            Errors::UnknownOrder
+        );
+        assert_noop!(
+            DataMarket::fill(Origin::signed(ORACLE), 0, None, 11, data(1)),
+            Errors::ExceedsBudget
+        );
+
+        assert_ok!(DataMarket::fill(
+            Origin::signed(ORACLE),
+            0,
+            None,
+            2,
+            data(1)
+        ));
+        assert_ok!(DataMarket::fill(
+            Origin::signed(DEVICE_OWNER),
+            0,
+            Some(DEVICE),
+            3,
+            data(2)
+        ));
+        assert_eq!(
+            DataMarket::delivery(1),
+            Some(Delivery {
+                order: 0,
+                buyer: BUYER,
+                seller: DEVICE_OWNER,
+                units: 3,
+                amount: 30,
+                data: data(2),
+                release: 15,
+                disputed: false,
+            })
+        );
+        assert_eq!(DataMarket::order(0).map(|order| order.budget), Some(50));
+        // Deliveries are paid out of the budget, which stays reserved until then
+        assert_eq!(DataMarket::deposits_of(&BUYER), 100);
+        assert_eq!(DataMarket::anchor(data(2)), Some(5));
+
+        // The same data can not be sold twice
+        assert_noop!(
+            DataMarket::fill(Origin::signed(ORACLE), 0, None, 1, data(2)),
+            Errors::AlreadyAnchored
+        );
+    });
+}
+
+#[test]
+fn spent_orders_are_closed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(DataMarket::post_order(
+            Origin::signed(BUYER),
+            b"noise".to_vec(),
+            10,
+            25
+        ));
+        assert_ok!(DataMarket::fill(
+            Origin::signed(ORACLE),
+            0,
+            None,
+            2,
+            data(1)
+        ));
+
+        // The remainder can not pay for another unit and is returned
+        assert_eq!(DataMarket::order(0), None);
+        assert_eq!(Balances::reserved_balance(BUYER), 20);
+    });
+}
+
+#[test]
+fn undisputed_deliveries_are_paid_after_acceptance() {
+    new_test_ext().execute_with(|| {
+        post_order();
+        assert_ok!(DataMarket::fill(
+            Origin::signed(ORACLE),
+            0,
+            None,
+            3,
+            data(1)
+        ));
+
+        System::set_block_number(9);
+        assert_noop!(
+            DataMarket::claim(Origin::signed(NOBODY), 0),
+            Errors::AcceptancePending
+        );
+
+        System::set_block_number(10);
+        assert_noop!(
+            DataMarket::dispute(Origin::signed(BUYER), 0),
+            Errors::AcceptanceOver
+        );
+        assert_ok!(DataMarket::claim(Origin::signed(NOBODY), 0));
+        assert_eq!(Balances::free_balance(ORACLE), 40);
+        assert_eq!(Balances::reserved_balance(BUYER), 70);
+        assert_eq!(Balances::free_balance(BUYER), 900);
+        assert_eq!(DataMarket::delivery(0), None);
+
+        assert_noop!(
+            DataMarket::claim(Origin::signed(NOBODY), 0),
+            Errors::UnknownDelivery
+        );
+    });
+}
+
+#[test]
+fn disputed_deliveries_are_settled_by_the_arbiters() {
+    new_test_ext().execute_with(|| {
+        post_order();
+        assert_ok!(DataMarket::fill(
+            Origin::signed(ORACLE),
+            0,
+            None,
+            3,
+            data(1)
+        ));
+        assert_ok!(DataMarket::fill(
+            Origin::signed(ORACLE),
+            0,
+            None,
+            2,
+            data(2)
+        ));
+
+        assert_noop!(
+            DataMarket::dispute(Origin::signed(NOBODY), 0),
+            Errors::NotBuyer
+        );
+        assert_noop!(
+            DataMarket::resolve(Origin::signed(ARBITER), 0, true),
+            Errors::NotDisputed
+        );
+        assert_ok!(DataMarket::dispute(Origin::signed(BUYER), 0));
+        assert_ok!(DataMarket::dispute(Origin::signed(BUYER), 1));
+        assert_noop!(
+            DataMarket::dispute(Origin::signed(BUYER), 0),
+            Errors::AlreadyDisputed
+        );
+
+        // Disputed deliveries wait for the arbiters
+        System::set_block_number(20);
+        assert_noop!(
+            DataMarket::claim(Origin::signed(NOBODY), 0),
+            Errors::AlreadyDisputed
+        );
+        assert_noop!(
+            DataMarket::resolve(Origin::signed(BUYER), 0, false),
+            BadOrigin
+        );
+
+        assert_ok!(DataMarket::resolve(Origin::signed(ARBITER), 0, false));
+        assert_eq!(Balances::free_balance(BUYER), 930);
+        assert_ok!(DataMarket::resolve(Origin::signed(ARBITER), 1, true));
+        assert_eq!(Balances::free_balance(ORACLE), 30);
+        assert_eq!(Balances::reserved_balance(BUYER), 50);
+    });
+}
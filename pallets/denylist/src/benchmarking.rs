@@ -0,0 +1,72 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Denylist pallet benchmarks
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::UnfilteredDispatchable;
+use sp_std::prelude::*;
+
+const MAX_DENIED: u32 = 1_000;
+const SEED: u32 = 0;
+
+/// Fill the list with `count` accounts.
+fn fill<T: Trait>(count: u32) {
+    for i in 0..count {
+        <Module<T> as Contains<T::AccountId>>::add(&account("denied", i, SEED));
+    }
+}
+
+benchmarks! {
+    _ { }
+
+    deny {
+        let d in 0 .. MAX_DENIED;
+
+        fill::<T>(d);
+        let call = Call::<T>::deny(account("target", 0, SEED));
+        let origin = T::ManagerOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+
+    allow {
+        let d in 1 .. MAX_DENIED;
+
+        fill::<T>(d);
+        let call = Call::<T>::allow(account("denied", 0, SEED));
+        let origin = T::ManagerOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_deny::<Test>());
+            assert_ok!(test_benchmark_allow::<Test>());
+        });
+    }
+}
@@ -0,0 +1,145 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Accounts that must not receive payouts, for instance because of sanctions that
+//! partners operating in regulated markets have to comply with. The list is kept by
+//! the registrars and exposed to other pallets as a `Contains` implementation.
+
+mod benchmarking;
+pub mod weights;
+
+#[cfg(test)]
+mod tests;
+
+pub use weights::WeightInfo;
+
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage,
+    dispatch::DispatchResult,
+    traits::{Contains, EnsureOrigin},
+    weights::DispatchClass,
+};
+use frame_system::ensure_root;
+use sp_std::prelude::Vec;
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Origin allowed to update the list, typically the registrars
+    type ManagerOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Denylist {
+        /// Denied accounts, sorted so that lookups can be binary searches
+        Denied get(fn denied): Vec<T::AccountId>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+    {
+        /// An account was added to the list
+        AccountDenied(AccountId),
+        /// An account was removed from the list
+        AccountAllowed(AccountId),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// The account is already on the list
+        AlreadyDenied,
+        /// The account is not on the list
+        NotDenied,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
+
+        /// Bar `who` from receiving payouts
+        #[weight = (T::WeightInfo::deny(), DispatchClass::Operational)]
+        pub fn deny(origin, who: T::AccountId) -> DispatchResult {
+            T::ManagerOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+
+            let mut denied = Self::denied();
+            let position = denied.binary_search(&who).err().ok_or(Error::<T>::AlreadyDenied)?;
+            denied.insert(position, who.clone());
+            <Denied<T>>::put(denied);
+
+            Self::deposit_event(RawEvent::AccountDenied(who));
+            Ok(())
+        }
+
+        /// Let `who` receive payouts again
+        #[weight = (T::WeightInfo::allow(), DispatchClass::Operational)]
+        pub fn allow(origin, who: T::AccountId) -> DispatchResult {
+            T::ManagerOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+
+            let mut denied = Self::denied();
+            let position = denied.binary_search(&who).map_err(|_| Error::<T>::NotDenied)?;
+            denied.remove(position);
+            <Denied<T>>::put(denied);
+
+            Self::deposit_event(RawEvent::AccountAllowed(who));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Contains<T::AccountId> for Module<T> {
+    fn sorted_members() -> Vec<T::AccountId> {
+        Self::denied()
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add(who: &T::AccountId) {
+        let mut denied = Self::denied();
+        if let Err(position) = denied.binary_search(who) {
+            denied.insert(position, who.clone());
+            <Denied<T>>::put(denied);
+        }
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        frame_support::ensure!(
+            Self::denied().windows(2).all(|pair| pair[0] < pair[1]),
+            "denied accounts are not sorted or contain duplicates"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, traits::Get};
+use frame_system::{EnsureSignedBy, RawOrigin};
+use sp_runtime::DispatchError::BadOrigin;
+use test_utils::impl_mock_system;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), ());
+
+ord_parameter_types! {
+    pub const Registrar: u64 = 1;
+}
+impl Trait for Test {
+    type Event = ();
+    type ManagerOrigin = EnsureSignedBy<Registrar, u64>;
+    type WeightInfo = ();
+}
+type Denylist = Module<Test>;
+type Errors = Error<Test>;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
+
+#[test]
+fn registrars_and_root_manage_the_list() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Denylist::deny(Origin::signed(Registrar::get()), 7));
+        assert_ok!(Denylist::deny(RawOrigin::Root.into(), 3));
+        assert_noop!(Denylist::deny(Origin::signed(7), 5), BadOrigin);
+
+        assert_eq!(Denylist::denied(), vec![3, 7]);
+        assert!(Denylist::contains(&7));
+        assert!(!Denylist::contains(&5));
+
+        assert_ok!(Denylist::allow(Origin::signed(Registrar::get()), 7));
+        assert!(!Denylist::contains(&7));
+    })
+}
+
+#[test]
+fn accounts_are_listed_once() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Denylist::deny(Origin::signed(Registrar::get()), 7));
+        assert_noop!(
+            Denylist::deny(Origin::signed(Registrar::get()), 7),
+            Errors::AlreadyDenied
+        );
+        assert_noop!(
+            Denylist::allow(Origin::signed(Registrar::get()), 3),
+            Errors::NotDenied
+        );
+    })
+}
@@ -0,0 +1,74 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Device sessions pallet benchmarks
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks};
+use sp_std::prelude::*;
+
+const SEED: u32 = 0;
+
+/// Register a session key for `owner`, valid for as long as possible.
+fn session<T: Trait>(owner: &T::AccountId) -> T::AccountId {
+    let key: T::AccountId = account("key", 0, SEED);
+    let expiry =
+        <frame_system::Module<T>>::block_number().saturating_add(T::MaxSessionDuration::get());
+    <Sessions<T>>::insert(&key, (owner.clone(), expiry));
+    key
+}
+
+benchmarks! {
+    _ { }
+
+    register_session_key {
+        let owner: T::AccountId = account("owner", 0, SEED);
+        let key: T::AccountId = account("key", 0, SEED);
+    }: _(RawOrigin::Signed(owner), key, T::MaxSessionDuration::get())
+
+    revoke_session_key {
+        let owner: T::AccountId = account("owner", 0, SEED);
+        let key = session::<T>(&owner);
+    }: _(RawOrigin::Signed(owner), key)
+
+    // Dispatches the smallest call there is, its own weight is added at runtime
+    act {
+        let owner: T::AccountId = account("owner", 0, SEED);
+        let key = session::<T>(&owner);
+        let call: <T as Trait>::SessionCall = frame_system::Call::<T>::remark(vec![]).into();
+    }: _(RawOrigin::Signed(key), Box::new(call))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_register_session_key::<Test>());
+            assert_ok!(test_benchmark_revoke_session_key::<Test>());
+            assert_ok!(test_benchmark_act::<Test>());
+        });
+    }
+}
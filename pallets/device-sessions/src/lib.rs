@@ -0,0 +1,271 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Let device owners hand short lived session keys to their devices, so that owner
+//! keys never have to be stored on hardware that can be tampered with. A session key
+//! can only submit the calls selected by `SessionCalls`, typically heartbeats and
+//! attestations, through `act` which dispatches them on behalf of the owner.
+//!
+//! The `CheckSessionKey` signed extension validates the delegation when transactions
+//! enter the pool, calls from unknown or expired session keys are thus never included.
+
+mod benchmarking;
+pub mod weights;
+
+#[cfg(test)]
+mod tests;
+
+pub use weights::WeightInfo;
+
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage,
+    dispatch::PostDispatchInfo,
+    ensure,
+    traits::{Filter, Get, IsSubType},
+    weights::GetDispatchInfo,
+    Parameter,
+};
+use frame_system::{ensure_signed, RawOrigin};
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{
+        DispatchInfoOf, Dispatchable, SaturatedConversion, Saturating, SignedExtension, Zero,
+    },
+    transaction_validity::{
+        InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+    },
+    DispatchResult,
+};
+use sp_std::{fmt::Debug, marker::PhantomData, prelude::Box};
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Calls session keys may submit on behalf of their owner
+    type SessionCall: Parameter
+        + Dispatchable<Origin = Self::Origin, PostInfo = PostDispatchInfo>
+        + GetDispatchInfo
+        + From<frame_system::Call<Self>>;
+    /// Selects the calls session keys may submit, typically heartbeats and attestations
+    type SessionCalls: Filter<<Self as Trait>::SessionCall>;
+    /// Session keys can not be registered for longer than this many blocks
+    type MaxSessionDuration: Get<Self::BlockNumber>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as DeviceSessions {
+        /// Owner of every session key and the block the key expires at
+        Sessions get(fn session): map hasher(blake2_128_concat) T::AccountId => Option<(T::AccountId, T::BlockNumber)>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        BlockNumber = <T as frame_system::Trait>::BlockNumber,
+    {
+        /// The owner registered a session key, valid until the given block
+        SessionKeyRegistered(AccountId, AccountId, BlockNumber),
+        /// The session key of the owner was revoked
+        SessionKeyRevoked(AccountId, AccountId),
+        /// A session key submitted a call on behalf of its owner, with the given result
+        SessionCallDispatched(AccountId, DispatchResult),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// The account is not a registered session key
+        UnknownSessionKey,
+        /// The session key is past its expiry block
+        SessionExpired,
+        /// Session keys may not submit this call
+        CallNotAllowed,
+        /// Sessions can not be empty or last longer than `MaxSessionDuration`
+        InvalidDuration,
+        /// The key is the live session key of another owner
+        KeyInUse,
+        /// Only the owner can revoke a session key before it expires
+        NotOwner,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const MaxSessionDuration: T::BlockNumber = T::MaxSessionDuration::get();
+
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Authorize `key` to submit the calls selected by `SessionCalls` on behalf of
+        /// the caller for the next `duration` blocks. Registering a key again renews it
+        #[weight = T::WeightInfo::register_session_key()]
+        pub fn register_session_key(origin, key: T::AccountId, duration: T::BlockNumber) -> DispatchResult {
+            let owner = ensure_signed(origin)?;
+            ensure!(
+                !duration.is_zero() && duration <= T::MaxSessionDuration::get(),
+                Error::<T>::InvalidDuration
+            );
+            let now = <frame_system::Module<T>>::block_number();
+            if let Some((current, expiry)) = Self::session(&key) {
+                ensure!(current == owner || now >= expiry, Error::<T>::KeyInUse);
+            }
+
+            let expiry = now.saturating_add(duration);
+            <Sessions<T>>::insert(&key, (owner.clone(), expiry));
+            Self::deposit_event(RawEvent::SessionKeyRegistered(owner, key, expiry));
+            Ok(())
+        }
+
+        /// Revoke `key`, for instance when a device is lost. Anyone can remove a key
+        /// once it expired
+        #[weight = T::WeightInfo::revoke_session_key()]
+        pub fn revoke_session_key(origin, key: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let (owner, expiry) = Self::session(&key).ok_or(Error::<T>::UnknownSessionKey)?;
+            ensure!(
+                who == owner || <frame_system::Module<T>>::block_number() >= expiry,
+                Error::<T>::NotOwner
+            );
+
+            <Sessions<T>>::remove(&key);
+            Self::deposit_event(RawEvent::SessionKeyRevoked(owner, key));
+            Ok(())
+        }
+
+        /// Dispatch `call` on behalf of the owner of the calling session key
+        #[weight = {
+            let info = call.get_dispatch_info();
+            (T::WeightInfo::act().saturating_add(info.weight), info.class)
+        }]
+        pub fn act(origin, call: Box<<T as Trait>::SessionCall>) -> DispatchResult {
+            let key = ensure_signed(origin)?;
+            let (owner, _) = Self::live_session(&key, &call)?;
+
+            let result = call.dispatch(RawOrigin::Signed(owner.clone()).into());
+            Self::deposit_event(RawEvent::SessionCallDispatched(
+                owner,
+                result.map(|_| ()).map_err(|e| e.error),
+            ));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Owner and expiry block of `key` if it can submit `call` right now.
+    pub fn live_session(
+        key: &T::AccountId,
+        call: &<T as Trait>::SessionCall,
+    ) -> Result<(T::AccountId, T::BlockNumber), Error<T>> {
+        let (owner, expiry) = Self::session(key).ok_or(Error::<T>::UnknownSessionKey)?;
+        ensure!(
+            <frame_system::Module<T>>::block_number() < expiry,
+            Error::<T>::SessionExpired
+        );
+        ensure!(T::SessionCalls::filter(call), Error::<T>::CallNotAllowed);
+        Ok((owner, expiry))
+    }
+}
+
+/// Reject the calls session keys submit through `act` unless the key is live and the
+/// call is allowed. Valid transactions leave the pool when the key expires.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckSessionKey<T: Trait + Send + Sync>(PhantomData<T>);
+
+impl<T: Trait + Send + Sync> CheckSessionKey<T> {
+    /// Create a new instance of the extension.
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Trait + Send + Sync> Default for CheckSessionKey<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Trait + Send + Sync> Debug for CheckSessionKey<T> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "CheckSessionKey")
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for CheckSessionKey<T>
+where
+    <T as frame_system::Trait>::Call: IsSubType<Call<T>>,
+{
+    const IDENTIFIER: &'static str = "CheckSessionKey";
+    type AccountId = T::AccountId;
+    type Call = <T as frame_system::Trait>::Call;
+    type AdditionalSigned = ();
+    type Pre = ();
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        _info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> TransactionValidity {
+        let inner = match call.is_sub_type() {
+            Some(Call::act(inner)) => inner,
+            _ => return Ok(ValidTransaction::default()),
+        };
+
+        let (_, expiry) = Module::<T>::live_session(who, inner).map_err(|e| match e {
+            Error::<T>::SessionExpired => InvalidTransaction::Stale,
+            Error::<T>::CallNotAllowed => InvalidTransaction::Call,
+            _ => InvalidTransaction::BadProof,
+        })?;
+        let now = <frame_system::Module<T>>::block_number();
+        Ok(ValidTransaction {
+            longevity: expiry.saturating_sub(now).saturated_into(),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        // Sessions are independent of each other and expire on their own
+        Ok(())
+    }
+}
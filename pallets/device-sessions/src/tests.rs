@@ -0,0 +1,294 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use crate as device_sessions;
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_dispatch, impl_outer_origin, parameter_types,
+    weights::DispatchInfo,
+};
+use sp_runtime::DispatchError::BadOrigin;
+use test_utils::impl_mock_system;
+
+impl_outer_origin! {
+    pub enum Origin for Test where system = frame_system {}
+}
+impl_outer_dispatch! {
+    pub enum Call for Test where origin: Origin {
+        frame_system::System,
+        device_sessions::DeviceSessions,
+    }
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, Call, ());
+
+/// Remarks stand for heartbeats, everything else is off limits to session keys.
+pub struct Heartbeats;
+impl Filter<Call> for Heartbeats {
+    fn filter(c: &Call) -> bool {
+        matches!(c, Call::System(frame_system::Call::remark(..)))
+    }
+}
+
+parameter_types! {
+    pub const MaxSessionDuration: u64 = 10;
+}
+impl Trait for Test {
+    type Event = ();
+    type SessionCall = Call;
+    type SessionCalls = Heartbeats;
+    type MaxSessionDuration = MaxSessionDuration;
+    type WeightInfo = ();
+}
+type DeviceSessions = Module<Test>;
+type System = frame_system::Module<Test>;
+type Errors = Error<Test>;
+
+const OWNER: u64 = 1;
+const KEY: u64 = 2;
+const OTHER: u64 = 3;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
+
+fn heartbeat() -> Box<Call> {
+    Box::new(Call::System(frame_system::Call::remark(vec![1])))
+}
+
+fn validate(who: u64, call: Call) -> TransactionValidity {
+    CheckSessionKey::<Test>::new().validate(&who, &call, &DispatchInfo::default(), 0)
+}
+
+#[test]
+fn owners_register_session_keys() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(3);
+        assert_ok!(DeviceSessions::register_session_key(
+            Origin::signed(OWNER),
+            KEY,
+            5
+        ));
+        assert_eq!(DeviceSessions::session(KEY), Some((OWNER, 8)));
+
+        // Owners renew their keys by registering them again
+        assert_ok!(DeviceSessions::register_session_key(
+            Origin::signed(OWNER),
+            KEY,
+            10
+        ));
+        assert_eq!(DeviceSessions::session(KEY), Some((OWNER, 13)));
+    })
+}
+
+#[test]
+fn sessions_are_bounded() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            DeviceSessions::register_session_key(Origin::signed(OWNER), KEY, 0),
+            Errors::InvalidDuration
+        );
+        assert_noop!(
+            DeviceSessions::register_session_key(
+                Origin::signed(OWNER),
+                KEY,
+                MaxSessionDuration::get() + 1
+            ),
+            Errors::InvalidDuration
+        );
+    })
+}
+
+#[test]
+fn live_keys_can_not_be_taken_over() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(DeviceSessions::register_session_key(
+            Origin::signed(OWNER),
+            KEY,
+            5
+        ));
+        assert_noop!(
+            DeviceSessions::register_session_key(Origin::signed(OTHER), KEY, 5),
+            Errors::KeyInUse
+        );
+
+        System::set_block_number(5);
+        assert_ok!(DeviceSessions::register_session_key(
+            Origin::signed(OTHER),
+            KEY,
+            5
+        ));
+        assert_eq!(DeviceSessions::session(KEY), Some((OTHER, 10)));
+    })
+}
+
+#[test]
+fn session_keys_act_on_behalf_of_their_owner() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(DeviceSessions::register_session_key(
+            Origin::signed(OWNER),
+            KEY,
+            5
+        ));
+
+        assert_ok!(DeviceSessions::act(Origin::signed(KEY), heartbeat()));
+        assert_noop!(
+            DeviceSessions::act(Origin::signed(OTHER), heartbeat()),
+            Errors::UnknownSessionKey
+        );
+    })
+}
+
+#[test]
+fn session_keys_only_submit_allowed_calls() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(DeviceSessions::register_session_key(
+            Origin::signed(OWNER),
+            KEY,
+            5
+        ));
+
+        // A session key must not be able to hand its rights to another key
+        let register = Box::new(Call::DeviceSessions(
+            device_sessions::Call::register_session_key(OTHER, 5),
+        ));
+        assert_noop!(
+            DeviceSessions::act(Origin::signed(KEY), register),
+            Errors::CallNotAllowed
+        );
+    })
+}
+
+#[test]
+fn expired_session_keys_can_not_act() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(DeviceSessions::register_session_key(
+            Origin::signed(OWNER),
+            KEY,
+            5
+        ));
+
+        System::set_block_number(5);
+        assert_noop!(
+            DeviceSessions::act(Origin::signed(KEY), heartbeat()),
+            Errors::SessionExpired
+        );
+    })
+}
+
+#[test]
+fn only_owners_revoke_live_keys() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(DeviceSessions::register_session_key(
+            Origin::signed(OWNER),
+            KEY,
+            5
+        ));
+        assert_noop!(
+            DeviceSessions::revoke_session_key(Origin::signed(OTHER), KEY),
+            Errors::NotOwner
+        );
+        assert_noop!(
+            DeviceSessions::revoke_session_key(Origin::none(), KEY),
+            BadOrigin
+        );
+
+        assert_ok!(DeviceSessions::revoke_session_key(
+            Origin::signed(OWNER),
+            KEY
+        ));
+        assert_eq!(DeviceSessions::session(KEY), None);
+        assert_noop!(
+            DeviceSessions::act(Origin::signed(KEY), heartbeat()),
+            Errors::UnknownSessionKey
+        );
+    })
+}
+
+#[test]
+fn anyone_removes_expired_keys() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(DeviceSessions::register_session_key(
+            Origin::signed(OWNER),
+            KEY,
+            5
+        ));
+
+        System::set_block_number(5);
+        assert_ok!(DeviceSessions::revoke_session_key(
+            Origin::signed(OTHER),
+            KEY
+        ));
+        assert_eq!(DeviceSessions::session(KEY), None);
+    })
+}
+
+#[test]
+fn extension_validates_the_delegation() {
+    new_test_ext().execute_with(|| {
+        let act = |call| Call::DeviceSessions(device_sessions::Call::act(call));
+        assert_eq!(
+            validate(KEY, act(heartbeat())),
+            Err(InvalidTransaction::BadProof.into())
+        );
+
+        assert_ok!(DeviceSessions::register_session_key(
+            Origin::signed(OWNER),
+            KEY,
+            5
+        ));
+        System::set_block_number(2);
+        assert_eq!(validate(KEY, act(heartbeat())).map(|v| v.longevity), Ok(3));
+        let revoke = Box::new(Call::DeviceSessions(
+            device_sessions::Call::revoke_session_key(KEY),
+        ));
+        assert_eq!(
+            validate(KEY, act(revoke)),
+            Err(InvalidTransaction::Call.into())
+        );
+
+        System::set_block_number(5);
+        assert_eq!(
+            validate(KEY, act(heartbeat())),
+            Err(InvalidTransaction::Stale.into())
+        );
+    })
+}
+
+#[test]
+fn extension_ignores_other_calls() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            validate(OTHER, *heartbeat()),
+            Ok(ValidTransaction::default())
+        );
+    })
+}
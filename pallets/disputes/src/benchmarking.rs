@@ -0,0 +1,93 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Disputes pallet benchmarks
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::UnfilteredDispatchable;
+use frame_system::RawOrigin;
+use sp_std::prelude::*;
+
+const SEED: u32 = 0;
+
+/// Fund a challenger and record a batch of era zero to dispute.
+fn challenger<T: Trait>() -> (T::AccountId, T::Hash) {
+    let challenger: T::AccountId = account("challenger", 0, SEED);
+    let funds = T::DisputeBond::get().saturating_mul(10u32.into());
+    T::Currency::make_free_balance_be(&challenger, funds);
+    let root = T::Hash::default();
+    T::Batches::add_batch(0, &root);
+    (challenger, root)
+}
+
+/// Open a dispute, returns its identifier.
+fn dispute<T: Trait>() -> DisputeId {
+    let (challenger, root) = challenger::<T>();
+    let id = Module::<T>::dispute_count();
+    Module::<T>::open(
+        RawOrigin::Signed(challenger).into(),
+        0,
+        root,
+        0.into(),
+        100.into(),
+    )
+    .expect("the challenger can afford the bond");
+    id
+}
+
+benchmarks! {
+    _ { }
+
+    open {
+        let (challenger, root) = challenger::<T>();
+    }: _(RawOrigin::Signed(challenger), 0, root, 0.into(), 100.into())
+
+    // Corrective allocations go through the allocations budget, which benchmarks can
+    // not rely on, their cost is part of the estimate
+    uphold {
+        let id = dispute::<T>();
+        let call = Call::<T>::uphold(id, None);
+        let origin = T::ArbiterOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+
+    reject {
+        let id = dispute::<T>();
+        let call = Call::<T>::reject(id);
+        let origin = T::ArbiterOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_open::<Test>());
+            assert_ok!(test_benchmark_uphold::<Test>());
+            assert_ok!(test_benchmark_reject::<Test>());
+        });
+    }
+}
@@ -0,0 +1,240 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Let contributors challenge the batches of allocations submitted by the oracles
+//! when they were left out or allocated an incorrect amount. Challengers post a bond
+//! which is returned if the arbiters uphold the dispute, possibly along with a
+//! corrective allocation, and slashed if they reject it.
+//!
+//! Batches are only known until their payout era is settled, disputes must thus be
+//! opened before then. They can be ruled on at any time.
+
+mod benchmarking;
+pub mod weights;
+
+#[cfg(test)]
+mod tests;
+
+pub use weights::WeightInfo;
+
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{Currency, EnsureOrigin, Get, Imbalance, OnUnbalanced, ReservableCurrency},
+    IterableStorageMap,
+};
+use frame_system::ensure_signed;
+use nodle_support::AllocationBatches;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{Saturating, Zero},
+    DispatchResult, RuntimeDebug,
+};
+
+type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+pub type DisputeOf<T> =
+    Dispute<<T as frame_system::Trait>::AccountId, BalanceOf<T>, <T as frame_system::Trait>::Hash>;
+
+/// Disputes are numbered in the order they are opened.
+pub type DisputeId = u32;
+
+/// Challenge of the allocation made to the challenger in a batch.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Dispute<AccountId, Balance, Hash> {
+    /// Account which opened the dispute and posted the bond
+    pub challenger: AccountId,
+    /// Payout era of the disputed batch
+    pub era: u32,
+    /// Merkle root of the disputed batch
+    pub root: Hash,
+    /// Amount the batch allocated to the challenger, zero if it was left out
+    pub allocated: Balance,
+    /// Amount the challenger claims it should have been allocated
+    pub expected: Balance,
+    /// Bond reserved from the challenger
+    pub bond: Balance,
+}
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// Batches disputes are opened against, corrective allocations are made through it
+    type Batches: AllocationBatches<Self::AccountId, BalanceOf<Self>, Self::Hash>;
+    /// Bond reserved when opening a dispute
+    type DisputeBond: Get<BalanceOf<Self>>;
+    /// Origin ruling on the disputes, it should not be controlled by the oracles
+    type ArbiterOrigin: EnsureOrigin<Self::Origin>;
+    /// Receives the bonds of rejected disputes
+    type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Disputes {
+        /// Disputes waiting for a ruling
+        Disputes get(fn dispute): map hasher(twox_64_concat) DisputeId => Option<DisputeOf<T>>;
+        /// Number of disputes ever opened, also the identifier of the next one
+        DisputeCount get(fn dispute_count): DisputeId;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        Balance = BalanceOf<T>,
+        Hash = <T as frame_system::Trait>::Hash,
+    {
+        /// The account challenged the batch of the given era and root
+        DisputeOpened(DisputeId, AccountId, u32, Hash),
+        /// The dispute was upheld and the bond returned, along with the corrective
+        /// allocation if any
+        DisputeUpheld(DisputeId, Option<Balance>),
+        /// The dispute was rejected and the given amount slashed from the bond
+        DisputeRejected(DisputeId, Balance),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// The expected amount is not above the allocated one
+        NothingToCorrect,
+        /// No batch with this root was submitted during the era, or the era was settled
+        UnknownBatch,
+        /// The challenger can not afford the bond
+        InsufficientBond,
+        /// No dispute is waiting for a ruling under this identifier
+        UnknownDispute,
+        /// The correction is larger than the difference the dispute is about
+        CorrectionTooLarge,
+        /// No more disputes can be opened
+        TooManyDisputes,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const DisputeBond: BalanceOf<T> = T::DisputeBond::get();
+
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Challenge the batch of `era` committed to by `root`, which allocated the
+        /// caller `allocated` instead of `expected`. `DisputeBond` is reserved until
+        /// the dispute is ruled on
+        #[weight = T::WeightInfo::open()]
+        pub fn open(origin, era: u32, root: T::Hash, allocated: BalanceOf<T>, expected: BalanceOf<T>) -> DispatchResult {
+            let challenger = ensure_signed(origin)?;
+            ensure!(expected > allocated, Error::<T>::NothingToCorrect);
+            ensure!(T::Batches::is_batch(era, &root), Error::<T>::UnknownBatch);
+            let id = Self::dispute_count();
+            let next = id.checked_add(1).ok_or(Error::<T>::TooManyDisputes)?;
+
+            let bond = T::DisputeBond::get();
+            T::Currency::reserve(&challenger, bond).map_err(|_| Error::<T>::InsufficientBond)?;
+            <Disputes<T>>::insert(id, Dispute {
+                challenger: challenger.clone(),
+                era,
+                root,
+                allocated,
+                expected,
+                bond,
+            });
+            DisputeCount::put(next);
+
+            Self::deposit_event(RawEvent::DisputeOpened(id, challenger, era, root));
+            Ok(())
+        }
+
+        /// Rule in favor of the challenger, its bond is returned. If `correction` is
+        /// set that much is allocated to the challenger to make up for the mistake
+        #[weight = T::WeightInfo::uphold()]
+        pub fn uphold(origin, id: DisputeId, correction: Option<BalanceOf<T>>) -> DispatchResult {
+            T::ArbiterOrigin::ensure_origin(origin)?;
+            let dispute = Self::dispute(id).ok_or(Error::<T>::UnknownDispute)?;
+
+            if let Some(amount) = correction {
+                ensure!(
+                    amount <= dispute.expected.saturating_sub(dispute.allocated),
+                    Error::<T>::CorrectionTooLarge
+                );
+                T::Batches::correct(&dispute.challenger, amount)?;
+            }
+            <Disputes<T>>::remove(id);
+            T::Currency::unreserve(&dispute.challenger, dispute.bond);
+
+            Self::deposit_event(RawEvent::DisputeUpheld(id, correction));
+            Ok(())
+        }
+
+        /// Rule against the challenger, its bond is slashed
+        #[weight = T::WeightInfo::reject()]
+        pub fn reject(origin, id: DisputeId) -> DispatchResult {
+            T::ArbiterOrigin::ensure_origin(origin)?;
+            let dispute = Self::dispute(id).ok_or(Error::<T>::UnknownDispute)?;
+
+            <Disputes<T>>::remove(id);
+            let (slashed, _) = T::Currency::slash_reserved(&dispute.challenger, dispute.bond);
+            let amount = slashed.peek();
+            T::Slashed::on_unbalanced(slashed);
+
+            Self::deposit_event(RawEvent::DisputeRejected(id, amount));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Bonds reserved from `who` for the disputes it opened and which await a ruling.
+    pub fn deposits_of(who: &T::AccountId) -> BalanceOf<T> {
+        <Disputes<T>>::iter()
+            .filter(|(_, dispute)| &dispute.challenger == who)
+            .fold(Zero::zero(), |total, (_, dispute)| {
+                total.saturating_add(dispute.bond)
+            })
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        for (id, dispute) in <Disputes<T>>::iter() {
+            ensure!(
+                id < Self::dispute_count(),
+                "dispute stored past the dispute count"
+            );
+            ensure!(
+                T::Currency::reserved_balance(&dispute.challenger) >= dispute.bond,
+                "challenger reserves less than its bond"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, parameter_types,
+};
+use frame_system::EnsureSignedBy;
+use sp_core::H256;
+use sp_runtime::DispatchError::BadOrigin;
+use std::cell::RefCell;
+use test_utils::{impl_mock_balances, impl_mock_system};
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), pallet_balances::AccountData<u64>);
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+impl_mock_balances!(Test, ExistentialDeposit);
+
+thread_local! {
+    static BATCHES: RefCell<Vec<(u32, H256)>> = RefCell::new(vec![]);
+    static CORRECTIONS: RefCell<Vec<(u64, u64)>> = RefCell::new(vec![]);
+}
+
+/// Records corrections instead of allocating them, corrections above the budget of
+/// 1000 coins fail.
+pub struct Batches;
+impl AllocationBatches<u64, u64, H256> for Batches {
+    fn is_batch(era: u32, root: &H256) -> bool {
+        BATCHES.with(|b| b.borrow().contains(&(era, *root)))
+    }
+
+    fn correct(who: &u64, amount: u64) -> Result<(), &'static str> {
+        if amount > 1_000 {
+            return Err("Too many coins");
+        }
+        CORRECTIONS.with(|c| c.borrow_mut().push((*who, amount)));
+        Ok(())
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_batch(era: u32, root: &H256) {
+        BATCHES.with(|b| b.borrow_mut().push((era, *root)));
+    }
+}
+
+ord_parameter_types! {
+    pub const Arbiter: u64 = 1;
+}
+parameter_types! {
+    pub const DisputeBond: u64 = 10;
+}
+impl Trait for Test {
+    type Event = ();
+    type Currency = pallet_balances::Module<Self>;
+    type Batches = Batches;
+    type DisputeBond = DisputeBond;
+    type ArbiterOrigin = EnsureSignedBy<Arbiter, u64>;
+    type Slashed = ();
+    type WeightInfo = ();
+}
+type Disputes = Module<Test>;
+type Balances = pallet_balances::Module<Test>;
+type Errors = Error<Test>;
+
+const CHALLENGER: u64 = 2;
+const ERA: u32 = 3;
+
+fn root() -> H256 {
+    H256::repeat_byte(7)
+}
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(CHALLENGER, 15)],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    BATCHES.with(|b| *b.borrow_mut() = vec![(ERA, root())]);
+    CORRECTIONS.with(|c| c.borrow_mut().clear());
+    storage.into()
+}
+
+fn open() -> DispatchResult {
+    Disputes::open(Origin::signed(CHALLENGER), ERA, root(), 20, 50)
+}
+
+#[test]
+fn challengers_post_a_bond() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(open());
+
+        assert_eq!(Balances::reserved_balance(CHALLENGER), 10);
+        assert_eq!(Disputes::deposits_of(&CHALLENGER), 10);
+        assert_eq!(Disputes::dispute_count(), 1);
+        assert_eq!(
+            Disputes::dispute(0),
+            Some(Dispute {
+                challenger: CHALLENGER,
+                era: ERA,
+                root: root(),
+                allocated: 20,
+                expected: 50,
+                bond: 10,
+            })
+        );
+
+        assert_noop!(open(), Errors::InsufficientBond);
+    })
+}
+
+#[test]
+fn disputes_target_known_batches() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Disputes::open(Origin::signed(CHALLENGER), ERA + 1, root(), 20, 50),
+            Errors::UnknownBatch
+        );
+        assert_noop!(
+            Disputes::open(Origin::signed(CHALLENGER), ERA, root(), 50, 50),
+            Errors::NothingToCorrect
+        );
+    })
+}
+
+#[test]
+fn only_arbiters_rule() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(open());
+
+        assert_noop!(
+            Disputes::uphold(Origin::signed(CHALLENGER), 0, None),
+            BadOrigin
+        );
+        assert_noop!(Disputes::reject(Origin::signed(CHALLENGER), 0), BadOrigin);
+        assert_noop!(
+            Disputes::reject(Origin::signed(Arbiter::get()), 1),
+            Errors::UnknownDispute
+        );
+    })
+}
+
+#[test]
+fn upheld_disputes_return_the_bond() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(open());
+
+        assert_ok!(Disputes::uphold(Origin::signed(Arbiter::get()), 0, None));
+        assert_eq!(Balances::free_balance(CHALLENGER), 15);
+        assert_eq!(Disputes::dispute(0), None);
+        assert!(CORRECTIONS.with(|c| c.borrow().is_empty()));
+    })
+}
+
+#[test]
+fn upheld_disputes_can_be_corrected() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(open());
+
+        assert_noop!(
+            Disputes::uphold(Origin::signed(Arbiter::get()), 0, Some(31)),
+            Errors::CorrectionTooLarge
+        );
+        assert_ok!(Disputes::uphold(
+            Origin::signed(Arbiter::get()),
+            0,
+            Some(30)
+        ));
+        assert_eq!(
+            CORRECTIONS.with(|c| c.borrow().clone()),
+            vec![(CHALLENGER, 30)]
+        );
+        assert_eq!(Balances::free_balance(CHALLENGER), 15);
+    })
+}
+
+#[test]
+fn failed_corrections_leave_the_dispute_open() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Disputes::open(
+            Origin::signed(CHALLENGER),
+            ERA,
+            root(),
+            0,
+            5_000
+        ));
+
+        assert_noop!(
+            Disputes::uphold(Origin::signed(Arbiter::get()), 0, Some(2_000)),
+            "Too many coins"
+        );
+        assert!(Disputes::dispute(0).is_some());
+        assert_eq!(Balances::reserved_balance(CHALLENGER), 10);
+    })
+}
+
+#[test]
+fn rejected_disputes_lose_the_bond() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(open());
+
+        assert_ok!(Disputes::reject(Origin::signed(Arbiter::get()), 0));
+        assert_eq!(Balances::free_balance(CHALLENGER), 5);
+        assert_eq!(Balances::reserved_balance(CHALLENGER), 0);
+        assert_eq!(Balances::total_issuance(), 5);
+        assert_eq!(Disputes::dispute(0), None);
+    })
+}
@@ -22,12 +22,16 @@
 //! operations, or resume them
 
 mod benchmarking;
+pub mod weights;
 
 #[cfg(test)]
 mod tests;
 
+pub use weights::WeightInfo;
+
 use frame_support::{
     decl_event, decl_module, decl_storage, dispatch::DispatchResult, traits::EnsureOrigin,
+    weights::DispatchClass,
 };
 use frame_system::ensure_root;
 
@@ -35,6 +39,9 @@ use frame_system::ensure_root;
 pub trait Trait: frame_system::Trait {
     type Event: From<Event> + Into<<Self as frame_system::Trait>::Event>;
     type ShutdownOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
 }
 
 decl_storage! {
@@ -49,7 +56,7 @@ decl_module! {
         fn deposit_event() = default;
 
         /// Toggle the shutdown state if authorized to do so.
-        #[weight = 10_000_000]
+        #[weight = (T::WeightInfo::toggle(), DispatchClass::Operational)]
         pub fn toggle(origin) -> DispatchResult {
             T::ShutdownOrigin::try_origin(origin)
                 .map(|_| ())
@@ -69,3 +76,13 @@ decl_event!(
         ShutdownToggled(bool),
     }
 );
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        // A single flag, any value is valid
+        Ok(())
+    }
+}
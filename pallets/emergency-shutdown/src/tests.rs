@@ -20,18 +20,10 @@
 
 use super::*;
 
-use frame_support::{
-    assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, parameter_types,
-    weights::Weight,
-};
+use frame_support::{assert_noop, assert_ok, impl_outer_origin, ord_parameter_types};
 use frame_system::{EnsureSignedBy, RawOrigin};
-use sp_core::H256;
-use sp_runtime::{
-    testing::Header,
-    traits::{BlakeTwo256, IdentityLookup},
-    DispatchError::BadOrigin,
-    Perbill,
-};
+use sp_runtime::DispatchError::BadOrigin;
+use test_utils::impl_mock_system;
 
 impl_outer_origin! {
     pub enum Origin for Test {}
@@ -42,39 +34,7 @@ impl_outer_origin! {
 // configuration traits of modules we want to use.
 #[derive(Clone, Eq, PartialEq)]
 pub struct Test;
-parameter_types! {
-    pub const BlockHashCount: u64 = 250;
-    pub const MaximumBlockWeight: Weight = 1024;
-    pub const MaximumBlockLength: u32 = 2 * 1024;
-    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
-}
-impl frame_system::Trait for Test {
-    type Origin = Origin;
-    type Call = ();
-    type Index = u64;
-    type BlockNumber = u64;
-    type Hash = H256;
-    type Hashing = BlakeTwo256;
-    type AccountId = u64;
-    type Lookup = IdentityLookup<Self::AccountId>;
-    type Header = Header;
-    type Event = ();
-    type BlockHashCount = BlockHashCount;
-    type MaximumBlockWeight = MaximumBlockWeight;
-    type MaximumBlockLength = MaximumBlockLength;
-    type AvailableBlockRatio = AvailableBlockRatio;
-    type Version = ();
-    type PalletInfo = ();
-    type AccountData = ();
-    type OnNewAccount = ();
-    type OnKilledAccount = ();
-    type DbWeight = ();
-    type BlockExecutionWeight = ();
-    type ExtrinsicBaseWeight = ();
-    type MaximumExtrinsicWeight = MaximumBlockWeight;
-    type BaseCallFilter = ();
-    type SystemWeightInfo = ();
-}
+impl_mock_system!(Test, Origin, (), ());
 
 ord_parameter_types! {
     pub const Admin: u64 = 1;
@@ -82,6 +42,7 @@ ord_parameter_types! {
 impl Trait for Test {
     type Event = ();
     type ShutdownOrigin = EnsureSignedBy<Admin, u64>;
+    type WeightInfo = ();
 }
 type TestModule = Module<Test>;
 
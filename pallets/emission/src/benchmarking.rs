@@ -0,0 +1,55 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Emission pallet benchmarks
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::benchmarks;
+use frame_support::traits::UnfilteredDispatchable;
+use sp_std::prelude::*;
+
+benchmarks! {
+    _ { }
+
+    set_curve {
+        let u in 0 .. 1000;
+
+        let call = Call::<T>::set_curve(EmissionCurve::Decaying {
+            initial: 1_000u32.into(),
+            decay: Perbill::from_percent(1),
+        });
+        let origin = T::CurveOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_set_curve::<Test>());
+        });
+    }
+}
@@ -0,0 +1,200 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Issue new coins at the beginning of every era following a curve configured by
+//! governance. A share of the emission is paid to the validators right away, the rest
+//! raises the budget oracles can allocate from, coins for allocations are only minted
+//! once actually allocated.
+
+mod benchmarking;
+mod tests;
+pub mod weights;
+
+pub use weights::WeightInfo;
+
+use chain_primitives::RewardBudget;
+use frame_support::{
+    decl_event, decl_module, decl_storage,
+    traits::{Currency, EnsureOrigin, Get, OnUnbalanced},
+    weights::{DispatchClass, Weight},
+};
+use frame_system::ensure_root;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{Saturating, Zero},
+    DispatchResult, Perbill, RuntimeDebug,
+};
+
+type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+
+/// How much to emit every era.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum EmissionCurve<Balance> {
+    /// Emit `per_era` every era until a total of `cap` was emitted
+    Capped { per_era: Balance, cap: Balance },
+    /// Emit `initial` during the first era, the emission then decreases by `decay`
+    /// every era
+    Decaying { initial: Balance, decay: Perbill },
+}
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    type Currency: Currency<Self::AccountId>;
+    /// Origin that can update the emission curve
+    type CurveOrigin: EnsureOrigin<Self::Origin>;
+    /// Length of an era, in blocks
+    type EraDuration: Get<Self::BlockNumber>;
+    /// Share of the emission going to the validators
+    type ValidatorsShare: Get<Perbill>;
+    /// Receives the validators' share of every emission
+    type ValidatorsRewards: OnUnbalanced<NegativeImbalanceOf<Self>>;
+    /// Allocations budget before anything was emitted, lets us take over from a
+    /// previously fixed budget
+    type InitialAllocationsBudget: Get<BalanceOf<Self>>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Emission {
+        /// Curve in use, nothing is emitted until one is set
+        pub Curve get(fn curve): Option<EmissionCurve<BalanceOf<T>>>;
+        /// Number of eras elapsed since the curve was set
+        pub Era get(fn era): u32;
+        /// Total emitted by the current curve
+        pub TotalEmitted get(fn total_emitted): BalanceOf<T>;
+        /// Total amount oracles are allowed to allocate since genesis
+        pub AllocationsBudget get(fn allocations_budget) config(): BalanceOf<T> = T::InitialAllocationsBudget::get();
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        Balance = BalanceOf<T>,
+    {
+        /// The emission curve was updated
+        CurveSet(EmissionCurve<Balance>),
+        /// Coins were emitted for the given era, to validators and allocations
+        Emitted(u32, Balance, Balance),
+    }
+);
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const EraDuration: T::BlockNumber = T::EraDuration::get();
+        const ValidatorsShare: Perbill = T::ValidatorsShare::get();
+
+        fn deposit_event() = default;
+
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            let duration = T::EraDuration::get();
+            if duration.is_zero() || !(n % duration).is_zero() {
+                return 0;
+            }
+
+            match Self::curve() {
+                Some(curve) => {
+                    Self::emit(curve);
+                    T::DbWeight::get().reads_writes(5, 5)
+                }
+                None => T::DbWeight::get().reads(1),
+            }
+        }
+
+        /// Replace the emission curve, restarting it from its first era
+        #[weight = (T::WeightInfo::set_curve(), DispatchClass::Operational)]
+        pub fn set_curve(origin, curve: EmissionCurve<BalanceOf<T>>) -> DispatchResult {
+            T::CurveOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+
+            <Curve<T>>::put(curve.clone());
+            Era::kill();
+            <TotalEmitted<T>>::kill();
+
+            Self::deposit_event(RawEvent::CurveSet(curve));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Amount to emit during `era` given what was already emitted by the curve.
+    pub fn era_emission(
+        curve: &EmissionCurve<BalanceOf<T>>,
+        era: u32,
+        emitted: BalanceOf<T>,
+    ) -> BalanceOf<T> {
+        match curve {
+            EmissionCurve::Capped { per_era, cap } => (*per_era).min(cap.saturating_sub(emitted)),
+            EmissionCurve::Decaying { initial, decay } => {
+                Perbill::one()
+                    .saturating_sub(*decay)
+                    .saturating_pow(era as usize)
+                    * *initial
+            }
+        }
+    }
+
+    fn emit(curve: EmissionCurve<BalanceOf<T>>) {
+        let era = Self::era();
+        let amount = Self::era_emission(&curve, era, Self::total_emitted());
+
+        let for_validators = T::ValidatorsShare::get() * amount;
+        let for_allocations = amount.saturating_sub(for_validators);
+
+        T::ValidatorsRewards::on_unbalanced(T::Currency::issue(for_validators));
+        <AllocationsBudget<T>>::mutate(|budget| *budget = budget.saturating_add(for_allocations));
+        <TotalEmitted<T>>::mutate(|total| *total = total.saturating_add(amount));
+        Era::put(era.saturating_add(1));
+
+        Self::deposit_event(RawEvent::Emitted(era, for_validators, for_allocations));
+    }
+}
+
+/// Allocations can never exceed what was emitted for them.
+impl<T: Trait> RewardBudget<BalanceOf<T>> for Module<T> {
+    fn budget() -> BalanceOf<T> {
+        Self::allocations_budget()
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        if let Some(EmissionCurve::Capped { cap, .. }) = Self::curve() {
+            frame_support::ensure!(
+                Self::total_emitted() <= cap,
+                "more coins were emitted than the curve allows"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, parameter_types,
+    traits::OnInitialize,
+};
+use frame_system::{EnsureSignedBy, RawOrigin};
+use sp_runtime::DispatchError::BadOrigin;
+use test_utils::{impl_mock_balances, impl_mock_system};
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), pallet_balances::AccountData<u64>);
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+impl_mock_balances!(Test, ExistentialDeposit);
+
+ord_parameter_types! {
+    pub const Admin: u64 = 1;
+    pub const Validator: u64 = 2;
+}
+parameter_types! {
+    pub const EraDuration: u64 = 10;
+    pub const ValidatorsShare: Perbill = Perbill::from_percent(20);
+    pub const InitialAllocationsBudget: u64 = 1_000;
+}
+pub struct RewardValidator;
+impl OnUnbalanced<NegativeImbalanceOf<Test>> for RewardValidator {
+    fn on_nonzero_unbalanced(amount: NegativeImbalanceOf<Test>) {
+        Balances::resolve_creating(&Validator::get(), amount);
+    }
+}
+impl Trait for Test {
+    type Event = ();
+    type Currency = Balances;
+    type CurveOrigin = EnsureSignedBy<Admin, u64>;
+    type EraDuration = EraDuration;
+    type ValidatorsShare = ValidatorsShare;
+    type ValidatorsRewards = RewardValidator;
+    type InitialAllocationsBudget = InitialAllocationsBudget;
+    type WeightInfo = ();
+}
+type Emission = Module<Test>;
+type Balances = pallet_balances::Module<Test>;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
+
+fn set_curve(curve: EmissionCurve<u64>) {
+    assert_ok!(Emission::set_curve(Origin::signed(Admin::get()), curve));
+}
+
+#[test]
+fn nothing_is_emitted_without_a_curve() {
+    new_test_ext().execute_with(|| {
+        Emission::on_initialize(10);
+
+        assert_eq!(Balances::total_issuance(), 0);
+        assert_eq!(
+            Emission::allocations_budget(),
+            InitialAllocationsBudget::get()
+        );
+    })
+}
+
+#[test]
+fn emits_only_at_the_beginning_of_eras() {
+    new_test_ext().execute_with(|| {
+        set_curve(EmissionCurve::Capped {
+            per_era: 100,
+            cap: 1_000,
+        });
+
+        Emission::on_initialize(5);
+        assert_eq!(Emission::total_emitted(), 0);
+
+        Emission::on_initialize(10);
+        assert_eq!(Emission::total_emitted(), 100);
+        assert_eq!(Emission::era(), 1);
+    })
+}
+
+#[test]
+fn emission_is_split_between_validators_and_allocations() {
+    new_test_ext().execute_with(|| {
+        set_curve(EmissionCurve::Capped {
+            per_era: 100,
+            cap: 1_000,
+        });
+
+        Emission::on_initialize(10);
+
+        assert_eq!(Balances::free_balance(Validator::get()), 20);
+        assert_eq!(Balances::total_issuance(), 20);
+        assert_eq!(
+            Emission::allocations_budget(),
+            InitialAllocationsBudget::get() + 80
+        );
+    })
+}
+
+#[test]
+fn capped_curve_stops_at_cap() {
+    new_test_ext().execute_with(|| {
+        set_curve(EmissionCurve::Capped {
+            per_era: 100,
+            cap: 250,
+        });
+
+        for era in 1..5 {
+            Emission::on_initialize(era * EraDuration::get());
+        }
+
+        assert_eq!(Emission::total_emitted(), 250);
+    })
+}
+
+#[test]
+fn decaying_curve_decays() {
+    new_test_ext().execute_with(|| {
+        set_curve(EmissionCurve::Decaying {
+            initial: 1_000,
+            decay: Perbill::from_percent(10),
+        });
+
+        Emission::on_initialize(10);
+        assert_eq!(Emission::total_emitted(), 1_000);
+
+        Emission::on_initialize(20);
+        assert_eq!(Emission::total_emitted(), 1_000 + 900);
+
+        Emission::on_initialize(30);
+        assert_eq!(Emission::total_emitted(), 1_000 + 900 + 810);
+    })
+}
+
+#[test]
+fn setting_a_curve_restarts_it() {
+    new_test_ext().execute_with(|| {
+        set_curve(EmissionCurve::Capped {
+            per_era: 100,
+            cap: 100,
+        });
+        Emission::on_initialize(10);
+
+        assert_ok!(Emission::set_curve(
+            RawOrigin::Root.into(),
+            EmissionCurve::Capped {
+                per_era: 100,
+                cap: 100,
+            }
+        ));
+        assert_eq!(Emission::era(), 0);
+        assert_eq!(Emission::total_emitted(), 0);
+
+        Emission::on_initialize(20);
+        assert_eq!(Emission::total_emitted(), 100);
+    })
+}
+
+#[test]
+fn non_origin_can_not_set_curve() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Emission::set_curve(
+                Origin::signed(Validator::get()),
+                EmissionCurve::Capped {
+                    per_era: 100,
+                    cap: 100,
+                }
+            ),
+            BadOrigin
+        );
+    })
+}
@@ -0,0 +1,30 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use parity_scale_codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// Fee of an extrinsic once the chain's own signed extensions are accounted for, the
+/// generic `payment_queryInfo` estimate prices feeless calls like any other.
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct FeeEstimate<Balance> {
+    /// Fee charged to the sender, tip excluded, zero for feeless calls
+    pub partial_fee: Balance,
+    /// The call is feeless and the sender still has some quota left
+    pub feeless: bool,
+    /// Feeless calls the sender can still make during the current quota period
+    pub feeless_quota_left: u32,
+    /// The sender already got all the transactions it is allowed to in the current
+    /// block, the extrinsic can only be included in a later one
+    pub rate_limited: bool,
+}
+
+sp_api::decl_runtime_apis! {
+    pub trait FeelessApi<Balance> where
+        Balance: parity_scale_codec::Codec,
+    {
+        fn query_fee(uxt: Block::Extrinsic, len: u32) -> FeeEstimate<Balance>;
+    }
+}
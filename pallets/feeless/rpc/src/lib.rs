@@ -0,0 +1,62 @@
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+pub use pallet_feeless_runtime_api::{FeeEstimate, FeelessApi as FeelessRuntimeApi};
+use parity_scale_codec::{Codec, Decode};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+mod rpc_errors;
+use rpc_errors::misc_rpc_error;
+
+#[rpc]
+pub trait FeesApi<BlockHash, Balance>
+where
+    Balance: Codec,
+{
+    /// Fee of the encoded extrinsic, unlike `payment_queryInfo` this accounts for the
+    /// feeless quota and the rate limit of its sender.
+    #[rpc(name = "fees_queryInfo")]
+    fn query_info(&self, encoded_xt: Bytes, at: Option<BlockHash>) -> Result<FeeEstimate<Balance>>;
+}
+
+pub struct Fees<C, M> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<M>,
+}
+
+impl<C, M> Fees<C, M> {
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, Block, Balance> FeesApi<<Block as BlockT>::Hash, Balance> for Fees<C, Block>
+where
+    Balance: Codec,
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block>,
+    C: HeaderBackend<Block>,
+    C::Api: FeelessRuntimeApi<Block, Balance>,
+{
+    fn query_info(
+        &self,
+        encoded_xt: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<FeeEstimate<Balance>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+        let len = encoded_xt.len() as u32;
+        let uxt: Block::Extrinsic = Decode::decode(&mut &*encoded_xt).map_err(misc_rpc_error)?;
+        api.query_fee(&at, uxt, len).map_err(misc_rpc_error)
+    }
+}
@@ -0,0 +1,248 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Some calls, such as device check-ins, are expected to be submitted very often and
+//! should not cost anything to devices. This module classifies such calls and let them
+//! go through without fees up to a per account quota, after which normal fees apply.
+//!
+//! The `ChargeFeelessOrPayment` signed extension is meant to replace the usual
+//! `ChargeTransactionPayment` extension, it uses the same encoding and identifier
+//! so that wallets do not need to be updated.
+
+mod tests;
+
+use frame_support::{
+    decl_module, decl_storage,
+    traits::{Currency, Get},
+    weights::{DispatchInfo, Weight},
+};
+use pallet_transaction_payment::ChargeTransactionPayment;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, Saturating, SignedExtension, Zero},
+    transaction_validity::{TransactionValidity, TransactionValidityError, ValidTransaction},
+    DispatchResult,
+};
+use sp_std::fmt::Debug;
+
+/// Select the calls that can be submitted without fees. The sender is known so that
+/// fees are only waived for calls that are expected to succeed.
+pub trait ClassifyFeeless<AccountId, Call> {
+    /// Return true if `call` submitted by `who` should not pay fees.
+    fn is_feeless(who: &AccountId, call: &Call) -> bool;
+}
+
+type BalanceOf<T> = <<T as pallet_transaction_payment::Trait>::Currency as Currency<
+    <T as frame_system::Trait>::AccountId,
+>>::Balance;
+
+/// The module's configuration trait.
+pub trait Trait: pallet_transaction_payment::Trait {
+    /// Calls that can be submitted without fees
+    type FeelessCalls: ClassifyFeeless<Self::AccountId, <Self as frame_system::Trait>::Call>;
+    /// Calls heavier than this are never feeless, even if selected by `FeelessCalls`
+    type MaximumFeelessWeight: Get<Weight>;
+    /// How many feeless calls an account can submit per period
+    type FeelessQuota: Get<u32>;
+    /// Length of a quota period, in blocks
+    type QuotaPeriod: Get<Self::BlockNumber>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Feeless {
+        /// Period index and number of feeless calls made by an account during that period
+        QuotaUsage get(fn quota_usage): map hasher(blake2_128_concat) T::AccountId => (T::BlockNumber, u32);
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const MaximumFeelessWeight: Weight = T::MaximumFeelessWeight::get();
+        const FeelessQuota: u32 = T::FeelessQuota::get();
+        const QuotaPeriod: T::BlockNumber = T::QuotaPeriod::get();
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Index of the quota period we are currently in.
+    fn current_period() -> T::BlockNumber {
+        let period = T::QuotaPeriod::get();
+        if period.is_zero() {
+            return Zero::zero();
+        }
+
+        <frame_system::Module<T>>::block_number() / period
+    }
+
+    /// How many feeless calls `who` already made during the current period.
+    pub fn used_quota(who: &T::AccountId) -> u32 {
+        let (period, used) = Self::quota_usage(who);
+        if period == Self::current_period() {
+            used
+        } else {
+            0
+        }
+    }
+
+    /// How many feeless calls `who` can still make during the current period.
+    pub fn quota_left(who: &T::AccountId) -> u32 {
+        T::FeelessQuota::get().saturating_sub(Self::used_quota(who))
+    }
+
+    /// Return true if `call` can be dispatched without fees on behalf of `who`.
+    pub fn is_feeless(
+        who: &T::AccountId,
+        call: &<T as frame_system::Trait>::Call,
+        info: &DispatchInfo,
+    ) -> bool {
+        T::FeelessCalls::is_feeless(who, call)
+            && info.weight <= T::MaximumFeelessWeight::get()
+            && Self::used_quota(who) < T::FeelessQuota::get()
+    }
+
+    /// Fee `who` would be charged for `call`, zero if it is feeless. Meant for wallets,
+    /// which would otherwise price feeless calls like any other.
+    pub fn estimate_fee(
+        who: &T::AccountId,
+        call: &<T as frame_system::Trait>::Call,
+        info: &DispatchInfo,
+        len: u32,
+    ) -> BalanceOf<T>
+    where
+        <T as frame_system::Trait>::Call: Dispatchable<Info = DispatchInfo>,
+    {
+        if Self::is_feeless(who, call, info) {
+            return Zero::zero();
+        }
+
+        <pallet_transaction_payment::Module<T>>::compute_fee(len, info, Zero::zero())
+    }
+
+    fn consume_quota(who: &T::AccountId) {
+        let used = Self::used_quota(who);
+        <QuotaUsage<T>>::insert(who, (Self::current_period(), used.saturating_add(1)));
+    }
+}
+
+/// Charge transaction fees, unless the call is feeless and its sender still has some
+/// quota left.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct ChargeFeelessOrPayment<T: Trait + Send + Sync>(ChargeTransactionPayment<T>);
+
+impl<T: Trait + Send + Sync> From<ChargeTransactionPayment<T>> for ChargeFeelessOrPayment<T> {
+    fn from(inner: ChargeTransactionPayment<T>) -> Self {
+        Self(inner)
+    }
+}
+
+impl<T: Trait + Send + Sync> Debug for ChargeFeelessOrPayment<T> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "ChargeFeelessOrPayment({:?})", self.0)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for ChargeFeelessOrPayment<T>
+where
+    ChargeTransactionPayment<T>: SignedExtension<
+        AccountId = T::AccountId,
+        Call = <T as frame_system::Trait>::Call,
+        AdditionalSigned = (),
+    >,
+    <T as frame_system::Trait>::Call: Dispatchable<Info = DispatchInfo>,
+{
+    // Wallets should keep treating us as the regular transaction payment extension.
+    const IDENTIFIER: &'static str = <ChargeTransactionPayment<T> as SignedExtension>::IDENTIFIER;
+    type AccountId = T::AccountId;
+    type Call = <T as frame_system::Trait>::Call;
+    type AdditionalSigned = ();
+    type Pre = Option<<ChargeTransactionPayment<T> as SignedExtension>::Pre>;
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> TransactionValidity {
+        if Module::<T>::is_feeless(who, call, info) {
+            return Ok(ValidTransaction::default());
+        }
+
+        self.0.validate(who, call, info, len)
+    }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> Result<Self::Pre, TransactionValidityError> {
+        if Module::<T>::is_feeless(who, call, info) {
+            Module::<T>::consume_quota(who);
+            return Ok(None);
+        }
+
+        self.0.pre_dispatch(who, call, info, len).map(Some)
+    }
+
+    fn post_dispatch(
+        pre: Self::Pre,
+        info: &DispatchInfoOf<Self::Call>,
+        post_info: &PostDispatchInfoOf<Self::Call>,
+        len: usize,
+        result: &DispatchResult,
+    ) -> Result<(), TransactionValidityError> {
+        match pre {
+            Some(pre) => {
+                ChargeTransactionPayment::<T>::post_dispatch(pre, info, post_info, len, result)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        use frame_support::IterableStorageMap;
+
+        let now = Self::current_period();
+        frame_support::ensure!(
+            <QuotaUsage<T>>::iter().all(|(_, (period, _))| period <= now),
+            "feeless quota used during a future period"
+        );
+
+        Ok(())
+    }
+}
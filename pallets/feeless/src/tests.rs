@@ -0,0 +1,235 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{
+    assert_ok, impl_outer_dispatch, impl_outer_origin, parameter_types,
+    weights::{DispatchClass, IdentityFee, Pays, PostDispatchInfo},
+};
+use test_utils::{impl_mock_balances, impl_mock_system};
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+impl_outer_dispatch! {
+    pub enum Call for Test where origin: Origin {
+        pallet_balances::Balances,
+    }
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, Call, pallet_balances::AccountData<u64>);
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+impl_mock_balances!(Test, ExistentialDeposit);
+parameter_types! {
+    pub const TransactionByteFee: u64 = 1;
+}
+impl pallet_transaction_payment::Trait for Test {
+    type Currency = Balances;
+    type OnTransactionPayment = ();
+    type TransactionByteFee = TransactionByteFee;
+    type WeightToFee = IdentityFee<u64>;
+    type FeeMultiplierUpdate = ();
+}
+
+parameter_types! {
+    pub const MaximumFeelessWeight: Weight = 100;
+    pub const FeelessQuota: u32 = 2;
+    pub const QuotaPeriod: u64 = 10;
+    pub const Device: u64 = 1;
+    pub const Receiver: u64 = 2;
+}
+/// Only transfers the sender can afford are feeless in our tests
+pub struct FeelessCalls;
+impl ClassifyFeeless<u64, Call> for FeelessCalls {
+    fn is_feeless(who: &u64, call: &Call) -> bool {
+        match call {
+            Call::Balances(pallet_balances::Call::transfer(_, amount)) => {
+                Balances::free_balance(who) >= *amount
+            }
+            _ => false,
+        }
+    }
+}
+impl Trait for Test {
+    type FeelessCalls = FeelessCalls;
+    type MaximumFeelessWeight = MaximumFeelessWeight;
+    type FeelessQuota = FeelessQuota;
+    type QuotaPeriod = QuotaPeriod;
+}
+type Feeless = Module<Test>;
+type Balances = pallet_balances::Module<Test>;
+type System = frame_system::Module<Test>;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut t = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(Device::get(), 1_000)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    t.into()
+}
+
+fn info(weight: Weight) -> DispatchInfo {
+    DispatchInfo {
+        weight,
+        class: DispatchClass::Normal,
+        pays_fee: Pays::Yes,
+    }
+}
+
+fn submit(call: &Call, info: &DispatchInfo) {
+    let pre = ChargeFeelessOrPayment::<Test>::from(ChargeTransactionPayment::from(0))
+        .pre_dispatch(&Device::get(), call, info, 10)
+        .unwrap();
+    assert_ok!(ChargeFeelessOrPayment::<Test>::post_dispatch(
+        pre,
+        info,
+        &PostDispatchInfo::default(),
+        10,
+        &Ok(())
+    ));
+}
+
+#[test]
+fn feeless_calls_are_free_within_quota() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let call = Call::Balances(pallet_balances::Call::transfer(Receiver::get(), 1));
+
+        submit(&call, &info(10));
+        submit(&call, &info(10));
+
+        assert_eq!(Balances::free_balance(Device::get()), 1_000);
+        assert_eq!(Feeless::used_quota(&Device::get()), 2);
+    })
+}
+
+#[test]
+fn fees_apply_once_quota_is_exhausted() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let call = Call::Balances(pallet_balances::Call::transfer(Receiver::get(), 1));
+
+        submit(&call, &info(10));
+        submit(&call, &info(10));
+        submit(&call, &info(10));
+
+        // 10 bytes and a weight of 10
+        assert_eq!(Balances::free_balance(Device::get()), 1_000 - 20);
+    })
+}
+
+#[test]
+fn quota_is_reset_every_period() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let call = Call::Balances(pallet_balances::Call::transfer(Receiver::get(), 1));
+
+        submit(&call, &info(10));
+        submit(&call, &info(10));
+        assert_eq!(Feeless::used_quota(&Device::get()), 2);
+
+        System::set_block_number(11);
+        assert_eq!(Feeless::used_quota(&Device::get()), 0);
+
+        submit(&call, &info(10));
+        assert_eq!(Balances::free_balance(Device::get()), 1_000);
+    })
+}
+
+#[test]
+fn other_calls_pay_fees() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let call = Call::Balances(pallet_balances::Call::transfer_keep_alive(
+            Receiver::get(),
+            1,
+        ));
+
+        submit(&call, &info(10));
+
+        assert_eq!(Balances::free_balance(Device::get()), 1_000 - 20);
+        assert_eq!(Feeless::used_quota(&Device::get()), 0);
+    })
+}
+
+#[test]
+fn calls_classified_for_their_sender() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let call = Call::Balances(pallet_balances::Call::transfer(Receiver::get(), 2_000));
+
+        assert!(!Feeless::is_feeless(&Device::get(), &call, &info(10)));
+        submit(&call, &info(10));
+
+        assert_eq!(Balances::free_balance(Device::get()), 1_000 - 20);
+        assert_eq!(Feeless::used_quota(&Device::get()), 0);
+    })
+}
+
+#[test]
+fn heavy_calls_pay_fees() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let call = Call::Balances(pallet_balances::Call::transfer(Receiver::get(), 1));
+
+        submit(&call, &info(MaximumFeelessWeight::get() + 1));
+
+        assert_eq!(
+            Balances::free_balance(Device::get()),
+            1_000 - 10 - MaximumFeelessWeight::get() - 1
+        );
+    })
+}
+
+#[test]
+fn fee_estimates_account_for_the_quota() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let call = Call::Balances(pallet_balances::Call::transfer(Receiver::get(), 1));
+
+        assert_eq!(
+            Feeless::estimate_fee(&Device::get(), &call, &info(10), 10),
+            0
+        );
+        submit(&call, &info(10));
+        submit(&call, &info(10));
+
+        assert_eq!(Feeless::quota_left(&Device::get()), 0);
+        assert_eq!(
+            Feeless::estimate_fee(&Device::get(), &call, &info(10), 10),
+            20
+        );
+    })
+}
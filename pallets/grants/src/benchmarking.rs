@@ -62,6 +62,24 @@ fn create_shared_config<T: Trait>(u: u32) -> BenchmarkConfig<T> {
     }
 }
 
+fn create_position<T: Trait>(u: u32) -> (BenchmarkConfig<T>, PositionId) {
+    let mut config = create_shared_config::<T>(u);
+    config.schedule.per_period = T::Currency::minimum_balance().saturating_mul(10u32.into());
+
+    Module::<T>::do_add_vesting_schedule(&config.granter, &config.grantee, config.schedule.clone())
+        .expect("granter is endowed");
+    let id = Module::<T>::position_count();
+    let index = Module::<T>::vesting_schedules(&config.grantee).len() as u32 - 1;
+    Module::<T>::tokenize(
+        RawOrigin::Signed(config.grantee.clone()).into(),
+        index,
+        config.schedule.per_period,
+    )
+    .expect("schedule is large enough to be tokenized");
+
+    (config, id)
+}
+
 benchmarks! {
     _ { }
 
@@ -104,6 +122,47 @@ benchmarks! {
         let call = Call::<T>::cancel_all_vesting_schedules(config.grantee_lookup, config.collector_lookup);
         let origin = T::CancelOrigin::successful_origin();
     }: { call.dispatch_bypass_filter(origin)? }
+
+    tokenize {
+        let u in 1 .. 1000;
+
+        let mut config = create_shared_config::<T>(u);
+        config.schedule.per_period = T::Currency::minimum_balance().saturating_mul(10u32.into());
+        Module::<T>::do_add_vesting_schedule(&config.granter, &config.grantee, config.schedule.clone())?;
+        let index = Module::<T>::vesting_schedules(&config.grantee).len() as u32 - 1;
+    }: _(RawOrigin::Signed(config.grantee), index, config.schedule.per_period)
+
+    transfer_position {
+        let u in 1 .. 1000;
+
+        let (config, id) = create_position::<T>(u);
+        let buyer: T::AccountId = account("buyer", u, SEED);
+    }: _(RawOrigin::Signed(config.grantee), id, T::Lookup::unlookup(buyer))
+
+    split_position {
+        let u in 1 .. 1000;
+
+        let (config, id) = create_position::<T>(u);
+        let buyer: T::AccountId = account("buyer", u, SEED);
+        let per_period = T::Currency::minimum_balance().saturating_mul(5u32.into());
+    }: _(RawOrigin::Signed(config.grantee), id, per_period, T::Lookup::unlookup(buyer))
+
+    claim_position {
+        let u in 1 .. 1000;
+
+        let (config, id) = create_position::<T>(u);
+        frame_system::Module::<T>::set_block_number(10.into());
+    }: _(RawOrigin::Signed(config.grantee), id)
+
+    cancel_position {
+        let u in 1 .. 1000;
+
+        let (config, id) = create_position::<T>(u);
+        frame_system::Module::<T>::set_block_number(10.into());
+
+        let call = Call::<T>::cancel_position(id, config.collector_lookup);
+        let origin = T::CancelOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
 }
 
 #[cfg(test)]
@@ -118,6 +177,11 @@ mod tests {
             assert_ok!(test_benchmark_add_vesting_schedule::<Runtime>());
             assert_ok!(test_benchmark_claim::<Runtime>());
             assert_ok!(test_benchmark_cancel_all_vesting_schedules::<Runtime>());
+            assert_ok!(test_benchmark_tokenize::<Runtime>());
+            assert_ok!(test_benchmark_transfer_position::<Runtime>());
+            assert_ok!(test_benchmark_split_position::<Runtime>());
+            assert_ok!(test_benchmark_claim_position::<Runtime>());
+            assert_ok!(test_benchmark_cancel_position::<Runtime>());
         });
     }
 }
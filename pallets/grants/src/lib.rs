@@ -6,12 +6,15 @@ use frame_support::{
         Currency, EnsureOrigin, ExistenceRequirement, Get, LockIdentifier, LockableCurrency,
         WithdrawReasons,
     },
+    transactional,
+    weights::DispatchClass,
 };
 use frame_system::{ensure_root, ensure_signed};
+use nodle_support::deposit_indexed_event;
 use parity_scale_codec::{Decode, Encode};
 use sp_runtime::{
-    traits::{AtLeast32Bit, CheckedAdd, Saturating, StaticLookup, Zero},
-    DispatchResult, RuntimeDebug,
+    traits::{AccountIdConversion, AtLeast32Bit, CheckedAdd, Saturating, StaticLookup, Zero},
+    DispatchError, DispatchResult, ModuleId, RuntimeDebug,
 };
 use sp_std::{
     cmp::{Eq, PartialEq},
@@ -21,6 +24,9 @@ use sp_std::{
 mod benchmarking;
 mod mock;
 mod tests;
+pub mod weights;
+
+pub use weights::WeightInfo;
 
 /// The vesting schedule.
 ///
@@ -67,10 +73,28 @@ impl<BlockNumber: AtLeast32Bit + Copy, Balance: AtLeast32Bit + Copy>
     }
 }
 
+/// Vesting positions are numbered in the order they are created.
+pub type PositionId = u64;
+
+/// A share of the future tranches of a vesting schedule. The funds are escrowed by the
+/// module so that the position can change hands while it keeps vesting on schedule.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub struct VestingPosition<AccountId, BlockNumber, Balance> {
+    pub owner: AccountId,
+    pub schedule: VestingSchedule<BlockNumber, Balance>,
+    /// Amount still escrowed for the position, vested or not
+    pub remaining: Balance,
+}
+
 pub type BalanceOf<T> =
     <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
 pub type VestingScheduleOf<T> =
     VestingSchedule<<T as frame_system::Trait>::BlockNumber, BalanceOf<T>>;
+pub type VestingPositionOf<T> = VestingPosition<
+    <T as frame_system::Trait>::AccountId,
+    <T as frame_system::Trait>::BlockNumber,
+    BalanceOf<T>,
+>;
 pub type ScheduledGrant<T> = (
     <T as frame_system::Trait>::BlockNumber,
     <T as frame_system::Trait>::BlockNumber,
@@ -86,12 +110,21 @@ pub trait Trait: frame_system::Trait {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
     type Currency: LockableCurrency<Self::AccountId, Moment = Self::BlockNumber>;
     type CancelOrigin: EnsureOrigin<Self::Origin>;
+    /// The account escrowing vesting positions is derived from it
+    type ModuleId: Get<ModuleId>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as Vesting {
         /// Vesting schedules of an account.
         pub VestingSchedules get(fn vesting_schedules): map hasher(blake2_128_concat) T::AccountId => Vec<VestingScheduleOf<T>>;
+        /// Vesting positions escrowed by the module.
+        pub Positions get(fn position): map hasher(twox_64_concat) PositionId => Option<VestingPositionOf<T>>;
+        /// Number of positions ever created, also the identifier of the next one.
+        pub PositionCount get(fn position_count): PositionId;
     }
 
     add_extra_genesis {
@@ -136,6 +169,14 @@ decl_event!(
         Claimed(AccountId, Balance),
         /// Canceled all vesting schedules (who)
         VestingSchedulesCanceled(AccountId),
+        /// Created a vesting position (id, owner, vesting_schedule)
+        PositionCreated(PositionId, AccountId, VestingSchedule),
+        /// Transferred a vesting position (id, from, to)
+        PositionTransferred(PositionId, AccountId, AccountId),
+        /// Paid out what vested in a position (id, owner, amount)
+        PositionClaimed(PositionId, AccountId, Balance),
+        /// Canceled a vesting position (id)
+        PositionCanceled(PositionId),
     }
 );
 
@@ -146,26 +187,36 @@ decl_error! {
         ZeroVestingPeriodCount,
         NumOverflow,
         InsufficientBalanceToLock,
+        UnknownSchedule,
+        UnknownPosition,
+        NotPositionOwner,
+        InvalidSplit,
+        PositionTooSmall,
+        /// The schedule would leave its recipient below the existential deposit
+        AmountBelowExistentialDeposit,
     }
 }
 
 decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
-        type Error = Error<T>;
+        const ModuleId: ModuleId = T::ModuleId::get();
 
-        fn deposit_event() = default;
+        type Error = Error<T>;
 
         /// Claim funds that have been vested so far
-        #[weight = 30_000_000 + T::DbWeight::get().reads_writes(2, 2)]
+        #[weight = T::WeightInfo::claim()]
         pub fn claim(origin) {
             let who = ensure_signed(origin)?;
             let locked_amount = Self::do_claim(&who);
 
-            Self::deposit_event(RawEvent::Claimed(who, locked_amount));
+            deposit_indexed_event::<T, <T as Trait>::Event>(
+                &[&who],
+                RawEvent::Claimed(who.clone(), locked_amount).into(),
+            );
         }
 
         /// Wire funds to be vested by the receiver
-        #[weight = 48_000_000 + T::DbWeight::get().reads_writes(4, 4)]
+        #[weight = T::WeightInfo::add_vesting_schedule()]
         pub fn add_vesting_schedule(
             origin,
             dest: <T::Lookup as StaticLookup>::Source,
@@ -175,12 +226,15 @@ decl_module! {
             let to = T::Lookup::lookup(dest)?;
             Self::do_add_vesting_schedule(&from, &to, schedule.clone())?;
 
-            Self::deposit_event(RawEvent::VestingScheduleAdded(from, to, schedule));
+            deposit_indexed_event::<T, <T as Trait>::Event>(
+                &[&from, &to],
+                RawEvent::VestingScheduleAdded(from.clone(), to.clone(), schedule).into(),
+            );
         }
 
         /// Cancel all vested schedules for the given user. If there are coins to be
         /// claimed they will be auto claimed for the given user.
-        #[weight = 48_000_000 + T::DbWeight::get().reads_writes(4, 4)]
+        #[weight = (T::WeightInfo::cancel_all_vesting_schedules(), DispatchClass::Operational)]
         pub fn cancel_all_vesting_schedules(
             origin,
             who: <T::Lookup as StaticLookup>::Source,
@@ -202,24 +256,208 @@ decl_module! {
                 ExistenceRequirement::AllowDeath
             )?;
 
-            Self::deposit_event(RawEvent::VestingSchedulesCanceled(account_with_schedule));
+            deposit_indexed_event::<T, <T as Trait>::Event>(
+                &[&account_with_schedule],
+                RawEvent::VestingSchedulesCanceled(account_with_schedule.clone()).into(),
+            );
+        }
+
+        /// Split `per_period` off the caller's vesting schedule at `index` into a
+        /// transferable position. The part of it which is still locked is escrowed
+        /// by the module and keeps vesting on the same schedule. The schedules are only
+        /// updated if the escrow transfer succeeds
+        #[weight = T::WeightInfo::tokenize()]
+        #[transactional]
+        pub fn tokenize(origin, index: u32, per_period: BalanceOf<T>) {
+            let who = ensure_signed(origin)?;
+            let mut schedules = Self::vesting_schedules(&who);
+            let schedule = schedules.get(index as usize).cloned().ok_or(Error::<T>::UnknownSchedule)?;
+            ensure!(!per_period.is_zero() && per_period <= schedule.per_period, Error::<T>::InvalidSplit);
+            let split = VestingSchedule { per_period, ..schedule.clone() };
+            let escrowed = split.locked_amount(<frame_system::Module<T>>::block_number());
+            ensure!(escrowed >= T::Currency::minimum_balance(), Error::<T>::PositionTooSmall);
+            let id = Self::position_count();
+            let next = id.checked_add(1).ok_or(Error::<T>::NumOverflow)?;
+
+            let left = schedule.per_period.saturating_sub(per_period);
+            if left.is_zero() {
+                schedules.remove(index as usize);
+            } else {
+                schedules[index as usize].per_period = left;
+            }
+            <VestingSchedules<T>>::insert(&who, schedules);
+            // The lock shrinks by the escrowed amount, which can then be moved
+            Self::do_claim(&who);
+            T::Currency::transfer(&who, &Self::escrow_account(), escrowed, ExistenceRequirement::AllowDeath)?;
+            <Positions<T>>::insert(id, VestingPosition {
+                owner: who.clone(),
+                schedule: split.clone(),
+                remaining: escrowed,
+            });
+            PositionCount::put(next);
+
+            deposit_indexed_event::<T, <T as Trait>::Event>(
+                &[&who],
+                RawEvent::PositionCreated(id, who.clone(), split).into(),
+            );
+        }
+
+        /// Give a vesting position to `dest`
+        #[weight = T::WeightInfo::transfer_position()]
+        pub fn transfer_position(origin, id: PositionId, dest: <T::Lookup as StaticLookup>::Source) {
+            let who = ensure_signed(origin)?;
+            let to = T::Lookup::lookup(dest)?;
+            let mut position = Self::position(id).ok_or(Error::<T>::UnknownPosition)?;
+            ensure!(position.owner == who, Error::<T>::NotPositionOwner);
+
+            position.owner = to.clone();
+            <Positions<T>>::insert(id, position);
+
+            deposit_indexed_event::<T, <T as Trait>::Event>(
+                &[&who, &to],
+                RawEvent::PositionTransferred(id, who.clone(), to.clone()).into(),
+            );
+        }
+
+        /// Split `per_period` off a vesting position into a new one owned by `dest`.
+        /// What vested so far stays with the current position
+        #[weight = T::WeightInfo::split_position()]
+        pub fn split_position(origin, id: PositionId, per_period: BalanceOf<T>, dest: <T::Lookup as StaticLookup>::Source) {
+            let who = ensure_signed(origin)?;
+            let to = T::Lookup::lookup(dest)?;
+            let mut position = Self::position(id).ok_or(Error::<T>::UnknownPosition)?;
+            ensure!(position.owner == who, Error::<T>::NotPositionOwner);
+            ensure!(!per_period.is_zero() && per_period < position.schedule.per_period, Error::<T>::InvalidSplit);
+            let split = VestingSchedule { per_period, ..position.schedule.clone() };
+            let now = <frame_system::Module<T>>::block_number();
+            let moved = split.locked_amount(now);
+            let kept = VestingSchedule {
+                per_period: position.schedule.per_period.saturating_sub(per_period),
+                ..position.schedule.clone()
+            };
+            // Both halves must be worth more than dust
+            ensure!(
+                moved >= T::Currency::minimum_balance() && kept.locked_amount(now) >= T::Currency::minimum_balance(),
+                Error::<T>::PositionTooSmall
+            );
+            let new_id = Self::position_count();
+            let next = new_id.checked_add(1).ok_or(Error::<T>::NumOverflow)?;
+
+            position.schedule = kept;
+            position.remaining = position.remaining.saturating_sub(moved);
+            <Positions<T>>::insert(id, position);
+            <Positions<T>>::insert(new_id, VestingPosition {
+                owner: to.clone(),
+                schedule: split.clone(),
+                remaining: moved,
+            });
+            PositionCount::put(next);
+
+            deposit_indexed_event::<T, <T as Trait>::Event>(
+                &[&to],
+                RawEvent::PositionCreated(new_id, to.clone(), split).into(),
+            );
+        }
+
+        /// Receive what vested so far in a vesting position
+        #[weight = T::WeightInfo::claim_position()]
+        pub fn claim_position(origin, id: PositionId) {
+            let who = ensure_signed(origin)?;
+            let position = Self::position(id).ok_or(Error::<T>::UnknownPosition)?;
+            ensure!(position.owner == who, Error::<T>::NotPositionOwner);
+
+            Self::release(id, position)?;
+        }
+
+        /// Cancel a vesting position, what vested so far is paid to its owner and the
+        /// rest to `funds_collector`
+        #[weight = (T::WeightInfo::cancel_position(), DispatchClass::Operational)]
+        pub fn cancel_position(origin, id: PositionId, funds_collector: <T::Lookup as StaticLookup>::Source) {
+            T::CancelOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+            let collector = T::Lookup::lookup(funds_collector)?;
+            let position = Self::position(id).ok_or(Error::<T>::UnknownPosition)?;
+
+            if let Some(position) = Self::release(id, position)? {
+                T::Currency::transfer(
+                    &Self::escrow_account(),
+                    &collector,
+                    position.remaining,
+                    ExistenceRequirement::AllowDeath
+                )?;
+                <Positions<T>>::remove(id);
+            }
+
+            deposit_indexed_event::<T, <T as Trait>::Event>(
+                &[],
+                RawEvent::PositionCanceled(id).into(),
+            );
         }
     }
 }
 
-const VESTING_LOCK_ID: LockIdentifier = *b"nvesting";
+/// Lock keeping the part of the grants which did not vest yet from being spent.
+pub const VESTING_LOCK_ID: LockIdentifier = *b"nvesting";
 
 impl<T: Trait> Module<T> {
+    /// The account escrowing the vesting positions.
+    pub fn escrow_account() -> T::AccountId {
+        T::ModuleId::get().into_account()
+    }
+
+    /// Pay the owner of position `id` what vested so far. Returns the position left, if
+    /// anything remains to vest.
+    fn release(
+        id: PositionId,
+        mut position: VestingPositionOf<T>,
+    ) -> Result<Option<VestingPositionOf<T>>, DispatchError> {
+        let locked = position
+            .schedule
+            .locked_amount(<frame_system::Module<T>>::block_number());
+        let vested = position.remaining.saturating_sub(locked);
+        if vested.is_zero() {
+            return Ok(Some(position));
+        }
+
+        T::Currency::transfer(
+            &Self::escrow_account(),
+            &position.owner,
+            vested,
+            ExistenceRequirement::AllowDeath,
+        )?;
+        position.remaining = locked;
+        deposit_indexed_event::<T, <T as Trait>::Event>(
+            &[&position.owner],
+            RawEvent::PositionClaimed(id, position.owner.clone(), vested).into(),
+        );
+
+        if locked.is_zero() {
+            <Positions<T>>::remove(id);
+            Ok(None)
+        } else {
+            <Positions<T>>::insert(id, &position);
+            Ok(Some(position))
+        }
+    }
+
     fn do_claim(who: &T::AccountId) -> BalanceOf<T> {
         let locked = Self::locked_balance(who);
         if locked.is_zero() {
             T::Currency::remove_lock(VESTING_LOCK_ID, who);
         } else {
-            T::Currency::set_lock(VESTING_LOCK_ID, who, locked, WithdrawReasons::all());
+            Self::set_vesting_lock(who, locked);
         }
         locked
     }
 
+    /// Lock `locked` coins of `who`, or the existential deposit if that is more: the
+    /// account could otherwise be reaped by a withdrawal, unvested grants included.
+    fn set_vesting_lock(who: &T::AccountId, locked: BalanceOf<T>) {
+        let locked = locked.max(T::Currency::minimum_balance());
+        T::Currency::set_lock(VESTING_LOCK_ID, who, locked, WithdrawReasons::all());
+    }
+
     /// Returns locked balance based on current block number.
     fn locked_balance(who: &T::AccountId) -> BalanceOf<T> {
         let now = <frame_system::Module<T>>::block_number();
@@ -241,9 +479,14 @@ impl<T: Trait> Module<T> {
         let total_amount = Self::locked_balance(to)
             .checked_add(&schedule_amount)
             .ok_or(Error::<T>::NumOverflow)?;
+        ensure!(
+            T::Currency::total_balance(to).saturating_add(schedule_amount)
+                >= T::Currency::minimum_balance(),
+            Error::<T>::AmountBelowExistentialDeposit
+        );
 
         T::Currency::transfer(from, to, schedule_amount, ExistenceRequirement::AllowDeath)?;
-        T::Currency::set_lock(VESTING_LOCK_ID, to, total_amount, WithdrawReasons::all());
+        Self::set_vesting_lock(to, total_amount);
         <VestingSchedules<T>>::mutate(to, |v| (*v).push(schedule));
 
         Ok(())
@@ -263,3 +506,35 @@ impl<T: Trait> Module<T> {
         schedule.total_amount().ok_or(Error::<T>::NumOverflow)
     }
 }
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        use frame_support::IterableStorageMap;
+
+        for (who, schedules) in <VestingSchedules<T>>::iter() {
+            ensure!(
+                schedules
+                    .iter()
+                    .all(|s| Self::ensure_valid_vesting_schedule(s).is_ok()),
+                "invalid vesting schedule in storage"
+            );
+            ensure!(
+                T::Currency::total_balance(&who) >= Self::locked_balance(&who),
+                "vesting schedules lock more than the account balance"
+            );
+        }
+
+        let escrowed = <Positions<T>>::iter().fold(Zero::zero(), |acc: BalanceOf<T>, (_, p)| {
+            acc.saturating_add(p.remaining)
+        });
+        ensure!(
+            T::Currency::free_balance(&Self::escrow_account()) >= escrowed,
+            "vesting positions are not fully escrowed"
+        );
+
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use frame_system::EnsureSignedBy;
 use pallet_balances;
 use sp_core::H256;
 use sp_runtime::{testing::Header, traits::IdentityLookup, Perbill};
+use std::cell::RefCell;
 
 use super::*;
 
@@ -67,8 +68,16 @@ pub type System = frame_system::Module<Runtime>;
 
 type Balance = u64;
 
+thread_local! {
+    static EXISTENTIAL_DEPOSIT: RefCell<u64> = RefCell::new(1);
+}
+pub struct ExistentialDeposit;
+impl Get<u64> for ExistentialDeposit {
+    fn get() -> u64 {
+        EXISTENTIAL_DEPOSIT.with(|v| *v.borrow())
+    }
+}
 parameter_types! {
-    pub const ExistentialDeposit: u64 = 1;
     pub const MaxLocks: u32 = 50;
 }
 
@@ -86,11 +95,16 @@ pub type PalletBalances = pallet_balances::Module<Runtime>;
 ord_parameter_types! {
     pub const CancelOrigin: AccountId = 42;
 }
+parameter_types! {
+    pub const GrantsModuleId: ModuleId = ModuleId(*b"py/grnts");
+}
 
 impl Trait for Runtime {
     type Event = TestEvent;
     type Currency = PalletBalances;
     type CancelOrigin = EnsureSignedBy<CancelOrigin, AccountId>;
+    type ModuleId = GrantsModuleId;
+    type WeightInfo = ();
 }
 pub type Vesting = Module<Runtime>;
 
@@ -99,12 +113,14 @@ pub const BOB: AccountId = 2;
 
 pub struct ExtBuilder {
     endowed_accounts: Vec<(AccountId, Balance)>,
+    existential_deposit: Balance,
 }
 
 impl Default for ExtBuilder {
     fn default() -> Self {
         Self {
             endowed_accounts: vec![],
+            existential_deposit: 1,
         }
     }
 }
@@ -119,7 +135,13 @@ impl ExtBuilder {
         self.balances(vec![(ALICE, 100)])
     }
 
+    pub fn existential_deposit(mut self, existential_deposit: Balance) -> Self {
+        self.existential_deposit = existential_deposit;
+        self
+    }
+
     pub fn build(self) -> sp_io::TestExternalities {
+        EXISTENTIAL_DEPOSIT.with(|v| *v.borrow_mut() = self.existential_deposit);
         let mut t = frame_system::GenesisConfig::default()
             .build_storage::<Runtime>()
             .unwrap();
@@ -9,7 +9,10 @@ use mock::{
     BOB,
 };
 use pallet_balances::{BalanceLock, Reasons};
-use sp_runtime::DispatchError::BadOrigin;
+use sp_runtime::{
+    traits::{BlakeTwo256, Hash},
+    DispatchError::BadOrigin,
+};
 
 #[test]
 fn add_vesting_schedule_works() {
@@ -189,6 +192,26 @@ fn add_vesting_schedule_fails_if_overflow() {
         });
 }
 
+#[test]
+fn add_vesting_schedule_fails_below_existential_deposit() {
+    ExtBuilder::default()
+        .one_hundred_for_alice()
+        .build()
+        .execute_with(|| {
+            let schedule = VestingSchedule {
+                start: 1u64,
+                period: 1u64,
+                period_count: 1u32,
+                per_period: 0u64,
+            };
+            assert_err!(
+                Vesting::add_vesting_schedule(Origin::signed(ALICE), BOB, schedule),
+                Error::<Runtime>::AmountBelowExistentialDeposit
+            );
+            assert_eq!(PalletBalances::locks(&BOB), vec![]);
+        });
+}
+
 #[test]
 fn claim_works() {
     ExtBuilder::default()
@@ -275,3 +298,231 @@ fn cancel_auto_claim_recipient_funds_and_wire_the_rest() {
             ));
         });
 }
+
+#[test]
+fn vesting_events_are_indexed_by_account() {
+    ExtBuilder::default()
+        .one_hundred_for_alice()
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+
+            let schedule = VestingSchedule {
+                start: 0u64,
+                period: 10u64,
+                period_count: 1u32,
+                per_period: 100u64,
+            };
+            assert_ok!(Vesting::add_vesting_schedule(
+                Origin::signed(ALICE),
+                BOB,
+                schedule
+            ));
+
+            let index = System::event_count() - 1;
+            for who in &[ALICE, BOB] {
+                assert!(System::event_topics(&BlakeTwo256::hash_of(who)).contains(&(1, index)));
+            }
+        });
+}
+
+fn grant_bob(per_period: u64) {
+    assert_ok!(Vesting::add_vesting_schedule(
+        Origin::signed(ALICE),
+        BOB,
+        VestingSchedule {
+            start: 0u64,
+            period: 10u64,
+            period_count: 2u32,
+            per_period,
+        }
+    ));
+}
+
+#[test]
+fn tokenize_escrows_future_tranches() {
+    ExtBuilder::default()
+        .one_hundred_for_alice()
+        .build()
+        .execute_with(|| {
+            grant_bob(10);
+
+            assert_noop!(
+                Vesting::tokenize(Origin::signed(BOB), 1, 5),
+                Error::<Runtime>::UnknownSchedule
+            );
+            assert_noop!(
+                Vesting::tokenize(Origin::signed(BOB), 0, 11),
+                Error::<Runtime>::InvalidSplit
+            );
+            assert_ok!(Vesting::tokenize(Origin::signed(BOB), 0, 6));
+
+            assert_eq!(Vesting::vesting_schedules(&BOB)[0].per_period, 4);
+            assert_eq!(PalletBalances::free_balance(&BOB), 8);
+            assert_eq!(PalletBalances::locks(&BOB).pop().unwrap().amount, 8);
+            assert_eq!(PalletBalances::free_balance(&Vesting::escrow_account()), 12);
+            assert_eq!(
+                Vesting::position(0),
+                Some(VestingPosition {
+                    owner: BOB,
+                    schedule: VestingSchedule {
+                        start: 0u64,
+                        period: 10u64,
+                        period_count: 2u32,
+                        per_period: 6u64,
+                    },
+                    remaining: 12,
+                })
+            );
+            assert_eq!(Vesting::position_count(), 1);
+        });
+}
+
+#[test]
+fn tokenize_needs_locked_funds() {
+    ExtBuilder::default()
+        .one_hundred_for_alice()
+        .build()
+        .execute_with(|| {
+            grant_bob(10);
+            System::set_block_number(20);
+
+            assert_noop!(
+                Vesting::tokenize(Origin::signed(BOB), 0, 10),
+                Error::<Runtime>::PositionTooSmall
+            );
+        });
+}
+
+#[test]
+fn failed_tokenize_keeps_schedules() {
+    ExtBuilder::default()
+        .one_hundred_for_alice()
+        .build()
+        .execute_with(|| {
+            grant_bob(10);
+            PalletBalances::set_lock(*b"otherlck", &BOB, 20, WithdrawReasons::all());
+
+            assert_noop!(
+                Vesting::tokenize(Origin::signed(BOB), 0, 6),
+                pallet_balances::Error::<Runtime, pallet_balances::DefaultInstance>::LiquidityRestrictions
+            );
+            assert_eq!(Vesting::vesting_schedules(&BOB)[0].per_period, 10);
+            assert_eq!(Vesting::position_count(), 0);
+        });
+}
+
+#[test]
+fn transferred_position_vests_for_its_new_owner() {
+    ExtBuilder::default()
+        .one_hundred_for_alice()
+        .build()
+        .execute_with(|| {
+            grant_bob(10);
+            assert_ok!(Vesting::tokenize(Origin::signed(BOB), 0, 6));
+
+            assert_noop!(
+                Vesting::transfer_position(Origin::signed(ALICE), 0, ALICE),
+                Error::<Runtime>::NotPositionOwner
+            );
+            assert_ok!(Vesting::transfer_position(Origin::signed(BOB), 0, ALICE));
+            assert_noop!(
+                Vesting::claim_position(Origin::signed(BOB), 0),
+                Error::<Runtime>::NotPositionOwner
+            );
+
+            System::set_block_number(10);
+            assert_ok!(Vesting::claim_position(Origin::signed(ALICE), 0));
+            assert_eq!(PalletBalances::free_balance(&ALICE), 86);
+            assert_eq!(Vesting::position(0).unwrap().remaining, 6);
+
+            System::set_block_number(20);
+            assert_ok!(Vesting::claim_position(Origin::signed(ALICE), 0));
+            assert_eq!(PalletBalances::free_balance(&ALICE), 92);
+            assert_eq!(Vesting::position(0), None);
+            assert_eq!(PalletBalances::free_balance(&Vesting::escrow_account()), 0);
+        });
+}
+
+#[test]
+fn split_position_keeps_vested_funds() {
+    ExtBuilder::default()
+        .one_hundred_for_alice()
+        .build()
+        .execute_with(|| {
+            grant_bob(10);
+            assert_ok!(Vesting::tokenize(Origin::signed(BOB), 0, 10));
+            assert_eq!(PalletBalances::locks(&BOB), vec![]);
+
+            System::set_block_number(10);
+            assert_noop!(
+                Vesting::split_position(Origin::signed(BOB), 0, 10, ALICE),
+                Error::<Runtime>::InvalidSplit
+            );
+            assert_ok!(Vesting::split_position(Origin::signed(BOB), 0, 4, ALICE));
+
+            let old = Vesting::position(0).unwrap();
+            assert_eq!(old.schedule.per_period, 6);
+            assert_eq!(old.remaining, 16);
+            let new = Vesting::position(1).unwrap();
+            assert_eq!(new.owner, ALICE);
+            assert_eq!(new.remaining, 4);
+
+            assert_ok!(Vesting::claim_position(Origin::signed(BOB), 0));
+            assert_eq!(PalletBalances::free_balance(&BOB), 10);
+            assert_eq!(Vesting::position(0).unwrap().remaining, 6);
+        });
+}
+
+#[test]
+fn split_positions_are_not_dust() {
+    ExtBuilder::default()
+        .one_hundred_for_alice()
+        .existential_deposit(5)
+        .build()
+        .execute_with(|| {
+            grant_bob(10);
+            assert_ok!(Vesting::tokenize(Origin::signed(BOB), 0, 10));
+
+            System::set_block_number(10);
+            // Either half would only hold 4 locked coins
+            assert_noop!(
+                Vesting::split_position(Origin::signed(BOB), 0, 4, ALICE),
+                Error::<Runtime>::PositionTooSmall
+            );
+            assert_noop!(
+                Vesting::split_position(Origin::signed(BOB), 0, 6, ALICE),
+                Error::<Runtime>::PositionTooSmall
+            );
+            assert_ok!(Vesting::split_position(Origin::signed(BOB), 0, 5, ALICE));
+
+            assert_eq!(Vesting::position(0).unwrap().schedule.per_period, 5);
+            assert_eq!(Vesting::position(1).unwrap().remaining, 5);
+        });
+}
+
+#[test]
+fn cancel_position_pays_vested_funds_to_owner() {
+    ExtBuilder::default()
+        .one_hundred_for_alice()
+        .build()
+        .execute_with(|| {
+            grant_bob(10);
+            assert_ok!(Vesting::tokenize(Origin::signed(BOB), 0, 10));
+
+            System::set_block_number(10);
+            assert_noop!(
+                Vesting::cancel_position(Origin::signed(BOB), 0, BOB),
+                BadOrigin
+            );
+            assert_ok!(Vesting::cancel_position(
+                Origin::signed(CancelOrigin::get()),
+                0,
+                CancelOrigin::get()
+            ));
+
+            assert_eq!(PalletBalances::free_balance(&BOB), 10);
+            assert_eq!(PalletBalances::free_balance(&CancelOrigin::get()), 10);
+            assert_eq!(Vesting::position(0), None);
+        });
+}
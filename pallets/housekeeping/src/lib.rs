@@ -0,0 +1,84 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Run the periodic tasks of other pallets, such as settling payouts or sweeping
+//! expired data, at the beginning of every block. Tasks share a configurable fraction
+//! of the block weight and resume in later blocks whatever did not fit. The task run
+//! first rotates every block so that a busy task can not starve the others.
+
+mod tests;
+
+use frame_support::{decl_module, decl_storage, traits::Get, weights::Weight};
+use nodle_support::HousekeepingTasks;
+use sp_runtime::{traits::Zero, Perbill};
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    /// Periodic tasks to run
+    type Tasks: HousekeepingTasks<Self::BlockNumber>;
+    /// Share of the maximum block weight the tasks can consume
+    type WeightShare: Get<Perbill>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Housekeeping {
+        /// Index of the task run first in the next block
+        NextTask get(fn next_task): u32;
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const WeightShare: Perbill = T::WeightShare::get();
+
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            let count = T::Tasks::count();
+            if count.is_zero() {
+                return 0;
+            }
+
+            let budget = T::WeightShare::get() * T::MaximumBlockWeight::get();
+            let first = Self::next_task() % count;
+            let mut consumed = T::DbWeight::get().reads_writes(1, 1);
+            for i in 0..count {
+                let remaining = budget.saturating_sub(consumed);
+                if remaining.is_zero() {
+                    break;
+                }
+                let used = T::Tasks::housekeep((first + i) % count, n, remaining);
+                consumed = consumed.saturating_add(used);
+            }
+            NextTask::put((first + 1) % count);
+
+            consumed
+        }
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        // The next task is taken modulo the number of tasks, any value is valid
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{impl_outer_origin, parameter_types, traits::OnInitialize};
+use nodle_support::HousekeepingTask;
+use std::cell::RefCell;
+use test_utils::impl_mock_system;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), ());
+
+thread_local! {
+    static RUNS: RefCell<Vec<(u8, Weight)>> = RefCell::new(vec![]);
+}
+
+/// Records its runs and consumes up to `WORK` of the budget it is given.
+pub struct Task<Id: Get<u8>, Work: Get<Weight>>(sp_std::marker::PhantomData<(Id, Work)>);
+impl<Id: Get<u8>, Work: Get<Weight>> HousekeepingTask<u64> for Task<Id, Work> {
+    fn housekeep(_n: u64, budget: Weight) -> Weight {
+        RUNS.with(|r| r.borrow_mut().push((Id::get(), budget)));
+        Work::get().min(budget)
+    }
+}
+
+parameter_types! {
+    pub const First: u8 = 1;
+    pub const Second: u8 = 2;
+    pub const Busy: Weight = 1_000_000_000;
+    pub const Idle: Weight = 0;
+    pub const WeightShare: Perbill = Perbill::from_percent(10);
+}
+impl Trait for Test {
+    type Tasks = (Task<First, Busy>, Task<Second, Idle>);
+    type WeightShare = WeightShare;
+}
+type Housekeeping = Module<Test>;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
+
+fn runs() -> Vec<u8> {
+    RUNS.with(|r| r.borrow_mut().drain(..).map(|(id, _)| id).collect())
+}
+
+#[test]
+fn tasks_share_a_bounded_budget() {
+    new_test_ext().execute_with(|| {
+        let budget = WeightShare::get() * <Test as frame_system::Trait>::MaximumBlockWeight::get();
+
+        let consumed = Housekeeping::on_initialize(1);
+
+        assert!(consumed <= budget);
+        // The busy task went first and used all of the budget
+        assert_eq!(runs(), vec![1]);
+    })
+}
+
+#[test]
+fn the_first_task_rotates() {
+    new_test_ext().execute_with(|| {
+        Housekeeping::on_initialize(1);
+        assert_eq!(runs(), vec![1]);
+
+        Housekeeping::on_initialize(2);
+        assert_eq!(runs(), vec![2, 1]);
+
+        Housekeeping::on_initialize(3);
+        assert_eq!(runs(), vec![1]);
+    })
+}
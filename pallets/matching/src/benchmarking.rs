@@ -0,0 +1,147 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Matching pallet benchmarks
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::UnfilteredDispatchable;
+use frame_system::RawOrigin;
+use sp_std::prelude::*;
+
+const SEED: u32 = 0;
+
+fn pots<T: Trait>(p: u32) -> Vec<T::AccountId> {
+    (0..p).map(|i| account("pot", i, SEED)).collect()
+}
+
+fn funded<T: Trait>(name: &'static str, index: u32) -> T::AccountId {
+    let who: T::AccountId = account(name, index, SEED);
+    let funds = T::Currency::minimum_balance().saturating_mul(1_000_000u32.into());
+    T::Currency::make_free_balance_be(&who, funds);
+    who
+}
+
+/// Start a round over `p` pots ending at block 10, replacing the round of a previous
+/// run if any.
+fn start<T: Trait>(p: u32) -> Vec<T::AccountId> {
+    <CurrentRound<T>>::kill();
+    let pots = pots::<T>(p);
+    let pool = T::Currency::minimum_balance().saturating_mul(1_000u32.into());
+    Module::<T>::start_round(
+        T::ManagerOrigin::successful_origin(),
+        pots.clone(),
+        pool,
+        10.into(),
+    )
+    .expect("no round is in progress");
+    pots
+}
+
+benchmarks! {
+    _ { }
+
+    start_round {
+        let p in 1 .. T::MaxPots::get();
+
+        <CurrentRound<T>>::kill();
+        let pots = pots::<T>(p);
+        let pool = T::Currency::minimum_balance();
+        let call = Call::<T>::start_round(pots, pool, 10.into());
+        let origin = T::ManagerOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+
+    donate {
+        let pots = start::<T>(T::MaxPots::get());
+        let donor = funded::<T>("donor", 0);
+        let amount = T::Currency::minimum_balance().saturating_mul(100u32.into());
+    }: _(RawOrigin::Signed(donor), pots[0].clone(), amount)
+
+    // Every pot receives two donations so that they are all matched
+    settle_round {
+        let p in 1 .. T::MaxPots::get();
+
+        let pots = start::<T>(p);
+        let amount = T::Currency::minimum_balance().saturating_mul(100u32.into());
+        for i in 0..2 {
+            let donor = funded::<T>("donor", i);
+            for pot in pots.iter() {
+                Module::<T>::donate(RawOrigin::Signed(donor.clone()).into(), pot.clone(), amount)?;
+            }
+        }
+        T::Currency::make_free_balance_be(
+            &T::Reserve::account_id(),
+            T::Currency::minimum_balance().saturating_mul(1_000_000u32.into()),
+        );
+    }: { Module::<T>::housekeep(10.into(), Weight::max_value()) }
+    verify {
+        assert!(Module::<T>::current_round().is_none());
+    }
+
+    register_public_good {
+        let pot: T::AccountId = account("pot", 0, SEED);
+        let call = Call::<T>::register_public_good(pot);
+        let origin = T::ManagerOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+
+    unregister_public_good {
+        let pot: T::AccountId = account("pot", 0, SEED);
+        <PublicGoods<T>>::insert(&pot, true);
+        let call = Call::<T>::unregister_public_good(pot);
+        let origin = T::ManagerOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+
+    burn_and_match {
+        let pot: T::AccountId = account("pot", 0, SEED);
+        <PublicGoods<T>>::insert(&pot, true);
+        let burner = funded::<T>("burner", 0);
+        let amount = T::Currency::minimum_balance().saturating_mul(100u32.into());
+        T::Currency::make_free_balance_be(
+            &T::Reserve::account_id(),
+            T::Currency::minimum_balance().saturating_mul(1_000_000u32.into()),
+        );
+    }: _(RawOrigin::Signed(burner), pot, amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_start_round::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_donate::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_settle_round::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_register_public_good::<Test>());
+            assert_ok!(test_benchmark_unregister_public_good::<Test>());
+            assert_ok!(test_benchmark_burn_and_match::<Test>());
+        });
+    }
+}
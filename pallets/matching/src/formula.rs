@@ -0,0 +1,56 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Formulas deciding how the matching pool of a round is split among the pots. Every
+//! pot gets a share of the pool proportional to its score.
+
+use sp_runtime::traits::AtLeast32BitUnsigned;
+
+pub trait MatchingFormula {
+    /// Score of a pot which received `total` in donations, `sum_of_roots` being the
+    /// sum of the square roots of what every donor gave.
+    fn score<Balance: AtLeast32BitUnsigned + Copy>(
+        total: Balance,
+        sum_of_roots: Balance,
+    ) -> Balance;
+}
+
+/// Quadratic funding, the number of donors weighs more than the amounts they gave.
+/// Pots with a single donor are not matched.
+pub struct Quadratic;
+impl MatchingFormula for Quadratic {
+    fn score<Balance: AtLeast32BitUnsigned + Copy>(
+        total: Balance,
+        sum_of_roots: Balance,
+    ) -> Balance {
+        sum_of_roots
+            .saturating_mul(sum_of_roots)
+            .saturating_sub(total)
+    }
+}
+
+/// Pots are matched in proportion to the donations they received.
+pub struct Proportional;
+impl MatchingFormula for Proportional {
+    fn score<Balance: AtLeast32BitUnsigned + Copy>(
+        total: Balance,
+        _sum_of_roots: Balance,
+    ) -> Balance {
+        total
+    }
+}
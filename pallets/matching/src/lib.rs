@@ -0,0 +1,378 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Community grant rounds. A manager flags a set of pots and sets aside a matching
+//! pool, anyone can then donate to the pots until the round ends. Donations go to the
+//! pots right away, once the round is over the pool is taken from the reserve and
+//! split among the pots according to the configured `Formula`.
+//!
+//! Settlement happens as a housekeeping task, the pool is capped by what the reserve
+//! holds at that time.
+//!
+//! Outside of rounds, anyone can burn coins in favour of a registered public good. The
+//! reserve matches the burnt amount by paying the public good, up to a cap per era.
+
+mod benchmarking;
+pub mod formula;
+pub mod weights;
+
+#[cfg(test)]
+mod tests;
+
+pub use formula::MatchingFormula;
+pub use weights::WeightInfo;
+
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{Currency, EnsureOrigin, ExistenceRequirement, Get, WithdrawReasons},
+    weights::Weight,
+};
+use frame_system::ensure_signed;
+use nodle_support::{HousekeepingTask, WithAccountId};
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{IntegerSquareRoot, One, SaturatedConversion, Saturating, Zero},
+    DispatchResult, Perbill, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+pub type RoundOf<T> = Round<
+    <T as frame_system::Trait>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Trait>::BlockNumber,
+>;
+
+/// A grant round, donations to its pots are matched once it ends.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Round<AccountId, Balance, BlockNumber> {
+    /// Rounds are numbered in the order they are started
+    pub index: u32,
+    /// Maximum amount taken from the reserve to match the donations
+    pub pool: Balance,
+    /// Donations are accepted until this block, the round is settled from then on
+    pub end: BlockNumber,
+    /// Pots flagged for the round, sorted
+    pub pots: Vec<AccountId>,
+}
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    type Currency: Currency<Self::AccountId>;
+    /// Account the matching pools are paid from
+    type Reserve: WithAccountId<Self::AccountId>;
+    /// Origin allowed to start rounds
+    type ManagerOrigin: EnsureOrigin<Self::Origin>;
+    /// How the matching pool is split among the pots
+    type Formula: MatchingFormula;
+    /// Maximum number of pots in a round, bounds the cost of settling it
+    type MaxPots: Get<u32>;
+    /// Length of the eras burns are matched in, in blocks
+    type MatchEraDuration: Get<Self::BlockNumber>;
+    /// Most the reserve matches burns with during an era
+    type MaxMatchPerEra: Get<BalanceOf<Self>>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Matching {
+        /// Round accepting donations or waiting to be settled, if any
+        CurrentRound get(fn current_round): Option<RoundOf<T>>;
+        /// Number of rounds ever started, also the index of the next one
+        RoundCount get(fn round_count): u32;
+        /// Donations made to a pot during the current round, by donor
+        Donations get(fn donation):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AccountId => BalanceOf<T>;
+        /// Total donated to a pot during the current round and sum of the square roots
+        /// of each donor's donations
+        PotTotals get(fn pot_totals): map hasher(blake2_128_concat) T::AccountId => (BalanceOf<T>, BalanceOf<T>);
+        /// Public goods burns can be matched in favour of
+        PublicGoods get(fn is_public_good): map hasher(blake2_128_concat) T::AccountId => bool;
+        /// Amounts burnt and matched during an era
+        EraMatches get(fn era_matches): map hasher(twox_64_concat) u32 => (BalanceOf<T>, BalanceOf<T>);
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        Balance = BalanceOf<T>,
+        BlockNumber = <T as frame_system::Trait>::BlockNumber,
+    {
+        /// A round with the given index and matching pool started, it ends at the
+        /// given block
+        RoundStarted(u32, Balance, BlockNumber),
+        /// An account donated to a pot
+        Donated(AccountId, AccountId, Balance),
+        /// A pot received the given amount from the matching pool
+        PotMatched(AccountId, Balance),
+        /// The round was settled, the given amount was matched in total
+        RoundSettled(u32, Balance),
+        /// The account was registered as a public good
+        PublicGoodRegistered(AccountId),
+        /// The account is not a public good anymore
+        PublicGoodUnregistered(AccountId),
+        /// An account burnt the given amount in favour of a public good, which received
+        /// the given amount from the reserve
+        BurnMatched(AccountId, AccountId, Balance, Balance),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// A round is already running or waiting to be settled
+        RoundInProgress,
+        /// More pots than `MaxPots` were flagged
+        TooManyPots,
+        /// No round is accepting donations
+        NoRound,
+        /// The account is not a pot of the current round
+        NotAPot,
+        /// Donations must not be empty
+        NothingDonated,
+        /// The account is not a registered public good
+        NotAPublicGood,
+        /// Burns must not be empty
+        NothingBurnt,
+        /// Nothing is left to match burns with during this era
+        MatchingExhausted,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const MaxPots: u32 = T::MaxPots::get();
+
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Start a round matching donations to `pots` with up to `pool` from the
+        /// reserve, donations are accepted for `duration` blocks
+        #[weight = T::WeightInfo::start_round(pots.len() as u32)]
+        pub fn start_round(origin, pots: Vec<T::AccountId>, pool: BalanceOf<T>, duration: T::BlockNumber) -> DispatchResult {
+            T::ManagerOrigin::ensure_origin(origin)?;
+            ensure!(Self::current_round().is_none(), Error::<T>::RoundInProgress);
+            ensure!(pots.len() as u32 <= T::MaxPots::get(), Error::<T>::TooManyPots);
+
+            let mut pots = pots;
+            pots.sort();
+            pots.dedup();
+            let index = Self::round_count();
+            let end = <frame_system::Module<T>>::block_number().saturating_add(duration);
+            <CurrentRound<T>>::put(Round {
+                index,
+                pool,
+                end,
+                pots,
+            });
+            RoundCount::put(index.saturating_add(1));
+
+            Self::deposit_event(RawEvent::RoundStarted(index, pool, end));
+            Ok(())
+        }
+
+        /// Donate `amount` to a pot of the current round, it is transferred right away
+        /// and counts towards the pot's share of the matching pool
+        #[weight = T::WeightInfo::donate()]
+        pub fn donate(origin, pot: T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+            let donor = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::NothingDonated);
+            let round = Self::current_round().ok_or(Error::<T>::NoRound)?;
+            ensure!(<frame_system::Module<T>>::block_number() < round.end, Error::<T>::NoRound);
+            ensure!(round.pots.binary_search(&pot).is_ok(), Error::<T>::NotAPot);
+
+            T::Currency::transfer(&donor, &pot, amount, ExistenceRequirement::AllowDeath)?;
+            let before = Self::donation(&pot, &donor);
+            let after = before.saturating_add(amount);
+            <Donations<T>>::insert(&pot, &donor, after);
+            <PotTotals<T>>::mutate(&pot, |(total, roots)| {
+                *total = total.saturating_add(amount);
+                *roots = roots
+                    .saturating_add(after.integer_sqrt())
+                    .saturating_sub(before.integer_sqrt());
+            });
+
+            Self::deposit_event(RawEvent::Donated(donor, pot, amount));
+            Ok(())
+        }
+
+        /// Let burns be matched in favour of `pot`
+        #[weight = T::WeightInfo::register_public_good()]
+        pub fn register_public_good(origin, pot: T::AccountId) -> DispatchResult {
+            T::ManagerOrigin::ensure_origin(origin)?;
+
+            <PublicGoods<T>>::insert(&pot, true);
+            Self::deposit_event(RawEvent::PublicGoodRegistered(pot));
+            Ok(())
+        }
+
+        /// Stop matching burns in favour of `pot`
+        #[weight = T::WeightInfo::unregister_public_good()]
+        pub fn unregister_public_good(origin, pot: T::AccountId) -> DispatchResult {
+            T::ManagerOrigin::ensure_origin(origin)?;
+            ensure!(Self::is_public_good(&pot), Error::<T>::NotAPublicGood);
+
+            <PublicGoods<T>>::remove(&pot);
+            Self::deposit_event(RawEvent::PublicGoodUnregistered(pot));
+            Ok(())
+        }
+
+        /// Burn `amount` in favour of the public good `pot`, the reserve pays it as much
+        /// as was burnt or what is left of `MaxMatchPerEra` if less. The whole amount
+        /// is burnt either way
+        #[weight = T::WeightInfo::burn_and_match()]
+        pub fn burn_and_match(origin, pot: T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::NothingBurnt);
+            ensure!(Self::is_public_good(&pot), Error::<T>::NotAPublicGood);
+
+            let era = Self::match_era();
+            let (burnt, matched) = Self::era_matches(era);
+            let reserve = T::Reserve::account_id();
+            let available =
+                T::Currency::free_balance(&reserve).saturating_sub(T::Currency::minimum_balance());
+            let matching = amount
+                .min(T::MaxMatchPerEra::get().saturating_sub(matched))
+                .min(available);
+            ensure!(!matching.is_zero(), Error::<T>::MatchingExhausted);
+
+            // Dropping the imbalance burns the coins, unless the reserve can not pay
+            let imbalance = T::Currency::withdraw(
+                &who,
+                amount,
+                WithdrawReasons::all(),
+                ExistenceRequirement::AllowDeath,
+            )?;
+            let paid = T::Currency::transfer(&reserve, &pot, matching, ExistenceRequirement::KeepAlive);
+            if let Err(e) = paid {
+                T::Currency::resolve_creating(&who, imbalance);
+                return Err(e);
+            }
+            drop(imbalance);
+
+            let totals = (burnt.saturating_add(amount), matched.saturating_add(matching));
+            <EraMatches<T>>::insert(era, totals);
+            Self::deposit_event(RawEvent::BurnMatched(who, pot, amount, matching));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Era burns made during the current block are matched in.
+    pub fn match_era() -> u32 {
+        let duration = T::MatchEraDuration::get().max(One::one());
+        (<frame_system::Module<T>>::block_number() / duration).saturated_into()
+    }
+
+    /// Split the matching pool of `round` among its pots and clear its donations,
+    /// returns the amount matched.
+    fn settle(round: RoundOf<T>) -> BalanceOf<T> {
+        let reserve = T::Reserve::account_id();
+        let available =
+            T::Currency::free_balance(&reserve).saturating_sub(T::Currency::minimum_balance());
+        let pool = round.pool.min(available);
+
+        let scores: Vec<(T::AccountId, BalanceOf<T>)> = round
+            .pots
+            .into_iter()
+            .map(|pot| {
+                let (total, roots) = <PotTotals<T>>::take(&pot);
+                <Donations<T>>::remove_prefix(&pot);
+                (pot, T::Formula::score(total, roots))
+            })
+            .collect();
+        let total_score = scores
+            .iter()
+            .fold(BalanceOf::<T>::zero(), |acc, (_, score)| {
+                acc.saturating_add(*score)
+            });
+        if total_score.is_zero() {
+            return Zero::zero();
+        }
+
+        let mut matched = BalanceOf::<T>::zero();
+        for (pot, score) in scores {
+            let amount = Perbill::from_rational_approximation(score, total_score) * pool;
+            if amount.is_zero() {
+                continue;
+            }
+            if T::Currency::transfer(&reserve, &pot, amount, ExistenceRequirement::KeepAlive)
+                .is_ok()
+            {
+                matched = matched.saturating_add(amount);
+                Self::deposit_event(RawEvent::PotMatched(pot, amount));
+            }
+        }
+        matched
+    }
+}
+
+impl<T: Trait> HousekeepingTask<T::BlockNumber> for Module<T> {
+    fn housekeep(n: T::BlockNumber, budget: Weight) -> Weight {
+        let consumed = T::DbWeight::get().reads(1);
+        if budget < consumed {
+            return 0;
+        }
+        let round = match Self::current_round() {
+            Some(round) if n >= round.end => round,
+            _ => return consumed,
+        };
+
+        // Rounds are settled at once, wait for a block with enough room
+        let weight = T::WeightInfo::settle_round(round.pots.len() as u32);
+        if budget < consumed.saturating_add(weight) {
+            return consumed;
+        }
+
+        <CurrentRound<T>>::kill();
+        let index = round.index;
+        let matched = Self::settle(round);
+        Self::deposit_event(RawEvent::RoundSettled(index, matched));
+        consumed.saturating_add(weight)
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        if let Some(round) = Self::current_round() {
+            ensure!(
+                round.index < Self::round_count(),
+                "the current round was not counted"
+            );
+            ensure!(
+                round.pots.windows(2).all(|pair| pair[0] < pair[1]),
+                "pots are not sorted or contain duplicates"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,340 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use crate::formula::{Proportional, Quadratic};
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, parameter_types,
+};
+use frame_system::EnsureSignedBy;
+use sp_runtime::DispatchError::BadOrigin;
+use test_utils::{impl_mock_balances, impl_mock_system};
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), pallet_balances::AccountData<u64>);
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+impl_mock_balances!(Test, ExistentialDeposit);
+
+pub struct Reserve;
+impl WithAccountId<u64> for Reserve {
+    fn account_id() -> u64 {
+        RESERVE
+    }
+}
+
+ord_parameter_types! {
+    pub const Manager: u64 = 1;
+}
+parameter_types! {
+    pub const MaxPots: u32 = 3;
+    pub const MatchEraDuration: u64 = 10;
+    pub const MaxMatchPerEra: u64 = 150;
+}
+impl Trait for Test {
+    type Event = ();
+    type Currency = pallet_balances::Module<Self>;
+    type Reserve = Reserve;
+    type ManagerOrigin = EnsureSignedBy<Manager, u64>;
+    type Formula = Quadratic;
+    type MaxPots = MaxPots;
+    type MatchEraDuration = MatchEraDuration;
+    type MaxMatchPerEra = MaxMatchPerEra;
+    type WeightInfo = ();
+}
+type Matching = Module<Test>;
+type Balances = pallet_balances::Module<Test>;
+type System = frame_system::Module<Test>;
+type Errors = Error<Test>;
+
+const MANAGER: u64 = 1;
+const DONORS: [u64; 4] = [2, 3, 4, 5];
+const POT_A: u64 = 10;
+const POT_B: u64 = 11;
+const RESERVE: u64 = 99;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    let mut balances: Vec<(u64, u64)> = DONORS.iter().map(|d| (*d, 1_000)).collect();
+    balances.push((RESERVE, 1_001));
+    pallet_balances::GenesisConfig::<Test> { balances }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+    storage.into()
+}
+
+fn start(pool: u64) -> DispatchResult {
+    Matching::start_round(Origin::signed(MANAGER), vec![POT_B, POT_A], pool, 10)
+}
+
+fn donate(donor: u64, pot: u64, amount: u64) -> DispatchResult {
+    Matching::donate(Origin::signed(donor), pot, amount)
+}
+
+/// Pot A gets 100 from two donors and scores 200, pot B gets 25 from four donors and
+/// scores 300.
+fn donate_to_both_pots() {
+    assert_ok!(donate(DONORS[0], POT_A, 100));
+    assert_ok!(donate(DONORS[1], POT_A, 100));
+    for donor in DONORS.iter() {
+        assert_ok!(donate(*donor, POT_B, 25));
+    }
+}
+
+#[test]
+fn manager_starts_rounds() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Matching::start_round(Origin::signed(DONORS[0]), vec![POT_A], 100, 10),
+            BadOrigin
+        );
+        assert_noop!(
+            Matching::start_round(Origin::signed(MANAGER), vec![1, 2, 3, 4], 100, 10),
+            Errors::TooManyPots
+        );
+
+        System::set_block_number(5);
+        assert_ok!(start(100));
+        assert_eq!(
+            Matching::current_round(),
+            Some(Round {
+                index: 0,
+                pool: 100,
+                end: 15,
+                pots: vec![POT_A, POT_B],
+            })
+        );
+        assert_eq!(Matching::round_count(), 1);
+
+        assert_noop!(start(100), Errors::RoundInProgress);
+    })
+}
+
+#[test]
+fn donations_go_to_the_pots() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(start(100));
+        assert_ok!(donate(DONORS[0], POT_A, 4));
+        assert_ok!(donate(DONORS[0], POT_A, 5));
+        assert_ok!(donate(DONORS[1], POT_A, 16));
+
+        assert_eq!(Balances::free_balance(POT_A), 25);
+        assert_eq!(Balances::free_balance(DONORS[0]), 991);
+        assert_eq!(Matching::donation(POT_A, DONORS[0]), 9);
+        // The square roots of the donors' totals are summed: 3 + 4
+        assert_eq!(Matching::pot_totals(POT_A), (25, 7));
+    })
+}
+
+#[test]
+fn donations_are_only_accepted_during_rounds() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(donate(DONORS[0], POT_A, 10), Errors::NoRound);
+
+        assert_ok!(start(100));
+        assert_noop!(donate(DONORS[0], POT_A, 0), Errors::NothingDonated);
+        assert_noop!(donate(DONORS[0], 12, 10), Errors::NotAPot);
+
+        System::set_block_number(10);
+        assert_noop!(donate(DONORS[0], POT_A, 10), Errors::NoRound);
+    })
+}
+
+#[test]
+fn pool_is_split_quadratically_at_round_end() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(start(500));
+        donate_to_both_pots();
+
+        assert_eq!(Matching::housekeep(9, Weight::max_value()), 0);
+        assert!(Matching::current_round().is_some());
+
+        Matching::housekeep(10, Weight::max_value());
+        assert_eq!(Matching::current_round(), None);
+        assert_eq!(Balances::free_balance(POT_A), 200 + 200);
+        assert_eq!(Balances::free_balance(POT_B), 100 + 300);
+        assert_eq!(Balances::free_balance(RESERVE), 501);
+
+        assert_eq!(Matching::pot_totals(POT_A), (0, 0));
+        assert_eq!(Matching::donation(POT_A, DONORS[0]), 0);
+
+        // Another round can start once the previous one is settled
+        assert_ok!(start(500));
+        assert_eq!(Matching::current_round().map(|r| r.index), Some(1));
+    })
+}
+
+#[test]
+fn pool_is_capped_by_the_reserve() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(start(5_000));
+        donate_to_both_pots();
+
+        Matching::housekeep(10, Weight::max_value());
+        assert_eq!(Balances::free_balance(POT_A), 200 + 400);
+        assert_eq!(Balances::free_balance(POT_B), 100 + 600);
+        assert_eq!(Balances::free_balance(RESERVE), 1);
+    })
+}
+
+#[test]
+fn rounds_without_scores_are_not_matched() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(start(500));
+        assert_ok!(donate(DONORS[0], POT_A, 100));
+
+        Matching::housekeep(10, Weight::max_value());
+        assert_eq!(Matching::current_round(), None);
+        assert_eq!(Balances::free_balance(POT_A), 100);
+        assert_eq!(Balances::free_balance(RESERVE), 1_001);
+    })
+}
+
+#[test]
+fn settlement_waits_for_enough_budget() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(start(500));
+        donate_to_both_pots();
+
+        Matching::housekeep(10, 0);
+        assert!(Matching::current_round().is_some());
+        assert_eq!(Balances::free_balance(RESERVE), 1_001);
+
+        assert_eq!(
+            Matching::housekeep(11, Weight::max_value()),
+            <() as WeightInfo>::settle_round(2)
+        );
+        assert_eq!(Matching::current_round(), None);
+    })
+}
+
+fn burn(who: u64, pot: u64, amount: u64) -> DispatchResult {
+    Matching::burn_and_match(Origin::signed(who), pot, amount)
+}
+
+#[test]
+fn manager_registers_public_goods() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Matching::register_public_good(Origin::signed(DONORS[0]), POT_A),
+            BadOrigin
+        );
+
+        assert_ok!(Matching::register_public_good(
+            Origin::signed(MANAGER),
+            POT_A
+        ));
+        assert!(Matching::is_public_good(POT_A));
+
+        assert_ok!(Matching::unregister_public_good(
+            Origin::signed(MANAGER),
+            POT_A
+        ));
+        assert!(!Matching::is_public_good(POT_A));
+        assert_noop!(
+            Matching::unregister_public_good(Origin::signed(MANAGER), POT_A),
+            Errors::NotAPublicGood
+        );
+    })
+}
+
+#[test]
+fn burns_are_matched_for_public_goods() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Matching::register_public_good(
+            Origin::signed(MANAGER),
+            POT_A
+        ));
+        assert_noop!(burn(DONORS[0], POT_A, 0), Errors::NothingBurnt);
+        assert_noop!(burn(DONORS[0], POT_B, 100), Errors::NotAPublicGood);
+
+        let issuance = Balances::total_issuance();
+        assert_ok!(burn(DONORS[0], POT_A, 100));
+
+        assert_eq!(Balances::free_balance(DONORS[0]), 900);
+        assert_eq!(Balances::free_balance(POT_A), 100);
+        assert_eq!(Balances::free_balance(RESERVE), 901);
+        assert_eq!(Balances::total_issuance(), issuance - 100);
+        assert_eq!(Matching::era_matches(0), (100, 100));
+    })
+}
+
+#[test]
+fn matching_is_capped_per_era() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Matching::register_public_good(
+            Origin::signed(MANAGER),
+            POT_A
+        ));
+        assert_ok!(burn(DONORS[0], POT_A, 100));
+        assert_ok!(burn(DONORS[1], POT_A, 100));
+
+        // The second burn is only matched up to the cap but burnt in full
+        assert_eq!(Balances::free_balance(DONORS[1]), 900);
+        assert_eq!(Balances::free_balance(POT_A), 150);
+        assert_eq!(Matching::era_matches(0), (200, 150));
+        assert_noop!(burn(DONORS[2], POT_A, 100), Errors::MatchingExhausted);
+
+        System::set_block_number(MatchEraDuration::get());
+        assert_ok!(burn(DONORS[2], POT_A, 100));
+        assert_eq!(Matching::era_matches(1), (100, 100));
+        assert_eq!(Balances::free_balance(POT_A), 250);
+    })
+}
+
+#[test]
+fn matching_is_capped_by_the_reserve() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Matching::register_public_good(
+            Origin::signed(MANAGER),
+            POT_A
+        ));
+        let _ = Balances::make_free_balance_be(&RESERVE, 31);
+
+        assert_ok!(burn(DONORS[0], POT_A, 100));
+        assert_eq!(Balances::free_balance(POT_A), 30);
+        assert_eq!(Balances::free_balance(RESERVE), 1);
+        assert_noop!(burn(DONORS[1], POT_A, 100), Errors::MatchingExhausted);
+    })
+}
+
+#[test]
+fn formulas_score_pots() {
+    // A single donor of 100
+    assert_eq!(Quadratic::score(100u64, 10u64), 0);
+    assert_eq!(Proportional::score(100u64, 10u64), 100);
+    // Four donors of 25
+    assert_eq!(Quadratic::score(100u64, 20u64), 300);
+    assert_eq!(Proportional::score(100u64, 20u64), 100);
+}
@@ -0,0 +1,129 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Hold tunable runtime parameters in storage so that governance can update them
+//! without having to go through a full runtime upgrade. The runtime reads them via
+//! non constant `parameter_types!`.
+
+mod benchmarking;
+mod tests;
+pub mod weights;
+
+pub use weights::WeightInfo;
+
+use frame_support::{
+    decl_event, decl_module, decl_storage,
+    traits::{Currency, EnsureOrigin},
+    weights::DispatchClass,
+};
+use frame_system::ensure_root;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{DispatchResult, Perbill, RuntimeDebug};
+
+type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// A new value for one of the parameters.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum ParameterValue<Balance> {
+    /// Share of every allocation going to the protocol
+    AllocationFee(Perbill),
+    /// Smallest amount an oracle can allocate at once
+    MinimumAllocation(Balance),
+    /// How many transactions a non exempted account can get included per block
+    MaxTransactionsPerBlock(u32),
+    /// How many feeless calls an account can make per quota period
+    FeelessQuota(u32),
+    /// Share of the transaction fees going to the company reserve, the rest goes
+    /// to the block author
+    ReserveFeeShare(Perbill),
+    /// Whether balance transfers are allowed, used to phase the token launch
+    TransfersEnabled(bool),
+}
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Currency used to express balance parameters
+    type Currency: Currency<Self::AccountId>;
+    /// Origin that can update the parameters
+    type UpdateOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Parameters {
+        pub AllocationFee get(fn allocation_fee): Perbill = Perbill::from_percent(20);
+        pub MinimumAllocation get(fn minimum_allocation): BalanceOf<T>;
+        pub MaxTransactionsPerBlock get(fn max_transactions_per_block): u32 = 5;
+        pub FeelessQuota get(fn feeless_quota): u32 = 10;
+        pub ReserveFeeShare get(fn reserve_fee_share): Perbill = Perbill::from_percent(20);
+        pub TransfersEnabled get(fn transfers_enabled): bool = true;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        Balance = BalanceOf<T>,
+    {
+        /// A parameter was updated to the given value
+        ParameterSet(ParameterValue<Balance>),
+    }
+);
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
+
+        /// Update the value of a parameter
+        #[weight = (T::WeightInfo::set(), DispatchClass::Operational)]
+        pub fn set(origin, value: ParameterValue<BalanceOf<T>>) -> DispatchResult {
+            T::UpdateOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+
+            match value.clone() {
+                ParameterValue::AllocationFee(fee) => AllocationFee::put(fee),
+                ParameterValue::MinimumAllocation(amount) => <MinimumAllocation<T>>::put(amount),
+                ParameterValue::MaxTransactionsPerBlock(max) => MaxTransactionsPerBlock::put(max),
+                ParameterValue::FeelessQuota(quota) => FeelessQuota::put(quota),
+                ParameterValue::ReserveFeeShare(share) => ReserveFeeShare::put(share),
+                ParameterValue::TransfersEnabled(enabled) => TransfersEnabled::put(enabled),
+            }
+
+            Self::deposit_event(RawEvent::ParameterSet(value));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        // Parameters are independent settings, validated when they are set
+        Ok(())
+    }
+}
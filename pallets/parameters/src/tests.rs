@@ -0,0 +1,133 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, parameter_types,
+};
+use frame_system::{EnsureSignedBy, RawOrigin};
+use sp_runtime::DispatchError::BadOrigin;
+use test_utils::{impl_mock_balances, impl_mock_system};
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), pallet_balances::AccountData<u64>);
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+impl_mock_balances!(Test, ExistentialDeposit);
+
+ord_parameter_types! {
+    pub const Admin: u64 = 1;
+}
+impl Trait for Test {
+    type Event = ();
+    type Currency = pallet_balances::Module<Self>;
+    type UpdateOrigin = EnsureSignedBy<Admin, u64>;
+    type WeightInfo = ();
+}
+type Parameters = Module<Test>;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
+
+#[test]
+fn defaults_match_previous_constants() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Parameters::allocation_fee(), Perbill::from_percent(20));
+        assert_eq!(Parameters::minimum_allocation(), 0);
+        assert_eq!(Parameters::max_transactions_per_block(), 5);
+        assert_eq!(Parameters::feeless_quota(), 10);
+        assert_eq!(Parameters::reserve_fee_share(), Perbill::from_percent(20));
+        assert_eq!(Parameters::transfers_enabled(), true);
+    })
+}
+
+#[test]
+fn update_origin_can_set_parameters() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Parameters::set(
+            Origin::signed(Admin::get()),
+            ParameterValue::AllocationFee(Perbill::from_percent(5))
+        ));
+        assert_ok!(Parameters::set(
+            Origin::signed(Admin::get()),
+            ParameterValue::MinimumAllocation(42)
+        ));
+        assert_ok!(Parameters::set(
+            Origin::signed(Admin::get()),
+            ParameterValue::MaxTransactionsPerBlock(3)
+        ));
+        assert_ok!(Parameters::set(
+            Origin::signed(Admin::get()),
+            ParameterValue::FeelessQuota(1)
+        ));
+        assert_ok!(Parameters::set(
+            Origin::signed(Admin::get()),
+            ParameterValue::ReserveFeeShare(Perbill::from_percent(50))
+        ));
+        assert_ok!(Parameters::set(
+            Origin::signed(Admin::get()),
+            ParameterValue::TransfersEnabled(false)
+        ));
+
+        assert_eq!(Parameters::allocation_fee(), Perbill::from_percent(5));
+        assert_eq!(Parameters::minimum_allocation(), 42);
+        assert_eq!(Parameters::max_transactions_per_block(), 3);
+        assert_eq!(Parameters::feeless_quota(), 1);
+        assert_eq!(Parameters::reserve_fee_share(), Perbill::from_percent(50));
+        assert_eq!(Parameters::transfers_enabled(), false);
+    })
+}
+
+#[test]
+fn root_can_set_parameters() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Parameters::set(
+            RawOrigin::Root.into(),
+            ParameterValue::FeelessQuota(1)
+        ));
+        assert_eq!(Parameters::feeless_quota(), 1);
+    })
+}
+
+#[test]
+fn non_origin_can_not_set_parameters() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Parameters::set(Origin::signed(0), ParameterValue::FeelessQuota(1)),
+            BadOrigin
+        );
+    })
+}
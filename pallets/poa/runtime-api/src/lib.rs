@@ -0,0 +1,17 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_core::crypto::KeyTypeId;
+use sp_session::MembershipProof;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    /// Let light clients and bridges verify that a key belonged to a validator at a
+    /// given session. Proofs are checked against the historical session roots, which
+    /// are kept in storage and can thus be proven themselves.
+    pub trait SessionProofApi<Hash> where
+        Hash: parity_scale_codec::Codec
+    {
+        fn prove_session_membership(key_type: KeyTypeId, key: Vec<u8>) -> Option<MembershipProof>;
+        fn historical_root(session: u32) -> Option<(Hash, u32)>;
+    }
+}
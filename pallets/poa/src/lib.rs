@@ -49,6 +49,20 @@ decl_module! {
     }
 }
 
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        frame_support::ensure!(
+            Self::validators().windows(2).all(|pair| pair[0] < pair[1]),
+            "validators are not sorted or contain duplicates"
+        );
+
+        Ok(())
+    }
+}
+
 impl<T: Trait> ChangeMembers<T::AccountId> for Module<T> {
     fn change_members_sorted(
         _incoming: &[T::AccountId],
@@ -0,0 +1,200 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Provide a `SignedExtension` limiting the number of transactions a given account
+//! can get included in a single block. Since fees are kept low for IoT use cases this
+//! makes spamming the chain from a single account more expensive. Some accounts, such
+//! as oracles or validators, can be exempted.
+
+mod tests;
+
+use frame_support::{
+    decl_module, decl_storage,
+    traits::{Contains, Get},
+    weights::Weight,
+};
+use nodle_support::HousekeepingTask;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{DispatchInfoOf, One, Saturating, SignedExtension, Zero},
+    transaction_validity::{
+        InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+    },
+};
+use sp_std::{fmt::Debug, marker::PhantomData};
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    /// How many transactions a non exempted account can get included per block
+    type MaxTransactionsPerBlock: Get<u32>;
+    /// Accounts not subject to rate limiting, typically oracles and validators
+    type Exempted: Contains<Self::AccountId>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as RateLimit {
+        /// Number of transactions included for an account in a given block
+        TransactionsCount get(fn transactions_count):
+            double_map hasher(twox_64_concat) T::BlockNumber, hasher(blake2_128_concat) T::AccountId => u32;
+        /// Last block whose counters were removed
+        Cleared get(fn cleared): T::BlockNumber;
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const MaxTransactionsPerBlock: u32 = T::MaxTransactionsPerBlock::get();
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Return true if a transaction from `who` could not be included in the current
+    /// block anymore.
+    pub fn is_rate_limited(who: &T::AccountId) -> bool {
+        let now = <frame_system::Module<T>>::block_number();
+        !T::Exempted::contains(who)
+            && Self::transactions_count(now, who) >= T::MaxTransactionsPerBlock::get()
+    }
+
+    /// Record a new transaction for `who` in the current block.
+    fn note_transaction(who: &T::AccountId) {
+        if T::Exempted::contains(who) {
+            return;
+        }
+
+        let now = <frame_system::Module<T>>::block_number();
+        <TransactionsCount<T>>::mutate(now, who, |count| *count = count.saturating_add(1));
+    }
+}
+
+impl<T: Trait> HousekeepingTask<T::BlockNumber> for Module<T> {
+    fn housekeep(n: T::BlockNumber, budget: Weight) -> Weight {
+        let mut consumed = T::DbWeight::get().reads_writes(1, 1);
+        if budget < consumed {
+            return 0;
+        }
+
+        // Counters from the blocks before `n` are no longer needed, the ones we could
+        // not remove in time are removed in the following blocks
+        let last = n.saturating_sub(One::one());
+        let mut cleared = Self::cleared();
+        if cleared.is_zero() {
+            cleared = last.saturating_sub(One::one());
+        }
+        let per_block = T::DbWeight::get().writes(1);
+        while cleared < last && consumed.saturating_add(per_block) <= budget {
+            cleared = cleared.saturating_add(One::one());
+            <TransactionsCount<T>>::remove_prefix(cleared);
+            consumed = consumed.saturating_add(per_block);
+        }
+        <Cleared<T>>::put(cleared);
+
+        consumed
+    }
+}
+
+/// Reject transactions once their sender went over its per block quota.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckRateLimit<T: Trait + Send + Sync>(PhantomData<T>);
+
+impl<T: Trait + Send + Sync> CheckRateLimit<T> {
+    /// Create a new instance of the extension.
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Trait + Send + Sync> Default for CheckRateLimit<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Trait + Send + Sync> Debug for CheckRateLimit<T> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "CheckRateLimit")
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for CheckRateLimit<T> {
+    const IDENTIFIER: &'static str = "CheckRateLimit";
+    type AccountId = T::AccountId;
+    type Call = <T as frame_system::Trait>::Call;
+    type AdditionalSigned = ();
+    type Pre = ();
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    // The pool validates against the state of the parent block, whose quota says nothing
+    // about the block the transaction will be included in. Transactions are always kept
+    // in the pool and the quota is only enforced when they are included.
+    fn validate(
+        &self,
+        _who: &Self::AccountId,
+        _call: &Self::Call,
+        _info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> TransactionValidity {
+        Ok(ValidTransaction::default())
+    }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        _call: &Self::Call,
+        _info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> Result<(), TransactionValidityError> {
+        if Module::<T>::is_rate_limited(who) {
+            // `ExhaustsResources` lets the block author keep the transaction in the pool
+            // so that it can be included in a later block.
+            return Err(InvalidTransaction::ExhaustsResources.into());
+        }
+
+        Module::<T>::note_transaction(who);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        use frame_support::IterableStorageDoubleMap;
+
+        let cleared = Self::cleared();
+        frame_support::ensure!(
+            cleared.is_zero() || <TransactionsCount<T>>::iter().all(|(n, _, _)| n > cleared),
+            "counters of cleared blocks were left behind"
+        );
+
+        Ok(())
+    }
+}
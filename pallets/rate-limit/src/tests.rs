@@ -0,0 +1,179 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{assert_ok, impl_outer_origin, parameter_types};
+use sp_std::prelude::Vec;
+use test_utils::impl_mock_system;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), ());
+
+parameter_types! {
+    pub const MaxTransactionsPerBlock: u32 = 2;
+    pub const Oracle: u64 = 0;
+    pub const User: u64 = 1;
+}
+pub struct Exempted;
+impl Contains<u64> for Exempted {
+    fn sorted_members() -> Vec<u64> {
+        vec![Oracle::get()]
+    }
+}
+impl Trait for Test {
+    type MaxTransactionsPerBlock = MaxTransactionsPerBlock;
+    type Exempted = Exempted;
+}
+type RateLimit = Module<Test>;
+type System = frame_system::Module<Test>;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
+
+fn submit(who: u64) -> Result<(), TransactionValidityError> {
+    CheckRateLimit::<Test>::new().pre_dispatch(&who, &(), &(), 0)
+}
+
+#[test]
+fn transactions_within_quota_pass() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(submit(User::get()));
+        assert_ok!(submit(User::get()));
+        assert_eq!(RateLimit::transactions_count(1, User::get()), 2);
+    })
+}
+
+#[test]
+fn transactions_over_quota_are_rejected() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(submit(User::get()));
+        assert_ok!(submit(User::get()));
+        assert_eq!(
+            submit(User::get()),
+            Err(InvalidTransaction::ExhaustsResources.into())
+        );
+        assert_eq!(RateLimit::transactions_count(1, User::get()), 2);
+    })
+}
+
+#[test]
+fn exempted_accounts_are_not_limited() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        for _ in 0..10 {
+            assert_ok!(submit(Oracle::get()));
+        }
+        assert_eq!(RateLimit::transactions_count(1, Oracle::get()), 0);
+    })
+}
+
+#[test]
+fn rate_limited_accounts_are_reported() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(submit(User::get()));
+        assert!(!RateLimit::is_rate_limited(&User::get()));
+        assert_ok!(submit(User::get()));
+        assert!(RateLimit::is_rate_limited(&User::get()));
+
+        for _ in 0..10 {
+            assert_ok!(submit(Oracle::get()));
+        }
+        assert!(!RateLimit::is_rate_limited(&Oracle::get()));
+    })
+}
+
+#[test]
+fn quota_is_reset_on_next_block() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(submit(User::get()));
+        assert_ok!(submit(User::get()));
+
+        System::set_block_number(2);
+        RateLimit::housekeep(2, Weight::max_value());
+
+        assert_eq!(RateLimit::transactions_count(1, User::get()), 0);
+        assert_ok!(submit(User::get()));
+    })
+}
+
+#[test]
+fn counters_left_behind_are_removed_later() {
+    new_test_ext().execute_with(|| {
+        for n in 1..4 {
+            System::set_block_number(n);
+            assert_ok!(submit(User::get()));
+            if n == 2 {
+                RateLimit::housekeep(2, Weight::max_value());
+            }
+        }
+
+        // Housekeeping did not run in block 3
+        System::set_block_number(4);
+        RateLimit::housekeep(4, Weight::max_value());
+
+        for n in 1..4 {
+            assert_eq!(RateLimit::transactions_count(n, User::get()), 0);
+        }
+        assert_eq!(RateLimit::cleared(), 3);
+    })
+}
+
+#[test]
+fn over_quota_transactions_stay_valid_in_the_pool() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(submit(User::get()));
+        assert_ok!(submit(User::get()));
+        assert!(RateLimit::is_rate_limited(&User::get()));
+
+        // Bursts, including transactions with future nonces, are only limited at
+        // inclusion time and are kept in the pool for later blocks.
+        for _ in 0..10 {
+            assert_ok!(CheckRateLimit::<Test>::new().validate(&User::get(), &(), &(), 0));
+        }
+        assert_eq!(
+            submit(User::get()),
+            Err(InvalidTransaction::ExhaustsResources.into())
+        );
+    })
+}
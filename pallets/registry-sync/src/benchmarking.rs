@@ -0,0 +1,59 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Registry sync pallet benchmarks
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::UnfilteredDispatchable;
+use sp_std::prelude::*;
+
+const SEED: u32 = 0;
+
+benchmarks! {
+    _ { }
+
+    set_registry_key {
+        let key: T::AccountId = account("registry", 0, SEED);
+        let call = Call::<T>::set_registry_key(key.clone());
+        let origin = T::RegistrarOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+    verify {
+        assert_eq!(Module::<T>::registry_key(), Some(key));
+    }
+
+    // Snapshots carry a signature of the registry which benchmarks can not produce for
+    // any signature scheme, `sync_oracles` and `sync_devices` are estimated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_set_registry_key::<Test>());
+        });
+    }
+}
@@ -0,0 +1,314 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Keep the oracles set and the allowlist of devices in sync with an external registry,
+//! for organizations managing membership in their own systems. The registry signs
+//! snapshots of its changes: the members added and removed along with its root before
+//! and after them. The registrar origin submits the snapshots, each of them is applied
+//! in full or not at all.
+//!
+//! Roots are opaque to the chain, they only chain the snapshots so that every snapshot
+//! is applied once and in order.
+//!
+//! The module resolves devices like `Devices` does, restricted to the allowlist once
+//! the registry synced one. Pallets paying or trusting devices should be given the
+//! module as their device resolver.
+
+mod benchmarking;
+pub mod weights;
+
+#[cfg(test)]
+mod tests;
+
+pub use weights::WeightInfo;
+
+use chain_primitives::DeviceResolver;
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{EnsureOrigin, Get},
+    Parameter,
+};
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{IdentifyAccount, Verify},
+    DispatchResult, RuntimeDebug,
+};
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+/// Signed by the registry along with its snapshots, so that they can not be mistaken
+/// for anything else.
+pub const SNAPSHOT_CONTEXT: &[u8] = b"nodle/registry-sync";
+
+pub type SnapshotOf<T, Member> = Snapshot<<T as frame_system::Trait>::Hash, Member>;
+pub type OracleSnapshotOf<T> = SnapshotOf<T, <T as frame_system::Trait>::AccountId>;
+pub type DeviceSnapshotOf<T> = SnapshotOf<T, <T as Trait>::DeviceId>;
+
+/// Sets the registry maintains, snapshots are signed for one of them only.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Registry {
+    Oracles,
+    Devices,
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum Change<Member> {
+    Add(Member),
+    Remove(Member),
+}
+
+/// Changes made to a set of the registry.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Snapshot<Hash, Member> {
+    /// Root of the registry the changes apply to
+    pub previous: Hash,
+    /// Root of the registry once the changes are applied
+    pub root: Hash,
+    pub changes: Vec<Change<Member>>,
+}
+
+/// A set of accounts maintained by another pallet, replaced at once when synced.
+pub trait SyncedSet<AccountId> {
+    fn sorted_members() -> Vec<AccountId>;
+
+    /// Replace the members of the set, nothing should be changed if this fails.
+    fn reset_members(members: &[AccountId]) -> DispatchResult;
+}
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Origin choosing the key of the registry and submitting its snapshots
+    type RegistrarOrigin: EnsureOrigin<Self::Origin>;
+    /// Key the registry signs its snapshots with, it identifies an account
+    type Public: IdentifyAccount<AccountId = Self::AccountId>;
+    /// Signature of the registry over a snapshot
+    type Signature: Parameter + Verify<Signer = Self::Public>;
+    /// The oracles set
+    type Oracles: SyncedSet<Self::AccountId>;
+    /// Identifier of the devices which can be allowed
+    type DeviceId: Parameter + Ord;
+    /// Devices known to the chain, the allowlist restricts them
+    type Devices: DeviceResolver<Self::DeviceId, Self::AccountId>;
+    /// Maximum number of changes in a snapshot
+    type MaxChanges: Get<u32>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as RegistrySync {
+        /// Account of the key snapshots must be signed with
+        RegistryKey get(fn registry_key): Option<T::AccountId>;
+        /// Root of the registry after the last snapshot applied to each set
+        Roots get(fn root): map hasher(twox_64_concat) Registry => T::Hash;
+        /// Devices the registry allows, only enforced once a device snapshot was applied
+        AllowedDevices get(fn is_device_allowed): map hasher(blake2_128_concat) T::DeviceId => bool;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        Hash = <T as frame_system::Trait>::Hash,
+        DeviceId = <T as Trait>::DeviceId,
+    {
+        /// Snapshots must now be signed by this account
+        RegistryKeySet(AccountId),
+        OracleAdded(AccountId),
+        OracleRemoved(AccountId),
+        DeviceAllowed(DeviceId),
+        DeviceDisallowed(DeviceId),
+        /// A snapshot was applied to a set, the registry is now at this root
+        SnapshotApplied(Registry, Hash),
+    }
+);
+
+decl_error! {
+    /// Errors for the registry sync pallet.
+    pub enum Error for Module<T: Trait> {
+        /// No key was set for the registry yet
+        NoRegistryKey,
+        /// The snapshot was not signed by the registry
+        InvalidSignature,
+        /// The snapshot does not apply to the current root of the registry
+        OutdatedSnapshot,
+        /// The snapshot holds more changes than allowed
+        TooManyChanges,
+        /// A member added by the snapshot is already part of the set
+        AlreadyMember,
+        /// A member removed by the snapshot is not part of the set
+        NotAMember,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+        fn deposit_event() = default;
+
+        const MaxChanges: u32 = T::MaxChanges::get();
+
+        /// Set the account of the key the registry signs its snapshots with.
+        #[weight = T::WeightInfo::set_registry_key()]
+        pub fn set_registry_key(origin, key: T::AccountId) -> DispatchResult {
+            T::RegistrarOrigin::ensure_origin(origin)?;
+
+            <RegistryKey<T>>::put(&key);
+            Self::deposit_event(RawEvent::RegistryKeySet(key));
+            Ok(())
+        }
+
+        /// Add and remove oracles as listed in a snapshot signed by the registry.
+        #[weight = T::WeightInfo::sync_oracles(snapshot.changes.len() as u32)]
+        pub fn sync_oracles(origin, snapshot: OracleSnapshotOf<T>, signature: T::Signature) -> DispatchResult {
+            T::RegistrarOrigin::ensure_origin(origin)?;
+            Self::check_snapshot(Registry::Oracles, &snapshot, &signature)?;
+
+            let mut members = T::Oracles::sorted_members();
+            for change in snapshot.changes.iter() {
+                match change {
+                    Change::Add(who) => {
+                        let position = members.binary_search(who).err().ok_or(Error::<T>::AlreadyMember)?;
+                        members.insert(position, who.clone());
+                    }
+                    Change::Remove(who) => {
+                        let position = members.binary_search(who).map_err(|_| Error::<T>::NotAMember)?;
+                        members.remove(position);
+                    }
+                }
+            }
+            // Nothing was written so far, the set is replaced at once
+            T::Oracles::reset_members(&members)?;
+
+            <Roots<T>>::insert(Registry::Oracles, snapshot.root);
+            for change in snapshot.changes {
+                Self::deposit_event(match change {
+                    Change::Add(who) => RawEvent::OracleAdded(who),
+                    Change::Remove(who) => RawEvent::OracleRemoved(who),
+                });
+            }
+            Self::deposit_event(RawEvent::SnapshotApplied(Registry::Oracles, snapshot.root));
+            Ok(())
+        }
+
+        /// Allow and disallow devices as listed in a snapshot signed by the registry.
+        #[weight = T::WeightInfo::sync_devices(snapshot.changes.len() as u32)]
+        pub fn sync_devices(origin, snapshot: DeviceSnapshotOf<T>, signature: T::Signature) -> DispatchResult {
+            T::RegistrarOrigin::ensure_origin(origin)?;
+            Self::check_snapshot(Registry::Devices, &snapshot, &signature)?;
+
+            // Changes are checked against the ones before them before anything is written
+            let mut allowed = BTreeMap::new();
+            for change in snapshot.changes.iter() {
+                let (device, allow) = match change {
+                    Change::Add(device) => (device, true),
+                    Change::Remove(device) => (device, false),
+                };
+                let was_allowed = allowed
+                    .get(device)
+                    .copied()
+                    .unwrap_or_else(|| Self::is_device_allowed(device));
+                match (was_allowed, allow) {
+                    (true, true) => return Err(Error::<T>::AlreadyMember.into()),
+                    (false, false) => return Err(Error::<T>::NotAMember.into()),
+                    _ => allowed.insert(device.clone(), allow),
+                };
+            }
+
+            for (device, allow) in allowed {
+                match allow {
+                    true => <AllowedDevices<T>>::insert(device, true),
+                    false => <AllowedDevices<T>>::remove(device),
+                }
+            }
+            <Roots<T>>::insert(Registry::Devices, snapshot.root);
+            for change in snapshot.changes {
+                Self::deposit_event(match change {
+                    Change::Add(device) => RawEvent::DeviceAllowed(device),
+                    Change::Remove(device) => RawEvent::DeviceDisallowed(device),
+                });
+            }
+            Self::deposit_event(RawEvent::SnapshotApplied(Registry::Devices, snapshot.root));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Payload the registry signs for a snapshot of one of its sets.
+    pub fn snapshot_payload<Member: Encode>(
+        registry: Registry,
+        snapshot: &SnapshotOf<T, Member>,
+    ) -> Vec<u8> {
+        (SNAPSHOT_CONTEXT, registry, snapshot).encode()
+    }
+
+    fn check_snapshot<Member: Encode>(
+        registry: Registry,
+        snapshot: &SnapshotOf<T, Member>,
+        signature: &T::Signature,
+    ) -> DispatchResult {
+        ensure!(
+            snapshot.changes.len() as u32 <= T::MaxChanges::get(),
+            Error::<T>::TooManyChanges
+        );
+        ensure!(
+            snapshot.previous == Self::root(registry),
+            Error::<T>::OutdatedSnapshot
+        );
+        let key = Self::registry_key().ok_or(Error::<T>::NoRegistryKey)?;
+        ensure!(
+            signature.verify(&Self::snapshot_payload(registry, snapshot)[..], &key),
+            Error::<T>::InvalidSignature
+        );
+        Ok(())
+    }
+}
+
+impl<T: Trait> DeviceResolver<T::DeviceId, T::AccountId> for Module<T> {
+    fn payout_account(device: &T::DeviceId) -> Option<T::AccountId> {
+        // Devices are not restricted until the registry manages them
+        let synced = Self::root(Registry::Devices) != T::Hash::default();
+        if synced && !Self::is_device_allowed(device) {
+            return None;
+        }
+
+        T::Devices::payout_account(device)
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_device(device: &T::DeviceId, owner: &T::AccountId) {
+        T::Devices::add_device(device, owner);
+        <AllowedDevices<T>>::insert(device, true);
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        // Roots and allowed devices are copied as is from the registry
+        Ok(())
+    }
+}
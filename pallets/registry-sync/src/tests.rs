@@ -0,0 +1,278 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, parameter_types,
+};
+use frame_system::EnsureSignedBy;
+use sp_core::H256;
+use sp_runtime::{
+    testing::{TestSignature, UintAuthorityId},
+    DispatchError::BadOrigin,
+};
+use std::cell::RefCell;
+use test_utils::impl_mock_system;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), ());
+
+thread_local! {
+    static ORACLES: RefCell<Vec<u64>> = RefCell::new(vec![ORACLE]);
+}
+
+/// Refuses to grow past three oracles, as a committee size bound would.
+pub struct Oracles;
+impl SyncedSet<u64> for Oracles {
+    fn sorted_members() -> Vec<u64> {
+        ORACLES.with(|o| o.borrow().clone())
+    }
+
+    fn reset_members(members: &[u64]) -> DispatchResult {
+        ensure!(members.len() <= 3, "too many oracles");
+        ORACLES.with(|o| *o.borrow_mut() = members.to_vec());
+        Ok(())
+    }
+}
+
+/// Every device is owned by the account with the same identifier.
+pub struct Devices;
+impl DeviceResolver<u64, u64> for Devices {
+    fn payout_account(device: &u64) -> Option<u64> {
+        Some(*device)
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_device(_device: &u64, _owner: &u64) {}
+}
+
+ord_parameter_types! {
+    pub const Registrar: u64 = 1;
+}
+parameter_types! {
+    pub const MaxChanges: u32 = 4;
+}
+impl Trait for Test {
+    type Event = ();
+    type RegistrarOrigin = EnsureSignedBy<Registrar, u64>;
+    type Public = UintAuthorityId;
+    type Signature = TestSignature;
+    type Oracles = Oracles;
+    type DeviceId = u64;
+    type Devices = Devices;
+    type MaxChanges = MaxChanges;
+    type WeightInfo = ();
+}
+type RegistrySync = Module<Test>;
+type Errors = Error<Test>;
+
+const REGISTRY_KEY: u64 = 2;
+const ORACLE: u64 = 10;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    ORACLES.with(|o| *o.borrow_mut() = vec![ORACLE]);
+    frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
+
+fn snapshot<Member>(registry: Registry, changes: Vec<Change<Member>>) -> Snapshot<H256, Member> {
+    let previous = RegistrySync::root(registry);
+    Snapshot {
+        previous,
+        root: H256::from_low_u64_be(previous.to_low_u64_be() + 1),
+        changes,
+    }
+}
+
+fn sign<Member: Encode>(registry: Registry, snapshot: &Snapshot<H256, Member>) -> TestSignature {
+    TestSignature(
+        REGISTRY_KEY,
+        RegistrySync::snapshot_payload(registry, snapshot),
+    )
+}
+
+fn sync_oracles(changes: Vec<Change<u64>>) -> DispatchResult {
+    let snapshot = snapshot(Registry::Oracles, changes);
+    let signature = sign(Registry::Oracles, &snapshot);
+    RegistrySync::sync_oracles(Origin::signed(Registrar::get()), snapshot, signature)
+}
+
+fn sync_devices(changes: Vec<Change<u64>>) -> DispatchResult {
+    let snapshot = snapshot(Registry::Devices, changes);
+    let signature = sign(Registry::Devices, &snapshot);
+    RegistrySync::sync_devices(Origin::signed(Registrar::get()), snapshot, signature)
+}
+
+fn set_registry_key() {
+    assert_ok!(RegistrySync::set_registry_key(
+        Origin::signed(Registrar::get()),
+        REGISTRY_KEY
+    ));
+}
+
+#[test]
+fn registrar_sets_the_registry_key() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(sync_oracles(vec![]), Errors::NoRegistryKey);
+        assert_noop!(
+            RegistrySync::set_registry_key(Origin::signed(REGISTRY_KEY), REGISTRY_KEY),
+            BadOrigin
+        );
+
+        set_registry_key();
+        assert_eq!(RegistrySync::registry_key(), Some(REGISTRY_KEY));
+    })
+}
+
+#[test]
+fn oracles_are_synced() {
+    new_test_ext().execute_with(|| {
+        set_registry_key();
+        assert_ok!(sync_oracles(vec![
+            Change::Add(12),
+            Change::Add(11),
+            Change::Remove(ORACLE),
+        ]));
+
+        assert_eq!(Oracles::sorted_members(), vec![11, 12]);
+        assert_eq!(
+            RegistrySync::root(Registry::Oracles),
+            H256::from_low_u64_be(1)
+        );
+        assert_eq!(RegistrySync::root(Registry::Devices), H256::zero());
+    })
+}
+
+#[test]
+fn snapshots_are_applied_in_full_or_not_at_all() {
+    new_test_ext().execute_with(|| {
+        set_registry_key();
+        assert_noop!(
+            sync_oracles(vec![Change::Add(11), Change::Add(ORACLE)]),
+            Errors::AlreadyMember
+        );
+        assert_noop!(sync_oracles(vec![Change::Remove(11)]), Errors::NotAMember);
+        assert_noop!(
+            sync_oracles(vec![Change::Add(11), Change::Add(12), Change::Add(13)]),
+            "too many oracles"
+        );
+
+        assert_ok!(sync_devices(vec![Change::Add(1), Change::Add(2)]));
+        assert_noop!(
+            sync_devices(vec![Change::Remove(1), Change::Add(3), Change::Remove(1)]),
+            Errors::NotAMember
+        );
+        assert_noop!(
+            sync_devices(vec![Change::Add(3); 5]),
+            Errors::TooManyChanges
+        );
+    })
+}
+
+#[test]
+fn devices_are_synced() {
+    new_test_ext().execute_with(|| {
+        set_registry_key();
+        assert_ok!(sync_devices(vec![Change::Add(1), Change::Add(2)]));
+        assert_ok!(sync_devices(vec![
+            Change::Remove(1),
+            Change::Add(3),
+            Change::Add(1),
+            Change::Remove(2),
+        ]));
+
+        assert!(RegistrySync::is_device_allowed(1));
+        assert!(!RegistrySync::is_device_allowed(2));
+        assert!(RegistrySync::is_device_allowed(3));
+        assert_eq!(
+            RegistrySync::root(Registry::Devices),
+            H256::from_low_u64_be(2)
+        );
+    })
+}
+
+#[test]
+fn devices_are_restricted_to_the_allowlist_once_synced() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(RegistrySync::payout_account(&1), Some(1));
+        assert_eq!(RegistrySync::payout_account(&2), Some(2));
+
+        set_registry_key();
+        assert_ok!(sync_devices(vec![Change::Add(1)]));
+        assert_eq!(RegistrySync::payout_account(&1), Some(1));
+        assert_eq!(RegistrySync::payout_account(&2), None);
+
+        assert_ok!(sync_devices(vec![Change::Remove(1)]));
+        assert_eq!(RegistrySync::payout_account(&1), None);
+    })
+}
+
+#[test]
+fn snapshots_must_be_signed_by_the_registry() {
+    new_test_ext().execute_with(|| {
+        set_registry_key();
+        let changes = vec![Change::Add(11)];
+        let snapshot = snapshot(Registry::Oracles, changes);
+
+        let forged = TestSignature(
+            ORACLE,
+            RegistrySync::snapshot_payload(Registry::Oracles, &snapshot),
+        );
+        assert_noop!(
+            RegistrySync::sync_oracles(Origin::signed(Registrar::get()), snapshot.clone(), forged),
+            Errors::InvalidSignature
+        );
+
+        // Snapshots of the devices can not be replayed on the oracles and conversely
+        let devices = sign(Registry::Devices, &snapshot);
+        assert_noop!(
+            RegistrySync::sync_oracles(Origin::signed(Registrar::get()), snapshot.clone(), devices),
+            Errors::InvalidSignature
+        );
+
+        let signature = sign(Registry::Oracles, &snapshot);
+        assert_noop!(
+            RegistrySync::sync_oracles(Origin::signed(ORACLE), snapshot.clone(), signature.clone()),
+            BadOrigin
+        );
+        assert_ok!(RegistrySync::sync_oracles(
+            Origin::signed(Registrar::get()),
+            snapshot.clone(),
+            signature.clone()
+        ));
+        assert_noop!(
+            RegistrySync::sync_oracles(Origin::signed(Registrar::get()), snapshot, signature),
+            Errors::OutdatedSnapshot
+        );
+    })
+}
@@ -0,0 +1,294 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Accounting of the relayers of a bridge. Relayers bond `RelayerBond` to be allowed
+//! to vote, the bridge reports their votes through `BridgeRelayers` and every
+//! `RewardPeriod` up to `RewardPerPeriod` is taken from the budget account and split
+//! among the relayers in proportion to the votes they cast during the period.
+//!
+//! Votes proven false are ruled on by `SlashOrigin`, which slashes the whole bond of
+//! the relayer and removes it.
+//!
+//! The pallet is not part of the runtime until the bridge reporting the votes is,
+//! its weights will be benchmarked then.
+
+#[cfg(test)]
+mod tests;
+
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{
+        Currency, EnsureOrigin, ExistenceRequirement, Get, Imbalance, OnUnbalanced,
+        ReservableCurrency,
+    },
+    weights::Weight,
+    IterableStorageMap,
+};
+use frame_system::ensure_signed;
+use nodle_support::{BridgeRelayers, HousekeepingTask, WithAccountId};
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{Saturating, Zero},
+    DispatchResult, Perbill, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+
+/// A bonded relayer.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Relayer<Balance> {
+    /// Bond reserved from the relayer
+    pub bond: Balance,
+    /// Votes cast since the last payout
+    pub votes: u32,
+}
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// Bond reserved from relayers
+    type RelayerBond: Get<BalanceOf<Self>>;
+    /// Maximum number of relayers, bounds the cost of payouts
+    type MaxRelayers: Get<u32>;
+    /// Account rewards are paid from
+    type Budget: WithAccountId<Self::AccountId>;
+    /// Number of blocks between payouts
+    type RewardPeriod: Get<Self::BlockNumber>;
+    /// Amount split among the relayers at every payout
+    type RewardPerPeriod: Get<BalanceOf<Self>>;
+    /// Origin ruling on the proofs of false votes
+    type SlashOrigin: EnsureOrigin<Self::Origin>;
+    /// Receives the bonds of slashed relayers
+    type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Relayers {
+        /// Bonded relayers and their votes since the last payout
+        Relayers get(fn relayer): map hasher(blake2_128_concat) T::AccountId => Option<Relayer<BalanceOf<T>>>;
+        /// Number of bonded relayers
+        RelayerCount get(fn relayer_count): u32;
+        /// Block from which the next payout is due
+        NextPayout get(fn next_payout): T::BlockNumber;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        Balance = BalanceOf<T>,
+        Hash = <T as frame_system::Trait>::Hash,
+    {
+        /// The account bonded the given amount and became a relayer
+        RelayerBonded(AccountId, Balance),
+        /// The relayer left and got its bond back, its votes since the last payout
+        /// are not rewarded
+        RelayerUnbonded(AccountId),
+        /// The relayer was rewarded the given amount for the given number of votes
+        RelayerRewarded(AccountId, Balance, u32),
+        /// The relayer was found to have cast a false vote, identified by the given
+        /// evidence, the given amount was slashed from its bond
+        RelayerSlashed(AccountId, Balance, Hash),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// The account is already a relayer
+        AlreadyRelayer,
+        /// The account is not a relayer
+        NotRelayer,
+        /// No more relayers can bond
+        TooManyRelayers,
+        /// The account can not afford the bond
+        InsufficientBond,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const RelayerBond: BalanceOf<T> = T::RelayerBond::get();
+        const MaxRelayers: u32 = T::MaxRelayers::get();
+        const RewardPeriod: T::BlockNumber = T::RewardPeriod::get();
+        const RewardPerPeriod: BalanceOf<T> = T::RewardPerPeriod::get();
+
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Become a relayer, `RelayerBond` is reserved until the caller unbonds
+        #[weight = 40_000_000 + T::DbWeight::get().reads_writes(3, 3)]
+        pub fn bond(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!<Relayers<T>>::contains_key(&who), Error::<T>::AlreadyRelayer);
+            let count = Self::relayer_count();
+            ensure!(count < T::MaxRelayers::get(), Error::<T>::TooManyRelayers);
+
+            let bond = T::RelayerBond::get();
+            T::Currency::reserve(&who, bond).map_err(|_| Error::<T>::InsufficientBond)?;
+            <Relayers<T>>::insert(&who, Relayer { bond, votes: 0 });
+            RelayerCount::put(count.saturating_add(1));
+
+            Self::deposit_event(RawEvent::RelayerBonded(who, bond));
+            Ok(())
+        }
+
+        /// Stop relaying and get the bond back, votes cast since the last payout are
+        /// forfeited
+        #[weight = 35_000_000 + T::DbWeight::get().reads_writes(3, 3)]
+        pub fn unbond(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let relayer = Self::relayer(&who).ok_or(Error::<T>::NotRelayer)?;
+
+            Self::remove_relayer(&who);
+            T::Currency::unreserve(&who, relayer.bond);
+
+            Self::deposit_event(RawEvent::RelayerUnbonded(who));
+            Ok(())
+        }
+
+        /// Slash the whole bond of `relayer` for casting a false vote, `evidence`
+        /// identifies the proof the ruling is based on
+        #[weight = 45_000_000 + T::DbWeight::get().reads_writes(3, 3)]
+        pub fn slash(origin, relayer: T::AccountId, evidence: T::Hash) -> DispatchResult {
+            T::SlashOrigin::ensure_origin(origin)?;
+            let info = Self::relayer(&relayer).ok_or(Error::<T>::NotRelayer)?;
+
+            Self::remove_relayer(&relayer);
+            let (slashed, _) = T::Currency::slash_reserved(&relayer, info.bond);
+            let amount = slashed.peek();
+            T::Slashed::on_unbalanced(slashed);
+
+            Self::deposit_event(RawEvent::RelayerSlashed(relayer, amount, evidence));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Weight of a payout to `relayers` relayers
+    pub fn pay_relayers_weight(relayers: u32) -> Weight {
+        (30_000_000 as Weight)
+            .saturating_add((65_000_000 as Weight).saturating_mul(relayers.into()))
+            .saturating_add(T::DbWeight::get().reads_writes(
+                3u64.saturating_add(2u64.saturating_mul(relayers.into())),
+                2u64.saturating_add(2u64.saturating_mul(relayers.into())),
+            ))
+    }
+
+    fn remove_relayer(who: &T::AccountId) {
+        <Relayers<T>>::remove(who);
+        RelayerCount::mutate(|count| *count = count.saturating_sub(1));
+    }
+
+    /// Split the rewards of the period among the relayers in proportion to their
+    /// votes and reset them.
+    fn pay_relayers() {
+        let relayers: Vec<(T::AccountId, Relayer<BalanceOf<T>>)> = <Relayers<T>>::iter()
+            .filter(|(_, relayer)| relayer.votes > 0)
+            .collect();
+        let total_votes = relayers
+            .iter()
+            .fold(0u32, |acc, (_, relayer)| acc.saturating_add(relayer.votes));
+        if total_votes.is_zero() {
+            return;
+        }
+
+        let budget = T::Budget::account_id();
+        let available =
+            T::Currency::free_balance(&budget).saturating_sub(T::Currency::minimum_balance());
+        let pool = T::RewardPerPeriod::get().min(available);
+        for (who, relayer) in relayers {
+            <Relayers<T>>::insert(
+                &who,
+                Relayer {
+                    bond: relayer.bond,
+                    votes: 0,
+                },
+            );
+
+            let amount = Perbill::from_rational_approximation(relayer.votes, total_votes) * pool;
+            if amount.is_zero() {
+                continue;
+            }
+            if T::Currency::transfer(&budget, &who, amount, ExistenceRequirement::KeepAlive).is_ok()
+            {
+                Self::deposit_event(RawEvent::RelayerRewarded(who, amount, relayer.votes));
+            }
+        }
+    }
+}
+
+impl<T: Trait> BridgeRelayers<T::AccountId> for Module<T> {
+    fn is_relayer(who: &T::AccountId) -> bool {
+        <Relayers<T>>::contains_key(who)
+    }
+
+    fn note_vote(who: &T::AccountId) {
+        <Relayers<T>>::mutate(who, |relayer| {
+            if let Some(relayer) = relayer {
+                relayer.votes = relayer.votes.saturating_add(1);
+            }
+        });
+    }
+}
+
+impl<T: Trait> HousekeepingTask<T::BlockNumber> for Module<T> {
+    fn housekeep(n: T::BlockNumber, budget: Weight) -> Weight {
+        let consumed = T::DbWeight::get().reads(1);
+        if budget < consumed {
+            return 0;
+        }
+        if n < Self::next_payout() {
+            return consumed;
+        }
+
+        // Payouts are made at once, wait for a block with enough room
+        let weight = Self::pay_relayers_weight(Self::relayer_count());
+        if budget < consumed.saturating_add(weight) {
+            return consumed;
+        }
+
+        Self::pay_relayers();
+        <NextPayout<T>>::put(n.saturating_add(T::RewardPeriod::get()));
+        consumed.saturating_add(weight)
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        ensure!(
+            <Relayers<T>>::iter().count() as u32 == Self::relayer_count(),
+            "relayer count does not match the bonded relayers"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, parameter_types,
+};
+use frame_system::EnsureSignedBy;
+use sp_core::H256;
+use sp_runtime::DispatchError::BadOrigin;
+use test_utils::{impl_mock_balances, impl_mock_system};
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), pallet_balances::AccountData<u64>);
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+impl_mock_balances!(Test, ExistentialDeposit);
+
+pub struct Budget;
+impl WithAccountId<u64> for Budget {
+    fn account_id() -> u64 {
+        BUDGET
+    }
+}
+
+ord_parameter_types! {
+    pub const Judge: u64 = 1;
+}
+parameter_types! {
+    pub const RelayerBond: u64 = 10;
+    pub const MaxRelayers: u32 = 3;
+    pub const RewardPeriod: u64 = 10;
+    pub const RewardPerPeriod: u64 = 100;
+}
+impl Trait for Test {
+    type Event = ();
+    type Currency = pallet_balances::Module<Self>;
+    type RelayerBond = RelayerBond;
+    type MaxRelayers = MaxRelayers;
+    type Budget = Budget;
+    type RewardPeriod = RewardPeriod;
+    type RewardPerPeriod = RewardPerPeriod;
+    type SlashOrigin = EnsureSignedBy<Judge, u64>;
+    type Slashed = ();
+}
+type Relayers = Module<Test>;
+type Balances = pallet_balances::Module<Test>;
+type Errors = Error<Test>;
+
+const JUDGE: u64 = 1;
+const RELAYERS: [u64; 3] = [2, 3, 4];
+const EXTRA: u64 = 5;
+const POOR: u64 = 6;
+const BUDGET: u64 = 99;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![
+            (RELAYERS[0], 100),
+            (RELAYERS[1], 100),
+            (RELAYERS[2], 100),
+            (EXTRA, 100),
+            (POOR, 5),
+            (BUDGET, 1_001),
+        ],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+    storage.into()
+}
+
+fn bond_all() {
+    for relayer in RELAYERS.iter() {
+        assert_ok!(Relayers::bond(Origin::signed(*relayer)));
+    }
+}
+
+fn vote(who: u64, times: u32) {
+    for _ in 0..times {
+        Relayers::note_vote(&who);
+    }
+}
+
+#[test]
+fn relayers_post_a_bond() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Relayers::bond(Origin::signed(RELAYERS[0])));
+        assert_eq!(Balances::reserved_balance(RELAYERS[0]), 10);
+        assert_eq!(
+            Relayers::relayer(RELAYERS[0]),
+            Some(Relayer { bond: 10, votes: 0 })
+        );
+        assert_eq!(Relayers::relayer_count(), 1);
+        assert!(Relayers::is_relayer(&RELAYERS[0]));
+
+        assert_noop!(
+            Relayers::bond(Origin::signed(RELAYERS[0])),
+            Errors::AlreadyRelayer
+        );
+        assert_noop!(
+            Relayers::bond(Origin::signed(POOR)),
+            Errors::InsufficientBond
+        );
+
+        assert_ok!(Relayers::bond(Origin::signed(RELAYERS[1])));
+        assert_ok!(Relayers::bond(Origin::signed(RELAYERS[2])));
+        assert_noop!(
+            Relayers::bond(Origin::signed(EXTRA)),
+            Errors::TooManyRelayers
+        );
+    })
+}
+
+#[test]
+fn relayers_can_unbond() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Relayers::unbond(Origin::signed(RELAYERS[0])),
+            Errors::NotRelayer
+        );
+
+        bond_all();
+        assert_ok!(Relayers::unbond(Origin::signed(RELAYERS[0])));
+        assert_eq!(Balances::reserved_balance(RELAYERS[0]), 0);
+        assert_eq!(Balances::free_balance(RELAYERS[0]), 100);
+        assert_eq!(Relayers::relayer_count(), 2);
+        assert!(!Relayers::is_relayer(&RELAYERS[0]));
+
+        // A slot was freed
+        assert_ok!(Relayers::bond(Origin::signed(EXTRA)));
+    })
+}
+
+#[test]
+fn only_relayers_votes_are_counted() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Relayers::bond(Origin::signed(RELAYERS[0])));
+        vote(RELAYERS[0], 2);
+        vote(EXTRA, 2);
+
+        assert_eq!(Relayers::relayer(RELAYERS[0]).map(|r| r.votes), Some(2));
+        assert_eq!(Relayers::relayer(EXTRA), None);
+    })
+}
+
+#[test]
+fn rewards_are_split_by_votes_every_period() {
+    new_test_ext().execute_with(|| {
+        bond_all();
+        vote(RELAYERS[0], 3);
+        vote(RELAYERS[1], 1);
+
+        Relayers::housekeep(0, Weight::max_value());
+        assert_eq!(Balances::free_balance(RELAYERS[0]), 90 + 75);
+        assert_eq!(Balances::free_balance(RELAYERS[1]), 90 + 25);
+        assert_eq!(Balances::free_balance(RELAYERS[2]), 90);
+        assert_eq!(Balances::free_balance(BUDGET), 901);
+        assert_eq!(Relayers::relayer(RELAYERS[0]).map(|r| r.votes), Some(0));
+        assert_eq!(Relayers::next_payout(), 10);
+
+        vote(RELAYERS[2], 1);
+        assert_eq!(Relayers::housekeep(9, Weight::max_value()), 0);
+        assert_eq!(Balances::free_balance(RELAYERS[2]), 90);
+
+        Relayers::housekeep(10, Weight::max_value());
+        assert_eq!(Balances::free_balance(RELAYERS[2]), 90 + 100);
+        assert_eq!(Relayers::next_payout(), 20);
+    })
+}
+
+#[test]
+fn rewards_are_capped_by_the_budget() {
+    new_test_ext().execute_with(|| {
+        bond_all();
+        vote(RELAYERS[0], 1);
+        vote(RELAYERS[1], 1);
+        Balances::make_free_balance_be(&BUDGET, 51);
+
+        Relayers::housekeep(0, Weight::max_value());
+        assert_eq!(Balances::free_balance(RELAYERS[0]), 90 + 25);
+        assert_eq!(Balances::free_balance(RELAYERS[1]), 90 + 25);
+        assert_eq!(Balances::free_balance(BUDGET), 1);
+    })
+}
+
+#[test]
+fn payouts_wait_for_enough_budget() {
+    new_test_ext().execute_with(|| {
+        bond_all();
+        vote(RELAYERS[0], 1);
+
+        Relayers::housekeep(0, 0);
+        assert_eq!(Balances::free_balance(RELAYERS[0]), 90);
+        assert_eq!(Relayers::next_payout(), 0);
+
+        assert_eq!(
+            Relayers::housekeep(1, Weight::max_value()),
+            Relayers::pay_relayers_weight(3)
+        );
+        assert_eq!(Balances::free_balance(RELAYERS[0]), 90 + 100);
+    })
+}
+
+#[test]
+fn false_votes_are_slashed() {
+    new_test_ext().execute_with(|| {
+        bond_all();
+        let evidence = H256::repeat_byte(1);
+
+        assert_noop!(
+            Relayers::slash(Origin::signed(RELAYERS[1]), RELAYERS[0], evidence),
+            BadOrigin
+        );
+        assert_noop!(
+            Relayers::slash(Origin::signed(JUDGE), EXTRA, evidence),
+            Errors::NotRelayer
+        );
+
+        assert_ok!(Relayers::slash(
+            Origin::signed(JUDGE),
+            RELAYERS[0],
+            evidence
+        ));
+        assert_eq!(Balances::reserved_balance(RELAYERS[0]), 0);
+        assert_eq!(Balances::free_balance(RELAYERS[0]), 90);
+        assert_eq!(Relayers::relayer_count(), 2);
+        assert!(!Relayers::is_relayer(&RELAYERS[0]));
+    })
+}
@@ -0,0 +1,14 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_runtime::ModuleId;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    /// List the accounts holding pallet owned funds along with the `ModuleId`
+    /// they are derived from.
+    pub trait ReserveApi<AccountId> where
+        AccountId: parity_scale_codec::Codec
+    {
+        fn accounts() -> Vec<(ModuleId, AccountId)>;
+    }
+}
@@ -22,14 +22,17 @@
 //! the company funds.
 
 mod benchmarking;
+pub mod weights;
 
 #[cfg(test)]
 mod tests;
 
+pub use weights::WeightInfo;
+
 use frame_support::{
     decl_event, decl_module, decl_storage,
     traits::{Currency, EnsureOrigin, ExistenceRequirement, Get, Imbalance, OnUnbalanced},
-    weights::GetDispatchInfo,
+    weights::{DispatchClass, GetDispatchInfo},
     Parameter,
 };
 use frame_system::{ensure_root, ensure_signed};
@@ -53,19 +56,23 @@ pub trait Trait<I: Instance = DefaultInstance>: frame_system::Trait {
     type Currency: Currency<Self::AccountId>;
     type Call: Parameter + Dispatchable<Origin = Self::Origin> + GetDispatchInfo;
     type ModuleId: Get<ModuleId>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
 }
 
 decl_storage! {
     trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as Reserve {}
     add_extra_genesis {
-        build(|_config| {
+        /// Funds minted into the reserve account at genesis, the account is always
+        /// created with at least the existential deposit.
+        config(initial_funding): BalanceOf<T, I>;
+        build(|config| {
             let our_account = &<Module<T, I>>::account_id();
+            let target = config.initial_funding.max(T::Currency::minimum_balance());
 
-            if T::Currency::free_balance(our_account) < T::Currency::minimum_balance() {
-                let _ = T::Currency::make_free_balance_be(
-                    our_account,
-                    T::Currency::minimum_balance(),
-                );
+            if T::Currency::free_balance(our_account) < target {
+                let _ = T::Currency::make_free_balance_be(our_account, target);
             }
         });
     }
@@ -94,7 +101,7 @@ decl_module! {
         fn deposit_event() = default;
 
         /// Spend `amount` funds from the reserve account to `to`.
-        #[weight = 100_000_000]
+        #[weight = (T::WeightInfo::spend(), DispatchClass::Operational)]
         pub fn spend(origin, to: T::AccountId, amount: BalanceOf<T, I>) -> DispatchResult {
             T::ExternalOrigin::try_origin(origin)
                 .map(|_| ())
@@ -108,7 +115,7 @@ decl_module! {
         }
 
         /// Deposit `amount` tokens in the treasure account
-        #[weight = 50_000_000]
+        #[weight = T::WeightInfo::tip()]
         pub fn tip(origin, amount: BalanceOf<T, I>) -> DispatchResult {
             let tipper = ensure_signed(origin)?;
 
@@ -120,7 +127,7 @@ decl_module! {
         }
 
         /// Dispatch a call as coming from the reserve account
-        #[weight = (call.get_dispatch_info().weight + 10_000, call.get_dispatch_info().class)]
+        #[weight = (call.get_dispatch_info().weight + T::WeightInfo::apply_as(), call.get_dispatch_info().class)]
         pub fn apply_as(origin, call: Box<<T as Trait<I>>::Call>) {
             T::ExternalOrigin::try_origin(origin)
                 .map(|_| ())
@@ -133,6 +140,13 @@ decl_module! {
     }
 }
 
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+    /// The identifier the reserve account is derived from.
+    pub fn module_id() -> ModuleId {
+        T::ModuleId::get()
+    }
+}
+
 impl<T: Trait<I>, I: Instance> WithAccountId<T::AccountId> for Module<T, I> {
     fn account_id() -> T::AccountId {
         T::ModuleId::get().into_account()
@@ -149,3 +163,13 @@ impl<T: Trait<I>, I: Instance> OnUnbalanced<NegativeImbalanceOf<T, I>> for Modul
         Self::deposit_event(RawEvent::Deposit(numeric_amount));
     }
 }
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        // The reserve only holds funds in its account, it has no storage of its own
+        Ok(())
+    }
+}
@@ -22,17 +22,12 @@ use super::*;
 
 use frame_support::{
     assert_noop, assert_ok, impl_outer_dispatch, impl_outer_origin, ord_parameter_types,
-    parameter_types, traits::Currency, weights::Weight,
+    parameter_types, traits::Currency,
 };
 use frame_system::{EnsureSignedBy, RawOrigin};
-use sp_core::H256;
-use sp_runtime::{
-    testing::Header,
-    traits::{BlakeTwo256, IdentityLookup},
-    DispatchError::BadOrigin,
-    Perbill,
-};
+use sp_runtime::DispatchError::BadOrigin;
 use sp_std::prelude::Box;
+use test_utils::{impl_mock_balances, impl_mock_system};
 
 impl_outer_origin! {
     pub enum Origin for Test {}
@@ -48,51 +43,8 @@ impl_outer_dispatch! {
 // configuration traits of modules we want to use.
 #[derive(Clone, Eq, PartialEq)]
 pub struct Test;
-parameter_types! {
-    pub const BlockHashCount: u64 = 250;
-    pub const MaximumBlockWeight: Weight = 1024;
-    pub const MaximumBlockLength: u32 = 2 * 1024;
-    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
-}
-impl frame_system::Trait for Test {
-    type Origin = Origin;
-    type Call = Call;
-    type Index = u64;
-    type BlockNumber = u64;
-    type Hash = H256;
-    type Hashing = BlakeTwo256;
-    type AccountId = u64;
-    type Lookup = IdentityLookup<Self::AccountId>;
-    type Header = Header;
-    type Event = ();
-    type BlockHashCount = BlockHashCount;
-    type MaximumBlockWeight = MaximumBlockWeight;
-    type MaximumBlockLength = MaximumBlockLength;
-    type AvailableBlockRatio = AvailableBlockRatio;
-    type Version = ();
-    type PalletInfo = ();
-    type AccountData = pallet_balances::AccountData<u64>;
-    type OnNewAccount = ();
-    type OnKilledAccount = ();
-    type DbWeight = ();
-    type BlockExecutionWeight = ();
-    type ExtrinsicBaseWeight = ();
-    type MaximumExtrinsicWeight = MaximumBlockWeight;
-    type BaseCallFilter = ();
-    type SystemWeightInfo = ();
-}
-parameter_types! {
-    pub const MaxLocks: u32 = 50;
-}
-impl pallet_balances::Trait for Test {
-    type Balance = u64;
-    type Event = ();
-    type DustRemoval = ();
-    type MaxLocks = MaxLocks;
-    type ExistentialDeposit = ();
-    type AccountStore = frame_system::Module<Test>;
-    type WeightInfo = ();
-}
+impl_mock_system!(Test, Origin, Call, pallet_balances::AccountData<u64>);
+impl_mock_balances!(Test, ());
 
 ord_parameter_types! {
     pub const Admin: u64 = 1;
@@ -106,6 +58,7 @@ impl Trait for Test {
     type ExternalOrigin = EnsureSignedBy<Admin, u64>;
     type Call = Call;
     type ModuleId = ReserveModuleId;
+    type WeightInfo = ();
 }
 type TestModule = Module<Test>;
 type Balances = pallet_balances::Module<Test>;
@@ -187,3 +140,20 @@ fn try_root_if_not_admin() {
         assert_ok!(TestModule::apply_as(RawOrigin::Root.into(), make_call(1)));
     })
 }
+
+#[test]
+fn genesis_funds_the_reserve() {
+    let mut t = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    GenesisConfig::<Test> {
+        initial_funding: 1_000,
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    sp_io::TestExternalities::from(t).execute_with(|| {
+        assert_eq!(Balances::free_balance(TestModule::account_id()), 1_000);
+        assert_eq!(Balances::total_issuance(), 1_000);
+    })
+}
@@ -23,6 +23,7 @@
 use super::*;
 
 use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::UnfilteredDispatchable;
 use frame_system::RawOrigin;
 use sp_runtime::traits::Bounded;
 use sp_std::{prelude::*, vec};
@@ -74,6 +75,21 @@ benchmarks! {
 
         let _ = <Module<T>>::book_slot(RawOrigin::Signed(manager.clone()).into(), certificate.clone());
     }: _(RawOrigin::Signed(manager), certificate, child)
+
+    remove_slot {
+        let u in 0 .. 1000;
+
+        let manager = register::<T>(u)?;
+        let certificate: T::CertificateId = Default::default();
+
+        // Previous runs may have left the slot behind
+        <Slots<T>>::remove(&certificate);
+        <Module<T>>::book_slot(RawOrigin::Signed(manager).into(), certificate.clone())
+            .expect("manager is a funded member");
+
+        let call = Call::<T>::remove_slot(certificate);
+        let origin = T::ForceOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
 }
 
 #[cfg(test)]
@@ -89,6 +105,7 @@ mod tests {
             assert_ok!(test_benchmark_renew_slot::<Test>());
             assert_ok!(test_benchmark_revoke_slot::<Test>());
             assert_ok!(test_benchmark_revoke_child::<Test>());
+            assert_ok!(test_benchmark_remove_slot::<Test>());
         });
     }
 }
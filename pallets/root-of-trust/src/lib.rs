@@ -21,20 +21,33 @@
 //! This module implements a Root Of Trust linked to a `membership` or `tcr` pallet which
 //! can be used to let entities represented by their `AccountId` manage certificates
 //! and off-chain certificates in Public Key Infrastructure fashion (SSL / TLS like).
+//!
+//! Booking a slot reserves a bond on top of its fee to deter sybil registrations, the
+//! bond is returned when the owner revokes the slot and slashed if the slot has to be
+//! removed by `ForceOrigin`.
 
 mod benchmarking;
 mod tests;
+pub mod weights;
 
+pub use weights::WeightInfo;
+
+use chain_primitives::DeviceResolver;
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::DispatchResult,
     ensure,
-    traits::{ChangeMembers, Currency, ExistenceRequirement, Get, OnUnbalanced, WithdrawReasons},
-    Parameter,
+    traits::{
+        ChangeMembers, Currency, EnsureOrigin, ExistenceRequirement, Get, OnUnbalanced,
+        ReservableCurrency, WithdrawReasons,
+    },
+    IterableStorageMap, Parameter,
 };
 use frame_system::{self as system, ensure_signed};
 use parity_scale_codec::{Decode, Encode};
-use sp_runtime::traits::{CheckedAdd, MaybeDisplay, MaybeSerializeDeserialize, Member};
+use sp_runtime::traits::{
+    CheckedAdd, MaybeDisplay, MaybeSerializeDeserialize, Member, Saturating, Zero,
+};
 use sp_std::{fmt::Debug, prelude::Vec};
 
 type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
@@ -57,7 +70,7 @@ pub trait Trait: system::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 
     /// The currency used to represent the voting power
-    type Currency: Currency<Self::AccountId>;
+    type Currency: ReservableCurrency<Self::AccountId>;
 
     /// How a certificate public key is represented, typically `AccountId`
     type CertificateId: Member
@@ -73,9 +86,17 @@ pub trait Trait: system::Trait {
     type SlotRenewingCost: Get<BalanceOf<Self>>;
     /// How long a certificate is considered valid
     type SlotValidity: Get<Self::BlockNumber>;
+    /// How much is reserved while a root certificate is held, returned when its owner
+    /// revokes it
+    type SlotBond: Get<BalanceOf<Self>>;
+    /// Can remove a misbehaving slot, slashing its bond
+    type ForceOrigin: EnsureOrigin<Self::Origin>;
     /// The module receiving funds paid by depositors, typically a company
     /// reserve
     type FundsCollector: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
 }
 
 decl_event!(
@@ -83,6 +104,7 @@ decl_event!(
     where
         AccountId = <T as system::Trait>::AccountId,
         CertificateId = <T as Trait>::CertificateId,
+        Balance = BalanceOf<T>,
     {
         /// A new slot has been booked
         SlotTaken(AccountId, CertificateId),
@@ -92,6 +114,8 @@ decl_event!(
         SlotRevoked(CertificateId),
         /// A child certificate was revoked
         ChildSlotRevoked(CertificateId, CertificateId),
+        /// A slot was forcibly removed and its bond slashed
+        SlotRemoved(CertificateId, Balance),
     }
 );
 
@@ -113,8 +137,10 @@ decl_error! {
 decl_storage! {
     trait Store for Module<T: Trait> as RootOfTrustModule {
         Members get(fn members): Vec<T::AccountId>;
-        Slots get(fn slots): map hasher(blake2_128_concat)
+        pub Slots get(fn slots): map hasher(blake2_128_concat)
             T::CertificateId => RootCertificate<T::AccountId, T::CertificateId, T::BlockNumber>;
+        /// Bond reserved from the owner of each slot
+        pub Bonds get(fn bonds): map hasher(blake2_128_concat) T::CertificateId => BalanceOf<T>;
     }
 }
 
@@ -124,16 +150,22 @@ decl_module! {
         fn deposit_event() = default;
 
         /// Book a certificate slot
-        #[weight = 160_000_000]
+        #[weight = T::WeightInfo::book_slot()]
         fn book_slot(origin, certificate_id: T::CertificateId) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(Self::is_member(&sender), Error::<T>::NotAMember);
             ensure!(!<Slots<T>>::contains_key(&certificate_id), Error::<T>::SlotTaken);
 
+            let bond = T::SlotBond::get();
+            T::Currency::reserve(&sender, bond).map_err(|_| Error::<T>::NotEnoughFunds)?;
             match T::Currency::withdraw(&sender, T::SlotBookingCost::get(), WithdrawReasons::all(), ExistenceRequirement::AllowDeath) {
                 Ok(imbalance) => T::FundsCollector::on_unbalanced(imbalance),
-                Err(_) => return Err(Error::<T>::NotEnoughFunds.into()),
+                Err(_) => {
+                    T::Currency::unreserve(&sender, bond);
+                    return Err(Error::<T>::NotEnoughFunds.into());
+                }
             };
+            <Bonds<T>>::insert(&certificate_id, bond);
 
             let now = <system::Module<T>>::block_number();
             <Slots<T>>::insert(&certificate_id, RootCertificate {
@@ -151,7 +183,7 @@ decl_module! {
         }
 
         /// Renew a non expired slot and make it valid for a longer time
-        #[weight = 150_000_000]
+        #[weight = T::WeightInfo::renew_slot()]
         fn renew_slot(origin, certificate_id: T::CertificateId) -> DispatchResult {
             let sender = ensure_signed(origin)?;
 
@@ -171,15 +203,17 @@ decl_module! {
             Ok(())
         }
 
-        /// Revoke a slot before it is expired thus invalidating all child certificates
-        #[weight = 75_000_000]
+        /// Revoke a slot thus invalidating all child certificates, its bond is returned.
+        /// Expired slots can be revoked too so that their owner recovers the bond
+        #[weight = T::WeightInfo::revoke_slot()]
         fn revoke_slot(origin, certificate_id: T::CertificateId) -> DispatchResult {
             let sender = ensure_signed(origin)?;
 
             let mut slot = <Slots<T>>::get(&certificate_id);
-            ensure!(Self::is_slot_valid(&slot), Error::<T>::NoLongerValid);
+            ensure!(<Slots<T>>::contains_key(&certificate_id) && !slot.revoked, Error::<T>::NoLongerValid);
             ensure!(slot.owner == sender, Error::<T>::NotTheOwner);
 
+            T::Currency::unreserve(&sender, <Bonds<T>>::take(&certificate_id));
             slot.revoked = true;
             <Slots<T>>::insert(&certificate_id, slot);
 
@@ -188,7 +222,7 @@ decl_module! {
         }
 
         /// Mark a slot's child as revoked thus invalidating it
-        #[weight = 75_000_000]
+        #[weight = T::WeightInfo::revoke_child()]
         fn revoke_child(origin, root: T::CertificateId, child: T::CertificateId) -> DispatchResult {
             let sender = ensure_signed(origin)?;
 
@@ -203,10 +237,38 @@ decl_module! {
             Self::deposit_event(RawEvent::ChildSlotRevoked(root, child));
             Ok(())
         }
+
+        /// Revoke a slot registered in bad faith, its bond is slashed and goes to
+        /// `FundsCollector`
+        #[weight = T::WeightInfo::remove_slot()]
+        fn remove_slot(origin, certificate_id: T::CertificateId) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            let mut slot = <Slots<T>>::get(&certificate_id);
+            ensure!(<Slots<T>>::contains_key(&certificate_id) && !slot.revoked, Error::<T>::NoLongerValid);
+
+            let bond = <Bonds<T>>::take(&certificate_id);
+            let (imbalance, _) = T::Currency::slash_reserved(&slot.owner, bond);
+            T::FundsCollector::on_unbalanced(imbalance);
+            slot.revoked = true;
+            <Slots<T>>::insert(&certificate_id, slot);
+
+            Self::deposit_event(RawEvent::SlotRemoved(certificate_id, bond));
+            Ok(())
+        }
     }
 }
 
 impl<T: Trait> Module<T> {
+    /// Bonds reserved from `who` for the slots it owns.
+    pub fn deposits_of(who: &T::AccountId) -> BalanceOf<T> {
+        <Slots<T>>::iter()
+            .filter(|(_, slot)| &slot.owner == who)
+            .fold(Zero::zero(), |total, (id, _)| {
+                total.saturating_add(Self::bonds(id))
+            })
+    }
+
     fn is_member(who: &T::AccountId) -> bool {
         Self::members().contains(who)
     }
@@ -255,6 +317,43 @@ impl<T: Trait> Module<T> {
     }
 }
 
+/// Devices are identified by their certificate and the root certificate it was issued
+/// under, their rewards go to the owner of the root certificate while it is valid and
+/// the device's certificate was not revoked.
+impl<T: Trait> DeviceResolver<(T::CertificateId, T::CertificateId), T::AccountId> for Module<T> {
+    fn payout_account(
+        (root, child): &(T::CertificateId, T::CertificateId),
+    ) -> Option<T::AccountId> {
+        if Self::is_child_certificate_valid(root, child) {
+            Some(<Slots<T>>::get(root).owner)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_device((root, _child): &(T::CertificateId, T::CertificateId), owner: &T::AccountId) {
+        <Members<T>>::mutate(|members| {
+            if !members.contains(owner) {
+                members.push(owner.clone());
+            }
+        });
+        let now = <system::Module<T>>::block_number();
+        <Slots<T>>::insert(
+            root,
+            RootCertificate {
+                owner: owner.clone(),
+                key: root.clone(),
+                created: now,
+                renewed: now,
+                revoked: false,
+                validity: T::SlotValidity::get(),
+                child_revocations: Vec::new(),
+            },
+        );
+    }
+}
+
 impl<T: Trait> ChangeMembers<T::AccountId> for Module<T> {
     fn change_members_sorted(
         _incoming: &[T::AccountId],
@@ -264,3 +363,26 @@ impl<T: Trait> ChangeMembers<T::AccountId> for Module<T> {
         <Members<T>>::put(new);
     }
 }
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        ensure!(
+            Self::members().windows(2).all(|pair| pair[0] < pair[1]),
+            "members are not sorted or contain duplicates"
+        );
+        for (id, slot) in <Slots<T>>::iter() {
+            ensure!(slot.key == id, "slot stored under another certificate");
+        }
+        for (id, _) in <Bonds<T>>::iter() {
+            ensure!(
+                <Slots<T>>::contains_key(&id) && !Self::slots(&id).revoked,
+                "bond kept for a missing or revoked slot"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -23,14 +23,9 @@ use super::*;
 use frame_support::{
     assert_noop, assert_ok, impl_outer_origin, parameter_types,
     traits::{Currency, OnFinalize},
-    weights::Weight,
-};
-use sp_core::H256;
-use sp_runtime::{
-    testing::Header,
-    traits::{BlakeTwo256, IdentityLookup},
-    Perbill,
 };
+use sp_runtime::Perbill;
+use test_utils::{impl_mock_balances, impl_mock_system};
 
 impl_outer_origin! {
     pub enum Origin for Test {}
@@ -41,52 +36,8 @@ impl_outer_origin! {
 // configuration traits of modules we want to use.
 #[derive(Clone, Eq, PartialEq)]
 pub struct Test;
-parameter_types! {
-    pub const BlockHashCount: u64 = 250;
-    pub const MaximumBlockWeight: Weight = 1024;
-    pub const MaximumBlockLength: u32 = 2 * 1024;
-    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
-}
-impl system::Trait for Test {
-    type Origin = Origin;
-    type Call = ();
-    type Index = u64;
-    type BlockNumber = u64;
-    type Hash = H256;
-    type Hashing = BlakeTwo256;
-    type AccountId = u64;
-    type Lookup = IdentityLookup<Self::AccountId>;
-    type Header = Header;
-    type Event = ();
-    type BlockHashCount = BlockHashCount;
-    type MaximumBlockWeight = MaximumBlockWeight;
-    type MaximumBlockLength = MaximumBlockLength;
-    type AvailableBlockRatio = AvailableBlockRatio;
-    type Version = ();
-    type PalletInfo = ();
-    type AccountData = pallet_balances::AccountData<u64>;
-    type OnNewAccount = ();
-    type OnKilledAccount = ();
-    type DbWeight = ();
-    type BlockExecutionWeight = ();
-    type ExtrinsicBaseWeight = ();
-    type MaximumExtrinsicWeight = MaximumBlockWeight;
-    type BaseCallFilter = ();
-    type SystemWeightInfo = ();
-}
-parameter_types! {
-    pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(33);
-    pub const MaxLocks: u32 = 50;
-}
-impl pallet_balances::Trait for Test {
-    type Balance = u64;
-    type Event = ();
-    type DustRemoval = ();
-    type MaxLocks = MaxLocks;
-    type AccountStore = system::Module<Test>;
-    type ExistentialDeposit = ();
-    type WeightInfo = ();
-}
+impl_mock_system!(Test, Origin, (), pallet_balances::AccountData<u64>);
+impl_mock_balances!(Test, ());
 parameter_types! {
     pub const MinimumApplicationAmount: u64 = 100;
     pub const MinimumCounterAmount: u64 = 1000;
@@ -105,11 +56,13 @@ impl pallet_tcr::Trait for Test {
     type FinalizeChallengePeriod = FinalizeChallengePeriod;
     type LoosersSlash = LoosersSlash;
     type ChangeMembers = TestModule;
+    type WeightInfo = ();
 }
 parameter_types! {
     pub const SlotBookingCost: u64 = 1000;
     pub const SlotRenewingCost: u64 = 10000;
     pub const SlotValidity: u64 = 100000;
+    pub const SlotBond: u64 = 500;
 }
 impl Trait for Test {
     type Event = ();
@@ -118,7 +71,10 @@ impl Trait for Test {
     type SlotBookingCost = SlotBookingCost;
     type SlotRenewingCost = SlotRenewingCost;
     type SlotValidity = SlotValidity;
+    type SlotBond = SlotBond;
+    type ForceOrigin = system::EnsureRoot<u64>;
     type FundsCollector = ();
+    type WeightInfo = ();
 }
 
 type BalancesModule = pallet_balances::Module<Test>;
@@ -143,7 +99,10 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 fn allocate_balances() {
     TestCurrency::make_free_balance_be(
         &ROOT_MANAGER,
-        MinimumApplicationAmount::get() + SlotBookingCost::get() + SlotRenewingCost::get(),
+        MinimumApplicationAmount::get()
+            + SlotBookingCost::get()
+            + SlotRenewingCost::get()
+            + SlotBond::get(),
     );
 }
 
@@ -776,3 +735,144 @@ fn can_not_revoke_child_if_not_valid_anymore() {
         );
     })
 }
+
+#[test]
+fn device_rewards_go_to_the_root_certificate_owner() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        let device = (OFFCHAIN_CERTIFICATE_SIGNER_1, OFFCHAIN_CERTIFICATE_SIGNER_2);
+        assert_eq!(TestModule::payout_account(&device), None);
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        ));
+        assert_eq!(TestModule::payout_account(&device), Some(ROOT_MANAGER));
+
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2
+        ));
+        assert_eq!(TestModule::payout_account(&device), None);
+    })
+}
+
+#[test]
+fn booking_a_slot_reserves_a_bond() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        ));
+
+        assert_eq!(
+            BalancesModule::reserved_balance(ROOT_MANAGER),
+            SlotBond::get()
+        );
+        assert_eq!(
+            TestModule::bonds(OFFCHAIN_CERTIFICATE_SIGNER_1),
+            SlotBond::get()
+        );
+        assert_eq!(TestModule::deposits_of(&ROOT_MANAGER), SlotBond::get());
+    })
+}
+
+#[test]
+fn can_not_buy_slot_without_the_bond() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        BalancesModule::make_free_balance_be(&ROOT_MANAGER, SlotBookingCost::get());
+
+        assert_noop!(
+            TestModule::book_slot(Origin::signed(ROOT_MANAGER), OFFCHAIN_CERTIFICATE_SIGNER_1),
+            Error::<Test>::NotEnoughFunds
+        );
+    })
+}
+
+#[test]
+fn revoking_a_slot_returns_the_bond() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        ));
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        ));
+
+        assert_eq!(BalancesModule::reserved_balance(ROOT_MANAGER), 0);
+        assert_eq!(
+            BalancesModule::free_balance(ROOT_MANAGER),
+            MinimumApplicationAmount::get() + SlotRenewingCost::get() + SlotBond::get()
+        );
+    })
+}
+
+#[test]
+fn expired_slots_can_be_revoked_to_recover_the_bond() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        ));
+        <system::Module<Test>>::set_block_number(SlotValidity::get() + 1);
+
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        ));
+        assert_eq!(BalancesModule::reserved_balance(ROOT_MANAGER), 0);
+    })
+}
+
+#[test]
+fn removing_a_slot_slashes_the_bond() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        ));
+
+        assert_noop!(
+            TestModule::remove_slot(Origin::signed(ROOT_MANAGER), OFFCHAIN_CERTIFICATE_SIGNER_1),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(TestModule::remove_slot(
+            Origin::root(),
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        ));
+
+        assert_eq!(BalancesModule::reserved_balance(ROOT_MANAGER), 0);
+        assert_eq!(
+            BalancesModule::free_balance(ROOT_MANAGER),
+            MinimumApplicationAmount::get() + SlotRenewingCost::get()
+        );
+        assert_eq!(
+            TestModule::is_root_certificate_valid(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            false
+        );
+        assert_noop!(
+            TestModule::revoke_slot(Origin::signed(ROOT_MANAGER), OFFCHAIN_CERTIFICATE_SIGNER_1),
+            Error::<Test>::NoLongerValid
+        );
+    })
+}
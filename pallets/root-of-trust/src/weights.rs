@@ -0,0 +1,80 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Weights for pallet_root_of_trust.
+//!
+//! The values below are carried over from the weights we used to hard code in the
+//! pallet and still need to be measured on reference hardware. Regenerate them with:
+//! `./scripts/benchmark.sh pallet_root_of_trust`
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_root_of_trust.
+pub trait WeightInfo {
+    fn book_slot() -> Weight;
+    fn renew_slot() -> Weight;
+    fn revoke_slot() -> Weight;
+    fn revoke_child() -> Weight;
+    fn remove_slot() -> Weight;
+}
+
+/// Estimated weights for pallet_root_of_trust, see the module documentation.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Trait> WeightInfo for SubstrateWeight<T> {
+    fn book_slot() -> Weight {
+        (160_000_000 as Weight)
+    }
+    fn renew_slot() -> Weight {
+        (150_000_000 as Weight)
+    }
+    fn revoke_slot() -> Weight {
+        (75_000_000 as Weight)
+    }
+    fn revoke_child() -> Weight {
+        (75_000_000 as Weight)
+    }
+    fn remove_slot() -> Weight {
+        (90_000_000 as Weight)
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn book_slot() -> Weight {
+        (160_000_000 as Weight)
+    }
+    fn renew_slot() -> Weight {
+        (150_000_000 as Weight)
+    }
+    fn revoke_slot() -> Weight {
+        (75_000_000 as Weight)
+    }
+    fn revoke_child() -> Weight {
+        (75_000_000 as Weight)
+    }
+    fn remove_slot() -> Weight {
+        (90_000_000 as Weight)
+    }
+}
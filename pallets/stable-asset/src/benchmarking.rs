@@ -0,0 +1,101 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Stable asset pallet benchmarks
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::UnfilteredDispatchable;
+use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+use sp_std::prelude::*;
+
+const SEED: u32 = 0;
+const AMOUNT: u32 = 1_000;
+
+/// Attest reserves covering any issuance the benchmarks may reach.
+fn attest<T: Trait>() {
+    <LastAttestation<T>>::put(Attestation {
+        reserves: T::Balance::max_value(),
+        report: T::Hash::default(),
+        at: <frame_system::Module<T>>::block_number(),
+    });
+}
+
+/// Credit `amount` to the holder without going through a mint.
+fn holder<T: Trait>(index: u32, amount: T::Balance) -> T::AccountId {
+    let who: T::AccountId = account("holder", index, SEED);
+    <Balances<T>>::insert(&who, amount);
+    <TotalIssuance<T>>::mutate(|issuance| *issuance = issuance.saturating_add(amount));
+    who
+}
+
+benchmarks! {
+    _ { }
+
+    attest_reserves {
+        let call = Call::<T>::attest_reserves(AMOUNT.into(), T::Hash::default());
+        let origin = T::CustodianOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+
+    // The recipient already holds some of the asset so its balance is updated
+    mint {
+        attest::<T>();
+        let to = holder::<T>(0, AMOUNT.into());
+        let call = Call::<T>::mint(to, AMOUNT.into());
+        let origin = T::CustodianOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+
+    transfer {
+        let who = holder::<T>(0, AMOUNT.into());
+        let to = holder::<T>(1, AMOUNT.into());
+    }: _(RawOrigin::Signed(who), to, AMOUNT.into())
+
+    redeem {
+        let who = holder::<T>(0, AMOUNT.into());
+    }: _(RawOrigin::Signed(who), AMOUNT.into())
+
+    burn {
+        let who = holder::<T>(0, AMOUNT.into());
+        Module::<T>::redeem(RawOrigin::Signed(who.clone()).into(), AMOUNT.into())
+            .expect("the holder was credited the amount");
+        let call = Call::<T>::burn(who, AMOUNT.into());
+        let origin = T::CustodianOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_attest_reserves::<Test>());
+            assert_ok!(test_benchmark_mint::<Test>());
+            assert_ok!(test_benchmark_transfer::<Test>());
+            assert_ok!(test_benchmark_redeem::<Test>());
+            assert_ok!(test_benchmark_burn::<Test>());
+        });
+    }
+}
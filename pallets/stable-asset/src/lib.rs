@@ -0,0 +1,267 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! A fiat backed asset held in custody off chain. The custodian periodically attests
+//! the reserves it holds along with the hash of the report backing them, and may only
+//! mint while its last attestation is younger than `AttestationValidity` and covers
+//! the whole issuance.
+//!
+//! Holders transfer the asset among themselves, typically to settle data purchases
+//! without being exposed to the volatility of the native token. To get fiat back they
+//! place the amount in redemption, where it stays until the custodian pays out and
+//! burns it.
+
+mod benchmarking;
+pub mod weights;
+
+#[cfg(test)]
+mod tests;
+
+pub use weights::WeightInfo;
+
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{EnsureOrigin, Get},
+    Parameter,
+};
+use frame_system::ensure_signed;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{AtLeast32BitUnsigned, CheckedAdd, Member, Saturating, Zero},
+    DispatchResult, RuntimeDebug,
+};
+
+/// Reserves attested by the custodian.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Attestation<Balance, Hash, BlockNumber> {
+    /// Fiat held in custody, in the smallest unit of the asset
+    pub reserves: Balance,
+    /// Hash of the report backing the attestation
+    pub report: Hash,
+    /// Block the attestation was made at
+    pub at: BlockNumber,
+}
+
+pub type AttestationOf<T> = Attestation<
+    <T as Trait>::Balance,
+    <T as frame_system::Trait>::Hash,
+    <T as frame_system::Trait>::BlockNumber,
+>;
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Balance type of the asset
+    type Balance: Member + Parameter + AtLeast32BitUnsigned + Default + Copy;
+    /// Origin attesting the reserves, minting and burning the asset
+    type CustodianOrigin: EnsureOrigin<Self::Origin>;
+    /// Number of blocks an attestation can be minted against
+    type AttestationValidity: Get<Self::BlockNumber>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as StableAsset {
+        /// Spendable balance of every holder
+        Balances get(fn balance_of): map hasher(blake2_128_concat) T::AccountId => T::Balance;
+        /// Amounts waiting to be paid out in fiat and burned
+        Redeeming get(fn redeeming): map hasher(blake2_128_concat) T::AccountId => T::Balance;
+        /// Total amount in circulation, including the amounts being redeemed
+        TotalIssuance get(fn total_issuance): T::Balance;
+        /// Last reserves attested by the custodian
+        LastAttestation get(fn last_attestation): Option<AttestationOf<T>>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        Balance = <T as Trait>::Balance,
+        Hash = <T as frame_system::Trait>::Hash,
+    {
+        /// The custodian attested holding the given reserves, backed by the report
+        /// with the given hash
+        ReservesAttested(Balance, Hash),
+        /// The given amount was minted to the account
+        Minted(AccountId, Balance),
+        /// The given amount was transferred between the two accounts
+        Transferred(AccountId, AccountId, Balance),
+        /// The account asked for the given amount to be paid out in fiat
+        RedemptionRequested(AccountId, Balance),
+        /// The given amount was paid out to the account and burned
+        Burned(AccountId, Balance),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// The amount is zero
+        ZeroAmount,
+        /// The custodian never attested its reserves
+        NoAttestation,
+        /// The last attestation is too old to mint against
+        StaleAttestation,
+        /// The attested reserves do not cover the issuance
+        InsufficientReserves,
+        /// The account does not hold enough of the asset
+        InsufficientBalance,
+        /// The account did not place enough in redemption
+        InsufficientRedemption,
+        /// The issuance would overflow
+        Overflow,
+    }
+}
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const AttestationValidity: T::BlockNumber = T::AttestationValidity::get();
+
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Record the reserves currently held in custody, `report` is the hash of the
+        /// document backing them
+        #[weight = T::WeightInfo::attest_reserves()]
+        pub fn attest_reserves(origin, reserves: T::Balance, report: T::Hash) -> DispatchResult {
+            T::CustodianOrigin::ensure_origin(origin)?;
+
+            <LastAttestation<T>>::put(Attestation {
+                reserves,
+                report,
+                at: <frame_system::Module<T>>::block_number(),
+            });
+
+            Self::deposit_event(RawEvent::ReservesAttested(reserves, report));
+            Ok(())
+        }
+
+        /// Mint `amount` to `to` against the last attestation
+        #[weight = T::WeightInfo::mint()]
+        pub fn mint(origin, to: T::AccountId, amount: T::Balance) -> DispatchResult {
+            T::CustodianOrigin::ensure_origin(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+
+            let attestation = Self::last_attestation().ok_or(Error::<T>::NoAttestation)?;
+            let age = <frame_system::Module<T>>::block_number().saturating_sub(attestation.at);
+            ensure!(age <= T::AttestationValidity::get(), Error::<T>::StaleAttestation);
+
+            let issuance = Self::total_issuance()
+                .checked_add(&amount)
+                .ok_or(Error::<T>::Overflow)?;
+            ensure!(issuance <= attestation.reserves, Error::<T>::InsufficientReserves);
+
+            <TotalIssuance<T>>::put(issuance);
+            <Balances<T>>::mutate(&to, |balance| *balance = balance.saturating_add(amount));
+
+            Self::deposit_event(RawEvent::Minted(to, amount));
+            Ok(())
+        }
+
+        /// Transfer `amount` of the caller's balance to `to`
+        #[weight = T::WeightInfo::transfer()]
+        pub fn transfer(origin, to: T::AccountId, amount: T::Balance) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+
+            Self::take(&who, amount)?;
+            <Balances<T>>::mutate(&to, |balance| *balance = balance.saturating_add(amount));
+
+            Self::deposit_event(RawEvent::Transferred(who, to, amount));
+            Ok(())
+        }
+
+        /// Place `amount` of the caller's balance in redemption, the custodian burns it
+        /// once it paid out the equivalent fiat
+        #[weight = T::WeightInfo::redeem()]
+        pub fn redeem(origin, amount: T::Balance) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+
+            Self::take(&who, amount)?;
+            <Redeeming<T>>::mutate(&who, |redeeming| *redeeming = redeeming.saturating_add(amount));
+
+            Self::deposit_event(RawEvent::RedemptionRequested(who, amount));
+            Ok(())
+        }
+
+        /// Burn `amount` placed in redemption by `who` after paying it out in fiat
+        #[weight = T::WeightInfo::burn()]
+        pub fn burn(origin, who: T::AccountId, amount: T::Balance) -> DispatchResult {
+            T::CustodianOrigin::ensure_origin(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+
+            let redeeming = Self::redeeming(&who);
+            ensure!(redeeming >= amount, Error::<T>::InsufficientRedemption);
+
+            let remaining = redeeming.saturating_sub(amount);
+            if remaining.is_zero() {
+                <Redeeming<T>>::remove(&who);
+            } else {
+                <Redeeming<T>>::insert(&who, remaining);
+            }
+            <TotalIssuance<T>>::mutate(|issuance| *issuance = issuance.saturating_sub(amount));
+
+            Self::deposit_event(RawEvent::Burned(who, amount));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Remove `amount` from the balance of `who`, dropping the entry once empty.
+    fn take(who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        let balance = Self::balance_of(who);
+        ensure!(balance >= amount, Error::<T>::InsufficientBalance);
+
+        let remaining = balance.saturating_sub(amount);
+        if remaining.is_zero() {
+            <Balances<T>>::remove(who);
+        } else {
+            <Balances<T>>::insert(who, remaining);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        use frame_support::IterableStorageMap;
+
+        let held = <Balances<T>>::iter()
+            .chain(<Redeeming<T>>::iter())
+            .fold(T::Balance::zero(), |total, (_, amount)| {
+                total.saturating_add(amount)
+            });
+        ensure!(
+            held == Self::total_issuance(),
+            "total issuance does not match the balances and redemptions"
+        );
+
+        Ok(())
+    }
+}
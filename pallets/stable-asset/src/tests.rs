@@ -0,0 +1,258 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_origin, ord_parameter_types, parameter_types,
+};
+use frame_system::EnsureSignedBy;
+use sp_core::H256;
+use sp_runtime::DispatchError::BadOrigin;
+use test_utils::impl_mock_system;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), ());
+
+ord_parameter_types! {
+    pub const Custodian: u64 = 1;
+}
+parameter_types! {
+    pub const AttestationValidity: u64 = 10;
+}
+impl Trait for Test {
+    type Event = ();
+    type Balance = u64;
+    type CustodianOrigin = EnsureSignedBy<Custodian, u64>;
+    type AttestationValidity = AttestationValidity;
+    type WeightInfo = ();
+}
+type StableAsset = Module<Test>;
+type System = frame_system::Module<Test>;
+type Errors = Error<Test>;
+
+const CUSTODIAN: u64 = 1;
+const ALICE: u64 = 2;
+const BOB: u64 = 3;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into()
+}
+
+fn report() -> H256 {
+    H256::repeat_byte(1)
+}
+
+#[test]
+fn custodian_attests_reserves() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(5);
+        assert_noop!(
+            StableAsset::attest_reserves(Origin::signed(ALICE), 100, report()),
+            BadOrigin
+        );
+
+        assert_ok!(StableAsset::attest_reserves(
+            Origin::signed(CUSTODIAN),
+            100,
+            report()
+        ));
+        assert_eq!(
+            StableAsset::last_attestation(),
+            Some(Attestation {
+                reserves: 100,
+                report: report(),
+                at: 5,
+            })
+        );
+    });
+}
+
+#[test]
+fn mint_requires_an_attestation() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            StableAsset::mint(Origin::signed(CUSTODIAN), ALICE, 10),
+            Errors::NoAttestation
+        );
+    });
+}
+
+#[test]
+fn mint_requires_a_fresh_attestation() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(StableAsset::attest_reserves(
+            Origin::signed(CUSTODIAN),
+            100,
+            report()
+        ));
+
+        System::set_block_number(11);
+        assert_ok!(StableAsset::mint(Origin::signed(CUSTODIAN), ALICE, 10));
+
+        System::set_block_number(12);
+        assert_noop!(
+            StableAsset::mint(Origin::signed(CUSTODIAN), ALICE, 10),
+            Errors::StaleAttestation
+        );
+    });
+}
+
+#[test]
+fn mint_is_bounded_by_reserves() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(StableAsset::attest_reserves(
+            Origin::signed(CUSTODIAN),
+            100,
+            report()
+        ));
+        assert_noop!(
+            StableAsset::mint(Origin::signed(ALICE), ALICE, 10),
+            BadOrigin
+        );
+        assert_noop!(
+            StableAsset::mint(Origin::signed(CUSTODIAN), ALICE, 0),
+            Errors::ZeroAmount
+        );
+
+        assert_ok!(StableAsset::mint(Origin::signed(CUSTODIAN), ALICE, 60));
+        assert_ok!(StableAsset::mint(Origin::signed(CUSTODIAN), BOB, 40));
+        assert_noop!(
+            StableAsset::mint(Origin::signed(CUSTODIAN), ALICE, 1),
+            Errors::InsufficientReserves
+        );
+
+        assert_eq!(StableAsset::balance_of(ALICE), 60);
+        assert_eq!(StableAsset::balance_of(BOB), 40);
+        assert_eq!(StableAsset::total_issuance(), 100);
+    });
+}
+
+#[test]
+fn holders_transfer_their_balance() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(StableAsset::attest_reserves(
+            Origin::signed(CUSTODIAN),
+            100,
+            report()
+        ));
+        assert_ok!(StableAsset::mint(Origin::signed(CUSTODIAN), ALICE, 60));
+
+        assert_noop!(
+            StableAsset::transfer(Origin::signed(ALICE), BOB, 61),
+            Errors::InsufficientBalance
+        );
+        assert_ok!(StableAsset::transfer(Origin::signed(ALICE), BOB, 20));
+        assert_eq!(StableAsset::balance_of(ALICE), 40);
+        assert_eq!(StableAsset::balance_of(BOB), 20);
+
+        assert_ok!(StableAsset::transfer(Origin::signed(ALICE), BOB, 40));
+        assert!(!<Balances<Test>>::contains_key(ALICE));
+        assert_eq!(StableAsset::balance_of(BOB), 60);
+        assert_eq!(StableAsset::total_issuance(), 60);
+    });
+}
+
+#[test]
+fn redemptions_are_burned_by_the_custodian() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(StableAsset::attest_reserves(
+            Origin::signed(CUSTODIAN),
+            100,
+            report()
+        ));
+        assert_ok!(StableAsset::mint(Origin::signed(CUSTODIAN), ALICE, 60));
+
+        assert_noop!(
+            StableAsset::redeem(Origin::signed(ALICE), 61),
+            Errors::InsufficientBalance
+        );
+        assert_ok!(StableAsset::redeem(Origin::signed(ALICE), 50));
+        assert_eq!(StableAsset::balance_of(ALICE), 10);
+        assert_eq!(StableAsset::redeeming(ALICE), 50);
+        assert_eq!(StableAsset::total_issuance(), 60);
+
+        // Funds in redemption can not be spent anymore
+        assert_noop!(
+            StableAsset::transfer(Origin::signed(ALICE), BOB, 11),
+            Errors::InsufficientBalance
+        );
+
+        assert_noop!(
+            StableAsset::burn(Origin::signed(ALICE), ALICE, 50),
+            BadOrigin
+        );
+        assert_noop!(
+            StableAsset::burn(Origin::signed(CUSTODIAN), ALICE, 51),
+            Errors::InsufficientRedemption
+        );
+        assert_ok!(StableAsset::burn(Origin::signed(CUSTODIAN), ALICE, 30));
+        assert_eq!(StableAsset::redeeming(ALICE), 20);
+        assert_eq!(StableAsset::total_issuance(), 30);
+
+        assert_ok!(StableAsset::burn(Origin::signed(CUSTODIAN), ALICE, 20));
+        assert!(!<Redeeming<Test>>::contains_key(ALICE));
+        assert_eq!(StableAsset::total_issuance(), 10);
+    });
+}
+
+#[test]
+fn redemptions_count_against_reserves() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(StableAsset::attest_reserves(
+            Origin::signed(CUSTODIAN),
+            100,
+            report()
+        ));
+        assert_ok!(StableAsset::mint(Origin::signed(CUSTODIAN), ALICE, 100));
+        assert_ok!(StableAsset::redeem(Origin::signed(ALICE), 100));
+
+        // Until burned the redeemed funds are still backed by the reserves
+        assert_noop!(
+            StableAsset::mint(Origin::signed(CUSTODIAN), BOB, 1),
+            Errors::InsufficientReserves
+        );
+
+        assert_ok!(StableAsset::burn(Origin::signed(CUSTODIAN), ALICE, 100));
+        assert_ok!(StableAsset::attest_reserves(
+            Origin::signed(CUSTODIAN),
+            0,
+            report()
+        ));
+        assert_noop!(
+            StableAsset::mint(Origin::signed(CUSTODIAN), BOB, 1),
+            Errors::InsufficientReserves
+        );
+    });
+}
@@ -0,0 +1,10 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+sp_api::decl_runtime_apis! {
+    pub trait StateSizeApi<Sample> where
+        Sample: parity_scale_codec::Codec
+    {
+        fn latest_sample() -> Option<(u32, Sample)>;
+        fn sample(era: u32) -> Option<Sample>;
+    }
+}
@@ -0,0 +1,216 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Keep track of how much state is held under some storage prefixes so that governance
+//! can monitor state bloat. There is no host function returning the size of the state,
+//! instead we walk the tracked prefixes at the beginning of every era, visiting a bounded
+//! number of keys per block, and record the number of keys and bytes we found once the
+//! walk completes. Values modified while a walk is in progress may be counted in their
+//! old or new form, samples are thus approximations.
+
+mod tests;
+
+use frame_support::{decl_event, decl_module, decl_storage, traits::Get, weights::Weight};
+use nodle_support::HousekeepingTask;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{SaturatedConversion, Saturating, Zero},
+    RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+/// Amount of state found under a given prefix.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct PrefixSample {
+    /// Human readable name of the prefix, typically `Pallet.Storage`
+    pub name: Vec<u8>,
+    /// Number of keys under the prefix
+    pub keys: u64,
+    /// Total size of the keys and their values
+    pub bytes: u64,
+}
+
+/// A walk over the tracked prefixes in progress.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct SamplingPass {
+    /// Era the sample will be recorded for
+    pub era: u32,
+    /// Index of the tracked prefix we are walking
+    pub prefix: u32,
+    /// Last key we visited under that prefix
+    pub last_key: Option<Vec<u8>>,
+    /// What we found so far
+    pub samples: Vec<PrefixSample>,
+}
+
+/// The module's configuration trait.
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event> + Into<<Self as frame_system::Trait>::Event>;
+    /// Storage prefixes to track along with a human readable name
+    type TrackedPrefixes: Get<Vec<(Vec<u8>, Vec<u8>)>>;
+    /// Length of an era, a new walk starts at the beginning of every era
+    type SamplingPeriod: Get<Self::BlockNumber>;
+    /// Maximum number of keys visited per block, bounds the cost of a walk
+    type MaxKeysPerBlock: Get<u32>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as StateSize {
+        /// Walk in progress, if any
+        pub Pass get(fn pass): Option<SamplingPass>;
+        /// Last era for which we recorded a sample
+        pub LatestEra get(fn latest_era): Option<u32>;
+        /// Samples recorded per era
+        pub Samples get(fn samples): map hasher(twox_64_concat) u32 => Option<Vec<PrefixSample>>;
+    }
+}
+
+decl_event!(
+    pub enum Event {
+        /// A state size sample was recorded for the given era
+        SampleRecorded(u32),
+    }
+);
+
+decl_module! {
+    /// The module declaration.
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        const SamplingPeriod: T::BlockNumber = T::SamplingPeriod::get();
+        const MaxKeysPerBlock: u32 = T::MaxKeysPerBlock::get();
+
+        fn deposit_event() = default;
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Start a new walk if the era of `n` was not sampled yet. The walk may start after
+    /// the first block of the era when housekeeping had too much to do then.
+    fn new_pass(n: T::BlockNumber) -> Option<SamplingPass> {
+        let period = T::SamplingPeriod::get();
+        if period.is_zero() {
+            return None;
+        }
+        let era: u32 = (n / period).saturated_into();
+        if era.is_zero() || Self::latest_era() >= Some(era) {
+            return None;
+        }
+
+        Some(SamplingPass {
+            era,
+            prefix: 0,
+            last_key: None,
+            samples: T::TrackedPrefixes::get()
+                .into_iter()
+                .map(|(name, _)| PrefixSample {
+                    name,
+                    ..Default::default()
+                })
+                .collect(),
+        })
+    }
+
+    /// Visit up to `max_keys` keys, returns how many were visited.
+    fn walk(pass: &mut SamplingPass, max_keys: u32) -> u32 {
+        let prefixes = T::TrackedPrefixes::get();
+        let mut visited = 0;
+
+        while let Some((_, prefix)) = prefixes.get(pass.prefix as usize) {
+            if visited >= max_keys {
+                break;
+            }
+
+            let start = pass.last_key.as_ref().unwrap_or(prefix);
+            match sp_io::storage::next_key(start).filter(|key| key.starts_with(prefix)) {
+                Some(key) => {
+                    let value_len = sp_io::storage::read(&key, &mut [], 0).unwrap_or_default();
+                    if let Some(sample) = pass.samples.get_mut(pass.prefix as usize) {
+                        sample.keys = sample.keys.saturating_add(1);
+                        sample.bytes = sample
+                            .bytes
+                            .saturating_add(key.len() as u64)
+                            .saturating_add(value_len.into());
+                    }
+
+                    pass.last_key = Some(key);
+                    visited += 1;
+                }
+                None => {
+                    pass.prefix = pass.prefix.saturating_add(1);
+                    pass.last_key = None;
+                }
+            }
+        }
+
+        visited
+    }
+
+    /// Latest recorded sample and its era, used by the runtime API.
+    pub fn latest_sample() -> Option<(u32, Vec<PrefixSample>)> {
+        Self::latest_era().and_then(|era| Self::samples(era).map(|sample| (era, sample)))
+    }
+}
+
+impl<T: Trait> HousekeepingTask<T::BlockNumber> for Module<T> {
+    fn housekeep(n: T::BlockNumber, budget: Weight) -> Weight {
+        // Every visited key costs us a `next_key` and a `read`
+        let base = T::DbWeight::get().reads_writes(2, 3);
+        let per_key = T::DbWeight::get().reads(2).max(1);
+        let max_keys =
+            (budget.saturating_sub(base) / per_key).min(T::MaxKeysPerBlock::get().into()) as u32;
+        if budget < base || max_keys.is_zero() {
+            return 0;
+        }
+        let mut pass = match Self::pass() {
+            Some(pass) => pass,
+            None => match Self::new_pass(n) {
+                Some(pass) => pass,
+                None => return T::DbWeight::get().reads(2),
+            },
+        };
+
+        let visited = Self::walk(&mut pass, max_keys) as Weight;
+        if pass.prefix as usize >= pass.samples.len() {
+            <Samples>::insert(pass.era, pass.samples);
+            <LatestEra>::put(pass.era);
+            <Pass>::kill();
+            Self::deposit_event(Event::SampleRecorded(pass.era));
+        } else {
+            <Pass>::put(pass);
+        }
+
+        T::DbWeight::get().reads_writes(visited.saturating_mul(2).saturating_add(2), 3)
+    }
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait> Module<T> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        if let Some(era) = Self::latest_era() {
+            frame_support::ensure!(
+                Self::samples(era).is_some(),
+                "the latest era was not sampled"
+            );
+        }
+
+        Ok(())
+    }
+}
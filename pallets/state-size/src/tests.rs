@@ -0,0 +1,141 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg(test)]
+
+use super::*;
+
+use frame_support::{impl_outer_origin, parameter_types};
+use test_utils::impl_mock_system;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// For testing the module, we construct most of a mock runtime. This means
+// first constructing a configuration type (`Test`) which `impl`s each of the
+// configuration traits of modules we want to use.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+impl_mock_system!(Test, Origin, (), ());
+
+parameter_types! {
+    pub const SamplingPeriod: u64 = 10;
+    pub const MaxKeysPerBlock: u32 = 2;
+    pub TrackedPrefixes: Vec<(Vec<u8>, Vec<u8>)> = vec![
+        (b"Tracked.First".to_vec(), b"first".to_vec()),
+        (b"Tracked.Second".to_vec(), b"second".to_vec()),
+    ];
+}
+impl Trait for Test {
+    type Event = ();
+    type TrackedPrefixes = TrackedPrefixes;
+    type SamplingPeriod = SamplingPeriod;
+    type MaxKeysPerBlock = MaxKeysPerBlock;
+}
+type StateSize = Module<Test>;
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut ext: sp_io::TestExternalities = frame_system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap()
+        .into();
+    ext.execute_with(|| {
+        sp_io::storage::set(b"first1", &[0; 10]);
+        sp_io::storage::set(b"first2", &[0; 20]);
+        sp_io::storage::set(b"first3", &[0; 30]);
+        sp_io::storage::set(b"second1", &[0; 40]);
+        sp_io::storage::set(b"untracked", &[0; 50]);
+    });
+    ext
+}
+
+fn sample(name: &[u8], keys: u64, bytes: u64) -> PrefixSample {
+    PrefixSample {
+        name: name.to_vec(),
+        keys,
+        bytes,
+    }
+}
+
+fn housekeep(n: u64) -> Weight {
+    StateSize::housekeep(n, Weight::max_value())
+}
+
+#[test]
+fn does_not_sample_outside_of_era_start() {
+    new_test_ext().execute_with(|| {
+        housekeep(5);
+        assert_eq!(StateSize::pass(), None);
+        assert_eq!(StateSize::latest_sample(), None);
+    })
+}
+
+#[test]
+fn walk_is_spread_over_multiple_blocks() {
+    new_test_ext().execute_with(|| {
+        housekeep(10);
+        assert_eq!(StateSize::pass().unwrap().samples[0].keys, 2);
+
+        housekeep(11);
+        assert!(StateSize::pass().is_some());
+
+        housekeep(12);
+        assert_eq!(StateSize::pass(), None);
+        assert_eq!(
+            StateSize::latest_sample(),
+            Some((
+                1,
+                vec![
+                    sample(b"Tracked.First", 3, 3 * 6 + 10 + 20 + 30),
+                    sample(b"Tracked.Second", 1, 7 + 40),
+                ]
+            ))
+        );
+    })
+}
+
+#[test]
+fn samples_are_kept_per_era() {
+    new_test_ext().execute_with(|| {
+        for n in 10..13 {
+            housekeep(n);
+        }
+        sp_io::storage::clear(b"first1");
+        for n in 20..23 {
+            housekeep(n);
+        }
+
+        assert_eq!(StateSize::samples(1).unwrap()[0].keys, 3);
+        assert_eq!(StateSize::samples(2).unwrap()[0].keys, 2);
+        assert_eq!(StateSize::latest_era(), Some(2));
+    })
+}
+
+#[test]
+fn walk_starts_late_if_housekeeping_was_busy() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(StateSize::housekeep(10, 0), 0);
+        assert_eq!(StateSize::pass(), None);
+
+        housekeep(13);
+        assert_eq!(StateSize::pass().unwrap().era, 1);
+    })
+}
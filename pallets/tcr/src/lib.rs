@@ -24,6 +24,9 @@
 
 mod benchmarking;
 mod tests;
+pub mod weights;
+
+pub use weights::WeightInfo;
 
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
@@ -35,7 +38,7 @@ use frame_support::{
 use frame_system::{self as system, ensure_signed};
 use parity_scale_codec::{Decode, Encode};
 use sp_runtime::{
-    traits::{CheckedAdd, CheckedDiv, CheckedSub, Saturating},
+    traits::{CheckedAdd, CheckedDiv, CheckedSub, Saturating, Zero},
     Perbill,
 };
 use sp_std::prelude::Vec;
@@ -108,6 +111,9 @@ pub trait Trait<I: Instance = DefaultInstance>: system::Trait {
     type LoosersSlash: Get<Perbill>;
     /// Hook that we call whenever some members are added or removed from the TCR
     type ChangeMembers: ChangeMembers<Self::AccountId>;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
 }
 
 decl_event!(
@@ -181,7 +187,7 @@ decl_module! {
         fn deposit_event() = default;
 
         /// Apply to join the TCR, `metadata` can be used to add something like a URL or ID
-        #[weight = 150_000_000]
+        #[weight = T::WeightInfo::apply()]
         pub fn apply(origin, metadata: Vec<u8>, deposit: BalanceOf<T, I>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(deposit >= T::MinimumApplicationAmount::get(), Error::<T, I>::DepositTooSmall);
@@ -212,7 +218,7 @@ decl_module! {
         }
 
         /// Counter a pending application, this will initiate a challenge
-        #[weight = 100_000_000]
+        #[weight = T::WeightInfo::counter()]
         pub fn counter(origin, member: T::AccountId, deposit: BalanceOf<T, I>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(deposit >= T::MinimumCounterAmount::get(), Error::<T, I>::DepositTooSmall);
@@ -235,7 +241,7 @@ decl_module! {
         }
 
         /// Vote in support or opposition of a given challenge
-        #[weight = 100_000_000]
+        #[weight = T::WeightInfo::vote()]
         pub fn vote(origin, member: T::AccountId, supporting: bool, deposit: BalanceOf<T, I>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(<Challenges<T, I>>::contains_key(member.clone()), Error::<T, I>::ChallengeNotFound);
@@ -260,7 +266,7 @@ decl_module! {
         }
 
         /// Trigger a new challenge to remove an existing member
-        #[weight = 150_000_000]
+        #[weight = T::WeightInfo::challenge()]
         pub fn challenge(origin, member: T::AccountId, deposit: BalanceOf<T, I>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(deposit >= T::MinimumChallengeAmount::get(), Error::<T, I>::DepositTooSmall);
@@ -316,6 +322,29 @@ type ResolvedChallengeResultFrom<T, I> = (
 );
 
 impl<T: Trait<I>, I: Instance> Module<T, I> {
+    /// Deposits reserved from `who` for the applications, challenges and votes it
+    /// takes part in. Members got their application deposit back already.
+    pub fn deposits_of(who: &T::AccountId) -> BalanceOf<T, I> {
+        let mut deposits = Vec::new();
+        for (_, application) in <Applications<T, I>>::iter() {
+            deposits.push((application.candidate, application.candidate_deposit));
+        }
+        for (member, challenge) in <Challenges<T, I>>::iter() {
+            if !<Members<T, I>>::contains_key(&member) {
+                deposits.push((challenge.candidate.clone(), challenge.candidate_deposit));
+            }
+            deposits.extend(challenge.voters_for.clone());
+            deposits.extend(challenge.voters_against_and_challenger());
+        }
+
+        deposits
+            .into_iter()
+            .filter(|(account, _)| account == who)
+            .fold(Zero::zero(), |total, (_, deposit)| {
+                total.saturating_add(deposit)
+            })
+    }
+
     /// Do not just call `set_lock`, rather increase the locked amount
     fn reserve_for(who: T::AccountId, amount: BalanceOf<T, I>) -> DispatchResult {
         // Make sure we can lock has many funds
@@ -568,3 +597,26 @@ impl<T: Trait<I>, I: Instance> Module<T, I> {
         }
     }
 }
+
+#[cfg(feature = "try-runtime")]
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+    /// Check our storage invariants, meant to be run against live state when
+    /// rehearsing runtime upgrades.
+    pub fn try_state() -> Result<(), &'static str> {
+        for (who, application) in <Applications<T, I>>::iter()
+            .chain(<Challenges<T, I>>::iter())
+            .chain(<Members<T, I>>::iter())
+        {
+            ensure!(
+                application.candidate == who,
+                "application stored under another account"
+            );
+        }
+        ensure!(
+            <Applications<T, I>>::iter().all(|(who, _)| !<Challenges<T, I>>::contains_key(&who)),
+            "application both pending and challenged"
+        );
+
+        Ok(())
+    }
+}
@@ -21,15 +21,11 @@
 use super::*;
 
 use frame_support::{
-    assert_noop, assert_ok, impl_outer_origin, parameter_types, traits::OnFinalize, weights::Weight,
-};
-use sp_core::H256;
-use sp_runtime::{
-    testing::Header,
-    traits::{BlakeTwo256, IdentityLookup},
-    Perbill,
+    assert_noop, assert_ok, impl_outer_origin, parameter_types, traits::OnFinalize,
 };
+use sp_runtime::Perbill;
 use std::cell::RefCell;
+use test_utils::{impl_mock_balances, impl_mock_system};
 
 impl_outer_origin! {
     pub enum Origin for Test {}
@@ -40,52 +36,8 @@ impl_outer_origin! {
 // configuration traits of modules we want to use.
 #[derive(Clone, Eq, PartialEq)]
 pub struct Test;
-parameter_types! {
-    pub const BlockHashCount: u64 = 250;
-    pub const MaximumBlockWeight: Weight = 1024;
-    pub const MaximumBlockLength: u32 = 2 * 1024;
-    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
-}
-impl system::Trait for Test {
-    type Origin = Origin;
-    type Call = ();
-    type Index = u64;
-    type BlockNumber = u64;
-    type Hash = H256;
-    type Hashing = BlakeTwo256;
-    type AccountId = u64;
-    type Lookup = IdentityLookup<Self::AccountId>;
-    type Header = Header;
-    type Event = ();
-    type BlockHashCount = BlockHashCount;
-    type MaximumBlockWeight = MaximumBlockWeight;
-    type MaximumBlockLength = MaximumBlockLength;
-    type AvailableBlockRatio = AvailableBlockRatio;
-    type Version = ();
-    type PalletInfo = ();
-    type AccountData = pallet_balances::AccountData<u64>;
-    type OnNewAccount = ();
-    type OnKilledAccount = ();
-    type DbWeight = ();
-    type BlockExecutionWeight = ();
-    type ExtrinsicBaseWeight = ();
-    type MaximumExtrinsicWeight = MaximumBlockWeight;
-    type BaseCallFilter = ();
-    type SystemWeightInfo = ();
-}
-parameter_types! {
-    pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(33);
-    pub const MaxLocks: u32 = 50;
-}
-impl pallet_balances::Trait for Test {
-    type Balance = u64;
-    type Event = ();
-    type DustRemoval = ();
-    type AccountStore = system::Module<Test>;
-    type MaxLocks = MaxLocks;
-    type ExistentialDeposit = ();
-    type WeightInfo = ();
-}
+impl_mock_system!(Test, Origin, (), pallet_balances::AccountData<u64>);
+impl_mock_balances!(Test, ());
 parameter_types! {
     pub const MinimumApplicationAmount: u64 = 100;
     pub const MinimumCounterAmount: u64 = 1000;
@@ -132,6 +84,7 @@ impl Trait for Test {
     type FinalizeChallengePeriod = FinalizeChallengePeriod;
     type LoosersSlash = LoosersSlash;
     type ChangeMembers = TestChangeMembers;
+    type WeightInfo = ();
 }
 
 const CANDIDATE: u64 = 1;
@@ -457,6 +410,16 @@ fn vote_positive_and_negative_works() {
 
         assert_eq!(BalancesModule::reserved_balance(VOTER_FOR), 100);
         assert_eq!(BalancesModule::reserved_balance(VOTER_AGAINST), 100);
+
+        assert_eq!(TestModule::deposits_of(&VOTER_FOR), 100);
+        assert_eq!(
+            TestModule::deposits_of(&CANDIDATE),
+            MinimumApplicationAmount::get()
+        );
+        assert_eq!(
+            TestModule::deposits_of(&CHALLENGER_1),
+            MinimumCounterAmount::get()
+        );
     })
 }
 
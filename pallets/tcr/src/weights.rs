@@ -0,0 +1,73 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Weights for pallet_tcr.
+//!
+//! The values below are carried over from the weights we used to hard code in the
+//! pallet and still need to be measured on reference hardware. Regenerate them with:
+//! `./scripts/benchmark.sh pallet_tcr`
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_tcr.
+pub trait WeightInfo {
+    fn apply() -> Weight;
+    fn counter() -> Weight;
+    fn vote() -> Weight;
+    fn challenge() -> Weight;
+}
+
+/// Estimated weights for pallet_tcr, see the module documentation.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Trait> WeightInfo for SubstrateWeight<T> {
+    fn apply() -> Weight {
+        (150_000_000 as Weight)
+    }
+    fn counter() -> Weight {
+        (100_000_000 as Weight)
+    }
+    fn vote() -> Weight {
+        (100_000_000 as Weight)
+    }
+    fn challenge() -> Weight {
+        (150_000_000 as Weight)
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn apply() -> Weight {
+        (150_000_000 as Weight)
+    }
+    fn counter() -> Weight {
+        (100_000_000 as Weight)
+    }
+    fn vote() -> Weight {
+        (100_000_000 as Weight)
+    }
+    fn challenge() -> Weight {
+        (150_000_000 as Weight)
+    }
+}
@@ -32,7 +32,6 @@ pub const fn deposit(items: u32, bytes: u32) -> Balance {
 /// Time and blocks.
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
 pub const SLOT_DURATION: u64 = MILLISECS_PER_BLOCK;
-pub const EPOCH_DURATION_IN_BLOCKS: BlockNumber = 4 * HOURS;
 pub const EPOCH_DURATION_IN_SLOTS: u64 = {
     const SLOT_FILL_RATE: f64 = MILLISECS_PER_BLOCK as f64 / SLOT_DURATION as f64;
     (EPOCH_DURATION_IN_BLOCKS as f64 * SLOT_FILL_RATE) as u64
@@ -46,6 +45,41 @@ pub const DAYS: BlockNumber = HOURS * 24;
 // 1 in 4 blocks (on average, not counting collisions) will be primary babe blocks.
 pub const PRIMARY_PROBABILITY: (u64, u64) = (1, 4);
 
+/// Network specific parameters. The staging runtime is built from the same sources
+/// with the `nodle_staging` cfg flag set by its build script, it uses shorter periods
+/// so that testnets can iterate quickly.
+#[cfg(not(nodle_staging))]
+mod network {
+    use super::*;
+
+    pub const SPEC_NAME: &str = "nodle-chain";
+    pub const EPOCH_DURATION_IN_BLOCKS: BlockNumber = 4 * HOURS;
+    pub const MOTION_DURATION: BlockNumber = 2 * DAYS;
+    pub const AMENDMENT_DELAY: BlockNumber = 2 * DAYS;
+    pub const ORACLE_ACTIVATION_DELAY: BlockNumber = 2 * DAYS;
+    pub const LAUNCH_PERIOD: BlockNumber = 7 * DAYS;
+    pub const VOTING_PERIOD: BlockNumber = 7 * DAYS;
+    pub const FAST_TRACK_VOTING_PERIOD: BlockNumber = 3 * HOURS;
+    pub const ENACTMENT_PERIOD: BlockNumber = 2 * DAYS;
+}
+
+#[cfg(nodle_staging)]
+mod network {
+    use super::*;
+
+    pub const SPEC_NAME: &str = "nodle-staging";
+    pub const EPOCH_DURATION_IN_BLOCKS: BlockNumber = 10 * MINUTES;
+    pub const MOTION_DURATION: BlockNumber = 10 * MINUTES;
+    pub const AMENDMENT_DELAY: BlockNumber = 10 * MINUTES;
+    pub const ORACLE_ACTIVATION_DELAY: BlockNumber = 10 * MINUTES;
+    pub const LAUNCH_PERIOD: BlockNumber = 10 * MINUTES;
+    pub const VOTING_PERIOD: BlockNumber = 10 * MINUTES;
+    pub const FAST_TRACK_VOTING_PERIOD: BlockNumber = 5 * MINUTES;
+    pub const ENACTMENT_PERIOD: BlockNumber = 10 * MINUTES;
+}
+
+pub use network::*;
+
 /// Fee-related.
 /// The block saturation level. Fees will be updates based on this value.
 pub const TARGET_BLOCK_FULLNESS: Perquintill = Perquintill::from_percent(25);
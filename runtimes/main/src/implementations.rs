@@ -0,0 +1,401 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+// Modified by Eliott Teissonniere (Nodle International) to contain
+// only the useful code for the Nodle Chain project.
+
+//! Auxillary struct/enums for polkadot runtime.
+
+use crate::{
+    Allocations, AllocationsOracles, Authorship, Balances, Call, CompanyReserve,
+    ConnectivityVerifiers, DataMarket, Disputes, Identity, Parameters, PkiRootOfTrust, PkiTcr,
+    PoaSessions, Proxy, Runtime, Timestamp,
+};
+use chain_deposits_api::{Deposit, DepositReason};
+use frame_support::{
+    storage::StoragePrefixedMap,
+    traits::{Contains, Currency, Filter, Get, Imbalance, InstanceFilter, OnUnbalanced},
+    RuntimeDebug,
+};
+use nodle_chain_primitives::{AccountId, Balance};
+use nodle_support::{KeylessAccounts, MemberSet};
+use pallet_allocations::ReportingPeriods;
+use pallet_feeless::ClassifyFeeless;
+use pallet_proxy::ProxyDefinition;
+use pallet_registry_sync::SyncedSet;
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::{traits::Saturating, DispatchResult, Perbill};
+use sp_std::prelude::*;
+
+/// Logic for the author to get a portion of fees.
+pub struct Author;
+impl OnUnbalanced<NegativeImbalance> for Author {
+    fn on_nonzero_unbalanced(amount: NegativeImbalance) {
+        Balances::resolve_creating(&Authorship::author(), amount);
+    }
+}
+
+type NegativeImbalance = <Balances as Currency<AccountId>>::NegativeImbalance;
+
+/// Splits fees between reserve and block author, by default 20/80. The ratio can be
+/// updated by governance through the parameters pallet.
+pub struct DealWithFees;
+impl OnUnbalanced<NegativeImbalance> for DealWithFees {
+    fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalance>) {
+        if let Some(fees) = fees_then_tips.next() {
+            let reserve_share = Parameters::reserve_fee_share();
+            let author_share = Perbill::one().saturating_sub(reserve_share);
+            let (reserve_part, author_part) =
+                (reserve_share.deconstruct(), author_share.deconstruct());
+
+            // for fees, by default 20% to treasury, 80% to author
+            let mut split = fees.ration(reserve_part, author_part);
+            if let Some(tips) = fees_then_tips.next() {
+                // for tips, if any, same split than fees (though this can be anything)
+                tips.ration_merge_into(reserve_part, author_part, &mut split);
+            }
+            CompanyReserve::on_unbalanced(split.0);
+            Author::on_unbalanced(split.1);
+        }
+    }
+}
+
+/// Share the validators' part of every emission equally between the current
+/// validators, what can not be split evenly goes to the company reserve.
+pub struct RewardValidators;
+impl OnUnbalanced<NegativeImbalance> for RewardValidators {
+    fn on_nonzero_unbalanced(amount: NegativeImbalance) {
+        let validators = PoaSessions::validators();
+        let mut remaining = amount;
+
+        if !validators.is_empty() {
+            let reward = remaining.peek() / validators.len() as Balance;
+            for validator in validators.iter() {
+                let (for_validator, rest) = remaining.split(reward);
+                Balances::resolve_creating(validator, for_validator);
+                remaining = rest;
+            }
+        }
+
+        CompanyReserve::on_unbalanced(remaining);
+    }
+}
+
+/// The type used to represent the kinds of proxying allowed.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug)]
+pub enum ProxyType {
+    Any,
+    NonTransfer,
+    Governance,
+}
+impl Default for ProxyType {
+    fn default() -> Self {
+        Self::Any
+    }
+}
+impl InstanceFilter<Call> for ProxyType {
+    fn filter(&self, c: &Call) -> bool {
+        match self {
+            ProxyType::Any => true,
+            ProxyType::NonTransfer => !matches!(
+                c,
+                Call::Balances(..)
+                    | Call::Grants(..)
+                    | Call::Indices(pallet_indices::Call::transfer(..))
+            ),
+            ProxyType::Governance => matches!(
+                c,
+                Call::Democracy(..)
+                    | Call::FinancialCommittee(..)
+                    | Call::RootCommittee(..)
+                    | Call::TechnicalCommittee(..)
+                    | Call::CompanyReserve(..)
+                    | Call::UsaReserve(..)
+                    | Call::InternationalReserve(..)
+            ),
+        }
+    }
+    fn is_superset(&self, o: &Self) -> bool {
+        match (self, o) {
+            (x, y) if x == y => true,
+            (ProxyType::Any, _) => true,
+            (_, ProxyType::Any) => false,
+            (ProxyType::NonTransfer, _) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Accounts which are not subject to transaction rate limiting: oracles and validators
+/// are expected to submit a lot of transactions.
+pub struct RateLimitExempted;
+impl Contains<AccountId> for RateLimitExempted {
+    fn contains(who: &AccountId) -> bool {
+        Allocations::is_oracle(who.clone()) || PoaSessions::validators().contains(who)
+    }
+
+    fn sorted_members() -> Vec<AccountId> {
+        let mut members = AllocationsOracles::members();
+        members.extend(PoaSessions::validators());
+        members.sort();
+        members.dedup();
+        members
+    }
+}
+
+/// Oracles are added and removed through the membership pallet, which keeps its
+/// members and prime consistent and notifies the allocations pallet.
+pub struct OracleMembership;
+impl MemberSet<AccountId> for OracleMembership {
+    fn add_member(who: &AccountId) -> Result<(), &'static str> {
+        AllocationsOracles::add_member(frame_system::RawOrigin::Root.into(), who.clone())
+            .map_err(|_| "Already a member of the oracles set")
+    }
+
+    fn remove_member(who: &AccountId) -> Result<(), &'static str> {
+        AllocationsOracles::remove_member(frame_system::RawOrigin::Root.into(), who.clone())
+            .map_err(|_| "Not a member of the oracles set")
+    }
+}
+
+/// Oracles listed in an external registry are synced at once, within the committee
+/// bounds of the allocations pallet.
+impl SyncedSet<AccountId> for OracleMembership {
+    fn sorted_members() -> Vec<AccountId> {
+        AllocationsOracles::members()
+    }
+
+    fn reset_members(members: &[AccountId]) -> DispatchResult {
+        Allocations::ensure_committee_size(members.len() as u32)?;
+        AllocationsOracles::reset_members(frame_system::RawOrigin::Root.into(), members.to_vec())
+    }
+}
+
+/// Oracles may also fill the orders of the data market on behalf of the network.
+impl Contains<AccountId> for OracleMembership {
+    fn sorted_members() -> Vec<AccountId> {
+        AllocationsOracles::members()
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add(who: &AccountId) {
+        let _ = <Self as MemberSet<AccountId>>::add_member(who);
+    }
+}
+
+/// Verifiers challenging gateways, managed in their own membership set.
+pub struct VerifierMembership;
+impl Contains<AccountId> for VerifierMembership {
+    fn sorted_members() -> Vec<AccountId> {
+        ConnectivityVerifiers::members()
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add(who: &AccountId) {
+        let _ =
+            ConnectivityVerifiers::add_member(frame_system::RawOrigin::Root.into(), who.clone());
+    }
+}
+
+/// Organizations are paid into pure proxies of their multisig. The chain creates them
+/// so that the multisig does not need funds to cover the proxy deposit.
+pub struct OrganizationAccounts;
+impl OrganizationAccounts {
+    fn account_of(controller: &AccountId) -> AccountId {
+        // Users can not spawn pure proxies at genesis, the account can only be ours
+        Proxy::anonymous_account(controller, &ProxyType::Any, 0, Some((0, 0)))
+    }
+}
+impl KeylessAccounts<AccountId> for OrganizationAccounts {
+    fn keyless_account(controller: &AccountId) -> Option<AccountId> {
+        Some(Self::account_of(controller))
+    }
+
+    fn create_keyless_account(controller: &AccountId) {
+        let account = Self::account_of(controller);
+        pallet_proxy::Proxies::<Runtime>::mutate(&account, |(proxies, _deposit)| {
+            if proxies.iter().all(|proxy| proxy.delegate != *controller) {
+                proxies.push(ProxyDefinition {
+                    delegate: controller.clone(),
+                    proxy_type: ProxyType::Any,
+                    delay: 0,
+                });
+            }
+        });
+    }
+}
+
+/// Filter applied to every call, transfers of value between accounts can be disabled by
+/// governance during a phased token launch, be it plain balance transfers, grants or
+/// vesting positions. Allocations, vesting claims and governance keep working.
+pub struct BaseCallFilter;
+impl Filter<Call> for BaseCallFilter {
+    fn filter(c: &Call) -> bool {
+        let is_transfer = matches!(
+            c,
+            Call::Balances(pallet_balances::Call::transfer(..))
+                | Call::Balances(pallet_balances::Call::transfer_keep_alive(..))
+                | Call::Grants(pallet_grants::Call::add_vesting_schedule(..))
+                | Call::Grants(pallet_grants::Call::tokenize(..))
+                | Call::Grants(pallet_grants::Call::transfer_position(..))
+                | Call::Grants(pallet_grants::Call::split_position(..))
+        );
+
+        !is_transfer || Parameters::transfers_enabled()
+    }
+}
+
+/// Calls that can be submitted without fees, up to a quota, by IoT devices.
+pub struct FeelessCalls;
+impl ClassifyFeeless<AccountId, Call> for FeelessCalls {
+    fn is_feeless(who: &AccountId, c: &Call) -> bool {
+        // Device check-ins, be it a gateway answering a connectivity challenge or a
+        // device anchoring an attestation, are the bulk of the traffic and should not
+        // cost anything. Revoking a compromised device certificate should never be
+        // delayed because its owner lacks funds to pay the fees, neither should a first
+        // payout, as long as there is one to claim. Device session keys are not
+        // funded at all.
+        match c {
+            Call::Allocations(pallet_allocations::Call::claim(era)) => {
+                Allocations::can_claim(*era, who)
+            }
+            _ => matches!(
+                c,
+                Call::Connectivity(pallet_connectivity::Call::respond(..))
+                    | Call::Attestations(pallet_attestations::Call::submit_attestation(..))
+                    | Call::PkiRootOfTrust(pallet_root_of_trust::Call::revoke_child(..))
+                    | Call::DeviceSessions(pallet_device_sessions::Call::act(..))
+            ),
+        }
+    }
+}
+
+/// Calls devices can submit with a session key on behalf of their owner.
+pub struct DeviceSessionCalls;
+impl Filter<Call> for DeviceSessionCalls {
+    fn filter(c: &Call) -> bool {
+        // There are no dedicated heartbeat or attestation calls yet, devices signal
+        // that they are alive with a remark
+        matches!(c, Call::System(frame_system::Call::remark(..)))
+    }
+}
+
+/// Allocations are reported per calendar month, periods are numbered `YYYYMM` after
+/// the UTC date of the block's timestamp.
+pub struct CalendarMonths;
+impl ReportingPeriods for CalendarMonths {
+    fn current() -> u32 {
+        // Days since the unix epoch converted to a civil date, see
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = Timestamp::now() / 86_400_000;
+        let z = days + 719_468;
+        let era = z / 146_097;
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let (year, month) = if mp < 10 {
+            (era * 400 + yoe, mp + 3)
+        } else {
+            (era * 400 + yoe + 1, mp - 9)
+        };
+        (year * 100 + month) as u32
+    }
+}
+
+/// Storage maps expected to grow with the network, their size is sampled every era.
+pub struct StateSizeTrackedPrefixes;
+impl Get<Vec<(Vec<u8>, Vec<u8>)>> for StateSizeTrackedPrefixes {
+    fn get() -> Vec<(Vec<u8>, Vec<u8>)> {
+        vec![
+            (
+                b"System.Account".to_vec(),
+                frame_system::Account::<Runtime>::final_prefix().to_vec(),
+            ),
+            (
+                b"Grants.VestingSchedules".to_vec(),
+                pallet_grants::VestingSchedules::<Runtime>::final_prefix().to_vec(),
+            ),
+            (
+                b"PkiTcr.Applications".to_vec(),
+                pallet_tcr::Applications::<Runtime, pallet_tcr::Instance1>::final_prefix().to_vec(),
+            ),
+            (
+                b"PkiTcr.Members".to_vec(),
+                pallet_tcr::Members::<Runtime, pallet_tcr::Instance1>::final_prefix().to_vec(),
+            ),
+            (
+                b"PkiRootOfTrust.Slots".to_vec(),
+                pallet_root_of_trust::Slots::<Runtime>::final_prefix().to_vec(),
+            ),
+        ]
+    }
+}
+
+/// Funds held on behalf of an account. Pallets report their own reserves since they
+/// are not labeled, what is left of the reserved balance is reported as a whole.
+pub struct AccountDeposits;
+impl AccountDeposits {
+    pub fn of(who: &AccountId) -> Vec<Deposit<Balance>> {
+        let mut deposits: Vec<Deposit<Balance>> = Balances::locks(who)
+            .into_iter()
+            .map(|lock| {
+                let reason = match lock.id {
+                    pallet_grants::VESTING_LOCK_ID => DepositReason::Grant,
+                    pallet_allocations::REWARDS_LOCK_ID => DepositReason::Rewards,
+                    _ => DepositReason::OtherLock,
+                };
+                Deposit {
+                    reason,
+                    amount: lock.amount,
+                    locked: true,
+                }
+            })
+            .collect();
+
+        let identity = Identity::identity(who)
+            .map(|registration| registration.deposit)
+            .unwrap_or_default()
+            .saturating_add(Identity::subs_of(who).0);
+        let reserves = vec![
+            (
+                DepositReason::CertificateSlot,
+                PkiRootOfTrust::deposits_of(who),
+            ),
+            (DepositReason::Identity, identity),
+            (DepositReason::Registry, PkiTcr::deposits_of(who)),
+            (DepositReason::DisputeBond, Disputes::deposits_of(who)),
+            (DepositReason::DataOrder, DataMarket::deposits_of(who)),
+        ];
+        let explained = reserves
+            .iter()
+            .fold(0, |acc: Balance, (_, amount)| acc.saturating_add(*amount));
+        let others = Balances::reserved_balance(who).saturating_sub(explained);
+
+        deposits.extend(
+            reserves
+                .into_iter()
+                .chain(sp_std::iter::once((DepositReason::OtherReserve, others)))
+                .map(|(reason, amount)| Deposit {
+                    reason,
+                    amount,
+                    locked: false,
+                }),
+        );
+        deposits.retain(|deposit| deposit.amount > 0);
+        deposits
+    }
+}
@@ -24,17 +24,19 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use chain_primitives::RewardBudget;
 use frame_support::{
     construct_runtime, debug, parameter_types,
     traits::{KeyOwnerProofSystem, Randomness},
     weights::{
         constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
-        IdentityFee, Weight,
+        GetDispatchInfo, IdentityFee, Weight,
     },
 };
 use nodle_chain_primitives::{
     AccountId, AccountIndex, Balance, BlockNumber, CertificateId, Hash, Index, Moment, Signature,
 };
+use pallet_feeless_runtime_api::FeeEstimate;
 use pallet_grandpa::{
     fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
@@ -46,7 +48,7 @@ use parity_scale_codec::Encode;
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use sp_core::{
     crypto::KeyTypeId,
-    u32_trait::{_1, _2},
+    u32_trait::{_1, _2, _3},
     OpaqueMetadata,
 };
 #[cfg(any(feature = "std", test))]
@@ -54,8 +56,8 @@ pub use sp_runtime::BuildStorage;
 use sp_runtime::{
     create_runtime_str, generic, impl_opaque_keys,
     traits::{
-        BlakeTwo256, Block as BlockT, ConvertInto, NumberFor, OpaqueKeys, SaturatedConversion,
-        Saturating, StaticLookup,
+        AccountIdConversion, BlakeTwo256, Block as BlockT, ConvertInto, NumberFor, OpaqueKeys,
+        SaturatedConversion, Saturating, StaticLookup,
     },
     transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
     ApplyExtrinsicResult, FixedPointNumber, ModuleId, Perbill, Perquintill,
@@ -68,8 +70,17 @@ use static_assertions::const_assert;
 
 pub mod constants;
 mod implementations;
+mod origins;
 
-use implementations::{DealWithFees, ProxyType};
+use implementations::{
+    AccountDeposits, BaseCallFilter, CalendarMonths, DealWithFees, DeviceSessionCalls,
+    FeelessCalls, OracleMembership, OrganizationAccounts, ProxyType, RateLimitExempted,
+    RewardValidators, StateSizeTrackedPrefixes, VerifierMembership,
+};
+use origins::{
+    AllocationsFallback, DisputeArbiter, OracleCommittee, RegistrarOrigin, ReserveSpender,
+    StableCustodian,
+};
 
 impl_opaque_keys! {
     pub struct SessionKeys {
@@ -86,8 +97,8 @@ impl_opaque_keys! {
 /// In particular: bug fixes should result in an increment of `spec_version` and possibly `authoring_version`,
 /// absolutely not `impl_version` since they change the semantics of the runtime.
 pub const VERSION: RuntimeVersion = RuntimeVersion {
-    spec_name: create_runtime_str!("nodle-chain"),
-    impl_name: create_runtime_str!("nodle-chain"),
+    spec_name: create_runtime_str!(constants::SPEC_NAME),
+    impl_name: create_runtime_str!(constants::SPEC_NAME),
 
     /// `authoring_version` is the version of the authorship interface. An authoring node
     /// will not attempt to author blocks unless this is equal to its native runtime.
@@ -96,7 +107,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     /// Version of the runtime specification. A full-node will not attempt to use its native
     /// runtime in substitute for the on-chain Wasm runtime unless all of `spec_name`,
     /// `spec_version` and `authoring_version` are the same between Wasm and native.
-    spec_version: 43,
+    spec_version: 53,
 
     /// Version of the implementation of the specification. Nodes are free to ignore this; it
     /// serves only as an indication that the code is different; as long as the other two versions
@@ -107,7 +118,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     impl_version: 0,
 
     /// Used for hardware wallets. This typically happens when `SignedExtra` changes.
-    transaction_version: 3,
+    transaction_version: 6,
 
     apis: RUNTIME_API_VERSIONS,
 };
@@ -126,6 +137,9 @@ parameter_types! {
     pub const BlockHashCount: BlockNumber = 2400;
     /// We allow for 2 seconds of compute with a 6 second average block time.
     pub const MaximumBlockWeight: Weight = 2 * WEIGHT_PER_SECOND;
+    /// Normal transactions can only fill 75% of the block weight and length, the rest
+    /// is reserved for operational ones (oracle allocations and governance calls) so
+    /// that they can not be crowded out by regular traffic.
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
     /// Assume 10% of weight for average on_initialize calls.
     pub MaximumExtrinsicWeight: Weight =
@@ -157,13 +171,14 @@ impl frame_system::Trait for Runtime {
     type Version = Version;
     type PalletInfo = PalletInfo;
     type AccountData = pallet_balances::AccountData<Balance>;
-    type OnNewAccount = ();
-    type OnKilledAccount = ();
+    /// Allocations keep track of the age of accounts to catch sybil farming
+    type OnNewAccount = Allocations;
+    type OnKilledAccount = Allocations;
     type DbWeight = RocksDbWeight;
     type BlockExecutionWeight = BlockExecutionWeight;
     type ExtrinsicBaseWeight = ExtrinsicBaseWeight;
     type MaximumExtrinsicWeight = MaximumExtrinsicWeight;
-    type BaseCallFilter = ();
+    type BaseCallFilter = BaseCallFilter;
     type SystemWeightInfo = ();
 }
 
@@ -276,7 +291,12 @@ where
             frame_system::CheckEra::<Runtime>::from(era),
             frame_system::CheckNonce::<Runtime>::from(nonce),
             frame_system::CheckWeight::<Runtime>::new(),
-            pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+            pallet_rate_limit::CheckRateLimit::<Runtime>::new(),
+            pallet_device_sessions::CheckSessionKey::<Runtime>::new(),
+            pallet_allocations::CheckOracle::<Runtime>::new(),
+            pallet_feeless::ChargeFeelessOrPayment::<Runtime>::from(
+                pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+            ),
         );
         let raw_payload = SignedPayload::new(call, extra)
             .map_err(|e| {
@@ -338,6 +358,8 @@ impl pallet_indices::Trait for Runtime {
 }
 
 parameter_types! {
+    // Grants and rewards locks never go below this amount, so that accounts holding
+    // nothing else are not reaped by a withdrawal.
     pub const ExistentialDeposit: Balance = 1 * constants::MILLICENTS;
     // For weight estimation, we assume that the most locks on an individual account will be 50.
     // This number may need to be adjusted in the future if this assumption no longer holds true.
@@ -372,11 +394,43 @@ impl pallet_transaction_payment::Trait for Runtime {
         TargetedFeeAdjustment<Self, TargetBlockFullness, AdjustmentVariable, MinimumMultiplier>;
 }
 
+parameter_types! {
+    // Feeless calls are meant to be small, frequent, operations, a few storage
+    // accesses at most
+    pub const MaximumFeelessWeight: Weight = 500_000_000;
+    pub FeelessQuota: u32 = Parameters::feeless_quota();
+    pub const FeelessQuotaPeriod: BlockNumber = constants::HOURS;
+}
+
+impl pallet_feeless::Trait for Runtime {
+    type FeelessCalls = FeelessCalls;
+    type MaximumFeelessWeight = MaximumFeelessWeight;
+    type FeelessQuota = FeelessQuota;
+    type QuotaPeriod = FeelessQuotaPeriod;
+}
+
+parameter_types! {
+    pub const GrantsModuleId: ModuleId = ModuleId(*b"py/grnts"); // 5EYCAe5ijiYeDjvvbsVDPLdi6pCFN43r49MvHVerFL4ihXHk
+}
+
 impl pallet_grants::Trait for Runtime {
     type Event = Event;
     type Currency = Balances;
-    type CancelOrigin =
-        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, FinancialCollective>;
+    type CancelOrigin = ReserveSpender;
+    type ModuleId = GrantsModuleId;
+    type WeightInfo = pallet_grants::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    pub const AirdropModuleId: ModuleId = ModuleId(*b"py/airdr"); // 5EYCAe5ijiYdQS1YJcEbhsRza6hA3Nu3QBTwvxFMrPoq6aLi
+    pub const AirdropUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+}
+
+impl pallet_airdrop::Trait for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type ModuleId = AirdropModuleId;
+    type UnsignedPriority = AirdropUnsignedPriority;
 }
 
 parameter_types! {
@@ -434,7 +488,7 @@ impl pallet_poa::Trait for Runtime {}
 
 // Shared parameters with all collectives / committees
 parameter_types! {
-    pub const MotionDuration: BlockNumber = 2 * constants::DAYS;
+    pub const MotionDuration: BlockNumber = constants::MOTION_DURATION;
     pub const MaxProposals: u32 = 100;
     pub const MaxMembers: u32 = 50;
 }
@@ -550,7 +604,7 @@ impl pallet_scheduler::Trait for Runtime {
 }
 
 parameter_types! {
-    pub const AmendmentDelay: BlockNumber = 2 * constants::DAYS;
+    pub const AmendmentDelay: BlockNumber = constants::AMENDMENT_DELAY;
 }
 
 impl pallet_amendments::Trait for Runtime {
@@ -563,6 +617,55 @@ impl pallet_amendments::Trait for Runtime {
         pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, RootCollective>;
     type Delay = AmendmentDelay;
     type PalletsOrigin = OriginCaller;
+    type WeightInfo = pallet_amendments::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    pub const LaunchPeriod: BlockNumber = constants::LAUNCH_PERIOD;
+    pub const VotingPeriod: BlockNumber = constants::VOTING_PERIOD;
+    pub const FastTrackVotingPeriod: BlockNumber = constants::FAST_TRACK_VOTING_PERIOD;
+    pub const EnactmentPeriod: BlockNumber = constants::ENACTMENT_PERIOD;
+    pub const CooloffPeriod: BlockNumber = constants::VOTING_PERIOD;
+    pub const InstantAllowed: bool = true;
+    pub const MinimumDeposit: Balance = 100 * constants::NODL;
+    pub const PreimageByteDeposit: Balance = constants::CENTS;
+    pub const MaxVotes: u32 = 100;
+}
+
+// Token holders vote on referenda, or delegate their voting power with a conviction
+// multiplier so that they do not have to vote on everything themselves.
+impl pallet_democracy::Trait for Runtime {
+    type Proposal = Call;
+    type Event = Event;
+    type Currency = Balances;
+    type EnactmentPeriod = EnactmentPeriod;
+    type LaunchPeriod = LaunchPeriod;
+    type VotingPeriod = VotingPeriod;
+    type MinimumDeposit = MinimumDeposit;
+    type ExternalOrigin =
+        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, RootCollective>;
+    type ExternalMajorityOrigin =
+        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, RootCollective>;
+    type ExternalDefaultOrigin =
+        pallet_collective::EnsureProportionAtLeast<_1, _1, AccountId, RootCollective>;
+    type FastTrackOrigin =
+        pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, TechnicalCollective>;
+    type InstantOrigin =
+        pallet_collective::EnsureProportionAtLeast<_1, _1, AccountId, TechnicalCollective>;
+    type InstantAllowed = InstantAllowed;
+    type FastTrackVotingPeriod = FastTrackVotingPeriod;
+    type CancellationOrigin =
+        pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, RootCollective>;
+    type VetoOrigin = pallet_collective::EnsureMember<AccountId, TechnicalCollective>;
+    type CooloffPeriod = CooloffPeriod;
+    type PreimageByteDeposit = PreimageByteDeposit;
+    type OperationalPreimageOrigin = pallet_collective::EnsureMember<AccountId, RootCollective>;
+    type Slash = CompanyReserve;
+    type Scheduler = Scheduler;
+    type PalletsOrigin = OriginCaller;
+    type MaxVotes = MaxVotes;
+    type WeightInfo = ();
+    type MaxProposals = MaxProposals;
 }
 
 parameter_types! {
@@ -572,10 +675,10 @@ parameter_types! {
 impl pallet_reserve::Trait<pallet_reserve::Instance1> for Runtime {
     type Event = Event;
     type Currency = pallet_balances::Module<Runtime>;
-    type ExternalOrigin =
-        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, FinancialCollective>;
+    type ExternalOrigin = ReserveSpender;
     type Call = Call;
     type ModuleId = CompanyReserveModuleId;
+    type WeightInfo = pallet_reserve::weights::SubstrateWeight<Runtime>;
 }
 
 parameter_types! {
@@ -585,10 +688,10 @@ parameter_types! {
 impl pallet_reserve::Trait<pallet_reserve::Instance2> for Runtime {
     type Event = Event;
     type Currency = pallet_balances::Module<Runtime>;
-    type ExternalOrigin =
-        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, FinancialCollective>;
+    type ExternalOrigin = ReserveSpender;
     type Call = Call;
     type ModuleId = InternationalReserveModuleId;
+    type WeightInfo = pallet_reserve::weights::SubstrateWeight<Runtime>;
 }
 
 parameter_types! {
@@ -598,10 +701,10 @@ parameter_types! {
 impl pallet_reserve::Trait<pallet_reserve::Instance3> for Runtime {
     type Event = Event;
     type Currency = pallet_balances::Module<Runtime>;
-    type ExternalOrigin =
-        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, FinancialCollective>;
+    type ExternalOrigin = ReserveSpender;
     type Call = Call;
     type ModuleId = UsaReserveModuleId;
+    type WeightInfo = pallet_reserve::weights::SubstrateWeight<Runtime>;
 }
 
 parameter_types! {
@@ -624,8 +727,7 @@ impl pallet_identity::Trait for Runtime {
     type Slashed = CompanyReserve;
     type ForceOrigin =
         pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, TechnicalCollective>;
-    type RegistrarOrigin =
-        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, TechnicalCollective>;
+    type RegistrarOrigin = RegistrarOrigin;
     type MaxRegistrars = MaxRegistrars;
     type WeightInfo = ();
 }
@@ -723,15 +825,18 @@ impl pallet_tcr::Trait<pallet_tcr::Instance1> for Runtime {
     type FinalizeApplicationPeriod = FinalizeApplicationPeriod;
     type FinalizeChallengePeriod = FinalizeChallengePeriod;
     type ChangeMembers = PkiRootOfTrust;
+    type WeightInfo = pallet_tcr::weights::SubstrateWeight<Runtime>;
 }
 
 parameter_types! {
-    // Total onboarding cost: 10 NODL + fees (with TCR application)
+    // Total onboarding cost: 10 NODL + 10 NODL refundable bond + fees (with TCR application)
     pub const SlotBookingCost: Balance = 10 * constants::NODL;
     // Doesn't need to be as expensive
     pub const SlotRenewingCost: Balance = 1 * constants::NODL;
     // One year validity, unless revoked or renewed
     pub const SlotValidity: BlockNumber = 365 * constants::DAYS;
+    // Returned when the slot is revoked, slashed if governance has to remove it
+    pub const SlotBond: Balance = 10 * constants::NODL;
 }
 
 impl pallet_root_of_trust::Trait for Runtime {
@@ -741,45 +846,308 @@ impl pallet_root_of_trust::Trait for Runtime {
     type SlotBookingCost = SlotBookingCost;
     type SlotRenewingCost = SlotRenewingCost;
     type SlotValidity = SlotValidity;
+    type SlotBond = SlotBond;
+    type ForceOrigin = RegistrarOrigin;
     type FundsCollector = CompanyReserve;
+    type WeightInfo = pallet_root_of_trust::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_denylist::Trait for Runtime {
+    type Event = Event;
+    type ManagerOrigin = RegistrarOrigin;
+    type WeightInfo = pallet_denylist::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    /// Devices get a new session key every week at least
+    pub const MaxDeviceSessionDuration: BlockNumber = 7 * constants::DAYS;
+}
+
+impl pallet_device_sessions::Trait for Runtime {
+    type Event = Event;
+    type SessionCall = Call;
+    type SessionCalls = DeviceSessionCalls;
+    type MaxSessionDuration = MaxDeviceSessionDuration;
+    type WeightInfo = pallet_device_sessions::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_code_registry::Trait for Runtime {
+    type Event = Event;
+    type ProposerOrigin = frame_system::EnsureSignedBy<TechnicalMembership, AccountId>;
+    type WeightInfo = pallet_code_registry::weights::SubstrateWeight<Runtime>;
 }
 
 impl pallet_emergency_shutdown::Trait for Runtime {
     type Event = Event;
     type ShutdownOrigin =
         pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, RootCollective>;
+    type WeightInfo = pallet_emergency_shutdown::weights::SubstrateWeight<Runtime>;
 }
 
 parameter_types! {
-    pub const ProtocolFee: Perbill = Perbill::from_percent(20);
-    pub const MaximumCoinsEverAllocated: Balance = 1_259_995_654_473_120_000_000;
+    pub ProtocolFee: Perbill = Parameters::allocation_fee();
+    pub MinimumAllocation: Balance = Parameters::minimum_allocation();
+    pub MaximumCoinsEverAllocated: Balance = <Emission as RewardBudget<Balance>>::budget();
+    /// Grantees are paid once a day rather than on every allocation
+    pub const PayoutEraDuration: BlockNumber = constants::DAYS;
+    pub const MaxSettlementsPerBlock: u32 = 500;
+    pub const MaxPrunedPerBlock: u32 = 1_000;
+    pub const MaxUnsignedClaimsPerBlock: u32 = 100;
+    pub const MaxClaimsPerBatch: u32 = 100;
+    /// Rewards are freely transferable, set to a future block to lock the payouts
+    /// made until then during a launch phase
+    pub const RewardsUnlockBlock: BlockNumber = 0;
+    /// Prices older than an hour are not used to convert fiat allocations
+    pub const PriceValidity: BlockNumber = constants::HOURS;
+    /// Oracles are considered down after six sessions without allocations
+    pub const AllocationsStallThreshold: BlockNumber = 6 * constants::EPOCH_DURATION_IN_BLOCKS;
+    /// New oracles can only mint once the community had time to review them
+    pub const OracleActivationDelay: BlockNumber = constants::ORACLE_ACTIVATION_DELAY;
+    pub const AllocationsUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 2;
 }
 
 impl pallet_allocations::Trait for Runtime {
     type Event = Event;
     type Currency = Balances;
     type ProtocolFee = ProtocolFee;
+    type FeeRounding = pallet_allocations::rounding::RemainderToBeneficiary;
     type ProtocolFeeReceiver = CompanyReserve;
     type MaximumCoinsEverAllocated = MaximumCoinsEverAllocated;
+    type MinimumAllocation = MinimumAllocation;
     type ExistentialDeposit = <Runtime as pallet_balances::Trait>::ExistentialDeposit;
+    type OnDust = pallet_allocations::dust::Accumulate<Runtime>;
+    type OracleOrigin = OracleCommittee;
+    type OracleActivationDelay = OracleActivationDelay;
+    type OracleMembership = OracleMembership;
+    type CommitteeOrigin = RegistrarOrigin;
+    type FallbackOrigin = AllocationsFallback;
+    type StallThreshold = AllocationsStallThreshold;
+    type DeviceId = (CertificateId, CertificateId);
+    type Devices = RegistrySync;
+    type OrganizationAccounts = OrganizationAccounts;
+    type ComplianceCheck = Denylist;
+    type PriceValidity = PriceValidity;
+    type PointsRateOrigin =
+        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, FinancialCollective>;
+    type DelegationOrigin =
+        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, FinancialCollective>;
+    type Coverage = Connectivity;
+    type OnAllocation = ();
+    type ReportingPeriods = CalendarMonths;
+    type Time = Timestamp;
+    type PayoutEraDuration = PayoutEraDuration;
+    type MaxSettlementsPerBlock = MaxSettlementsPerBlock;
+    type MaxPrunedPerBlock = MaxPrunedPerBlock;
+    type MaxUnsignedClaimsPerBlock = MaxUnsignedClaimsPerBlock;
+    type UnsignedPriority = AllocationsUnsignedPriority;
+    type MaxClaimsPerBatch = MaxClaimsPerBatch;
+    type Public = <Signature as sp_runtime::traits::Verify>::Signer;
+    type Signature = Signature;
+    type RewardsUnlockBlock = RewardsUnlockBlock;
+    type WeightInfo = pallet_allocations::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    /// Discourages frivolous disputes, arbiters have to look into every one of them
+    pub const DisputeBond: Balance = 10 * constants::DOLLARS;
+}
+
+impl pallet_disputes::Trait for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type Batches = Allocations;
+    type DisputeBond = DisputeBond;
+    type ArbiterOrigin = DisputeArbiter;
+    type Slashed = CompanyReserve;
+    type WeightInfo = pallet_disputes::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    pub const DataAcceptancePeriod: BlockNumber = 2 * constants::DAYS;
+    pub const MaxDataTypeLength: u32 = 64;
+}
+
+impl pallet_data_market::Trait for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type DeviceId = (CertificateId, CertificateId);
+    type Devices = RegistrySync;
+    type Oracles = OracleMembership;
+    type AcceptancePeriod = DataAcceptancePeriod;
+    type MaxDataTypeLength = MaxDataTypeLength;
+    type ArbiterOrigin = DisputeArbiter;
+    type WeightInfo = pallet_data_market::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    pub const MinAttestationInterval: BlockNumber = 10 * constants::MINUTES;
+    pub const MaxUnsignedAttestationsPerBlock: u32 = 200;
+    pub const AttestationsUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 4;
+}
+
+impl pallet_attestations::Trait for Runtime {
+    type Event = Event;
+    type Signature = Signature;
+    type Public = <Signature as sp_runtime::traits::Verify>::Signer;
+    type Devices = RegistrySync;
+    type MinAttestationInterval = MinAttestationInterval;
+    type MaxUnsignedAttestationsPerBlock = MaxUnsignedAttestationsPerBlock;
+    type UnsignedPriority = AttestationsUnsignedPriority;
+    type WeightInfo = pallet_attestations::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    pub const MaxMatchedPots: u32 = 100;
+    /// Burns in favour of public goods are matched up to a weekly budget
+    pub const MatchEraDuration: BlockNumber = 7 * constants::DAYS;
+    pub const MaxMatchPerEra: Balance = 10_000 * constants::DOLLARS;
 }
 
+impl pallet_matching::Trait for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type Reserve = CompanyReserve;
+    type ManagerOrigin = ReserveSpender;
+    type Formula = pallet_matching::formula::Quadratic;
+    type MaxPots = MaxMatchedPots;
+    type MatchEraDuration = MatchEraDuration;
+    type MaxMatchPerEra = MaxMatchPerEra;
+    type WeightInfo = pallet_matching::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    // Fees are low for IoT devices, we thus limit how many transactions a single
+    // account can get included per block to make spamming more expensive
+    pub MaxTransactionsPerBlock: u32 = Parameters::max_transactions_per_block();
+}
+
+impl pallet_rate_limit::Trait for Runtime {
+    type MaxTransactionsPerBlock = MaxTransactionsPerBlock;
+    type Exempted = RateLimitExempted;
+}
+
+impl pallet_parameters::Trait for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type UpdateOrigin =
+        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, FinancialCollective>;
+    type WeightInfo = pallet_parameters::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    pub const StateSizeSamplingPeriod: BlockNumber = constants::DAYS;
+    pub const MaxStateKeysPerBlock: u32 = 500;
+}
+
+impl pallet_state_size::Trait for Runtime {
+    type Event = Event;
+    type TrackedPrefixes = StateSizeTrackedPrefixes;
+    type SamplingPeriod = StateSizeSamplingPeriod;
+    type MaxKeysPerBlock = MaxStateKeysPerBlock;
+}
+
+parameter_types! {
+    /// Half of what we assume on_initialize calls use on average
+    pub const HousekeepingWeightShare: Perbill = Perbill::from_percent(5);
+}
+
+impl pallet_housekeeping::Trait for Runtime {
+    // Emission runs on its own, issuing coins at the start of an era must never wait
+    type Tasks = (Allocations, RateLimit, StateSize, Matching, Connectivity);
+    type WeightShare = HousekeepingWeightShare;
+}
+
+parameter_types! {
+    pub const EmissionEraDuration: BlockNumber = constants::DAYS;
+    pub const ValidatorsEmissionShare: Perbill = Perbill::from_percent(20);
+    /// Allocations budget we used before emissions were introduced
+    pub const InitialAllocationsBudget: Balance = 1_259_995_654_473_120_000_000;
+}
+
+impl pallet_emission::Trait for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type CurveOrigin =
+        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, FinancialCollective>;
+    type EraDuration = EmissionEraDuration;
+    type ValidatorsShare = ValidatorsEmissionShare;
+    type ValidatorsRewards = RewardValidators;
+    type InitialAllocationsBudget = InitialAllocationsBudget;
+    type WeightInfo = pallet_emission::weights::SubstrateWeight<Runtime>;
+}
+
+// Governance adds and removes oracles through the allocations pallet, which keeps the
+// committee within its bounds
 impl pallet_membership::Trait<pallet_membership::Instance5> for Runtime {
     type Event = Event;
-    type AddOrigin =
-        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, TechnicalCollective>;
-    type RemoveOrigin =
-        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, TechnicalCollective>;
-    type SwapOrigin =
-        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, TechnicalCollective>;
-    type ResetOrigin =
-        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, TechnicalCollective>;
-    type PrimeOrigin =
-        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, TechnicalCollective>;
+    type AddOrigin = frame_system::EnsureRoot<AccountId>;
+    type RemoveOrigin = frame_system::EnsureRoot<AccountId>;
+    type SwapOrigin = RegistrarOrigin;
+    type ResetOrigin = frame_system::EnsureRoot<AccountId>;
+    type PrimeOrigin = RegistrarOrigin;
     type MembershipInitialized = Allocations;
     type MembershipChanged = Allocations;
 }
 
+parameter_types! {
+    pub const MaxRegistryChanges: u32 = 100;
+}
+
+impl pallet_registry_sync::Trait for Runtime {
+    type Event = Event;
+    type RegistrarOrigin = RegistrarOrigin;
+    type Public = <Signature as sp_runtime::traits::Verify>::Signer;
+    type Signature = Signature;
+    type Oracles = OracleMembership;
+    type DeviceId = (CertificateId, CertificateId);
+    type Devices = PkiRootOfTrust;
+    type MaxChanges = MaxRegistryChanges;
+    type WeightInfo = pallet_registry_sync::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_membership::Trait<pallet_membership::Instance6> for Runtime {
+    type Event = Event;
+    type AddOrigin = RegistrarOrigin;
+    type RemoveOrigin = RegistrarOrigin;
+    type SwapOrigin = RegistrarOrigin;
+    type ResetOrigin = RegistrarOrigin;
+    type PrimeOrigin = RegistrarOrigin;
+    type MembershipInitialized = ();
+    type MembershipChanged = ();
+}
+
+parameter_types! {
+    pub const ConnectivityEpochDuration: BlockNumber = constants::DAYS;
+    pub const ChallengeResponseWindow: BlockNumber = 10 * constants::MINUTES;
+    /// Keeps closing an epoch within the housekeeping share of a block
+    pub const MaxGateways: u32 = 1_000;
+}
+
+impl pallet_connectivity::Trait for Runtime {
+    type Event = Event;
+    type Verifiers = VerifierMembership;
+    type Randomness = RandomnessCollectiveFlip;
+    type Public = <Signature as sp_runtime::traits::Verify>::Signer;
+    type Signature = Signature;
+    type EpochDuration = ConnectivityEpochDuration;
+    type ResponseWindow = ChallengeResponseWindow;
+    type MaxGateways = MaxGateways;
+    type ManagerOrigin = RegistrarOrigin;
+    type WeightInfo = pallet_connectivity::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+    pub const AttestationValidity: BlockNumber = constants::DAYS;
+}
+
+impl pallet_stable_asset::Trait for Runtime {
+    type Event = Event;
+    type Balance = Balance;
+    type CustodianOrigin = StableCustodian;
+    type AttestationValidity = AttestationValidity;
+    type WeightInfo = pallet_stable_asset::weights::SubstrateWeight<Runtime>;
+}
+
 construct_runtime!(
     pub enum Runtime where
         Block = Block,
@@ -817,9 +1185,10 @@ construct_runtime!(
         Scheduler: pallet_scheduler::{Module, Call, Storage, Event<T>},
         Amendments: pallet_amendments::{Module, Call, Storage, Event<T>},
         Mandate: pallet_mandate::{Module, Call, Event},
-        CompanyReserve: pallet_reserve::<Instance1>::{Module, Call, Storage, Config, Event<T>},
-        InternationalReserve: pallet_reserve::<Instance2>::{Module, Call, Storage, Config, Event<T>},
-        UsaReserve: pallet_reserve::<Instance3>::{Module, Call, Storage, Config, Event<T>},
+        Democracy: pallet_democracy::{Module, Call, Storage, Config, Event<T>},
+        CompanyReserve: pallet_reserve::<Instance1>::{Module, Call, Storage, Config<T>, Event<T>},
+        InternationalReserve: pallet_reserve::<Instance2>::{Module, Call, Storage, Config<T>, Event<T>},
+        UsaReserve: pallet_reserve::<Instance3>::{Module, Call, Storage, Config<T>, Event<T>},
         Grants: pallet_grants::{Module, Call, Storage, Config<T>, Event<T>},
 
         // Neat things
@@ -833,8 +1202,26 @@ construct_runtime!(
         PkiTcr: pallet_tcr::<Instance1>::{Module, Call, Storage, Event<T>},
         PkiRootOfTrust: pallet_root_of_trust::{Module, Call, Storage, Event<T>},
         EmergencyShutdown: pallet_emergency_shutdown::{Module, Call, Event, Storage},
-        Allocations: pallet_allocations::{Module, Call, Event<T>, Storage},
+        Denylist: pallet_denylist::{Module, Call, Event<T>, Storage},
+        DeviceSessions: pallet_device_sessions::{Module, Call, Storage, Event<T>},
+        Allocations: pallet_allocations::{Module, Call, Event<T>, Storage, ValidateUnsigned},
         AllocationsOracles: pallet_membership::<Instance5>::{Module, Call, Storage, Event<T>, Config<T>},
+        RegistrySync: pallet_registry_sync::{Module, Call, Storage, Event<T>},
+        Disputes: pallet_disputes::{Module, Call, Storage, Event<T>},
+        ConnectivityVerifiers: pallet_membership::<Instance6>::{Module, Call, Storage, Event<T>},
+        Connectivity: pallet_connectivity::{Module, Call, Storage, Event<T>},
+        Matching: pallet_matching::{Module, Call, Storage, Event<T>},
+        DataMarket: pallet_data_market::{Module, Call, Storage, Event<T>},
+        Attestations: pallet_attestations::{Module, Call, Storage, Event<T>, ValidateUnsigned},
+        StableAsset: pallet_stable_asset::{Module, Call, Storage, Event<T>},
+        RateLimit: pallet_rate_limit::{Module, Call, Storage},
+        Feeless: pallet_feeless::{Module, Call, Storage},
+        Parameters: pallet_parameters::{Module, Call, Storage, Event<T>},
+        StateSize: pallet_state_size::{Module, Call, Storage, Event},
+        Emission: pallet_emission::{Module, Call, Storage, Config<T>, Event<T>},
+        Housekeeping: pallet_housekeeping::{Module, Call, Storage},
+        CodeRegistry: pallet_code_registry::{Module, Call, Storage, Event<T>},
+        Airdrop: pallet_airdrop::{Module, Call, Storage, Config<T>, Event<T>, ValidateUnsigned},
     }
 );
 
@@ -878,6 +1265,41 @@ impl frame_support::traits::OnRuntimeUpgrade for RuntimeUpgradeStallFork {
     }
 }
 
+/// Check the storage invariants of our pallets, used to rehearse runtime upgrades and
+/// their migrations against a copy of the live state.
+#[cfg(feature = "try-runtime")]
+pub fn try_state() -> Result<(), &'static str> {
+    Airdrop::try_state()?;
+    Allocations::try_state()?;
+    Amendments::try_state()?;
+    Attestations::try_state()?;
+    CodeRegistry::try_state()?;
+    CompanyReserve::try_state()?;
+    Connectivity::try_state()?;
+    DataMarket::try_state()?;
+    Denylist::try_state()?;
+    DeviceSessions::try_state()?;
+    Disputes::try_state()?;
+    EmergencyShutdown::try_state()?;
+    Emission::try_state()?;
+    Feeless::try_state()?;
+    Grants::try_state()?;
+    Housekeeping::try_state()?;
+    InternationalReserve::try_state()?;
+    Matching::try_state()?;
+    Parameters::try_state()?;
+    PkiRootOfTrust::try_state()?;
+    PkiTcr::try_state()?;
+    PoaSessions::try_state()?;
+    RateLimit::try_state()?;
+    RegistrySync::try_state()?;
+    StableAsset::try_state()?;
+    StateSize::try_state()?;
+    UsaReserve::try_state()?;
+
+    Ok(())
+}
+
 /// The address format for describing accounts.
 pub type Address = <Indices as StaticLookup>::Source;
 /// Block header type as expected by this runtime.
@@ -896,7 +1318,10 @@ pub type SignedExtra = (
     frame_system::CheckEra<Runtime>,
     frame_system::CheckNonce<Runtime>,
     frame_system::CheckWeight<Runtime>,
-    pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+    pallet_rate_limit::CheckRateLimit<Runtime>,
+    pallet_device_sessions::CheckSessionKey<Runtime>,
+    pallet_allocations::CheckOracle<Runtime>,
+    pallet_feeless::ChargeFeelessOrPayment<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -1081,6 +1506,105 @@ sp_api::impl_runtime_apis! {
         }
     }
 
+    impl pallet_feeless_runtime_api::FeelessApi<Block, Balance> for Runtime {
+        fn query_fee(uxt: <Block as BlockT>::Extrinsic, len: u32) -> FeeEstimate<Balance> {
+            let RuntimeDispatchInfo { partial_fee, .. } = TransactionPayment::query_info(uxt.clone(), len);
+            let sender = uxt.signature.as_ref().and_then(|(address, _, _)| Indices::lookup(address.clone()).ok());
+            let who = match sender {
+                Some(who) => who,
+                // Unsigned extrinsics do not go through the signed extensions
+                None => return FeeEstimate { partial_fee, ..Default::default() },
+            };
+
+            let info = uxt.get_dispatch_info();
+            FeeEstimate {
+                partial_fee: Feeless::estimate_fee(&who, &uxt.function, &info, len),
+                feeless: Feeless::is_feeless(&who, &uxt.function, &info),
+                feeless_quota_left: Feeless::quota_left(&who),
+                rate_limited: RateLimit::is_rate_limited(&who),
+            }
+        }
+    }
+
+    impl chain_deposits_api::DepositsApi<Block, AccountId, Balance> for Runtime {
+        fn deposits(who: AccountId) -> Vec<chain_deposits_api::Deposit<Balance>> {
+            AccountDeposits::of(&who)
+        }
+    }
+
+    impl chain_try_runtime_api::TryRuntime<Block> for Runtime {
+        fn try_state() -> Result<(), Vec<u8>> {
+            #[cfg(feature = "try-runtime")]
+            return try_state().map_err(|e| e.as_bytes().to_vec());
+            #[cfg(not(feature = "try-runtime"))]
+            return Err(b"the runtime was built without the try-runtime feature".to_vec());
+        }
+    }
+
+    impl pallet_allocations_runtime_api::OraclesApi<Block, AccountId, Balance, pallet_allocations::OracleStatusOf<Runtime>> for Runtime {
+        fn oracles() -> Vec<(AccountId, pallet_allocations::OracleStatusOf<Runtime>)> {
+            Allocations::oracle_statuses()
+        }
+
+        fn budget() -> (Balance, Balance) {
+            (Allocations::coins_consumed(), MaximumCoinsEverAllocated::get())
+        }
+    }
+
+    impl pallet_allocations_runtime_api::OracleMembershipApi<Block, AccountId, BlockNumber> for Runtime {
+        fn pending_oracles() -> Vec<(AccountId, BlockNumber)> {
+            Allocations::pending_oracles()
+        }
+    }
+
+    impl pallet_allocations_runtime_api::CampaignsApi<Block, Balance> for Runtime {
+        fn campaign() -> pallet_allocations_runtime_api::CampaignStats<Balance> {
+            let spent = Allocations::coins_consumed();
+            pallet_allocations_runtime_api::CampaignStats {
+                spent,
+                remaining: MaximumCoinsEverAllocated::get().saturating_sub(spent),
+                unique_recipients: Allocations::recipients_count(),
+                largest_payout: Allocations::largest_allocation(),
+            }
+        }
+    }
+
+    impl pallet_poa_runtime_api::SessionProofApi<Block, Hash> for Runtime {
+        fn prove_session_membership(
+            key_type: KeyTypeId,
+            key: Vec<u8>,
+        ) -> Option<sp_session::MembershipProof> {
+            Historical::prove((key_type, key))
+        }
+
+        fn historical_root(session: u32) -> Option<(Hash, u32)> {
+            Historical::historical_root(session)
+        }
+    }
+
+    impl pallet_reserve_runtime_api::ReserveApi<Block, AccountId> for Runtime {
+        fn accounts() -> Vec<(ModuleId, AccountId)> {
+            vec![
+                CompanyReserve::module_id(),
+                InternationalReserve::module_id(),
+                UsaReserve::module_id(),
+            ]
+            .into_iter()
+            .map(|id| (id, id.into_account()))
+            .collect()
+        }
+    }
+
+    impl pallet_state_size_runtime_api::StateSizeApi<Block, Vec<pallet_state_size::PrefixSample>> for Runtime {
+        fn latest_sample() -> Option<(u32, Vec<pallet_state_size::PrefixSample>)> {
+            StateSize::latest_sample()
+        }
+
+        fn sample(era: u32) -> Option<Vec<pallet_state_size::PrefixSample>> {
+            StateSize::samples(era)
+        }
+    }
+
     impl pallet_root_of_trust_runtime_api::RootOfTrustApi<Block, CertificateId> for Runtime {
         fn is_root_certificate_valid(cert: &CertificateId) -> bool {
             PkiRootOfTrust::is_root_certificate_valid(cert)
@@ -1117,22 +1641,35 @@ sp_api::impl_runtime_apis! {
             add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
             add_benchmark!(params, batches, pallet_allocations, Allocations);
             add_benchmark!(params, batches, pallet_amendments, Amendments);
+            add_benchmark!(params, batches, pallet_attestations, Attestations);
             add_benchmark!(params, batches, pallet_babe, Babe);
             add_benchmark!(params, batches, pallet_balances, Balances);
+            add_benchmark!(params, batches, pallet_code_registry, CodeRegistry);
             add_benchmark!(params, batches, pallet_collective, TechnicalCommittee);
+            add_benchmark!(params, batches, pallet_connectivity, Connectivity);
+            add_benchmark!(params, batches, pallet_data_market, DataMarket);
+            add_benchmark!(params, batches, pallet_democracy, Democracy);
+            add_benchmark!(params, batches, pallet_denylist, Denylist);
+            add_benchmark!(params, batches, pallet_device_sessions, DeviceSessions);
+            add_benchmark!(params, batches, pallet_disputes, Disputes);
             add_benchmark!(params, batches, pallet_emergency_shutdown, EmergencyShutdown);
+            add_benchmark!(params, batches, pallet_emission, Emission);
             add_benchmark!(params, batches, pallet_grandpa, Grandpa);
             add_benchmark!(params, batches, pallet_grants, Grants);
             add_benchmark!(params, batches, pallet_identity, Identity);
             add_benchmark!(params, batches, pallet_im_online, ImOnline);
             add_benchmark!(params, batches, pallet_indices, Indices);
+            add_benchmark!(params, batches, pallet_matching, Matching);
             add_benchmark!(params, batches, pallet_multisig, Multisig);
+            add_benchmark!(params, batches, pallet_parameters, Parameters);
             //add_benchmark!(params, batches, pallet_offences, OffencesBench::<Runtime>);
             add_benchmark!(params, batches, pallet_proxy, Proxy);
+            add_benchmark!(params, batches, pallet_registry_sync, RegistrySync);
             add_benchmark!(params, batches, pallet_reserve, CompanyReserve);
             //add_benchmark!(params, batches, pallet_session, SessionBench::<Runtime>);
             add_benchmark!(params, batches, pallet_root_of_trust, PkiRootOfTrust);
             add_benchmark!(params, batches, pallet_scheduler, Scheduler);
+            add_benchmark!(params, batches, pallet_stable_asset, StableAsset);
             add_benchmark!(params, batches, pallet_tcr, PkiTcr);
             add_benchmark!(params, batches, pallet_timestamp, Timestamp);
             add_benchmark!(params, batches, pallet_utility, Utility);
@@ -1146,7 +1683,9 @@ sp_api::impl_runtime_apis! {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use frame_support::weights::DispatchClass;
     use frame_system::offchain::CreateSignedTransaction;
+    use pallet_allocations::WeightInfo;
 
     #[test]
     fn validate_transaction_submitter_bounds() {
@@ -1158,4 +1697,86 @@ mod tests {
 
         is_submit_signed_transaction::<Runtime>();
     }
+
+    /// Allocations the oracle daemon packs in a transaction by default
+    const FULL_BATCH: u32 = 100;
+
+    /// Batches of `size` allocations, submitted with `allocate_batch` and as
+    /// `allocate` calls wrapped in a Utility `batch`.
+    fn allocations_batches(size: u32) -> [Call; 2] {
+        let proof = Hash::default().as_bytes().to_vec();
+        let allocate = Call::Allocations(pallet_allocations::Call::allocate(
+            AccountId::default(),
+            1_000 * constants::NODL,
+            proof.clone(),
+        ));
+        [
+            Call::Allocations(pallet_allocations::Call::allocate_batch(
+                vec![(AccountId::default(), 1_000 * constants::NODL); size as usize],
+                proof,
+            )),
+            Call::Utility(pallet_utility::Call::batch(vec![allocate; size as usize])),
+        ]
+    }
+
+    #[test]
+    fn a_full_allocations_batch_fits_in_a_block() {
+        for batch in &allocations_batches(FULL_BATCH) {
+            let info = batch.get_dispatch_info();
+
+            assert_eq!(info.class, DispatchClass::Operational);
+            assert!(info.weight <= Perbill::from_percent(80) * MaximumBlockWeight::get());
+            assert!(
+                batch.encoded_size() as u32
+                    <= AvailableBlockRatio::get() * MaximumBlockLength::get()
+            );
+        }
+    }
+
+    #[test]
+    fn allocations_batches_are_not_cheaper_than_their_allocations() {
+        let allocate = <Runtime as pallet_allocations::Trait>::WeightInfo::allocate();
+
+        for size in &[1, FULL_BATCH / 2, FULL_BATCH] {
+            for batch in &allocations_batches(*size) {
+                assert!(batch.get_dispatch_info().weight >= allocate * Weight::from(*size));
+            }
+        }
+    }
+
+    #[test]
+    fn payouts_settlement_fits_in_the_housekeeping_budget() {
+        let settlement = <Runtime as pallet_allocations::Trait>::WeightInfo::settle_payouts(1);
+
+        assert!(HousekeepingWeightShare::get() <= AVERAGE_ON_INITIALIZE_WEIGHT);
+        assert!(settlement <= HousekeepingWeightShare::get() * MaximumBlockWeight::get());
+    }
+
+    #[test]
+    fn claiming_payouts_fits_in_an_extrinsic() {
+        let claim = Call::Allocations(pallet_allocations::Call::claim(0)).get_dispatch_info();
+
+        assert_eq!(claim.class, DispatchClass::Normal);
+        assert!(claim.weight <= MaximumExtrinsicWeight::get());
+    }
+
+    #[test]
+    fn light_clients_find_the_apis_they_need() {
+        use sp_api::RuntimeApiInfo;
+
+        // Following BABE and GRANDPA, then submitting transactions, as smoldot does
+        let needed =
+            [
+                <dyn sp_consensus_babe::BabeApi<Block>>::ID,
+                <dyn fg_primitives::GrandpaApi<Block>>::ID,
+                <dyn frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Index>>::ID,
+                <dyn pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<
+                    Block,
+                    Balance,
+                >>::ID,
+            ];
+        for id in needed.iter() {
+            assert!(VERSION.apis.iter().any(|(api, _)| api == id));
+        }
+    }
 }
@@ -0,0 +1,69 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Origins gating our privileged calls, each of them maps to the body expected to
+//! approve a given class of operations instead of funneling everything through root.
+
+use crate::{FinancialCollective, Runtime, TechnicalCollective};
+use frame_system::{EnsureOneOf, EnsureRoot};
+use nodle_chain_primitives::AccountId;
+use pallet_collective::EnsureProportionMoreThan;
+use sp_core::u32_trait::{_1, _2};
+
+/// Signed by one of the allocations oracles, used to submit allocations.
+pub type OracleCommittee = pallet_allocations::EnsureOracle<Runtime>;
+
+/// A majority of the technical committee, or root, can submit allocations while the
+/// oracles are stalled so that payouts do not freeze during an outage.
+pub type AllocationsFallback = EnsureOneOf<
+    AccountId,
+    EnsureRoot<AccountId>,
+    EnsureProportionMoreThan<_1, _2, AccountId, TechnicalCollective>,
+>;
+
+/// A majority of the technical committee, or root, rule on allocation disputes. The
+/// oracles can not be judges of their own batches.
+pub type DisputeArbiter = EnsureOneOf<
+    AccountId,
+    EnsureRoot<AccountId>,
+    EnsureProportionMoreThan<_1, _2, AccountId, TechnicalCollective>,
+>;
+
+/// A majority of the financial committee, or root, can spend the reserves' funds and
+/// cancel vesting grants.
+pub type ReserveSpender = EnsureOneOf<
+    AccountId,
+    EnsureRoot<AccountId>,
+    EnsureProportionMoreThan<_1, _2, AccountId, FinancialCollective>,
+>;
+
+/// A majority of the financial committee, or root, act as the custodian of the stable
+/// asset, attesting its fiat reserves and minting or burning against them.
+pub type StableCustodian = EnsureOneOf<
+    AccountId,
+    EnsureRoot<AccountId>,
+    EnsureProportionMoreThan<_1, _2, AccountId, FinancialCollective>,
+>;
+
+/// A majority of the technical committee, or root, can register identity registrars,
+/// manage the set of allocations oracles and remove root of trust slots.
+pub type RegistrarOrigin = EnsureOneOf<
+    AccountId,
+    EnsureRoot<AccountId>,
+    EnsureProportionMoreThan<_1, _2, AccountId, TechnicalCollective>,
+>;
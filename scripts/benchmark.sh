@@ -0,0 +1,48 @@
+#!/usr/bin/env bash
+
+# Measure the weights of our pallets and write them to their `weights.rs` file.
+# Usage, from the root of the repository: ./scripts/benchmark.sh [pallet_name...]
+# Every one of our pallets the runtime benchmarks is measured if none is given.
+
+set -e
+
+# Our pallets are the ones with a directory under `pallets/`, the others come from Substrate
+OUR_PALLETS=$(
+    grep -o 'add_benchmark!(params, batches, pallet_[a-z_]*' runtimes/main/src/lib.rs |
+    sed 's/.*, //' |
+    while read PALLET; do
+        if [ -d pallets/$(echo ${PALLET#pallet_} | tr '_' '-') ]; then
+            echo $PALLET
+        fi
+    done
+)
+PALLETS=${@:-$OUR_PALLETS}
+
+echo "*** Building the node with benchmarks enabled"
+cargo build --release -p nodle-chain --features runtime-benchmarks
+
+for PALLET in $PALLETS; do
+    DIR=pallets/$(echo ${PALLET#pallet_} | tr '_' '-')
+    OUTPUT=$(mktemp)
+
+    echo "*** Benchmarking $PALLET"
+    ./target/release/nodle-chain benchmark pallet \
+        --chain=dev \
+        --steps=50 \
+        --repeat=20 \
+        --pallet=$PALLET \
+        --extrinsic='*' \
+        --execution=wasm \
+        --wasm-execution=compiled \
+        --heap-pages=4096 \
+        --weight-trait \
+        --output=$OUTPUT
+
+    # Keep the license header of the pallets that have one
+    if head -n 1 $DIR/src/lib.rs | grep -q '^/\*'; then
+        cat scripts/HEADER $OUTPUT > $DIR/src/weights.rs
+    else
+        cp $OUTPUT $DIR/src/weights.rs
+    fi
+    rm $OUTPUT
+done
@@ -0,0 +1,21 @@
+#!/usr/bin/env bash
+
+# Build a runtime deterministically with srtool, so that anyone can check that the WASM
+# enacted on chain was built from these sources with `nodle-chain verify-runtime`.
+# Usage, from the root of the repository: ./scripts/build-runtime.sh [main|staging]
+
+set -e
+
+RUNTIME=${1:-main}
+case $RUNTIME in
+    main) PACKAGE=nodle-chain-runtime ;;
+    staging) PACKAGE=nodle-staging-runtime ;;
+    *) echo "Unknown runtime $RUNTIME, expected main or staging" && exit 1 ;;
+esac
+
+echo "*** Building $PACKAGE with srtool"
+docker run --rm \
+    -e PACKAGE=$PACKAGE \
+    -e RUNTIME_DIR=runtimes/$RUNTIME \
+    -v $PWD:/build \
+    chevdor/srtool:`cat rust-toolchain` build
@@ -0,0 +1,74 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime API reporting the funds pallets hold on behalf of an account, so that
+//! wallets can explain why part of a balance can not be transferred.
+
+use parity_scale_codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_std::vec::Vec;
+
+/// Why some funds of an account are held.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub enum DepositReason {
+    /// Grants which did not vest yet
+    Grant,
+    /// Rewards paid out before they can be transferred
+    Rewards,
+    /// Bonds of the root of trust slots of the account, vouching for its devices
+    CertificateSlot,
+    /// On chain identity and sub identities
+    Identity,
+    /// Applications, challenges and votes in the token curated registry
+    Registry,
+    /// Disputes opened against allocation batches
+    DisputeBond,
+    /// Budget of data market orders and deliveries waiting to be paid
+    DataOrder,
+    /// Reserved by another pallet, for instance for proxies or governance proposals
+    OtherReserve,
+    /// Locked by another pallet, for instance for governance votes
+    OtherLock,
+}
+
+/// Funds held for a given reason.
+#[derive(Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct Deposit<Balance> {
+    pub reason: DepositReason,
+    pub amount: Balance,
+    /// Locked funds stay in the free balance and locks overlap, only the largest one
+    /// matters. Reserved funds add up.
+    pub locked: bool,
+}
+
+sp_api::decl_runtime_apis! {
+    pub trait DepositsApi<AccountId, Balance> where
+        AccountId: parity_scale_codec::Codec,
+        Balance: parity_scale_codec::Codec,
+    {
+        /// Funds held on behalf of `who`, the reserved ones add up to its reserved
+        /// balance.
+        fn deposits(who: AccountId) -> Vec<Deposit<Balance>>;
+    }
+}
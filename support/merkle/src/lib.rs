@@ -0,0 +1,249 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Merkle trees over allocation batches. Oracles commit to a batch by passing its
+//! root as the proof of every allocation it contains; leaves are the blake2 256 hash
+//! of the SCALE encoded `(grantee, amount)` pairs, amount including the protocol fee,
+//! in the order the allocations were included. Inner nodes hash the concatenation of
+//! their children and a node without a sibling is promoted to the next level as is.
+//!
+//! `vectors.json` pins this format: for trees of various sizes it lists the
+//! allocations, with accounts as hex encoded 32 bytes and amounts as decimal strings,
+//! their leaves, the root and the proof of every leaf. Any other implementation, such
+//! as an oracle written in another language, should reproduce them exactly.
+
+use parity_scale_codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::H256 as Hash;
+use sp_io::hashing::blake2_256;
+use sp_std::prelude::Vec;
+
+/// Everything needed to prove that a leaf belongs to a batch.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct MerkleProof {
+    pub root: Hash,
+    pub leaf: Hash,
+    pub leaf_index: u32,
+    pub leaf_count: u32,
+    /// Siblings of the nodes on the path from the leaf to the root, bottom up
+    pub siblings: Vec<Hash>,
+}
+
+/// The test vectors described above, for the tests of crates building batches.
+#[cfg(feature = "std")]
+pub const TEST_VECTORS: &str = include_str!("../vectors.json");
+
+pub fn leaf<AccountId: Encode, Balance: Encode>(account: &AccountId, amount: Balance) -> Hash {
+    blake2_256(&(account, amount).encode()).into()
+}
+
+fn hash_pair(left: &Hash, right: &Hash) -> Hash {
+    blake2_256(&[left.as_ref(), right.as_ref()].concat()).into()
+}
+
+fn next_level(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_pair(left, right),
+            [single] => *single,
+            _ => unreachable!("chunks are made of one or two elements; qed"),
+        })
+        .collect()
+}
+
+/// Root of the tree built over `leaves`, `None` if there are none.
+pub fn root(leaves: &[Hash]) -> Option<Hash> {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.first().copied()
+}
+
+/// Proof that the leaf at `index` belongs to the tree built over `leaves`.
+pub fn prove(leaves: &[Hash], index: usize) -> Option<MerkleProof> {
+    let leaf = *leaves.get(index)?;
+    let mut siblings = Vec::new();
+    let mut level = leaves.to_vec();
+    let mut position = index;
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(position ^ 1) {
+            siblings.push(*sibling);
+        }
+        level = next_level(&level);
+        position /= 2;
+    }
+
+    Some(MerkleProof {
+        root: level[0],
+        leaf,
+        leaf_index: index as u32,
+        leaf_count: leaves.len() as u32,
+        siblings,
+    })
+}
+
+/// Proofs of every leaf of the tree built over `leaves`, the tree is only built once
+/// which matters for large trees such as airdrops.
+pub fn prove_all(leaves: &[Hash]) -> Vec<MerkleProof> {
+    let mut levels = vec![leaves.to_vec()];
+    while levels[levels.len() - 1].len() > 1 {
+        let next = next_level(&levels[levels.len() - 1]);
+        levels.push(next);
+    }
+    let root = match levels[levels.len() - 1].first() {
+        Some(root) => *root,
+        None => return Vec::new(),
+    };
+
+    (0..leaves.len())
+        .map(|index| MerkleProof {
+            root,
+            leaf: leaves[index],
+            leaf_index: index as u32,
+            leaf_count: leaves.len() as u32,
+            siblings: levels[..levels.len() - 1]
+                .iter()
+                .enumerate()
+                .filter_map(|(depth, level)| level.get((index >> depth) ^ 1).copied())
+                .collect(),
+        })
+        .collect()
+}
+
+/// Check `proof` against its root, this is what a claim would do on chain.
+pub fn verify(proof: &MerkleProof) -> bool {
+    let mut siblings = proof.siblings.iter();
+    let mut node = proof.leaf;
+    let mut position = proof.leaf_index;
+    let mut width = proof.leaf_count;
+    if position >= width {
+        return false;
+    }
+
+    while width > 1 {
+        if position % 2 == 1 {
+            match siblings.next() {
+                Some(sibling) => node = hash_pair(sibling, &node),
+                None => return false,
+            }
+        } else if position + 1 < width {
+            match siblings.next() {
+                Some(sibling) => node = hash_pair(&node, sibling),
+                None => return false,
+            }
+        }
+        position /= 2;
+        width = (width + 1) / 2;
+    }
+
+    siblings.next().is_none() && node == proof.root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Allocation {
+        to: Hash,
+        amount: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Vector {
+        allocations: Vec<Allocation>,
+        leaves: Vec<Hash>,
+        root: Option<Hash>,
+        proofs: Vec<MerkleProof>,
+    }
+
+    fn leaves(count: u8) -> Vec<Hash> {
+        (0..count).map(|i| leaf(&[i; 32], i as u128)).collect()
+    }
+
+    #[test]
+    fn every_leaf_of_every_tree_can_be_proven() {
+        for count in 1..10 {
+            let leaves = leaves(count);
+            for index in 0..leaves.len() {
+                let proof = prove(&leaves, index).expect("leaf exists");
+                assert_eq!(Some(proof.root), root(&leaves));
+                assert!(verify(&proof), "{} of {}", index, count);
+            }
+        }
+    }
+
+    #[test]
+    fn proving_all_leaves_matches_proving_them_one_by_one() {
+        assert!(prove_all(&[]).is_empty());
+        for count in 1..10 {
+            let leaves = leaves(count);
+            let proofs = prove_all(&leaves);
+            assert_eq!(proofs.len(), leaves.len());
+            for (index, proof) in proofs.into_iter().enumerate() {
+                assert_eq!(Some(proof), prove(&leaves, index));
+            }
+        }
+    }
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let leaves = leaves(5);
+        let proof = prove(&leaves, 2).expect("leaf exists");
+
+        let mut wrong_leaf = proof.clone();
+        wrong_leaf.leaf = leaves[3];
+        assert!(!verify(&wrong_leaf));
+
+        let mut wrong_index = proof.clone();
+        wrong_index.leaf_index = 3;
+        assert!(!verify(&wrong_index));
+
+        let mut missing_sibling = proof;
+        missing_sibling.siblings.pop();
+        assert!(!verify(&missing_sibling));
+    }
+
+    #[test]
+    fn test_vectors_are_reproduced() {
+        let vectors: Vec<Vector> = serde_json::from_str(TEST_VECTORS).expect("valid vectors");
+        for vector in vectors {
+            let leaves = vector
+                .allocations
+                .iter()
+                .map(|allocation| {
+                    let amount: u128 = allocation.amount.parse().expect("valid amount");
+                    leaf(&allocation.to, amount)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(leaves, vector.leaves);
+            assert_eq!(root(&leaves), vector.root);
+            assert_eq!(prove_all(&leaves), vector.proofs);
+            for proof in vector.proofs {
+                assert!(verify(&proof));
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use sp_core::offchain::Duration;
+use sp_runtime::offchain::http;
+use sp_std::vec::Vec;
+
+/// Reasons why fetching a remote resource failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FetchError {
+    /// The request could not be sent or did not complete before its deadline.
+    Unreachable,
+    /// The server answered with a non successful status code.
+    BadStatus(u16),
+}
+
+/// Send a GET request to `url` and return the response body. Every attempt is given
+/// `timeout_ms` milliseconds to complete, failed attempts are retried up to `retries`
+/// times; a non successful status code is not retried.
+pub fn fetch(url: &str, timeout_ms: u64, retries: u32) -> Result<Vec<u8>, FetchError> {
+    let mut attempt = 0;
+    loop {
+        match fetch_once(url, timeout_ms) {
+            Err(FetchError::Unreachable) if attempt < retries => attempt += 1,
+            result => return result,
+        }
+    }
+}
+
+fn fetch_once(url: &str, timeout_ms: u64) -> Result<Vec<u8>, FetchError> {
+    let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(timeout_ms));
+    let pending = http::Request::get(url)
+        .deadline(deadline)
+        .send()
+        .map_err(|_| FetchError::Unreachable)?;
+    let response = pending
+        .try_wait(deadline)
+        .map_err(|_| FetchError::Unreachable)?
+        .map_err(|_| FetchError::Unreachable)?;
+
+    if response.code != 200 {
+        return Err(FetchError::BadStatus(response.code));
+    }
+
+    Ok(response.body().collect())
+}
@@ -0,0 +1,32 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Building blocks for off-chain workers: fetching remote data over HTTP, making sure
+//! only one worker runs a given job at a time and submitting the results back on chain.
+
+pub mod http;
+pub mod lock;
+pub mod submit;
+mod tests;
+
+pub use http::{fetch, FetchError};
+pub use lock::with_lock;
+pub use submit::{
+    submit_signed, submit_unsigned, submit_unsigned_with_signed_payload, SubmitError,
+};
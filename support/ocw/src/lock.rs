@@ -0,0 +1,30 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use sp_core::offchain::Duration;
+use sp_runtime::offchain::storage_lock::{StorageLock, Time};
+
+/// Run `f` while holding the off-chain storage lock `key`, which makes sure that
+/// overlapping workers (e.g. one per imported block) do not run the same job twice.
+/// The lock expires after `expiration_ms` milliseconds in case a worker dies while
+/// holding it. Returns `None` without calling `f` if the lock is already taken.
+pub fn with_lock<R>(key: &[u8], expiration_ms: u64, f: impl FnOnce() -> R) -> Option<R> {
+    let mut lock = StorageLock::<Time>::with_deadline(key, Duration::from_millis(expiration_ms));
+    let _guard = lock.try_lock().ok()?;
+
+    Some(f())
+}
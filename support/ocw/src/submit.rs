@@ -0,0 +1,74 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use frame_system::offchain::{
+    AppCrypto, CreateSignedTransaction, SendSignedTransaction, SendTransactionTypes,
+    SendUnsignedTransaction, SignedPayload, Signer, SigningTypes, SubmitTransaction,
+};
+
+/// Reasons why submitting a transaction from an off-chain worker failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmitError {
+    /// None of the keys of the expected type are present in the local keystore.
+    NoLocalAccount,
+    /// The transaction pool rejected the transaction.
+    Rejected,
+}
+
+/// Sign `call` with any local key matching `C` and submit it to the pool.
+pub fn submit_signed<T, C, LocalCall>(call: LocalCall) -> Result<(), SubmitError>
+where
+    T: CreateSignedTransaction<LocalCall>,
+    C: AppCrypto<T::Public, T::Signature>,
+    LocalCall: Clone,
+{
+    let (_account, result) = Signer::<T, C>::any_account()
+        .send_signed_transaction(|_| call.clone())
+        .ok_or(SubmitError::NoLocalAccount)?;
+
+    result.map_err(|_| SubmitError::Rejected)
+}
+
+/// Submit `call` as an unsigned transaction, it is up to the pallet's
+/// `ValidateUnsigned` implementation to decide whether it is acceptable.
+pub fn submit_unsigned<T, LocalCall>(call: LocalCall) -> Result<(), SubmitError>
+where
+    T: SendTransactionTypes<LocalCall>,
+{
+    SubmitTransaction::<T, LocalCall>::submit_unsigned_transaction(call.into())
+        .map_err(|_| SubmitError::Rejected)
+}
+
+/// Submit an unsigned transaction carrying a payload signed by any local key matching
+/// `C`. This avoids paying fees while still letting the pallet check who produced the
+/// payload. `build_payload` receives the public key used to sign, `build_call` the
+/// payload and its signature.
+pub fn submit_unsigned_with_signed_payload<T, C, LocalCall, Payload>(
+    build_payload: impl Fn(T::Public) -> Payload,
+    build_call: impl Fn(Payload, T::Signature) -> LocalCall,
+) -> Result<(), SubmitError>
+where
+    T: SigningTypes + SendTransactionTypes<LocalCall>,
+    C: AppCrypto<T::Public, T::Signature>,
+    Payload: SignedPayload<T>,
+{
+    let (_account, result) = Signer::<T, C>::any_account()
+        .send_unsigned_transaction(|account| build_payload(account.public.clone()), build_call)
+        .ok_or(SubmitError::NoLocalAccount)?;
+
+    result.map_err(|_| SubmitError::Rejected)
+}
@@ -0,0 +1,66 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+#![cfg(test)]
+
+use super::*;
+
+use sp_core::offchain::{testing, OffchainExt};
+use sp_io::TestExternalities;
+
+const URL: &str = "https://example.com/price";
+
+fn new_test_ext() -> (
+    TestExternalities,
+    std::sync::Arc<parking_lot::RwLock<testing::OffchainState>>,
+) {
+    let (offchain, state) = testing::TestOffchainExt::new();
+    let mut t = TestExternalities::default();
+    t.register_extension(OffchainExt::new(offchain));
+
+    (t, state)
+}
+
+#[test]
+fn fetch_returns_the_response_body() {
+    let (mut t, state) = new_test_ext();
+    state.write().expect_request(testing::PendingRequest {
+        method: "GET".into(),
+        uri: URL.into(),
+        response: Some(b"42".to_vec()),
+        sent: true,
+        ..Default::default()
+    });
+
+    t.execute_with(|| {
+        assert_eq!(fetch(URL, 1_000, 0), Ok(b"42".to_vec()));
+    })
+}
+
+#[test]
+fn lock_prevents_concurrent_runs() {
+    let (mut t, _state) = new_test_ext();
+
+    t.execute_with(|| {
+        let outer = with_lock(b"chain-ocw::test", 1_000, || {
+            with_lock(b"chain-ocw::test", 1_000, || ())
+        });
+        assert_eq!(outer, Some(None));
+
+        assert_eq!(with_lock(b"chain-ocw::test", 1_000, || 1), Some(1));
+    })
+}
@@ -0,0 +1,66 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Capabilities pallets offer one another. Pallets are configured with the ones they
+//! need and the runtime wires them to the pallets providing them, so that pallets
+//! depend on this crate rather than on each other.
+
+use frame_support::traits::Contains;
+
+/// Accounts no coins may be minted to, for instance because of sanctions. Any set of
+/// accounts, such as a denylist, can serve as a compliance check.
+pub trait ComplianceCheck<AccountId: Ord>: Contains<AccountId> {}
+
+impl<AccountId: Ord, T: Contains<AccountId>> ComplianceCheck<AccountId> for T {}
+
+/// Resolves stable device identities to the account the rewards of a device are paid
+/// to, so that rewards can be addressed to devices rather than accounts.
+pub trait DeviceResolver<DeviceId, AccountId> {
+    /// Account rewards of `device` go to, `None` if the device is unknown or no longer
+    /// trusted.
+    fn payout_account(device: &DeviceId) -> Option<AccountId>;
+
+    /// Trust `device` and pay its rewards to `owner`, used to set up benchmarks.
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_device(device: &DeviceId, owner: &AccountId);
+}
+
+impl<DeviceId, AccountId> DeviceResolver<DeviceId, AccountId> for () {
+    fn payout_account(_device: &DeviceId) -> Option<AccountId> {
+        None
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_device(_device: &DeviceId, _owner: &AccountId) {}
+}
+
+/// Coins the network may allocate as rewards, in total since genesis.
+pub trait RewardBudget<Balance> {
+    fn budget() -> Balance;
+}
+
+/// Told about the coins allocated to an account, once they are bound to be paid out.
+pub trait OnAllocation<AccountId, Balance> {
+    fn on_allocation(who: &AccountId, amount: Balance);
+}
+
+impl<AccountId, Balance> OnAllocation<AccountId, Balance> for () {
+    fn on_allocation(_who: &AccountId, _amount: Balance) {}
+}
@@ -18,6 +18,157 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use sp_runtime::traits::Hash;
+use sp_std::prelude::*;
+
 pub trait WithAccountId<AccountId> {
     fn account_id() -> AccountId;
 }
+
+/// Deposit `event` with the hashes of `accounts` as topics, this lets clients
+/// subscribe to the events concerning a given account without scanning every block.
+pub fn deposit_indexed_event<T, E>(accounts: &[&T::AccountId], event: E)
+where
+    T: frame_system::Trait,
+    E: Into<<T as frame_system::Trait>::Event>,
+{
+    let topics = accounts
+        .iter()
+        .map(|who| T::Hashing::hash_of(who))
+        .collect::<Vec<_>>();
+    <frame_system::Module<T>>::deposit_event_indexed(&topics, event.into());
+}
+
+/// Creates keyless accounts, such as pure proxies, controlled by another account so
+/// that organizations can hold funds without any single private key.
+pub trait KeylessAccounts<AccountId> {
+    /// Keyless account controlled by `controller`, `None` if they are not supported.
+    fn keyless_account(controller: &AccountId) -> Option<AccountId>;
+
+    /// Give `controller` control over its keyless account.
+    fn create_keyless_account(controller: &AccountId);
+}
+
+impl<AccountId> KeylessAccounts<AccountId> for () {
+    fn keyless_account(_controller: &AccountId) -> Option<AccountId> {
+        None
+    }
+
+    fn create_keyless_account(_controller: &AccountId) {}
+}
+
+/// Lets pallets change a set maintained by another pallet, for instance so that
+/// members can leave the set on their own.
+pub trait MemberSet<AccountId> {
+    /// Add `who` to the set.
+    fn add_member(who: &AccountId) -> Result<(), &'static str>;
+
+    /// Remove `who` from the set.
+    fn remove_member(who: &AccountId) -> Result<(), &'static str>;
+}
+
+/// Batches of allocations committed to by the oracles, so that other pallets can
+/// check claims against them and make up for their mistakes.
+pub trait AllocationBatches<AccountId, Balance, Hash> {
+    /// Whether `root` is the merkle root of a batch submitted during payout era `era`.
+    fn is_batch(era: u32, root: &Hash) -> bool;
+
+    /// Allocate `amount` to `who` on top of the batches, to correct an omission or an
+    /// incorrect amount.
+    fn correct(who: &AccountId, amount: Balance) -> Result<(), &'static str>;
+
+    /// Record a batch, used to set up benchmarks.
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_batch(era: u32, root: &Hash);
+}
+
+/// Relayers bonded to carry messages over a bridge, the bridge reports the votes they
+/// cast so that they are rewarded for them.
+pub trait BridgeRelayers<AccountId> {
+    /// Whether `who` is a bonded relayer allowed to vote.
+    fn is_relayer(who: &AccountId) -> bool;
+
+    /// Count a vote cast by `who` towards its rewards for the current period.
+    fn note_vote(who: &AccountId);
+}
+
+/// Coverage of the network measured on chain, committed to once per epoch so that the
+/// oracles can reference it in their batches of rewards.
+pub trait CoverageEpochs<Hash> {
+    /// Whether batches must reference the coverage of an epoch before rewards are
+    /// allocated in them.
+    fn is_required() -> bool;
+
+    /// Commitment to the coverage of `epoch`, `None` until the epoch is closed.
+    fn coverage_root(epoch: u32) -> Option<Hash>;
+
+    /// Close `epoch` with the given commitment, used to set up benchmarks.
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_coverage(epoch: u32, root: &Hash);
+}
+
+impl<Hash> CoverageEpochs<Hash> for () {
+    fn is_required() -> bool {
+        false
+    }
+
+    fn coverage_root(_epoch: u32) -> Option<Hash> {
+        None
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add_coverage(_epoch: u32, _root: &Hash) {}
+}
+
+/// Periodic work of a pallet, run by the housekeeping pallet at the beginning of blocks
+/// within a bounded share of the block weight. Work which does not fit in the budget
+/// must be resumed in a later block.
+pub trait HousekeepingTask<BlockNumber> {
+    /// Do the work due at block `n` without consuming more than `budget`, returns the
+    /// weight actually consumed.
+    fn housekeep(n: BlockNumber, budget: u64) -> u64;
+}
+
+/// An ordered list of housekeeping tasks, implemented for tuples of tasks.
+pub trait HousekeepingTasks<BlockNumber> {
+    /// Number of tasks in the list.
+    fn count() -> u32;
+
+    /// Run the task at `index`, see `HousekeepingTask::housekeep`.
+    fn housekeep(index: u32, n: BlockNumber, budget: u64) -> u64;
+}
+
+impl<BlockNumber> HousekeepingTasks<BlockNumber> for () {
+    fn count() -> u32 {
+        0
+    }
+
+    fn housekeep(_index: u32, _n: BlockNumber, _budget: u64) -> u64 {
+        0
+    }
+}
+
+macro_rules! impl_housekeeping_tasks {
+    ($($task:ident),+) => {
+        impl<BlockNumber, $($task: HousekeepingTask<BlockNumber>),+> HousekeepingTasks<BlockNumber>
+            for ($($task,)+)
+        {
+            fn count() -> u32 {
+                [$(stringify!($task)),+].len() as u32
+            }
+
+            fn housekeep(index: u32, n: BlockNumber, budget: u64) -> u64 {
+                let tasks: &[fn(BlockNumber, u64) -> u64] =
+                    &[$(<$task as HousekeepingTask<BlockNumber>>::housekeep),+];
+                tasks.get(index as usize).map_or(0, |task| task(n, budget))
+            }
+        }
+    };
+}
+
+impl_housekeeping_tasks!(A);
+impl_housekeeping_tasks!(A, B);
+impl_housekeeping_tasks!(A, B, C);
+impl_housekeeping_tasks!(A, B, C, D);
+impl_housekeeping_tasks!(A, B, C, D, E);
+impl_housekeeping_tasks!(A, B, C, D, E, F);
@@ -0,0 +1,92 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Mock runtime pieces shared by the pallet tests: the `frame_system` and
+//! `pallet_balances` configurations every mock repeats. Mocks still declare their
+//! own `Test` type, origin and genesis, the macros below implement the common traits
+//! for it.
+
+pub use frame_system;
+pub use pallet_balances;
+pub use sp_core;
+pub use sp_runtime;
+
+use frame_support::{parameter_types, weights::Weight};
+use sp_runtime::Perbill;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+    pub const MaxLocks: u32 = 50;
+}
+
+/// Implement `frame_system::Trait` for `$runtime`, accounts and block numbers are
+/// `u64`s. Hooks notified of account creations and removals can be passed last.
+#[macro_export]
+macro_rules! impl_mock_system {
+    ($runtime:ty, $origin:ty, $call:ty, $account_data:ty) => {
+        $crate::impl_mock_system!($runtime, $origin, $call, $account_data, (), ());
+    };
+    ($runtime:ty, $origin:ty, $call:ty, $account_data:ty, $on_new_account:ty, $on_killed_account:ty) => {
+        impl $crate::frame_system::Trait for $runtime {
+            type Origin = $origin;
+            type Call = $call;
+            type Index = u64;
+            type BlockNumber = u64;
+            type Hash = $crate::sp_core::H256;
+            type Hashing = $crate::sp_runtime::traits::BlakeTwo256;
+            type AccountId = u64;
+            type Lookup = $crate::sp_runtime::traits::IdentityLookup<Self::AccountId>;
+            type Header = $crate::sp_runtime::testing::Header;
+            type Event = ();
+            type BlockHashCount = $crate::BlockHashCount;
+            type MaximumBlockWeight = $crate::MaximumBlockWeight;
+            type MaximumBlockLength = $crate::MaximumBlockLength;
+            type AvailableBlockRatio = $crate::AvailableBlockRatio;
+            type Version = ();
+            type PalletInfo = ();
+            type AccountData = $account_data;
+            type OnNewAccount = $on_new_account;
+            type OnKilledAccount = $on_killed_account;
+            type DbWeight = ();
+            type BlockExecutionWeight = ();
+            type ExtrinsicBaseWeight = ();
+            type MaximumExtrinsicWeight = $crate::MaximumBlockWeight;
+            type BaseCallFilter = ();
+            type SystemWeightInfo = ();
+        }
+    };
+}
+
+/// Implement `pallet_balances::Trait` for `$runtime`, balances are `u64`s.
+#[macro_export]
+macro_rules! impl_mock_balances {
+    ($runtime:ty, $existential_deposit:ty) => {
+        impl $crate::pallet_balances::Trait for $runtime {
+            type Balance = u64;
+            type Event = ();
+            type DustRemoval = ();
+            type ExistentialDeposit = $existential_deposit;
+            type MaxLocks = $crate::MaxLocks;
+            type AccountStore = $crate::frame_system::Module<$runtime>;
+            type WeightInfo = ();
+        }
+    };
+}
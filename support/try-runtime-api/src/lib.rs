@@ -0,0 +1,33 @@
+/*
+ * This file is part of the Nodle Chain distributed at https://github.com/NodleCode/chain
+ * Copyright (C) 2020  Nodle International
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime API checking the storage invariants of the pallets, so that migrations
+//! can be rehearsed against the state of a live chain before being enacted.
+
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    pub trait TryRuntime {
+        /// Check the storage invariants of the pallets, the error describes the
+        /// first one found broken. Runtimes built without the `try-runtime`
+        /// feature always return an error.
+        fn try_state() -> Result<(), Vec<u8>>;
+    }
+}